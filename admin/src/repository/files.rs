@@ -103,7 +103,13 @@ where
     /// provider otherwise reports `u64::MAX` (no such thing as "disk free space"
     /// on object storage), which is meaningless to show an operator.
     pub(crate) async fn available_space(&self, used: i64) -> AppResult<u64> {
-        if let Some(quota) = self.repository.context().config.app.storage_instance_quota_bytes {
+        if let Some(quota) = self
+            .repository
+            .context()
+            .config
+            .app
+            .storage_instance_quota_bytes
+        {
             return Ok(quota.saturating_sub(used.max(0) as u64));
         }
 
@@ -65,8 +65,10 @@ where
                         .like(needle.clone())
                         .or(Expr::expr(Func::lower(Expr::col(sessions::Column::Ip)))
                             .like(needle.clone()))
-                        .or(Expr::expr(Func::lower(Expr::col(sessions::Column::UserAgent)))
-                            .like(needle)),
+                        .or(
+                            Expr::expr(Func::lower(Expr::col(sessions::Column::UserAgent)))
+                                .like(needle),
+                        ),
                 );
             }
         }
@@ -1,8 +1,9 @@
 use chrono::Utc;
 use entity::{
-    paginated::Paginated, sessions, sort::Sortable, users, ActiveValue, ColumnTrait,
-    ConnectionTrait, EntityTrait, Expr, IntoCondition, JoinType, ModelTrait, NullOrdering, Order,
-    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, RelationTrait, Select, Uuid,
+    paginated::Paginated, sessions, sort::Sortable, tfa_recovery_codes, users, ActiveValue,
+    ColumnTrait, ConnectionTrait, EntityTrait, Expr, IntoCondition, JoinType, ModelTrait,
+    NullOrdering, Order, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, RelationTrait,
+    Select, Uuid,
 };
 use error::{AppResult, Error};
 use validr::Validation;
@@ -37,9 +38,7 @@ where
                 .on_condition(move |_left, right| {
                     Expr::col((right.clone(), sessions::Column::ExpiresAt))
                         .gt(Utc::now().timestamp())
-                        .and(
-                            Expr::col((right, sessions::Column::Refresh)).is_not_null(),
-                        )
+                        .and(Expr::col((right, sessions::Column::Refresh)).is_not_null())
                         .into_condition()
                 }),
         );
@@ -48,11 +47,8 @@ where
         // engines: PG puts NULLs first, SQLite puts them last. Force
         // NULLS LAST so users without an active session sort *after*
         // those with one — preserving the "logged-in users first" intent.
-        query = query.order_by_with_nulls(
-            sessions::Column::UpdatedAt,
-            Order::Desc,
-            NullOrdering::Last,
-        );
+        query =
+            query.order_by_with_nulls(sessions::Column::UpdatedAt, Order::Desc, NullOrdering::Last);
 
         query
     }
@@ -197,6 +193,12 @@ where
         .exec(self.repository.connection())
         .await?;
 
+        // Recovery codes belong to the secret that was just removed.
+        tfa_recovery_codes::Entity::delete_many()
+            .filter(tfa_recovery_codes::Column::UserId.eq(user.id))
+            .exec(self.repository.connection())
+            .await?;
+
         Ok(())
     }
 }
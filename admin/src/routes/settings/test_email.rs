@@ -55,7 +55,9 @@ pub(crate) async fn test_email(
 
     let app_name = context.config.get_app_name();
     let app_version = context.config.get_app_version();
-    let sent_at = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC").to_string();
+    let sent_at = chrono::Utc::now()
+        .format("%Y-%m-%d %H:%M:%S UTC")
+        .to_string();
 
     let mut template = sender.template(
         "Test Email - SMTP Configuration",
@@ -75,4 +77,3 @@ pub(crate) async fn test_email(
         "message": format!("Test email sent successfully to {}", &user.email)
     })))
 }
-
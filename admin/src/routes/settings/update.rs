@@ -20,15 +20,28 @@ pub(crate) async fn update(
 ) -> AppResult<HttpResponse> {
     staff.is_admin_or_err()?;
 
+    let mut data = data.into_inner();
+
     let cipher = data.sharing.default_cipher();
     if cipher.is_empty() || Cipher::from_str(cipher).is_err() {
-        return Err(Error::as_validation("sharing.default_cipher", "unknown_cipher"));
+        return Err(Error::as_validation(
+            "sharing.default_cipher",
+            "unknown_cipher",
+        ));
+    }
+
+    if data.security.min_password_score() > 4 {
+        return Err(Error::as_validation(
+            "security.min_password_score",
+            "out_of_range",
+        ));
     }
 
-    context
-        .settings
-        .update(&context.config, data.into_inner())
-        .await?;
+    let previous = context.settings.inner().await.security.clone();
+    data.security
+        .carry_enforcement(&previous, chrono::Utc::now().timestamp());
+
+    context.settings.update(&context.config, data).await?;
 
     Ok(HttpResponse::Ok().json(context.settings.inner().await.clone()))
 }
@@ -1,6 +1,8 @@
 use crate::contracts::{
-    account::Account, cookies::Cookies, ctx::Ctx, email::Email, migration::Migration,
-    opaque::Opaque, register::Register, repository::Repository, sessions::Sessions,
+    account::Account, cookies::Cookies, ctx::Ctx, email::Email, migration::Migration, oidc::Oidc,
+    opaque::Opaque, personal_access_tokens::PersonalAccessTokens, policy::Policy,
+    recovery_codes::RecoveryCodes, register::Register, repository::Repository, sessions::Sessions,
+    signing_keys::SigningKeys, webauthn::WebAuthn,
 };
 use context::Context;
 
@@ -16,6 +18,12 @@ impl Sessions for Auth<'_> {}
 impl Account for Auth<'_> {}
 impl Opaque for Auth<'_> {}
 impl Migration for Auth<'_> {}
+impl WebAuthn for Auth<'_> {}
+impl RecoveryCodes for Auth<'_> {}
+impl PersonalAccessTokens for Auth<'_> {}
+impl SigningKeys for Auth<'_> {}
+impl Oidc for Auth<'_> {}
+impl Policy for Auth<'_> {}
 
 impl Ctx for Auth<'_> {
     fn ctx(&self) -> &Context {
@@ -11,12 +11,12 @@ use crate::data::{
     activity_query::ActivityQuery, change_password::ChangePassword, two_factor::Enable,
 };
 
-use super::repository::Repository;
+use super::{recovery_codes::RecoveryCodes, repository::Repository};
 
 #[async_trait::async_trait]
 pub(crate) trait Account
 where
-    Self: Repository,
+    Self: Repository + RecoveryCodes,
 {
     /// Verify the payload and change the users password
     async fn change_password(&self, data: ChangePassword) -> AppResult<users::Model> {
@@ -56,6 +56,9 @@ where
             user.id,
             users::ActiveModel {
                 password: ActiveValue::Set(Some(util::password::hash(&new_password))),
+                password_score: ActiveValue::Set(
+                    util::validation::password_score(&new_password).map(i16::from),
+                ),
                 encrypted_private_key: ActiveValue::Set(Some(encrypted_private_key)),
                 ..Default::default()
             },
@@ -63,11 +66,12 @@ where
         .await
     }
 
-    /// Disable the two factor authentication for the user
+    /// Disable the two factor authentication for the user. A recovery code
+    /// is accepted in place of the token, for a user who lost the device.
     async fn disable_two_factor(&self, id: Uuid, token: Option<String>) -> AppResult<()> {
         let user = self.get_by_id(id).await?;
 
-        if !user.verify_tfa(token) {
+        if !self.verify_second_factor(&user, token).await? {
             return Err(Error::Unauthorized("invalid_otp_token".to_string()));
         }
 
@@ -80,11 +84,12 @@ where
         )
         .await?;
 
-        Ok(())
+        self.revoke_recovery_codes(user.id).await
     }
 
-    /// Enable two factor authentication for the user
-    async fn enable_two_factor(&self, id: Uuid, data: Enable) -> AppResult<()> {
+    /// Enable two factor authentication for the user, returning the recovery
+    /// codes issued with it
+    async fn enable_two_factor(&self, id: Uuid, data: Enable) -> AppResult<Vec<String>> {
         let secret = data.into_value()?;
         let user = self.get_by_id(id).await?;
 
@@ -101,7 +106,26 @@ where
         )
         .await?;
 
-        Ok(())
+        self.issue_recovery_codes(user.id).await
+    }
+
+    /// Replace the user's recovery codes, proven with a current TOTP token
+    async fn regenerate_recovery_codes(
+        &self,
+        id: Uuid,
+        token: Option<String>,
+    ) -> AppResult<Vec<String>> {
+        let user = self.get_by_id(id).await?;
+
+        if user.secret.is_none() {
+            return Err(Error::BadRequest("two_factor_not_enabled".to_string()));
+        }
+
+        if !user.verify_tfa(token) {
+            return Err(Error::Unauthorized("invalid_otp_token".to_string()));
+        }
+
+        self.issue_recovery_codes(user.id).await
     }
 
     /// Load the paginated list of users activity (sessions)
@@ -170,7 +194,11 @@ fn verify_password(user: &users::Model, password: Option<&str>) -> AppResult<boo
 
 /// Verify the signature over `message`. Returns `true` only when a signature
 /// was supplied and verified against the user's current key.
-fn verify_signature(user: &users::Model, message: &str, signature: Option<&str>) -> AppResult<bool> {
+fn verify_signature(
+    user: &users::Model,
+    message: &str,
+    signature: Option<&str>,
+) -> AppResult<bool> {
     match signature {
         Some(signature) => {
             let key_type = cryptfns::identity::KeyType::from_str(&user.key_type)?;
@@ -205,6 +233,8 @@ mod tests {
             created_at: 0,
             updated_at: 0,
             share_notifications_enabled: true,
+            login_alerts_enabled: true,
+            password_score: None,
         }
     }
 
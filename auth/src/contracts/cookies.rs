@@ -4,17 +4,20 @@ use error::AppResult;
 
 use crate::data::authenticated::Authenticated;
 
-use super::ctx::Ctx;
+use super::{policy::Policy, signing_keys::SigningKeys};
 
 /// Cookie management
+#[async_trait::async_trait]
 pub(crate) trait Cookies
 where
-    Self: Ctx,
+    Self: SigningKeys + Policy,
 {
-    /// Sets a cookie on the request
-    fn manage_cookies(
+    /// Sets a cookie on the request. What the security policy asks of the
+    /// user is looked up again on every session issued or refreshed, so a
+    /// policy change reaches live sessions with their next token.
+    async fn manage_cookies(
         &self,
-        authenticated: &Authenticated,
+        authenticated: &mut Authenticated,
         issuer: &str,
     ) -> AppResult<(Cookie<'static>, Cookie<'static>)> {
         let destroy = authenticated.session.refresh.is_none();
@@ -32,7 +35,11 @@ where
         let jwt = match destroy {
             true => "destroyed".to_string(),
             false => {
-                crate::jwt::generate(authenticated, issuer, &self.ctx().config.auth.jwt_secret)?
+                authenticated.enrollment = self.enrollment(&authenticated.user).await?;
+
+                let key = self.current_signing_key().await?;
+
+                crate::jwt::generate(authenticated, issuer, &key)?
             }
         };
 
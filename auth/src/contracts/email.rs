@@ -1,5 +1,5 @@
 use context::{DatabaseConnection, SenderContract};
-use entity::{user_actions, users};
+use entity::{sessions, user_actions, users};
 use error::{AppResult, Error};
 
 use crate::actions::UserActions;
@@ -9,6 +9,9 @@ use super::ctx::Ctx;
 pub(crate) const ACTION_NAME: &str = "activate-email";
 pub(crate) const ACTION_COOLDOWN_IN_MINUTES: i64 = 1;
 
+/// Action behind the "this wasn't me" link of a new device alert.
+pub(crate) const KILL_SESSIONS_ACTION_NAME: &str = "kill-sessions";
+
 /// Email management
 #[async_trait::async_trait]
 pub(crate) trait Email
@@ -84,6 +87,94 @@ where
         Ok(())
     }
 
+    /// Tell the user one of their two factor recovery codes was just used to
+    /// sign in, so a stolen code does not go unnoticed.
+    async fn email_recovery_code_used(&self, user: &users::Model, remaining: u64) -> AppResult<()> {
+        let sender = match &self.ctx().sender {
+            Some(s) => s,
+            None => return Ok(()),
+        };
+
+        let content = r#"
+        <h1>A recovery code was used</h1>
+        <p>
+            One of your two factor recovery codes was just used to sign in to your account.
+            You have {{remaining}} unused codes left.
+        </p>
+        <p>
+            If this was not you, change your password and generate new recovery codes right away.
+        </p>
+        "#
+        .to_string();
+
+        let mut template = sender.template(
+            "Two factor recovery code used",
+            "One of your two factor recovery codes was just used to sign in",
+        )?;
+        template.add_template_var("remaining", remaining);
+        template.register_content_template(content.as_str())?;
+
+        let template = template.to(&user.email)?;
+
+        sender.send(vec![template]).await?;
+
+        Ok(())
+    }
+
+    /// Tell the user their account was just signed in to from a device or
+    /// address it was not used from before, with a link that ends every
+    /// session if it was not them.
+    async fn email_new_device(
+        &self,
+        user: &users::Model,
+        session: &sessions::Model,
+    ) -> AppResult<()> {
+        let sender = match &self.ctx().sender {
+            Some(s) => s,
+            None => return Ok(()),
+        };
+
+        let content = r#"
+        <h1>New sign-in to your account</h1>
+        <p>
+            Your account was just signed in to from a device we have not seen before.
+        </p>
+        <p>
+            IP address: <code>{{ip}}</code><br />
+            Device: <code>{{user_agent}}</code>
+        </p>
+        <p>
+            If this was you, there is nothing to do. If it was not, sign out everywhere
+            and change your password right away.
+        </p>
+        <p>
+            <a href="{{link}}" class="btn-primary">This wasn't me</a>
+        </p>
+        "#
+        .to_string();
+
+        let action = UserActions::<DatabaseConnection>::new(&self.ctx().db)
+            .for_user(user, KILL_SESSIONS_ACTION_NAME)
+            .await?;
+
+        let link = self.generate_client_link(&action)?;
+
+        let mut template = sender.template(
+            "New sign-in to your account",
+            "Your account was just signed in to from a new device",
+        )?;
+        template.add_template_var("ip", &session.ip);
+        template.add_template_var("user_agent", &session.user_agent);
+        template.add_template_var("link", &link);
+        template.register_content_template(content.as_str())?;
+
+        let template = template.to(&user.email)?;
+
+        sender.send(vec![template]).await?;
+
+        Ok(())
+    }
+
     /// Generate link for email activation
     fn generate_client_link(&self, action: &user_actions::Model) -> AppResult<String> {
         Ok(format!(
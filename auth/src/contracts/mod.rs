@@ -3,8 +3,14 @@ pub(crate) mod cookies;
 pub(crate) mod ctx;
 pub(crate) mod email;
 pub(crate) mod migration;
+pub(crate) mod oidc;
 pub(crate) mod opaque;
+pub(crate) mod personal_access_tokens;
+pub(crate) mod policy;
 pub(crate) mod provider;
+pub(crate) mod recovery_codes;
 pub(crate) mod register;
 pub(crate) mod repository;
 pub(crate) mod sessions;
+pub(crate) mod signing_keys;
+pub(crate) mod webauthn;
@@ -0,0 +1,339 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use config::oidc::OidcConfig;
+use cryptfns::rand::{rngs::OsRng, RngCore};
+use entity::{
+    invitations, oidc_logins, users, ActiveModelTrait, ActiveValue, ColumnTrait, EntityTrait,
+    QueryFilter, QueryOrder, Uuid,
+};
+use error::{AppResult, Error};
+use jsonwebtoken::{jwk::JwkSet, Algorithm, DecodingKey, Validation};
+use serde_json::Value;
+
+use crate::data::oidc::{map_role, Discovery, OidcSignIn, TokenResponse};
+
+use super::repository::Repository;
+
+/// How long the user has to get through the identity provider, and then to
+/// use the ticket it earned them.
+const LOGIN_SECONDS: i64 = 600;
+
+/// Requests to the identity provider give up after this long.
+const PROVIDER_TIMEOUT_SECONDS: u64 = 10;
+
+/// Sign-in at an OpenID Connect provider with the authorization code flow
+/// and PKCE, and the gate that holds logins and registrations to it.
+#[async_trait::async_trait]
+pub(crate) trait Oidc
+where
+    Self: Repository,
+{
+    /// The provider configuration, when the gate is enabled.
+    fn oidc_config(&self) -> AppResult<&OidcConfig> {
+        self.ctx()
+            .config
+            .oidc
+            .as_ref()
+            .ok_or_else(|| Error::NotFound("oidc_not_configured".to_string()))
+    }
+
+    fn oidc_http(&self) -> AppResult<reqwest::Client> {
+        reqwest::Client::builder()
+            .timeout(Duration::from_secs(PROVIDER_TIMEOUT_SECONDS))
+            .build()
+            .map_err(Error::from)
+    }
+
+    /// Read the provider's discovery document.
+    async fn oidc_discovery(&self) -> AppResult<Discovery> {
+        let config = self.oidc_config()?;
+
+        let http = self.oidc_http()?;
+        let discovery: Discovery = http
+            .get(format!(
+                "{}/.well-known/openid-configuration",
+                config.issuer
+            ))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        if discovery.issuer.trim_end_matches('/') != config.issuer {
+            return Err(Error::InternalError("oidc_issuer_mismatch".to_string()));
+        }
+
+        Ok(discovery)
+    }
+
+    /// Start a sign-in: remember its PKCE verifier and nonce under a fresh
+    /// `state`, and return the provider URL to send the browser to.
+    async fn oidc_authorize_url(&self) -> AppResult<String> {
+        let config = self.oidc_config()?;
+        let discovery = self.oidc_discovery().await?;
+
+        let pkce = cryptfns::pkce::generate();
+        let mut nonce = [0u8; 16];
+        OsRng.fill_bytes(&mut nonce);
+        let nonce = cryptfns::base64::encode_url(nonce);
+        let state = Uuid::new_v4();
+        let now = Utc::now().timestamp();
+
+        oidc_logins::Entity::insert(oidc_logins::ActiveModel {
+            id: ActiveValue::Set(state),
+            code_verifier: ActiveValue::Set(pkce.verifier),
+            nonce: ActiveValue::Set(nonce.clone()),
+            ticket: ActiveValue::Set(None),
+            email: ActiveValue::Set(None),
+            role: ActiveValue::Set(None),
+            invitation_id: ActiveValue::Set(None),
+            created_at: ActiveValue::Set(now),
+            expires_at: ActiveValue::Set(now + LOGIN_SECONDS),
+        })
+        .exec(self.connection())
+        .await?;
+
+        let url = url::Url::parse_with_params(
+            &discovery.authorization_endpoint,
+            &[
+                ("response_type", "code"),
+                ("client_id", config.client_id.as_str()),
+                ("redirect_uri", config.redirect_url.as_str()),
+                ("scope", config.scopes.as_str()),
+                ("state", state.to_string().as_str()),
+                ("nonce", nonce.as_str()),
+                ("code_challenge", pkce.challenge.as_str()),
+                ("code_challenge_method", "S256"),
+            ],
+        )
+        .map_err(|_| Error::InternalError("oidc_invalid_authorization_endpoint".to_string()))?;
+
+        Ok(url.to_string())
+    }
+
+    /// Finish a sign-in: trade the code for an ID token, check who it
+    /// names may use Hoodik, and hand out the ticket for the login.
+    ///
+    /// Someone without an account is only let in with an open invitation
+    /// or when the registration settings would let them register anyway.
+    async fn oidc_callback(&self, code: &str, state: Uuid) -> AppResult<OidcSignIn> {
+        let config = self.oidc_config()?;
+        let now = Utc::now().timestamp();
+
+        let login = oidc_logins::Entity::find_by_id(state)
+            .filter(oidc_logins::Column::Ticket.is_null())
+            .one(self.connection())
+            .await?
+            .ok_or_else(|| Error::Unauthorized("oidc_invalid_state".to_string()))?;
+
+        if login.expires_at < now {
+            oidc_logins::Entity::delete_by_id(login.id)
+                .exec(self.connection())
+                .await?;
+
+            return Err(Error::Unauthorized("oidc_login_expired".to_string()));
+        }
+
+        let discovery = self.oidc_discovery().await?;
+
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", config.redirect_url.as_str()),
+            ("client_id", config.client_id.as_str()),
+            ("code_verifier", login.code_verifier.as_str()),
+        ];
+        if let Some(secret) = config.client_secret.as_deref() {
+            form.push(("client_secret", secret));
+        }
+
+        let http = self.oidc_http()?;
+        let response = http
+            .post(&discovery.token_endpoint)
+            .form(&form)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(Error::Unauthorized("oidc_code_rejected".to_string()));
+        }
+        let token: TokenResponse = response.json().await?;
+
+        let claims = self
+            .oidc_verify_id_token(&discovery, &token.id_token, &login.nonce)
+            .await?;
+
+        let email = claims
+            .get("email")
+            .and_then(Value::as_str)
+            .map(|e| e.trim().to_lowercase())
+            .filter(|e| !e.is_empty())
+            .ok_or_else(|| Error::Unauthorized("oidc_email_missing".to_string()))?;
+
+        if claims.get("email_verified").and_then(Value::as_bool) == Some(false) {
+            return Err(Error::Unauthorized("oidc_email_unverified".to_string()));
+        }
+
+        let role = config
+            .role_claim
+            .as_deref()
+            .map(|claim| map_role(claims.get(claim), &config.admin_role).to_string());
+
+        let registered = self.get_by_email(&email).await.is_ok();
+        let mut invitation_id = None;
+
+        if !registered {
+            invitation_id = invitations::Entity::find()
+                .filter(invitations::Column::Email.eq(email.as_str()))
+                .filter(invitations::Column::ExpiresAt.gte(now))
+                .order_by_desc(invitations::Column::CreatedAt)
+                .one(self.connection())
+                .await?
+                .map(|invitation| invitation.id);
+
+            if invitation_id.is_none() {
+                self.can_register_or_else(&email, || {
+                    Err(Error::Forbidden("oidc_not_provisioned".to_string()))
+                })
+                .await?;
+            }
+        }
+
+        let ticket = Uuid::new_v4();
+
+        let mut active_model: oidc_logins::ActiveModel = login.into();
+        active_model.ticket = ActiveValue::Set(Some(ticket));
+        active_model.email = ActiveValue::Set(Some(email.clone()));
+        active_model.role = ActiveValue::Set(role);
+        active_model.invitation_id = ActiveValue::Set(invitation_id);
+        active_model.expires_at = ActiveValue::Set(now + LOGIN_SECONDS);
+        active_model.update(self.connection()).await?;
+
+        Ok(OidcSignIn {
+            ticket,
+            email,
+            registered,
+        })
+    }
+
+    /// Verify the ID token against the provider's published keys and return
+    /// its claims.
+    async fn oidc_verify_id_token(
+        &self,
+        discovery: &Discovery,
+        id_token: &str,
+        nonce: &str,
+    ) -> AppResult<Value> {
+        let config = self.oidc_config()?;
+        let header = jsonwebtoken::decode_header(id_token)?;
+
+        // The provider signs with a key of its own, never a shared secret.
+        if matches!(
+            header.alg,
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+        ) {
+            return Err(Error::Unauthorized(
+                "oidc_unsupported_algorithm".to_string(),
+            ));
+        }
+
+        let http = self.oidc_http()?;
+        let jwks: JwkSet = http
+            .get(&discovery.jwks_uri)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let jwk = match header.kid.as_deref() {
+            Some(kid) => jwks.find(kid),
+            None if jwks.keys.len() == 1 => jwks.keys.first(),
+            None => None,
+        }
+        .ok_or_else(|| Error::Unauthorized("oidc_unknown_key".to_string()))?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&discovery.issuer]);
+        validation.set_audience(&[&config.client_id]);
+
+        let claims =
+            jsonwebtoken::decode::<Value>(id_token, &DecodingKey::from_jwk(jwk)?, &validation)
+                .map_err(|_| Error::Unauthorized("oidc_invalid_id_token".to_string()))?
+                .claims;
+
+        if claims.get("nonce").and_then(Value::as_str) != Some(nonce) {
+            return Err(Error::Unauthorized("oidc_nonce_mismatch".to_string()));
+        }
+
+        Ok(claims)
+    }
+
+    /// Consume the ticket a login or registration for `email` came with.
+    /// Without an issuer configured there is no gate and nothing to consume.
+    async fn oidc_take_ticket(
+        &self,
+        email: &str,
+        ticket: Option<Uuid>,
+    ) -> AppResult<Option<oidc_logins::Model>> {
+        if self.ctx().config.oidc.is_none() {
+            return Ok(None);
+        }
+
+        let required = || Error::Unauthorized("oidc_login_required".to_string());
+
+        let ticket = ticket.ok_or_else(required)?;
+        let login = oidc_logins::Entity::find()
+            .filter(oidc_logins::Column::Ticket.eq(ticket))
+            .one(self.connection())
+            .await?
+            .ok_or_else(required)?;
+
+        // Single-use, whatever the outcome.
+        oidc_logins::Entity::delete_by_id(login.id)
+            .exec(self.connection())
+            .await?;
+
+        if login.expires_at < Utc::now().timestamp() || login.email.as_deref() != Some(email) {
+            return Err(required());
+        }
+
+        Ok(Some(login))
+    }
+
+    /// Let an existing user through the gate, bringing their role in line
+    /// with the provider when roles are mapped.
+    async fn oidc_gate(&self, user: users::Model, ticket: Option<Uuid>) -> AppResult<users::Model> {
+        let login = self.oidc_take_ticket(&user.email, ticket).await?;
+
+        match login {
+            Some(login) => self.oidc_apply_role(user, login.role).await,
+            None => Ok(user),
+        }
+    }
+
+    /// Give the user the role mapped from their ID token, if any.
+    async fn oidc_apply_role(
+        &self,
+        mut user: users::Model,
+        role: Option<String>,
+    ) -> AppResult<users::Model> {
+        let Some(role) = role else {
+            return Ok(user);
+        };
+
+        let role = (role == "admin").then_some(role);
+        if user.role != role {
+            users::Entity::update_many()
+                .col_expr(users::Column::Role, entity::Expr::value(role.clone()))
+                .filter(users::Column::Id.eq(user.id))
+                .exec(self.connection())
+                .await?;
+
+            user.role = role;
+        }
+
+        Ok(user)
+    }
+}
@@ -1,8 +1,8 @@
 use chrono::{Duration, Utc};
 use cryptfns::identity::KeyType;
 use entity::{
-    opaque_config, opaque_ksf, opaque_login_sessions, users, ActiveValue, ColumnTrait, EntityTrait,
-    Expr, OnConflict, QueryFilter, Uuid,
+    opaque_config, opaque_ksf, opaque_login_sessions, users, webauthn_challenges, ActiveValue,
+    ColumnTrait, EntityTrait, Expr, OnConflict, QueryFilter, Uuid,
 };
 use error::{AppResult, Error};
 use std::str::FromStr;
@@ -10,9 +10,10 @@ use std::str::FromStr;
 use crate::data::{
     authenticated::Authenticated,
     opaque::{
-        KsfParamsResponse, OpaqueLoginStartResponse, OpaqueRegisterFinish,
+        KsfParamsResponse, LoginFinish, OpaqueLoginStartResponse, OpaqueRegisterFinish,
         OpaqueRegisterStartResponse,
     },
+    webauthn::LoginAssertion,
 };
 
 /// The OPAQUE protocol version new registrations and migrations record. Bumped
@@ -33,7 +34,10 @@ fn default_ksf_response() -> KsfParamsResponse {
     }
 }
 
-use super::{repository::Repository, sessions::Sessions};
+use super::{
+    oidc::Oidc, recovery_codes::RecoveryCodes, repository::Repository, sessions::Sessions,
+    webauthn::WebAuthn,
+};
 
 /// How long a login-start server state stays valid before the client must
 /// restart the login.
@@ -56,7 +60,7 @@ const PASSWORD_CHANGE_REPLAY_WINDOW_SECONDS: i64 = 300;
 #[async_trait::async_trait]
 pub(crate) trait Opaque
 where
-    Self: Repository + Sessions,
+    Self: Repository + Sessions + WebAuthn + RecoveryCodes + Oidc,
 {
     /// Read the singleton server OPRF seed, generating and persisting it on
     /// first use. It is never rotated — every registration is bound to it.
@@ -73,7 +77,11 @@ where
             id: ActiveValue::Set(opaque_config::Model::SINGLETON_ID),
             server_setup: ActiveValue::Set(server_setup),
         })
-        .on_conflict(OnConflict::column(opaque_config::Column::Id).do_nothing().to_owned())
+        .on_conflict(
+            OnConflict::column(opaque_config::Column::Id)
+                .do_nothing()
+                .to_owned(),
+        )
         .exec_without_returning(self.connection())
         .await?;
 
@@ -101,7 +109,9 @@ where
         )
         .map_err(|_| Error::BadRequest("opaque_registration_request_invalid".to_string()))?;
 
-        Ok(OpaqueRegisterStartResponse { registration_response })
+        Ok(OpaqueRegisterStartResponse {
+            registration_response,
+        })
     }
 
     /// Unauthenticated OPAQUE registration start for a brand-new signup, keyed
@@ -126,7 +136,9 @@ where
         )
         .map_err(|_| Error::BadRequest("opaque_registration_request_invalid".to_string()))?;
 
-        Ok(OpaqueRegisterStartResponse { registration_response })
+        Ok(OpaqueRegisterStartResponse {
+            registration_response,
+        })
     }
 
     /// Finish an authenticated OPAQUE re-registration — the server half of a
@@ -164,6 +176,11 @@ where
             return Err(Error::Unauthorized("invalid_otp_token".to_string()));
         }
 
+        let security = self.ctx().settings.inner().await.security.clone();
+        if !security.password_score_valid(data.password_score) {
+            return Err(Error::as_validation("password_score", "password_too_weak"));
+        }
+
         let canonical = format!(
             "{PAKE_REGISTER_CANONICAL_PREFIX}\0{}\0{}",
             data.registration_upload, data.issued_at
@@ -176,19 +193,27 @@ where
         // the user's only copy of their private key with nothing — a one-way
         // brick. Same guard as migration/complete.
         if data.encrypted_private_key.trim().is_empty() {
-            return Err(Error::BadRequest("encrypted_private_key_required".to_string()));
+            return Err(Error::BadRequest(
+                "encrypted_private_key_required".to_string(),
+            ));
         }
 
-        let password_file =
-            cryptfns::opaque::server_registration_finish(&data.registration_upload)
-                .map_err(|_| Error::BadRequest("opaque_registration_upload_invalid".to_string()))?;
+        let password_file = cryptfns::opaque::server_registration_finish(&data.registration_upload)
+            .map_err(|_| Error::BadRequest("opaque_registration_upload_invalid".to_string()))?;
 
         users::Entity::update_many()
-            .col_expr(users::Column::OpaquePasswordFile, Expr::value(password_file))
+            .col_expr(
+                users::Column::OpaquePasswordFile,
+                Expr::value(password_file),
+            )
             .col_expr(
                 users::Column::EncryptedPrivateKey,
                 Expr::value(data.encrypted_private_key.clone()),
             )
+            .col_expr(
+                users::Column::PasswordScore,
+                Expr::value(data.password_score.map(i16::from)),
+            )
             .filter(users::Column::Id.eq(user_id))
             .exec(self.connection())
             .await?;
@@ -214,13 +239,17 @@ where
         let user = match self.get_by_email(email).await {
             Ok(user) => user,
             Err(e) if e.is_not_found() => {
-                return Ok(OpaqueLoginStartResponse::Password { ksf: default_ksf_response() })
+                return Ok(OpaqueLoginStartResponse::Password {
+                    ksf: default_ksf_response(),
+                })
             }
             Err(e) => return Err(e),
         };
 
         let Some(password_file) = user.opaque_password_file.clone() else {
-            return Ok(OpaqueLoginStartResponse::Password { ksf: default_ksf_response() });
+            return Ok(OpaqueLoginStartResponse::Password {
+                ksf: default_ksf_response(),
+            });
         };
 
         let ksf = match opaque_ksf::Entity::find_by_id(user.id)
@@ -272,16 +301,20 @@ where
         })
     }
 
-    /// Finish an OPAQUE login: consume the server state, verify the client's
-    /// proof, run TOTP and activation checks, and mint a session.
+    /// Finish an OPAQUE login: consume the server state and verify the
+    /// client's proof, then ask for the second factor. A right TOTP code (or
+    /// an unused recovery code) is enough on its own; otherwise an account with security keys gets a
+    /// [`LoginFinish::WebAuthn`] challenge to answer at
+    /// [`Opaque::opaque_login_webauthn`] before any session is issued.
     async fn opaque_login_finish(
         &self,
         login_id: Uuid,
         credential_finalization: &str,
         token: Option<String>,
+        oidc_ticket: Option<Uuid>,
         user_agent: &str,
         ip: &str,
-    ) -> AppResult<Authenticated> {
+    ) -> AppResult<LoginFinish> {
         let session = opaque_login_sessions::Entity::find_by_id(login_id)
             .one(self.connection())
             .await?
@@ -299,12 +332,54 @@ where
         cryptfns::opaque::server_login_finish(&session.server_login_state, credential_finalization)
             .map_err(|_| Error::Unauthorized("invalid_credentials".to_string()))?;
 
-        let mut user = self.get_by_id(session.user_id).await?;
+        let user = self.get_by_id(session.user_id).await?;
+        let user = self.oidc_gate(user, oidc_ticket).await?;
 
-        if !user.verify_tfa(token) {
-            return Err(Error::Unauthorized("invalid_otp_token".to_string()));
+        let totp_passed =
+            user.secret.is_some() && self.verify_second_factor(&user, token.clone()).await?;
+        if !totp_passed {
+            if let Some(challenge) = self.webauthn_login_challenge(&user).await? {
+                return Ok(LoginFinish::WebAuthn(challenge));
+            }
+
+            if !user.verify_tfa(token) {
+                return Err(Error::Unauthorized("invalid_otp_token".to_string()));
+            }
         }
 
+        self.opaque_login_complete(user, user_agent, ip)
+            .await
+            .map(|authenticated| LoginFinish::Authenticated(Box::new(authenticated)))
+    }
+
+    /// Second step of a login for an account with security keys: verify the
+    /// assertion over the challenge `login/finish` handed out, and mint the
+    /// session.
+    async fn opaque_login_webauthn(
+        &self,
+        data: &LoginAssertion,
+        user_agent: &str,
+        ip: &str,
+    ) -> AppResult<Authenticated> {
+        let challenge = self
+            .webauthn_take_challenge(data.login_id, webauthn_challenges::Model::LOGIN)
+            .await?;
+
+        self.webauthn_verify(&challenge, &data.response).await?;
+
+        let user = self.get_by_id(challenge.user_id).await?;
+
+        self.opaque_login_complete(user, user_agent, ip).await
+    }
+
+    /// Run the activation check and mint a session for a user who passed
+    /// every factor.
+    async fn opaque_login_complete(
+        &self,
+        mut user: users::Model,
+        user_agent: &str,
+        ip: &str,
+    ) -> AppResult<Authenticated> {
         if self.enforce_email_activation().await && user.email_verified_at.is_none() {
             return Err(Error::Unauthorized("inactive_account".to_string()));
         }
@@ -322,6 +397,10 @@ where
 
         let session = self.generate(&user, user_agent, ip).await?;
 
-        Ok(Authenticated { user, session })
+        Ok(Authenticated {
+            user,
+            session,
+            enrollment: None,
+        })
     }
 }
@@ -0,0 +1,221 @@
+use chrono::Utc;
+use context::Context;
+use cryptfns::rand::{rngs::OsRng, RngCore};
+use entity::{
+    files, personal_access_tokens, users, ActiveValue, ColumnTrait, EntityTrait, QueryFilter,
+    QueryOrder, Uuid,
+};
+use error::{AppResult, Error};
+
+use crate::data::{
+    claims::Claims,
+    personal_access_token::{
+        CreatePersonalAccessToken, CreatedPersonalAccessToken, Grant, PersonalAccessToken, PREFIX,
+    },
+};
+
+use super::{policy::Policy, repository::Repository};
+
+/// Random bytes behind a token, 256 bits so a fast hash is enough to store it.
+const SECRET_BYTES: usize = 32;
+
+const MAX_TOKEN_NAME_LENGTH: usize = 64;
+
+/// `last_used_at` is only written when it is older than this, so a script
+/// uploading thousands of chunks does not turn every request into a write.
+const LAST_USED_PRECISION_SECONDS: i64 = 60;
+
+/// Deepest folder nesting walked when checking that a file sits under a
+/// token's folder; anything deeper is treated as outside.
+const MAX_FOLDER_DEPTH: usize = 1024;
+
+/// Long-lived, scoped credentials for automation, created and revoked by the
+/// user from their account and resolved into [`Claims`] when a request
+/// carries one.
+#[async_trait::async_trait]
+pub(crate) trait PersonalAccessTokens
+where
+    Self: Repository + Policy,
+{
+    /// Every token the user created, newest first.
+    async fn personal_access_tokens(&self, user_id: Uuid) -> AppResult<Vec<PersonalAccessToken>> {
+        let tokens = personal_access_tokens::Entity::find()
+            .filter(personal_access_tokens::Column::UserId.eq(user_id))
+            .order_by_desc(personal_access_tokens::Column::CreatedAt)
+            .all(self.connection())
+            .await?;
+
+        Ok(tokens.into_iter().map(PersonalAccessToken::from).collect())
+    }
+
+    /// Create a token and return it with its secret, which is not stored.
+    async fn create_personal_access_token(
+        &self,
+        user_id: Uuid,
+        data: CreatePersonalAccessToken,
+    ) -> AppResult<CreatedPersonalAccessToken> {
+        refuse_with_oidc(self.ctx(), Error::BadRequest)?;
+
+        let data = data.into_value()?;
+
+        if let Some(folder_id) = data.folder_id {
+            let folder = files::Entity::find_by_id(folder_id)
+                .one(self.connection())
+                .await?
+                .filter(|f| f.mime == "dir" && f.deleted_at.is_none())
+                .ok_or_else(|| Error::NotFound("folder_not_found".to_string()))?;
+
+            if !entity::permission::permission(self.connection(), folder.id, user_id)
+                .await?
+                .can_read()
+            {
+                return Err(Error::NotFound("folder_not_found".to_string()));
+            }
+        }
+
+        let mut secret = [0u8; SECRET_BYTES];
+        OsRng.fill_bytes(&mut secret);
+        let token = format!("{PREFIX}{}", cryptfns::base64::encode_url(secret));
+
+        let model = personal_access_tokens::ActiveModel {
+            id: ActiveValue::Set(Uuid::new_v4()),
+            user_id: ActiveValue::Set(user_id),
+            name: ActiveValue::Set(
+                data.name
+                    .trim()
+                    .chars()
+                    .take(MAX_TOKEN_NAME_LENGTH)
+                    .collect(),
+            ),
+            token_hash: ActiveValue::Set(hash(&token)),
+            scopes: ActiveValue::Set(
+                data.scopes
+                    .iter()
+                    .map(|s| s.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            ),
+            folder_id: ActiveValue::Set(data.folder_id),
+            expires_at: ActiveValue::Set(data.expires_at),
+            last_used_at: ActiveValue::Set(None),
+            created_at: ActiveValue::Set(Utc::now().timestamp()),
+        };
+
+        let model = personal_access_tokens::Entity::insert(model)
+            .exec_with_returning(self.connection())
+            .await?;
+
+        Ok(CreatedPersonalAccessToken {
+            token,
+            personal_access_token: model.into(),
+        })
+    }
+
+    /// Revoke one of the user's tokens; it stops working immediately.
+    async fn revoke_personal_access_token(&self, user_id: Uuid, id: Uuid) -> AppResult<()> {
+        let deleted = personal_access_tokens::Entity::delete_many()
+            .filter(personal_access_tokens::Column::Id.eq(id))
+            .filter(personal_access_tokens::Column::UserId.eq(user_id))
+            .exec(self.connection())
+            .await?
+            .rows_affected;
+
+        if deleted == 0 {
+            return Err(Error::NotFound("token_not_found".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Resolve a token from a request into claims for its owner, recording
+    /// the use.
+    async fn personal_access_token_claims(&self, token: &str) -> AppResult<Claims> {
+        refuse_with_oidc(self.ctx(), Error::Unauthorized)?;
+
+        let now = Utc::now().timestamp();
+
+        let (model, user) = personal_access_tokens::Entity::find()
+            .filter(personal_access_tokens::Column::TokenHash.eq(hash(token)))
+            .find_also_related(users::Entity)
+            .one(self.connection())
+            .await?
+            .and_then(|(model, user)| Some((model, user?)))
+            .ok_or_else(|| Error::Unauthorized("invalid_token".to_string()))?;
+
+        if model.expires_at.is_some_and(|e| e <= now) {
+            return Err(Error::Unauthorized("token_expired".to_string()));
+        }
+
+        // Automation runs as its owner, so it stops with them until they
+        // meet the security policy.
+        if self.enrollment_enforced(&user).await? {
+            return Err(Error::EnrollmentRequired("enrollment_required".to_string()));
+        }
+
+        if model
+            .last_used_at
+            .is_none_or(|l| l + LAST_USED_PRECISION_SECONDS <= now)
+        {
+            personal_access_tokens::Entity::update_many()
+                .col_expr(
+                    personal_access_tokens::Column::LastUsedAt,
+                    entity::Expr::value(now),
+                )
+                .filter(personal_access_tokens::Column::Id.eq(model.id))
+                .exec(self.connection())
+                .await?;
+        }
+
+        Ok(Claims {
+            iss: String::from("token"),
+            sub: user.id,
+            exp: model.expires_at.unwrap_or(i64::MAX),
+            iat: model.created_at,
+            device: model.id,
+            // Tokens never carry staff rights, whatever the owner's role.
+            role: None,
+            quota: user.quota,
+            grant: Some(Grant::from(&model)),
+            enroll: false,
+        })
+    }
+
+    /// Whether `file_id` is `folder_id` or sits somewhere below it.
+    async fn is_in_folder(&self, folder_id: Uuid, file_id: Uuid) -> AppResult<bool> {
+        let mut current = Some(file_id);
+
+        for _ in 0..MAX_FOLDER_DEPTH {
+            let Some(id) = current else {
+                return Ok(false);
+            };
+
+            if id == folder_id {
+                return Ok(true);
+            }
+
+            current = files::Entity::find_by_id(id)
+                .one(self.connection())
+                .await?
+                .and_then(|f| f.file_id);
+        }
+
+        Ok(false)
+    }
+}
+
+/// Tokens never go back to the OpenID Connect provider, so with the gate on
+/// they would keep working for someone disabled there. Unless the instance
+/// allows that, they are refused, including the ones made before the gate
+/// was turned on.
+fn refuse_with_oidc(ctx: &Context, error: fn(String) -> Error) -> AppResult<()> {
+    match &ctx.config.oidc {
+        Some(oidc) if !oidc.allow_personal_access_tokens => {
+            Err(error("oidc_personal_access_tokens_disabled".to_string()))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn hash(token: &str) -> String {
+    cryptfns::sha256::digest(token)
+}
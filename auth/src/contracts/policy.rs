@@ -0,0 +1,64 @@
+use chrono::Utc;
+use entity::{users, webauthn_credentials, ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter};
+use error::AppResult;
+
+use crate::data::enrollment::{Enrollment, Requirement};
+
+use super::repository::Repository;
+
+/// The security policy from the settings, applied to a user: whether they
+/// have to use two-factor and whether their password is strong enough.
+#[async_trait::async_trait]
+pub(crate) trait Policy
+where
+    Self: Repository,
+{
+    /// What the policy still asks of the user, `None` when nothing. Users
+    /// newly required to use two-factor have the grace period to set it up
+    /// before it is enforced; a password that falls short is enforced right
+    /// away.
+    async fn enrollment(&self, user: &users::Model) -> AppResult<Option<Enrollment>> {
+        let security = self.ctx().settings.inner().await.security.clone();
+        let mut enrollment = Enrollment::default();
+
+        let is_admin = user.role.as_deref() == Some("admin");
+        if security.requires_two_factor(is_admin) && !self.has_second_factor(user).await? {
+            let due_at = security.two_factor_due_at(user.created_at);
+
+            if due_at <= Utc::now().timestamp() {
+                enrollment.required.push(Requirement::TwoFactor);
+            } else {
+                enrollment.two_factor_due_at = Some(due_at);
+            }
+        }
+
+        let score = user.password_score.and_then(|s| u8::try_from(s).ok());
+        if !security.password_score_valid(score) {
+            enrollment.required.push(Requirement::Password);
+        }
+
+        Ok((enrollment != Enrollment::default()).then_some(enrollment))
+    }
+
+    /// Whether the user's sessions are confined to enrollment.
+    async fn enrollment_enforced(&self, user: &users::Model) -> AppResult<bool> {
+        Ok(self
+            .enrollment(user)
+            .await?
+            .is_some_and(|enrollment| enrollment.is_enforced()))
+    }
+
+    /// A TOTP secret or at least one security key.
+    async fn has_second_factor(&self, user: &users::Model) -> AppResult<bool> {
+        if user.secret.is_some() {
+            return Ok(true);
+        }
+
+        let keys = webauthn_credentials::Entity::find()
+            .filter(webauthn_credentials::Column::UserId.eq(user.id))
+            .count(self.connection())
+            .await?;
+
+        Ok(keys > 0)
+    }
+}
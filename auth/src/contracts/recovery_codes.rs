@@ -0,0 +1,165 @@
+use chrono::Utc;
+use cryptfns::rand::{rngs::OsRng, Rng};
+use entity::{
+    tfa_recovery_codes, users, ActiveValue, ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter,
+    Uuid,
+};
+use error::AppResult;
+
+use super::{email::Email, repository::Repository};
+
+/// How many codes a user gets each time they are issued.
+const RECOVERY_CODES: usize = 10;
+
+/// Characters a code is drawn from: lowercase letters and digits without the
+/// ones easily mistaken for each other (`0`/`o`, `1`/`i`/`l`).
+const ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+
+/// Characters in a code, shown in groups of four. 16 of 31 symbols is ~79
+/// bits, enough that a fast hash of the code is not worth brute forcing.
+const CODE_LENGTH: usize = 16;
+
+/// Single-use recovery codes standing in for a TOTP token when the device is
+/// lost. Issued when TOTP is enabled, regenerated on request, and burned on
+/// use, with an email to the account owner each time one is.
+#[async_trait::async_trait]
+pub(crate) trait RecoveryCodes
+where
+    Self: Repository + Email,
+{
+    /// Replace the user's recovery codes with a fresh set and return them.
+    /// This is the only time the codes are ever available in the clear.
+    async fn issue_recovery_codes(&self, user_id: Uuid) -> AppResult<Vec<String>> {
+        self.revoke_recovery_codes(user_id).await?;
+
+        let now = Utc::now().timestamp();
+        let codes = (0..RECOVERY_CODES).map(|_| generate()).collect::<Vec<_>>();
+
+        tfa_recovery_codes::Entity::insert_many(codes.iter().map(|code| {
+            tfa_recovery_codes::ActiveModel {
+                id: ActiveValue::Set(Uuid::new_v4()),
+                user_id: ActiveValue::Set(user_id),
+                code_hash: ActiveValue::Set(hash(user_id, code)),
+                used_at: ActiveValue::Set(None),
+                created_at: ActiveValue::Set(now),
+            }
+        }))
+        .exec_without_returning(self.connection())
+        .await?;
+
+        Ok(codes)
+    }
+
+    /// Drop every recovery code of the user, used or not.
+    async fn revoke_recovery_codes(&self, user_id: Uuid) -> AppResult<()> {
+        tfa_recovery_codes::Entity::delete_many()
+            .filter(tfa_recovery_codes::Column::UserId.eq(user_id))
+            .exec(self.connection())
+            .await?;
+
+        Ok(())
+    }
+
+    /// [`users::Model::verify_tfa`] that also accepts an unused recovery code
+    /// in place of the token, burning it.
+    async fn verify_second_factor(
+        &self,
+        user: &users::Model,
+        token: Option<String>,
+    ) -> AppResult<bool> {
+        if user.verify_tfa(token.clone()) {
+            return Ok(true);
+        }
+
+        let Some(code) = token.as_deref().and_then(normalize) else {
+            return Ok(false);
+        };
+
+        // Burn it in the same statement that checks it is unused, so two
+        // concurrent logins cannot both spend one code.
+        let burned = tfa_recovery_codes::Entity::update_many()
+            .col_expr(
+                tfa_recovery_codes::Column::UsedAt,
+                entity::Expr::value(Utc::now().timestamp()),
+            )
+            .filter(tfa_recovery_codes::Column::UserId.eq(user.id))
+            .filter(tfa_recovery_codes::Column::CodeHash.eq(hash(user.id, &code)))
+            .filter(tfa_recovery_codes::Column::UsedAt.is_null())
+            .exec(self.connection())
+            .await?
+            .rows_affected;
+
+        if burned == 0 {
+            return Ok(false);
+        }
+
+        let remaining = tfa_recovery_codes::Entity::find()
+            .filter(tfa_recovery_codes::Column::UserId.eq(user.id))
+            .filter(tfa_recovery_codes::Column::UsedAt.is_null())
+            .count(self.connection())
+            .await?;
+
+        // The code already let the user in; a mail outage must not undo that.
+        if let Err(e) = self.email_recovery_code_used(user, remaining).await {
+            log::error!("Failed to send recovery code notification: {e}");
+        }
+
+        Ok(true)
+    }
+}
+
+fn generate() -> String {
+    let mut rng = OsRng;
+    let code = (0..CODE_LENGTH)
+        .map(|_| ALPHABET[rng.gen_range(0..ALPHABET.len())] as char)
+        .collect::<Vec<_>>();
+
+    code.chunks(4)
+        .map(|group| group.iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// The code as stored, or `None` when the token is not shaped like one (a
+/// TOTP token, for instance).
+fn normalize(token: &str) -> Option<String> {
+    let code = token
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .map(|c| c.to_ascii_lowercase())
+        .collect::<String>();
+
+    (code.len() == CODE_LENGTH && code.bytes().all(|c| ALPHABET.contains(&c))).then_some(code)
+}
+
+fn hash(user_id: Uuid, code: &str) -> String {
+    let code = normalize(code).unwrap_or_default();
+
+    cryptfns::sha256::digest(format!("{user_id}:{code}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{generate, hash, normalize};
+    use entity::Uuid;
+
+    #[test]
+    fn generated_codes_normalize_to_themselves() {
+        let code = generate();
+        assert_eq!(code.len(), 19);
+        assert_eq!(normalize(&code.to_uppercase()), Some(code.replace('-', "")));
+    }
+
+    #[test]
+    fn totp_tokens_are_not_codes() {
+        assert_eq!(normalize("123456"), None);
+    }
+
+    #[test]
+    fn hash_is_bound_to_the_user() {
+        let code = generate();
+        assert_ne!(hash(Uuid::new_v4(), &code), hash(Uuid::new_v4(), &code));
+        let user = Uuid::new_v4();
+        assert_eq!(hash(user, &code), hash(user, &code.replace('-', " ")));
+    }
+}
@@ -25,14 +25,18 @@ where
         let invitation_id = data.invitation_id;
         let opaque_upload = data.opaque_registration_upload.clone();
 
+        let security = self.ctx().settings.inner().await.security.clone();
+        if !security.password_score_valid(data.password_score) {
+            return Err(Error::as_validation("password_score", "password_too_weak"));
+        }
+
         let mut active_model = data.into_active_model()?;
 
         // Validation guarantees the OPAQUE registration upload; finish it into
         // the password file that login start/finish verifies against, so the
         // account authenticates without a password ever reaching the server.
-        let upload = opaque_upload.ok_or_else(|| {
-            Error::as_validation("opaque_registration_upload", "required")
-        })?;
+        let upload = opaque_upload
+            .ok_or_else(|| Error::as_validation("opaque_registration_upload", "required"))?;
         let password_file = cryptfns::opaque::server_registration_finish(&upload)
             .map_err(|_| Error::as_validation("opaque_registration_upload", "invalid"))?;
         active_model.opaque_password_file = ActiveValue::Set(Some(password_file));
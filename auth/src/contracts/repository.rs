@@ -144,7 +144,11 @@ where
         // always Some so we can unwrap it safely
         let (session, user) = (result.0, result.1.unwrap());
 
-        Ok(Authenticated { user, session })
+        Ok(Authenticated {
+            user,
+            session,
+            enrollment: None,
+        })
     }
 
     /// Get user and session by refresh token, session must be valid
@@ -162,7 +166,11 @@ where
         // always Some so we can unwrap it safely
         let (session, user) = (result.0, result.1.unwrap());
 
-        Ok(Authenticated { user, session })
+        Ok(Authenticated {
+            user,
+            session,
+            enrollment: None,
+        })
     }
 
     /// Get user and session by device id, session must be valid
@@ -181,7 +189,11 @@ where
         // always Some so we can unwrap it safely
         let (session, user) = (result.0, result.1.unwrap());
 
-        Ok(Authenticated { user, session })
+        Ok(Authenticated {
+            user,
+            session,
+            enrollment: None,
+        })
     }
 
     /// Create a new user
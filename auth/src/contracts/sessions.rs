@@ -1,20 +1,31 @@
 use chrono::{Duration, Utc};
+use context::DatabaseConnection;
 use entity::{
-    sessions, users, ActiveModelTrait, ActiveValue, ColumnTrait, EntityTrait, QueryFilter, Uuid,
+    sessions, users, ActiveModelTrait, ActiveValue, ColumnTrait, EntityTrait, QueryFilter,
+    QuerySelect, Uuid,
 };
 use error::{AppResult, Error};
 
-use crate::data::authenticated::Authenticated;
+use crate::{
+    actions::UserActions,
+    data::{authenticated::Authenticated, device::Device},
+};
 
-use super::{ctx::Ctx, repository::Repository};
+use super::{
+    ctx::Ctx,
+    email::{Email, KILL_SESSIONS_ACTION_NAME},
+    repository::Repository,
+};
 
 /// Session management contract
 #[async_trait::async_trait]
 pub(crate) trait Sessions
 where
-    Self: Ctx + Repository,
+    Self: Ctx + Repository + Email,
 {
-    /// Generate a new session for a user
+    /// Generate a new session for a user. A session from a device and
+    /// address the user has not signed in from before is announced to them
+    /// by email, unless they opted out.
     async fn generate(
         &self,
         user: &users::Model,
@@ -24,6 +35,9 @@ where
         let expires_at = Utc::now()
             + Duration::seconds(self.ctx().config.auth.short_term_session_duration_seconds);
 
+        let new_device =
+            user.login_alerts_enabled && self.is_new_device(user, user_agent, ip).await?;
+
         let id = entity::Uuid::new_v4();
 
         let active_model = sessions::ActiveModel {
@@ -42,15 +56,72 @@ where
             .exec_without_returning(self.connection())
             .await?;
 
-        let result = sessions::Entity::find_by_id(id)
+        let session = sessions::Entity::find_by_id(id)
             .one(self.connection())
+            .await?
+            .ok_or(Error::NotFound("session_not_found".to_string()))?;
+
+        // The user is already in; a mail outage must not undo that.
+        if new_device {
+            if let Err(e) = self.email_new_device(user, &session).await {
+                log::error!("Failed to send new device notification: {e}");
+            }
+        }
+
+        Ok(session)
+    }
+
+    /// Whether the user has sessions, but none from this [`Device`]: the
+    /// same browser and system on the same network. With no sessions on
+    /// record, right after registering or once the old ones were purged,
+    /// there is nothing to compare with.
+    async fn is_new_device(
+        &self,
+        user: &users::Model,
+        user_agent: &str,
+        ip: &str,
+    ) -> AppResult<bool> {
+        let seen = sessions::Entity::find()
+            .select_only()
+            .column(sessions::Column::UserAgent)
+            .column(sessions::Column::Ip)
+            .filter(sessions::Column::UserId.eq(user.id))
+            .into_tuple::<(String, String)>()
+            .all(self.connection())
             .await?;
 
-        result.ok_or(Error::NotFound("session_not_found".to_string()))
+        if seen.is_empty() {
+            return Ok(false);
+        }
+
+        let device = Device::new(user_agent, ip);
+
+        Ok(!seen
+            .iter()
+            .any(|(user_agent, ip)| Device::new(user_agent, ip) == device))
     }
 
     /// Refresh session, if it's not expired. Refreshing a session will extend the expiration date by N minutes.
+    /// A session older than the maximum lifetime in the security settings is ended instead,
+    /// and so is one that outlived its sign-in at the OpenID Connect provider.
     async fn refresh(&self, session: &sessions::Model) -> AppResult<Authenticated> {
+        let now = Utc::now().timestamp();
+
+        let security = self.ctx().settings.inner().await.security.clone();
+        if security.session_outlived(session.created_at, now) {
+            self.destroy(session).await?;
+
+            return Err(Error::Unauthorized("session_lifetime_exceeded".to_string()));
+        }
+
+        if let Some(oidc) = self.ctx().config.oidc.as_ref() {
+            if oidc.session_outlived(session.created_at, now) {
+                self.destroy(session).await?;
+
+                return Err(Error::Unauthorized("oidc_login_required".to_string()));
+            }
+        }
+
         let expires_at = Utc::now().naive_utc()
             + Duration::seconds(self.ctx().config.auth.short_term_session_duration_seconds);
 
@@ -110,6 +181,36 @@ where
         Ok(())
     }
 
+    /// Follow the "this wasn't me" link of a new device alert: end every
+    /// session of the user. Links older than the longest a session can live
+    /// have nothing left to end and are refused.
+    async fn destroy_all_by_action(&self, user_action_id: Uuid) -> AppResult<()> {
+        let user_actions = UserActions::<DatabaseConnection>::new(&self.ctx().db);
+        let (action, user) = user_actions.get_by_id(user_action_id).await?;
+
+        if action.action != KILL_SESSIONS_ACTION_NAME {
+            return Err(Error::as_not_found("wrong_user_action"));
+        }
+
+        let max_age = self.ctx().config.auth.long_term_session_duration_days * 24 * 60 * 60;
+        if action.created_at + max_age < Utc::now().timestamp() {
+            user_actions.delete(action.id).await?;
+
+            return Err(Error::as_not_found("user_action_not_found_or_executed"));
+        }
+
+        self.destroy_all(Uuid::nil(), user.id).await?;
+
+        // Every alert sent so far points at sessions that are now gone.
+        entity::user_actions::Entity::delete_many()
+            .filter(entity::user_actions::Column::UserId.eq(user.id))
+            .filter(entity::user_actions::Column::Action.eq(KILL_SESSIONS_ACTION_NAME))
+            .exec(self.connection())
+            .await?;
+
+        Ok(())
+    }
+
     /// Find session by its id
     async fn get(&self, id: Uuid, user_id: Uuid) -> AppResult<sessions::Model> {
         let session = sessions::Entity::find_by_id(id)
@@ -0,0 +1,196 @@
+use chrono::Utc;
+use entity::{
+    jwt_signing_keys, ActiveValue, ColumnTrait, Condition, EntityTrait, QueryFilter, QueryOrder,
+    Uuid,
+};
+use error::{AppResult, Error};
+use jsonwebtoken::jwk::{Jwk, JwkSet};
+use serde_json::json;
+
+use super::repository::Repository;
+
+/// How long the in-memory copy of the keys is trusted before it is read
+/// again, which is how a rotation made by `hoodik rotate-jwt-key` reaches a
+/// running server.
+const CACHE_SECONDS: i64 = 60;
+
+/// Least time between two reloads forced by a token naming a key the cache
+/// does not know.
+const FORCED_RELOAD_SECONDS: i64 = 5;
+
+/// Keys signing session and transfer tokens, kept in the database so they
+/// outlive restarts. The newest unretired key signs; a rotated-out key keeps
+/// verifying until every token it could have signed has expired.
+#[async_trait::async_trait]
+pub(crate) trait SigningKeys
+where
+    Self: Repository,
+{
+    /// How long a retired key keeps verifying: the longest a token it signed
+    /// can still be presented, either as a transfer token or to refresh a
+    /// session.
+    fn retired_key_lifetime(&self) -> i64 {
+        self.ctx().config.auth.long_term_session_duration_days * 86_400
+    }
+
+    /// Every key that still verifies, newest first. The first one signs.
+    async fn signing_keys(&self) -> AppResult<Vec<jwt_signing_keys::Model>> {
+        let now = Utc::now().timestamp();
+
+        if let Some(keys) = self.ctx().jwt_keys.fresh(now, CACHE_SECONDS) {
+            if keys.first().is_some_and(|k| k.retired_at.is_none()) {
+                return Ok(keys);
+            }
+        }
+
+        self.reload_signing_keys(now).await
+    }
+
+    /// Like [`Self::signing_keys`], but a `kid` missing from the cache makes
+    /// it read the keys again, so a key rotated in from another process
+    /// verifies right away instead of once the cache goes stale.
+    async fn signing_keys_for(&self, kids: &[String]) -> AppResult<Vec<jwt_signing_keys::Model>> {
+        let keys = self.signing_keys().await?;
+        let known = |kid: &String| keys.iter().any(|k| k.id.to_string() == *kid);
+
+        if kids.iter().all(known) {
+            return Ok(keys);
+        }
+
+        let now = Utc::now().timestamp();
+
+        if !self.ctx().jwt_keys.force(now, FORCED_RELOAD_SECONDS) {
+            return Ok(keys);
+        }
+
+        self.reload_signing_keys(now).await
+    }
+
+    /// Read the keys into the cache, creating one to sign with if none is.
+    async fn reload_signing_keys(&self, now: i64) -> AppResult<Vec<jwt_signing_keys::Model>> {
+        let mut keys = self.load_signing_keys(now).await?;
+
+        if keys.first().is_none_or(|k| k.retired_at.is_some()) {
+            self.create_signing_key(now).await?;
+            keys = self.load_signing_keys(now).await?;
+        }
+
+        self.ctx().jwt_keys.set(now, keys.clone());
+
+        Ok(keys)
+    }
+
+    /// The key new tokens are signed with.
+    async fn current_signing_key(&self) -> AppResult<jwt_signing_keys::Model> {
+        self.signing_keys()
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::InternalError("no_signing_key".to_string()))
+    }
+
+    /// Start signing with a new key and retire the current one, dropping keys
+    /// retired long enough ago that nothing they signed is still valid.
+    async fn rotate_signing_key(&self) -> AppResult<jwt_signing_keys::Model> {
+        let now = Utc::now().timestamp();
+
+        jwt_signing_keys::Entity::update_many()
+            .col_expr(
+                jwt_signing_keys::Column::RetiredAt,
+                entity::Expr::value(now),
+            )
+            .filter(jwt_signing_keys::Column::RetiredAt.is_null())
+            .exec(self.connection())
+            .await?;
+
+        jwt_signing_keys::Entity::delete_many()
+            .filter(jwt_signing_keys::Column::RetiredAt.lt(now - self.retired_key_lifetime()))
+            .exec(self.connection())
+            .await?;
+
+        let key = self.create_signing_key(now).await?;
+        let keys = self.load_signing_keys(now).await?;
+        self.ctx().jwt_keys.set(now, keys);
+
+        Ok(key)
+    }
+
+    /// The public halves of every key that still verifies.
+    async fn jwks(&self) -> AppResult<JwkSet> {
+        let keys = self
+            .signing_keys()
+            .await?
+            .iter()
+            .map(|key| serde_json::from_str::<Jwk>(&key.jwk).map_err(Error::from))
+            .collect::<AppResult<Vec<_>>>()?;
+
+        Ok(JwkSet { keys })
+    }
+
+    async fn load_signing_keys(&self, now: i64) -> AppResult<Vec<jwt_signing_keys::Model>> {
+        jwt_signing_keys::Entity::find()
+            .filter(
+                Condition::any()
+                    .add(jwt_signing_keys::Column::RetiredAt.is_null())
+                    .add(
+                        jwt_signing_keys::Column::RetiredAt.gte(now - self.retired_key_lifetime()),
+                    ),
+            )
+            // Unretired keys first, so the newest of them is the one signing.
+            .order_by_asc(jwt_signing_keys::Column::RetiredAt.is_not_null())
+            .order_by_desc(jwt_signing_keys::Column::CreatedAt)
+            .all(self.connection())
+            .await
+            .map_err(Error::from)
+    }
+
+    /// Generate a key with the configured algorithm and store it.
+    async fn create_signing_key(&self, now: i64) -> AppResult<jwt_signing_keys::Model> {
+        let id = Uuid::new_v4();
+        let algorithm = self.ctx().config.auth.jwt_algorithm.clone();
+
+        let (pair, jwk) = match algorithm.as_str() {
+            "ES256" => {
+                let pair = cryptfns::jws::generate_es256()?;
+                let jwk = json!({
+                    "kty": "EC",
+                    "crv": "P-256",
+                    "x": pair.x,
+                    "y": pair.y,
+                    "kid": id.to_string(),
+                    "alg": "ES256",
+                    "use": "sig",
+                });
+
+                (pair, jwk)
+            }
+            _ => {
+                let pair = cryptfns::jws::generate_ed25519()?;
+                let jwk = json!({
+                    "kty": "OKP",
+                    "crv": "Ed25519",
+                    "x": pair.x,
+                    "kid": id.to_string(),
+                    "alg": "EdDSA",
+                    "use": "sig",
+                });
+
+                (pair, jwk)
+            }
+        };
+
+        let key = jwt_signing_keys::ActiveModel {
+            id: ActiveValue::Set(id),
+            algorithm: ActiveValue::Set(jwk["alg"].as_str().unwrap_or_default().to_string()),
+            private_key: ActiveValue::Set(pair.private_key),
+            jwk: ActiveValue::Set(jwk.to_string()),
+            created_at: ActiveValue::Set(now),
+            retired_at: ActiveValue::Set(None),
+        };
+
+        jwt_signing_keys::Entity::insert(key)
+            .exec_with_returning(self.connection())
+            .await
+            .map_err(Error::from)
+    }
+}
@@ -0,0 +1,387 @@
+use chrono::{Duration, Utc};
+use cryptfns::webauthn::RelyingParty;
+use entity::{
+    users, webauthn_challenges, webauthn_credentials, ActiveValue, ColumnTrait, EntityTrait,
+    QueryFilter, QueryOrder, Uuid,
+};
+use error::{AppResult, Error};
+
+use crate::data::webauthn::{
+    AssertionChallenge, AssertionResponse, CreationOptions, CredentialDescriptor,
+    CredentialParameters, DeleteCredential, LoginChallenge, RegisterFinish, RegistrationChallenge,
+    RelyingPartyEntity, RequestOptions, UserEntity,
+};
+
+use super::repository::Repository;
+
+/// How long the browser lets the user take to touch the key, in milliseconds.
+const CEREMONY_TIMEOUT_MS: u32 = 120_000;
+
+/// How long an issued challenge stays valid: the browser timeout plus some
+/// slack for the round trips around it.
+const CHALLENGE_TTL_SECONDS: i64 = 180;
+
+/// Label for a key registered without a name.
+const DEFAULT_CREDENTIAL_NAME: &str = "Security key";
+
+const MAX_CREDENTIAL_NAME_LENGTH: usize = 64;
+
+/// Security keys (WebAuthn credentials) as a second factor next to TOTP. A
+/// user may enroll several; any one of them passes the login, which asks for
+/// it from [`super::opaque::Opaque::opaque_login_finish`].
+#[async_trait::async_trait]
+pub(crate) trait WebAuthn
+where
+    Self: Repository,
+{
+    fn relying_party(&self) -> RelyingParty<'_> {
+        let config = &self.ctx().config.auth;
+
+        RelyingParty {
+            id: &config.webauthn_rp_id,
+            origin: &config.webauthn_origin,
+        }
+    }
+
+    /// Every key the user enrolled, oldest first.
+    async fn webauthn_credentials(
+        &self,
+        user_id: Uuid,
+    ) -> AppResult<Vec<webauthn_credentials::Model>> {
+        webauthn_credentials::Entity::find()
+            .filter(webauthn_credentials::Column::UserId.eq(user_id))
+            .order_by_asc(webauthn_credentials::Column::CreatedAt)
+            .all(self.connection())
+            .await
+            .map_err(Error::from)
+    }
+
+    /// Record a fresh challenge for the user's next ceremony and return its id
+    /// and bytes.
+    async fn webauthn_issue_challenge(
+        &self,
+        user_id: Uuid,
+        ceremony: &str,
+    ) -> AppResult<(Uuid, Vec<u8>)> {
+        // Abandoned ceremonies would otherwise accumulate forever.
+        webauthn_challenges::Entity::delete_many()
+            .filter(webauthn_challenges::Column::ExpiresAt.lt(Utc::now().timestamp()))
+            .exec(self.connection())
+            .await?;
+
+        let id = Uuid::new_v4();
+        let challenge = cryptfns::webauthn::challenge();
+
+        webauthn_challenges::Entity::insert(webauthn_challenges::ActiveModel {
+            id: ActiveValue::Set(id),
+            user_id: ActiveValue::Set(user_id),
+            ceremony: ActiveValue::Set(ceremony.to_string()),
+            challenge: ActiveValue::Set(cryptfns::base64::encode_url(&challenge)),
+            expires_at: ActiveValue::Set(
+                (Utc::now() + Duration::seconds(CHALLENGE_TTL_SECONDS)).timestamp(),
+            ),
+        })
+        .exec_without_returning(self.connection())
+        .await?;
+
+        Ok((id, challenge))
+    }
+
+    /// Consume a challenge issued for `ceremony`. It is single-use whatever
+    /// the outcome, like an OPAQUE login state.
+    async fn webauthn_take_challenge(
+        &self,
+        id: Uuid,
+        ceremony: &str,
+    ) -> AppResult<webauthn_challenges::Model> {
+        let challenge = webauthn_challenges::Entity::find_by_id(id)
+            .one(self.connection())
+            .await?
+            .ok_or_else(|| Error::Unauthorized("invalid_webauthn_challenge".to_string()))?;
+
+        webauthn_challenges::Entity::delete_by_id(id)
+            .exec(self.connection())
+            .await?;
+
+        if challenge.ceremony != ceremony {
+            return Err(Error::Unauthorized(
+                "invalid_webauthn_challenge".to_string(),
+            ));
+        }
+
+        if challenge.expires_at < Utc::now().timestamp() {
+            return Err(Error::Unauthorized(
+                "webauthn_challenge_expired".to_string(),
+            ));
+        }
+
+        Ok(challenge)
+    }
+
+    async fn webauthn_request_options(
+        &self,
+        user_id: Uuid,
+        challenge: &[u8],
+    ) -> AppResult<RequestOptions> {
+        let allow_credentials = self
+            .webauthn_credentials(user_id)
+            .await?
+            .into_iter()
+            .map(|credential| CredentialDescriptor::new(credential.credential_id))
+            .collect();
+
+        Ok(RequestOptions {
+            challenge: cryptfns::base64::encode_url(challenge),
+            rp_id: self.relying_party().id.to_string(),
+            allow_credentials,
+            timeout: CEREMONY_TIMEOUT_MS,
+            user_verification: "discouraged",
+        })
+    }
+
+    /// Begin enrolling a key. Only accounts that log in over OPAQUE can: the
+    /// legacy password login has no second round trip to ask for the key in.
+    async fn webauthn_register_start(&self, user_id: Uuid) -> AppResult<RegistrationChallenge> {
+        let user = self.get_by_id(user_id).await?;
+
+        if user.opaque_password_file.is_none() {
+            return Err(Error::BadRequest("webauthn_requires_migration".to_string()));
+        }
+
+        let (registration_id, challenge) = self
+            .webauthn_issue_challenge(user.id, webauthn_challenges::Model::REGISTER)
+            .await?;
+
+        let exclude_credentials = self
+            .webauthn_credentials(user.id)
+            .await?
+            .into_iter()
+            .map(|credential| CredentialDescriptor::new(credential.credential_id))
+            .collect();
+
+        let rp = self.relying_party();
+
+        Ok(RegistrationChallenge {
+            registration_id,
+            public_key: CreationOptions {
+                challenge: cryptfns::base64::encode_url(&challenge),
+                rp: RelyingPartyEntity {
+                    id: rp.id.to_string(),
+                    name: self.ctx().config.app.name.clone(),
+                },
+                user: UserEntity {
+                    id: cryptfns::base64::encode_url(user.id.as_bytes()),
+                    name: user.email.clone(),
+                    display_name: user.email.clone(),
+                },
+                pub_key_cred_params: cryptfns::webauthn::SUPPORTED_ALGORITHMS
+                    .iter()
+                    .map(|alg| CredentialParameters {
+                        kind: "public-key",
+                        alg: *alg,
+                    })
+                    .collect(),
+                timeout: CEREMONY_TIMEOUT_MS,
+                exclude_credentials,
+                attestation: "none",
+            },
+        })
+    }
+
+    /// Finish enrolling a key: check the authenticator answered our challenge
+    /// for this relying party, and store its public key.
+    async fn webauthn_register_finish(
+        &self,
+        user_id: Uuid,
+        data: RegisterFinish,
+    ) -> AppResult<webauthn_credentials::Model> {
+        let user = self.get_by_id(user_id).await?;
+
+        if !user.verify_tfa(data.token) {
+            return Err(Error::Unauthorized("invalid_otp_token".to_string()));
+        }
+
+        let challenge = self
+            .webauthn_take_challenge(data.registration_id, webauthn_challenges::Model::REGISTER)
+            .await?;
+
+        if challenge.user_id != user.id {
+            return Err(Error::Unauthorized(
+                "invalid_webauthn_challenge".to_string(),
+            ));
+        }
+
+        let registration = cryptfns::webauthn::verify_registration(
+            self.relying_party(),
+            &decode(&challenge.challenge)?,
+            &decode(&data.response.client_data_json)?,
+            &decode(&data.response.attestation_object)?,
+        )
+        .map_err(|e| Error::BadRequest(ceremony_error(e)))?;
+
+        let credential_id = cryptfns::base64::encode_url(&registration.credential_id);
+        let existing = webauthn_credentials::Entity::find()
+            .filter(webauthn_credentials::Column::CredentialId.eq(credential_id.as_str()))
+            .one(self.connection())
+            .await?;
+        if existing.is_some() {
+            return Err(Error::Conflict("webauthn_credential_exists".to_string()));
+        }
+
+        let name = data
+            .name
+            .as_deref()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .unwrap_or(DEFAULT_CREDENTIAL_NAME)
+            .chars()
+            .take(MAX_CREDENTIAL_NAME_LENGTH)
+            .collect();
+
+        let credential = webauthn_credentials::Model {
+            id: Uuid::new_v4(),
+            user_id: user.id,
+            credential_id,
+            public_key: cryptfns::base64::encode_url(&registration.public_key),
+            sign_count: registration.sign_count as i64,
+            name,
+            created_at: Utc::now().timestamp(),
+            last_used_at: None,
+        };
+
+        webauthn_credentials::Entity::insert(webauthn_credentials::ActiveModel::from(
+            credential.clone(),
+        ))
+        .exec_without_returning(self.connection())
+        .await?;
+
+        Ok(credential)
+    }
+
+    /// Begin a confirmation an authenticated user signs with one of their
+    /// keys, before a change to the keys themselves.
+    async fn webauthn_assertion_start(&self, user_id: Uuid) -> AppResult<AssertionChallenge> {
+        if self.webauthn_credentials(user_id).await?.is_empty() {
+            return Err(Error::BadRequest("webauthn_not_enrolled".to_string()));
+        }
+
+        let (challenge_id, challenge) = self
+            .webauthn_issue_challenge(user_id, webauthn_challenges::Model::CONFIRM)
+            .await?;
+
+        Ok(AssertionChallenge {
+            challenge_id,
+            public_key: self.webauthn_request_options(user_id, &challenge).await?,
+        })
+    }
+
+    /// Verify an assertion over `challenge` made with one of the user's keys,
+    /// and move that key's signature counter forward.
+    async fn webauthn_verify(
+        &self,
+        challenge: &webauthn_challenges::Model,
+        response: &AssertionResponse,
+    ) -> AppResult<()> {
+        let credential = webauthn_credentials::Entity::find()
+            .filter(webauthn_credentials::Column::UserId.eq(challenge.user_id))
+            .filter(webauthn_credentials::Column::CredentialId.eq(response.credential_id.as_str()))
+            .one(self.connection())
+            .await?
+            .ok_or_else(|| Error::Unauthorized("unknown_webauthn_credential".to_string()))?;
+
+        let sign_count = cryptfns::webauthn::verify_assertion(
+            self.relying_party(),
+            &decode(&challenge.challenge)?,
+            &decode(&credential.public_key)?,
+            credential.sign_count as u32,
+            &decode(&response.client_data_json)?,
+            &decode(&response.authenticator_data)?,
+            &decode(&response.signature)?,
+        )
+        .map_err(|e| Error::Unauthorized(ceremony_error(e)))?;
+
+        webauthn_credentials::Entity::update(webauthn_credentials::ActiveModel {
+            id: ActiveValue::Unchanged(credential.id),
+            sign_count: ActiveValue::Set(sign_count as i64),
+            last_used_at: ActiveValue::Set(Some(Utc::now().timestamp())),
+            ..Default::default()
+        })
+        .exec(self.connection())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Remove one of the user's keys, proven with another factor (see
+    /// [`DeleteCredential`]).
+    async fn webauthn_delete(
+        &self,
+        user_id: Uuid,
+        id: Uuid,
+        data: DeleteCredential,
+    ) -> AppResult<()> {
+        let user = self.get_by_id(user_id).await?;
+
+        let credential = webauthn_credentials::Entity::find_by_id(id)
+            .filter(webauthn_credentials::Column::UserId.eq(user.id))
+            .one(self.connection())
+            .await?
+            .ok_or_else(|| Error::NotFound("webauthn_credential_not_found".to_string()))?;
+
+        match (data.challenge_id, data.response.as_ref()) {
+            (Some(challenge_id), Some(response)) => {
+                let challenge = self
+                    .webauthn_take_challenge(challenge_id, webauthn_challenges::Model::CONFIRM)
+                    .await?;
+
+                if challenge.user_id != user.id {
+                    return Err(Error::Unauthorized(
+                        "invalid_webauthn_challenge".to_string(),
+                    ));
+                }
+
+                self.webauthn_verify(&challenge, response).await?;
+            }
+            _ if user.secret.is_some() && user.verify_tfa(data.token) => {}
+            _ => return Err(Error::Unauthorized("second_factor_required".to_string())),
+        }
+
+        webauthn_credentials::Entity::delete_by_id(credential.id)
+            .exec(self.connection())
+            .await?;
+
+        Ok(())
+    }
+
+    /// The challenge for the second step of a login, or `None` when the user
+    /// has no keys enrolled.
+    async fn webauthn_login_challenge(
+        &self,
+        user: &users::Model,
+    ) -> AppResult<Option<LoginChallenge>> {
+        if self.webauthn_credentials(user.id).await?.is_empty() {
+            return Ok(None);
+        }
+
+        let (login_id, challenge) = self
+            .webauthn_issue_challenge(user.id, webauthn_challenges::Model::LOGIN)
+            .await?;
+
+        Ok(Some(LoginChallenge {
+            login_id,
+            public_key: self.webauthn_request_options(user.id, &challenge).await?,
+        }))
+    }
+}
+
+fn decode(input: &str) -> AppResult<Vec<u8>> {
+    cryptfns::base64::decode_url(input)
+        .map_err(|_| Error::BadRequest("invalid_webauthn_response".to_string()))
+}
+
+fn ceremony_error(error: cryptfns::error::Error) -> String {
+    match error {
+        cryptfns::error::Error::WebAuthn(reason) => format!("webauthn_{reason}"),
+        _ => "invalid_webauthn_response".to_string(),
+    }
+}
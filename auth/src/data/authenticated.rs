@@ -9,14 +9,23 @@ use futures_util::Future;
 use serde::{Deserialize, Serialize};
 use std::pin::Pin;
 
-use crate::{auth::Auth, contracts::repository::Repository};
+use crate::{
+    auth::Auth,
+    contracts::{policy::Policy, repository::Repository},
+};
 
-use super::claims::Claims;
+use super::{
+    claims::Claims,
+    enrollment::{self, Enrollment},
+};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Authenticated {
     pub user: users::Model,
     pub session: sessions::Model,
+    /// What the security policy still asks of the user, when anything
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enrollment: Option<Enrollment>,
 }
 
 impl Authenticated {
@@ -43,24 +52,21 @@ impl FromRequest for Authenticated {
             }
         };
 
-        let claims = match Claims::try_from(req) {
-            Ok(c) => c,
-            Err(e) => return Box::pin(async { Err(e) }),
-        };
+        let req = req.clone();
+
+        Box::pin(async move {
+            super::claims::load_signing_keys(&req).await?;
 
-        if claims.is_expired() {
-            return Box::pin(async {
-                Err(Error::Unauthorized(
+            let claims = Claims::try_from(&req)?;
+
+            if claims.is_expired() {
+                return Err(Error::Unauthorized(
                     "auth::data::authenticated|claims_expired".to_string(),
-                ))
-            });
-        }
+                ));
+            }
 
-        Box::pin(async move {
-            let authenticated = match Auth::new(&context).get_by_device_id(claims.device).await {
-                Ok(a) => a,
-                Err(e) => return Err(e),
-            };
+            let auth = Auth::new(&context);
+            let mut authenticated = auth.get_by_device_id(claims.device).await?;
 
             if authenticated.is_expired() {
                 return Err(Error::Unauthorized(
@@ -68,6 +74,16 @@ impl FromRequest for Authenticated {
                 ));
             }
 
+            authenticated.enrollment = auth.enrollment(&authenticated.user).await?;
+
+            let enforced = authenticated
+                .enrollment
+                .as_ref()
+                .is_some_and(|enrollment| enrollment.is_enforced());
+            if enforced && !enrollment::allowed_for(&req) {
+                return Err(Error::EnrollmentRequired("enrollment_required".to_string()));
+            }
+
             Ok(authenticated)
         })
     }
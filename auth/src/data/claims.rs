@@ -3,13 +3,26 @@
 //! either in the header or cookie.
 use actix_web::{web, FromRequest, HttpRequest};
 use context::Context;
-use entity::Uuid;
-use error::Error;
+use entity::{links, users, EntityTrait, Uuid};
+use error::{AppResult, Error};
 use futures_util::Future;
 use serde::{Deserialize, Serialize};
 use std::pin::Pin;
 
-use super::{authenticated::Authenticated, extractor::Extractor};
+use crate::{
+    auth::Auth,
+    contracts::{
+        personal_access_tokens::PersonalAccessTokens, repository::Repository,
+        signing_keys::SigningKeys,
+    },
+};
+
+use super::{
+    authenticated::Authenticated,
+    enrollment,
+    extractor::Extractor,
+    personal_access_token::{self, Grant, Target},
+};
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Claims {
@@ -27,6 +40,14 @@ pub struct Claims {
     pub role: Option<String>,
     /// User quota for the storage
     pub quota: Option<i64>,
+    /// Set when the request was made with a personal access token rather
+    /// than a session
+    #[serde(skip)]
+    pub grant: Option<Grant>,
+    /// Set when the security policy confines the session to enrollment,
+    /// see [`super::enrollment`]
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub enroll: bool,
 }
 
 impl From<&Authenticated> for Claims {
@@ -39,6 +60,11 @@ impl From<&Authenticated> for Claims {
             device: authenticated.session.device_id,
             role: authenticated.user.role.clone(),
             quota: authenticated.user.quota,
+            grant: None,
+            enroll: authenticated
+                .enrollment
+                .as_ref()
+                .is_some_and(|enrollment| enrollment.is_enforced()),
         }
     }
 }
@@ -58,6 +84,31 @@ impl Claims {
         !self.is_expired()
     }
 
+    /// Load the user the claims belong to.
+    pub async fn user(&self, context: &Context) -> AppResult<users::Model> {
+        Auth::new(context).get_by_id(self.sub).await
+    }
+
+    /// For a personal access token confined to a folder, fail unless
+    /// `file_id` is that folder or lies under it; `None` stands for the root.
+    /// Sessions and unrestricted tokens always pass.
+    pub async fn require_folder(&self, context: &Context, file_id: Option<Uuid>) -> AppResult<()> {
+        let Some(folder_id) = self.grant.as_ref().and_then(|g| g.folder_id) else {
+            return Ok(());
+        };
+
+        let inside = match file_id {
+            Some(file_id) => Auth::new(context).is_in_folder(folder_id, file_id).await?,
+            None => false,
+        };
+
+        if !inside {
+            return Err(Error::Forbidden("token_folder_restricted".to_string()));
+        }
+
+        Ok(())
+    }
+
     pub async fn get_quota(&self, context: &Context) -> Option<u64> {
         match self.quota {
             Some(v) => Some(v as u64),
@@ -83,14 +134,11 @@ impl TryFrom<&HttpRequest> for Claims {
             }
         };
 
-        Extractor::default()
-            .jwt(context)
-            .req(req)
-            .map_err(|e| {
-                log::debug!("auth::data::claims|no_jwt: {:?}", e);
+        Extractor::default().jwt(context).req(req).map_err(|e| {
+            log::debug!("auth::data::claims|no_jwt: {:?}", e);
 
-                Error::Unauthorized("auth::data::claims|no_claims".to_string())
-            })
+            Error::Unauthorized("auth::data::claims|no_claims".to_string())
+        })
     }
 }
 
@@ -99,23 +147,114 @@ impl FromRequest for Claims {
     type Future = Pin<Box<dyn Future<Output = Result<Self, Error>>>>;
 
     fn from_request(req: &actix_web::HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
-        let claims = match Claims::try_from(req) {
-            Ok(c) => c,
-            Err(e) => return Box::pin(async { Err(e) }),
-        };
+        if let Some(token) = personal_access_token(req) {
+            let token = token.to_string();
+            let req = req.clone();
 
-        if claims.is_expired() {
-            return Box::pin(async {
-                Err(Error::Unauthorized(
-                    "auth::data::claims|expired".to_string(),
-                ))
-            });
+            return Box::pin(async move { from_personal_access_token(&req, &token).await });
         }
 
-        Box::pin(async move { Ok(claims) })
+        let req = req.clone();
+
+        Box::pin(async move {
+            load_signing_keys(&req).await?;
+
+            let claims = Claims::try_from(&req)?;
+
+            if claims.is_expired() {
+                return Err(Error::Unauthorized(
+                    "auth::data::claims|expired".to_string(),
+                ));
+            }
+
+            if claims.enroll && !enrollment::allowed_for(&req) {
+                return Err(Error::EnrollmentRequired("enrollment_required".to_string()));
+            }
+
+            Ok(claims)
+        })
     }
 
     fn extract(req: &actix_web::HttpRequest) -> Self::Future {
         Self::from_request(req, &mut actix_web::dev::Payload::None)
     }
 }
+
+/// Make sure the signing keys are cached on the context, since verifying a
+/// JWT is synchronous and only looks at the cache. The keys are read again
+/// when the request carries a token signed by a key the cache lacks.
+pub(crate) async fn load_signing_keys(req: &HttpRequest) -> AppResult<()> {
+    let context = req
+        .app_data::<web::Data<Context>>()
+        .ok_or_else(|| Error::Unauthorized("auth::data::claims|no_context".to_string()))?;
+
+    let kids = Extractor::default().jwt(context).kids(req);
+
+    Auth::new(context).signing_keys_for(&kids).await?;
+
+    Ok(())
+}
+
+/// The personal access token in the `Authorization` header, if that is what
+/// the request carries. Tokens are always sent as a header, whether sessions
+/// use cookies or not.
+fn personal_access_token(req: &HttpRequest) -> Option<&str> {
+    req.headers()
+        .get("Authorization")?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .filter(|token| token.starts_with(personal_access_token::PREFIX))
+}
+
+/// Resolve the token and hold it to the scope and folder the matched route
+/// asks for.
+async fn from_personal_access_token(req: &HttpRequest, token: &str) -> AppResult<Claims> {
+    let context = req
+        .app_data::<web::Data<Context>>()
+        .ok_or_else(|| Error::Unauthorized("auth::data::claims|no_context".to_string()))?;
+
+    let claims = Auth::new(context)
+        .personal_access_token_claims(token)
+        .await?;
+    let grant = claims
+        .grant
+        .as_ref()
+        .ok_or_else(|| Error::Unauthorized("invalid_token".to_string()))?;
+
+    let (scope, target) = personal_access_token::rule_for(req)
+        .ok_or_else(|| Error::Forbidden("token_not_allowed".to_string()))?;
+
+    if !grant.scopes.contains(&scope) {
+        return Err(Error::Forbidden(format!(
+            "token_scope_missing:{}",
+            scope.as_str()
+        )));
+    }
+
+    if grant.folder_id.is_none() {
+        return Ok(claims);
+    }
+
+    let file_id = match target {
+        Target::File(name) => Some(util::actix::path_var::<Uuid>(req, name)?),
+        Target::Folder(name) => util::actix::query_var::<Uuid>(req, name).ok(),
+        Target::Link(name) => {
+            let link_id = util::actix::path_var::<Uuid>(req, name)?;
+            let link = links::Entity::find_by_id(link_id)
+                .one(&context.db)
+                .await?
+                .ok_or_else(|| Error::NotFound("link_not_found".to_string()))?;
+
+            Some(link.file_id)
+        }
+        Target::Body | Target::Unbound => return Ok(claims),
+        Target::Account => {
+            return Err(Error::Forbidden("token_folder_restricted".to_string()));
+        }
+    };
+
+    claims.require_folder(context, file_id).await?;
+
+    Ok(claims)
+}
@@ -21,6 +21,9 @@ pub struct CreateUser {
     /// file. A brand-new account authenticates only via OPAQUE, so this is
     /// required and a plaintext-`password` field is refused.
     pub opaque_registration_upload: Option<String>,
+    /// zxcvbn score (0-4) the client computed for the password, held to the
+    /// minimum in the security settings.
+    pub password_score: Option<u8>,
     pub invitation_id: Option<Uuid>,
 }
 
@@ -50,6 +53,11 @@ impl Validation for CreateUser {
             rule_required!(wrapping_pubkey),
             rule_required!(encrypted_private_key),
             rule_required!(opaque_registration_upload),
+            Rule::new("password_score", |obj: &Self, error| {
+                if obj.password_score.is_some_and(|s| s > 4) {
+                    error.add("out_of_range");
+                }
+            }),
             Rule::new("secret", |obj: &Self, error| {
                 if let Some(v) = &obj.secret {
                     if !validate_otp(v, obj.token.as_ref()) {
@@ -140,6 +148,8 @@ impl CreateUser {
             created_at: ActiveValue::Set(Utc::now().timestamp()),
             updated_at: ActiveValue::Set(Utc::now().timestamp()),
             share_notifications_enabled: ActiveValue::Set(true),
+            login_alerts_enabled: ActiveValue::Set(true),
+            password_score: ActiveValue::Set(data.password_score.map(i16::from)),
         })
     }
 }
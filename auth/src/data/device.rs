@@ -0,0 +1,130 @@
+//! # Devices
+//! What a sign-in is compared by to decide whether it comes from a device
+//! the account has seen before.
+use std::net::IpAddr;
+
+/// The browser (or client) and operating system a user agent names, and
+/// the network the address belongs to. Browser updates and addresses handed
+/// out from the same /24 (or /48 for IPv6) leave it unchanged, so only a
+/// different browser, system or network makes a sign-in look new.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Device {
+    client: String,
+    os: &'static str,
+    network: String,
+}
+
+impl Device {
+    pub(crate) fn new(user_agent: &str, ip: &str) -> Self {
+        Self {
+            client: client(user_agent),
+            os: os(user_agent),
+            network: network(ip),
+        }
+    }
+}
+
+/// Browser family, or the product name of anything else (`hoodik-dav/1.2`
+/// is `hoodik-dav`). Edge and Opera also claim to be Chrome and Safari, and
+/// Chrome claims to be Safari, so they are looked for first.
+fn client(user_agent: &str) -> String {
+    let families = [
+        (&["Edg/", "EdgA/", "EdgiOS/"][..], "Edge"),
+        (&["OPR/", "Opera"][..], "Opera"),
+        (&["Firefox/", "FxiOS/"][..], "Firefox"),
+        (&["Chrome/", "CriOS/", "Chromium/"][..], "Chrome"),
+        (&["Safari/"][..], "Safari"),
+    ];
+
+    for (markers, family) in families {
+        if markers.iter().any(|marker| user_agent.contains(marker)) {
+            return family.to_string();
+        }
+    }
+
+    user_agent
+        .split(['/', ' '])
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Operating system family. Android and ChromeOS user agents mention Linux
+/// too, and iOS ones mention Mac OS X, so they are looked for first.
+fn os(user_agent: &str) -> &'static str {
+    let families = [
+        (&["Android"][..], "Android"),
+        (&["iPhone", "iPad", "iPod"][..], "iOS"),
+        (&["CrOS"][..], "ChromeOS"),
+        (&["Windows"][..], "Windows"),
+        (&["Macintosh", "Mac OS X"][..], "macOS"),
+        (&["Linux", "X11"][..], "Linux"),
+    ];
+
+    families
+        .iter()
+        .find(|(markers, _)| markers.iter().any(|marker| user_agent.contains(marker)))
+        .map(|(_, family)| *family)
+        .unwrap_or("other")
+}
+
+/// The /24 an IPv4 address is in, or the /48 of an IPv6 one. Anything that
+/// does not parse as an address is compared as is.
+fn network(ip: &str) -> String {
+    let ip = match ip.parse::<IpAddr>() {
+        Ok(IpAddr::V6(v6)) => v6
+            .to_ipv4_mapped()
+            .map(IpAddr::V4)
+            .unwrap_or(IpAddr::V6(v6)),
+        Ok(ip) => ip,
+        Err(_) => return ip.to_string(),
+    };
+
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, c, _] = v4.octets();
+
+            format!("{a}.{b}.{c}.0/24")
+        }
+        IpAddr::V6(v6) => {
+            let [a, b, c, ..] = v6.segments();
+
+            format!("{a:x}:{b:x}:{c:x}::/48")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHROME_WINDOWS: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/129.0.0.0 Safari/537.36";
+    const CHROME_WINDOWS_UPDATED: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/130.0.0.0 Safari/537.36";
+    const EDGE_WINDOWS: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/130.0.0.0 Safari/537.36 Edg/130.0.0.0";
+    const SAFARI_IPHONE: &str = "Mozilla/5.0 (iPhone; CPU iPhone OS 17_6 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.6 Mobile/15E148 Safari/604.1";
+
+    #[test]
+    fn browser_updates_and_nearby_addresses_are_the_same_device() {
+        let known = Device::new(CHROME_WINDOWS, "203.0.113.7");
+
+        assert_eq!(known, Device::new(CHROME_WINDOWS_UPDATED, "203.0.113.200"));
+        assert_eq!(
+            Device::new(CHROME_WINDOWS, "2001:db8:1:2::1"),
+            Device::new(CHROME_WINDOWS, "2001:db8:1:ffff::9")
+        );
+        assert_eq!(known, Device::new(CHROME_WINDOWS, "::ffff:203.0.113.9"));
+    }
+
+    #[test]
+    fn another_browser_system_or_network_is_a_new_device() {
+        let known = Device::new(CHROME_WINDOWS, "203.0.113.7");
+
+        assert_ne!(known, Device::new(EDGE_WINDOWS, "203.0.113.7"));
+        assert_ne!(known, Device::new(SAFARI_IPHONE, "203.0.113.7"));
+        assert_ne!(known, Device::new(CHROME_WINDOWS, "198.51.100.7"));
+        assert_ne!(
+            Device::new("hoodik-dav/1.2.0", "203.0.113.7"),
+            Device::new("hoodik-cli/1.2.0", "203.0.113.7")
+        );
+    }
+}
@@ -0,0 +1,71 @@
+//! # Security policy enrollment
+//! What the security policy in the settings still asks of a user, and the
+//! routes a session confined to enrollment may use meanwhile.
+use actix_web::{http::Method, HttpRequest};
+use serde::{Deserialize, Serialize};
+
+/// Something the user has to set up before they can use their account.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Requirement {
+    /// A TOTP app or a security key
+    TwoFactor,
+    /// A password scoring at least the required minimum
+    Password,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Enrollment {
+    /// Outstanding requirements; while any remain, the session is confined
+    /// to the routes that fulfill them
+    pub required: Vec<Requirement>,
+    /// When two-factor becomes required, while the user is still within
+    /// the grace period
+    pub two_factor_due_at: Option<i64>,
+}
+
+impl Enrollment {
+    pub fn is_enforced(&self) -> bool {
+        !self.required.is_empty()
+    }
+}
+
+/// Whether a session confined to enrollment may use the route: reading the
+/// account, setting up a second factor, changing the password (finishing
+/// the migration onto OPAQUE sets one too) and signing out.
+pub(crate) fn allowed(method: &Method, pattern: &str) -> bool {
+    matches!(
+        (method.as_str(), pattern),
+        ("POST", "/api/auth/self")
+            | ("POST", "/api/auth/logout")
+            | ("GET" | "POST", "/api/auth/two-factor")
+            | ("GET", "/api/auth/webauthn/credentials")
+            | ("POST", "/api/auth/webauthn/register/start")
+            | ("POST", "/api/auth/webauthn/register/finish")
+            | ("POST", "/api/auth/account/change-password")
+            | ("POST", "/api/auth/pake/register/start")
+            | ("POST", "/api/auth/pake/register/finish")
+            | ("GET", "/api/auth/migration/keys")
+            | ("POST", "/api/auth/migration/rewrap")
+            | ("POST", "/api/auth/migration/complete")
+    )
+}
+
+/// [`allowed`] for the route the request was matched to.
+pub(crate) fn allowed_for(req: &HttpRequest) -> bool {
+    req.match_pattern()
+        .is_some_and(|pattern| allowed(req.method(), &pattern))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_enrollment_routes_stay_open() {
+        assert!(allowed(&Method::POST, "/api/auth/two-factor"));
+        assert!(allowed(&Method::POST, "/api/auth/pake/register/finish"));
+        assert!(!allowed(&Method::POST, "/api/auth/two-factor/disable"));
+        assert!(!allowed(&Method::GET, "/api/storage"));
+    }
+}
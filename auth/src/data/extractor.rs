@@ -2,7 +2,7 @@
 use crate::data::claims::Claims;
 use actix_web::HttpRequest;
 use context::Context;
-use entity::Uuid;
+use entity::{jwt_signing_keys, Uuid};
 use error::AppResult;
 use std::marker::PhantomData;
 
@@ -22,7 +22,8 @@ pub(crate) struct Refresh<'ext>(Source<'ext>);
 
 pub(crate) struct Jwt<'ext> {
     source: Source<'ext>,
-    jwt_secret: &'ext str,
+    keys: Vec<jwt_signing_keys::Model>,
+    jwt_secret: Option<&'ext str>,
 }
 
 impl Extractor<'_, Useless> {
@@ -51,7 +52,8 @@ impl<'ext> Extractor<'ext, Useless> {
         Extractor {
             extractor: Jwt {
                 source,
-                jwt_secret: &ctx.config.auth.jwt_secret,
+                keys: ctx.jwt_keys.cached(),
+                jwt_secret: ctx.config.auth.legacy_jwt_secret(),
             },
             _p: &PhantomData,
         }
@@ -82,21 +84,48 @@ impl<'ext> Extractor<'ext, Jwt<'ext>> {
         matches!(self.extractor.source, Source::Header(_))
     }
 
-    fn jwt_secret(&self) -> &'ext str {
+    fn jwt_secret(&self) -> Option<&'ext str> {
         self.extractor.jwt_secret
     }
 
     /// Extract the authenticated session from the regular request and verify it.
     ///
-    /// It does not verify if the session is expired!
+    /// Verification uses the signing keys cached on the context, so they
+    /// have to be loaded with
+    /// [`crate::contracts::signing_keys::SigningKeys::signing_keys`] first.
     pub(crate) fn req(&self, req: &HttpRequest) -> AppResult<Claims> {
-        let claims = if self.use_headers() {
-            self.req_header(req)?
-        } else {
-            self.req_cookie(req)?
-        };
+        crate::jwt::extract(&self.token(req)?, &self.extractor.keys, self.jwt_secret())
+    }
 
-        crate::jwt::extract(&claims, self.jwt_secret())
+    /// Same as [`Self::req`], but the token may already be expired.
+    pub(crate) fn req_expired(&self, req: &HttpRequest) -> AppResult<Claims> {
+        crate::jwt::extract_expired(&self.token(req)?, &self.extractor.keys, self.jwt_secret())
+    }
+
+    /// The `kid` of every JWT the request carries: the session token, and a
+    /// transfer token in the `Authorization` header when sessions use cookies.
+    pub(crate) fn kids(&self, req: &HttpRequest) -> Vec<String> {
+        let bearer = req
+            .headers()
+            .get("Authorization")
+            .and_then(|header| header.to_str().ok())
+            .and_then(|header| header.strip_prefix("Bearer "))
+            .map(str::to_string);
+
+        self.token(req)
+            .ok()
+            .into_iter()
+            .chain(bearer)
+            .filter_map(|token| jsonwebtoken::decode_header(&token).ok()?.kid)
+            .collect()
+    }
+
+    fn token(&self, req: &HttpRequest) -> AppResult<String> {
+        if self.use_headers() {
+            self.req_header(req)
+        } else {
+            self.req_cookie(req)
+        }
     }
 
     fn req_cookie(&self, req: &HttpRequest) -> AppResult<String> {
@@ -4,12 +4,17 @@ pub mod change_password;
 pub mod claims;
 pub mod create_user;
 pub mod credentials;
+pub mod enrollment;
+pub mod oidc;
 pub mod opaque;
+pub mod personal_access_token;
 pub mod resend_activation;
 pub mod signature;
 pub mod staff;
 pub mod transfer_claims;
 pub mod transfer_token;
 pub mod two_factor;
+pub mod webauthn;
 
+pub(crate) mod device;
 pub(crate) mod extractor;
@@ -0,0 +1,94 @@
+//! # OpenID Connect login gate
+//!
+//! When an issuer is configured, a login or registration is only let through
+//! with a ticket from a fresh sign-in at the identity provider. The ticket is
+//! handed to the browser as a cookie by the callback, clients without cookies
+//! can send it in the [`TICKET_HEADER`] instead.
+use actix_web::HttpRequest;
+use entity::Uuid;
+use serde::Deserialize;
+
+/// Cookie the callback stores the ticket in.
+pub const TICKET_COOKIE: &str = "hoodik_oidc";
+
+/// Header carrying the ticket for clients that do not keep cookies.
+pub const TICKET_HEADER: &str = "x-oidc-ticket";
+
+/// The parts of the provider's discovery document the login needs.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Discovery {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub jwks_uri: String,
+}
+
+/// Answer of the token endpoint, only the ID token is used.
+#[derive(Clone, Debug, Deserialize)]
+pub struct TokenResponse {
+    pub id_token: String,
+}
+
+/// Query the provider sends the browser back to the callback with.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Callback {
+    pub code: Option<String>,
+    pub state: Option<Uuid>,
+    pub error: Option<String>,
+}
+
+/// Who the provider vouched for, and whether they already have an account.
+#[derive(Clone, Debug)]
+pub struct OidcSignIn {
+    pub ticket: Uuid,
+    pub email: String,
+    pub registered: bool,
+}
+
+/// The ticket a request carries, header first.
+pub(crate) fn ticket(req: &HttpRequest) -> Option<Uuid> {
+    let header = req
+        .headers()
+        .get(TICKET_HEADER)
+        .and_then(|h| h.to_str().ok())
+        .map(str::to_string);
+
+    header
+        .or_else(|| req.cookie(TICKET_COOKIE).map(|c| c.value().to_string()))
+        .and_then(|t| Uuid::parse_str(&t).ok())
+}
+
+/// Map the role claim of an ID token, a string or an array of strings, to a
+/// Hoodik role.
+pub(crate) fn map_role(claim: Option<&serde_json::Value>, admin_role: &str) -> &'static str {
+    let is_admin = match claim {
+        Some(serde_json::Value::String(value)) => value == admin_role,
+        Some(serde_json::Value::Array(values)) => values
+            .iter()
+            .any(|v| v.as_str().is_some_and(|v| v == admin_role)),
+        _ => false,
+    };
+
+    if is_admin {
+        "admin"
+    } else {
+        "user"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::map_role;
+    use serde_json::json;
+
+    #[test]
+    fn role_is_mapped_from_a_string_or_a_list() {
+        assert_eq!(map_role(Some(&json!("admin")), "admin"), "admin");
+        assert_eq!(
+            map_role(Some(&json!(["staff", "hoodik-admins"])), "hoodik-admins"),
+            "admin"
+        );
+        assert_eq!(map_role(Some(&json!(["staff"])), "hoodik-admins"), "user");
+        assert_eq!(map_role(None, "admin"), "user");
+    }
+}
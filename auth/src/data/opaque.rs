@@ -1,6 +1,8 @@
 use entity::Uuid;
 use serde::{Deserialize, Serialize};
 
+use super::{authenticated::Authenticated, webauthn::LoginChallenge};
+
 /// Client's registration request (`ClientRegistration::start` output).
 #[derive(Clone, Deserialize)]
 pub struct OpaqueRegisterStart {
@@ -34,6 +36,8 @@ pub struct OpaqueRegisterStartResponse {
 /// `hoodik-pake-register-v1\0` + `registration_upload` + `\0` + `issued_at`,
 /// proving possession of the identity key (which never lives in the session);
 /// `issued_at` bounds the replay window and `token` carries TOTP when 2FA is on.
+/// `password_score` is the zxcvbn score (0-4) the client computed for the new
+/// password, held to the minimum in the security settings.
 #[derive(Clone, Deserialize)]
 pub struct OpaqueRegisterFinish {
     pub registration_upload: String,
@@ -41,6 +45,7 @@ pub struct OpaqueRegisterFinish {
     pub signature: String,
     pub issued_at: i64,
     pub token: Option<String>,
+    pub password_score: Option<u8>,
 }
 
 /// Client's login request (`ClientLogin::start` output) plus the account it is
@@ -90,6 +95,13 @@ pub struct OpaqueLoginFinish {
     pub token: Option<String>,
 }
 
+/// Outcome of `login/finish`: a session, or the security key challenge an
+/// account with keys enrolled has to answer first.
+pub enum LoginFinish {
+    Authenticated(Box<Authenticated>),
+    WebAuthn(LoginChallenge),
+}
+
 /// One re-wrapped file key: the file the caller holds, with its key now
 /// wrapped under the new X25519 key instead of the old RSA key.
 #[derive(Clone, Deserialize)]
@@ -182,4 +194,8 @@ pub struct MigrationComplete {
     /// server re-encodes that canonical from its own state and verifies it
     /// before appending the hash-chained `key_rotation` audit event.
     pub audit_event_signature: String,
+    /// zxcvbn score (0-4) the client computed for the password it moves
+    /// onto OPAQUE. Recorded, not enforced: a weak one is caught by the
+    /// security policy once the user is signed in.
+    pub password_score: Option<u8>,
 }
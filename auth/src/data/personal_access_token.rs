@@ -0,0 +1,278 @@
+//! # Personal access tokens
+//!
+//! Long-lived credentials for scripts, sent as `Authorization: Bearer hdk_…`
+//! and accepted by the [`super::claims::Claims`] extractor. A token only
+//! reaches the routes listed in [`rule`], each of which names the scope it
+//! needs and how the file it acts on is found, so a folder-restricted token
+//! can be held to that folder's subtree. Every other route refuses tokens.
+use ::error::AppResult;
+use actix_web::{http::Method, HttpRequest};
+use entity::{personal_access_tokens, Uuid};
+use serde::{Deserialize, Serialize};
+use validr::*;
+
+/// Every token starts with this, so a token is told apart from a session
+/// JWT without trying to decode it, and is easy to spot when leaked.
+pub const PREFIX: &str = "hdk_";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Scope {
+    /// List and search files, read their metadata, versions and thumbnails.
+    #[serde(rename = "metadata:read")]
+    MetadataRead,
+    /// Create files and folders and upload their content.
+    #[serde(rename = "upload")]
+    Upload,
+    /// Download file content.
+    #[serde(rename = "download")]
+    Download,
+    /// Create, update and delete public links.
+    #[serde(rename = "links")]
+    Links,
+    /// Share files with other users and revoke those shares.
+    #[serde(rename = "shares")]
+    Shares,
+}
+
+impl Scope {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::MetadataRead => "metadata:read",
+            Self::Upload => "upload",
+            Self::Download => "download",
+            Self::Links => "links",
+            Self::Shares => "shares",
+        }
+    }
+
+    pub fn parse(scope: &str) -> Option<Self> {
+        match scope {
+            "metadata:read" => Some(Self::MetadataRead),
+            "upload" => Some(Self::Upload),
+            "download" => Some(Self::Download),
+            "links" => Some(Self::Links),
+            "shares" => Some(Self::Shares),
+            _ => None,
+        }
+    }
+
+    /// Scopes stored as a space-separated list, unknown ones dropped.
+    pub fn parse_list(scopes: &str) -> Vec<Self> {
+        scopes.split_whitespace().filter_map(Self::parse).collect()
+    }
+}
+
+/// What the personal access token behind a request may do.
+#[derive(Clone, Debug)]
+pub struct Grant {
+    pub token_id: Uuid,
+    pub scopes: Vec<Scope>,
+    pub folder_id: Option<Uuid>,
+}
+
+impl From<&personal_access_tokens::Model> for Grant {
+    fn from(token: &personal_access_tokens::Model) -> Self {
+        Self {
+            token_id: token.id,
+            scopes: Scope::parse_list(&token.scopes),
+            folder_id: token.folder_id,
+        }
+    }
+}
+
+/// Where a route finds the file it acts on, for folder-restricted tokens.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Target {
+    /// A file id in the path, under this name.
+    File(&'static str),
+    /// A parent folder id in the query string; absent means the root.
+    Folder(&'static str),
+    /// A link id in the path, under this name.
+    Link(&'static str),
+    /// Ids in the request body; the route checks them with
+    /// [`super::claims::Claims::require_folder`].
+    Body,
+    /// Not tied to any file.
+    Unbound,
+    /// Spans the whole account, so a folder-restricted token cannot use it.
+    Account,
+}
+
+/// The scope a route needs from a personal access token and where it finds
+/// its file, or `None` when the route is not open to tokens at all.
+pub(crate) fn rule(method: &Method, pattern: &str) -> Option<(Scope, Target)> {
+    use Scope::*;
+    use Target::*;
+
+    let rule = match (method.as_str(), pattern) {
+        ("GET", "/api/storage") => (MetadataRead, Folder("dir_id")),
+        ("GET", "/api/storage/{file_id}/metadata") => (MetadataRead, File("file_id")),
+        ("GET", "/api/storage/{file_id}/thumbnail") => (MetadataRead, File("file_id")),
+        ("GET", "/api/storage/{file_id}/versions") => (MetadataRead, File("file_id")),
+        ("GET", "/api/storage/{name_hash}/name-hash") => (MetadataRead, Account),
+        ("POST", "/api/storage/search") => (MetadataRead, Account),
+        ("POST", "/api/storage/stats") => (MetadataRead, Account),
+
+        ("GET" | "HEAD", "/api/storage/{file_id}") => (Download, File("file_id")),
+        ("GET", "/api/storage/{file_id}/versions/{version}") => (Download, File("file_id")),
+
+        ("POST", "/api/storage") => (Upload, Body),
+        ("POST", "/api/storage/{file_id}") => (Upload, File("file_id")),
+        ("PUT", "/api/storage/{file_id}/content") => (Upload, File("file_id")),
+        ("PUT", "/api/storage/{file_id}/hashes") => (Upload, File("file_id")),
+
+        ("GET", "/api/links") => (Links, Account),
+        ("POST", "/api/links") => (Links, Body),
+        ("PUT" | "DELETE", "/api/links/{link_id}") => (Links, Link("link_id")),
+
+        ("POST", "/api/shares") => (Shares, Body),
+        ("GET", "/api/shares/{file_id}") => (Shares, File("file_id")),
+        ("DELETE", "/api/shares/{file_id}/{user_id}") => (Shares, File("file_id")),
+        ("GET", "/api/users/discover") => (Shares, Unbound),
+
+        _ => return None,
+    };
+
+    Some(rule)
+}
+
+/// The rule for the route the request was matched to.
+pub(crate) fn rule_for(req: &HttpRequest) -> Option<(Scope, Target)> {
+    rule(req.method(), &req.match_pattern()?)
+}
+
+/// Request to create a personal access token.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CreatePersonalAccessToken {
+    /// Label shown in the list of tokens
+    pub name: Option<String>,
+
+    /// What the token may do, see [`Scope`]
+    pub scopes: Option<Vec<String>>,
+
+    /// Confine the token to this folder and everything under it
+    pub folder_id: Option<Uuid>,
+
+    /// Unix timestamp after which the token stops working, never if omitted
+    pub expires_at: Option<i64>,
+}
+
+impl Validation for CreatePersonalAccessToken {
+    fn rules(&self) -> Vec<Rule<Self>> {
+        vec![
+            rule_required!(name),
+            Rule::new("scopes", |obj: &Self, error| match &obj.scopes {
+                Some(scopes) if !scopes.is_empty() => {
+                    if scopes.iter().any(|s| Scope::parse(s).is_none()) {
+                        error.add("unknown_scope");
+                    }
+                }
+                _ => error.add("required"),
+            }),
+            Rule::new("expires_at", |obj: &Self, error| {
+                if let Some(expires_at) = obj.expires_at {
+                    if expires_at <= chrono::Utc::now().timestamp() {
+                        error.add("in_the_past");
+                    }
+                }
+            }),
+        ]
+    }
+}
+
+impl CreatePersonalAccessToken {
+    pub fn into_value(self) -> AppResult<NewPersonalAccessToken> {
+        let data = self.validate()?;
+
+        let mut scopes = Vec::new();
+        for scope in data.scopes.unwrap_or_default() {
+            if let Some(scope) = Scope::parse(&scope) {
+                if !scopes.contains(&scope) {
+                    scopes.push(scope);
+                }
+            }
+        }
+
+        Ok(NewPersonalAccessToken {
+            name: data.name.unwrap_or_default(),
+            scopes,
+            folder_id: data.folder_id,
+            expires_at: data.expires_at,
+        })
+    }
+}
+
+/// Validated [`CreatePersonalAccessToken`], scopes deduplicated.
+pub struct NewPersonalAccessToken {
+    pub name: String,
+    pub scopes: Vec<Scope>,
+    pub folder_id: Option<Uuid>,
+    pub expires_at: Option<i64>,
+}
+
+/// A personal access token as listed to its owner; the secret is never
+/// shown again after creation.
+#[derive(Clone, Debug, Serialize)]
+pub struct PersonalAccessToken {
+    pub id: Uuid,
+    pub name: String,
+    pub scopes: Vec<Scope>,
+    pub folder_id: Option<Uuid>,
+    pub expires_at: Option<i64>,
+    pub last_used_at: Option<i64>,
+    pub created_at: i64,
+}
+
+impl From<personal_access_tokens::Model> for PersonalAccessToken {
+    fn from(token: personal_access_tokens::Model) -> Self {
+        Self {
+            id: token.id,
+            scopes: Scope::parse_list(&token.scopes),
+            name: token.name,
+            folder_id: token.folder_id,
+            expires_at: token.expires_at,
+            last_used_at: token.last_used_at,
+            created_at: token.created_at,
+        }
+    }
+}
+
+/// Answer of token creation, the only time `token` is available.
+#[derive(Clone, Debug, Serialize)]
+pub struct CreatedPersonalAccessToken {
+    pub token: String,
+    #[serde(flatten)]
+    pub personal_access_token: PersonalAccessToken,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{rule, Scope, Target};
+    use actix_web::http::Method;
+
+    #[test]
+    fn routes_not_listed_refuse_tokens() {
+        assert!(rule(&Method::POST, "/api/auth/account/tokens").is_none());
+        assert!(rule(&Method::DELETE, "/api/storage/{file_id}").is_none());
+        assert!(rule(&Method::GET, "/api/admin/users").is_none());
+    }
+
+    #[test]
+    fn storage_routes_map_to_their_scope() {
+        assert_eq!(
+            rule(&Method::HEAD, "/api/storage/{file_id}"),
+            Some((Scope::Download, Target::File("file_id")))
+        );
+        assert_eq!(
+            rule(&Method::POST, "/api/storage/{file_id}"),
+            Some((Scope::Upload, Target::File("file_id")))
+        );
+    }
+
+    #[test]
+    fn scope_list_round_trips() {
+        let scopes = Scope::parse_list("upload bogus metadata:read");
+        assert_eq!(scopes, vec![Scope::Upload, Scope::MetadataRead]);
+        assert_eq!(Scope::parse(Scope::Shares.as_str()), Some(Scope::Shares));
+    }
+}
@@ -42,9 +42,7 @@ impl TransferClaims {
     pub fn validate_path(&self, file_id: Uuid, action: &str) -> AppResult<()> {
         let expected = format!("{}/{}", action, file_id);
         if self.path != expected {
-            return Err(Error::Forbidden(
-                "transfer_token_path_mismatch".to_string(),
-            ));
+            return Err(Error::Forbidden("transfer_token_path_mismatch".to_string()));
         }
         Ok(())
     }
@@ -90,24 +88,19 @@ impl FromRequest for StorageClaims {
     type Error = Error;
     type Future = Pin<Box<dyn Future<Output = Result<Self, Error>>>>;
 
-    fn from_request(
-        req: &HttpRequest,
-        payload: &mut actix_web::dev::Payload,
-    ) -> Self::Future {
-        // Try to extract a transfer token from the Authorization header first.
-        let transfer_result = try_extract_transfer_claims(req);
+    fn from_request(req: &HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+        let req = req.clone();
 
-        match transfer_result {
-            Ok(tc) => Box::pin(async move { Ok(StorageClaims::Transfer(tc)) }),
-            Err(_) => {
+        Box::pin(async move {
+            super::claims::load_signing_keys(&req).await?;
+
+            // Try to extract a transfer token from the Authorization header first.
+            match try_extract_transfer_claims(&req) {
+                Ok(tc) => Ok(StorageClaims::Transfer(tc)),
                 // Fall back to regular Claims extraction (cookie or header).
-                let claims_fut = Claims::from_request(req, payload);
-                Box::pin(async move {
-                    let claims = claims_fut.await?;
-                    Ok(StorageClaims::Session(claims))
-                })
+                Err(_) => Ok(StorageClaims::Session(Claims::extract(&req).await?)),
             }
-        }
+        })
     }
 
     fn extract(req: &HttpRequest) -> Self::Future {
@@ -136,7 +129,11 @@ fn try_extract_transfer_claims(req: &HttpRequest) -> AppResult<TransferClaims> {
         .strip_prefix("Bearer ")
         .ok_or_else(|| Error::Unauthorized("invalid_authorization_header".to_string()))?;
 
-    let tc = crate::jwt::extract_transfer_claims(token, &context.config.auth.jwt_secret)?;
+    let tc = crate::jwt::extract_transfer_claims(
+        token,
+        &context.jwt_keys.cached(),
+        context.config.auth.legacy_jwt_secret(),
+    )?;
 
     if tc.iss != "transfer" {
         return Err(Error::Unauthorized("not_a_transfer_token".to_string()));
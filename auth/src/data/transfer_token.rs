@@ -1,6 +1,6 @@
 //! # Transfer token request and response data
-use entity::Uuid;
 use ::error::AppResult;
+use entity::Uuid;
 use serde::{Deserialize, Serialize};
 use validr::*;
 
@@ -23,6 +23,23 @@ impl Disable {
     }
 }
 
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RegenerateRecoveryCodes {
+    pub token: Option<String>,
+}
+
+impl Validation for RegenerateRecoveryCodes {
+    fn rules(&self) -> Vec<Rule<Self>> {
+        vec![rule_required!(token)]
+    }
+}
+
+impl RegenerateRecoveryCodes {
+    pub fn into_value(self) -> AppResult<Option<String>> {
+        Ok(self.validate()?.token)
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Enable {
     pub token: Option<String>,
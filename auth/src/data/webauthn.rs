@@ -0,0 +1,140 @@
+//! # WebAuthn data
+//!
+//! Binary fields travel as unpadded base64url, the encoding browsers use for
+//! credential ids. The `public_key` options are shaped like the
+//! `PublicKeyCredentialCreationOptions` / `PublicKeyCredentialRequestOptions`
+//! dictionaries so a client only has to decode the binary fields before
+//! handing them to `navigator.credentials`.
+use entity::Uuid;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize)]
+pub struct CredentialDescriptor {
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub id: String,
+}
+
+impl CredentialDescriptor {
+    pub fn new(id: String) -> Self {
+        Self {
+            kind: "public-key",
+            id,
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct RelyingPartyEntity {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserEntity {
+    pub id: String,
+    pub name: String,
+    pub display_name: String,
+}
+
+#[derive(Clone, Serialize)]
+pub struct CredentialParameters {
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub alg: i64,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreationOptions {
+    pub challenge: String,
+    pub rp: RelyingPartyEntity,
+    pub user: UserEntity,
+    pub pub_key_cred_params: Vec<CredentialParameters>,
+    pub timeout: u32,
+    /// Keys the user already enrolled, so the same key is not added twice.
+    pub exclude_credentials: Vec<CredentialDescriptor>,
+    pub attestation: &'static str,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestOptions {
+    pub challenge: String,
+    pub rp_id: String,
+    pub allow_credentials: Vec<CredentialDescriptor>,
+    pub timeout: u32,
+    pub user_verification: &'static str,
+}
+
+/// Answer of `webauthn/register/start`: the options for
+/// `navigator.credentials.create()` and the id to finish the registration
+/// with.
+#[derive(Clone, Serialize)]
+pub struct RegistrationChallenge {
+    pub registration_id: Uuid,
+    pub public_key: CreationOptions,
+}
+
+/// The authenticator's answer to `navigator.credentials.create()`.
+#[derive(Clone, Deserialize)]
+pub struct AttestationResponse {
+    pub client_data_json: String,
+    pub attestation_object: String,
+}
+
+/// Request of `webauthn/register/finish`. `name` labels the key in the list of
+/// enrolled keys; `token` carries the TOTP code when TOTP is enabled, so a
+/// session alone cannot add a factor.
+#[derive(Clone, Deserialize)]
+pub struct RegisterFinish {
+    pub registration_id: Uuid,
+    pub name: Option<String>,
+    pub token: Option<String>,
+    pub response: AttestationResponse,
+}
+
+/// `login/finish` answer for an account with security keys enrolled: the
+/// password proof passed, and a session is issued only once one of the keys
+/// signs the challenge at `login/webauthn`.
+#[derive(Clone, Serialize)]
+pub struct LoginChallenge {
+    pub login_id: Uuid,
+    pub public_key: RequestOptions,
+}
+
+/// The authenticator's answer to `navigator.credentials.get()`.
+#[derive(Clone, Deserialize)]
+pub struct AssertionResponse {
+    pub credential_id: String,
+    pub client_data_json: String,
+    pub authenticator_data: String,
+    pub signature: String,
+}
+
+/// Request of `login/webauthn`, the second step of a login.
+#[derive(Clone, Deserialize)]
+pub struct LoginAssertion {
+    pub login_id: Uuid,
+    pub response: AssertionResponse,
+}
+
+/// Answer of `webauthn/assertion/start`: a challenge for an authenticated
+/// user to sign with one of their keys, confirming a change to their keys.
+#[derive(Clone, Serialize)]
+pub struct AssertionChallenge {
+    pub challenge_id: Uuid,
+    pub public_key: RequestOptions,
+}
+
+/// Request to remove a security key. Removing a second factor takes a second
+/// factor: either an assertion over a challenge from `webauthn/assertion/start`
+/// made with any enrolled key, or the TOTP code when TOTP is enabled too — the
+/// way out for someone who lost the key.
+#[derive(Clone, Deserialize)]
+pub struct DeleteCredential {
+    pub token: Option<String>,
+    pub challenge_id: Option<Uuid>,
+    pub response: Option<AssertionResponse>,
+}
@@ -1,5 +1,10 @@
+use std::str::FromStr;
+
+use entity::jwt_signing_keys;
 use error::{AppResult, Error};
+use jsonwebtoken::{jwk::Jwk, Algorithm, DecodingKey, EncodingKey, Header, Validation};
 use log::error;
+use serde::{de::DeserializeOwned, Serialize};
 
 use crate::data::{authenticated::Authenticated, claims::Claims, transfer_claims::TransferClaims};
 
@@ -7,71 +12,116 @@ use crate::data::{authenticated::Authenticated, claims::Claims, transfer_claims:
 pub(crate) fn generate(
     authenticated: &Authenticated,
     issuer: &str,
-    secret: &str,
+    key: &jwt_signing_keys::Model,
 ) -> AppResult<String> {
-    if secret.is_empty() {
-        error!("Generating unsecure JWT without secret set!");
-    }
-
     let claims = Claims::from(authenticated).set_iss(issuer);
 
-    jsonwebtoken::encode(
-        &jsonwebtoken::Header::default(),
-        &claims,
-        &jsonwebtoken::EncodingKey::from_secret(secret.as_bytes()),
-    )
-    .map_err(Error::from)
+    sign(&claims, key)
 }
 
-/// Extract and verify given token and return authenticated data
-pub(crate) fn extract(claims: &str, secret: &str) -> AppResult<Claims> {
-    if secret.is_empty() {
-        error!("Generating unsecure JWT without secret set!");
-    }
-
-    let mut validator = jsonwebtoken::Validation::new(jsonwebtoken::Algorithm::HS256);
-    validator.validate_exp = false;
-
-    jsonwebtoken::decode::<Claims>(
-        claims,
-        &jsonwebtoken::DecodingKey::from_secret(secret.as_bytes()),
-        &validator,
-    )
-    .map_err(Error::from)
-    .map(|data| data.claims)
+/// Extract and verify given token and return authenticated data,
+/// refusing it once expired.
+pub(crate) fn extract(
+    claims: &str,
+    keys: &[jwt_signing_keys::Model],
+    secret: Option<&str>,
+) -> AppResult<Claims> {
+    verify(claims, keys, secret, true)
+}
+
+/// Extract and verify given token like [`extract`], but accept it after it
+/// expired, which is what refreshing a session needs.
+pub(crate) fn extract_expired(
+    claims: &str,
+    keys: &[jwt_signing_keys::Model],
+    secret: Option<&str>,
+) -> AppResult<Claims> {
+    verify(claims, keys, secret, false)
 }
 
 /// Generate a transfer token JWT scoped to a specific file and action.
 pub(crate) fn generate_transfer_token(
     claims: &TransferClaims,
-    secret: &str,
+    key: &jwt_signing_keys::Model,
 ) -> AppResult<String> {
-    if secret.is_empty() {
-        error!("Generating unsecure JWT without secret set!");
-    }
-
-    jsonwebtoken::encode(
-        &jsonwebtoken::Header::default(),
-        claims,
-        &jsonwebtoken::EncodingKey::from_secret(secret.as_bytes()),
-    )
-    .map_err(Error::from)
+    sign(claims, key)
 }
 
 /// Extract and verify a transfer token JWT.
-pub(crate) fn extract_transfer_claims(token: &str, secret: &str) -> AppResult<TransferClaims> {
-    if secret.is_empty() {
-        error!("Generating unsecure JWT without secret set!");
-    }
-
-    let mut validator = jsonwebtoken::Validation::new(jsonwebtoken::Algorithm::HS256);
-    validator.validate_exp = false;
-
-    jsonwebtoken::decode::<TransferClaims>(
-        token,
-        &jsonwebtoken::DecodingKey::from_secret(secret.as_bytes()),
-        &validator,
-    )
-    .map_err(Error::from)
-    .map(|data| data.claims)
+pub(crate) fn extract_transfer_claims(
+    token: &str,
+    keys: &[jwt_signing_keys::Model],
+    secret: Option<&str>,
+) -> AppResult<TransferClaims> {
+    verify(token, keys, secret, true)
+}
+
+/// Sign the claims with the given key, naming it in the `kid` header so
+/// verifiers can pick the right public key from the JWKS.
+fn sign<T: Serialize>(claims: &T, key: &jwt_signing_keys::Model) -> AppResult<String> {
+    let algorithm = Algorithm::from_str(&key.algorithm)?;
+
+    let encoding_key = match algorithm {
+        Algorithm::ES256 => EncodingKey::from_ec_pem(key.private_key.as_bytes())?,
+        Algorithm::EdDSA => EncodingKey::from_ed_pem(key.private_key.as_bytes())?,
+        _ => {
+            return Err(Error::InternalError(format!(
+                "unsupported_signing_algorithm:{}",
+                key.algorithm
+            )))
+        }
+    };
+
+    let mut header = Header::new(algorithm);
+    header.kid = Some(key.id.to_string());
+
+    jsonwebtoken::encode(&header, claims, &encoding_key).map_err(Error::from)
+}
+
+/// Verify a token against the key its `kid` names. Tokens without a `kid`
+/// were signed before keys were introduced and are checked with the shared
+/// secret instead, if legacy tokens are accepted at all.
+fn verify<T: DeserializeOwned>(
+    token: &str,
+    keys: &[jwt_signing_keys::Model],
+    secret: Option<&str>,
+    validate_exp: bool,
+) -> AppResult<T> {
+    let header = jsonwebtoken::decode_header(token)?;
+
+    let (decoding_key, algorithm) = match header.kid {
+        Some(kid) => {
+            let key = keys
+                .iter()
+                .find(|k| k.id.to_string() == kid)
+                .ok_or_else(|| Error::Unauthorized("unknown_signing_key".to_string()))?;
+            let jwk: Jwk = serde_json::from_str(&key.jwk)?;
+
+            (
+                DecodingKey::from_jwk(&jwk)?,
+                Algorithm::from_str(&key.algorithm)?,
+            )
+        }
+        None => {
+            let secret =
+                secret.ok_or_else(|| Error::Unauthorized("legacy_token_rejected".to_string()))?;
+
+            if secret.is_empty() {
+                error!("Verifying unsecure JWT without secret set!");
+            }
+
+            (
+                DecodingKey::from_secret(secret.as_bytes()),
+                Algorithm::HS256,
+            )
+        }
+    };
+
+    let mut validator = Validation::new(algorithm);
+    validator.validate_exp = validate_exp;
+    validator.leeway = 0;
+
+    jsonwebtoken::decode::<T>(token, &decoding_key, &validator)
+        .map_err(Error::from)
+        .map(|data| data.claims)
 }
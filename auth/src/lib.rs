@@ -6,10 +6,23 @@ pub(crate) mod auth;
 pub(crate) mod contracts;
 pub(crate) mod jwt;
 pub(crate) mod providers;
-pub(crate) mod rate_limit;
+pub mod rate_limit;
 
 pub(crate) const REFRESH_PATH: &str = "/api/auth/refresh";
 
+pub use contracts::migration::STAGING_TTL_SECONDS as REWRAP_STAGING_TTL_SECONDS;
+
+/// Start signing tokens with a fresh key and retire the current one, which
+/// keeps verifying what it already signed. Backs `hoodik rotate-jwt-key`;
+/// returns the id of the new key, its `kid` in the JWKS.
+pub async fn rotate_jwt_signing_key(context: &context::Context) -> error::AppResult<entity::Uuid> {
+    use contracts::signing_keys::SigningKeys;
+
+    let key = auth::Auth::new(context).rotate_signing_key().await?;
+
+    Ok(key.id)
+}
+
 #[cfg(test)]
 mod test;
 
@@ -17,21 +30,30 @@ mod test;
 pub mod mock;
 
 /// Test-only hooks into the login rate limiter, hidden behind `mock` so they
-/// never link into production binaries. Integration suites drive them to clear
-/// window state and to advance the clock without sleeping.
+/// never link into production binaries. Integration suites drive them to
+/// advance the clock without sleeping.
 #[cfg(feature = "mock")]
 pub mod test_support {
-    pub use crate::rate_limit::reset_for_tests as reset_auth_rate_limit;
+    use context::Context;
 
-    pub fn auth_rate_limit_check(
+    pub async fn auth_rate_limit_attempt(
+        context: &Context,
         identity: Option<&str>,
         ip: &str,
         now: i64,
-    ) -> error::AppResult<()> {
-        crate::rate_limit::check(identity, ip, now)
+    ) -> error::AppResult<crate::rate_limit::Attempt> {
+        crate::rate_limit::attempt(context, identity, ip, now).await
     }
 
-    pub fn auth_rate_limit_charge_failure(identity: Option<&str>, ip: &str, now: i64) {
-        crate::rate_limit::charge_failure(identity, ip, now)
+    /// An attempt that is never handed back, as a failed login leaves it.
+    pub async fn auth_rate_limit_charge_failure(
+        context: &Context,
+        identity: Option<&str>,
+        ip: &str,
+        now: i64,
+    ) -> error::AppResult<()> {
+        crate::rate_limit::attempt(context, identity, ip, now)
+            .await
+            .map(drop)
     }
 }
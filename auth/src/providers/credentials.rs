@@ -1,19 +1,36 @@
 use crate::{
     auth::Auth,
-    contracts::{ctx::Ctx, provider::AuthProvider, repository::Repository, sessions::Sessions},
+    contracts::{
+        ctx::Ctx, oidc::Oidc, provider::AuthProvider, recovery_codes::RecoveryCodes,
+        repository::Repository, sessions::Sessions,
+    },
     data::{authenticated::Authenticated, credentials::Credentials},
 };
+use entity::Uuid;
 use error::{AppResult, Error};
 
 /// Authentication provider for logging in with credentials (email + password)
 pub(crate) struct CredentialsProvider<'ctx> {
     auth: &'ctx Auth<'ctx>,
     data: Credentials,
+    oidc_ticket: Option<Uuid>,
 }
 
 impl<'ctx> CredentialsProvider<'ctx> {
     pub(crate) fn new(auth: &'ctx Auth, data: Credentials) -> Self {
-        Self { auth, data }
+        Self {
+            auth,
+            data,
+            oidc_ticket: None,
+        }
+    }
+
+    /// The OpenID Connect ticket the login came with, see
+    /// [`crate::contracts::oidc::Oidc::oidc_gate`].
+    pub(crate) fn oidc_ticket(mut self, ticket: Option<Uuid>) -> Self {
+        self.oidc_ticket = ticket;
+
+        self
     }
 }
 
@@ -58,7 +75,7 @@ impl AuthProvider for CredentialsProvider<'_> {
             return Err(Error::Unauthorized("invalid_credentials".to_string()));
         }
 
-        if !user.verify_tfa(token) {
+        if !self.auth.verify_second_factor(&user, token).await? {
             return Err(Error::Unauthorized("invalid_otp_token".to_string()));
         }
 
@@ -66,8 +83,13 @@ impl AuthProvider for CredentialsProvider<'_> {
             return Err(Error::Unauthorized("inactive_account".to_string()));
         }
 
+        let user = self.auth.oidc_gate(user, self.oidc_ticket).await?;
         let session = self.auth.generate(&user, user_agent, ip).await?;
 
-        Ok(Authenticated { user, session })
+        Ok(Authenticated {
+            user,
+            session,
+            enrollment: None,
+        })
     }
 }
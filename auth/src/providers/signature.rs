@@ -1,10 +1,11 @@
 use crate::{
     auth::Auth,
-    contracts::{provider::AuthProvider, repository::Repository, sessions::Sessions},
+    contracts::{oidc::Oidc, provider::AuthProvider, repository::Repository, sessions::Sessions},
     data::{authenticated::Authenticated, signature::Signature},
 };
 use chrono::Utc;
 use cryptfns::identity::KeyType;
+use entity::Uuid;
 use error::{AppResult, Error};
 use std::str::FromStr;
 
@@ -12,6 +13,7 @@ use std::str::FromStr;
 pub(crate) struct SignatureProvider<'ctx> {
     auth: &'ctx Auth<'ctx>,
     data: Signature,
+    oidc_ticket: Option<Uuid>,
 }
 
 /// Widest clock skew accepted between the client-signed timestamp and the
@@ -20,7 +22,19 @@ const CLIENT_NONCE_MAX_SKEW_SECONDS: i64 = 300;
 
 impl<'ctx> SignatureProvider<'ctx> {
     pub(crate) fn new(auth: &'ctx Auth, data: Signature) -> Self {
-        Self { auth, data }
+        Self {
+            auth,
+            data,
+            oidc_ticket: None,
+        }
+    }
+
+    /// The OpenID Connect ticket the login came with, see
+    /// [`crate::contracts::oidc::Oidc::oidc_gate`].
+    pub(crate) fn oidc_ticket(mut self, ticket: Option<Uuid>) -> Self {
+        self.oidc_ticket = ticket;
+
+        self
     }
 
     pub(crate) fn generate_nonce_minutes() -> String {
@@ -51,8 +65,7 @@ impl AuthProvider for SignatureProvider<'_> {
         // known limitation that a second login in the same bucket is refused.
         // Either canonical is built from the *presented* fingerprint (the
         // client may send an old one from a pre-migration backup key).
-        let (message, nonce, expires_at) = match (self.data.timestamp, self.data.nonce.as_deref())
-        {
+        let (message, nonce, expires_at) = match (self.data.timestamp, self.data.nonce.as_deref()) {
             (Some(timestamp), Some(nonce)) if !nonce.is_empty() => {
                 if (Utc::now().timestamp() - timestamp).abs() > CLIENT_NONCE_MAX_SKEW_SECONDS {
                     return Err(Error::Unauthorized("signature_expired".to_string()));
@@ -116,19 +129,27 @@ impl AuthProvider for SignatureProvider<'_> {
                 .map(|v| v as i64);
         }
 
-        KeyType::from_str(&verify_key_type)?
-            .verify(&message, &signature, &verify_pubkey)?;
+        KeyType::from_str(&verify_key_type)?.verify(&message, &signature, &verify_pubkey)?;
 
         // Recording the verified nonce makes a captured request single-use;
         // the row only has to outlive the window in which the signature is
         // still acceptable — the timestamp skew for client nonces, the minute
         // bucket for legacy ones.
-        if !self.auth.consume_login_nonce(&fingerprint, &nonce, expires_at).await? {
+        if !self
+            .auth
+            .consume_login_nonce(&fingerprint, &nonce, expires_at)
+            .await?
+        {
             return Err(Error::Unauthorized("signature_replayed".to_string()));
         }
 
+        let user = self.auth.oidc_gate(user, self.oidc_ticket).await?;
         let session = self.auth.generate(&user, user_agent, ip).await?;
 
-        Ok(Authenticated { user, session })
+        Ok(Authenticated {
+            user,
+            session,
+            enrollment: None,
+        })
     }
 }
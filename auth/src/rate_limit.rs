@@ -1,7 +1,9 @@
 //! Sliding-window lockout for the surfaces where a password or key is guessed
-//! online: `login`, `login/finish`, `signature`, and `change-password`. Only
-//! *failed* authentications are charged, following OWASP guidance — a wrong
-//! password or bad signature counts against the budget, a correct one does not.
+//! online: `login`, `login/finish`, `signature`, `change-password`, and the
+//! unlock route of password-protected public links (`links` charges those
+//! under the identity `link:{id}`). Only *failed* authentications are charged,
+//! following OWASP guidance — a wrong password or bad signature counts against
+//! the budget, a correct one does not.
 //!
 //! Charging failures only is also what keeps the limiter from leaking account
 //! existence. An attacker probing whether an address is registered never has
@@ -12,8 +14,11 @@
 //! let any user lock themselves out with correct passwords. Enumeration is
 //! defended by uniform responses, not by counting the honest path.
 //!
-//! Two windows guard each attempt. They are read before authentication and the
-//! request is refused (429) when either is already full:
+//! Two windows guard each attempt. Before authentication the attempt takes a
+//! slot in each, counted and recorded as one step so concurrent guesses cannot
+//! all find room for the last one, and the request is refused (429) when either
+//! is already full. A successful authentication hands its slots back, which is
+//! what leaves only the failures counted:
 //!   - identity (email or key fingerprint): 10 failures / 5 min. The primary
 //!     guard — focused guessing of one account trips here whatever the source.
 //!   - source IP: 100 failures / 5 min. A coarse backstop for the case the
@@ -35,138 +40,169 @@
 //! endpoint ever need a ceiling, the fit is a generous per-IP window charged
 //! unconditionally there — or reverse-proxy flood control, where it belongs.
 //!
-//! In-memory and process-local: the windows live in a static and are lost on
-//! restart, the trade-off the discover limiter also makes. Hoodik runs one
-//! server process, so a shared cross-node store is scope it does not yet have.
-
-use std::collections::{HashMap, VecDeque};
-use std::sync::{Mutex, OnceLock};
+//! The windows live in the configured [`context::RateLimitBackend`]: process
+//! memory by default, or the database with `RATE_LIMIT_BACKEND=database` so
+//! several server processes behind a load balancer share one lockout.
 
+use context::Context;
+use entity::Uuid;
 use error::{AppResult, Error};
 
 const WINDOW_SECONDS: i64 = 300;
-const IDENTITY_ATTEMPTS: usize = 10;
-const IP_ATTEMPTS: usize = 100;
-
-/// Ceiling on distinct tracked keys. Crossing it sweeps out windows whose most
-/// recent failure has already aged out, reclaiming the long tail of one-off
-/// attacker IPs without a background task.
-const MAX_TRACKED_KEYS: usize = 50_000;
-
-type Window = VecDeque<i64>;
+const IDENTITY_ATTEMPTS: u64 = 10;
+const IP_ATTEMPTS: u64 = 100;
 
-fn windows() -> &'static Mutex<HashMap<String, Window>> {
-    static WINDOWS: OnceLock<Mutex<HashMap<String, Window>>> = OnceLock::new();
-    WINDOWS.get_or_init(|| Mutex::new(HashMap::new()))
+fn ip_key(ip: &str) -> String {
+    format!("auth:ip:{ip}")
 }
 
-fn is_full(windows: &HashMap<String, Window>, key: &str, threshold: usize, cutoff: i64) -> bool {
-    windows
-        .get(key)
-        .is_some_and(|w| w.iter().filter(|&&t| t >= cutoff).count() >= threshold)
+fn identity_key(identity: &str) -> String {
+    format!("auth:id:{identity}")
 }
 
-fn record(windows: &mut HashMap<String, Window>, key: String, now: i64, cutoff: i64) {
-    let window = windows.entry(key).or_default();
-    while window.front().is_some_and(|&t| t < cutoff) {
-        window.pop_front();
-    }
-    window.push_back(now);
+/// Slots an authentication in progress holds in the windows. Unless
+/// [`Attempt::succeeded`] hands them back they stay charged as a failure,
+/// whichever way the request ends.
+#[must_use = "the slots count as a failure until handed back"]
+pub struct Attempt {
+    hits: Vec<(String, Uuid)>,
 }
 
-/// Refuse with [`Error::TooManyRequests`] when the identity or source-IP window
-/// is already full. Read-only: a mere check never counts as an attempt, so the
-/// honest login path leaves no trace and cannot be used to drain a budget.
-pub(crate) fn check(identity: Option<&str>, ip: &str, now: i64) -> AppResult<()> {
-    let cutoff = now - WINDOW_SECONDS;
-    let windows = windows().lock().expect("auth rate-limit windows poisoned");
+impl Attempt {
+    /// Hand the slots back: a correct password or signature is not charged.
+    pub async fn succeeded(self, context: &Context) -> AppResult<()> {
+        self.release(context).await
+    }
 
-    let ip_full = is_full(&windows, &format!("ip:{ip}"), IP_ATTEMPTS, cutoff);
-    let identity_full =
-        identity.is_some_and(|id| is_full(&windows, &format!("id:{id}"), IDENTITY_ATTEMPTS, cutoff));
+    async fn release(self, context: &Context) -> AppResult<()> {
+        for (key, hit) in self.hits {
+            context.rate_limits.forget(&key, hit).await?;
+        }
 
-    if ip_full || identity_full {
-        Err(Error::TooManyRequests("too_many_attempts".to_string()))
-    } else {
         Ok(())
     }
 }
 
-/// Charge one failed attempt to the identity and source-IP windows. Call only
-/// after authentication has actually failed.
-pub(crate) fn charge_failure(identity: Option<&str>, ip: &str, now: i64) {
-    let cutoff = now - WINDOW_SECONDS;
-    let mut windows = windows().lock().expect("auth rate-limit windows poisoned");
-
-    record(&mut windows, format!("ip:{ip}"), now, cutoff);
-    if let Some(id) = identity {
-        record(&mut windows, format!("id:{id}"), now, cutoff);
+/// Take a slot in the source-IP window and, when there is one, the identity
+/// window, refusing with [`Error::TooManyRequests`] when either is already
+/// full. A backend that cannot record the attempt refuses it as well.
+pub async fn attempt(
+    context: &Context,
+    identity: Option<&str>,
+    ip: &str,
+    now: i64,
+) -> AppResult<Attempt> {
+    let mut windows = vec![(ip_key(ip), IP_ATTEMPTS)];
+    windows.extend(identity.map(|id| (identity_key(id), IDENTITY_ATTEMPTS)));
+
+    let mut attempt = Attempt { hits: Vec::new() };
+
+    for (key, limit) in windows {
+        let hit = context
+            .rate_limits
+            .acquire(&key, now, WINDOW_SECONDS, limit)
+            .await;
+
+        match hit {
+            Ok(Some(hit)) => attempt.hits.push((key, hit)),
+            Ok(None) => {
+                attempt.release(context).await?;
+
+                return Err(Error::TooManyRequests("too_many_attempts".to_string()));
+            }
+            Err(e) => {
+                attempt.release(context).await?;
+
+                return Err(e);
+            }
+        }
     }
 
-    if windows.len() > MAX_TRACKED_KEYS {
-        windows.retain(|_, w| w.back().is_some_and(|&t| t >= cutoff));
-    }
-}
-
-#[cfg(any(test, feature = "mock"))]
-pub fn reset_for_tests() {
-    windows()
-        .lock()
-        .expect("auth rate-limit windows poisoned")
-        .clear();
+    Ok(attempt)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use entity::Uuid;
+    use futures_util::future::join_all;
 
-    // Unique keys per test so the shared static never aliases under the
-    // parallel runner; no global reset, no inter-test races.
+    // Unique keys per test, so a test never sees another test's failures.
     fn unique() -> (String, String) {
         (Uuid::new_v4().to_string(), Uuid::new_v4().to_string())
     }
 
-    #[test]
-    fn refuses_once_the_identity_window_is_full() {
+    // An attempt that fails: its slots are never handed back.
+    async fn fail(context: &Context, identity: Option<&str>, ip: &str, now: i64) {
+        drop(attempt(context, identity, ip, now).await.unwrap());
+    }
+
+    #[async_std::test]
+    async fn refuses_once_the_identity_window_is_full() {
+        let context = Context::mock();
         let (id, ip) = unique();
         for _ in 0..IDENTITY_ATTEMPTS {
-            assert!(check(Some(&id), &ip, 0).is_ok());
-            charge_failure(Some(&id), &ip, 0);
+            fail(&context, Some(&id), &ip, 0).await;
         }
-        assert!(check(Some(&id), &ip, 0).is_err());
+        assert!(attempt(&context, Some(&id), &ip, 0).await.is_err());
+
+        // The refused attempt handed back the slot it took in the IP window.
+        let charged = context.rate_limits.count(&ip_key(&ip), 0).await.unwrap();
+        assert_eq!(charged, IDENTITY_ATTEMPTS);
     }
 
-    #[test]
-    fn successes_never_accumulate() {
+    #[async_std::test]
+    async fn successes_never_accumulate() {
+        let context = Context::mock();
         let (id, ip) = unique();
-        // check() is the only call the success path makes; on its own it must
-        // never fill a window no matter how often a user logs in.
+        // A success hands its slots back, so no matter how often a user logs in
+        // the window never fills.
         for _ in 0..(IDENTITY_ATTEMPTS * 5) {
-            assert!(check(Some(&id), &ip, 0).is_ok());
+            let attempt = attempt(&context, Some(&id), &ip, 0).await.unwrap();
+            attempt.succeeded(&context).await.unwrap();
         }
     }
 
-    #[test]
-    fn window_slides_forward() {
+    #[async_std::test]
+    async fn concurrent_attempts_cannot_overshoot_the_window() {
+        let context = Context::mock();
+        let (id, ip) = unique();
+        let attempts = (0..IDENTITY_ATTEMPTS * 3).map(|_| attempt(&context, Some(&id), &ip, 0));
+
+        let admitted = join_all(attempts)
+            .await
+            .into_iter()
+            .filter(Result::is_ok)
+            .count() as u64;
+
+        assert_eq!(admitted, IDENTITY_ATTEMPTS);
+    }
+
+    #[async_std::test]
+    async fn window_slides_forward() {
+        let context = Context::mock();
         let (id, ip) = unique();
         for _ in 0..IDENTITY_ATTEMPTS {
-            charge_failure(Some(&id), &ip, 0);
+            fail(&context, Some(&id), &ip, 0).await;
         }
-        assert!(check(Some(&id), &ip, 0).is_err());
-        assert!(check(Some(&id), &ip, WINDOW_SECONDS + 1).is_ok());
+        assert!(attempt(&context, Some(&id), &ip, 0).await.is_err());
+        assert!(attempt(&context, Some(&id), &ip, WINDOW_SECONDS + 1)
+            .await
+            .is_ok());
     }
 
-    #[test]
-    fn ip_window_trips_independent_of_identity() {
+    #[async_std::test]
+    async fn ip_window_trips_independent_of_identity() {
+        let context = Context::mock();
         let ip = Uuid::new_v4().to_string();
         // Each failure names a different identity, so no identity window fills —
         // only the shared IP window does.
         for _ in 0..IP_ATTEMPTS {
-            charge_failure(Some(&Uuid::new_v4().to_string()), &ip, 0);
+            fail(&context, Some(&Uuid::new_v4().to_string()), &ip, 0).await;
         }
-        assert!(check(None, &ip, 0).is_err());
+        assert!(attempt(&context, None, &ip, 0).await.is_err());
         let (fresh_id, fresh_ip) = unique();
-        assert!(check(Some(&fresh_id), &fresh_ip, 0).is_ok());
+        assert!(attempt(&context, Some(&fresh_id), &fresh_ip, 0)
+            .await
+            .is_ok());
     }
 }
@@ -16,15 +16,18 @@ pub(crate) async fn change_password(
     let auth = Auth::new(&context);
 
     let ip = util::actix::extract_ip_ua(&req).1;
-    let identity = data.email.as_deref().unwrap_or_default().trim().to_lowercase();
+    let identity = data
+        .email
+        .as_deref()
+        .unwrap_or_default()
+        .trim()
+        .to_lowercase();
     let identity = (!identity.is_empty()).then_some(identity.as_str());
     let now = chrono::Utc::now().timestamp();
-    crate::rate_limit::check(identity, &ip, now)?;
+    let attempt = crate::rate_limit::attempt(&context, identity, &ip, now).await?;
 
-    if let Err(e) = auth.change_password(data.into_inner()).await {
-        crate::rate_limit::charge_failure(identity, &ip, now);
-        return Err(e);
-    }
+    auth.change_password(data.into_inner()).await?;
+    attempt.succeeded(&context).await?;
 
     Ok(HttpResponse::NoContent().finish())
 }
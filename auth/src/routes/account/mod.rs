@@ -3,9 +3,11 @@ pub mod change_password;
 pub mod kill;
 pub mod kill_all;
 pub mod patch_me;
+pub mod tokens;
 
 pub use activity::*;
 pub use change_password::*;
 pub use kill::*;
 pub use kill_all::*;
 pub use patch_me::*;
+pub use tokens::*;
@@ -9,11 +9,13 @@ use crate::{auth::Auth, contracts::repository::Repository, data::authenticated::
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct PatchMe {
     pub share_notifications_enabled: Option<bool>,
+    pub login_alerts_enabled: Option<bool>,
 }
 
 /// Apply a partial update to the caller's own user row. The body is a
 /// thin patch object — fields left unset stay untouched. Currently the
-/// only supported field is the share-notifications opt-out flag.
+/// supported fields are the share-notification and login-alert opt-out
+/// flags.
 #[route("/api/users/me", method = "PATCH")]
 pub(crate) async fn patch_me(
     context: web::Data<Context>,
@@ -27,6 +29,9 @@ pub(crate) async fn patch_me(
     if let Some(enabled) = payload.share_notifications_enabled {
         active.share_notifications_enabled = ActiveValue::Set(enabled);
     }
+    if let Some(enabled) = payload.login_alerts_enabled {
+        active.login_alerts_enabled = ActiveValue::Set(enabled);
+    }
     let auth = Auth::new(&context);
     let updated = auth.update_user(authenticated.user.id, active).await?;
     Ok(HttpResponse::Ok().json(updated))
@@ -0,0 +1,59 @@
+use actix_web::{route, web, HttpRequest, HttpResponse};
+use context::Context;
+use entity::Uuid;
+use error::AppResult;
+
+use crate::{
+    auth::Auth,
+    contracts::personal_access_tokens::PersonalAccessTokens,
+    data::{claims::Claims, personal_access_token::CreatePersonalAccessToken},
+};
+
+/// List the user's personal access tokens
+///
+/// Response: [Vec<crate::data::personal_access_token::PersonalAccessToken>]
+#[route("/api/auth/account/tokens", method = "GET")]
+pub(crate) async fn list_tokens(
+    claims: Claims,
+    context: web::Data<Context>,
+) -> AppResult<HttpResponse> {
+    let auth = Auth::new(&context);
+    let tokens = auth.personal_access_tokens(claims.sub).await?;
+
+    Ok(HttpResponse::Ok().json(tokens))
+}
+
+/// Create a personal access token. The token itself is only ever returned
+/// in this response.
+///
+/// Request: [crate::data::personal_access_token::CreatePersonalAccessToken]
+///
+/// Response: [crate::data::personal_access_token::CreatedPersonalAccessToken]
+#[route("/api/auth/account/tokens", method = "POST")]
+pub(crate) async fn create_token(
+    claims: Claims,
+    context: web::Data<Context>,
+    data: web::Json<CreatePersonalAccessToken>,
+) -> AppResult<HttpResponse> {
+    let auth = Auth::new(&context);
+    let token = auth
+        .create_personal_access_token(claims.sub, data.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Created().json(token))
+}
+
+/// Revoke a personal access token
+#[route("/api/auth/account/tokens/{id}/revoke", method = "POST")]
+pub(crate) async fn revoke_token(
+    req: HttpRequest,
+    claims: Claims,
+    context: web::Data<Context>,
+) -> AppResult<HttpResponse> {
+    let auth = Auth::new(&context);
+    let id = util::actix::path_var::<Uuid>(&req, "id")?;
+
+    auth.revoke_personal_access_token(claims.sub, id).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
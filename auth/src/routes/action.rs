@@ -3,7 +3,10 @@ use context::Context;
 use entity::Uuid;
 use error::AppResult;
 
-use crate::{auth::Auth, contracts::register::Register};
+use crate::{
+    auth::Auth,
+    contracts::{register::Register, sessions::Sessions},
+};
 
 /// Activation link in the email will point towards frontend application.
 ///
@@ -11,7 +14,10 @@ use crate::{auth::Auth, contracts::register::Register};
 /// backend with the action `activate-email` and the id of the action,
 /// which will verify users account.
 ///
-/// Response: [entity::users::Model]
+/// The `kill-sessions` action comes from the "this wasn't me" link of a new
+/// device alert and ends every session of the user.
+///
+/// Response: [entity::users::Model] || 204 No Content
 #[route("/api/auth/action/{action}/{id}", method = "POST")]
 pub(crate) async fn action(
     req: HttpRequest,
@@ -27,6 +33,11 @@ pub(crate) async fn action(
 
             Ok(HttpResponse::Ok().json(user))
         }
+        "kill-sessions" => {
+            auth.destroy_all_by_action(id).await?;
+
+            Ok(HttpResponse::NoContent().finish())
+        }
         _ => Err(error::Error::BadRequest(format!("unknown_action:{action}"))),
     }
 }
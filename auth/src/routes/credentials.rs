@@ -23,37 +23,34 @@ pub(crate) async fn credentials(
     let auth = Auth::new(&context);
     let (user_agent, ip) = util::actix::extract_ip_ua(&req);
 
-    let identity = data.email.as_deref().unwrap_or_default().trim().to_lowercase();
+    let identity = data
+        .email
+        .as_deref()
+        .unwrap_or_default()
+        .trim()
+        .to_lowercase();
     let identity = (!identity.is_empty()).then_some(identity.as_str());
     let now = chrono::Utc::now().timestamp();
-    crate::rate_limit::check(identity, &ip, now)?;
+    let attempt = crate::rate_limit::attempt(&context, identity, &ip, now).await?;
 
-    let provider = CredentialsProvider::new(&auth, data.into_inner());
+    let provider = CredentialsProvider::new(&auth, data.into_inner())
+        .oidc_ticket(crate::data::oidc::ticket(&req));
 
-    let authenticated = match provider.authenticate(&user_agent, &ip).await {
-        Ok(authenticated) => authenticated,
-        Err(e) => {
-            crate::rate_limit::charge_failure(identity, &ip, now);
-            return Err(e);
-        }
-    };
+    let mut authenticated = provider.authenticate(&user_agent, &ip).await?;
+    attempt.succeeded(&context).await?;
 
     let mut response = HttpResponse::Ok();
 
-    let (jwt, refresh) = auth.manage_cookies(&authenticated, module_path!())?;
+    let (jwt, refresh) = auth
+        .manage_cookies(&mut authenticated, module_path!())
+        .await?;
 
     if !context.config.auth.use_headers_for_auth {
         response.cookie(jwt);
         response.cookie(refresh);
     } else {
-        response.append_header((
-            "x-auth-jwt".to_string(), 
-            jwt.value()
-        ));
-        response.append_header((
-            "x-auth-refresh".to_string(), 
-            refresh.value()
-        ));
+        response.append_header(("x-auth-jwt".to_string(), jwt.value()));
+        response.append_header(("x-auth-refresh".to_string(), refresh.value()));
     }
 
     Ok(response.json(authenticated))
@@ -0,0 +1,20 @@
+use actix_web::{http::header, route, web, HttpResponse};
+use context::Context;
+use error::AppResult;
+
+use crate::{auth::Auth, contracts::signing_keys::SigningKeys};
+
+/// Public keys that verify the session and transfer tokens this server
+/// signs, looked up by the `kid` in the token header. Retired keys stay
+/// listed while tokens they signed can still be in use, so proxies and other
+/// services can verify Hoodik tokens without holding any secret.
+///
+/// Response: [jsonwebtoken::jwk::JwkSet]
+#[route("/.well-known/jwks.json", method = "GET")]
+pub(crate) async fn jwks(context: web::Data<Context>) -> AppResult<HttpResponse> {
+    let jwks = Auth::new(&context).jwks().await?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header((header::CACHE_CONTROL, "public, max-age=60"))
+        .json(jwks))
+}
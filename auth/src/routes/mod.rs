@@ -14,11 +14,14 @@ const MIGRATION_JSON_LIMIT_BYTES: usize = 4 * 1024 * 1024;
 
 pub mod account;
 pub mod two_factor;
+pub mod webauthn;
 
 pub mod action;
 pub mod authenticated_self;
 pub mod credentials;
+pub mod jwks;
 pub mod logout;
+pub mod oidc;
 pub mod opaque;
 pub mod refresh;
 pub mod register;
@@ -35,15 +38,22 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
     cfg.service(account::kill_all);
     cfg.service(account::kill);
     cfg.service(account::patch_me);
+    cfg.service(account::list_tokens);
+    cfg.service(account::create_token);
+    cfg.service(account::revoke_token);
     cfg.service(action::action);
     cfg.service(authenticated_self::authenticated_self);
     cfg.service(credentials::credentials);
+    cfg.service(jwks::jwks);
     cfg.service(logout::logout);
+    cfg.service(oidc::authorize);
+    cfg.service(oidc::callback);
     cfg.service(opaque::register_start);
     cfg.service(opaque::register_finish);
     cfg.service(opaque::signup_register_start);
     cfg.service(opaque::login_start);
     cfg.service(opaque::login_finish);
+    cfg.service(opaque::login_webauthn);
     cfg.service(opaque::migration_keys);
     // The two migration POSTs carry the re-wrap batches, so give them an explicit
     // per-resource JSON limit instead of the framework default. Set on the
@@ -66,7 +76,13 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
     cfg.service(two_factor::disable_two_factor);
     cfg.service(two_factor::enable_two_factor);
     cfg.service(two_factor::generate_two_factor);
+    cfg.service(two_factor::regenerate_recovery_codes);
     cfg.service(transfer_token::create_transfer_token);
+    cfg.service(webauthn::assertion_start);
+    cfg.service(webauthn::delete_credential);
+    cfg.service(webauthn::list_credentials);
+    cfg.service(webauthn::register_finish);
+    cfg.service(webauthn::register_start);
 
     // Refresh is defined this way because we cannot use constant as path in `web::resource` macro
     cfg.service(web::resource(crate::REFRESH_PATH).route(web::post().to(refresh::refresh)));
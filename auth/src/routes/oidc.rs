@@ -0,0 +1,96 @@
+use actix_web::{
+    cookie::{time::Duration, Cookie, SameSite},
+    http::header,
+    route, web, HttpResponse,
+};
+use context::Context;
+use error::{AppResult, Error, ErrorResponse};
+
+use crate::{
+    auth::Auth,
+    contracts::oidc::Oidc,
+    data::oidc::{Callback, OidcSignIn, TICKET_COOKIE},
+};
+
+/// Send the browser to the identity provider to sign in.
+///
+/// Response: 302 redirect to the provider's authorization endpoint
+#[route("/api/auth/oidc/authorize", method = "GET")]
+pub(crate) async fn authorize(context: web::Data<Context>) -> AppResult<HttpResponse> {
+    let url = Auth::new(&context).oidc_authorize_url().await?;
+
+    Ok(HttpResponse::Found()
+        .insert_header((header::LOCATION, url))
+        .finish())
+}
+
+/// Where the identity provider sends the browser back to. On success the
+/// ticket for the login is stored in a cookie and the browser continues to
+/// the login page, or to registration when the user has no account yet.
+/// Failures land on the login page with `oidc_error` set.
+///
+/// Response: 302 redirect to the web client
+#[route("/api/auth/oidc/callback", method = "GET")]
+pub(crate) async fn callback(
+    context: web::Data<Context>,
+    query: web::Query<Callback>,
+) -> AppResult<HttpResponse> {
+    let client_url = context.config.get_client_url();
+    let query = query.into_inner();
+
+    let result = match (query.error, query.code, query.state) {
+        (None, Some(code), Some(state)) => Auth::new(&context).oidc_callback(&code, state).await,
+        (Some(error), _, _) => Err(Error::Unauthorized(format!("oidc_provider:{error}"))),
+        _ => Err(Error::BadRequest("oidc_invalid_callback".to_string())),
+    };
+
+    let OidcSignIn {
+        ticket,
+        email,
+        registered,
+    } = match result {
+        Ok(sign_in) => sign_in,
+        Err(e) => {
+            let response = ErrorResponse::from(&e);
+            let message = if response.status >= 500 {
+                log::error!("OpenID Connect sign-in failed: {e}");
+
+                "oidc_failed".to_string()
+            } else {
+                response.message
+            };
+
+            return redirect(
+                &format!("{client_url}/auth/login"),
+                &[("oidc_error", &message)],
+            );
+        }
+    };
+
+    let page = if registered { "login" } else { "register" };
+    let mut response = redirect(&format!("{client_url}/auth/{page}"), &[("email", &email)])?;
+
+    let mut cookie = Cookie::build(TICKET_COOKIE, ticket.to_string())
+        .path("/api/auth")
+        .secure(context.config.auth.cookie_secure)
+        .http_only(true)
+        // Lax, so the cookie is kept from a redirect the provider started.
+        .same_site(SameSite::Lax)
+        .max_age(Duration::minutes(10))
+        .finish();
+    cookie.set_domain(context.config.auth.cookie_domain.clone());
+    response
+        .add_cookie(&cookie)
+        .map_err(|_| Error::InternalError("oidc_cookie".to_string()))?;
+
+    Ok(response)
+}
+
+fn redirect(url: &str, params: &[(&str, &str)]) -> AppResult<HttpResponse> {
+    let url = url::Url::parse_with_params(url, params)
+        .map_err(|_| Error::InternalError("invalid_client_url".to_string()))?;
+
+    Ok(HttpResponse::Found()
+        .insert_header((header::LOCATION, url.to_string()))
+        .finish())
+}
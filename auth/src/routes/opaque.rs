@@ -6,11 +6,14 @@ use crate::{
     auth::Auth,
     contracts::{cookies::Cookies, migration::Migration, opaque::Opaque, repository::Repository},
     data::{
+        authenticated::Authenticated,
         claims::Claims,
         opaque::{
-            MigrationComplete, MigrationKeysQuery, OpaqueLoginFinish, OpaqueLoginStart,
-            OpaqueRegisterFinish, OpaqueRegisterStart, RewrapBatch, SignupRegisterStart,
+            LoginFinish, MigrationComplete, MigrationKeysQuery, OpaqueLoginFinish,
+            OpaqueLoginStart, OpaqueRegisterFinish, OpaqueRegisterStart, RewrapBatch,
+            SignupRegisterStart,
         },
+        webauthn::LoginAssertion,
     },
 };
 use entity::Uuid;
@@ -99,11 +102,14 @@ pub(crate) async fn login_start(
     Ok(HttpResponse::Ok().json(response))
 }
 
-/// Finish an OPAQUE login, issuing a session on success.
+/// Finish an OPAQUE login, issuing a session on success. An account with
+/// security keys gets `{"webauthn": LoginChallenge}` instead, and no session
+/// until it is answered at `/api/auth/login/webauthn`.
 ///
 /// Request: [crate::data::opaque::OpaqueLoginFinish]
 ///
-/// Response: [crate::data::authenticated::Authenticated]
+/// Response: [crate::data::authenticated::Authenticated] or
+/// [crate::data::webauthn::LoginChallenge]
 #[route("/api/auth/login/finish", method = "POST")]
 pub(crate) async fn login_finish(
     req: HttpRequest,
@@ -118,21 +124,63 @@ pub(crate) async fn login_finish(
     // The wire carries only a fresh `login_id`, never the email, so the identity
     // window is unavailable — a failed finish is charged to the source IP alone.
     let now = chrono::Utc::now().timestamp();
-    crate::rate_limit::check(None, &ip, now)?;
-
-    let authenticated = match auth
-        .opaque_login_finish(data.login_id, &data.credential_finalization, data.token, &user_agent, &ip)
-        .await
-    {
-        Ok(authenticated) => authenticated,
-        Err(e) => {
-            crate::rate_limit::charge_failure(None, &ip, now);
-            return Err(e);
+    let attempt = crate::rate_limit::attempt(&context, None, &ip, now).await?;
+
+    let finish = auth
+        .opaque_login_finish(
+            data.login_id,
+            &data.credential_finalization,
+            data.token,
+            crate::data::oidc::ticket(&req),
+            &user_agent,
+            &ip,
+        )
+        .await?;
+    attempt.succeeded(&context).await?;
+
+    let authenticated = match finish {
+        LoginFinish::Authenticated(authenticated) => *authenticated,
+        LoginFinish::WebAuthn(challenge) => {
+            return Ok(HttpResponse::Ok().json(serde_json::json!({ "webauthn": challenge })))
         }
     };
 
+    session_response(&context, &auth, authenticated).await
+}
+
+/// Second step of a login for an account with security keys: the assertion
+/// over the challenge `login/finish` returned. Issues the session on success.
+///
+/// Request: [crate::data::webauthn::LoginAssertion]
+///
+/// Response: [crate::data::authenticated::Authenticated]
+#[route("/api/auth/login/webauthn", method = "POST")]
+pub(crate) async fn login_webauthn(
+    req: HttpRequest,
+    context: web::Data<Context>,
+    data: web::Json<LoginAssertion>,
+) -> AppResult<HttpResponse> {
+    let auth = Auth::new(&context);
+    let (user_agent, ip) = util::actix::extract_ip_ua(&req);
+
+    let now = chrono::Utc::now().timestamp();
+    let attempt = crate::rate_limit::attempt(&context, None, &ip, now).await?;
+
+    let authenticated = auth.opaque_login_webauthn(&data, &user_agent, &ip).await?;
+    attempt.succeeded(&context).await?;
+
+    session_response(&context, &auth, authenticated).await
+}
+
+async fn session_response(
+    context: &Context,
+    auth: &Auth<'_>,
+    mut authenticated: Authenticated,
+) -> AppResult<HttpResponse> {
     let mut response = HttpResponse::Ok();
-    let (jwt, refresh) = auth.manage_cookies(&authenticated, module_path!())?;
+    let (jwt, refresh) = auth
+        .manage_cookies(&mut authenticated, module_path!())
+        .await?;
 
     if !context.config.auth.use_headers_for_auth {
         response.cookie(jwt);
@@ -199,7 +247,9 @@ pub(crate) async fn migration_complete(
     data: web::Json<MigrationComplete>,
 ) -> AppResult<HttpResponse> {
     let auth = Auth::new(&context);
-    let user = auth.migration_complete(claims.sub, data.into_inner()).await?;
+    let user = auth
+        .migration_complete(claims.sub, data.into_inner())
+        .await?;
 
     Ok(HttpResponse::Ok().json(user))
 }
@@ -218,7 +268,9 @@ pub(crate) async fn key_transitions(
 ) -> AppResult<HttpResponse> {
     let auth = Auth::new(&context);
     let target = match q.get("user_id") {
-        Some(s) => Uuid::parse_str(s).map_err(|_| Error::BadRequest("invalid_user_id".to_string()))?,
+        Some(s) => {
+            Uuid::parse_str(s).map_err(|_| Error::BadRequest("invalid_user_id".to_string()))?
+        }
         None => claims.sub,
     };
     let rows = auth.list_key_transitions(target).await?;
@@ -5,7 +5,7 @@ use error::{AppResult, Error};
 use crate::{
     auth::Auth,
     contracts::{cookies::Cookies, repository::Repository, sessions::Sessions},
-    data::extractor::Extractor,
+    data::{claims::load_signing_keys, extractor::Extractor},
 };
 
 /// This route behaves same as the [crate::routes::authenticated_self] route,
@@ -19,15 +19,19 @@ pub(crate) async fn refresh(req: HttpRequest) -> AppResult<HttpResponse> {
         .app_data::<web::Data<Context>>()
         .ok_or_else(|| Error::InternalError("missing_context".to_string()))?;
 
-    let _claims = Extractor::default().jwt(context).req(&req)?;
+    let auth = Auth::new(context);
+    load_signing_keys(&req).await?;
+
+    let _claims = Extractor::default().jwt(context).req_expired(&req)?;
 
     let refresh_token = Extractor::default().refresh(context).req(&req)?;
 
-    let auth = Auth::new(context);
     let authenticated = auth.get_by_refresh(refresh_token).await?;
-    let authenticated = auth.refresh(&authenticated.session).await?;
+    let mut authenticated = auth.refresh(&authenticated.session).await?;
 
-    let (jwt, refresh) = auth.manage_cookies(&authenticated, module_path!())?;
+    let (jwt, refresh) = auth
+        .manage_cookies(&mut authenticated, module_path!())
+        .await?;
     let mut response = HttpResponse::Ok();
 
     if !context.config.auth.use_headers_for_auth {
@@ -5,7 +5,7 @@ use validr::Validation;
 
 use crate::{
     auth::Auth,
-    contracts::{cookies::Cookies, ctx::Ctx, register::Register, sessions::Sessions},
+    contracts::{cookies::Cookies, ctx::Ctx, oidc::Oidc, register::Register, sessions::Sessions},
     data::{authenticated::Authenticated, create_user::CreateUser},
 };
 
@@ -29,9 +29,18 @@ pub(crate) async fn register(
     let auth = Auth::new(&context);
     let (user_agent, ip) = util::actix::extract_ip_ua(&req);
 
-    let data = data.into_inner().validate()?;
+    let mut data = data.into_inner().validate()?;
     let email = data.email.clone().unwrap();
 
+    // Behind the OpenID Connect gate the provider already decided who may
+    // register, including through which invitation.
+    let oidc = auth
+        .oidc_take_ticket(&email, crate::data::oidc::ticket(&req))
+        .await?;
+    if let Some(login) = &oidc {
+        data.invitation_id = data.invitation_id.or(login.invitation_id);
+    }
+
     if data.invitation_id.is_none() {
         auth.can_register_or_else(&email, || {
             Err(Error::as_validation("email", "not allowed to register"))
@@ -40,6 +49,10 @@ pub(crate) async fn register(
     }
 
     let user = auth.register(data).await?;
+    let user = match oidc {
+        Some(login) => auth.oidc_apply_role(user, login.role).await?,
+        None => user,
+    };
 
     if context
         .settings
@@ -53,11 +66,17 @@ pub(crate) async fn register(
     }
 
     let session = auth.generate(&user, &user_agent, &ip).await?;
-    let authenticated = Authenticated { user, session };
+    let mut authenticated = Authenticated {
+        user,
+        session,
+        enrollment: None,
+    };
 
     let mut response = HttpResponse::Created();
 
-    let (jwt, refresh) = auth.manage_cookies(&authenticated, module_path!())?;
+    let (jwt, refresh) = auth
+        .manage_cookies(&mut authenticated, module_path!())
+        .await?;
 
     if !context.config.auth.use_headers_for_auth {
         response.cookie(jwt);
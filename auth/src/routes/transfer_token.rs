@@ -3,10 +3,14 @@ use chrono::{Duration, Utc};
 use context::Context;
 use error::AppResult;
 
-use crate::data::{
-    authenticated::Authenticated,
-    transfer_claims::TransferClaims,
-    transfer_token::{CreateTransferToken, TransferTokenResponse},
+use crate::{
+    auth::Auth,
+    contracts::signing_keys::SigningKeys,
+    data::{
+        authenticated::Authenticated,
+        transfer_claims::TransferClaims,
+        transfer_token::{CreateTransferToken, TransferTokenResponse},
+    },
 };
 
 /// Create a long-lived transfer token scoped to a specific file and action.
@@ -37,8 +41,8 @@ pub(crate) async fn create_transfer_token(
         path: format!("{}/{}", action, file_id),
     };
 
-    let token =
-        crate::jwt::generate_transfer_token(&claims, &context.config.auth.jwt_secret)?;
+    let key = Auth::new(&context).current_signing_key().await?;
+    let token = crate::jwt::generate_transfer_token(&claims, &key)?;
 
     Ok(HttpResponse::Ok().json(TransferTokenResponse {
         token,
@@ -8,9 +8,12 @@ use crate::{
     data::{claims::Claims, two_factor::Enable},
 };
 
-/// Enable two factor authentication for the user
+/// Enable two factor authentication for the user, answering with the
+/// recovery codes issued with it
 ///
 /// Request: [Enable]
+///
+/// Response: `{ "recovery_codes": [String] }`
 #[route("/api/auth/two-factor", method = "POST")]
 pub(crate) async fn enable_two_factor(
     context: web::Data<Context>,
@@ -19,8 +22,9 @@ pub(crate) async fn enable_two_factor(
 ) -> AppResult<HttpResponse> {
     let auth = Auth::new(&context);
 
-    auth.enable_two_factor(claims.sub, data.into_inner())
+    let recovery_codes = auth
+        .enable_two_factor(claims.sub, data.into_inner())
         .await?;
 
-    Ok(HttpResponse::NoContent().finish())
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "recovery_codes": recovery_codes })))
 }
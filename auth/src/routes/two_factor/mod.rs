@@ -1,7 +1,9 @@
 pub mod disable_two_factor;
 pub mod enable_two_factor;
 pub mod generate_two_factor;
+pub mod regenerate_recovery_codes;
 
 pub use disable_two_factor::*;
 pub use enable_two_factor::*;
 pub use generate_two_factor::*;
+pub use regenerate_recovery_codes::*;
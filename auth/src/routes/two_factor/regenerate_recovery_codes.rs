@@ -0,0 +1,28 @@
+use actix_web::{route, web, HttpResponse};
+use context::Context;
+use error::AppResult;
+
+use crate::{
+    auth::Auth,
+    contracts::account::Account,
+    data::{claims::Claims, two_factor::RegenerateRecoveryCodes},
+};
+
+/// Replace the user's two factor recovery codes with a fresh set
+///
+/// Request: [RegenerateRecoveryCodes]
+///
+/// Response: `{ "recovery_codes": [String] }`
+#[route("/api/auth/two-factor/recovery-codes", method = "POST")]
+pub(crate) async fn regenerate_recovery_codes(
+    context: web::Data<Context>,
+    claims: Claims,
+    data: web::Json<RegenerateRecoveryCodes>,
+) -> AppResult<HttpResponse> {
+    let auth = Auth::new(&context);
+    let token = data.into_inner().into_value()?;
+
+    let recovery_codes = auth.regenerate_recovery_codes(claims.sub, token).await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "recovery_codes": recovery_codes })))
+}
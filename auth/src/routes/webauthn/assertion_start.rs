@@ -0,0 +1,20 @@
+use actix_web::{route, web, HttpResponse};
+use context::Context;
+use error::AppResult;
+
+use crate::{auth::Auth, contracts::webauthn::WebAuthn, data::claims::Claims};
+
+/// Challenge the user to sign with one of their security keys, confirming
+/// a change to the keys
+///
+/// Response: [crate::data::webauthn::AssertionChallenge]
+#[route("/api/auth/webauthn/assertion/start", method = "POST")]
+pub(crate) async fn assertion_start(
+    context: web::Data<Context>,
+    claims: Claims,
+) -> AppResult<HttpResponse> {
+    let auth = Auth::new(&context);
+    let challenge = auth.webauthn_assertion_start(claims.sub).await?;
+
+    Ok(HttpResponse::Ok().json(challenge))
+}
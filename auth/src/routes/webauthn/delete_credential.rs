@@ -0,0 +1,29 @@
+use actix_web::{route, web, HttpRequest, HttpResponse};
+use context::Context;
+use entity::Uuid;
+use error::AppResult;
+
+use crate::{
+    auth::Auth,
+    contracts::webauthn::WebAuthn,
+    data::{claims::Claims, webauthn::DeleteCredential},
+};
+
+/// Remove one of the user's security keys
+///
+/// Request: [DeleteCredential]
+#[route("/api/auth/webauthn/credentials/{id}/delete", method = "POST")]
+pub(crate) async fn delete_credential(
+    req: HttpRequest,
+    context: web::Data<Context>,
+    claims: Claims,
+    data: web::Json<DeleteCredential>,
+) -> AppResult<HttpResponse> {
+    let auth = Auth::new(&context);
+    let id = util::actix::path_var::<Uuid>(&req, "id")?;
+
+    auth.webauthn_delete(claims.sub, id, data.into_inner())
+        .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
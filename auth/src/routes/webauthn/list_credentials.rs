@@ -0,0 +1,19 @@
+use actix_web::{route, web, HttpResponse};
+use context::Context;
+use error::AppResult;
+
+use crate::{auth::Auth, contracts::webauthn::WebAuthn, data::claims::Claims};
+
+/// List the security keys the user enrolled
+///
+/// Response: [Vec<entity::webauthn_credentials::Model>]
+#[route("/api/auth/webauthn/credentials", method = "GET")]
+pub(crate) async fn list_credentials(
+    context: web::Data<Context>,
+    claims: Claims,
+) -> AppResult<HttpResponse> {
+    let auth = Auth::new(&context);
+    let credentials = auth.webauthn_credentials(claims.sub).await?;
+
+    Ok(HttpResponse::Ok().json(credentials))
+}
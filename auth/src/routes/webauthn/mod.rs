@@ -0,0 +1,11 @@
+pub mod assertion_start;
+pub mod delete_credential;
+pub mod list_credentials;
+pub mod register_finish;
+pub mod register_start;
+
+pub use assertion_start::*;
+pub use delete_credential::*;
+pub use list_credentials::*;
+pub use register_finish::*;
+pub use register_start::*;
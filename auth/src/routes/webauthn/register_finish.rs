@@ -0,0 +1,28 @@
+use actix_web::{route, web, HttpResponse};
+use context::Context;
+use error::AppResult;
+
+use crate::{
+    auth::Auth,
+    contracts::webauthn::WebAuthn,
+    data::{claims::Claims, webauthn::RegisterFinish},
+};
+
+/// Finish enrolling a security key with the authenticator's answer
+///
+/// Request: [RegisterFinish]
+///
+/// Response: [entity::webauthn_credentials::Model]
+#[route("/api/auth/webauthn/register/finish", method = "POST")]
+pub(crate) async fn register_finish(
+    context: web::Data<Context>,
+    claims: Claims,
+    data: web::Json<RegisterFinish>,
+) -> AppResult<HttpResponse> {
+    let auth = Auth::new(&context);
+    let credential = auth
+        .webauthn_register_finish(claims.sub, data.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Created().json(credential))
+}
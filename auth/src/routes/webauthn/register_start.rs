@@ -0,0 +1,19 @@
+use actix_web::{route, web, HttpResponse};
+use context::Context;
+use error::AppResult;
+
+use crate::{auth::Auth, contracts::webauthn::WebAuthn, data::claims::Claims};
+
+/// Begin enrolling a security key for the user
+///
+/// Response: [crate::data::webauthn::RegistrationChallenge]
+#[route("/api/auth/webauthn/register/start", method = "POST")]
+pub(crate) async fn register_start(
+    context: web::Data<Context>,
+    claims: Claims,
+) -> AppResult<HttpResponse> {
+    let auth = Auth::new(&context);
+    let challenge = auth.webauthn_register_start(claims.sub).await?;
+
+    Ok(HttpResponse::Ok().json(challenge))
+}
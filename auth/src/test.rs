@@ -10,6 +10,7 @@ use crate::{
     auth::Auth,
     contracts::{
         cookies::Cookies, provider::AuthProvider, register::Register, repository::Repository,
+        signing_keys::SigningKeys,
     },
     data::{create_user::CreateUser, credentials::Credentials},
     providers::credentials::CredentialsProvider,
@@ -47,6 +48,8 @@ async fn seed_legacy_user(lib: &Auth<'_>, email: &str, password: &str) -> users:
         created_at: ActiveValue::Set(now),
         updated_at: ActiveValue::Set(now),
         share_notifications_enabled: ActiveValue::Set(true),
+        login_alerts_enabled: ActiveValue::Set(true),
+        password_score: ActiveValue::Set(None),
     })
     .await
     .unwrap()
@@ -60,13 +63,17 @@ fn create_curve25519_user(email: &str, password: &str) -> CreateUser {
     let pubkey = cryptfns::ed25519::public::from_private(&ed_private).unwrap();
     let fingerprint = cryptfns::spki::fingerprint(&pubkey).unwrap();
     let wrapping_pubkey =
-        cryptfns::ecdh::public::from_private(&cryptfns::ecdh::private::generate().unwrap()).unwrap();
+        cryptfns::ecdh::public::from_private(&cryptfns::ecdh::private::generate().unwrap())
+            .unwrap();
 
     let server_setup = cryptfns::opaque::server_setup_new();
     let reg_start = cryptfns::opaque::client_registration_start(password.as_bytes()).unwrap();
-    let reg_response =
-        cryptfns::opaque::server_registration_start(&server_setup, &reg_start.message, email.as_bytes())
-            .unwrap();
+    let reg_response = cryptfns::opaque::server_registration_start(
+        &server_setup,
+        &reg_start.message,
+        email.as_bytes(),
+    )
+    .unwrap();
     let reg_finish = cryptfns::opaque::client_registration_finish(
         &reg_start.state,
         &reg_response,
@@ -85,6 +92,7 @@ fn create_curve25519_user(email: &str, password: &str) -> CreateUser {
         wrapping_pubkey: Some(wrapping_pubkey),
         encrypted_private_key: Some("encrypted-gibberish".to_string()),
         opaque_registration_upload: Some(reg_finish.message),
+        password_score: None,
         invitation_id: None,
     }
 }
@@ -95,7 +103,10 @@ async fn auth_create_user() {
     let lib = create_lib(&context);
 
     let response = lib
-        .register(create_curve25519_user("john@doe.com", "very-strong-password"))
+        .register(create_curve25519_user(
+            "john@doe.com",
+            "very-strong-password",
+        ))
         .await;
 
     if let Err(e) = response {
@@ -227,9 +238,10 @@ async fn test_jwt_generate_and_claim() {
 
     let authenticated = response.unwrap();
 
-    let jwt = crate::jwt::generate(&authenticated, module_path!(), "some-secret").unwrap();
+    let keys = auth.signing_keys().await.unwrap();
+    let jwt = crate::jwt::generate(&authenticated, module_path!(), &keys[0]).unwrap();
 
-    let response = crate::jwt::extract(&jwt, "some-secret");
+    let response = crate::jwt::extract(&jwt, &keys, None);
 
     if let Err(e) = response {
         panic!("Errored: {:#?}", e);
@@ -243,7 +255,10 @@ async fn test_register_and_send_email() {
     let auth = create_lib(&context);
 
     let response = auth
-        .register(create_curve25519_user("john@doe.com", "very-strong-password"))
+        .register(create_curve25519_user(
+            "john@doe.com",
+            "very-strong-password",
+        ))
         .await;
 
     if let Err(e) = response {
@@ -267,7 +282,10 @@ async fn test_activate_user() {
     let auth = create_lib(&context);
 
     let response = auth
-        .register(create_curve25519_user("john@doe.com", "very-strong-password"))
+        .register(create_curve25519_user(
+            "john@doe.com",
+            "very-strong-password",
+        ))
         .await;
 
     if let Err(e) = response {
@@ -304,12 +322,15 @@ async fn test_set_cookie_for_both() {
         token: None,
     };
     let credentials_provider = CredentialsProvider::new(&auth, credentials);
-    let authenticated = credentials_provider
+    let mut authenticated = credentials_provider
         .authenticate("n/a", "127.0.0.1")
         .await
         .unwrap();
 
-    let (jwt, refresh) = auth.manage_cookies(&authenticated, module_path!()).unwrap();
+    let (jwt, refresh) = auth
+        .manage_cookies(&mut authenticated, module_path!())
+        .await
+        .unwrap();
 
     let mut res = HttpResponse::Ok();
 
@@ -0,0 +1,481 @@
+//! `hoodik-cli`, the Hoodik account from a shell or a CI pipeline.
+//!
+//! Signs in with the account password over OPAQUE, or with the private key
+//! file, and does all encryption on the machine it runs on through the
+//! `client` crate, the same way the web app does in the browser.
+use std::path::{Path, PathBuf};
+
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use client::{tree::segments, Entry, Error, KeyPair, LinkOptions, Result, Role, Session};
+
+pub fn command() -> Command {
+    Command::new("hoodik-cli")
+        .version(env!("CARGO_PKG_VERSION"))
+        .about("Work with a Hoodik account, encrypting and decrypting on this machine")
+        .subcommand_required(true)
+        .arg(
+            Arg::new("url")
+                .long("url")
+                .env("HOODIK_URL")
+                .help("Address of the Hoodik instance, for example https://hoodik.example.com")
+                .required(true),
+        )
+        .arg(
+            Arg::new("email")
+                .long("email")
+                .env("HOODIK_EMAIL")
+                .help("Email of the account, to sign in with the password"),
+        )
+        .arg(
+            Arg::new("password")
+                .long("password")
+                .env("HOODIK_PASSWORD")
+                .hide_env_values(true)
+                .help("Password of the account, it never leaves this machine"),
+        )
+        .arg(
+            Arg::new("otp")
+                .long("otp")
+                .env("HOODIK_OTP")
+                .help("Current two factor code, for accounts that have it enabled"),
+        )
+        .arg(
+            Arg::new("key")
+                .long("key")
+                .env("HOODIK_KEY_FILE")
+                .help("File with the account's private key, instead of the email and password")
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .subcommand(
+            Command::new("ls")
+                .about("List a folder")
+                .arg(Arg::new("path").help("Folder to list").default_value("/")),
+        )
+        .subcommand(
+            Command::new("upload")
+                .about("Upload files and folders into a folder, creating it when missing")
+                .arg(
+                    Arg::new("local")
+                        .help("Local files or folders to upload")
+                        .value_parser(value_parser!(PathBuf))
+                        .num_args(1..)
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("remote")
+                        .help("Folder to upload into")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("replace")
+                        .long("replace")
+                        .help("Replace files that already exist instead of failing")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("download")
+                .about("Download a file or a whole folder")
+                .arg(
+                    Arg::new("remote")
+                        .help("File or folder to download")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("local")
+                        .help("Where to save it, into it when it is an existing folder")
+                        .value_parser(value_parser!(PathBuf))
+                        .default_value("."),
+                ),
+        )
+        .subcommand(
+            Command::new("mkdir")
+                .about("Create a folder along with any missing parents")
+                .arg(Arg::new("path").required(true)),
+        )
+        .subcommand(
+            Command::new("mv")
+                .about("Move or rename a file or folder")
+                .arg(Arg::new("source").required(true))
+                .arg(
+                    Arg::new("destination")
+                        .help("New path, or an existing folder to move into")
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            Command::new("cp")
+                .about("Copy a file or a folder with everything in it, on the server")
+                .arg(Arg::new("source").required(true))
+                .arg(
+                    Arg::new("destination")
+                        .help("New path, or an existing folder to copy into")
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            Command::new("rm")
+                .about("Delete a file or a folder with everything in it")
+                .arg(Arg::new("path").required(true)),
+        )
+        .subcommand(
+            Command::new("share")
+                .about("Share a file or folder with another account")
+                .arg(Arg::new("path").required(true))
+                .arg(
+                    Arg::new("email")
+                        .help("Email of the account to share with")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("role")
+                        .long("role")
+                        .value_parser(["reader", "editor", "co-owner"])
+                        .default_value("reader"),
+                )
+                .arg(
+                    Arg::new("expires_at")
+                        .long("expires-at")
+                        .help("Unix timestamp at which the account loses access")
+                        .value_parser(value_parser!(i64)),
+                ),
+        )
+        .subcommand(
+            Command::new("link")
+                .about("Public links")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("create")
+                        .about("Create a public link to a file or folder and print its URL")
+                        .arg(Arg::new("path").required(true))
+                        .arg(
+                            Arg::new("expires_at")
+                                .long("expires-at")
+                                .help("Unix timestamp after which the link stops working")
+                                .value_parser(value_parser!(i64)),
+                        )
+                        .arg(
+                            Arg::new("max_downloads")
+                                .long("max-downloads")
+                                .help("Completed downloads after which the link stops working")
+                                .value_parser(value_parser!(i32).range(1..)),
+                        )
+                        .arg(
+                            Arg::new("password")
+                                .long("password")
+                                .help("Password recipients have to give before downloading"),
+                        ),
+                ),
+        )
+}
+
+/// Sign in and run the subcommand in `matches`.
+pub async fn run(matches: &ArgMatches) -> Result<()> {
+    let session = sign_in(matches).await?;
+
+    match matches.subcommand() {
+        Some(("ls", args)) => ls(&session, arg(args, "path")).await,
+        Some(("upload", args)) => {
+            let parent = session.create_dirs(arg(args, "remote")).await?;
+            let replace = args.get_flag("replace");
+
+            for local in args.get_many::<PathBuf>("local").unwrap_or_default() {
+                upload(&session, local, parent.as_deref(), replace).await?;
+            }
+
+            Ok(())
+        }
+        Some(("download", args)) => {
+            let remote = arg(args, "remote");
+            let entry = existing(&session, remote).await?;
+            let local = args.get_one::<PathBuf>("local").unwrap();
+
+            download(&session, &entry, local).await
+        }
+        Some(("mkdir", args)) => session.create_dirs(arg(args, "path")).await.map(|_| ()),
+        Some(("mv", args)) => mv(&session, arg(args, "source"), arg(args, "destination")).await,
+        Some(("cp", args)) => cp(&session, arg(args, "source"), arg(args, "destination")).await,
+        Some(("rm", args)) => {
+            let entry = existing(&session, arg(args, "path")).await?;
+
+            session.delete(&entry.id).await
+        }
+        Some(("share", args)) => {
+            let entry = existing(&session, arg(args, "path")).await?;
+            let role = arg(args, "role").parse::<Role>()?;
+
+            let expires_at = args.get_one::<i64>("expires_at").copied();
+
+            session
+                .share(&entry, arg(args, "email"), role, expires_at)
+                .await
+        }
+        Some(("link", args)) => match args.subcommand() {
+            Some(("create", args)) => {
+                let entry = existing(&session, arg(args, "path")).await?;
+                let options = LinkOptions {
+                    expires_at: args.get_one::<i64>("expires_at").copied(),
+                    max_downloads: args.get_one::<i32>("max_downloads").copied(),
+                    password: args.get_one::<String>("password").cloned(),
+                };
+                let link = session.create_link(&entry, &options).await?;
+                println!("{}", link.url);
+
+                Ok(())
+            }
+            _ => unreachable!("link requires a subcommand"),
+        },
+        _ => unreachable!("a subcommand is required"),
+    }
+}
+
+async fn sign_in(matches: &ArgMatches) -> Result<Session> {
+    let url = arg(matches, "url");
+
+    if let Some(key) = matches.get_one::<PathBuf>("key") {
+        return Session::login_with_key(url, KeyPair::from_file(key)?).await;
+    }
+
+    match (
+        matches.get_one::<String>("email"),
+        matches.get_one::<String>("password"),
+    ) {
+        (Some(email), Some(password)) => {
+            let otp = matches.get_one::<String>("otp").map(|otp| otp.as_str());
+
+            Session::login_with_password(url, email, password, otp).await
+        }
+        _ => Err(Error::Invalid(
+            "Pass --email and --password, or --key to sign in".to_string(),
+        )),
+    }
+}
+
+async fn ls(session: &Session, path: &str) -> Result<()> {
+    let entries = match segments(path).is_empty() {
+        true => session.list(None).await?,
+        false => {
+            let entry = existing(session, path).await?;
+
+            match entry.is_dir() {
+                true => session.list(Some(&entry.id)).await?,
+                false => vec![entry],
+            }
+        }
+    };
+
+    for entry in entries {
+        let modified = chrono::DateTime::from_timestamp(entry.modified_at, 0)
+            .map(|time| time.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default();
+
+        match entry.is_dir() {
+            true => println!("d {:>12} {modified} {}/", "-", entry.name),
+            false => println!("- {:>12} {modified} {}", entry.size, entry.name),
+        }
+    }
+
+    Ok(())
+}
+
+/// Upload the file or folder at `local` into `parent`.
+pub async fn upload(
+    session: &Session,
+    local: &Path,
+    parent: Option<&str>,
+    replace: bool,
+) -> Result<()> {
+    let name = file_name(local)?;
+
+    if !local.is_dir() {
+        let listing = session.list(parent).await?;
+
+        return upload_file(session, local, parent, &name, &listing, replace).await;
+    }
+
+    let mut pending = vec![(local.to_path_buf(), folder(session, parent, &name).await?)];
+
+    while let Some((path, folder_id)) = pending.pop() {
+        let listing = session.list(Some(&folder_id)).await?;
+
+        let mut children = std::fs::read_dir(&path)?
+            .map(|child| child.map(|child| child.path()))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        children.sort();
+
+        for child in children {
+            let name = file_name(&child)?;
+
+            if child.is_dir() {
+                let id = match listing.iter().find(|entry| entry.name == name) {
+                    Some(entry) if entry.is_dir() => entry.id.clone(),
+                    Some(_) => return Err(exists(&name)),
+                    None => session.create_dir(Some(&folder_id), &name).await?.id,
+                };
+                pending.push((child, id));
+            } else {
+                upload_file(session, &child, Some(&folder_id), &name, &listing, replace).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn upload_file(
+    session: &Session,
+    local: &Path,
+    parent: Option<&str>,
+    name: &str,
+    listing: &[Entry],
+    replace: bool,
+) -> Result<()> {
+    if std::fs::metadata(local)?.len() == 0 {
+        log::warn!("Skipping {}, empty files are not stored", local.display());
+
+        return Ok(());
+    }
+
+    let Some(existing) = listing.iter().find(|entry| entry.name == name) else {
+        session.upload(parent, name, local).await?;
+        println!("{}", local.display());
+
+        return Ok(());
+    };
+
+    if existing.is_dir() {
+        return Err(exists(name));
+    }
+    if !replace {
+        return Err(Error::Invalid(format!(
+            "{name} already exists, pass --replace to overwrite it"
+        )));
+    }
+
+    session.replace(existing, local).await?;
+    println!("{}", local.display());
+
+    Ok(())
+}
+
+/// Download the file or folder `entry` to `local`, or into it when it is
+/// an existing folder.
+pub async fn download(session: &Session, entry: &Entry, local: &Path) -> Result<()> {
+    let target = match local.is_dir() {
+        true => local.join(&entry.name),
+        false => local.to_path_buf(),
+    };
+
+    if !entry.is_dir() {
+        session.download(entry, &target).await?;
+        println!("{}", target.display());
+
+        return Ok(());
+    }
+
+    tokio::fs::create_dir_all(&target).await?;
+    for (path, child) in session.walk(Some(&entry.id)).await? {
+        let path = target.join(path);
+
+        if child.is_dir() {
+            tokio::fs::create_dir_all(&path).await?;
+        } else {
+            session.download(&child, &path).await?;
+            println!("{}", path.display());
+        }
+    }
+
+    Ok(())
+}
+
+async fn mv(session: &Session, source: &str, destination: &str) -> Result<()> {
+    let entry = existing(session, source).await?;
+
+    if segments(destination).is_empty() {
+        return session.move_many(&[entry.id], None).await;
+    }
+
+    match session.find(destination).await? {
+        Some(folder) if folder.is_dir() => session.move_many(&[entry.id], Some(&folder.id)).await,
+        Some(_) => Err(exists(destination)),
+        None => {
+            let mut segments = segments(destination);
+            let name = segments.pop().unwrap_or_default();
+            let parent = session.find_dir(&segments.join("/")).await?;
+
+            if parent != entry.parent {
+                session
+                    .move_many(std::slice::from_ref(&entry.id), parent.as_deref())
+                    .await?;
+            }
+            if name != entry.name {
+                session.rename(&entry, name).await?;
+            }
+
+            Ok(())
+        }
+    }
+}
+
+async fn cp(session: &Session, source: &str, destination: &str) -> Result<()> {
+    let entry = existing(session, source).await?;
+
+    if segments(destination).is_empty() {
+        return session.copy(&entry, None, &entry.name).await.map(|_| ());
+    }
+
+    let (parent, name) = match session.find(destination).await? {
+        Some(folder) if folder.is_dir() => (Some(folder.id), entry.name.as_str()),
+        Some(_) => return Err(exists(destination)),
+        None => {
+            let mut segments = segments(destination);
+            let name = segments.pop().unwrap_or_default();
+
+            (session.find_dir(&segments.join("/")).await?, name)
+        }
+    };
+
+    session.copy(&entry, parent.as_deref(), name).await?;
+
+    Ok(())
+}
+
+/// A folder called `name` in `parent`, created when it is not there yet.
+async fn folder(session: &Session, parent: Option<&str>, name: &str) -> Result<String> {
+    match session
+        .list(parent)
+        .await?
+        .into_iter()
+        .find(|entry| entry.name == name)
+    {
+        Some(entry) if entry.is_dir() => Ok(entry.id),
+        Some(_) => Err(exists(name)),
+        None => Ok(session.create_dir(parent, name).await?.id),
+    }
+}
+
+async fn existing(session: &Session, path: &str) -> Result<Entry> {
+    session
+        .find(path)
+        .await?
+        .ok_or_else(|| Error::NotFound(path.to_string()))
+}
+
+fn arg<'a>(matches: &'a ArgMatches, name: &str) -> &'a str {
+    matches
+        .get_one::<String>(name)
+        .map(|value| value.as_str())
+        .unwrap_or_default()
+}
+
+fn file_name(path: &Path) -> Result<String> {
+    path.canonicalize()?
+        .file_name()
+        .and_then(|name| name.to_str())
+        .map(|name| name.to_string())
+        .ok_or_else(|| Error::Invalid(format!("{} has no usable name", path.display())))
+}
+
+fn exists(name: &str) -> Error {
+    Error::Invalid(format!("{name} already exists"))
+}
@@ -0,0 +1,14 @@
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    if std::env::var("RUST_LOG").is_err() {
+        std::env::set_var("RUST_LOG", "warn");
+    }
+    env_logger::init();
+
+    let matches = cli::command().get_matches();
+
+    if let Err(e) = cli::run(&matches).await {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+}
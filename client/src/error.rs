@@ -0,0 +1,75 @@
+use std::fmt;
+
+#[derive(Debug)]
+pub enum Error {
+    /// The server answered with an error status.
+    Http {
+        status: u16,
+        message: String,
+    },
+    /// The request never got an answer.
+    Request(String),
+    Crypto(String),
+    Io(String),
+    Transfer(transfer::error::Error),
+    /// A path or file the caller asked for does not exist.
+    NotFound(String),
+    /// Input the client refuses before asking the server.
+    Invalid(String),
+}
+
+impl Error {
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            Error::Http { status, .. } => Some(*status),
+            Error::Transfer(transfer::error::Error::Http(e)) => Some(e.status),
+            _ => None,
+        }
+    }
+
+    pub fn is_unauthorized(&self) -> bool {
+        self.status() == Some(401)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Http { status, message } => write!(f, "HTTP {status}: {message}"),
+            Error::Request(msg) => write!(f, "Request failed: {msg}"),
+            Error::Crypto(msg) => write!(f, "Crypto error: {msg}"),
+            Error::Io(msg) => write!(f, "IO error: {msg}"),
+            Error::Transfer(e) => write!(f, "Transfer failed: {e}"),
+            Error::NotFound(what) => write!(f, "Not found: {what}"),
+            Error::Invalid(msg) => write!(f, "{msg}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<cryptfns::error::Error> for Error {
+    fn from(e: cryptfns::error::Error) -> Self {
+        Error::Crypto(format!("{e}"))
+    }
+}
+
+impl From<transfer::error::Error> for Error {
+    fn from(e: transfer::error::Error) -> Self {
+        Error::Transfer(e)
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        Error::Request(format!("{e}"))
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(format!("{e}"))
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
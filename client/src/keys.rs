@@ -0,0 +1,163 @@
+//! The user's private keys, as backed up from the web app.
+//!
+//! A Curve25519 account backs up the bundle `v1|ed:<PEM>|x:<PEM>`, with an
+//! extra `rsa:<PEM>` segment for accounts migrated from RSA. A legacy RSA
+//! account backs up just its RSA private key PEM. Both are accepted.
+use std::path::Path;
+
+use crate::error::{Error, Result};
+
+pub enum KeyPair {
+    Curve25519 {
+        /// Ed25519 identity key, signs logins.
+        identity: String,
+        identity_public: String,
+        /// X25519 + ML-KEM wrapping key, unwraps file keys.
+        wrapping: String,
+        wrapping_public: String,
+        /// RSA key the account had before migrating, for file keys still
+        /// wrapped to it.
+        rsa: Option<String>,
+    },
+    Rsa {
+        private: String,
+        public: String,
+    },
+}
+
+impl KeyPair {
+    /// Parse a backed up private key bundle or RSA PEM.
+    pub fn from_material(material: &str) -> Result<Self> {
+        let material = material.trim();
+
+        if !material.starts_with("v1") {
+            let private = cryptfns::rsa::private::from_str(material)?;
+            let public = cryptfns::rsa::public::from_private(&private)?;
+
+            return Ok(Self::Rsa {
+                private: material.to_string(),
+                public: cryptfns::rsa::public::to_string(&public)?,
+            });
+        }
+
+        let mut identity = None;
+        let mut wrapping = None;
+        let mut rsa = None;
+        for part in material.split('|') {
+            if let Some(pem) = part.strip_prefix("ed:") {
+                identity = Some(pem.to_string());
+            } else if let Some(pem) = part.strip_prefix("x:") {
+                wrapping = Some(pem.to_string());
+            } else if let Some(pem) = part.strip_prefix("rsa:") {
+                rsa = Some(pem.to_string());
+            }
+        }
+
+        let (identity, wrapping) = identity.zip(wrapping).ok_or_else(|| {
+            Error::Invalid("Key bundle is missing its ed: or x: part".to_string())
+        })?;
+
+        Ok(Self::Curve25519 {
+            identity_public: cryptfns::ed25519::public::from_private(&identity)?,
+            wrapping_public: cryptfns::ecdh::public::from_private(&wrapping)?,
+            identity,
+            wrapping,
+            rsa,
+        })
+    }
+
+    /// Read the key from a file holding the backed up material.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let material = std::fs::read_to_string(path)
+            .map_err(|e| Error::Io(format!("Cannot read key {}: {e}", path.display())))?;
+
+        Self::from_material(&material)
+    }
+
+    /// Fingerprint the server knows the account by.
+    pub fn fingerprint(&self) -> Result<String> {
+        match self {
+            Self::Curve25519 {
+                identity_public, ..
+            } => Ok(cryptfns::ed25519::fingerprint(identity_public)?),
+            Self::Rsa { public, .. } => Ok(cryptfns::rsa::fingerprint(
+                cryptfns::rsa::public::from_str(public)?,
+            )?),
+        }
+    }
+
+    /// Sign a login canonical with the identity key.
+    pub fn sign(&self, message: &str) -> Result<String> {
+        match self {
+            Self::Curve25519 { identity, .. } => {
+                Ok(cryptfns::ed25519::private::sign(message, identity)?)
+            }
+            Self::Rsa { private, .. } => Ok(cryptfns::rsa::private::sign(message, private)?),
+        }
+    }
+
+    /// Sign raw bytes, such as the DER payloads of share requests.
+    pub fn sign_bytes(&self, message: &[u8]) -> Result<String> {
+        match self {
+            Self::Curve25519 { identity, .. } => {
+                Ok(cryptfns::ed25519::private::sign_bytes(message, identity)?)
+            }
+            Self::Rsa { private, .. } => Ok(cryptfns::rsa::private::sign_bytes(message, private)?),
+        }
+    }
+
+    /// Wrap a new file key to this account, the way the web app does.
+    pub fn wrap(&self, file_key: &[u8]) -> Result<String> {
+        match self {
+            Self::Curve25519 {
+                wrapping_public, ..
+            } => Ok(cryptfns::ecdh::wrap(file_key, wrapping_public)?),
+            Self::Rsa { public, .. } => Ok(cryptfns::rsa::public::encrypt(
+                &hex::encode(file_key),
+                public,
+            )?),
+        }
+    }
+
+    /// Recover a file key from the `encrypted_key` the server returns.
+    pub fn unwrap(&self, encrypted_key: &str) -> Result<Vec<u8>> {
+        match self {
+            Self::Curve25519 { wrapping, rsa, .. } => {
+                match (cryptfns::ecdh::unwrap(encrypted_key, wrapping), rsa) {
+                    (Ok(key), _) => Ok(key),
+                    (Err(_), Some(rsa)) => rsa_unwrap(encrypted_key, rsa),
+                    (Err(e), None) => Err(e.into()),
+                }
+            }
+            Self::Rsa { private, .. } => rsa_unwrap(encrypted_key, private),
+        }
+    }
+}
+
+/// Wrap a file key for another account, given the keys the server
+/// publishes for it.
+pub fn wrap_for(
+    file_key: &[u8],
+    key_type: &str,
+    pubkey: &str,
+    wrapping_pubkey: Option<&str>,
+) -> Result<String> {
+    match key_type {
+        "curve25519" => {
+            let wrapping_pubkey = wrapping_pubkey
+                .ok_or_else(|| Error::Invalid("The recipient has no wrapping key".to_string()))?;
+
+            Ok(cryptfns::ecdh::wrap(file_key, wrapping_pubkey)?)
+        }
+        _ => Ok(cryptfns::rsa::public::encrypt(
+            &hex::encode(file_key),
+            pubkey,
+        )?),
+    }
+}
+
+fn rsa_unwrap(encrypted_key: &str, private: &str) -> Result<Vec<u8>> {
+    let key = cryptfns::rsa::private::decrypt(encrypted_key, private)?;
+
+    hex::decode(key).map_err(|e| Error::Crypto(format!("{e}")))
+}
@@ -0,0 +1,20 @@
+//! Native client for the Hoodik API.
+//!
+//! Signs in with the user's private key and keeps it on the machine it runs
+//! on: file keys are unwrapped, names decrypted and chunks encrypted locally,
+//! through the same `transfer` pipelines the web app uses.
+pub mod error;
+pub mod keys;
+pub mod links;
+pub mod session;
+pub mod shares;
+pub mod storage;
+pub mod tree;
+pub mod worker;
+
+pub use error::{Error, Result};
+pub use keys::KeyPair;
+pub use links::{Link, LinkOptions};
+pub use session::Session;
+pub use shares::Role;
+pub use storage::Entry;
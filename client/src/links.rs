@@ -0,0 +1,98 @@
+//! Public links. The link key travels only in the fragment of the URL, the
+//! server stores the name and file key encrypted under it. A link to a
+//! folder carries the name and key of everything in it the same way.
+use std::str::FromStr;
+
+use cryptfns::cipher::Cipher;
+use reqwest::Method;
+use serde::Deserialize;
+
+use crate::{error::Result, session::Session, storage::Entry};
+
+/// The cipher link metadata is encrypted with, same as the web app.
+const LINK_CIPHER: &str = "ascon128a";
+
+/// A link as the server returns it after creating it.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Link {
+    pub id: String,
+    pub file_id: String,
+    pub expires_at: Option<i64>,
+    pub max_downloads: Option<i32>,
+    #[serde(default)]
+    pub has_password: bool,
+    /// Full URL with the link key in the fragment, not sent by the server.
+    #[serde(skip)]
+    pub url: String,
+}
+
+/// Restrictions on a new link, none by default.
+#[derive(Debug, Clone, Default)]
+pub struct LinkOptions {
+    /// Unix timestamp after which the link stops working.
+    pub expires_at: Option<i64>,
+    /// Completed downloads after which the link stops serving the file.
+    pub max_downloads: Option<i32>,
+    /// Password recipients have to give on top of the key in the URL.
+    pub password: Option<String>,
+}
+
+impl Session {
+    /// Create a public link to the file or folder `entry` with the given
+    /// restrictions.
+    pub async fn create_link(&self, entry: &Entry, options: &LinkOptions) -> Result<Link> {
+        let cipher = Cipher::from_str(LINK_CIPHER)?;
+        let link_key = cipher.generate_key()?;
+        let encrypt = |value: String| -> Result<String> {
+            Ok(hex::encode(
+                cipher.encrypt_string(link_key.clone(), value.into())?,
+            ))
+        };
+
+        let mut body = serde_json::json!({
+            "file_id": entry.id,
+            "signature": self.keys().sign(&entry.id)?,
+            "encrypted_link_key": self.keys().wrap(&link_key)?,
+            "encrypted_name": encrypt(entry.name.clone())?,
+            "expires_at": options.expires_at,
+            "max_downloads": options.max_downloads,
+            "password": options.password,
+        });
+
+        if entry.is_dir() {
+            let files = self
+                .walk(Some(&entry.id))
+                .await?
+                .into_iter()
+                .map(|(_, file)| {
+                    Ok(serde_json::json!({
+                        "file_id": file.id,
+                        "encrypted_name": encrypt(file.name.clone())?,
+                        "encrypted_file_key": match file.is_dir() {
+                            true => None,
+                            false => Some(encrypt(hex::encode(&file.key))?),
+                        },
+                    }))
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            body["files"] = files.into();
+        } else {
+            body["encrypted_file_key"] = encrypt(hex::encode(&entry.key))?.into();
+        }
+
+        let mut link: Link = self
+            .send(Method::POST, "/api/links", |request| request.json(&body))
+            .await?
+            .json()
+            .await?;
+        link.url = format!(
+            "{}/l/{}#{}",
+            self.base_url(),
+            link.id,
+            hex::encode(&link_key)
+        );
+
+        Ok(link)
+    }
+}
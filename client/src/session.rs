@@ -0,0 +1,398 @@
+//! Signing in and keeping the session alive.
+//!
+//! Sessions are short lived, two minutes by default, so every request goes
+//! out with a session refreshed when it is getting old. When the server
+//! still says no, the client signs in with the private key again, also when
+//! the first login was done with a password.
+use std::{
+    collections::BTreeMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use reqwest::{header::HeaderMap, Method, RequestBuilder, Response};
+use serde::Deserialize;
+use tokio::sync::Mutex;
+use transfer::{native::http::NativeHttpClient, types::Auth};
+
+use crate::{
+    error::{Error, Result},
+    keys::KeyPair,
+    worker::Transfers,
+};
+
+/// Sessions older than this are refreshed before they are used.
+const REFRESH_AFTER: Duration = Duration::from_secs(60);
+
+/// Idle connections are dropped after this, well before the server closes
+/// them (actix keeps them for five seconds). Reusing one the server is
+/// closing fails the request with "connection closed before message
+/// completed", e.g. after the seconds Argon2 takes in a password login.
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// The signed in user, as returned by the login endpoints.
+#[derive(Debug, Clone, Deserialize)]
+pub struct User {
+    pub id: String,
+    pub email: String,
+    pub quota: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct Authenticated {
+    user: User,
+}
+
+/// The login answer with the private key envelope, sealed under the
+/// password's OPAQUE export key.
+#[derive(Deserialize)]
+struct Unsealed {
+    user: EnvelopeHolder,
+}
+
+#[derive(Deserialize)]
+struct EnvelopeHolder {
+    encrypted_private_key: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "method", rename_all = "lowercase")]
+enum LoginStart {
+    Password,
+    Opaque {
+        login_id: String,
+        credential_response: String,
+        ksf: Ksf,
+    },
+}
+
+#[derive(Deserialize)]
+struct Ksf {
+    m_cost: u32,
+    t_cost: u32,
+    p_cost: u32,
+}
+
+struct Credentials {
+    auth: Auth,
+    cookies: BTreeMap<String, String>,
+    refreshed_at: Instant,
+}
+
+/// An authenticated connection to one Hoodik instance.
+#[derive(Clone)]
+pub struct Session {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    base_url: String,
+    http: reqwest::Client,
+    keys: KeyPair,
+    user: User,
+    credentials: Mutex<Credentials>,
+    transfers: Transfers,
+}
+
+impl Session {
+    /// Sign in to `base_url` with the private key, no password needed.
+    pub async fn login_with_key(base_url: &str, keys: KeyPair) -> Result<Self> {
+        let base_url = base_url.trim_end_matches('/').to_string();
+        let http = http_client()?;
+        let (user, credentials) = sign_in(&http, &base_url, &keys).await?;
+
+        Self::new(base_url, http, keys, user, credentials)
+    }
+
+    /// Sign in to `base_url` with the account password over OPAQUE and
+    /// unseal the private key from its envelope. The password never leaves
+    /// the machine. `token` is the current TOTP code for accounts with two
+    /// factor authentication.
+    pub async fn login_with_password(
+        base_url: &str,
+        email: &str,
+        password: &str,
+        token: Option<&str>,
+    ) -> Result<Self> {
+        let base_url = base_url.trim_end_matches('/').to_string();
+        let http = http_client()?;
+        let start = cryptfns::opaque::client_login_start(password.as_bytes())?;
+
+        let response = http
+            .post(format!("{base_url}/api/auth/login/start"))
+            .json(&serde_json::json!({
+                "email": email,
+                "credential_request": start.message,
+            }))
+            .send()
+            .await?;
+        let (login_id, credential_response, ksf) = match check(response).await?.json().await? {
+            LoginStart::Opaque {
+                login_id,
+                credential_response,
+                ksf,
+            } => (login_id, credential_response, ksf),
+            LoginStart::Password => {
+                return Err(Error::Invalid(
+                    "The account has not been migrated to password authenticated login yet, sign in once with the web app or use the private key".to_string(),
+                ))
+            }
+        };
+
+        // Argon2 with the account's parameters takes seconds, keep it off
+        // the runtime.
+        let state = start.state;
+        let secret = password.as_bytes().to_vec();
+        let finish = tokio::task::spawn_blocking(move || {
+            cryptfns::opaque::client_login_finish_with_params(
+                &state,
+                &credential_response,
+                &secret,
+                ksf.m_cost,
+                ksf.t_cost,
+                ksf.p_cost,
+            )
+        })
+        .await
+        .map_err(|e| Error::Crypto(format!("{e}")))?
+        .map_err(|_| Error::Invalid("Invalid email or password".to_string()))?;
+
+        let response = http
+            .post(format!("{base_url}/api/auth/login/finish"))
+            .json(&serde_json::json!({
+                "login_id": login_id,
+                "credential_finalization": finish.finalization,
+                "token": token,
+            }))
+            .send()
+            .await?;
+        let response = check(response).await?;
+        let credentials = credentials_from(&base_url, BTreeMap::new(), response.headers());
+        let body: serde_json::Value = response.json().await?;
+        if body.get("webauthn").is_some() {
+            return Err(Error::Invalid(
+                "The account signs in with a security key, sign in with the web app or use the private key".to_string(),
+            ));
+        }
+
+        let Unsealed { user: holder } = serde_json::from_value(body.clone())
+            .map_err(|e| Error::Invalid(format!("Unexpected login response: {e}")))?;
+        let Authenticated { user } = serde_json::from_value(body)
+            .map_err(|e| Error::Invalid(format!("Unexpected login response: {e}")))?;
+
+        let envelope = holder
+            .encrypted_private_key
+            .ok_or_else(|| Error::Invalid("The account has no private key envelope".to_string()))?;
+        let export_key = cryptfns::base64::decode(&finish.export_key)?;
+        let kek = cryptfns::envelope::derive_kek(&export_key)?;
+        let bundle = cryptfns::envelope::open(&kek, &envelope)?;
+        let keys = KeyPair::from_material(
+            std::str::from_utf8(&bundle).map_err(|e| Error::Crypto(format!("{e}")))?,
+        )?;
+
+        Self::new(base_url, http, keys, user, credentials)
+    }
+
+    fn new(
+        base_url: String,
+        http: reqwest::Client,
+        keys: KeyPair,
+        user: User,
+        credentials: Credentials,
+    ) -> Result<Self> {
+        Ok(Self {
+            inner: Arc::new(Inner {
+                base_url,
+                http,
+                keys,
+                user,
+                credentials: Mutex::new(credentials),
+                transfers: Transfers::spawn()?,
+            }),
+        })
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.inner.base_url
+    }
+
+    pub fn keys(&self) -> &KeyPair {
+        &self.inner.keys
+    }
+
+    pub fn user(&self) -> &User {
+        &self.inner.user
+    }
+
+    pub(crate) fn transfers(&self) -> &Transfers {
+        &self.inner.transfers
+    }
+
+    /// Credentials for the `transfer` crate, refreshed when getting old.
+    pub async fn auth(&self) -> Result<Auth> {
+        let mut credentials = self.inner.credentials.lock().await;
+
+        if credentials.refreshed_at.elapsed() >= REFRESH_AFTER {
+            *credentials = match self.refresh(&credentials).await {
+                Ok(refreshed) => refreshed,
+                Err(e) => {
+                    log::debug!("Session refresh failed, signing in again: {e}");
+                    self.sign_in().await?
+                }
+            };
+        }
+
+        Ok(credentials.auth.clone())
+    }
+
+    /// Throw the current session away and sign in again.
+    pub async fn renew(&self) -> Result<Auth> {
+        let mut credentials = self.inner.credentials.lock().await;
+        *credentials = self.sign_in().await?;
+
+        Ok(credentials.auth.clone())
+    }
+
+    /// Send a request to `path`, signing in again once if the session was
+    /// refused. Error statuses come back as [`Error::Http`].
+    pub async fn send<F>(&self, method: Method, path: &str, build: F) -> Result<Response>
+    where
+        F: Fn(RequestBuilder) -> RequestBuilder,
+    {
+        let url = format!("{}{}", self.inner.base_url, path);
+        let auth = self.auth().await?;
+        let request = build(self.inner.http.request(method.clone(), &url));
+        let response = request.headers(headers(&auth)).send().await?;
+
+        let response = if response.status().as_u16() == 401 {
+            let auth = self.renew().await?;
+            let request = build(self.inner.http.request(method, &url));
+            request.headers(headers(&auth)).send().await?
+        } else {
+            response
+        };
+
+        check(response).await
+    }
+
+    async fn sign_in(&self) -> Result<Credentials> {
+        sign_in(&self.inner.http, &self.inner.base_url, &self.inner.keys)
+            .await
+            .map(|(_, credentials)| credentials)
+    }
+
+    async fn refresh(&self, credentials: &Credentials) -> Result<Credentials> {
+        let response = self
+            .inner
+            .http
+            .post(format!("{}/api/auth/refresh", self.inner.base_url))
+            .headers(headers(&credentials.auth))
+            .send()
+            .await?;
+        let response = check(response).await?;
+
+        Ok(credentials_from(
+            &self.inner.base_url,
+            credentials.cookies.clone(),
+            response.headers(),
+        ))
+    }
+}
+
+/// The connection pool every request of a session goes through.
+fn http_client() -> Result<reqwest::Client> {
+    Ok(reqwest::Client::builder()
+        .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+        .build()?)
+}
+
+/// Headers carrying the session, in whichever form the server handed it out.
+fn headers(auth: &Auth) -> HeaderMap {
+    NativeHttpClient::auth_headers(auth)
+}
+
+async fn sign_in(
+    http: &reqwest::Client,
+    base_url: &str,
+    keys: &KeyPair,
+) -> Result<(User, Credentials)> {
+    let fingerprint = keys.fingerprint()?;
+    let timestamp = chrono::Utc::now().timestamp();
+    let nonce = cryptfns::hex::encode(cryptfns::rand::random::<[u8; 16]>());
+    let signature = keys.sign(&format!("{fingerprint}:{timestamp}:{nonce}"))?;
+
+    let response = http
+        .post(format!("{base_url}/api/auth/signature"))
+        .json(&serde_json::json!({
+            "fingerprint": fingerprint,
+            "signature": signature,
+            "timestamp": timestamp,
+            "nonce": nonce,
+        }))
+        .send()
+        .await?;
+    let response = check(response).await?;
+    let credentials = credentials_from(base_url, BTreeMap::new(), response.headers());
+    let authenticated: Authenticated = response.json().await?;
+
+    Ok((authenticated.user, credentials))
+}
+
+/// Pick the session up from a login or refresh response: the `x-auth-*`
+/// headers when the server runs with `USE_HEADERS_FOR_AUTH`, cookies
+/// otherwise.
+fn credentials_from(
+    base_url: &str,
+    mut cookies: BTreeMap<String, String>,
+    response: &HeaderMap,
+) -> Credentials {
+    let header = |name: &str| {
+        response
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string())
+    };
+
+    for value in response.get_all(reqwest::header::SET_COOKIE) {
+        let Ok(value) = value.to_str() else {
+            continue;
+        };
+        let pair = value.split(';').next().unwrap_or_default();
+
+        if let Some((name, value)) = pair.split_once('=') {
+            cookies.insert(name.trim().to_string(), value.trim().to_string());
+        }
+    }
+
+    let cookie = (!cookies.is_empty()).then(|| {
+        cookies
+            .iter()
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect::<Vec<_>>()
+            .join("; ")
+    });
+
+    Credentials {
+        auth: Auth {
+            base_url: base_url.to_string(),
+            jwt_token: header("x-auth-jwt"),
+            refresh_token: header("x-auth-refresh"),
+            cookie,
+        },
+        cookies,
+        refreshed_at: Instant::now(),
+    }
+}
+
+/// Turn error statuses into [`Error::Http`].
+pub(crate) async fn check(response: Response) -> Result<Response> {
+    let status = response.status().as_u16();
+
+    if status < 400 {
+        return Ok(response);
+    }
+
+    let message = response.text().await.unwrap_or_default();
+
+    Err(Error::Http { status, message })
+}
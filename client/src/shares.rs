@@ -0,0 +1,292 @@
+//! Sharing files and folders with other accounts.
+//!
+//! The file keys of everything shared are re-wrapped to the recipient here,
+//! and the request is signed with the identity key so the server and every
+//! other member can tell who granted what.
+use cryptfns::{
+    asn1::{
+        encode_audit_event_sig_input_v1, encode_entries_v1, encode_folder_member_list_v1,
+        encode_member_sig_v1, encode_share_request_v1, AuditEventActionEnum, AuditEventSigInputV1,
+        FolderListMember, FolderMemberListV1, MemberSigPayloadV1, ShareEntry,
+        ShareRequestPayloadV1, ShareRoleEnum, AUDIT_EVENT_SIG_V1_PREFIX, FOLDER_LIST_V1_PREFIX,
+        MEMBER_SIG_V1_PREFIX, SHARE_REQUEST_V1_PREFIX,
+    },
+    identity::KeyType,
+};
+use digest::Digest;
+use reqwest::Method;
+use serde::Deserialize;
+use std::str::FromStr;
+
+use crate::{
+    error::{Error, Result},
+    keys::wrap_for,
+    session::Session,
+    storage::Entry,
+};
+
+/// What the recipient of a share may do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Reader,
+    Editor,
+    CoOwner,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Reader => "reader",
+            Role::Editor => "editor",
+            Role::CoOwner => "co-owner",
+        }
+    }
+
+    fn wire(&self) -> ShareRoleEnum {
+        match self {
+            Role::Reader => ShareRoleEnum::Reader,
+            Role::Editor => ShareRoleEnum::Editor,
+            Role::CoOwner => ShareRoleEnum::CoOwner,
+        }
+    }
+}
+
+impl FromStr for Role {
+    type Err = Error;
+
+    fn from_str(role: &str) -> Result<Self> {
+        match role {
+            "reader" => Ok(Role::Reader),
+            "editor" => Ok(Role::Editor),
+            "co-owner" => Ok(Role::CoOwner),
+            other => Err(Error::Invalid(format!(
+                "Unknown role {other}, expected reader, editor or co-owner"
+            ))),
+        }
+    }
+}
+
+/// An account found by email, with the keys to share with it.
+#[derive(Deserialize)]
+struct Recipient {
+    user_id: String,
+    pubkey: String,
+    key_type: String,
+    wrapping_pubkey: Option<String>,
+    fingerprint: String,
+}
+
+#[derive(Deserialize)]
+struct FolderMembers {
+    folder_owner_id: String,
+    members: Vec<FolderMember>,
+}
+
+#[derive(Deserialize)]
+struct FolderMember {
+    user_id: String,
+    pubkey_fingerprint: String,
+    share_role: String,
+    is_owner: bool,
+    signed_by_user_id: Option<String>,
+}
+
+impl Session {
+    /// Share `entry`, and everything in it when it is a folder, with the
+    /// account registered under `email`. With `expires_at` the account
+    /// loses access on its own at that Unix timestamp.
+    pub async fn share(
+        &self,
+        entry: &Entry,
+        email: &str,
+        role: Role,
+        expires_at: Option<i64>,
+    ) -> Result<()> {
+        let recipient: Recipient = self
+            .send(Method::GET, "/api/users/discover", |request| {
+                request.query(&[("email", email)])
+            })
+            .await?
+            .json()
+            .await?;
+
+        let mut shared = vec![entry.clone()];
+        if entry.is_dir() {
+            shared.extend(
+                self.walk(Some(&entry.id))
+                    .await?
+                    .into_iter()
+                    .map(|(_, entry)| entry),
+            );
+        }
+
+        let entries = shared
+            .iter()
+            .map(|entry| {
+                let wrapped = wrap_for(
+                    &entry.key,
+                    &recipient.key_type,
+                    &recipient.pubkey,
+                    recipient.wrapping_pubkey.as_deref(),
+                )?;
+
+                Ok((entry.id.clone(), wrapped))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let keys = self.keys();
+        let sender_id = uuid_bytes(&self.user().id)?;
+        let recipient_id = uuid_bytes(&recipient.user_id)?;
+        let root_file_id = uuid_bytes(&entry.id)?;
+        let fingerprint = fingerprint_bytes(&recipient.fingerprint)?;
+        let timestamp = chrono::Utc::now().timestamp();
+
+        let share_entries = entries
+            .iter()
+            .map(|(id, wrapped)| {
+                Ok(ShareEntry {
+                    file_id: uuid_bytes(id)?,
+                    encrypted_key: cryptfns::base64::decode(wrapped)?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let entries_hash = sha2::Sha256::digest(encode_entries_v1(&share_entries)?).into();
+
+        let payload_der = encode_share_request_v1(&ShareRequestPayloadV1 {
+            sender_id,
+            recipient_id,
+            recipient_pubkey_fingerprint: fingerprint,
+            share_role: role.wire(),
+            root_file_id,
+            entries_hash,
+            timestamp,
+            nonce: cryptfns::rand::random(),
+        })?;
+        let signature = keys.sign_bytes(&prefixed(SHARE_REQUEST_V1_PREFIX, &payload_der))?;
+
+        let event = encode_audit_event_sig_input_v1(&AuditEventSigInputV1 {
+            sender_id,
+            recipient_id: Some(recipient_id),
+            file_id: root_file_id,
+            action: AuditEventActionEnum::Grant,
+            share_role_before: None,
+            share_role_after: Some(role.wire()),
+            timestamp,
+        })?;
+        let event_signature = keys.sign_bytes(&prefixed(AUDIT_EVENT_SIG_V1_PREFIX, &event))?;
+
+        let member = encode_member_sig_v1(&MemberSigPayloadV1 {
+            user_id: recipient_id,
+            pubkey_der: KeyType::from_str(&recipient.key_type)?
+                .member_pubkey_der(&recipient.pubkey)?,
+            fingerprint,
+            share_role: role.wire(),
+            signed_at: timestamp,
+        })?;
+        let member_signature = keys.sign_bytes(&prefixed(MEMBER_SIG_V1_PREFIX, &member))?;
+
+        let mut envelope = serde_json::json!({
+            "payload_der": cryptfns::base64::encode(&payload_der),
+            "signature": signature,
+            "event_signature": event_signature,
+            "member_signature": member_signature,
+            "member_signed_at": timestamp,
+            "entries": entries
+                .iter()
+                .map(|(id, wrapped)| serde_json::json!({ "file_id": id, "encrypted_key": wrapped }))
+                .collect::<Vec<_>>(),
+            "expires_at": expires_at,
+        });
+
+        if entry.is_dir() {
+            envelope["members_list_signature"] = self
+                .sign_members_after(entry, &recipient, role, timestamp)
+                .await?;
+        }
+
+        self.send(Method::POST, "/api/shares", |request| {
+            request.json(&envelope)
+        })
+        .await?;
+
+        Ok(())
+    }
+
+    /// Sign the member list the folder will have once the recipient is in.
+    async fn sign_members_after(
+        &self,
+        folder: &Entry,
+        recipient: &Recipient,
+        role: Role,
+        signed_at: i64,
+    ) -> Result<serde_json::Value> {
+        let current: FolderMembers = self
+            .send(
+                Method::GET,
+                &format!("/api/shares/folder/{}/members", folder.id),
+                |r| r,
+            )
+            .await?
+            .json()
+            .await?;
+
+        let mut members = current
+            .members
+            .iter()
+            .filter(|member| member.user_id != recipient.user_id)
+            .map(|member| {
+                Ok(FolderListMember {
+                    user_id: uuid_bytes(&member.user_id)?,
+                    pubkey_fingerprint: fingerprint_bytes(&member.pubkey_fingerprint)?,
+                    share_role: Role::from_str(&member.share_role)
+                        .map(|role| role.wire())
+                        .unwrap_or(ShareRoleEnum::Reader),
+                    is_owner: member.is_owner,
+                    signed_by_user_id: uuid_bytes(
+                        member
+                            .signed_by_user_id
+                            .as_deref()
+                            .unwrap_or(&current.folder_owner_id),
+                    )?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        members.push(FolderListMember {
+            user_id: uuid_bytes(&recipient.user_id)?,
+            pubkey_fingerprint: fingerprint_bytes(&recipient.fingerprint)?,
+            share_role: role.wire(),
+            is_owner: false,
+            signed_by_user_id: uuid_bytes(&self.user().id)?,
+        });
+
+        let list = encode_folder_member_list_v1(&FolderMemberListV1 {
+            folder_id: uuid_bytes(&folder.id)?,
+            folder_owner_id: uuid_bytes(&current.folder_owner_id)?,
+            members,
+            members_signed_at: signed_at,
+        })?;
+
+        Ok(serde_json::json!({
+            "signature": self.keys().sign_bytes(&prefixed(FOLDER_LIST_V1_PREFIX, &list))?,
+            "signed_at": signed_at,
+            "signed_by_user_id": self.user().id,
+        }))
+    }
+}
+
+fn prefixed(prefix: &[u8], der: &[u8]) -> Vec<u8> {
+    [prefix, der].concat()
+}
+
+fn uuid_bytes(id: &str) -> Result<[u8; 16]> {
+    uuid::Uuid::parse_str(id)
+        .map(|id| id.into_bytes())
+        .map_err(|e| Error::Invalid(format!("Invalid id {id}: {e}")))
+}
+
+fn fingerprint_bytes(fingerprint: &str) -> Result<[u8; 32]> {
+    hex::decode(fingerprint)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| Error::Invalid(format!("Invalid fingerprint {fingerprint}")))
+}
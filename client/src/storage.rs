@@ -0,0 +1,603 @@
+//! Files and folders, decrypted on the way in and encrypted on the way out.
+//! The server only ever sees wrapped keys, encrypted names and encrypted
+//! chunks.
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{atomic::AtomicBool, Arc},
+};
+
+use cryptfns::cipher::Cipher;
+use digest::Digest;
+use reqwest::Method;
+use serde::Deserialize;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use transfer::{
+    config::CHUNK_SIZE_BYTES,
+    native::{progress::NativeProgressReporter, source::FileSource},
+    platform::HttpClient,
+    types::{DownloadSource, FileHashes},
+    upload_tar::upload_chunks_as_tar_in_memory,
+    Uploader,
+};
+
+use crate::{
+    error::{Error, Result},
+    session::Session,
+};
+
+/// How many times an upload is resumed after its session ran out.
+const MAX_UPLOAD_RESUMES: usize = 20;
+
+/// Files up to this size are encrypted in memory and sent as one tar
+/// archive, which beats a request per chunk on slow links. Bigger files go
+/// through the concurrent, resumable chunk pipeline.
+const TAR_BATCH_MAX_BYTES: u64 = 8 * CHUNK_SIZE_BYTES;
+
+/// A file or folder with its key and name decrypted.
+#[derive(Clone)]
+pub struct Entry {
+    pub id: String,
+    /// Folder the entry is in, `None` at the root.
+    pub parent: Option<String>,
+    pub name: String,
+    pub key: Vec<u8>,
+    pub cipher: String,
+    pub mime: String,
+    pub size: u64,
+    pub chunks: u64,
+    pub modified_at: i64,
+    pub created_at: i64,
+    pub finished_upload_at: Option<i64>,
+    pub sha256: Option<String>,
+    pub editable: bool,
+    /// Content version, bumped every time an editable file is saved.
+    pub version: i32,
+    pub is_owner: bool,
+    pub uploaded_chunks: Vec<u64>,
+}
+
+impl std::fmt::Debug for Entry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Entry")
+            .field("id", &self.id)
+            .field("parent", &self.parent)
+            .field("name", &self.name)
+            .field("mime", &self.mime)
+            .field("size", &self.size)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Entry {
+    pub fn is_dir(&self) -> bool {
+        self.mime == "dir"
+    }
+}
+
+/// The parts of the server's file response the client needs.
+#[derive(Deserialize)]
+struct RemoteFile {
+    id: String,
+    file_id: Option<String>,
+    encrypted_key: String,
+    encrypted_name: String,
+    cipher: String,
+    mime: String,
+    size: Option<i64>,
+    chunks: Option<i64>,
+    file_modified_at: i64,
+    created_at: i64,
+    finished_upload_at: Option<i64>,
+    sha256: Option<String>,
+    editable: bool,
+    active_version: i32,
+    is_owner: bool,
+    uploaded_chunks: Option<Vec<i64>>,
+}
+
+#[derive(Deserialize)]
+struct Listing {
+    children: Vec<RemoteFile>,
+    next_cursor: Option<String>,
+}
+
+/// Rows fetched per `/api/storage` request while walking a folder.
+const LIST_PAGE_SIZE: u64 = 500;
+
+impl Session {
+    fn decrypt(&self, file: RemoteFile) -> Result<Entry> {
+        let key = self.keys().unwrap(&file.encrypted_key)?;
+        let encrypted_name =
+            hex::decode(&file.encrypted_name).map_err(|e| Error::Crypto(format!("{e}")))?;
+        let name = Cipher::from_str(&file.cipher)?.decrypt_string(key.clone(), encrypted_name)?;
+
+        Ok(Entry {
+            id: file.id,
+            parent: file.file_id,
+            name: String::from_utf8(name).map_err(|e| Error::Crypto(format!("{e}")))?,
+            key,
+            cipher: file.cipher,
+            mime: file.mime,
+            size: file.size.unwrap_or_default() as u64,
+            chunks: file.chunks.unwrap_or_default() as u64,
+            modified_at: file.file_modified_at,
+            created_at: file.created_at,
+            finished_upload_at: file.finished_upload_at,
+            sha256: file.sha256,
+            editable: file.editable,
+            version: file.active_version,
+            is_owner: file.is_owner,
+            uploaded_chunks: file
+                .uploaded_chunks
+                .unwrap_or_default()
+                .into_iter()
+                .map(|chunk| chunk as u64)
+                .collect(),
+        })
+    }
+
+    /// Everything in the folder `parent`, or at the root, fetched a page at
+    /// a time. Entries whose name cannot be decrypted with this account's
+    /// keys are left out.
+    pub async fn list(&self, parent: Option<&str>) -> Result<Vec<Entry>> {
+        let mut children = vec![];
+        let mut cursor: Option<String> = None;
+
+        loop {
+            let listing: Listing = self
+                .send(Method::GET, "/api/storage", |request| {
+                    let mut query = vec![("limit", LIST_PAGE_SIZE.to_string())];
+                    if let Some(parent) = parent {
+                        query.push(("dir_id", parent.to_string()));
+                    }
+                    if let Some(cursor) = &cursor {
+                        query.push(("cursor", cursor.clone()));
+                    }
+                    request.query(&query)
+                })
+                .await?
+                .json()
+                .await?;
+
+            children.extend(listing.children);
+
+            match listing.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+
+        Ok(children
+            .into_iter()
+            .filter_map(|file| {
+                let id = file.id.clone();
+
+                self.decrypt(file)
+                    .map_err(|e| log::warn!("Skipping file {id} that cannot be decrypted: {e}"))
+                    .ok()
+            })
+            .collect())
+    }
+
+    pub async fn metadata(&self, id: &str) -> Result<Entry> {
+        let file: RemoteFile = self
+            .send(Method::GET, &format!("/api/storage/{id}/metadata"), |r| r)
+            .await?
+            .json()
+            .await?;
+
+        self.decrypt(file)
+    }
+
+    pub async fn create_dir(&self, parent: Option<&str>, name: &str) -> Result<Entry> {
+        self.create(parent, name, "dir", None).await
+    }
+
+    /// Create the file row for an upload of `size` bytes.
+    async fn create(
+        &self,
+        parent: Option<&str>,
+        name: &str,
+        mime: &str,
+        size: Option<u64>,
+    ) -> Result<Entry> {
+        let cipher = Cipher::from_str(cryptfns::cipher::DEFAULT)?;
+        let key = cipher.generate_key()?;
+        let encrypted_key = self.keys().wrap(&key)?;
+        let encrypted_name = hex::encode(cipher.encrypt_string(key.clone(), name.into())?);
+
+        let file: RemoteFile = self
+            .send(Method::POST, "/api/storage", |request| {
+                request.json(&serde_json::json!({
+                    "encrypted_key": encrypted_key,
+                    "encrypted_name": encrypted_name,
+                    "name_hash": cryptfns::sha256::digest(name),
+                    "search_tokens_hashed": search_tokens(name),
+                    "mime": mime,
+                    "size": size,
+                    "chunks": size.map(|size| size.div_ceil(CHUNK_SIZE_BYTES)),
+                    "file_id": parent,
+                    "cipher": cipher.as_str(),
+                }))
+            })
+            .await?
+            .json()
+            .await?;
+
+        self.decrypt(file)
+    }
+
+    /// Encrypt and upload the local file at `path` as `name` into `parent`.
+    pub async fn upload(&self, parent: Option<&str>, name: &str, path: &Path) -> Result<Entry> {
+        let size = tokio::fs::metadata(path).await?.len();
+        if size == 0 {
+            return Err(Error::Invalid(format!(
+                "{name} is empty, Hoodik does not store empty files"
+            )));
+        }
+
+        let mime = mime_guess::from_path(name).first_or_octet_stream();
+        let entry = self
+            .create(parent, name, mime.essence_str(), Some(size))
+            .await?;
+
+        let uploaded = if size <= TAR_BATCH_MAX_BYTES {
+            self.upload_batch(&entry, path).await
+        } else {
+            self.upload_chunks(&entry, path, &[]).await
+        };
+
+        if let Err(e) = uploaded {
+            if let Err(e) = self.delete(&entry.id).await {
+                log::warn!("Failed to remove the unfinished upload of {name}: {e}");
+            }
+
+            return Err(e);
+        }
+
+        self.metadata(&entry.id).await
+    }
+
+    /// Upload the local file at `path` as the new content of the editable
+    /// file `entry`. The server keeps the previous content as a version.
+    /// Chunks that did not change are reused from the current version
+    /// instead of uploaded again.
+    pub async fn replace_content(&self, entry: &Entry, path: &Path) -> Result<Entry> {
+        let size = tokio::fs::metadata(path).await?.len();
+        if size == 0 {
+            return Err(Error::Invalid(format!(
+                "{} is empty, Hoodik does not store empty files",
+                entry.name
+            )));
+        }
+        let chunks = size.div_ceil(CHUNK_SIZE_BYTES);
+        let current = self.metadata(&entry.id).await?;
+        let reused = self.unchanged_chunks(&current, path, chunks).await?;
+        let reuse_chunks = reused
+            .iter()
+            .map(|chunk| serde_json::json!({ "chunk": chunk, "version": current.version }))
+            .collect::<Vec<_>>();
+
+        self.send(
+            Method::PUT,
+            &format!("/api/storage/{}/content", entry.id),
+            |request| {
+                request.json(&serde_json::json!({
+                    "size": size,
+                    "chunks": chunks,
+                    "search_tokens_hashed": search_tokens(&entry.name),
+                    "reuse_chunks": reuse_chunks,
+                }))
+            },
+        )
+        .await?;
+
+        // The chunk pipeline skips reused chunks but still hashes them, the
+        // tar batch would send them again.
+        if reused.is_empty() && size <= TAR_BATCH_MAX_BYTES {
+            self.upload_batch(entry, path).await?;
+        } else {
+            self.upload_chunks(entry, path, &reused).await?;
+        }
+
+        self.metadata(&entry.id).await
+    }
+
+    /// Put the local file at `path` in place of `existing`. Editable files
+    /// get a new version, other files are uploaded again under a new id
+    /// while the old one steps aside, and are restored when that fails.
+    pub async fn replace(&self, existing: &Entry, path: &Path) -> Result<Entry> {
+        if existing.editable {
+            return self.replace_content(existing, path).await;
+        }
+
+        // Names are unique in a folder.
+        let aside = format!(".{}.{}.replaced", existing.name, uuid::Uuid::new_v4());
+        self.rename(existing, &aside).await?;
+
+        match self
+            .upload(existing.parent.as_deref(), &existing.name, path)
+            .await
+        {
+            Ok(entry) => {
+                self.delete(&existing.id).await?;
+
+                Ok(entry)
+            }
+            Err(e) => {
+                if let Err(e) = self.rename(existing, &existing.name).await {
+                    log::error!(
+                        "Failed to restore {} after a failed upload: {e}",
+                        existing.name
+                    );
+                }
+
+                Err(e)
+            }
+        }
+    }
+
+    /// Chunks of the local file at `path` that are byte for byte the same
+    /// as the chunk at that index of `entry`'s current content. Finding
+    /// them downloads the current content, which is cheaper than storing
+    /// another full copy of a large file for a small edit.
+    async fn unchanged_chunks(&self, entry: &Entry, path: &Path, chunks: u64) -> Result<Vec<u64>> {
+        let mut file = tokio::fs::File::open(path).await?;
+        let mut unchanged = vec![];
+
+        for index in 0..chunks.min(entry.chunks) {
+            let mut local = Vec::with_capacity(CHUNK_SIZE_BYTES as usize);
+            (&mut file)
+                .take(CHUNK_SIZE_BYTES)
+                .read_to_end(&mut local)
+                .await?;
+
+            if self.read_chunk(entry, index).await? == local {
+                unchanged.push(index);
+            }
+        }
+
+        Ok(unchanged)
+    }
+
+    /// Run the upload pipeline, resuming it from the chunks the server
+    /// already has whenever the session expires midway. Chunks in `skip`
+    /// are hashed but never sent.
+    async fn upload_chunks(&self, entry: &Entry, path: &Path, skip: &[u64]) -> Result<()> {
+        let mut uploaded = skip.to_vec();
+        let mut resumes = 0;
+
+        loop {
+            let auth = self.auth().await?;
+            let id = entry.id.clone();
+            let key = entry.key.clone();
+            let cipher = entry.cipher.clone();
+            let path = PathBuf::from(path);
+            let already_uploaded = uploaded.clone();
+
+            let result = self
+                .transfers()
+                .run(move |http| async move {
+                    let source = FileSource::new(path).await?;
+                    let progress =
+                        NativeProgressReporter::new(|_: &str| {}, Arc::new(AtomicBool::new(false)));
+
+                    Uploader::new(auth, id, key)
+                        .with_cipher(cipher)
+                        .with_already_uploaded(&already_uploaded)
+                        .run(http.as_ref(), &source, &progress, None)
+                        .await?;
+
+                    Ok(())
+                })
+                .await;
+
+            match result {
+                Err(e) if e.is_unauthorized() && resumes < MAX_UPLOAD_RESUMES => {
+                    resumes += 1;
+                    self.renew().await?;
+                    uploaded = self.metadata(&entry.id).await?.uploaded_chunks;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Encrypt a small file in memory and send all of its chunks in a
+    /// single tar request, followed by its hashes.
+    async fn upload_batch(&self, entry: &Entry, path: &Path) -> Result<()> {
+        let data = tokio::fs::read(path).await?;
+        let cipher = Cipher::from_str(&entry.cipher)?;
+        let chunks = data
+            .chunks(CHUNK_SIZE_BYTES as usize)
+            .enumerate()
+            .map(|(index, chunk)| {
+                let encrypted = cipher.encrypt_chunk(&entry.key, index as u64, chunk.to_vec())?;
+
+                Ok((format!("{index:06}.enc"), encrypted))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let hashes = hashes(&data);
+        let mut renewed = false;
+
+        loop {
+            let auth = self.auth().await?;
+            let id = entry.id.clone();
+            let chunks = chunks.clone();
+            let hashes = hashes.clone();
+
+            let result = self
+                .transfers()
+                .run(move |http| async move {
+                    let progress =
+                        NativeProgressReporter::new(|_: &str| {}, Arc::new(AtomicBool::new(false)));
+
+                    upload_chunks_as_tar_in_memory(http.as_ref(), &progress, &auth, &id, chunks)
+                        .await?;
+                    http.update_hashes(&auth, &id, &hashes).await?;
+
+                    Ok(())
+                })
+                .await;
+
+            match result {
+                Err(e) if e.is_unauthorized() && !renewed => {
+                    renewed = true;
+                    self.renew().await?;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Download a whole file, decrypting it into `path`.
+    pub async fn download(&self, entry: &Entry, path: &Path) -> Result<()> {
+        let mut file = tokio::fs::File::create(path).await?;
+
+        for index in 0..entry.chunks {
+            let data = self.read_chunk(entry, index).await?;
+            file.write_all(&data).await?;
+        }
+        file.flush().await?;
+
+        Ok(())
+    }
+
+    /// Download and decrypt chunk `index` of a file.
+    pub async fn read_chunk(&self, entry: &Entry, index: u64) -> Result<Vec<u8>> {
+        let mut auth = self.auth().await?;
+        let mut renewed = false;
+
+        loop {
+            let id = entry.id.clone();
+            let request_auth = auth.clone();
+            let result = self
+                .transfers()
+                .run(move |http| async move {
+                    http.download_chunk(
+                        &request_auth,
+                        DownloadSource::Storage(&id),
+                        index,
+                        Box::new(|_| {}),
+                    )
+                    .await
+                    .map_err(Error::from)
+                })
+                .await;
+
+            match result {
+                Ok(data) => {
+                    let cipher = Cipher::from_str(&entry.cipher)?;
+
+                    return Ok(cipher.decrypt_chunk(&entry.key, index, data)?);
+                }
+                Err(e) if e.is_unauthorized() && !renewed => {
+                    auth = self.renew().await?;
+                    renewed = true;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Give the entry a new name, encrypted with its own key.
+    pub async fn rename(&self, entry: &Entry, name: &str) -> Result<()> {
+        let cipher = Cipher::from_str(&entry.cipher)?;
+        let encrypted_name = hex::encode(cipher.encrypt_string(entry.key.clone(), name.into())?);
+
+        self.send(
+            Method::PUT,
+            &format!("/api/storage/{}", entry.id),
+            |request| {
+                request.json(&serde_json::json!({
+                    "encrypted_name": encrypted_name,
+                    "name_hash": cryptfns::sha256::digest(name),
+                    "search_tokens_hashed": search_tokens(name),
+                }))
+            },
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Move entries into `parent`, or to the root.
+    pub async fn move_many(&self, ids: &[String], parent: Option<&str>) -> Result<()> {
+        self.send(Method::POST, "/api/storage/move-many", |request| {
+            request.json(&serde_json::json!({ "ids": ids, "file_id": parent }))
+        })
+        .await?;
+
+        Ok(())
+    }
+
+    /// Copy `entry`, with everything in it when it is a folder, into
+    /// `parent` as `name`. The content is copied on the server; only the
+    /// metadata of each copy is sent, under the key of its source.
+    pub async fn copy(&self, entry: &Entry, parent: Option<&str>, name: &str) -> Result<Entry> {
+        let mut nodes = vec![self.copy_node(entry, name)?];
+        if entry.is_dir() {
+            for (_, child) in self.walk(Some(&entry.id)).await? {
+                nodes.push(self.copy_node(&child, &child.name)?);
+            }
+        }
+
+        let file: RemoteFile = self
+            .send(Method::POST, "/api/storage/copy", |request| {
+                request.json(&serde_json::json!({
+                    "id": entry.id,
+                    "file_id": parent,
+                    "nodes": nodes,
+                }))
+            })
+            .await?
+            .json()
+            .await?;
+
+        self.decrypt(file)
+    }
+
+    /// Metadata of the copy of `entry` called `name`.
+    fn copy_node(&self, entry: &Entry, name: &str) -> Result<serde_json::Value> {
+        let cipher = Cipher::from_str(&entry.cipher)?;
+        let encrypted_name = hex::encode(cipher.encrypt_string(entry.key.clone(), name.into())?);
+
+        Ok(serde_json::json!({
+            "id": entry.id,
+            "encrypted_key": self.keys().wrap(&entry.key)?,
+            "encrypted_name": encrypted_name,
+            "name_hash": cryptfns::sha256::digest(name),
+            "search_tokens_hashed": search_tokens(name),
+        }))
+    }
+
+    /// Delete an entry, into the trash when the instance has one.
+    pub async fn delete(&self, id: &str) -> Result<()> {
+        self.send(Method::DELETE, &format!("/api/storage/{id}"), |r| r)
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// The content hashes the chunk pipeline computes, for uploads that skip it.
+fn hashes(data: &[u8]) -> FileHashes {
+    FileHashes {
+        md5: Some(hex::encode(md5::Md5::digest(data))),
+        sha1: Some(hex::encode(sha1::Sha1::digest(data))),
+        sha256: hex::encode(sha2::Sha256::digest(data)),
+        blake2b: Some(hex::encode(blake2::Blake2b512::digest(data))),
+    }
+}
+
+/// Hashed search tokens of a name, in the form the server stores them.
+fn search_tokens(name: &str) -> Vec<String> {
+    cryptfns::tokenizer::into_hashed_tokens(name)
+        .map(cryptfns::tokenizer::into_string)
+        .unwrap_or_default()
+        .split(';')
+        .filter(|token| !token.is_empty())
+        .map(|token| token.to_string())
+        .collect()
+}
@@ -0,0 +1,106 @@
+//! Paths over the decrypted tree. The server only knows files by id and
+//! encrypted name, so a path is resolved one folder listing at a time.
+use crate::{
+    error::{Error, Result},
+    session::Session,
+    storage::Entry,
+};
+
+/// Segments of a `/` separated path, leading and repeated slashes ignored.
+pub fn segments(path: &str) -> Vec<&str> {
+    path.split('/')
+        .filter(|segment| !segment.is_empty() && *segment != ".")
+        .collect()
+}
+
+impl Session {
+    /// The entry at `path`, `None` when nothing is there. The root itself
+    /// has no entry, so `/` is an error.
+    pub async fn find(&self, path: &str) -> Result<Option<Entry>> {
+        let segments = segments(path);
+        let Some((name, folders)) = segments.split_last() else {
+            return Err(Error::Invalid("The root is not an entry".to_string()));
+        };
+
+        let mut parent: Option<String> = None;
+        for folder in folders {
+            let entry = self
+                .list(parent.as_deref())
+                .await?
+                .into_iter()
+                .find(|entry| entry.is_dir() && entry.name == *folder);
+
+            match entry {
+                Some(entry) => parent = Some(entry.id),
+                None => return Ok(None),
+            }
+        }
+
+        Ok(self
+            .list(parent.as_deref())
+            .await?
+            .into_iter()
+            .find(|entry| entry.name == *name))
+    }
+
+    /// The folder at `path`, `None` for the root.
+    pub async fn find_dir(&self, path: &str) -> Result<Option<String>> {
+        if segments(path).is_empty() {
+            return Ok(None);
+        }
+
+        match self.find(path).await? {
+            Some(entry) if entry.is_dir() => Ok(Some(entry.id)),
+            Some(_) => Err(Error::Invalid(format!("{path} is not a folder"))),
+            None => Err(Error::NotFound(path.to_string())),
+        }
+    }
+
+    /// The folder at `path`, created along with any missing parents.
+    pub async fn create_dirs(&self, path: &str) -> Result<Option<String>> {
+        let mut parent: Option<String> = None;
+
+        for folder in segments(path) {
+            let existing = self
+                .list(parent.as_deref())
+                .await?
+                .into_iter()
+                .find(|entry| entry.name == folder);
+
+            parent = Some(match existing {
+                Some(entry) if entry.is_dir() => entry.id,
+                Some(_) => {
+                    return Err(Error::Invalid(format!(
+                        "{folder} in {path} is a file, not a folder"
+                    )))
+                }
+                None => self.create_dir(parent.as_deref(), folder).await?.id,
+            });
+        }
+
+        Ok(parent)
+    }
+
+    /// Everything under the folder `root`, with paths relative to it,
+    /// parents before their children.
+    pub async fn walk(&self, root: Option<&str>) -> Result<Vec<(String, Entry)>> {
+        let mut found = vec![];
+        let mut pending = vec![(String::new(), root.map(|root| root.to_string()))];
+
+        while let Some((prefix, folder)) = pending.pop() {
+            for entry in self.list(folder.as_deref()).await? {
+                let path = match prefix.is_empty() {
+                    true => entry.name.clone(),
+                    false => format!("{prefix}/{}", entry.name),
+                };
+
+                if entry.is_dir() {
+                    pending.push((path.clone(), Some(entry.id.clone())));
+                }
+                found.push((path, entry));
+            }
+        }
+
+        Ok(found)
+    }
+}
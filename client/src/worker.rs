@@ -0,0 +1,69 @@
+//! The transfer pipelines of the `transfer` crate are built on `?Send`
+//! futures, the same code runs in the browser. The worker gives them a
+//! thread of their own with a single threaded runtime, so they can be driven
+//! from any async context, multi threaded servers included.
+use std::{future::Future, rc::Rc};
+
+use futures::future::LocalBoxFuture;
+use tokio::sync::{mpsc, oneshot};
+use transfer::native::http::NativeHttpClient;
+
+use crate::error::{Error, Result};
+
+type Job = Box<dyn FnOnce(Rc<NativeHttpClient>) -> LocalBoxFuture<'static, ()> + Send>;
+
+#[derive(Clone)]
+pub struct Transfers {
+    jobs: mpsc::UnboundedSender<Job>,
+}
+
+impl Transfers {
+    /// Start the worker thread. It stops once every handle is dropped.
+    pub fn spawn() -> Result<Self> {
+        let (jobs, mut receiver) = mpsc::unbounded_channel::<Job>();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        let http = NativeHttpClient::new()?;
+
+        std::thread::Builder::new()
+            .name("hoodik-transfers".to_string())
+            .spawn(move || {
+                let http = Rc::new(http);
+                let local = tokio::task::LocalSet::new();
+
+                local.block_on(&runtime, async move {
+                    while let Some(job) = receiver.recv().await {
+                        tokio::task::spawn_local(job(http.clone()));
+                    }
+                });
+            })?;
+
+        Ok(Self { jobs })
+    }
+
+    /// Run `job` on the worker and wait for its result. Jobs run
+    /// concurrently with each other.
+    pub async fn run<T, F, Fut>(&self, job: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(Rc<NativeHttpClient>) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<T>> + 'static,
+    {
+        let (sender, receiver) = oneshot::channel();
+
+        let job: Job = Box::new(move |http| {
+            Box::pin(async move {
+                let _ = sender.send(job(http).await);
+            })
+        });
+
+        self.jobs
+            .send(job)
+            .map_err(|_| Error::Io("Transfer worker has stopped".to_string()))?;
+
+        receiver
+            .await
+            .map_err(|_| Error::Io("Transfer worker dropped the job".to_string()))?
+    }
+}
@@ -83,14 +83,13 @@ pub struct AppConfig {
     pub storage_provider: String,
 
     /// MAX_FILE_VERSIONS — how many historical versions to retain per
-    /// editable file. The active version is always preserved; this cap
-    /// only affects historical snapshots created on each edit. When
-    /// exceeded, the oldest historical version is pruned (database row
-    /// + on-disk chunk directory) right after a successful finalize.
+    /// editable file. Kept for code reading it from here; the cap is part of
+    /// the version retention settings now.
     ///
     /// *optional*
     ///
     /// default: 30
+    #[deprecated(note = "use `Config::versions.max_file_versions`")]
     pub max_file_versions: usize,
 
     /// WORKERS — number of HTTP worker threads to spawn.
@@ -146,15 +145,14 @@ impl AppConfig {
         let storage_provider = vars
             .var_default("STORAGE_PROVIDER", "local".to_string())
             .get();
-        let max_file_versions = vars
-            .var_default::<usize>("MAX_FILE_VERSIONS", 30)
-            .get();
+        let max_file_versions = vars.var_default::<usize>("MAX_FILE_VERSIONS", 30).get();
         let workers = vars.maybe_var::<usize>("WORKERS");
         let storage_instance_quota_bytes = vars.maybe_var::<u64>("STORAGE_INSTANCE_QUOTA_BYTES");
         let mailer_disable_test = vars.var_default("MAILER_DISABLE_TEST", false).get();
 
         vars.panic_if_errors("AppConfig");
 
+        #[allow(deprecated)]
         Self {
             port,
             address,
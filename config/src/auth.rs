@@ -4,16 +4,35 @@ use crate::{app::AppConfig, vars::Vars};
 
 #[derive(Debug, Clone)]
 pub struct AuthConfig {
-    /// JWT_SECRET secret that will be used to sign the JWT tokens
-    /// if you don't set this it will generate a random secret every time
-    /// the application restarts, that means that all the sessions will be
-    /// invalidated every time the application restarts.
+    /// JWT_SECRET secret that signed the JWT tokens before signing keys replaced it.
+    /// It is only used to verify tokens carrying no key id, and only while
+    /// JWT_ACCEPT_LEGACY is turned on.
     ///
     /// *optional*
     ///
     /// default: generates a random secret
     pub jwt_secret: String,
 
+    /// JWT_ACCEPT_LEGACY: Keep accepting tokens signed with JWT_SECRET, so sessions made
+    /// before the upgrade survive it. Anyone who knows the secret can sign such a token
+    /// for any user, so turn it on only for the upgrade and off again once the old
+    /// sessions ran out.
+    ///
+    /// *optional*
+    ///
+    /// default: false
+    pub jwt_accept_legacy: bool,
+
+    /// JWT_ALGORITHM: The algorithm of newly generated JWT signing keys. Keys already
+    /// made keep their algorithm, so changing this takes effect on the next rotation.
+    ///
+    /// *optional*
+    ///
+    /// default: EdDSA
+    ///
+    /// *possible values: EdDSA, ES256*
+    pub jwt_algorithm: String,
+
     /// APP_COOKIE_DOMAIN: If the backend is working by using cookies and not JWT this will be used as the cookie domain.
     /// it automatically defaults to be the same as the APP_URL
     ///
@@ -83,16 +102,48 @@ pub struct AuthConfig {
     /// USE_HEADERS_FOR_AUTH: This tells us if the headers should be used for authentication
     /// instead of cookies. This method will be less secure, but will allow you to use the backend
     /// with a frontend that is not on the same domain, or if you have multiple domains.
-    /// 
+    ///
     /// *optional*
-    /// 
+    ///
     /// default: false
     pub use_headers_for_auth: bool,
+
+    /// WEBAUTHN_RP_ID: The relying party id security keys are registered for. It has to be
+    /// the domain the web client is served from (or a parent of it), and changing it later
+    /// makes every enrolled key unusable.
+    ///
+    /// *optional*
+    ///
+    /// default: host of the APP_CLIENT_URL
+    pub webauthn_rp_id: String,
+
+    /// WEBAUTHN_ORIGIN: The origin the browser reports when a security key is used, WebAuthn
+    /// ceremonies from any other origin are refused.
+    ///
+    /// *optional*
+    ///
+    /// default: origin of the APP_CLIENT_URL
+    pub webauthn_origin: String,
+
+    /// RATE_LIMIT_BACKEND: Where the login lockout and the user discovery limiter keep
+    /// their windows. `memory` keeps them in the server process, they are lost on restart
+    /// and every process counts on its own. `database` keeps them in the database, so
+    /// several Hoodik processes behind a load balancer share one lockout.
+    ///
+    /// *optional*
+    ///
+    /// default: memory
+    ///
+    /// *possible values: memory, database*
+    pub rate_limit_backend: String,
 }
 
 impl AuthConfig {
     pub(crate) fn new(app: &AppConfig, vars: &mut Vars) -> Self {
         let jwt_secret = vars.var_default("JWT_SECRET", uuid::Uuid::new_v4().to_string());
+        let jwt_accept_legacy = vars.var_default("JWT_ACCEPT_LEGACY", false);
+        let jwt_algorithm = parse_jwt_algorithm(vars);
+        let rate_limit_backend = parse_rate_limit_backend(vars);
         let session_cookie = vars.var_default("SESSION_COOKIE", "hoodik_session".to_string());
         let refresh_cookie = vars.var_default("REFRESH_COOKIE", "hoodik_refresh".to_string());
         let cookie_http_only = vars.var_default("COOKIE_HTTP_ONLY", true);
@@ -106,11 +157,22 @@ impl AuthConfig {
 
         let cookie_domain = get_cookie_domain(vars, &app.app_url);
 
+        let webauthn_rp_id = vars.var_default(
+            "WEBAUTHN_RP_ID",
+            app.client_url.host_str().unwrap_or("localhost").to_string(),
+        );
+        let webauthn_origin = vars.var_default(
+            "WEBAUTHN_ORIGIN",
+            app.client_url.origin().ascii_serialization(),
+        );
+
         vars.panic_if_errors("AuthConfig");
 
         Self {
             cookie_domain,
             jwt_secret: jwt_secret.get(),
+            jwt_accept_legacy: jwt_accept_legacy.get(),
+            jwt_algorithm,
             session_cookie: session_cookie.get(),
             refresh_cookie: refresh_cookie.get(),
             cookie_http_only: cookie_http_only.get(),
@@ -119,6 +181,47 @@ impl AuthConfig {
             long_term_session_duration_days: long_term_session_duration_days.get(),
             short_term_session_duration_seconds: short_term_session_duration_seconds.get(),
             use_headers_for_auth: use_headers_for_auth.get(),
+            webauthn_rp_id: webauthn_rp_id.get(),
+            webauthn_origin: webauthn_origin.get(),
+            rate_limit_backend,
+        }
+    }
+
+    /// Secret to verify tokens without a key id with, `None` when they
+    /// are refused.
+    pub fn legacy_jwt_secret(&self) -> Option<&str> {
+        self.jwt_accept_legacy.then_some(self.jwt_secret.as_str())
+    }
+}
+
+fn parse_jwt_algorithm(vars: &mut Vars) -> String {
+    let value = vars.maybe_var::<String>("JWT_ALGORITHM").maybe_get();
+
+    match value.as_deref() {
+        None | Some("EdDSA") => "EdDSA".to_string(),
+        Some("ES256") => "ES256".to_string(),
+        Some(other) => {
+            log::warn!("Unsupported JWT_ALGORITHM '{other}', using EdDSA");
+
+            "EdDSA".to_string()
+        }
+    }
+}
+
+fn parse_rate_limit_backend(vars: &mut Vars) -> String {
+    let value = vars.maybe_var::<String>("RATE_LIMIT_BACKEND").maybe_get();
+
+    match value.as_deref() {
+        None | Some("memory") => "memory".to_string(),
+        Some("database") => "database".to_string(),
+        // Falling back to memory would quietly split the lockout between
+        // replicas that were meant to share it.
+        Some(other) => {
+            vars.add_error(format!(
+                "RATE_LIMIT_BACKEND '{other}' is not supported, use memory or database"
+            ));
+
+            "memory".to_string()
         }
     }
 }
@@ -1,4 +1,9 @@
-use crate::{app::AppConfig, email::EmailConfig, s3::S3Config, ssl::SslConfig, vars::Vars};
+use clap::ArgMatches;
+
+use crate::{
+    app::AppConfig, email::EmailConfig, jobs::JobsConfig, oidc::OidcConfig, s3::S3Config,
+    ssl::SslConfig, vars::Vars, versions::VersionsConfig,
+};
 
 /// Config struct that holds all the loaded configuration
 /// from the env and arguments.
@@ -29,12 +34,29 @@ pub struct Config {
     /// see more details in the [crate::email::EmailConfig] struct.
     pub mailer: crate::email::EmailConfig,
 
-    /// S3 configuration, present when STORAGE_PROVIDER=s3.
+    /// S3 configuration, present when STORAGE_PROVIDER=s3 or when S3_BUCKET
+    /// is set, so `migrate-storage` can reach a bucket the server is not
+    /// using yet.
     pub s3: Option<S3Config>,
 
+    /// OpenID Connect login gate, present when OIDC_ISSUER is set,
+    /// see more details in the [crate::oidc::OidcConfig] struct.
+    pub oidc: Option<OidcConfig>,
+
+    /// Background housekeeping jobs configuration,
+    /// see more details in the [crate::jobs::JobsConfig] struct.
+    pub jobs: JobsConfig,
+
+    /// Default retention of the version history of editable files,
+    /// see more details in the [crate::versions::VersionsConfig] struct.
+    pub versions: VersionsConfig,
+
     /// CLI subcommand invoked, if any (e.g. "migrate-storage").
     pub subcommand: Option<String>,
 
+    /// Arguments passed to the invoked subcommand, if any.
+    pub subcommand_matches: Option<ArgMatches>,
+
     /// Warnings collected during configuration initialization
     pub(crate) warnings: Vec<String>,
 }
@@ -51,13 +73,29 @@ impl From<Vars> for Config {
         let mailer = EmailConfig::new(&mut vars);
         let auth = crate::auth::AuthConfig::new(&app, &mut vars);
 
-        let s3 = if app.storage_provider == "s3" {
+        let s3 = if app.storage_provider == "s3"
+            || vars.maybe_var::<String>("S3_BUCKET").maybe_get().is_some()
+        {
             Some(S3Config::new(&mut vars))
         } else {
             None
         };
 
+        let oidc = if vars
+            .maybe_var::<String>("OIDC_ISSUER")
+            .maybe_get()
+            .is_some()
+        {
+            Some(OidcConfig::new(&app, &mut vars))
+        } else {
+            None
+        };
+
+        let jobs = JobsConfig::new(&mut vars);
+        let versions = VersionsConfig::new(&mut vars);
+
         let subcommand = vars.subcommand.clone();
+        let subcommand_matches = vars.subcommand_matches.clone();
 
         vars.panic_if_errors("Config");
 
@@ -70,7 +108,11 @@ impl From<Vars> for Config {
             auth,
             mailer,
             s3,
+            oidc,
+            jobs,
+            versions,
             subcommand,
+            subcommand_matches,
             warnings,
         }
     }
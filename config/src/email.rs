@@ -82,20 +82,20 @@ impl SmtpCredentials {
         let username = vars.var::<String>("SMTP_USERNAME");
         let password = vars.var::<String>("SMTP_PASSWORD");
         let port = vars.var_default::<u16>("SMTP_PORT", 465);
-        
+
         // New variables (preferred)
         let default_from_email = vars.maybe_var::<String>("SMTP_DEFAULT_FROM_EMAIL");
         let default_from_name = vars.maybe_var::<String>("SMTP_DEFAULT_FROM_NAME");
-        
+
         // Old variable (deprecated)
         let smtp_default_from = vars.maybe_var::<String>("SMTP_DEFAULT_FROM");
-        
+
         let tls_mode_str = vars.var_default::<String>("SMTP_TLS_MODE", String::new());
 
         // Validate TLS mode and add warning if invalid (will auto-detect from port)
         let tls_mode_str_value = tls_mode_str.get();
         let port_value = port.get();
-        
+
         let tls_mode = if !tls_mode_str_value.is_empty() {
             match TlsMode::from_str(&tls_mode_str_value) {
                 Some(mode) => mode,
@@ -119,19 +119,23 @@ impl SmtpCredentials {
         };
 
         // Check if using deprecated SMTP_DEFAULT_FROM (peek without consuming)
-        let used_deprecated_default_from = default_from_email.is_none() && smtp_default_from.is_some();
-        
+        let used_deprecated_default_from =
+            default_from_email.is_none() && smtp_default_from.is_some();
+
         if used_deprecated_default_from {
             vars.add_warning(
                 "SMTP_DEFAULT_FROM is deprecated and will be removed in a future version. \
-                Please use SMTP_DEFAULT_FROM_EMAIL and SMTP_DEFAULT_FROM_NAME instead.".to_string()
+                Please use SMTP_DEFAULT_FROM_EMAIL and SMTP_DEFAULT_FROM_NAME instead."
+                    .to_string(),
             );
         }
 
         Box::new(move || {
-
             // Determine default_from based on new or old variables
-            let default_from = match (default_from_email.maybe_get(), default_from_name.maybe_get()) {
+            let default_from = match (
+                default_from_email.maybe_get(),
+                default_from_name.maybe_get(),
+            ) {
                 (Some(email), Some(name)) if !email.is_empty() && !name.is_empty() => {
                     // Both email and name provided: format as "Name <email@example.com>"
                     format!("{name} <{email}>")
@@ -0,0 +1,207 @@
+use crate::vars::Vars;
+
+/// Background housekeeping jobs run by the in-process scheduler. Every job can
+/// also be triggered by hand with `hoodik run-job <name>`, whether or not the
+/// scheduler itself is enabled. Setting a job interval to `0` keeps the
+/// scheduler from starting that job.
+#[derive(Debug, Clone)]
+pub struct JobsConfig {
+    /// JOBS_ENABLED — run the housekeeping scheduler alongside the HTTP server.
+    ///
+    /// *optional*
+    ///
+    /// default: true
+    ///
+    /// Turn it off when several instances share one database and only one of
+    /// them should do the sweeping, or when an external cron drives
+    /// `hoodik run-job` instead.
+    pub enabled: bool,
+
+    /// JOBS_TICK_SECONDS — how often the scheduler wakes up to look for jobs
+    /// that are due. A job never runs more often than its own interval.
+    ///
+    /// *optional*
+    ///
+    /// default: 60
+    pub tick_seconds: u64,
+
+    /// JOBS_HISTORY_RETENTION_DAYS — how long expired sessions and expired
+    /// invitations stay around after they stop being valid. They are still
+    /// listed in the account activity and the admin panel until then.
+    ///
+    /// *optional*
+    ///
+    /// default: 30
+    pub history_retention_days: i64,
+
+    /// JOBS_ABANDONED_UPLOAD_IDLE_SECONDS — how long an unfinished upload or
+    /// an unfinalized edit may go without receiving a chunk before the
+    /// `reap-abandoned-uploads` job throws its partial chunks away.
+    ///
+    /// *optional*
+    ///
+    /// default: 86400
+    pub abandoned_upload_idle_seconds: i64,
+
+    /// JOB_PURGE_EXPIRED_LINKS_INTERVAL_SECONDS — interval of the
+    /// `purge-expired-links` job.
+    ///
+    /// *optional*
+    ///
+    /// default: 3600
+    pub purge_expired_links_interval_seconds: u64,
+
+    /// JOB_PURGE_EXPIRED_SHARES_INTERVAL_SECONDS — interval of the
+    /// `purge-expired-shares` job. Recipients lose access the moment a
+    /// share expires; the job only removes the rows and logs the expiry.
+    ///
+    /// *optional*
+    ///
+    /// default: 900
+    pub purge_expired_shares_interval_seconds: u64,
+
+    /// JOB_PURGE_STALE_SESSIONS_INTERVAL_SECONDS — interval of the
+    /// `purge-stale-sessions` job.
+    ///
+    /// *optional*
+    ///
+    /// default: 3600
+    pub purge_stale_sessions_interval_seconds: u64,
+
+    /// JOB_PURGE_EXPIRED_INVITATIONS_INTERVAL_SECONDS — interval of the
+    /// `purge-expired-invitations` job.
+    ///
+    /// *optional*
+    ///
+    /// default: 86400
+    pub purge_expired_invitations_interval_seconds: u64,
+
+    /// JOB_PURGE_USED_NONCES_INTERVAL_SECONDS — interval of the
+    /// `purge-used-nonces` job.
+    ///
+    /// *optional*
+    ///
+    /// default: 900
+    pub purge_used_nonces_interval_seconds: u64,
+
+    /// JOB_PURGE_OPAQUE_LOGIN_SESSIONS_INTERVAL_SECONDS — interval of the
+    /// `purge-opaque-login-sessions` job.
+    ///
+    /// *optional*
+    ///
+    /// default: 900
+    pub purge_opaque_login_sessions_interval_seconds: u64,
+
+    /// JOB_PURGE_RATE_LIMIT_HITS_INTERVAL_SECONDS — interval of the
+    /// `purge-rate-limit-hits` job, which only has work to do with
+    /// `RATE_LIMIT_BACKEND=database`.
+    ///
+    /// *optional*
+    ///
+    /// default: 900
+    pub purge_rate_limit_hits_interval_seconds: u64,
+
+    /// JOB_PURGE_REWRAP_STAGING_INTERVAL_SECONDS — interval of the
+    /// `purge-rewrap-staging` job.
+    ///
+    /// *optional*
+    ///
+    /// default: 3600
+    pub purge_rewrap_staging_interval_seconds: u64,
+
+    /// JOB_PURGE_TRASH_INTERVAL_SECONDS — interval of the `purge-trash` job.
+    /// How long files stay in the trash is an admin setting, this only
+    /// decides how often the expired ones are looked for.
+    ///
+    /// *optional*
+    ///
+    /// default: 3600
+    pub purge_trash_interval_seconds: u64,
+
+    /// JOB_REAP_ABANDONED_UPLOADS_INTERVAL_SECONDS — interval of the
+    /// `reap-abandoned-uploads` job.
+    ///
+    /// *optional*
+    ///
+    /// default: 3600
+    pub reap_abandoned_uploads_interval_seconds: u64,
+
+    /// JOB_PRUNE_FILE_VERSIONS_INTERVAL_SECONDS — interval of the
+    /// `prune-file-versions` job. History is also pruned after every edit,
+    /// the job catches versions that aged out of their policy since.
+    ///
+    /// *optional*
+    ///
+    /// default: 3600
+    pub prune_file_versions_interval_seconds: u64,
+}
+
+impl JobsConfig {
+    pub(crate) fn new(vars: &mut Vars) -> Self {
+        let enabled = vars.var_default("JOBS_ENABLED", true).get();
+        let tick_seconds = vars.var_default::<u64>("JOBS_TICK_SECONDS", 60).get();
+        let history_retention_days = vars
+            .var_default::<i64>("JOBS_HISTORY_RETENTION_DAYS", 30)
+            .get();
+        let abandoned_upload_idle_seconds = vars
+            .var_default::<i64>("JOBS_ABANDONED_UPLOAD_IDLE_SECONDS", 86400)
+            .get();
+        let purge_expired_links_interval_seconds = vars
+            .var_default::<u64>("JOB_PURGE_EXPIRED_LINKS_INTERVAL_SECONDS", 3600)
+            .get();
+        let purge_expired_shares_interval_seconds = vars
+            .var_default::<u64>("JOB_PURGE_EXPIRED_SHARES_INTERVAL_SECONDS", 900)
+            .get();
+        let purge_stale_sessions_interval_seconds = vars
+            .var_default::<u64>("JOB_PURGE_STALE_SESSIONS_INTERVAL_SECONDS", 3600)
+            .get();
+        let purge_expired_invitations_interval_seconds = vars
+            .var_default::<u64>("JOB_PURGE_EXPIRED_INVITATIONS_INTERVAL_SECONDS", 86400)
+            .get();
+        let purge_used_nonces_interval_seconds = vars
+            .var_default::<u64>("JOB_PURGE_USED_NONCES_INTERVAL_SECONDS", 900)
+            .get();
+        let purge_opaque_login_sessions_interval_seconds = vars
+            .var_default::<u64>("JOB_PURGE_OPAQUE_LOGIN_SESSIONS_INTERVAL_SECONDS", 900)
+            .get();
+        let purge_rate_limit_hits_interval_seconds = vars
+            .var_default::<u64>("JOB_PURGE_RATE_LIMIT_HITS_INTERVAL_SECONDS", 900)
+            .get();
+        let purge_rewrap_staging_interval_seconds = vars
+            .var_default::<u64>("JOB_PURGE_REWRAP_STAGING_INTERVAL_SECONDS", 3600)
+            .get();
+        let purge_trash_interval_seconds = vars
+            .var_default::<u64>("JOB_PURGE_TRASH_INTERVAL_SECONDS", 3600)
+            .get();
+        let reap_abandoned_uploads_interval_seconds = vars
+            .var_default::<u64>("JOB_REAP_ABANDONED_UPLOADS_INTERVAL_SECONDS", 3600)
+            .get();
+        let prune_file_versions_interval_seconds = vars
+            .var_default::<u64>("JOB_PRUNE_FILE_VERSIONS_INTERVAL_SECONDS", 3600)
+            .get();
+
+        if tick_seconds == 0 {
+            vars.add_warning("JOBS_TICK_SECONDS is 0, falling back to 1 second".to_string());
+        }
+
+        vars.panic_if_errors("JobsConfig");
+
+        Self {
+            enabled,
+            tick_seconds: tick_seconds.max(1),
+            history_retention_days,
+            abandoned_upload_idle_seconds,
+            purge_expired_links_interval_seconds,
+            purge_expired_shares_interval_seconds,
+            purge_stale_sessions_interval_seconds,
+            purge_expired_invitations_interval_seconds,
+            purge_used_nonces_interval_seconds,
+            purge_opaque_login_sessions_interval_seconds,
+            purge_rate_limit_hits_interval_seconds,
+            purge_rewrap_staging_interval_seconds,
+            purge_trash_interval_seconds,
+            reap_abandoned_uploads_interval_seconds,
+            prune_file_versions_interval_seconds,
+        }
+    }
+}
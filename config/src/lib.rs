@@ -3,13 +3,17 @@ pub mod auth;
 pub mod config;
 pub mod email;
 pub(crate) mod helpers;
+pub mod jobs;
+pub mod oidc;
 pub mod s3;
 pub mod ssl;
 pub mod vars;
+pub mod versions;
 
 use helpers::remove_trailing_slash;
 
 pub use crate::config::Config;
+pub use clap::ArgMatches;
 
 impl Config {
     pub fn get_app_name(&self) -> String {
@@ -0,0 +1,121 @@
+use crate::{app::AppConfig, helpers::remove_trailing_slash, vars::Vars};
+
+/// OpenID Connect login gate. When an issuer is configured every login and
+/// registration has to be preceded by a sign-in at the identity provider,
+/// so disabling someone there keeps them out of Hoodik. The OPAQUE password
+/// is still asked for afterwards, it is what unseals the private key.
+///
+/// Sessions are not refreshed past [`OidcConfig::session_lifetime_hours`],
+/// so they do not outlive a sign-in at the provider for long. Personal
+/// access tokens never go back to the provider, see
+/// [`OidcConfig::allow_personal_access_tokens`].
+#[derive(Debug, Clone)]
+pub struct OidcConfig {
+    /// OIDC_ISSUER: Issuer URL of the identity provider, its discovery document is
+    /// read from `{issuer}/.well-known/openid-configuration`.
+    ///
+    /// *required*
+    pub issuer: String,
+
+    /// OIDC_CLIENT_ID: Client id Hoodik is registered under at the identity provider.
+    ///
+    /// *required*
+    pub client_id: String,
+
+    /// OIDC_CLIENT_SECRET: Client secret, leave it out for a public client, the
+    /// authorization code is protected with PKCE either way.
+    ///
+    /// *optional*
+    pub client_secret: Option<String>,
+
+    /// OIDC_REDIRECT_URL: Where the identity provider sends the browser back to,
+    /// it has to be registered with the provider as is.
+    ///
+    /// *optional*
+    ///
+    /// default: {APP_URL}/api/auth/oidc/callback
+    pub redirect_url: String,
+
+    /// OIDC_SCOPES: Scopes requested from the identity provider, space separated.
+    ///
+    /// *optional*
+    ///
+    /// default: openid email profile
+    pub scopes: String,
+
+    /// OIDC_ROLE_CLAIM: ID token claim the user's role is mapped from, a string or
+    /// an array of strings such as a list of groups. When set, the role is updated
+    /// on every login; when not, roles are managed in Hoodik.
+    ///
+    /// *optional*
+    pub role_claim: Option<String>,
+
+    /// OIDC_ADMIN_ROLE: Value of the role claim that makes the user an admin,
+    /// any other value makes them a regular user.
+    ///
+    /// *optional*
+    ///
+    /// default: admin
+    pub admin_role: String,
+
+    /// OIDC_SESSION_LIFETIME_HOURS: How long a session lasts after the sign-in at the
+    /// identity provider that opened it. It is not refreshed past that, so someone
+    /// disabled at the provider is out of Hoodik within this many hours.
+    ///
+    /// *optional*
+    ///
+    /// default: 24
+    pub session_lifetime_hours: i64,
+
+    /// OIDC_ALLOW_PERSONAL_ACCESS_TOKENS: Whether personal access tokens keep
+    /// working while the gate is on. They never go back to the identity provider,
+    /// so a token keeps working for someone disabled there until it expires or is
+    /// revoked. Set to false to refuse every token, including the ones made before
+    /// the gate was turned on.
+    ///
+    /// *optional*
+    ///
+    /// default: true
+    pub allow_personal_access_tokens: bool,
+}
+
+impl OidcConfig {
+    pub(crate) fn new(app: &AppConfig, vars: &mut Vars) -> Self {
+        let issuer = vars.var::<String>("OIDC_ISSUER");
+        let client_id = vars.var::<String>("OIDC_CLIENT_ID");
+        let client_secret = vars.maybe_var::<String>("OIDC_CLIENT_SECRET");
+        let redirect_url = vars.var_default(
+            "OIDC_REDIRECT_URL",
+            format!(
+                "{}/api/auth/oidc/callback",
+                remove_trailing_slash(app.app_url.to_string())
+            ),
+        );
+        let scopes = vars.var_default("OIDC_SCOPES", "openid email profile".to_string());
+        let role_claim = vars.maybe_var::<String>("OIDC_ROLE_CLAIM");
+        let admin_role = vars.var_default("OIDC_ADMIN_ROLE", "admin".to_string());
+        let session_lifetime_hours = vars.var_default("OIDC_SESSION_LIFETIME_HOURS", 24);
+        let allow_personal_access_tokens =
+            vars.var_default("OIDC_ALLOW_PERSONAL_ACCESS_TOKENS", true);
+
+        vars.panic_if_errors("OidcConfig");
+
+        Self {
+            issuer: remove_trailing_slash(issuer.get()),
+            client_id: client_id.get(),
+            client_secret: client_secret.maybe_get(),
+            redirect_url: redirect_url.get(),
+            scopes: scopes.get(),
+            role_claim: role_claim.maybe_get(),
+            admin_role: admin_role.get(),
+            session_lifetime_hours: session_lifetime_hours.get(),
+            allow_personal_access_tokens: allow_personal_access_tokens.get(),
+        }
+    }
+
+    /// Whether a session created at `created_at` has outlived the sign-in
+    /// at the provider that opened it.
+    pub fn session_outlived(&self, created_at: i64, now: i64) -> bool {
+        created_at + self.session_lifetime_hours * 60 * 60 < now
+    }
+}
@@ -14,9 +14,9 @@ pub struct SslConfig {
     /// SSL_DISABLED: Disable SSL, if this is set to true, the server will not use SSL
     /// even if the cert and key files are provided.
     /// This is useful for development and testing.
-    /// 
+    ///
     /// *optional*
-    /// 
+    ///
     /// default: false
     pub disabled: bool,
 
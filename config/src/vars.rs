@@ -1,6 +1,6 @@
 use std::str::FromStr;
 
-use clap::{builder::Str, Arg, ArgMatches, Command};
+use clap::{builder::Str, Arg, ArgAction, ArgMatches, Command};
 // `from_path_iter` is deprecated upstream in favour of `from_path` + `var`,
 // but the replacement implies a singleton-load of `.env` that we explicitly
 // must avoid here so ENV_FILE-only loads (e.g. `.env.e2e`) don't get
@@ -107,6 +107,8 @@ pub(crate) struct Vars {
     warnings: Vec<String>,
     /// Subcommand invoked from the CLI, if any.
     pub(crate) subcommand: Option<String>,
+    /// Arguments of the subcommand invoked from the CLI, if any.
+    pub(crate) subcommand_matches: Option<ArgMatches>,
 }
 
 impl Vars {
@@ -134,6 +136,7 @@ impl Vars {
             errors: Vec::new(),
             warnings: Vec::new(),
             subcommand: None,
+            subcommand_matches: None,
         }
     }
 
@@ -148,6 +151,11 @@ impl Vars {
         }
     }
 
+    /// Add an error that stops the startup at the next [`Self::panic_if_errors`]
+    pub(crate) fn add_error(&mut self, error: String) {
+        self.errors.push(error);
+    }
+
     /// Add a warning to the warning list
     pub(crate) fn add_warning(&mut self, warning: String) {
         self.warnings.push(warning);
@@ -369,11 +377,83 @@ impl Vars {
         )
         .subcommand(
             Command::new("migrate-storage")
-                .about("Migrate file data from local filesystem to S3 storage"),
+                .about("Copy file data between storage providers, local to S3 by default")
+                .arg(
+                    Arg::new("from")
+                        .long("from")
+                        .help("Source storage: local[:<dir>] or s3[:<bucket>[/<prefix>]]")
+                        .default_value("local"),
+                )
+                .arg(
+                    Arg::new("to")
+                        .long("to")
+                        .help("Destination storage: local[:<dir>] or s3[:<bucket>[/<prefix>]]")
+                        .default_value("s3"),
+                )
+                .arg(
+                    Arg::new("checkpoint")
+                        .long("checkpoint")
+                        .help("Progress file used to resume an interrupted migration, default: DATA_DIR/migrate-storage.checkpoint"),
+                )
+                .arg(
+                    Arg::new("concurrency")
+                        .long("concurrency")
+                        .help("How many files to copy at the same time")
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value("8"),
+                ),
+        )
+        .subcommand(
+            Command::new("fsck")
+                .about("Check stored file chunks against the database and report drift")
+                .arg(
+                    Arg::new("repair")
+                        .long("repair")
+                        .help("Delete orphaned chunks and flag files with missing chunks")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("rotate-jwt-key")
+                .about("Sign new tokens with a fresh key; the current one keeps verifying until its tokens expire"),
+        )
+        .subcommand(
+            Command::new("run-job")
+                .about("Run a single background housekeeping job once and exit")
+                .arg(
+                    Arg::new("name")
+                        .help("Name of the job to run, e.g. purge-expired-links")
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            Command::new("backup")
+                .about("Write the database, settings and stored chunks into a backup directory")
+                .arg(
+                    Arg::new("path")
+                        .help("Backup directory; an earlier backup in it is updated incrementally")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("full")
+                        .long("full")
+                        .help("Copy every file's chunks, not only the ones changed since the last backup")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("restore")
+                .about("Rebuild an empty instance from a backup directory")
+                .arg(
+                    Arg::new("path")
+                        .help("Backup directory written by `hoodik backup`")
+                        .required(true),
+                ),
         );
 
         let matches = command.get_matches();
         self.subcommand = matches.subcommand_name().map(|s| s.to_string());
+        self.subcommand_matches = matches.subcommand().map(|(_, m)| m.clone());
         self.matches = Some(matches);
     }
 
@@ -457,13 +537,15 @@ mod test {
 
         // Unset: stays None so only per-user quotas apply.
         assert_eq!(
-            vars.maybe_var::<u64>("STORAGE_INSTANCE_QUOTA_BYTES").maybe_get(),
+            vars.maybe_var::<u64>("STORAGE_INSTANCE_QUOTA_BYTES")
+                .maybe_get(),
             None
         );
 
         std::env::set_var("STORAGE_INSTANCE_QUOTA_BYTES", "1048576");
         assert_eq!(
-            vars.maybe_var::<u64>("STORAGE_INSTANCE_QUOTA_BYTES").maybe_get(),
+            vars.maybe_var::<u64>("STORAGE_INSTANCE_QUOTA_BYTES")
+                .maybe_get(),
             Some(1048576)
         );
 
@@ -474,6 +556,44 @@ mod test {
         std::env::remove_var("STORAGE_INSTANCE_QUOTA_BYTES");
     }
 
+    #[test]
+    fn test_jobs_config_vars() {
+        let mut vars = Vars::create("test", "0.1.0", "test");
+
+        let jobs = crate::jobs::JobsConfig::new(&mut vars);
+        assert!(jobs.enabled);
+        assert_eq!(jobs.purge_expired_links_interval_seconds, 3600);
+        assert_eq!(jobs.purge_expired_shares_interval_seconds, 900);
+
+        std::env::set_var("JOB_PURGE_USED_NONCES_INTERVAL_SECONDS", "0");
+        std::env::set_var("JOBS_TICK_SECONDS", "0");
+        let jobs = crate::jobs::JobsConfig::new(&mut vars);
+        assert_eq!(jobs.purge_used_nonces_interval_seconds, 0);
+        assert_eq!(jobs.tick_seconds, 1);
+        assert_eq!(vars.get_warnings().len(), 1);
+
+        std::env::remove_var("JOB_PURGE_USED_NONCES_INTERVAL_SECONDS");
+        std::env::remove_var("JOBS_TICK_SECONDS");
+    }
+
+    #[test]
+    fn test_versions_config_vars() {
+        let mut vars = Vars::create("test", "0.1.0", "test");
+
+        let versions = crate::versions::VersionsConfig::new(&mut vars);
+        assert_eq!(versions.keep_all_days, 7);
+        assert_eq!(versions.max_file_versions, 30);
+
+        std::env::set_var("VERSIONS_KEEP_ALL_DAYS", "30");
+        std::env::set_var("MAX_FILE_VERSIONS", "0");
+        let versions = crate::versions::VersionsConfig::new(&mut vars);
+        assert_eq!(versions.keep_all_days, 30);
+        assert_eq!(versions.max_file_versions, 0);
+
+        std::env::remove_var("VERSIONS_KEEP_ALL_DAYS");
+        std::env::remove_var("MAX_FILE_VERSIONS");
+    }
+
     #[test]
     #[should_panic]
     fn test_vars_fails_on_empty_env() {
@@ -0,0 +1,79 @@
+use crate::vars::Vars;
+
+/// Instance-wide defaults for how much version history of editable files is
+/// kept. Users can replace them with a policy of their own, and a policy set
+/// on a single file wins over both.
+///
+/// History is thinned grandfather-father-son style: everything younger than
+/// `keep_all_days` stays, and past that only the newest version of each of
+/// the last `keep_hourly` hours, `keep_daily` days, `keep_weekly` weeks and
+/// `keep_monthly` months that have any version at all. Pinned versions are
+/// never pruned.
+#[derive(Debug, Clone)]
+pub struct VersionsConfig {
+    /// VERSIONS_KEEP_ALL_DAYS — days during which every version is kept.
+    ///
+    /// *optional*
+    ///
+    /// default: 7
+    pub keep_all_days: u32,
+
+    /// VERSIONS_KEEP_HOURLY — number of hours to keep one version for.
+    ///
+    /// *optional*
+    ///
+    /// default: 24
+    pub keep_hourly: u32,
+
+    /// VERSIONS_KEEP_DAILY — number of days to keep one version for.
+    ///
+    /// *optional*
+    ///
+    /// default: 30
+    pub keep_daily: u32,
+
+    /// VERSIONS_KEEP_WEEKLY — number of weeks to keep one version for.
+    ///
+    /// *optional*
+    ///
+    /// default: 12
+    pub keep_weekly: u32,
+
+    /// VERSIONS_KEEP_MONTHLY — number of months to keep one version for.
+    ///
+    /// *optional*
+    ///
+    /// default: 12
+    pub keep_monthly: u32,
+
+    /// MAX_FILE_VERSIONS — hard cap on the historical versions kept per
+    /// editable file once the thinning is done, oldest dropped first. The
+    /// active version and pinned versions don't count. `0` means no cap.
+    ///
+    /// *optional*
+    ///
+    /// default: 30
+    pub max_file_versions: u32,
+}
+
+impl VersionsConfig {
+    pub(crate) fn new(vars: &mut Vars) -> Self {
+        let keep_all_days = vars.var_default::<u32>("VERSIONS_KEEP_ALL_DAYS", 7).get();
+        let keep_hourly = vars.var_default::<u32>("VERSIONS_KEEP_HOURLY", 24).get();
+        let keep_daily = vars.var_default::<u32>("VERSIONS_KEEP_DAILY", 30).get();
+        let keep_weekly = vars.var_default::<u32>("VERSIONS_KEEP_WEEKLY", 12).get();
+        let keep_monthly = vars.var_default::<u32>("VERSIONS_KEEP_MONTHLY", 12).get();
+        let max_file_versions = vars.var_default::<u32>("MAX_FILE_VERSIONS", 30).get();
+
+        vars.panic_if_errors("VersionsConfig");
+
+        Self {
+            keep_all_days,
+            keep_hourly,
+            keep_daily,
+            keep_weekly,
+            keep_monthly,
+            max_file_versions,
+        }
+    }
+}
@@ -0,0 +1,66 @@
+//! # JWT signing keys
+use entity::jwt_signing_keys;
+use std::sync::{
+    atomic::{AtomicI64, Ordering},
+    Arc, RwLock,
+};
+
+/// In-memory copy of `jwt_signing_keys`, so verifying a token costs no
+/// database round trip. Filled and refreshed by the `auth` crate; a key
+/// rotated from another process is picked up once the copy goes stale.
+#[derive(Clone, Debug, Default)]
+pub struct JwtKeys {
+    inner: Arc<RwLock<Option<Loaded>>>,
+    forced_at: Arc<AtomicI64>,
+}
+
+#[derive(Debug)]
+struct Loaded {
+    at: i64,
+    keys: Vec<jwt_signing_keys::Model>,
+}
+
+impl JwtKeys {
+    /// The cached keys, or `None` when they were never loaded or were loaded
+    /// more than `max_age` seconds before `now`.
+    pub fn fresh(&self, now: i64, max_age: i64) -> Option<Vec<jwt_signing_keys::Model>> {
+        let inner = self.inner.read().unwrap_or_else(|e| e.into_inner());
+
+        inner
+            .as_ref()
+            .filter(|loaded| loaded.at + max_age > now)
+            .map(|loaded| loaded.keys.clone())
+    }
+
+    /// The cached keys however old, empty when never loaded.
+    pub fn cached(&self) -> Vec<jwt_signing_keys::Model> {
+        let inner = self.inner.read().unwrap_or_else(|e| e.into_inner());
+
+        inner
+            .as_ref()
+            .map(|loaded| loaded.keys.clone())
+            .unwrap_or_default()
+    }
+
+    /// Replace the cached keys with ones loaded at `now`.
+    pub fn set(&self, now: i64, keys: Vec<jwt_signing_keys::Model>) {
+        let mut inner = self.inner.write().unwrap_or_else(|e| e.into_inner());
+
+        *inner = Some(Loaded { at: now, keys });
+    }
+
+    /// Claim the right to reload the keys before they go stale, granted at
+    /// most once every `interval` seconds so tokens naming made-up keys
+    /// cannot turn every request into a database read.
+    pub fn force(&self, now: i64, interval: i64) -> bool {
+        let last = self.forced_at.load(Ordering::Acquire);
+
+        if last != 0 && last + interval > now {
+            return false;
+        }
+
+        self.forced_at
+            .compare_exchange(last, now, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok()
+    }
+}
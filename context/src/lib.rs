@@ -3,6 +3,12 @@ use email::Sender;
 use error::AppResult;
 use sea_orm::Database;
 
+pub mod jwt_keys;
+pub mod rate_limit;
+
+pub use jwt_keys::JwtKeys;
+pub use rate_limit::{RateLimitBackend, RateLimits};
+
 /// Re-export the database connection type
 pub use sea_orm::DatabaseConnection;
 
@@ -18,6 +24,8 @@ pub struct Context {
     pub db: DatabaseConnection,
     pub sender: Option<Sender>,
     pub settings: Settings,
+    pub jwt_keys: JwtKeys,
+    pub rate_limits: RateLimits,
 }
 
 /// We need to implement clone for the context manually because
@@ -29,22 +37,29 @@ impl Clone for Context {
     fn clone(&self) -> Context {
         Context {
             config: self.config.clone(),
-            db: match &self.db {
-                DatabaseConnection::SqlxPostgresPoolConnection(conn) => {
-                    DatabaseConnection::SqlxPostgresPoolConnection(conn.clone())
-                }
-                DatabaseConnection::SqlxSqlitePoolConnection(conn) => {
-                    DatabaseConnection::SqlxSqlitePoolConnection(conn.clone())
-                }
-                DatabaseConnection::Disconnected => DatabaseConnection::Disconnected,
-
-                #[cfg(feature = "mock")]
-                DatabaseConnection::MockDatabaseConnection(conn) => {
-                    DatabaseConnection::MockDatabaseConnection(conn.clone())
-                }
-            },
+            db: clone_connection(&self.db),
             sender: self.sender.clone(),
             settings: self.settings.clone(),
+            jwt_keys: self.jwt_keys.clone(),
+            rate_limits: self.rate_limits.clone(),
+        }
+    }
+}
+
+/// Clone of a connection sharing its pool.
+pub fn clone_connection(db: &DatabaseConnection) -> DatabaseConnection {
+    match db {
+        DatabaseConnection::SqlxPostgresPoolConnection(conn) => {
+            DatabaseConnection::SqlxPostgresPoolConnection(conn.clone())
+        }
+        DatabaseConnection::SqlxSqlitePoolConnection(conn) => {
+            DatabaseConnection::SqlxSqlitePoolConnection(conn.clone())
+        }
+        DatabaseConnection::Disconnected => DatabaseConnection::Disconnected,
+
+        #[cfg(feature = "mock")]
+        DatabaseConnection::MockDatabaseConnection(conn) => {
+            DatabaseConnection::MockDatabaseConnection(conn.clone())
         }
     }
 }
@@ -62,11 +77,16 @@ impl Context {
 
         let settings = Settings::default().create(&config).await?;
 
+        let rate_limits =
+            rate_limit::from_config(&config.auth.rate_limit_backend, clone_connection(&db));
+
         Ok(Context {
             config,
             db,
             sender,
             settings,
+            jwt_keys: JwtKeys::default(),
+            rate_limits,
         })
     }
 
@@ -75,11 +95,16 @@ impl Context {
         let config = Config::mock_with_env();
         let settings = Settings::mock();
 
+        let rate_limits =
+            rate_limit::from_config(&config.auth.rate_limit_backend, clone_connection(&db));
+
         Context {
             config,
             db,
             sender: None,
             settings,
+            jwt_keys: JwtKeys::default(),
+            rate_limits,
         }
     }
 
@@ -88,11 +113,18 @@ impl Context {
         let config = Config::mock_with_env();
         let settings = Settings::mock();
 
+        let rate_limits = rate_limit::from_config(
+            &config.auth.rate_limit_backend,
+            DatabaseConnection::Disconnected,
+        );
+
         Context {
             config,
             db: DatabaseConnection::Disconnected,
             sender: None,
             settings,
+            jwt_keys: JwtKeys::default(),
+            rate_limits,
         }
     }
 
@@ -110,11 +142,16 @@ impl Context {
         let db = Self::mock_db_connection(data_dir.as_deref()).await;
         let settings = Settings::mock();
 
+        let rate_limits =
+            rate_limit::from_config(&config.auth.rate_limit_backend, clone_connection(&db));
+
         let context = Context {
             config,
             db,
             sender: None,
             settings,
+            jwt_keys: JwtKeys::default(),
+            rate_limits,
         };
 
         migration::Migrator::up(&context.db, None).await.unwrap();
@@ -135,11 +172,16 @@ impl Context {
         let db = Self::mock_db_connection(None).await;
         let settings = Settings::mock();
 
+        let rate_limits =
+            rate_limit::from_config(&config.auth.rate_limit_backend, clone_connection(&db));
+
         let context = Context {
             config,
             db,
             sender: None,
             settings,
+            jwt_keys: JwtKeys::default(),
+            rate_limits,
         };
 
         migration::Migrator::up(&context.db, None).await.unwrap();
@@ -172,11 +214,16 @@ impl Context {
         let db = Database::connect(opt).await.unwrap();
         let settings = Settings::mock();
 
+        let rate_limits =
+            rate_limit::from_config(&config.auth.rate_limit_backend, clone_connection(&db));
+
         let context = Context {
             config,
             db,
             sender: None,
             settings,
+            jwt_keys: JwtKeys::default(),
+            rate_limits,
         };
 
         migration::Migrator::up(&context.db, None).await.unwrap();
@@ -198,10 +245,7 @@ impl Context {
     }
 
     #[cfg(feature = "mock")]
-    async fn bootstrap_fresh_pg(
-        admin_url: &str,
-        slug_hint: Option<&str>,
-    ) -> DatabaseConnection {
+    async fn bootstrap_fresh_pg(admin_url: &str, slug_hint: Option<&str>) -> DatabaseConnection {
         use sea_orm::{ConnectionTrait, Statement};
 
         let slug_source = slug_hint
@@ -209,10 +253,20 @@ impl Context {
             .unwrap_or_else(|| format!("mock_{}", entity::Uuid::new_v4().simple()));
         let sanitized: String = slug_source
             .chars()
-            .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+            .map(|c| {
+                if c.is_ascii_alphanumeric() {
+                    c.to_ascii_lowercase()
+                } else {
+                    '_'
+                }
+            })
             .collect();
         let unique = entity::Uuid::new_v4().simple().to_string();
-        let db_name = format!("hoodik_test_{}_{}", &sanitized[..sanitized.len().min(32)], &unique[..8]);
+        let db_name = format!(
+            "hoodik_test_{}_{}",
+            &sanitized[..sanitized.len().min(32)],
+            &unique[..8]
+        );
 
         let admin = Database::connect(admin_url).await.unwrap();
         let backend = admin.get_database_backend();
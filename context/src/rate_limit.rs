@@ -0,0 +1,253 @@
+//! # Rate limit windows
+//!
+//! Sliding windows of hits kept under a key, shared by every rate limiter in
+//! the application. Which backend keeps them is chosen with
+//! `RATE_LIMIT_BACKEND`: process memory, lost on restart and private to one
+//! server process, or the database, shared by every process using it.
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+use entity::{
+    rate_limit_hits, ActiveValue, ColumnTrait, ConnectionTrait, DbBackend, EntityTrait, Expr,
+    PaginatorTrait, Query, QueryFilter, SimpleExpr, Statement, SubQueryStatement, TransactionTrait,
+    Uuid,
+};
+use error::AppResult;
+use sea_orm::DatabaseConnection;
+
+/// Ceiling on distinct keys the memory backend tracks. Crossing it sweeps out
+/// windows whose most recent hit has already aged out, reclaiming the long
+/// tail of one-off keys without a background task.
+const MAX_TRACKED_KEYS: usize = 50_000;
+
+/// Where the rate limiters keep their windows.
+#[async_trait::async_trait]
+pub trait RateLimitBackend: Send + Sync {
+    /// Number of hits recorded under `key` at or after `since`.
+    async fn count(&self, key: &str, since: i64) -> AppResult<u64>;
+
+    /// Record one hit under `key` at `now`. Hits more than `window` seconds
+    /// old no longer count for any limiter using the key and may be
+    /// forgotten.
+    async fn record(&self, key: &str, now: i64, window: i64) -> AppResult<()>;
+
+    /// Record one hit under `key` at `now` unless `limit` hits are already
+    /// counted within `window` seconds before it. Counting and recording are
+    /// one step, so concurrent callers cannot all find room for the last
+    /// hit. Returns the hit, for [`Self::forget`], or `None` when full.
+    async fn acquire(
+        &self,
+        key: &str,
+        now: i64,
+        window: i64,
+        limit: u64,
+    ) -> AppResult<Option<Uuid>>;
+
+    /// Remove a hit [`Self::acquire`] recorded, so it no longer counts.
+    async fn forget(&self, key: &str, hit: Uuid) -> AppResult<()>;
+}
+
+/// The backend the application was configured with.
+pub type RateLimits = Arc<dyn RateLimitBackend>;
+
+/// Build the backend named by `RATE_LIMIT_BACKEND`. The configuration only
+/// lets `memory` and `database` through.
+pub fn from_config(backend: &str, db: DatabaseConnection) -> RateLimits {
+    match backend {
+        "database" => Arc::new(DatabaseRateLimits::new(db)),
+        "memory" => Arc::new(MemoryRateLimits::default()),
+        other => panic!("Unsupported rate limit backend '{other}'"),
+    }
+}
+
+/// Windows kept in the server process.
+#[derive(Default)]
+pub struct MemoryRateLimits {
+    windows: Mutex<HashMap<String, VecDeque<(i64, Uuid)>>>,
+}
+
+impl MemoryRateLimits {
+    fn push(
+        windows: &mut HashMap<String, VecDeque<(i64, Uuid)>>,
+        key: &str,
+        now: i64,
+        window: i64,
+    ) -> Uuid {
+        let cutoff = now - window;
+        let hit = Uuid::new_v4();
+
+        let hits = windows.entry(key.to_string()).or_default();
+        while hits.front().is_some_and(|&(t, _)| t < cutoff) {
+            hits.pop_front();
+        }
+        hits.push_back((now, hit));
+
+        if windows.len() > MAX_TRACKED_KEYS {
+            windows.retain(|_, w| w.back().is_some_and(|&(t, _)| t >= cutoff));
+        }
+
+        hit
+    }
+}
+
+#[async_trait::async_trait]
+impl RateLimitBackend for MemoryRateLimits {
+    async fn count(&self, key: &str, since: i64) -> AppResult<u64> {
+        let windows = self.windows.lock().expect("rate-limit windows poisoned");
+
+        Ok(windows
+            .get(key)
+            .map(|w| w.iter().filter(|&&(t, _)| t >= since).count() as u64)
+            .unwrap_or_default())
+    }
+
+    async fn record(&self, key: &str, now: i64, window: i64) -> AppResult<()> {
+        let mut windows = self.windows.lock().expect("rate-limit windows poisoned");
+        Self::push(&mut windows, key, now, window);
+
+        Ok(())
+    }
+
+    async fn acquire(
+        &self,
+        key: &str,
+        now: i64,
+        window: i64,
+        limit: u64,
+    ) -> AppResult<Option<Uuid>> {
+        let since = now - window;
+        let mut windows = self.windows.lock().expect("rate-limit windows poisoned");
+
+        let counted = windows
+            .get(key)
+            .map(|w| w.iter().filter(|&&(t, _)| t >= since).count() as u64)
+            .unwrap_or_default();
+
+        if counted >= limit {
+            return Ok(None);
+        }
+
+        Ok(Some(Self::push(&mut windows, key, now, window)))
+    }
+
+    async fn forget(&self, key: &str, hit: Uuid) -> AppResult<()> {
+        let mut windows = self.windows.lock().expect("rate-limit windows poisoned");
+
+        if let Some(hits) = windows.get_mut(key) {
+            hits.retain(|&(_, id)| id != hit);
+        }
+
+        Ok(())
+    }
+}
+
+/// Windows kept in the `rate_limit_hits` table.
+pub struct DatabaseRateLimits {
+    db: DatabaseConnection,
+}
+
+impl DatabaseRateLimits {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait::async_trait]
+impl RateLimitBackend for DatabaseRateLimits {
+    async fn count(&self, key: &str, since: i64) -> AppResult<u64> {
+        let count = rate_limit_hits::Entity::find()
+            .filter(rate_limit_hits::Column::Key.eq(key))
+            .filter(rate_limit_hits::Column::CreatedAt.gte(since))
+            .count(&self.db)
+            .await?;
+
+        Ok(count)
+    }
+
+    async fn record(&self, key: &str, now: i64, window: i64) -> AppResult<()> {
+        rate_limit_hits::Entity::insert(rate_limit_hits::ActiveModel {
+            id: ActiveValue::Set(Uuid::new_v4()),
+            key: ActiveValue::Set(key.to_string()),
+            created_at: ActiveValue::Set(now),
+            expires_at: ActiveValue::Set(now + window),
+        })
+        .exec_without_returning(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// The row goes in through `INSERT ... SELECT ... WHERE (count) < limit`,
+    /// which SQLite runs as one write. Postgres could still let two of them
+    /// count the same rows, so there they queue on a lock taken on the key.
+    async fn acquire(
+        &self,
+        key: &str,
+        now: i64,
+        window: i64,
+        limit: u64,
+    ) -> AppResult<Option<Uuid>> {
+        let hit = Uuid::new_v4();
+        let txn = self.db.begin().await?;
+        let backend = txn.get_database_backend();
+
+        if backend == DbBackend::Postgres {
+            txn.execute(Statement::from_sql_and_values(
+                backend,
+                "SELECT pg_advisory_xact_lock(hashtext($1))",
+                [key.into()],
+            ))
+            .await?;
+        }
+
+        let counted = Query::select()
+            .expr(Expr::col(rate_limit_hits::Column::Id).count())
+            .from(rate_limit_hits::Entity)
+            .and_where(rate_limit_hits::Column::Key.eq(key))
+            .and_where(rate_limit_hits::Column::CreatedAt.gte(now - window))
+            .to_owned();
+
+        let row = Query::select()
+            .exprs([
+                Expr::val(hit),
+                Expr::val(key),
+                Expr::val(now),
+                Expr::val(now + window),
+            ])
+            .and_where(
+                Expr::expr(SimpleExpr::SubQuery(
+                    None,
+                    Box::new(SubQueryStatement::SelectStatement(counted)),
+                ))
+                .lt(limit as i64),
+            )
+            .to_owned();
+
+        let insert = Query::insert()
+            .into_table(rate_limit_hits::Entity)
+            .columns([
+                rate_limit_hits::Column::Id,
+                rate_limit_hits::Column::Key,
+                rate_limit_hits::Column::CreatedAt,
+                rate_limit_hits::Column::ExpiresAt,
+            ])
+            .select_from(row)
+            .map_err(|e| error::Error::InternalError(e.to_string()))?
+            .to_owned();
+
+        let inserted = txn.execute(backend.build(&insert)).await?.rows_affected();
+        txn.commit().await?;
+
+        Ok((inserted == 1).then_some(hit))
+    }
+
+    async fn forget(&self, _key: &str, hit: Uuid) -> AppResult<()> {
+        rate_limit_hits::Entity::delete_by_id(hit)
+            .exec(&self.db)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -16,8 +16,12 @@ pub fn generate_key() -> CryptoResult<Vec<u8>> {
 pub fn encrypt(key: Vec<u8>, plaintext: Vec<u8>) -> CryptoResult<Vec<u8>> {
     let (k, n) = split_key_nonce(key);
     let (ct, tag) = Aegis128L::<TAG_LENGTH>::new(
-        k.as_slice().try_into().map_err(|_| Error::UnknownCipher("bad key length".into()))?,
-        n.as_slice().try_into().map_err(|_| Error::UnknownCipher("bad nonce length".into()))?,
+        k.as_slice()
+            .try_into()
+            .map_err(|_| Error::UnknownCipher("bad key length".into()))?,
+        n.as_slice()
+            .try_into()
+            .map_err(|_| Error::UnknownCipher("bad nonce length".into()))?,
     )
     .encrypt(&plaintext, &[]);
 
@@ -40,8 +44,12 @@ pub fn decrypt(key: Vec<u8>, ciphertext_with_tag: Vec<u8>) -> CryptoResult<Vec<u
         .map_err(|_| Error::UnknownCipher("bad tag length".into()))?;
 
     Aegis128L::<TAG_LENGTH>::new(
-        k.as_slice().try_into().map_err(|_| Error::UnknownCipher("bad key length".into()))?,
-        n.as_slice().try_into().map_err(|_| Error::UnknownCipher("bad nonce length".into()))?,
+        k.as_slice()
+            .try_into()
+            .map_err(|_| Error::UnknownCipher("bad key length".into()))?,
+        n.as_slice()
+            .try_into()
+            .map_err(|_| Error::UnknownCipher("bad nonce length".into()))?,
     )
     .decrypt(ct, tag, &[])
     .map_err(|_| Error::UnknownCipher("aegis128l decryption failed".into()))
@@ -108,9 +108,6 @@ mod test {
 
         let decrypted = super::decrypt(key, encrypted.clone()).unwrap();
 
-        assert_eq!(
-            non_ascii,
-            &String::from_utf8(decrypted).unwrap()
-        );
+        assert_eq!(non_ascii, &String::from_utf8(decrypted).unwrap());
     }
 }
@@ -421,9 +421,7 @@ pub fn encode_share_request_v1(payload: &ShareRequestPayloadV1) -> CryptoResult<
 }
 
 /// DER-encode the per-row sender signature input (`AuditEventSigInputV1`).
-pub fn encode_audit_event_sig_input_v1(
-    payload: &AuditEventSigInputV1,
-) -> CryptoResult<Vec<u8>> {
+pub fn encode_audit_event_sig_input_v1(payload: &AuditEventSigInputV1) -> CryptoResult<Vec<u8>> {
     Ok(AuditEventSigInputV1Der::from_native(payload)?.to_vec()?)
 }
 
@@ -756,7 +754,9 @@ mod tests {
     fn audit_event_sig_fixture() -> AuditEventSigInputV1 {
         AuditEventSigInputV1 {
             sender_id: *b"\xAA\xAA\xAA\xAA\xAA\xAA\xAA\xAA\xAA\xAA\xAA\xAA\xAA\xAA\xAA\xAA",
-            recipient_id: Some(*b"\xBB\xBB\xBB\xBB\xBB\xBB\xBB\xBB\xBB\xBB\xBB\xBB\xBB\xBB\xBB\xBB"),
+            recipient_id: Some(
+                *b"\xBB\xBB\xBB\xBB\xBB\xBB\xBB\xBB\xBB\xBB\xBB\xBB\xBB\xBB\xBB\xBB",
+            ),
             file_id: *b"\xCC\xCC\xCC\xCC\xCC\xCC\xCC\xCC\xCC\xCC\xCC\xCC\xCC\xCC\xCC\xCC",
             action: AuditEventActionEnum::RoleChange,
             share_role_before: Some(ShareRoleEnum::Reader),
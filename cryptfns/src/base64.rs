@@ -11,6 +11,18 @@ pub fn decode(input: &str) -> CryptoResult<Vec<u8>> {
     general_purpose::STANDARD.decode(input).map_err(Error::from)
 }
 
+/// Encode input to unpadded base64url, the encoding WebAuthn uses on the wire
+pub fn encode_url<T: AsRef<[u8]>>(input: T) -> String {
+    general_purpose::URL_SAFE_NO_PAD.encode(input)
+}
+
+/// Decode unpadded base64url string to bytes
+pub fn decode_url(input: &str) -> CryptoResult<Vec<u8>> {
+    general_purpose::URL_SAFE_NO_PAD
+        .decode(input)
+        .map_err(Error::from)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -1,5 +1,5 @@
-use crate::{aegis, aegis256, aes, chacha};
 use crate::error::{CryptoResult, Error};
+use crate::{aegis, aegis256, aes, chacha};
 
 /// Default cipher identifier — used when no cipher is specified.
 pub const DEFAULT: &str = "aegis128l";
@@ -36,7 +36,6 @@ impl std::str::FromStr for Cipher {
 }
 
 impl Cipher {
-
     /// Return the canonical string identifier for this cipher.
     pub fn as_str(&self) -> &'static str {
         match self {
@@ -192,7 +191,10 @@ mod tests {
     fn from_str_defaults() {
         assert_eq!(Cipher::from_str("ascon128a").unwrap(), Cipher::Ascon128a);
         assert_eq!(Cipher::from_str("").unwrap(), Cipher::Ascon128a);
-        assert_eq!(Cipher::from_str("chacha20poly1305").unwrap(), Cipher::ChaCha20Poly1305);
+        assert_eq!(
+            Cipher::from_str("chacha20poly1305").unwrap(),
+            Cipher::ChaCha20Poly1305
+        );
         assert_eq!(Cipher::from_str("aegis128l").unwrap(), Cipher::Aegis128L);
         assert_eq!(Cipher::from_str("aegis256").unwrap(), Cipher::Aegis256);
     }
@@ -281,7 +283,9 @@ mod tests {
             let key = cipher.generate_key().unwrap();
             for index in [0u64, 1, 7, u64::MAX] {
                 let plaintext = format!("chunk {index}").into_bytes();
-                let ciphertext = cipher.encrypt_chunk(&key, index, plaintext.clone()).unwrap();
+                let ciphertext = cipher
+                    .encrypt_chunk(&key, index, plaintext.clone())
+                    .unwrap();
                 let recovered = cipher.decrypt_chunk(&key, index, ciphertext).unwrap();
                 assert_eq!(plaintext, recovered, "{} chunk {index}", cipher.as_str());
             }
@@ -298,12 +302,18 @@ mod tests {
         // Under a shared nonce the keystream cancels: ct_a XOR ct_b == pt_a XOR pt_b.
         let leak_a = cipher.encrypt(key.clone(), zeros.clone()).unwrap();
         let leak_b = cipher.encrypt(key.clone(), ones.clone()).unwrap();
-        assert!(leak_a[..64].iter().zip(&leak_b[..64]).all(|(a, b)| a ^ b == 0xFF));
+        assert!(leak_a[..64]
+            .iter()
+            .zip(&leak_b[..64])
+            .all(|(a, b)| a ^ b == 0xFF));
 
         // Per-chunk nonces must break that relation.
         let ct_a = cipher.encrypt_chunk(&key, 1, zeros).unwrap();
         let ct_b = cipher.encrypt_chunk(&key, 2, ones).unwrap();
-        assert!(!ct_a[..64].iter().zip(&ct_b[..64]).all(|(a, b)| a ^ b == 0xFF));
+        assert!(!ct_a[..64]
+            .iter()
+            .zip(&ct_b[..64])
+            .all(|(a, b)| a ^ b == 0xFF));
     }
 
     #[test]
@@ -325,7 +335,9 @@ mod tests {
     fn chunk_at_wrong_index_fails_authentication() {
         let cipher = Cipher::Aegis128L;
         let key = cipher.generate_key().unwrap();
-        let ciphertext = cipher.encrypt_chunk(&key, 2, b"reordered".to_vec()).unwrap();
+        let ciphertext = cipher
+            .encrypt_chunk(&key, 2, b"reordered".to_vec())
+            .unwrap();
         assert!(cipher.decrypt_chunk(&key, 3, ciphertext).is_err());
     }
 
@@ -353,7 +365,9 @@ mod tests {
             let key = cipher.generate_key().unwrap();
             let plaintext = b"same name twice".to_vec();
             assert_ne!(
-                cipher.encrypt_string(key.clone(), plaintext.clone()).unwrap(),
+                cipher
+                    .encrypt_string(key.clone(), plaintext.clone())
+                    .unwrap(),
                 cipher.encrypt_string(key, plaintext).unwrap(),
                 "{} must randomize the nonce",
                 cipher.as_str()
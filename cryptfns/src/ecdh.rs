@@ -98,7 +98,8 @@ pub fn wrap(file_key: &[u8], recipient_public_key: &str) -> CryptoResult<String>
     aead_key.extend_from_slice(&nonce);
     let ciphertext = crate::aegis256::encrypt(aead_key.to_vec(), file_key.to_vec())?;
 
-    let mut blob = Vec::with_capacity(1 + X_PUBLIC_LEN + MK_CT_LEN + NONCE_LENGTH + ciphertext.len());
+    let mut blob =
+        Vec::with_capacity(1 + X_PUBLIC_LEN + MK_CT_LEN + NONCE_LENGTH + ciphertext.len());
     blob.push(BLOB_VERSION);
     blob.extend_from_slice(ephemeral_public.as_bytes());
     blob.extend_from_slice(mk_ct.as_ref());
@@ -115,7 +116,10 @@ pub fn unwrap(blob: &str, private_key: &str) -> CryptoResult<Vec<u8>> {
         return Err(Error::InvalidLength("hybrid wrap blob too short"));
     }
     if blob[0] != BLOB_VERSION {
-        return Err(Error::KeyEncoding(format!("unsupported wrap version {}", blob[0])));
+        return Err(Error::KeyEncoding(format!(
+            "unsupported wrap version {}",
+            blob[0]
+        )));
     }
 
     let ct_offset = 1 + X_PUBLIC_LEN + MK_CT_LEN + NONCE_LENGTH;
@@ -166,8 +170,9 @@ fn derive_wrap_key(
     ikm.extend_from_slice(ss_k);
     ikm.extend_from_slice(ss_x);
 
-    let mut salt =
-        Vec::with_capacity(ephemeral_public.len() + mk_ct.len() + recipient_x_pk.len() + recipient_mk_ek.len());
+    let mut salt = Vec::with_capacity(
+        ephemeral_public.len() + mk_ct.len() + recipient_x_pk.len() + recipient_mk_ek.len(),
+    );
     salt.extend_from_slice(ephemeral_public);
     salt.extend_from_slice(mk_ct);
     salt.extend_from_slice(recipient_x_pk);
@@ -185,13 +190,17 @@ fn derive_wrap_key(
 /// contributed nothing and the "secret" is attacker-known.
 fn reject_non_contributory(shared: &x25519_dalek::SharedSecret) -> CryptoResult<()> {
     if shared.as_bytes().iter().all(|b| *b == 0) {
-        return Err(Error::KeyEncoding("non-contributory x25519 public key".to_string()));
+        return Err(Error::KeyEncoding(
+            "non-contributory x25519 public key".to_string(),
+        ));
     }
     Ok(())
 }
 
 fn to_array<const N: usize>(bytes: &[u8]) -> CryptoResult<[u8; N]> {
-    bytes.try_into().map_err(|_| Error::InvalidLength("unexpected key component length"))
+    bytes
+        .try_into()
+        .map_err(|_| Error::InvalidLength("unexpected key component length"))
 }
 
 fn encode_pem(label: &str, body: &[u8]) -> CryptoResult<String> {
@@ -202,7 +211,9 @@ fn decode_pem(expected_label: &str, pem: &str) -> CryptoResult<Vec<u8>> {
     let (label, data) =
         decode_vec(pem.as_bytes()).map_err(|e| Error::KeyEncoding(e.to_string()))?;
     if label != expected_label {
-        return Err(Error::KeyEncoding(format!("expected {expected_label} pem, got {label}")));
+        return Err(Error::KeyEncoding(format!(
+            "expected {expected_label} pem, got {label}"
+        )));
     }
     Ok(data)
 }
@@ -359,8 +370,9 @@ mod tests {
             -----END PUBLIC KEY-----\n";
         assert!(public::from_str(X25519_SPKI).is_err());
 
-        let ed = crate::ed25519::public::from_private(&crate::ed25519::private::generate().unwrap())
-            .unwrap();
+        let ed =
+            crate::ed25519::public::from_private(&crate::ed25519::private::generate().unwrap())
+                .unwrap();
         assert!(public::from_str(&ed).is_err());
     }
 
@@ -441,8 +453,7 @@ mod tests {
         let mut bytes = crate::base64::decode(&blob).unwrap();
 
         let container = public::from_str(&public).unwrap();
-        let recipient_mk =
-            MlKem768PublicKey::try_from(&container[1 + 32..]).unwrap();
+        let recipient_mk = MlKem768PublicKey::try_from(&container[1 + 32..]).unwrap();
         let (other_ct, _) = mlkem768::encapsulate(&recipient_mk, [9u8; SHARED_SECRET_SIZE]);
 
         let ct_start = 1 + 32;
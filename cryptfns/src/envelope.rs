@@ -57,7 +57,11 @@ pub fn seal(kek: &[u8; KEK_LENGTH], bundle: &[u8]) -> CryptoResult<String> {
     let wrapped_data_key = crate::aegis256::encrypt(wrap_key(kek, &kek_nonce), data_key.clone())?;
     let ciphertext = crate::aegis256::encrypt(data_key, bundle.to_vec())?;
 
-    Ok(crate::base64::encode(assemble(&kek_nonce, &wrapped_data_key, &ciphertext)))
+    Ok(crate::base64::encode(assemble(
+        &kek_nonce,
+        &wrapped_data_key,
+        &ciphertext,
+    )))
 }
 
 /// Open an envelope: recover the data key with `kek`, then the bundle.
@@ -83,7 +87,8 @@ pub fn rewrap(
     let blob = crate::base64::decode(envelope)?;
     let (kek_nonce, wrapped_data_key, ciphertext) = parse(&blob)?;
 
-    let data_key = crate::aegis256::decrypt(wrap_key(old_kek, kek_nonce), wrapped_data_key.to_vec())?;
+    let data_key =
+        crate::aegis256::decrypt(wrap_key(old_kek, kek_nonce), wrapped_data_key.to_vec())?;
     if data_key.len() != DATA_KEY_LENGTH {
         return Err(Error::InvalidLength("envelope data key wrong length"));
     }
@@ -92,7 +97,11 @@ pub fn rewrap(
     getrandom::getrandom(&mut new_nonce)?;
     let new_wrapped_data_key = crate::aegis256::encrypt(wrap_key(new_kek, &new_nonce), data_key)?;
 
-    Ok(crate::base64::encode(assemble(&new_nonce, &new_wrapped_data_key, ciphertext)))
+    Ok(crate::base64::encode(assemble(
+        &new_nonce,
+        &new_wrapped_data_key,
+        ciphertext,
+    )))
 }
 
 fn wrap_key(kek: &[u8; KEK_LENGTH], nonce: &[u8]) -> Vec<u8> {
@@ -102,7 +111,8 @@ fn wrap_key(kek: &[u8; KEK_LENGTH], nonce: &[u8]) -> Vec<u8> {
 }
 
 fn assemble(kek_nonce: &[u8], wrapped_data_key: &[u8], ciphertext: &[u8]) -> Vec<u8> {
-    let mut out = Vec::with_capacity(1 + KEK_NONCE_LENGTH + 2 + wrapped_data_key.len() + ciphertext.len());
+    let mut out =
+        Vec::with_capacity(1 + KEK_NONCE_LENGTH + 2 + wrapped_data_key.len() + ciphertext.len());
     out.push(VERSION);
     out.extend_from_slice(kek_nonce);
     out.extend_from_slice(&(wrapped_data_key.len() as u16).to_be_bytes());
@@ -117,7 +127,10 @@ fn parse(blob: &[u8]) -> CryptoResult<(&[u8], &[u8], &[u8])> {
         return Err(Error::InvalidLength("envelope too short"));
     }
     if blob[0] != VERSION {
-        return Err(Error::KeyEncoding(format!("unsupported envelope version {}", blob[0])));
+        return Err(Error::KeyEncoding(format!(
+            "unsupported envelope version {}",
+            blob[0]
+        )));
     }
 
     let kek_nonce = &blob[1..1 + KEK_NONCE_LENGTH];
@@ -167,7 +180,10 @@ mod tests {
         let a = crate::base64::decode(&super::seal(&kek, b"same").unwrap()).unwrap();
         let b = crate::base64::decode(&super::seal(&kek, b"same").unwrap()).unwrap();
         // kek_nonce and the fresh data key both differ, so nothing repeats.
-        assert_ne!(a[1..1 + super::KEK_NONCE_LENGTH], b[1..1 + super::KEK_NONCE_LENGTH]);
+        assert_ne!(
+            a[1..1 + super::KEK_NONCE_LENGTH],
+            b[1..1 + super::KEK_NONCE_LENGTH]
+        );
         assert_ne!(a, b);
     }
 
@@ -204,8 +220,14 @@ mod tests {
 
     #[test]
     fn derive_kek_is_deterministic() {
-        assert_eq!(super::derive_kek(b"export").unwrap(), super::derive_kek(b"export").unwrap());
-        assert_ne!(super::derive_kek(b"a").unwrap(), super::derive_kek(b"b").unwrap());
+        assert_eq!(
+            super::derive_kek(b"export").unwrap(),
+            super::derive_kek(b"export").unwrap()
+        );
+        assert_ne!(
+            super::derive_kek(b"a").unwrap(),
+            super::derive_kek(b"b").unwrap()
+        );
     }
 
     #[test]
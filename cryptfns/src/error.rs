@@ -31,6 +31,8 @@ pub enum Error {
     /// Key parsing/encoding failures from the Curve25519 PKCS#8/SPKI stack,
     /// which uses a newer `der` major than the RSA-era variants above.
     KeyEncoding(String),
+    /// A WebAuthn ceremony that failed verification, with the reason.
+    WebAuthn(&'static str),
     #[cfg(feature = "tokenizer")]
     TokenizersError(TokenizersError),
 }
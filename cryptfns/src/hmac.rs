@@ -0,0 +1,54 @@
+//! # HMAC
+//!
+//! HMAC-SHA256 tags for tokens the server hands out and later takes back,
+//! keyed with a secret only the server holds.
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Hex encoded tag of `message` under `key`.
+pub fn sign(key: &[u8], message: &[u8]) -> String {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC takes a key of any length");
+    mac.update(message);
+
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Whether `tag` is the hex encoded tag of `message` under `key`, compared in
+/// constant time so the time taken does not tell how much of a guess was
+/// right.
+pub fn verify(key: &[u8], message: &[u8], tag: &str) -> bool {
+    let Ok(tag) = hex::decode(tag) else {
+        return false;
+    };
+
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC takes a key of any length");
+    mac.update(message);
+
+    mac.verify_slice(&tag).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_rfc_4231_test_case_2() {
+        assert_eq!(
+            sign(b"Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn verifies_only_the_right_tag() {
+        let tag = sign(b"key", b"message");
+
+        assert!(verify(b"key", b"message", &tag));
+        assert!(!verify(b"other key", b"message", &tag));
+        assert!(!verify(b"key", b"other message", &tag));
+        assert!(!verify(b"key", b"message", "not hex"));
+    }
+}
@@ -95,7 +95,10 @@ mod tests {
     fn from_str_round_trips_and_defaults() {
         assert_eq!(KeyType::from_str("rsa").unwrap(), KeyType::Rsa);
         assert_eq!(KeyType::from_str("").unwrap(), KeyType::Rsa);
-        assert_eq!(KeyType::from_str("curve25519").unwrap(), KeyType::Curve25519);
+        assert_eq!(
+            KeyType::from_str("curve25519").unwrap(),
+            KeyType::Curve25519
+        );
         assert!(KeyType::from_str("ed448").is_err());
 
         for key_type in [KeyType::Rsa, KeyType::Curve25519] {
@@ -109,8 +112,12 @@ mod tests {
         let ed_public = crate::ed25519::public::from_private(&ed_private).unwrap();
         let signature = crate::ed25519::private::sign("message", &ed_private).unwrap();
 
-        KeyType::Curve25519.verify("message", &signature, &ed_public).unwrap();
-        assert!(KeyType::Rsa.verify("message", &signature, &ed_public).is_err());
+        KeyType::Curve25519
+            .verify("message", &signature, &ed_public)
+            .unwrap();
+        assert!(KeyType::Rsa
+            .verify("message", &signature, &ed_public)
+            .is_err());
 
         let rsa_private = crate::rsa::private::generate().unwrap();
         let rsa_private_pem = crate::rsa::private::to_string(&rsa_private).unwrap();
@@ -118,8 +125,12 @@ mod tests {
         let rsa_public_pem = crate::rsa::public::to_string(&rsa_public).unwrap();
         let signature = crate::rsa::private::sign("message", &rsa_private_pem).unwrap();
 
-        KeyType::Rsa.verify("message", &signature, &rsa_public_pem).unwrap();
-        assert!(KeyType::Curve25519.verify("message", &signature, &rsa_public_pem).is_err());
+        KeyType::Rsa
+            .verify("message", &signature, &rsa_public_pem)
+            .unwrap();
+        assert!(KeyType::Curve25519
+            .verify("message", &signature, &rsa_public_pem)
+            .is_err());
     }
 
     #[test]
@@ -155,7 +166,9 @@ mod tests {
         let der = KeyType::Curve25519.member_pubkey_der(&ed_public).unwrap();
         let rebuilt = KeyType::Curve25519.pem_from_member_der(&der).unwrap();
         let sig = crate::ed25519::private::sign("rotated", &ed_private).unwrap();
-        KeyType::Curve25519.verify("rotated", &sig, &rebuilt).unwrap();
+        KeyType::Curve25519
+            .verify("rotated", &sig, &rebuilt)
+            .unwrap();
 
         // RSA: the current real transition — old RSA key superseded by curve.
         let rsa_private = crate::rsa::private::generate().unwrap();
@@ -0,0 +1,87 @@
+//! # JWS signing keys
+//!
+//! Key pairs the server signs its session and transfer tokens with. The
+//! private key is a PKCS#8 PEM; the public half comes as the coordinates of
+//! a JSON Web Key (RFC 7517), unpadded base64url, ready for a JWKS document.
+
+use crate::error::{CryptoResult, Error};
+
+use ed25519_dalek::pkcs8::{spki::der::pem::LineEnding, EncodePrivateKey};
+
+/// A freshly generated signing key.
+pub struct KeyPair {
+    /// PKCS#8 PEM of the private key
+    pub private_key: String,
+    /// `x` of the JWK: the whole public key for Ed25519, the x coordinate
+    /// for P-256
+    pub x: String,
+    /// `y` of the JWK, P-256 only
+    pub y: Option<String>,
+}
+
+/// Generate an Ed25519 key pair, for `EdDSA`.
+pub fn generate_ed25519() -> CryptoResult<KeyPair> {
+    let key = ed25519_dalek::SigningKey::generate(&mut rand::rngs::OsRng);
+    let private_key = key
+        .to_pkcs8_pem(LineEnding::LF)
+        .map_err(|e| Error::KeyEncoding(e.to_string()))?;
+
+    Ok(KeyPair {
+        private_key: private_key.to_string(),
+        x: crate::base64::encode_url(key.verifying_key().as_bytes()),
+        y: None,
+    })
+}
+
+/// Generate a P-256 key pair, for `ES256`.
+pub fn generate_es256() -> CryptoResult<KeyPair> {
+    let key = p256::SecretKey::random(&mut rand::rngs::OsRng);
+    let private_key = key
+        .to_pkcs8_pem(LineEnding::LF)
+        .map_err(|e| Error::KeyEncoding(e.to_string()))?;
+
+    let point =
+        p256::elliptic_curve::sec1::ToEncodedPoint::to_encoded_point(&key.public_key(), false);
+    let (x, y) = point
+        .x()
+        .zip(point.y())
+        .ok_or_else(|| Error::KeyEncoding("p256_identity_point".to_string()))?;
+
+    Ok(KeyPair {
+        private_key: private_key.to_string(),
+        x: crate::base64::encode_url(x),
+        y: Some(crate::base64::encode_url(y)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::pkcs8::DecodePrivateKey;
+
+    #[test]
+    fn ed25519_jwk_matches_the_private_key() {
+        let pair = generate_ed25519().unwrap();
+        let key = ed25519_dalek::SigningKey::from_pkcs8_pem(&pair.private_key).unwrap();
+
+        assert_eq!(
+            crate::base64::decode_url(&pair.x).unwrap(),
+            key.verifying_key().as_bytes()
+        );
+        assert!(pair.y.is_none());
+    }
+
+    #[test]
+    fn es256_jwk_has_both_coordinates() {
+        let pair = generate_es256().unwrap();
+        let key = p256::SecretKey::from_pkcs8_pem(&pair.private_key).unwrap();
+
+        let mut sec1 = vec![0x04];
+        sec1.extend(crate::base64::decode_url(&pair.x).unwrap());
+        sec1.extend(crate::base64::decode_url(pair.y.as_deref().unwrap()).unwrap());
+        assert_eq!(
+            p256::PublicKey::from_sec1_bytes(&sec1).unwrap(),
+            key.public_key()
+        );
+    }
+}
@@ -10,13 +10,17 @@ pub mod ecdh;
 pub mod ed25519;
 pub mod envelope;
 pub mod error;
+pub mod hmac;
 pub mod identity;
+pub mod jws;
 pub mod opaque;
+pub mod pkce;
 pub mod rsa;
 pub mod spki;
-pub mod transition;
 #[cfg(feature = "tokenizer")]
 pub mod tokenizer;
+pub mod transition;
+pub mod webauthn;
 
 pub use hex;
 pub use rand;
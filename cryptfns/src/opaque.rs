@@ -19,9 +19,8 @@ use opaque_ke::rand::rngs::OsRng;
 use opaque_ke::{
     ClientLogin, ClientLoginFinishParameters, ClientRegistration,
     ClientRegistrationFinishParameters, CredentialFinalization, CredentialRequest,
-    CredentialResponse, Identifiers, RegistrationRequest, RegistrationResponse,
-    RegistrationUpload, Ristretto255, ServerLogin, ServerLoginParameters, ServerRegistration,
-    ServerSetup, TripleDh,
+    CredentialResponse, Identifiers, RegistrationRequest, RegistrationResponse, RegistrationUpload,
+    Ristretto255, ServerLogin, ServerLoginParameters, ServerRegistration, ServerSetup, TripleDh,
 };
 
 /// Frozen OPAQUE cipher suite. Changing any associated type is a
@@ -50,7 +49,12 @@ pub struct KsfParams {
 /// `login/start` returns them for accounts that hold no record of their own.
 #[cfg(not(feature = "fast-test-kdf"))]
 pub fn current_ksf_params() -> KsfParams {
-    KsfParams { algorithm: "argon2id".to_string(), m_cost: 64 * 1024, t_cost: 3, p_cost: 1 }
+    KsfParams {
+        algorithm: "argon2id".to_string(),
+        m_cost: 64 * 1024,
+        t_cost: 3,
+        p_cost: 1,
+    }
 }
 
 /// Cheap defaults for the e2e WASM build only, enabled solely by the
@@ -58,7 +62,12 @@ pub fn current_ksf_params() -> KsfParams {
 /// never set.
 #[cfg(feature = "fast-test-kdf")]
 pub fn current_ksf_params() -> KsfParams {
-    KsfParams { algorithm: "argon2id".to_string(), m_cost: 8 * 1024, t_cost: 1, p_cost: 1 }
+    KsfParams {
+        algorithm: "argon2id".to_string(),
+        m_cost: 8 * 1024,
+        t_cost: 1,
+        p_cost: 1,
+    }
 }
 
 fn build_argon2(m_cost: u32, t_cost: u32, p_cost: u32) -> CryptoResult<Argon2<'static>> {
@@ -110,7 +119,10 @@ pub fn server_registration_start(
     registration_request: &str,
     username: &[u8],
 ) -> CryptoResult<String> {
-    let setup = de(ServerSetup::<HoodikCipherSuite>::deserialize(&b64(server_setup)?), "server setup")?;
+    let setup = de(
+        ServerSetup::<HoodikCipherSuite>::deserialize(&b64(server_setup)?),
+        "server setup",
+    )?;
     let request = de(
         RegistrationRequest::<HoodikCipherSuite>::deserialize(&b64(registration_request)?),
         "reg request",
@@ -141,7 +153,10 @@ pub fn server_login_start(
     credential_request: &str,
     username: &[u8],
 ) -> CryptoResult<ServerLoginStart> {
-    let setup = de(ServerSetup::<HoodikCipherSuite>::deserialize(&b64(server_setup)?), "server setup")?;
+    let setup = de(
+        ServerSetup::<HoodikCipherSuite>::deserialize(&b64(server_setup)?),
+        "server setup",
+    )?;
     let file = de(
         ServerRegistration::<HoodikCipherSuite>::deserialize(&b64(password_file)?),
         "password file",
@@ -173,7 +188,10 @@ pub fn server_login_finish(
     login_state: &str,
     credential_finalization: &str,
 ) -> CryptoResult<String> {
-    let state = de(ServerLogin::<HoodikCipherSuite>::deserialize(&b64(login_state)?), "login state")?;
+    let state = de(
+        ServerLogin::<HoodikCipherSuite>::deserialize(&b64(login_state)?),
+        "login state",
+    )?;
     let finalization = de(
         CredentialFinalization::<HoodikCipherSuite>::deserialize(&b64(credential_finalization)?),
         "cred finalization",
@@ -358,10 +376,8 @@ mod tests {
 
     fn register(setup: &str) -> (String, String) {
         let start = super::client_registration_start(PASSWORD).unwrap();
-        let response =
-            super::server_registration_start(setup, &start.message, USERNAME).unwrap();
-        let finish =
-            super::client_registration_finish(&start.state, &response, PASSWORD).unwrap();
+        let response = super::server_registration_start(setup, &start.message, USERNAME).unwrap();
+        let finish = super::client_registration_finish(&start.state, &response, PASSWORD).unwrap();
         let password_file = super::server_registration_finish(&finish.message).unwrap();
         (password_file, finish.export_key)
     }
@@ -371,7 +387,10 @@ mod tests {
         let server = super::server_login_start(setup, password_file, &start.message, USERNAME)?;
         let finish = super::client_login_finish(&start.state, &server.response, password)?;
         let server_session = super::server_login_finish(&server.state, &finish.finalization)?;
-        assert_eq!(finish.session_key, server_session, "KE session keys must agree");
+        assert_eq!(
+            finish.session_key, server_session,
+            "KE session keys must agree"
+        );
         Ok(finish.export_key)
     }
 
@@ -380,7 +399,10 @@ mod tests {
         let setup = super::server_setup_new();
         let (password_file, reg_export) = register(&setup);
         let login_export = login(&setup, &password_file, PASSWORD).unwrap();
-        assert_eq!(reg_export, login_export, "export_key must match registration");
+        assert_eq!(
+            reg_export, login_export,
+            "export_key must match registration"
+        );
     }
 
     #[test]
@@ -396,8 +418,7 @@ mod tests {
         let (password_file, _) = register(&setup);
         let export = login(&setup, &password_file, PASSWORD).unwrap();
 
-        let kek =
-            crate::envelope::derive_kek(&crate::base64::decode(&export).unwrap()).unwrap();
+        let kek = crate::envelope::derive_kek(&crate::base64::decode(&export).unwrap()).unwrap();
         let bundle = b"rsa-pem||ed25519-pem||x25519-pem";
         let sealed = crate::envelope::seal(&kek, bundle).unwrap();
         assert_eq!(crate::envelope::open(&kek, &sealed).unwrap(), bundle);
@@ -407,10 +428,8 @@ mod tests {
     fn password_is_absent_from_every_message() {
         let setup = super::server_setup_new();
         let start = super::client_registration_start(PASSWORD).unwrap();
-        let response =
-            super::server_registration_start(&setup, &start.message, USERNAME).unwrap();
-        let finish =
-            super::client_registration_finish(&start.state, &response, PASSWORD).unwrap();
+        let response = super::server_registration_start(&setup, &start.message, USERNAME).unwrap();
+        let finish = super::client_registration_finish(&start.state, &response, PASSWORD).unwrap();
 
         for wire in [&start.message, &response, &finish.message] {
             let bytes = crate::base64::decode(wire).unwrap();
@@ -176,11 +176,7 @@ pub mod public {
     /// Verify a signature over raw bytes with a public key. The sharing
     /// protocol signs DER bytes prefixed with a domain separator, so the
     /// server verifies against the exact bytes-as-received (sign-what-you-send).
-    pub fn verify_with_bytes(
-        message: &[u8],
-        signature: &str,
-        key: PublicKey,
-    ) -> CryptoResult<()> {
+    pub fn verify_with_bytes(message: &[u8], signature: &str, key: PublicKey) -> CryptoResult<()> {
         let signature_decoded = crate::base64::decode(signature)?;
         let signature = Signature::try_from(signature_decoded.as_slice())?;
         let verifying_key = VerifyingKey::<Sha256>::from(key);
@@ -7,11 +7,13 @@ use crate::error::{CryptoResult, Error};
 /// algorithm because it hashes the whole SubjectPublicKeyInfo document, and it
 /// is the go-forward scheme for Ed25519/X25519 identities.
 pub fn fingerprint(public_key_pem: &str) -> CryptoResult<String> {
-    let (label, document) = pkcs8::Document::from_pem(public_key_pem)
-        .map_err(|e| Error::KeyEncoding(e.to_string()))?;
+    let (label, document) =
+        pkcs8::Document::from_pem(public_key_pem).map_err(|e| Error::KeyEncoding(e.to_string()))?;
 
     if label != "PUBLIC KEY" {
-        return Err(Error::KeyEncoding(format!("expected PUBLIC KEY pem, got {label}")));
+        return Err(Error::KeyEncoding(format!(
+            "expected PUBLIC KEY pem, got {label}"
+        )));
     }
 
     Ok(sha256::digest(hex::encode(document.as_bytes())))
@@ -37,7 +39,10 @@ mod tests {
             .unwrap();
         let b = crate::ed25519::public::from_private(&crate::ed25519::private::generate().unwrap())
             .unwrap();
-        assert_ne!(super::fingerprint(&a).unwrap(), super::fingerprint(&b).unwrap());
+        assert_ne!(
+            super::fingerprint(&a).unwrap(),
+            super::fingerprint(&b).unwrap()
+        );
     }
 
     #[test]
@@ -31,7 +31,8 @@ impl Certificate<'_> {
             user_id: self.user_id,
             old_key_spki: self.old_key_type.member_pubkey_der(self.old_key_pem)?,
             old_fingerprint: hex32(self.old_fingerprint)?,
-            new_identity_key_spki: KeyType::Curve25519.member_pubkey_der(self.new_identity_key_pem)?,
+            new_identity_key_spki: KeyType::Curve25519
+                .member_pubkey_der(self.new_identity_key_pem)?,
             new_wrapping_key_spki: crate::ecdh::public::from_str(self.new_wrapping_key_pem)?,
             new_fingerprint: hex32(self.new_fingerprint)?,
             issued_at: self.issued_at,
@@ -47,7 +48,11 @@ impl Certificate<'_> {
     /// Sign the certificate with both keys. The client holds the old private
     /// key (still decryptable at migration time) and the freshly generated new
     /// identity key.
-    pub fn sign(&self, old_private_key: &str, new_identity_private_key: &str) -> CryptoResult<Signatures> {
+    pub fn sign(
+        &self,
+        old_private_key: &str,
+        new_identity_private_key: &str,
+    ) -> CryptoResult<Signatures> {
         let input = self.signing_input()?;
 
         let old_signature = match self.old_key_type {
@@ -56,7 +61,10 @@ impl Certificate<'_> {
         };
         let new_signature = crate::ed25519::private::sign_bytes(&input, new_identity_private_key)?;
 
-        Ok(Signatures { old_signature, new_signature })
+        Ok(Signatures {
+            old_signature,
+            new_signature,
+        })
     }
 
     /// Verify both signatures against the canonical re-encoded here. The old
@@ -370,8 +378,12 @@ mod tests {
         let attacker = keys();
         let mut forged = cert(&attacker);
         forged.old_fingerprint = &victim.rsa_fingerprint;
-        let signatures = forged.sign(&attacker.rsa_private, &attacker.ed_private).unwrap();
-        let spki = KeyType::Rsa.member_pubkey_der(&attacker.rsa_public).unwrap();
+        let signatures = forged
+            .sign(&attacker.rsa_private, &attacker.ed_private)
+            .unwrap();
+        let spki = KeyType::Rsa
+            .member_pubkey_der(&attacker.rsa_public)
+            .unwrap();
         assert!(verify_row(&attacker, &spki, &victim.rsa_fingerprint, &signatures).is_err());
     }
 
@@ -275,7 +275,9 @@ pub fn opaque_client_login_finish(
 pub fn envelope_derive_kek(export_key: Vec<u8>) -> Option<Vec<u8>> {
     crate::utils::set_panic_hook();
 
-    crate::envelope::derive_kek(&export_key).ok().map(|k| k.to_vec())
+    crate::envelope::derive_kek(&export_key)
+        .ok()
+        .map(|k| k.to_vec())
 }
 
 /// Seal a private-key bundle under `kek`; returns the base64 envelope.
@@ -432,19 +434,28 @@ pub fn chacha_decrypt(key: Vec<u8>, ciphertext: Vec<u8>) -> Option<Vec<u8>> {
 /// Generate a key for the given cipher identifier (e.g. `"ascon128a"`, `"chacha20poly1305"`).
 #[wasm_bindgen]
 pub fn cipher_generate_key(cipher: &str) -> Option<Vec<u8>> {
-    crate::cipher::Cipher::from_str(cipher).ok()?.generate_key().ok()
+    crate::cipher::Cipher::from_str(cipher)
+        .ok()?
+        .generate_key()
+        .ok()
 }
 
 /// Encrypt `plaintext` with `key` using the named cipher.
 #[wasm_bindgen]
 pub fn cipher_encrypt(cipher: &str, key: Vec<u8>, plaintext: Vec<u8>) -> Option<Vec<u8>> {
-    crate::cipher::Cipher::from_str(cipher).ok()?.encrypt(key, plaintext).ok()
+    crate::cipher::Cipher::from_str(cipher)
+        .ok()?
+        .encrypt(key, plaintext)
+        .ok()
 }
 
 /// Decrypt `ciphertext` with `key` using the named cipher.
 #[wasm_bindgen]
 pub fn cipher_decrypt(cipher: &str, key: Vec<u8>, ciphertext: Vec<u8>) -> Option<Vec<u8>> {
-    crate::cipher::Cipher::from_str(cipher).ok()?.decrypt(key, ciphertext).ok()
+    crate::cipher::Cipher::from_str(cipher)
+        .ok()?
+        .decrypt(key, ciphertext)
+        .ok()
 }
 
 /// Encrypt a metadata string (file name, thumbnail, link fields) with a
@@ -0,0 +1,303 @@
+//! Open files. Reads fetch and decrypt one chunk at a time, writes are
+//! spooled to a temporary file and uploaded when the client is done.
+use std::{
+    io::SeekFrom,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bytes::{Buf, Bytes};
+use client::Entry;
+use dav_server::fs::{DavFile, DavMetaData, FsError, FsFuture};
+use futures::FutureExt;
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    fs::{fs_error, HoodikFs},
+    meta::Meta,
+};
+
+pub(crate) struct ReadFile {
+    source: Option<(HoodikFs, Entry)>,
+    meta: Meta,
+    position: u64,
+    /// Plain size of every chunk but the last, known after the first read.
+    chunk_size: Option<u64>,
+    cached: Option<(u64, Bytes)>,
+}
+
+impl std::fmt::Debug for ReadFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReadFile")
+            .field("entry", &self.source.as_ref().map(|(_, entry)| entry))
+            .field("position", &self.position)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ReadFile {
+    pub(crate) fn new(fs: HoodikFs, entry: Entry) -> Self {
+        Self {
+            meta: Meta::from(&entry),
+            source: Some((fs, entry)),
+            position: 0,
+            chunk_size: None,
+            cached: None,
+        }
+    }
+
+    /// A file that only exists in the gateway.
+    pub(crate) fn empty(created: SystemTime) -> Self {
+        Self {
+            source: None,
+            meta: Meta::empty_file(created),
+            position: 0,
+            chunk_size: None,
+            cached: None,
+        }
+    }
+
+    async fn chunk(&mut self, index: u64) -> Result<Bytes, FsError> {
+        if let Some((cached, data)) = &self.cached {
+            if *cached == index {
+                return Ok(data.clone());
+            }
+        }
+
+        let (fs, entry) = self.source.as_ref().ok_or(FsError::NotFound)?;
+        let data = Bytes::from(
+            fs.session()
+                .read_chunk(entry, index)
+                .await
+                .map_err(fs_error)?,
+        );
+        self.cached = Some((index, data.clone()));
+
+        Ok(data)
+    }
+
+    async fn chunk_size(&mut self) -> Result<u64, FsError> {
+        if let Some(chunk_size) = self.chunk_size {
+            return Ok(chunk_size);
+        }
+
+        let chunks = self.source.as_ref().map(|(_, entry)| entry.chunks);
+        let chunk_size = match chunks {
+            Some(chunks) if chunks > 1 => self.chunk(0).await?.len() as u64,
+            _ => self.meta.len().max(1),
+        };
+        self.chunk_size = Some(chunk_size);
+
+        Ok(chunk_size)
+    }
+}
+
+impl DavFile for ReadFile {
+    fn metadata(&mut self) -> FsFuture<'_, Box<dyn DavMetaData>> {
+        let meta: Box<dyn DavMetaData> = Box::new(self.meta.clone());
+
+        async move { Ok(meta) }.boxed()
+    }
+
+    fn write_buf(&mut self, _buf: Box<dyn Buf + Send>) -> FsFuture<'_, ()> {
+        async move { Err(FsError::Forbidden) }.boxed()
+    }
+
+    fn write_bytes(&mut self, _buf: Bytes) -> FsFuture<'_, ()> {
+        async move { Err(FsError::Forbidden) }.boxed()
+    }
+
+    fn read_bytes(&mut self, count: usize) -> FsFuture<'_, Bytes> {
+        async move {
+            if self.position >= self.meta.len() || count == 0 {
+                return Ok(Bytes::new());
+            }
+
+            let chunk_size = self.chunk_size().await?;
+            let index = self.position / chunk_size;
+            let chunk = self.chunk(index).await?;
+
+            let offset = (self.position - index * chunk_size) as usize;
+            if offset >= chunk.len() {
+                return Ok(Bytes::new());
+            }
+
+            let end = chunk.len().min(offset + count);
+            self.position += (end - offset) as u64;
+
+            Ok(chunk.slice(offset..end))
+        }
+        .boxed()
+    }
+
+    fn seek(&mut self, position: SeekFrom) -> FsFuture<'_, u64> {
+        async move {
+            let len = self.meta.len() as i64;
+            let position = match position {
+                SeekFrom::Start(position) => position as i64,
+                SeekFrom::Current(offset) => self.position as i64 + offset,
+                SeekFrom::End(offset) => len + offset,
+            };
+
+            if position < 0 {
+                return Err(FsError::GeneralFailure);
+            }
+            self.position = position as u64;
+
+            Ok(self.position)
+        }
+        .boxed()
+    }
+
+    fn flush(&mut self) -> FsFuture<'_, ()> {
+        async move { Ok(()) }.boxed()
+    }
+}
+
+pub(crate) struct WriteFile {
+    fs: HoodikFs,
+    parent: Option<String>,
+    name: String,
+    /// The file being replaced, if any.
+    existing: Option<Entry>,
+    spool: PathBuf,
+    file: tokio::fs::File,
+    len: u64,
+    created: SystemTime,
+    stored: bool,
+}
+
+impl std::fmt::Debug for WriteFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WriteFile")
+            .field("parent", &self.parent)
+            .field("name", &self.name)
+            .field("len", &self.len)
+            .finish_non_exhaustive()
+    }
+}
+
+impl WriteFile {
+    pub(crate) async fn create(
+        fs: HoodikFs,
+        parent: Option<String>,
+        name: String,
+        existing: Option<Entry>,
+    ) -> Result<Self, FsError> {
+        let spool = std::env::temp_dir().join(format!("hoodik-dav-{}", uuid::Uuid::new_v4()));
+        let file = tokio::fs::File::create(&spool).await.map_err(|e| {
+            log::error!("Failed to create {}: {e}", spool.display());
+            FsError::GeneralFailure
+        })?;
+        let created = existing
+            .as_ref()
+            .map(|entry| {
+                UNIX_EPOCH + std::time::Duration::from_secs(entry.created_at.max(0) as u64)
+            })
+            .unwrap_or_else(SystemTime::now);
+
+        Ok(Self {
+            fs,
+            parent,
+            name,
+            existing,
+            spool,
+            file,
+            len: 0,
+            created,
+            stored: false,
+        })
+    }
+
+    async fn write(&mut self, data: &[u8]) -> Result<(), FsError> {
+        if self.stored {
+            return Err(FsError::GeneralFailure);
+        }
+
+        self.file.write_all(data).await.map_err(|e| {
+            log::error!("Failed to write {}: {e}", self.spool.display());
+            FsError::GeneralFailure
+        })?;
+        self.len += data.len() as u64;
+
+        Ok(())
+    }
+}
+
+impl DavFile for WriteFile {
+    fn metadata(&mut self) -> FsFuture<'_, Box<dyn DavMetaData>> {
+        let meta: Box<dyn DavMetaData> =
+            Box::new(Meta::empty_file(self.created).with_len(self.len));
+
+        async move { Ok(meta) }.boxed()
+    }
+
+    fn write_buf(&mut self, mut buf: Box<dyn Buf + Send>) -> FsFuture<'_, ()> {
+        async move {
+            while buf.has_remaining() {
+                let chunk = Bytes::copy_from_slice(buf.chunk());
+                buf.advance(chunk.len());
+                self.write(&chunk).await?;
+            }
+
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn write_bytes(&mut self, buf: Bytes) -> FsFuture<'_, ()> {
+        async move { self.write(&buf).await }.boxed()
+    }
+
+    fn read_bytes(&mut self, _count: usize) -> FsFuture<'_, Bytes> {
+        async move { Err(FsError::Forbidden) }.boxed()
+    }
+
+    fn seek(&mut self, position: SeekFrom) -> FsFuture<'_, u64> {
+        async move {
+            // Uploads are written front to back, the only position there is
+            // is the end of what was written so far.
+            match position {
+                SeekFrom::Current(0) | SeekFrom::End(0) => Ok(self.len),
+                SeekFrom::Start(position) if position == self.len => Ok(self.len),
+                _ => Err(FsError::NotImplemented),
+            }
+        }
+        .boxed()
+    }
+
+    fn flush(&mut self) -> FsFuture<'_, ()> {
+        async move {
+            if self.stored {
+                return Ok(());
+            }
+
+            self.file.flush().await.map_err(|e| {
+                log::error!("Failed to write {}: {e}", self.spool.display());
+                FsError::GeneralFailure
+            })?;
+
+            self.fs
+                .store(
+                    self.parent.as_deref(),
+                    &self.name,
+                    self.existing.take(),
+                    &self.spool,
+                    self.len,
+                )
+                .await?;
+            self.stored = true;
+
+            Ok(())
+        }
+        .boxed()
+    }
+}
+
+impl Drop for WriteFile {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_file(&self.spool) {
+            log::warn!("Failed to remove {}: {e}", self.spool.display());
+        }
+    }
+}
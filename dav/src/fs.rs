@@ -0,0 +1,447 @@
+//! The Hoodik tree as a [`DavFileSystem`].
+//!
+//! Paths are resolved one folder listing at a time by matching decrypted
+//! names. WebDAV clients look paths up constantly, so listings are cached
+//! for a few seconds and dropped for every folder the gateway changes.
+//!
+//! Hoodik does not store empty files, yet WebDAV clients create them all the
+//! time, usually right before they write the content. Empty files are kept
+//! in the gateway only, until something is written to them.
+use std::{
+    collections::HashMap,
+    path::{Component, Path},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime},
+};
+
+use client::{Entry, Session};
+use dav_server::{
+    davpath::DavPath,
+    fs::{
+        DavDirEntry, DavFile, DavFileSystem, DavMetaData, FsError, FsFuture, FsResult, FsStream,
+        OpenOptions, ReadDirMeta,
+    },
+};
+use futures::FutureExt;
+
+use crate::{
+    file::{ReadFile, WriteFile},
+    meta::{DirEntry, Meta},
+};
+
+/// What a path points to.
+pub(crate) enum Node {
+    Root,
+    Entry(Box<Entry>),
+    Empty(SystemTime),
+}
+
+/// Cached folder listings by folder id, `None` for the root.
+type Listings = HashMap<Option<String>, (Instant, Vec<Entry>)>;
+
+#[derive(Clone)]
+pub struct HoodikFs {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    session: Session,
+    ttl: Duration,
+    listings: Mutex<Listings>,
+    /// Empty files by folder and name.
+    empty: Mutex<HashMap<(Option<String>, String), SystemTime>>,
+}
+
+impl HoodikFs {
+    /// Serve the account of `session`, caching folder listings for `ttl`.
+    pub fn new(session: Session, ttl: Duration) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                session,
+                ttl,
+                listings: Mutex::new(HashMap::new()),
+                empty: Mutex::new(HashMap::new()),
+            }),
+        }
+    }
+
+    pub(crate) fn session(&self) -> &Session {
+        &self.inner.session
+    }
+
+    async fn listing(&self, parent: Option<&str>) -> FsResult<Vec<Entry>> {
+        let key = parent.map(|parent| parent.to_string());
+
+        if let Some((at, entries)) = self.inner.listings.lock().unwrap().get(&key) {
+            if at.elapsed() < self.inner.ttl {
+                return Ok(entries.clone());
+            }
+        }
+
+        let entries = self.session().list(parent).await.map_err(fs_error)?;
+        self.inner
+            .listings
+            .lock()
+            .unwrap()
+            .insert(key, (Instant::now(), entries.clone()));
+
+        Ok(entries)
+    }
+
+    /// Drop the cached listing of a folder after changing it.
+    fn forget(&self, parent: Option<&str>) {
+        self.inner
+            .listings
+            .lock()
+            .unwrap()
+            .remove(&parent.map(|parent| parent.to_string()));
+    }
+
+    fn empty_file(&self, parent: Option<&str>, name: &str) -> Option<SystemTime> {
+        self.inner
+            .empty
+            .lock()
+            .unwrap()
+            .get(&(parent.map(|p| p.to_string()), name.to_string()))
+            .copied()
+    }
+
+    fn set_empty_file(&self, parent: Option<&str>, name: &str, created: Option<SystemTime>) {
+        let key = (parent.map(|p| p.to_string()), name.to_string());
+        let mut empty = self.inner.empty.lock().unwrap();
+
+        match created {
+            Some(created) => empty.insert(key, created),
+            None => empty.remove(&key),
+        };
+    }
+
+    /// Id of the folder at `segments`, `None` for the root.
+    async fn folder(&self, segments: &[String]) -> FsResult<Option<String>> {
+        let mut parent: Option<String> = None;
+
+        for segment in segments {
+            let entry = self
+                .listing(parent.as_deref())
+                .await?
+                .into_iter()
+                .find(|entry| entry.is_dir() && &entry.name == segment)
+                .ok_or(FsError::NotFound)?;
+
+            parent = Some(entry.id);
+        }
+
+        Ok(parent)
+    }
+
+    /// The folder a path is in and its last segment.
+    async fn locate(&self, path: &DavPath) -> FsResult<(Option<String>, String)> {
+        let mut segments = segments(path)?;
+        let name = segments.pop().ok_or(FsError::Forbidden)?;
+
+        Ok((self.folder(&segments).await?, name))
+    }
+
+    pub(crate) async fn resolve(&self, path: &DavPath) -> FsResult<Node> {
+        if segments(path)?.is_empty() {
+            return Ok(Node::Root);
+        }
+
+        let (parent, name) = self.locate(path).await?;
+        let entry = self
+            .listing(parent.as_deref())
+            .await?
+            .into_iter()
+            .find(|entry| entry.name == name);
+
+        match (entry, self.empty_file(parent.as_deref(), &name)) {
+            (Some(entry), _) => Ok(Node::Entry(Box::new(entry))),
+            (None, Some(created)) => Ok(Node::Empty(created)),
+            (None, None) => Err(FsError::NotFound),
+        }
+    }
+
+    /// Put the content written to `path` in place of `existing`.
+    ///
+    /// A replaced file is renamed out of the way first and only deleted
+    /// once the new content is stored, a failed upload puts it back.
+    pub(crate) async fn store(
+        &self,
+        parent: Option<&str>,
+        name: &str,
+        existing: Option<Entry>,
+        content: &Path,
+        len: u64,
+    ) -> FsResult<()> {
+        let session = self.session();
+        self.forget(parent);
+
+        if len == 0 {
+            if let Some(existing) = existing {
+                session.delete(&existing.id).await.map_err(fs_error)?;
+            }
+            self.set_empty_file(parent, name, Some(SystemTime::now()));
+
+            return Ok(());
+        }
+
+        if let Some(existing) = &existing {
+            let aside = format!(".{name}.{}.replaced", uuid::Uuid::new_v4());
+            session.rename(existing, &aside).await.map_err(fs_error)?;
+        }
+
+        match session.upload(parent, name, content).await {
+            Ok(_) => {
+                self.set_empty_file(parent, name, None);
+
+                if let Some(existing) = existing {
+                    session.delete(&existing.id).await.map_err(fs_error)?;
+                }
+
+                Ok(())
+            }
+            Err(e) => {
+                if let Some(existing) = existing {
+                    if let Err(e) = session.rename(&existing, name).await {
+                        log::error!("Failed to restore {name} after a failed upload: {e}");
+                    }
+                }
+
+                Err(fs_error(e))
+            }
+        }
+    }
+}
+
+/// Decoded path segments.
+fn segments(path: &DavPath) -> FsResult<Vec<String>> {
+    path.as_rel_ospath()
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(segment) => Some(
+                segment
+                    .to_str()
+                    .map(|segment| segment.to_string())
+                    .ok_or(FsError::NotFound),
+            ),
+            _ => None,
+        })
+        .collect()
+}
+
+pub(crate) fn fs_error(e: client::Error) -> FsError {
+    match e.status() {
+        Some(404) => FsError::NotFound,
+        Some(401) | Some(403) => FsError::Forbidden,
+        Some(400) if e.to_string().contains("quota_exceeded") => FsError::InsufficientStorage,
+        Some(400) if e.to_string().contains("file_or_directory_exists") => FsError::Exists,
+        _ => match e {
+            client::Error::NotFound(_) => FsError::NotFound,
+            e => {
+                log::error!("{e}");
+                FsError::GeneralFailure
+            }
+        },
+    }
+}
+
+impl DavFileSystem for HoodikFs {
+    fn open<'a>(
+        &'a self,
+        path: &'a DavPath,
+        options: OpenOptions,
+    ) -> FsFuture<'a, Box<dyn DavFile>> {
+        async move {
+            if !options.write {
+                return match self.resolve(path).await? {
+                    Node::Entry(entry) if !entry.is_dir() => {
+                        Ok(Box::new(ReadFile::new(self.clone(), *entry)) as Box<dyn DavFile>)
+                    }
+                    Node::Empty(created) => {
+                        Ok(Box::new(ReadFile::empty(created)) as Box<dyn DavFile>)
+                    }
+                    _ => Err(FsError::Forbidden),
+                };
+            }
+
+            let (parent, name) = self.locate(path).await?;
+            let existing = match self.resolve(path).await {
+                Ok(Node::Entry(entry)) if entry.is_dir() => return Err(FsError::Forbidden),
+                Ok(Node::Entry(entry)) => Some(Some(*entry)),
+                Ok(Node::Empty(_)) => Some(None),
+                Ok(Node::Root) => return Err(FsError::Forbidden),
+                Err(FsError::NotFound) => None,
+                Err(e) => return Err(e),
+            };
+
+            match &existing {
+                Some(_) if options.create_new => return Err(FsError::Exists),
+                None if !options.create && !options.create_new => return Err(FsError::NotFound),
+                // Appending or writing into the middle would mean downloading
+                // the whole file first, uploads always replace the content.
+                Some(Some(_)) if options.append || !options.truncate => {
+                    return Err(FsError::NotImplemented)
+                }
+                _ => {}
+            }
+
+            let file = WriteFile::create(self.clone(), parent, name, existing.flatten()).await?;
+
+            Ok(Box::new(file) as Box<dyn DavFile>)
+        }
+        .boxed()
+    }
+
+    fn read_dir<'a>(
+        &'a self,
+        path: &'a DavPath,
+        _meta: ReadDirMeta,
+    ) -> FsFuture<'a, FsStream<Box<dyn DavDirEntry>>> {
+        async move {
+            let parent = match self.resolve(path).await? {
+                Node::Root => None,
+                Node::Entry(entry) if entry.is_dir() => Some(entry.id),
+                _ => return Err(FsError::Forbidden),
+            };
+
+            let mut entries: Vec<Box<dyn DavDirEntry>> = self
+                .listing(parent.as_deref())
+                .await?
+                .iter()
+                .map(|entry| {
+                    Box::new(DirEntry {
+                        name: entry.name.clone(),
+                        meta: Meta::from(entry),
+                    }) as Box<dyn DavDirEntry>
+                })
+                .collect();
+
+            for ((folder, name), created) in self.inner.empty.lock().unwrap().iter() {
+                if folder == &parent {
+                    entries.push(Box::new(DirEntry {
+                        name: name.clone(),
+                        meta: Meta::empty_file(*created),
+                    }));
+                }
+            }
+
+            let stream: FsStream<Box<dyn DavDirEntry>> =
+                Box::pin(futures::stream::iter(entries.into_iter().map(Ok)));
+
+            Ok(stream)
+        }
+        .boxed()
+    }
+
+    fn metadata<'a>(&'a self, path: &'a DavPath) -> FsFuture<'a, Box<dyn DavMetaData>> {
+        async move {
+            let meta = match self.resolve(path).await? {
+                Node::Root => Meta::root(),
+                Node::Entry(entry) => Meta::from(entry.as_ref()),
+                Node::Empty(created) => Meta::empty_file(created),
+            };
+
+            Ok(Box::new(meta) as Box<dyn DavMetaData>)
+        }
+        .boxed()
+    }
+
+    fn create_dir<'a>(&'a self, path: &'a DavPath) -> FsFuture<'a, ()> {
+        async move {
+            if self.resolve(path).await.is_ok() {
+                return Err(FsError::Exists);
+            }
+
+            let (parent, name) = self.locate(path).await?;
+            self.session()
+                .create_dir(parent.as_deref(), &name)
+                .await
+                .map_err(fs_error)?;
+            self.forget(parent.as_deref());
+
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn remove_dir<'a>(&'a self, path: &'a DavPath) -> FsFuture<'a, ()> {
+        async move {
+            let Node::Entry(entry) = self.resolve(path).await? else {
+                return Err(FsError::Forbidden);
+            };
+            if !entry.is_dir() {
+                return Err(FsError::Forbidden);
+            }
+
+            self.session().delete(&entry.id).await.map_err(fs_error)?;
+            self.forget(entry.parent.as_deref());
+            self.forget(Some(&entry.id));
+            self.inner
+                .empty
+                .lock()
+                .unwrap()
+                .retain(|(folder, _), _| folder.as_deref() != Some(entry.id.as_str()));
+
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn remove_file<'a>(&'a self, path: &'a DavPath) -> FsFuture<'a, ()> {
+        async move {
+            match self.resolve(path).await? {
+                Node::Entry(entry) if !entry.is_dir() => {
+                    self.session().delete(&entry.id).await.map_err(fs_error)?;
+                    self.forget(entry.parent.as_deref());
+
+                    Ok(())
+                }
+                Node::Empty(_) => {
+                    let (parent, name) = self.locate(path).await?;
+                    self.set_empty_file(parent.as_deref(), &name, None);
+
+                    Ok(())
+                }
+                _ => Err(FsError::Forbidden),
+            }
+        }
+        .boxed()
+    }
+
+    fn rename<'a>(&'a self, from: &'a DavPath, to: &'a DavPath) -> FsFuture<'a, ()> {
+        async move {
+            let node = self.resolve(from).await?;
+            let (parent, name) = self.locate(to).await?;
+
+            match node {
+                Node::Root => Err(FsError::Forbidden),
+                Node::Empty(created) => {
+                    let (from_parent, from_name) = self.locate(from).await?;
+                    self.set_empty_file(from_parent.as_deref(), &from_name, None);
+                    self.set_empty_file(parent.as_deref(), &name, Some(created));
+
+                    Ok(())
+                }
+                Node::Entry(entry) => {
+                    let session = self.session();
+
+                    if entry.parent != parent {
+                        session
+                            .move_many(std::slice::from_ref(&entry.id), parent.as_deref())
+                            .await
+                            .map_err(fs_error)?;
+                    }
+                    if entry.name != name {
+                        session.rename(&entry, &name).await.map_err(fs_error)?;
+                    }
+
+                    self.forget(entry.parent.as_deref());
+                    self.forget(parent.as_deref());
+
+                    Ok(())
+                }
+            }
+        }
+        .boxed()
+    }
+}
@@ -0,0 +1,104 @@
+//! # Hoodik WebDAV gateway
+//!
+//! Serves a Hoodik account over WebDAV from the user's own machine. The
+//! gateway holds the private key, so file managers and office suites can
+//! mount the account while every name and chunk is still decrypted and
+//! encrypted locally. The server never sees anything it would not see from
+//! the web client.
+pub mod fs;
+
+mod file;
+mod meta;
+
+use std::{net::TcpListener, time::Duration};
+
+use actix_web::{web, App, Either, HttpRequest, HttpResponse, HttpServer};
+use base64::Engine;
+use client::Session;
+use dav_server::{
+    actix::{DavRequest, DavResponse},
+    fakels::FakeLs,
+    DavHandler,
+};
+
+pub use crate::fs::HoodikFs;
+
+/// Username and password the gateway asks WebDAV clients for.
+#[derive(Clone)]
+pub struct Credentials {
+    pub username: String,
+    pub password: String,
+}
+
+/// WebDAV handler serving the account of `session`.
+pub fn handler(session: Session, cache_ttl: Duration) -> DavHandler {
+    DavHandler::builder()
+        .filesystem(Box::new(HoodikFs::new(session, cache_ttl)))
+        .locksystem(FakeLs::new())
+        .build_handler()
+}
+
+/// Run the gateway on `listener` until the process is stopped.
+pub async fn serve(
+    handler: DavHandler,
+    listener: TcpListener,
+    credentials: Option<Credentials>,
+) -> std::io::Result<()> {
+    let handler = web::Data::new(handler);
+    let credentials = web::Data::new(credentials);
+
+    HttpServer::new(move || {
+        App::new()
+            .app_data(handler.clone())
+            .app_data(credentials.clone())
+            .default_service(web::to(dav))
+    })
+    .listen(listener)?
+    .run()
+    .await
+}
+
+async fn dav(
+    http: HttpRequest,
+    request: DavRequest,
+    handler: web::Data<DavHandler>,
+    credentials: web::Data<Option<Credentials>>,
+) -> Either<HttpResponse, DavResponse> {
+    if let Some(credentials) = credentials.as_ref() {
+        if !authorized(&http, credentials) {
+            return Either::Left(
+                HttpResponse::Unauthorized()
+                    .insert_header(("WWW-Authenticate", "Basic realm=\"Hoodik\""))
+                    .finish(),
+            );
+        }
+    }
+
+    Either::Right(handler.handle(request.request).await.into())
+}
+
+/// Check the basic auth header against the configured credentials.
+fn authorized(request: &HttpRequest, credentials: &Credentials) -> bool {
+    let Some(header) = request
+        .headers()
+        .get(actix_web::http::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Basic "))
+    else {
+        return false;
+    };
+
+    let Ok(decoded) = base64::engine::general_purpose::STANDARD.decode(header.trim()) else {
+        return false;
+    };
+
+    let expected = format!("{}:{}", credentials.username, credentials.password);
+
+    // Compare every byte, so the time taken does not tell how much matched.
+    decoded.len() == expected.len()
+        && decoded
+            .iter()
+            .zip(expected.as_bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
@@ -0,0 +1,110 @@
+use std::{
+    net::{SocketAddr, TcpListener},
+    path::PathBuf,
+    time::Duration,
+};
+
+use clap::{value_parser, Arg, Command};
+use client::{KeyPair, Session};
+use dav::Credentials;
+
+#[actix_web::main]
+async fn main() -> std::io::Result<()> {
+    if std::env::var("RUST_LOG").is_err() {
+        std::env::set_var("RUST_LOG", "info");
+    }
+    env_logger::init();
+
+    let matches = Command::new("hoodik-dav")
+        .version(env!("CARGO_PKG_VERSION"))
+        .about("Serve a Hoodik account over WebDAV, encrypting and decrypting on this machine")
+        .arg(
+            Arg::new("url")
+                .long("url")
+                .env("HOODIK_URL")
+                .help("Address of the Hoodik instance, for example https://hoodik.example.com")
+                .required(true),
+        )
+        .arg(
+            Arg::new("key")
+                .long("key")
+                .env("HOODIK_KEY_FILE")
+                .help("File with the account's private key, as downloaded from the web client")
+                .value_parser(value_parser!(PathBuf))
+                .required(true),
+        )
+        .arg(
+            Arg::new("listen")
+                .long("listen")
+                .env("HOODIK_DAV_LISTEN")
+                .help("Address the WebDAV server listens on")
+                .value_parser(value_parser!(SocketAddr))
+                .default_value("127.0.0.1:4918"),
+        )
+        .arg(
+            Arg::new("username")
+                .long("username")
+                .env("HOODIK_DAV_USERNAME")
+                .help("Username WebDAV clients have to send, requires --password")
+                .requires("password"),
+        )
+        .arg(
+            Arg::new("password")
+                .long("password")
+                .env("HOODIK_DAV_PASSWORD")
+                .help("Password WebDAV clients have to send, requires --username")
+                .requires("username"),
+        )
+        .arg(
+            Arg::new("cache_seconds")
+                .long("cache-seconds")
+                .env("HOODIK_DAV_CACHE_SECONDS")
+                .help("How long folder listings are cached")
+                .value_parser(value_parser!(u64))
+                .default_value("5"),
+        )
+        .get_matches();
+
+    let url = matches.get_one::<String>("url").unwrap();
+    let key = matches.get_one::<PathBuf>("key").unwrap();
+    let listen = *matches.get_one::<SocketAddr>("listen").unwrap();
+    let cache = Duration::from_secs(*matches.get_one::<u64>("cache_seconds").unwrap());
+    let credentials = matches
+        .get_one::<String>("username")
+        .zip(matches.get_one::<String>("password"))
+        .map(|(username, password)| Credentials {
+            username: username.clone(),
+            password: password.clone(),
+        });
+
+    let session = async {
+        let keys = KeyPair::from_file(key)?;
+        Session::login_with_key(url, keys).await
+    }
+    .await
+    .unwrap_or_else(|e| {
+        log::error!("Failed to sign in to {url}: {e}");
+        std::process::exit(1);
+    });
+
+    if !listen.ip().is_loopback() {
+        if credentials.is_none() {
+            log::warn!(
+                "Listening on {listen} without --username and --password, anyone who can reach it can read and change the account"
+            );
+        } else {
+            log::warn!(
+                "Listening on {listen}, basic auth is sent in the clear without TLS in front"
+            );
+        }
+    }
+
+    log::info!(
+        "Serving {} from {url} on http://{listen}",
+        session.user().email
+    );
+
+    let listener = TcpListener::bind(listen)?;
+
+    dav::serve(dav::handler(session, cache), listener, credentials).await
+}
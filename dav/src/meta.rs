@@ -0,0 +1,89 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use client::Entry;
+use dav_server::fs::{DavDirEntry, DavMetaData, FsFuture, FsResult};
+use futures::FutureExt;
+
+#[derive(Debug, Clone)]
+pub(crate) struct Meta {
+    len: u64,
+    is_dir: bool,
+    modified: SystemTime,
+    created: SystemTime,
+}
+
+impl Meta {
+    pub(crate) fn root() -> Self {
+        Self {
+            len: 0,
+            is_dir: true,
+            modified: UNIX_EPOCH,
+            created: UNIX_EPOCH,
+        }
+    }
+
+    /// A file that only exists in the gateway, see [`crate::fs::HoodikFs`].
+    pub(crate) fn empty_file(created: SystemTime) -> Self {
+        Self {
+            len: 0,
+            is_dir: false,
+            modified: created,
+            created,
+        }
+    }
+
+    pub(crate) fn with_len(mut self, len: u64) -> Self {
+        self.len = len;
+        self
+    }
+}
+
+impl From<&Entry> for Meta {
+    fn from(entry: &Entry) -> Self {
+        Self {
+            len: entry.size,
+            is_dir: entry.is_dir(),
+            modified: timestamp(entry.modified_at),
+            created: timestamp(entry.created_at),
+        }
+    }
+}
+
+fn timestamp(seconds: i64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(seconds.max(0) as u64)
+}
+
+impl DavMetaData for Meta {
+    fn len(&self) -> u64 {
+        self.len
+    }
+
+    fn modified(&self) -> FsResult<SystemTime> {
+        Ok(self.modified)
+    }
+
+    fn created(&self) -> FsResult<SystemTime> {
+        Ok(self.created)
+    }
+
+    fn is_dir(&self) -> bool {
+        self.is_dir
+    }
+}
+
+pub(crate) struct DirEntry {
+    pub(crate) name: String,
+    pub(crate) meta: Meta,
+}
+
+impl DavDirEntry for DirEntry {
+    fn name(&self) -> Vec<u8> {
+        self.name.as_bytes().to_vec()
+    }
+
+    fn metadata(&self) -> FsFuture<'_, Box<dyn DavMetaData>> {
+        let meta: Box<dyn DavMetaData> = Box::new(self.meta.clone());
+
+        async move { Ok(meta) }.boxed()
+    }
+}
@@ -0,0 +1,50 @@
+//! `SeaORM` Entity for the rows a server-side copy wrote.
+//!
+//! A copy that fails or is interrupted removes these and nothing else, so
+//! files uploaded or moved into the copied tree while it ran are kept.
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "copy_job_files")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub job_id: Uuid,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub file_id: Uuid,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::copy_jobs::Entity",
+        from = "Column::JobId",
+        to = "super::copy_jobs::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    CopyJobs,
+    #[sea_orm(
+        belongs_to = "super::files::Entity",
+        from = "Column::FileId",
+        to = "super::files::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Files,
+}
+
+impl Related<super::copy_jobs::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::CopyJobs.def()
+    }
+}
+
+impl Related<super::files::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Files.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,50 @@
+//! `SeaORM` Entity for server-side copies running in the background.
+//!
+//! The rows of the copy exist from the start, with its files left
+//! unfinished until their chunks are in place; the job says how far it got.
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "copy_jobs")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub user_id: Uuid,
+    /// Root of the copy.
+    pub file_id: Uuid,
+    /// `"running"`, `"done"` or `"failed"`.
+    pub status: String,
+    /// Files of the copy that carry chunks, and how many of them are done.
+    pub files_total: i64,
+    pub files_copied: i64,
+    /// Why the copy failed; its rows and chunks are removed right after.
+    pub error: Option<String>,
+    pub created_at: i64,
+    /// Moves with every copied file and every few minutes while a file is
+    /// being copied, a running copy whose `updated_at` stopped moving was
+    /// interrupted.
+    pub updated_at: i64,
+    pub finished_at: Option<i64>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::users::Entity",
+        from = "Column::UserId",
+        to = "super::users::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Users,
+}
+
+impl Related<super::users::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Users.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,73 @@
+//! `SeaORM` Entity for chunks a file version borrows from an older version
+//! instead of storing its own copy.
+//!
+//! The storage provider only holds the chunks each version stores; reads
+//! of a version attach its file's references to the [`Filename`] so they
+//! find borrowed chunks in the version holding them. The loaders live here
+//! rather than in `storage/` so the public link downloads can use them too.
+
+use error::AppResult;
+use fs::prelude::{ChunkRefs, Filename, IntoFilename};
+use sea_orm::{entity::prelude::*, QuerySelect};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "file_chunk_refs")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub file_id: Uuid,
+    /// Version borrowing the chunk.
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub version: i32,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub chunk: i64,
+    /// Version whose directory stores the chunk, never one that borrows it
+    /// in turn.
+    pub source_version: i32,
+    pub created_at: i64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::files::Entity",
+        from = "Column::FileId",
+        to = "super::files::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Files,
+}
+
+impl Related<super::files::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Files.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+/// Every chunk reference of the file, across all of its versions.
+pub async fn chunk_refs(db: &impl ConnectionTrait, file_id: Uuid) -> AppResult<ChunkRefs> {
+    let rows = Entity::find()
+        .select_only()
+        .columns([Column::Version, Column::Chunk, Column::SourceVersion])
+        .filter(Column::FileId.eq(file_id))
+        .into_tuple::<(i32, i64, i32)>()
+        .all(db)
+        .await?;
+
+    Ok(rows.into_iter().collect())
+}
+
+/// Name of the file with its chunk references attached, for reading its
+/// versions from the storage provider.
+pub async fn filename<T: IntoFilename>(
+    db: &impl ConnectionTrait,
+    file_id: Uuid,
+    file: &T,
+) -> AppResult<Filename> {
+    Ok(file
+        .filename()?
+        .with_chunk_refs(chunk_refs(db, file_id).await?))
+}
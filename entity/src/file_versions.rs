@@ -31,6 +31,10 @@ pub struct Model {
     /// families are deprecated; clients only compute sha256.
     pub sha256: Option<String>,
     pub created_at: i64,
+    /// Set while the owner keeps this version forever; retention pruning
+    /// skips it. Defaulted so account exports from before pinning import.
+    #[serde(default)]
+    pub pinned_at: Option<i64>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -1,6 +1,6 @@
 use error::{AppResult, Error};
 use fs::prelude::*;
-use sea_orm::entity::prelude::*;
+use sea_orm::{entity::prelude::*, Statement};
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, DeriveEntityModel, Eq)]
@@ -62,6 +62,25 @@ pub struct Model {
     /// User who produced `members_list_signature`. FK to `users.id`,
     /// SET NULL on actor deletion so the audit trail survives.
     pub members_list_signed_by_user_id: Option<Uuid>,
+    /// When the owner moved this row to the trash, NULL for live rows.
+    /// Every row of a trashed subtree carries the same stamp; the trash
+    /// purge deletes the rows and their chunks once the retention window
+    /// configured by the admin has passed.
+    pub deleted_at: Option<i64>,
+    /// Parent the trashed root was detached from (its `file_id` is set to
+    /// NULL while in the trash). Restore moves the root back under it.
+    /// NULL for live rows, for descendants of a trashed root and for roots
+    /// that were trashed from the top level.
+    pub deleted_parent_id: Option<Uuid>,
+    /// When the last chunk of the upload in progress landed, the initial
+    /// upload or a pending edit. The abandoned-upload reaper counts idle
+    /// time from it, falling back to `created_at` when no chunk ever
+    /// arrived.
+    pub last_chunk_at: Option<i64>,
+    /// Set by `hoodik fsck --repair` when chunks of the active version are
+    /// missing on the storage provider, cleared again once a later check
+    /// finds them all.
+    pub damaged_at: Option<i64>,
 }
 
 impl IntoFilename for Model {
@@ -143,3 +162,42 @@ impl Related<super::tokens::Entity> for Entity {
 }
 
 impl ActiveModelBehavior for ActiveModel {}
+
+/// Recursive CTE naming the file or directory bound to `$1` and everything
+/// under it `file_tree(id, depth)`, for a query to start with.
+///
+/// The `depth` guard bounds the recursion: parent cycles are refused before
+/// they can be written, and this cap is the backstop so a cycle that ever did
+/// exist degrades to a bounded walk on both SQLite and Postgres instead of
+/// looping forever. Real folder nesting is nowhere near 10000 levels deep.
+pub const FILE_TREE_CTE: &str = r#"
+    WITH RECURSIVE file_tree(id, depth) AS (
+        SELECT id, 0 FROM files WHERE id = $1
+        UNION ALL
+        SELECT child.id, parent.depth + 1 FROM files child
+        JOIN file_tree parent ON parent.id = child.file_id
+        WHERE parent.depth < 10000
+    )
+"#;
+
+/// Ids of the file or directory and everything under it, with no ownership
+/// or trash filter.
+pub async fn subtree_ids(db: &impl ConnectionTrait, root_id: Uuid) -> AppResult<Vec<Uuid>> {
+    let sql = format!("{FILE_TREE_CTE} SELECT id FROM file_tree");
+
+    let rows = Entity::find()
+        .from_raw_sql(Statement::from_sql_and_values(
+            db.get_database_backend(),
+            sql,
+            [root_id.into()],
+        ))
+        .into_json()
+        .all(db)
+        .await?;
+
+    Ok(rows
+        .iter()
+        .filter_map(|row| row.get("id")?.as_str())
+        .filter_map(|id| Uuid::parse_str(id).ok())
+        .collect())
+}
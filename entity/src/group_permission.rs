@@ -14,9 +14,7 @@
 use error::AppResult;
 use sea_orm::ConnectionTrait;
 
-use crate::{
-    share_group_members, share_groups, ColumnTrait, EntityTrait, QueryFilter, Uuid,
-};
+use crate::{share_group_members, share_groups, ColumnTrait, EntityTrait, QueryFilter, Uuid};
 
 /// A caller's effective standing in a group. The group owner is always
 /// `Owner` (the owner has no `share_group_members` row); every other
@@ -0,0 +1,28 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Bookkeeping of the background housekeeping scheduler, one row per named
+/// job. Written after every run, whether the scheduler started it or an
+/// operator did through `hoodik run-job`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "job_runs")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub name: String,
+    pub last_started_at: Option<i64>,
+    pub last_finished_at: Option<i64>,
+    /// `"ok"` or `"error"`; `None` until the job has run once.
+    pub last_status: Option<String>,
+    pub last_error: Option<String>,
+    /// Rows (or bytes, depending on the job) the last run cleaned up.
+    pub last_affected: Option<i64>,
+    /// Bytes of storage the last run freed, only set by the jobs that
+    /// remove chunks and know how much.
+    pub last_reclaimed_bytes: Option<i64>,
+    pub next_run_at: i64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,24 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A key the server signs session and transfer tokens with. The newest key
+/// without `retired_at` signs; retired keys keep verifying the tokens they
+/// signed until those have run out, so rotating does not end any session.
+/// `jwk` is the public half as a JSON Web Key, served from the JWKS endpoint.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "jwt_signing_keys")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub algorithm: String,
+    #[serde(skip_serializing)]
+    pub private_key: String,
+    pub jwk: String,
+    pub created_at: i64,
+    pub retired_at: Option<i64>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
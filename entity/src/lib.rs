@@ -1,26 +1,40 @@
+pub mod copy_job_files;
+pub mod copy_jobs;
+pub mod file_chunk_refs;
 pub mod file_tokens;
 pub mod file_versions;
 pub mod files;
 pub mod group_permission;
 pub mod invitations;
+pub mod job_runs;
+pub mod jwt_signing_keys;
 pub mod key_transitions;
+pub mod link_config;
+pub mod link_files;
 pub mod links;
 pub mod migration_rewrap_staging;
+pub mod oidc_logins;
 pub mod opaque_config;
 pub mod opaque_ksf;
 pub mod opaque_login_sessions;
 pub mod paginated;
 pub mod permission;
+pub mod personal_access_tokens;
 pub mod prelude;
+pub mod rate_limit_hits;
 pub mod sessions;
 pub mod share_events;
 pub mod share_group_members;
 pub mod share_groups;
+pub mod tfa_recovery_codes;
 pub mod tokens;
 pub mod used_nonces;
 pub mod user_actions;
 pub mod user_files;
 pub mod users;
+pub mod version_policies;
+pub mod webauthn_challenges;
+pub mod webauthn_credentials;
 
 pub mod join;
 pub mod sort;
@@ -37,10 +51,11 @@ pub use sea_orm::{
         Alias, Expr, Func, IntoCondition, NullOrdering, OnConflict, Query, SelectStatement,
         SimpleExpr, SubQueryOper, SubQueryStatement, UnionType,
     },
-    ActiveValue, Condition, ConnectionTrait, DbBackend, DbConn, DbErr, EntityOrSelect,
-    FromQueryResult, Identity, JoinType, JsonValue, ModelTrait, Order, PaginatorTrait, QueryFilter,
-    QueryOrder, QueryResult, QuerySelect, QueryTrait, Select, SelectTwo, SqlErr, Statement,
-    TransactionTrait, TryGetableMany, Value,
+    AccessMode, ActiveValue, ColumnType, Condition, ConnectionTrait, DbBackend, DbConn, DbErr,
+    EntityName, EntityOrSelect, FromQueryResult, IdenStatic, Identity, IntoActiveModel,
+    IsolationLevel, Iterable, JoinType, JsonValue, ModelTrait, Order, PaginatorTrait,
+    PrimaryKeyToColumn, QueryFilter, QueryOrder, QueryResult, QuerySelect, QueryTrait, Select,
+    SelectTwo, SqlErr, Statement, TransactionTrait, TryGetableMany, Value,
 };
 
 /// Helper to convert `Option<String>` to `Option<Uuid>`
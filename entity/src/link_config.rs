@@ -0,0 +1,23 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Singleton (`id` is always [`Model::SINGLETON_ID`]) holding the secret the
+/// access tokens of password-protected links are keyed with. `access_secret`
+/// is hex and must never be exposed.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "link_config")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: i32,
+    #[serde(skip_serializing)]
+    pub access_secret: String,
+}
+
+impl Model {
+    pub const SINGLETON_ID: i32 = 1;
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
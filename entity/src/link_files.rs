@@ -0,0 +1,52 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "link_files")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: Uuid,
+    pub link_id: Uuid,
+    /// A file or directory under the folder the link was created for.
+    pub file_id: Uuid,
+    /// Encrypted under the link key — not refreshed if the file is
+    /// renamed after the link is created.
+    pub encrypted_name: String,
+    /// File's symmetric key wrapped with the link key. Directories have
+    /// no content and therefore no key.
+    pub encrypted_file_key: Option<String>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::links::Entity",
+        from = "Column::LinkId",
+        to = "super::links::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Links,
+    #[sea_orm(
+        belongs_to = "super::files::Entity",
+        from = "Column::FileId",
+        to = "super::files::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Files,
+}
+
+impl Related<super::links::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Links.def()
+    }
+}
+
+impl Related<super::files::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Files.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
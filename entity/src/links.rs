@@ -29,6 +29,12 @@ pub struct Model {
     /// Cron periodically purges expired rows' file metadata and
     /// `encrypted_file_key` to cut the attack surface on stale links.
     pub expires_at: Option<i64>,
+    /// Completed downloads after which the link stops serving the file.
+    pub max_downloads: Option<i32>,
+    /// Bcrypt hash of the extra password a recipient has to give before
+    /// the ciphertext is streamed.
+    #[serde(skip_serializing)]
+    pub password_hash: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -32,6 +32,8 @@ pub async fn create_user<T: super::ConnectionTrait>(
         created_at: ActiveValue::Set(Utc::now().timestamp()),
         updated_at: ActiveValue::Set(Utc::now().timestamp()),
         share_notifications_enabled: ActiveValue::Set(true),
+        login_alerts_enabled: ActiveValue::Set(true),
+        password_score: ActiveValue::Set(None),
     };
 
     crate::users::Entity::insert(user)
@@ -61,7 +63,8 @@ pub async fn create_session<T: super::ConnectionTrait>(
     } else {
         Utc::now().naive_utc() + Duration::minutes(5)
     }
-    .and_utc().timestamp();
+    .and_utc()
+    .timestamp();
 
     let session = super::sessions::ActiveModel {
         id: ActiveValue::Set(id),
@@ -77,8 +80,16 @@ pub async fn create_session<T: super::ConnectionTrait>(
                 .unwrap_or_else(|| "127.0.0.1".to_string()),
         ),
         refresh: ActiveValue::Set(Some(Uuid::new_v4())),
-        created_at: ActiveValue::Set((Utc::now().naive_utc() - Duration::minutes(5)).and_utc().timestamp()),
-        updated_at: ActiveValue::Set((Utc::now().naive_utc() - Duration::minutes(5)).and_utc().timestamp()),
+        created_at: ActiveValue::Set(
+            (Utc::now().naive_utc() - Duration::minutes(5))
+                .and_utc()
+                .timestamp(),
+        ),
+        updated_at: ActiveValue::Set(
+            (Utc::now().naive_utc() - Duration::minutes(5))
+                .and_utc()
+                .timestamp(),
+        ),
         expires_at: ActiveValue::Set(expires_at),
     };
 
@@ -138,6 +149,10 @@ pub async fn create_file<T: super::ConnectionTrait>(
         members_list_signature: ActiveValue::Set(None),
         members_list_signed_at: ActiveValue::Set(None),
         members_list_signed_by_user_id: ActiveValue::Set(None),
+        deleted_at: ActiveValue::Set(None),
+        deleted_parent_id: ActiveValue::Set(None),
+        last_chunk_at: ActiveValue::Set(None),
+        damaged_at: ActiveValue::Set(None),
     };
 
     crate::files::Entity::insert(file)
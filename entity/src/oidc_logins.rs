@@ -0,0 +1,30 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A sign-in at the OpenID Connect provider. It starts as the PKCE verifier
+/// and nonce of an authorization request, keyed by its `state`; once the
+/// provider vouches for the user it carries the single-use `ticket` the
+/// login or registration that follows has to present.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "oidc_logins")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    #[serde(skip_serializing)]
+    pub code_verifier: String,
+    pub nonce: String,
+    #[sea_orm(unique)]
+    pub ticket: Option<Uuid>,
+    pub email: Option<String>,
+    /// Role mapped from the ID token, `None` when roles are not mapped
+    pub role: Option<String>,
+    /// Invitation that let a new user in, applied when they register
+    pub invitation_id: Option<Uuid>,
+    pub created_at: i64,
+    pub expires_at: i64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
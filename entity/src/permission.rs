@@ -6,7 +6,7 @@
 use error::AppResult;
 use sea_orm::ConnectionTrait;
 
-use crate::{user_files, ColumnTrait, EntityTrait, QueryFilter, Uuid};
+use crate::{files, user_files, ColumnTrait, EntityTrait, QueryFilter, Uuid};
 
 /// Outcome of a `(file_id, user_id)` lookup against `user_files`.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -55,19 +55,24 @@ impl SharePermission {
 /// Resolve `(file_id, user_id)` to a `SharePermission` via one indexed
 /// lookup. Owner rows return `Owner` regardless of their `share_role`
 /// value (the convention is `'co-owner'` from migration 1; the helper
-/// treats it as moot).
+/// treats it as moot). Files in the trash resolve to `None` for everyone;
+/// the trash routes go through the owner's trash repository instead.
+/// Shares past their `expires_at` resolve to `None` as well, even before
+/// the `purge-expired-shares` job has dropped the row.
 pub async fn permission(
     db: &impl ConnectionTrait,
     file_id: Uuid,
     user_id: Uuid,
 ) -> AppResult<SharePermission> {
     let row = user_files::Entity::find()
+        .inner_join(files::Entity)
         .filter(user_files::Column::FileId.eq(file_id))
         .filter(user_files::Column::UserId.eq(user_id))
+        .filter(files::Column::DeletedAt.is_null())
         .one(db)
         .await?;
 
-    Ok(map_row(row.as_ref()))
+    Ok(map_row(row.as_ref(), chrono::Utc::now().timestamp()))
 }
 
 /// Batch variant for routes that act on many files at once (`delete_many`,
@@ -85,25 +90,31 @@ pub async fn permissions_for(
     }
 
     let rows = user_files::Entity::find()
+        .inner_join(files::Entity)
         .filter(user_files::Column::UserId.eq(user_id))
         .filter(user_files::Column::FileId.is_in(file_ids.iter().copied()))
+        .filter(files::Column::DeletedAt.is_null())
         .all(db)
         .await?;
 
+    let now = chrono::Utc::now().timestamp();
     let mut by_file: HashMap<Uuid, SharePermission> = file_ids
         .iter()
         .map(|id| (*id, SharePermission::None))
         .collect();
     for row in rows {
-        by_file.insert(row.file_id, map_row(Some(&row)));
+        by_file.insert(row.file_id, map_row(Some(&row), now));
     }
     Ok(by_file)
 }
 
-fn map_row(row: Option<&user_files::Model>) -> SharePermission {
+fn map_row(row: Option<&user_files::Model>, now: i64) -> SharePermission {
     match row {
         None => SharePermission::None,
         Some(r) if r.is_owner => SharePermission::Owner,
+        Some(r) if r.expires_at.is_some_and(|expires_at| expires_at <= now) => {
+            SharePermission::None
+        }
         Some(r) => match r.share_role.as_str() {
             "co-owner" => SharePermission::CoOwner,
             "editor" => SharePermission::Editor,
@@ -172,7 +183,7 @@ mod tests {
                 member_signature: None,
                 member_signed_at: None,
             };
-            assert_eq!(map_row(Some(&row)), SharePermission::Owner);
+            assert_eq!(map_row(Some(&row), 0), SharePermission::Owner);
         }
     }
 
@@ -199,13 +210,34 @@ mod tests {
                 member_signature: None,
                 member_signed_at: None,
             };
-            assert_eq!(map_row(Some(&row)), expect);
+            assert_eq!(map_row(Some(&row), 0), expect);
         }
     }
 
     #[test]
     fn missing_row_maps_to_none() {
-        assert_eq!(map_row(None), SharePermission::None);
+        assert_eq!(map_row(None, 0), SharePermission::None);
+    }
+
+    #[test]
+    fn expired_share_maps_to_none() {
+        let row = user_files::Model {
+            id: Uuid::nil(),
+            file_id: Uuid::nil(),
+            user_id: Uuid::nil(),
+            encrypted_key: String::new(),
+            is_owner: false,
+            created_at: 0,
+            expires_at: Some(100),
+            share_role: "editor".to_string(),
+            shared_at: None,
+            shared_by_user_id: None,
+            member_signature: None,
+            member_signed_at: None,
+        };
+        assert_eq!(map_row(Some(&row), 99), SharePermission::Editor);
+        assert_eq!(map_row(Some(&row), 100), SharePermission::None);
+        assert_eq!(map_row(Some(&row), 101), SharePermission::None);
     }
 
     #[test]
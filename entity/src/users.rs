@@ -32,6 +32,11 @@ pub struct Model {
     pub created_at: i64,
     pub updated_at: i64,
     pub share_notifications_enabled: bool,
+    pub login_alerts_enabled: bool,
+    /// zxcvbn score the client attested for the current password, unknown
+    /// for passwords set before it was recorded
+    #[serde(skip_serializing)]
+    pub password_score: Option<i16>,
 }
 
 impl Model {
@@ -87,6 +92,8 @@ mod test {
             created_at: 0,
             updated_at: 0,
             share_notifications_enabled: true,
+            login_alerts_enabled: true,
+            password_score: None,
         };
 
         let mut user2 = user.clone();
@@ -0,0 +1,65 @@
+//! `SeaORM` Entity for version-history retention policies.
+//!
+//! A row with `file_id = NULL` is the user's default for every file they
+//! own; a row with a `file_id` overrides it for that file. Files without
+//! either fall back to the instance defaults from the config.
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "version_policies")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: Uuid,
+    /// Owner of the policy, and of the file when `file_id` is set.
+    pub user_id: Uuid,
+    /// The single file the policy applies to. `file_id` is unique.
+    pub file_id: Option<Uuid>,
+    /// Every version younger than this many days is kept.
+    pub keep_all_days: i32,
+    /// Past that, the newest version of each of the last N hours, days,
+    /// weeks and months that have any version is kept.
+    pub keep_hourly: i32,
+    pub keep_daily: i32,
+    pub keep_weekly: i32,
+    pub keep_monthly: i32,
+    /// Hard cap on the unpinned versions kept, NULL for none.
+    pub max_versions: Option<i32>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::users::Entity",
+        from = "Column::UserId",
+        to = "super::users::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Users,
+    #[sea_orm(
+        belongs_to = "super::files::Entity",
+        from = "Column::FileId",
+        to = "super::files::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Files,
+}
+
+impl Related<super::users::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Users.def()
+    }
+}
+
+impl Related<super::files::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Files.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
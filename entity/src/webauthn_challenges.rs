@@ -0,0 +1,45 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A WebAuthn challenge waiting for the authenticator's answer: a registration
+/// or a confirmation started from an authenticated session, or the second step
+/// of a login whose password proof already passed. Single-use, and expired
+/// after a few minutes.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "webauthn_challenges")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub user_id: Uuid,
+    /// `register`, `login` or `confirm`.
+    pub ceremony: String,
+    #[serde(skip_serializing)]
+    pub challenge: String,
+    pub expires_at: i64,
+}
+
+impl Model {
+    pub const REGISTER: &'static str = "register";
+    pub const LOGIN: &'static str = "login";
+    pub const CONFIRM: &'static str = "confirm";
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::users::Entity",
+        from = "Column::UserId",
+        to = "super::users::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Users,
+}
+
+impl Related<super::users::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Users.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
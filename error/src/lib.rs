@@ -64,6 +64,10 @@ pub enum Error {
     /// off, so every `/api/shares/...` route refuses traffic until it is
     /// flipped back on. Existing rows are preserved across toggles.
     ServiceUnavailable(String),
+    /// HTTP 403. The security policy in `Settings.security` asks the user
+    /// to set up two-factor or change their password, and until they do
+    /// their session only reaches the routes that get them there.
+    EnrollmentRequired(String),
 }
 
 impl Error {
@@ -423,6 +427,11 @@ impl From<&Error> for ErrorResponse {
                 message: message.to_string(),
                 context: None,
             },
+            Error::EnrollmentRequired(message) => ErrorResponse {
+                status: 403,
+                message: message.to_string(),
+                context: None,
+            },
         }
     }
 }
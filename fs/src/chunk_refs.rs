@@ -0,0 +1,129 @@
+use std::collections::BTreeMap;
+
+/// Chunks that versions of a file borrow from an older version instead of
+/// storing their own copy, as recorded in the database. Attached to a
+/// [`crate::filename::Filename`] so the versioned reads find each chunk
+/// where its bytes are. A reference always names the version that stores
+/// the bytes, never another reference.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ChunkRefs {
+    versions: BTreeMap<i32, BTreeMap<i64, i32>>,
+}
+
+impl ChunkRefs {
+    /// Record that chunk `chunk` of `version` is stored under `source`.
+    pub fn insert(&mut self, version: i32, chunk: i64, source: i32) {
+        self.versions
+            .entry(version)
+            .or_default()
+            .insert(chunk, source);
+    }
+
+    /// Version storing the bytes of `chunk` of `version`, the version
+    /// itself unless it borrows the chunk.
+    pub fn source(&self, version: i32, chunk: i64) -> i32 {
+        self.versions
+            .get(&version)
+            .and_then(|chunks| chunks.get(&chunk))
+            .copied()
+            .unwrap_or(version)
+    }
+
+    /// Chunks `version` borrows, paired with the version storing them, in
+    /// chunk order.
+    pub fn borrowed(&self, version: i32) -> impl Iterator<Item = (i64, i32)> + '_ {
+        self.versions
+            .get(&version)
+            .into_iter()
+            .flat_map(|chunks| chunks.iter().map(|(chunk, source)| (*chunk, *source)))
+    }
+
+    /// Work out which chunks of `purged` must survive its purge. The oldest
+    /// borrower of each chunk becomes its new holder.
+    pub fn relocations(&self, purged: i32) -> Vec<Relocation> {
+        let mut borrowers: BTreeMap<i64, Vec<i32>> = BTreeMap::new();
+        for (version, chunks) in &self.versions {
+            for (chunk, source) in chunks {
+                if *source == purged && *version != purged {
+                    borrowers.entry(*chunk).or_default().push(*version);
+                }
+            }
+        }
+
+        borrowers
+            .into_iter()
+            .map(|(chunk, versions)| Relocation {
+                chunk,
+                holder: versions[0],
+                repoint: versions[1..].to_vec(),
+            })
+            .collect()
+    }
+}
+
+impl FromIterator<(i32, i64, i32)> for ChunkRefs {
+    /// Collect `(version, chunk, source)` triples.
+    fn from_iter<I: IntoIterator<Item = (i32, i64, i32)>>(iter: I) -> Self {
+        let mut refs = Self::default();
+        for (version, chunk, source) in iter {
+            refs.insert(version, chunk, source);
+        }
+        refs
+    }
+}
+
+/// One chunk of a purged version that other versions still borrow. The
+/// bytes are copied into `holder`, which drops its reference, and every
+/// version in `repoint` points at `holder` instead.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Relocation {
+    pub chunk: i64,
+    pub holder: i32,
+    pub repoint: Vec<i32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunks_resolve_to_their_source() {
+        let refs = ChunkRefs::from_iter([(3, 1, 2), (3, 4, 1), (2, 0, 1)]);
+
+        assert_eq!(refs.source(3, 1), 2);
+        assert_eq!(refs.source(3, 4), 1);
+        assert_eq!(refs.source(3, 0), 3);
+        assert_eq!(refs.source(5, 1), 5);
+        assert_eq!(refs.borrowed(3).collect::<Vec<_>>(), vec![(1, 2), (4, 1)]);
+        assert_eq!(refs.borrowed(1).count(), 0);
+    }
+
+    #[test]
+    fn oldest_borrower_takes_over_a_purged_chunk() {
+        let refs = ChunkRefs::from_iter([
+            (3, 0, 2),
+            (4, 0, 2),
+            (4, 1, 2),
+            (5, 0, 2),
+            (5, 1, 3),
+            (2, 2, 1),
+        ]);
+
+        assert_eq!(
+            refs.relocations(2),
+            vec![
+                Relocation {
+                    chunk: 0,
+                    holder: 3,
+                    repoint: vec![4, 5],
+                },
+                Relocation {
+                    chunk: 1,
+                    holder: 4,
+                    repoint: vec![],
+                },
+            ]
+        );
+        assert!(refs.relocations(5).is_empty());
+    }
+}
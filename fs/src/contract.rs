@@ -1,4 +1,4 @@
-use crate::{filename::IntoFilename, streamer::Streamer};
+use crate::{filename::IntoFilename, inventory::StoredFile, streamer::Streamer};
 use error::AppResult;
 
 use async_trait::async_trait;
@@ -23,6 +23,10 @@ pub trait FsProviderContract {
     /// Check if the chunk already exists in the storage provider
     async fn exists<T: IntoFilename>(&self, filename: &T, chunk: i64) -> AppResult<bool>;
 
+    /// Size in bytes of a stored chunk, `None` when it does not exist.
+    async fn chunk_size<T: IntoFilename>(&self, filename: &T, chunk: i64)
+        -> AppResult<Option<u64>>;
+
     /// Push specific data chunk into a part file
     async fn push<T: IntoFilename>(&self, filename: &T, chunk: i64, data: &[u8]) -> AppResult<()>;
 
@@ -59,6 +63,13 @@ pub trait FsProviderContract {
     //
     // Writes go straight to the new layout
     // (`{uuid}/v{version}/{chunk:06}.chunk`) — legacy is read-only.
+    //
+    // A version may borrow chunks unchanged since an older version instead
+    // of storing them. The references live in the database and are handed
+    // in through `Filename::with_chunk_refs`; every read below follows
+    // them. A borrowed chunk only counts as uploaded while its source
+    // still has it. `purge_version` knows nothing of references, callers
+    // move borrowed chunks out of a version before purging it.
 
     /// Write a chunk into a specific version's directory.
     async fn push_v<T: IntoFilename>(
@@ -87,9 +98,19 @@ pub trait FsProviderContract {
         chunk: i64,
     ) -> AppResult<bool>;
 
-    /// List uploaded chunk indices for a specific version. Returns an
-    /// empty Vec when the version directory does not exist. Legacy
-    /// fallback applies for `version == 1`.
+    /// Size in bytes of a chunk in the given version, `None` when it does
+    /// not exist. Same legacy fallback as `pull_v`.
+    async fn chunk_size_v<T: IntoFilename>(
+        &self,
+        filename: &T,
+        version: i32,
+        chunk: i64,
+    ) -> AppResult<Option<u64>>;
+
+    /// List uploaded chunk indices for a specific version, borrowed ones
+    /// included. Returns an empty Vec when the version directory does not
+    /// exist and nothing is borrowed. Legacy fallback applies for
+    /// `version == 1`.
     async fn get_uploaded_chunks_v<T: IntoFilename>(
         &self,
         filename: &T,
@@ -121,16 +142,13 @@ pub trait FsProviderContract {
     /// Delete a single version's directory and its chunks. No-op if the
     /// directory does not exist (recovery case after a half-finished
     /// abandon).
-    async fn purge_version<T: IntoFilename>(
-        &self,
-        filename: &T,
-        version: i32,
-    ) -> AppResult<()>;
+    async fn purge_version<T: IntoFilename>(&self, filename: &T, version: i32) -> AppResult<()>;
 
     /// Copy all chunks from `src/v{src_version}/` to
     /// `dst/v{dst_version}/`. Source and destination filenames are
     /// independent — pass the same one for restore-in-place, different
-    /// ones for fork-as-new-note.
+    /// ones for fork-as-new-note. Borrowed chunks of the source are copied
+    /// from where they are stored.
     async fn copy_version<S: IntoFilename, D: IntoFilename>(
         &self,
         src: &S,
@@ -139,7 +157,17 @@ pub trait FsProviderContract {
         dst_version: i32,
     ) -> AppResult<()>;
 
+    /// Copy every chunk of `src` to `dst` in the flat (non-versioned)
+    /// layout that write-once files are read from. The counterpart of
+    /// [`Self::copy_version`] for files that were never edited.
+    async fn copy<S: IntoFilename, D: IntoFilename>(&self, src: &S, dst: &D) -> AppResult<()>;
+
     /// Delete the file's entire on-disk footprint — every version
     /// directory plus any legacy chunks. Used on full file deletion.
     async fn purge_all<T: IntoFilename>(&self, filename: &T) -> AppResult<()>;
+
+    /// List every file that has chunks on the storage provider, in either
+    /// layout. Walks the whole data directory or bucket prefix, so it is
+    /// meant for maintenance tooling like `hoodik fsck`, not request paths.
+    async fn inventory(&self) -> AppResult<Vec<StoredFile>>;
 }
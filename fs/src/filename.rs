@@ -1,3 +1,4 @@
+use crate::chunk_refs::ChunkRefs;
 use error::AppResult;
 use std::fmt::{Display, Formatter, Result};
 
@@ -10,6 +11,7 @@ pub struct Filename {
     inner_name: String,
     extension: Option<String>,
     chunk: Option<String>,
+    chunk_refs: ChunkRefs,
 }
 
 impl Display for Filename {
@@ -32,6 +34,7 @@ impl Filename {
             inner_name: name.to_string(),
             extension: None,
             chunk: None,
+            chunk_refs: ChunkRefs::default(),
         }
     }
 
@@ -59,6 +62,30 @@ impl Filename {
     pub fn inner_name(&self) -> &str {
         &self.inner_name
     }
+
+    /// Attach the chunks the file's versions borrow from older versions,
+    /// loaded from the database. Without them the versioned reads only see
+    /// the chunks each version stores itself.
+    pub fn with_chunk_refs(mut self, chunk_refs: ChunkRefs) -> Self {
+        self.chunk_refs = chunk_refs;
+
+        self
+    }
+
+    pub fn chunk_refs(&self) -> &ChunkRefs {
+        &self.chunk_refs
+    }
+
+    /// The same name with no chunk references attached.
+    pub fn without_chunk_refs(&self) -> Self {
+        Self {
+            timestamp: self.timestamp.clone(),
+            inner_name: self.inner_name.clone(),
+            extension: self.extension.clone(),
+            chunk: self.chunk.clone(),
+            chunk_refs: ChunkRefs::default(),
+        }
+    }
 }
 
 /// Trait to implement on file representations
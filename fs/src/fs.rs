@@ -4,9 +4,7 @@ use config::Config;
 use error::AppResult;
 
 use crate::{
-    contract::FsProviderContract,
-    filename::IntoFilename,
-    providers::fs,
+    contract::FsProviderContract, filename::IntoFilename, inventory::StoredFile, providers::fs,
     streamer::Streamer,
 };
 
@@ -54,6 +52,14 @@ impl FsProviderContract for StorageProvider<'_> {
         dispatch!(self, exists(filename, chunk))
     }
 
+    async fn chunk_size<T: IntoFilename>(
+        &self,
+        filename: &T,
+        chunk: i64,
+    ) -> AppResult<Option<u64>> {
+        dispatch!(self, chunk_size(filename, chunk))
+    }
+
     async fn push<T: IntoFilename>(&self, filename: &T, chunk: i64, data: &[u8]) -> AppResult<()> {
         dispatch!(self, push(filename, chunk, data))
     }
@@ -114,6 +120,15 @@ impl FsProviderContract for StorageProvider<'_> {
         dispatch!(self, exists_v(filename, version, chunk))
     }
 
+    async fn chunk_size_v<T: IntoFilename>(
+        &self,
+        filename: &T,
+        version: i32,
+        chunk: i64,
+    ) -> AppResult<Option<u64>> {
+        dispatch!(self, chunk_size_v(filename, version, chunk))
+    }
+
     async fn get_uploaded_chunks_v<T: IntoFilename>(
         &self,
         filename: &T,
@@ -147,11 +162,7 @@ impl FsProviderContract for StorageProvider<'_> {
         dispatch!(self, tar_content_length_v(filename, version))
     }
 
-    async fn purge_version<T: IntoFilename>(
-        &self,
-        filename: &T,
-        version: i32,
-    ) -> AppResult<()> {
+    async fn purge_version<T: IntoFilename>(&self, filename: &T, version: i32) -> AppResult<()> {
         dispatch!(self, purge_version(filename, version))
     }
 
@@ -165,9 +176,17 @@ impl FsProviderContract for StorageProvider<'_> {
         dispatch!(self, copy_version(src, src_version, dst, dst_version))
     }
 
+    async fn copy<S: IntoFilename, D: IntoFilename>(&self, src: &S, dst: &D) -> AppResult<()> {
+        dispatch!(self, copy(src, dst))
+    }
+
     async fn purge_all<T: IntoFilename>(&self, filename: &T) -> AppResult<()> {
         dispatch!(self, purge_all(filename))
     }
+
+    async fn inventory(&self) -> AppResult<Vec<StoredFile>> {
+        dispatch!(self, inventory())
+    }
 }
 
 pub struct Fs<'ctx> {
@@ -245,6 +264,14 @@ impl FsProviderContract for Fs<'_> {
         self.provider().exists(filename, chunk).await
     }
 
+    async fn chunk_size<T: IntoFilename>(
+        &self,
+        filename: &T,
+        chunk: i64,
+    ) -> AppResult<Option<u64>> {
+        self.provider().chunk_size(filename, chunk).await
+    }
+
     async fn push<T: IntoFilename>(&self, filename: &T, chunk: i64, data: &[u8]) -> AppResult<()> {
         self.provider().push(filename, chunk, data).await
     }
@@ -305,6 +332,15 @@ impl FsProviderContract for Fs<'_> {
         self.provider().exists_v(filename, version, chunk).await
     }
 
+    async fn chunk_size_v<T: IntoFilename>(
+        &self,
+        filename: &T,
+        version: i32,
+        chunk: i64,
+    ) -> AppResult<Option<u64>> {
+        self.provider().chunk_size_v(filename, version, chunk).await
+    }
+
     async fn get_uploaded_chunks_v<T: IntoFilename>(
         &self,
         filename: &T,
@@ -337,14 +373,12 @@ impl FsProviderContract for Fs<'_> {
         filename: &T,
         version: i32,
     ) -> AppResult<u64> {
-        self.provider().tar_content_length_v(filename, version).await
+        self.provider()
+            .tar_content_length_v(filename, version)
+            .await
     }
 
-    async fn purge_version<T: IntoFilename>(
-        &self,
-        filename: &T,
-        version: i32,
-    ) -> AppResult<()> {
+    async fn purge_version<T: IntoFilename>(&self, filename: &T, version: i32) -> AppResult<()> {
         self.provider().purge_version(filename, version).await
     }
 
@@ -360,7 +394,15 @@ impl FsProviderContract for Fs<'_> {
             .await
     }
 
+    async fn copy<S: IntoFilename, D: IntoFilename>(&self, src: &S, dst: &D) -> AppResult<()> {
+        self.provider().copy(src, dst).await
+    }
+
     async fn purge_all<T: IntoFilename>(&self, filename: &T) -> AppResult<()> {
         self.provider().purge_all(filename).await
     }
+
+    async fn inventory(&self) -> AppResult<Vec<StoredFile>> {
+        self.provider().inventory().await
+    }
 }
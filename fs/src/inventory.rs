@@ -0,0 +1,138 @@
+use std::collections::BTreeMap;
+
+use crate::filename::Filename;
+
+/// Footprint of a single file on the storage provider, as found by
+/// [`crate::contract::FsProviderContract::inventory`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StoredFile {
+    /// Inner name of the file, the file id for everything the storage
+    /// crate writes.
+    pub name: String,
+    /// Timestamp prefixes of legacy flat chunks (`{timestamp}-{name}.part.N`),
+    /// empty when the file has none.
+    pub legacy: Vec<String>,
+    /// Versions that have a `{name}/v{N}/` directory, ascending.
+    pub versions: Vec<i32>,
+}
+
+impl StoredFile {
+    /// Filenames covering the whole footprint: one per legacy timestamp,
+    /// or just the bare name when the file only has versioned chunks.
+    /// Passing each one to `purge_all` removes everything listed here.
+    pub fn filenames(&self) -> Vec<Filename> {
+        if self.legacy.is_empty() {
+            return vec![Filename::new(&self.name)];
+        }
+
+        self.legacy
+            .iter()
+            .map(|timestamp| Filename::new(&self.name).with_timestamp(timestamp))
+            .collect()
+    }
+}
+
+/// Collects storage keys relative to the data directory (or bucket prefix)
+/// into [`StoredFile`] entries. Keys matching neither layout, like the
+/// sqlite database living next to the chunks, are ignored.
+#[derive(Default)]
+pub(crate) struct Inventory {
+    files: BTreeMap<String, StoredFile>,
+}
+
+impl Inventory {
+    pub(crate) fn add(&mut self, key: &str) {
+        if let Some((name, rest)) = key.split_once('/') {
+            let version = rest
+                .split('/')
+                .next()
+                .and_then(|dir| dir.strip_prefix('v'))
+                .and_then(|version| version.parse::<i32>().ok());
+
+            if let Some(version) = version {
+                let file = self.entry(name);
+                if !file.versions.contains(&version) {
+                    file.versions.push(version);
+                }
+            }
+
+            return;
+        }
+
+        let Some((stem, chunk)) = key.rsplit_once(".part.") else {
+            return;
+        };
+
+        if chunk.parse::<i64>().is_err() {
+            return;
+        }
+
+        if let Some((timestamp, name)) = stem.split_once('-') {
+            if !timestamp.is_empty() && timestamp.chars().all(|c| c.is_ascii_digit()) {
+                let file = self.entry(name);
+                if !file.legacy.iter().any(|t| t == timestamp) {
+                    file.legacy.push(timestamp.to_string());
+                }
+            }
+        }
+    }
+
+    fn entry(&mut self, name: &str) -> &mut StoredFile {
+        self.files
+            .entry(name.to_string())
+            .or_insert_with(|| StoredFile {
+                name: name.to_string(),
+                ..Default::default()
+            })
+    }
+
+    pub(crate) fn into_files(self) -> Vec<StoredFile> {
+        self.files
+            .into_values()
+            .filter(|file| !file.name.is_empty())
+            .map(|mut file| {
+                file.versions.sort_unstable();
+                file
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_versioned_and_legacy_keys_by_name() {
+        let mut inventory = Inventory::default();
+        for key in [
+            "abc/v2/000000.chunk",
+            "abc/v1/000000.chunk",
+            "abc/v2/000001.chunk",
+            "17-abc.part.0",
+            "17-abc.part.1",
+            "20-def.part.0",
+            "sqlite.db",
+            "abc/notes.txt",
+            "x-abc.part.0",
+        ] {
+            inventory.add(key);
+        }
+
+        assert_eq!(
+            inventory.into_files(),
+            vec![
+                StoredFile {
+                    name: "abc".to_string(),
+                    legacy: vec!["17".to_string()],
+                    versions: vec![1, 2],
+                },
+                StoredFile {
+                    name: "def".to_string(),
+                    legacy: vec!["20".to_string()],
+                    versions: vec![],
+                },
+            ]
+        );
+    }
+}
@@ -1,6 +1,8 @@
+mod chunk_refs;
 mod contract;
 mod filename;
 mod fs;
+mod inventory;
 mod providers;
 mod streamer;
 pub mod tar;
@@ -18,8 +20,7 @@ pub const MAX_CHUNK_SIZE_BYTES: u64 = 1024 * 1024 * 4;
 /// bytes of tag (AEGIS-128L, ChaCha20-Poly1305, Ascon-128a), so 1 % is far
 /// more than the worst case but keeps the per-chunk and tar wire ceilings
 /// in lockstep with one shared constant.
-pub const MAX_CHUNK_PAYLOAD_BYTES: u64 =
-    MAX_CHUNK_SIZE_BYTES + MAX_CHUNK_SIZE_BYTES / 100;
+pub const MAX_CHUNK_PAYLOAD_BYTES: u64 = MAX_CHUNK_SIZE_BYTES + MAX_CHUNK_SIZE_BYTES / 100;
 
 // S3 CopyObject's single-operation limit is 5 GiB. Any chunk larger than that
 // would force the S3 `copy_version` path into a multipart-copy code path we
@@ -28,9 +29,11 @@ pub const MAX_CHUNK_PAYLOAD_BYTES: u64 =
 const _: () = assert!(MAX_CHUNK_SIZE_BYTES < 5 * 1024 * 1024 * 1024);
 
 pub mod prelude {
+    pub use super::chunk_refs::{ChunkRefs, Relocation};
     pub use super::contract::FsProviderContract;
     pub use super::filename::{Filename, IntoFilename};
     pub use super::fs::Fs;
+    pub use super::inventory::StoredFile;
     pub use super::streamer::Streamer;
 
     #[cfg(feature = "s3")]
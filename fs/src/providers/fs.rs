@@ -1,15 +1,18 @@
+use std::collections::BTreeMap;
+
 use actix_web::web::Bytes;
 use async_trait::async_trait;
 use error::{AppResult, Error};
 use fs4::available_space;
 use tokio::{
-    fs::{copy as fs_copy, create_dir_all, read_dir, remove_dir_all, remove_file, File},
+    fs::{copy as fs_copy, create_dir_all, metadata, read_dir, remove_dir_all, remove_file, File},
     io::{AsyncReadExt, AsyncWriteExt},
 };
 
 use crate::{
     contract::FsProviderContract,
     filename::{Filename, IntoFilename},
+    inventory::{Inventory, StoredFile},
     streamer::Streamer,
     tar,
 };
@@ -40,11 +43,7 @@ impl<'provider> FsProvider<'provider> {
 
     /// Path of one chunk file under a version: `.../v{N}/{chunk:06}.chunk`.
     fn versioned_chunk_path(&self, filename: &Filename, version: i32, chunk: i64) -> String {
-        format!(
-            "{}/{:06}.chunk",
-            self.version_dir(filename, version),
-            chunk
-        )
+        format!("{}/{:06}.chunk", self.version_dir(filename, version), chunk)
     }
 
     /// True if a versioned chunk directory exists and contains at least one
@@ -76,7 +75,11 @@ impl<'provider> FsProvider<'provider> {
 
     /// List chunk indices inside a version directory by reading its entries.
     /// Returns empty if the directory is missing.
-    async fn list_versioned_chunks(&self, filename: &Filename, version: i32) -> AppResult<Vec<i64>> {
+    async fn list_versioned_chunks(
+        &self,
+        filename: &Filename,
+        version: i32,
+    ) -> AppResult<Vec<i64>> {
         let dir = self.version_dir(filename, version);
         let mut entries = match read_dir(&dir).await {
             Ok(e) => e,
@@ -104,6 +107,62 @@ impl<'provider> FsProvider<'provider> {
         Ok(chunks)
     }
 
+    /// Stored chunks of a version plus every chunk it borrows, whether or
+    /// not its source still has it. Reads go through this so a missing
+    /// source chunk fails the read instead of silently leaving a gap.
+    async fn version_chunks(&self, filename: &Filename, version: i32) -> AppResult<Vec<i64>> {
+        let mut chunks = self.list_versioned_chunks(filename, version).await?;
+        chunks.extend(filename.chunk_refs().borrowed(version).map(|(c, _)| c));
+        chunks.sort();
+        chunks.dedup();
+        Ok(chunks)
+    }
+
+    /// Borrowed chunks of a version whose source version stores them.
+    async fn present_borrowed_chunks(
+        &self,
+        filename: &Filename,
+        version: i32,
+    ) -> AppResult<Vec<i64>> {
+        let mut by_source: BTreeMap<i32, Vec<i64>> = BTreeMap::new();
+        for (chunk, source) in filename.chunk_refs().borrowed(version) {
+            by_source.entry(source).or_default().push(chunk);
+        }
+
+        let stored = filename.without_chunk_refs();
+        let mut present = Vec::new();
+        for (source, chunks) in by_source {
+            let available = self.get_uploaded_chunks_v(&stored, source).await?;
+            present.extend(chunks.into_iter().filter(|c| available.contains(c)));
+        }
+        Ok(present)
+    }
+
+    /// Path holding the bytes of each chunk of a version, following the
+    /// chunk references of the filename. A chunk borrowed from a `v1`
+    /// that still sits in the legacy flat layout is read from there.
+    async fn chunk_paths(
+        &self,
+        filename: &Filename,
+        version: i32,
+        chunks: Vec<i64>,
+    ) -> Vec<(i64, String)> {
+        let refs = filename.chunk_refs();
+        let legacy = refs.borrowed(version).any(|(_, source)| source == 1)
+            && self.should_use_legacy(filename, 1).await;
+
+        chunks
+            .into_iter()
+            .map(|chunk| {
+                let path = match refs.source(version, chunk) {
+                    1 if legacy => self.full_path(&filename.clone().with_chunk(chunk)),
+                    source => self.versioned_chunk_path(filename, source, chunk),
+                };
+                (chunk, path)
+            })
+            .collect()
+    }
+
     /// Create the inner streaming method that is then passed into the streamer for
     /// better readability of the code.
     ///
@@ -362,6 +421,15 @@ impl FsProviderContract for FsProvider<'_> {
         .exists())
     }
 
+    async fn chunk_size<T: IntoFilename>(
+        &self,
+        filename: &T,
+        chunk: i64,
+    ) -> AppResult<Option<u64>> {
+        let path = self.full_path(&filename.filename()?.with_chunk(chunk));
+        file_size(&path).await
+    }
+
     async fn push<T: IntoFilename>(&self, filename: &T, chunk: i64, data: &[u8]) -> AppResult<()> {
         let filename = filename.filename()?.with_chunk(chunk);
 
@@ -484,6 +552,13 @@ impl FsProviderContract for FsProvider<'_> {
         chunk: i64,
     ) -> AppResult<Vec<u8>> {
         let filename = filename.filename()?;
+        let source = filename.chunk_refs().source(version, chunk);
+        if source != version {
+            return self
+                .pull_v(&filename.without_chunk_refs(), source, chunk)
+                .await;
+        }
+
         if self.should_use_legacy(&filename, version).await {
             return self.pull(&filename, chunk).await;
         }
@@ -502,6 +577,13 @@ impl FsProviderContract for FsProvider<'_> {
         chunk: i64,
     ) -> AppResult<bool> {
         let filename = filename.filename()?;
+        let source = filename.chunk_refs().source(version, chunk);
+        if source != version {
+            return self
+                .exists_v(&filename.without_chunk_refs(), source, chunk)
+                .await;
+        }
+
         if self.should_use_legacy(&filename, version).await {
             return self.exists(&filename, chunk).await;
         }
@@ -510,6 +592,27 @@ impl FsProviderContract for FsProvider<'_> {
         Ok(std::path::Path::new(&path).exists())
     }
 
+    async fn chunk_size_v<T: IntoFilename>(
+        &self,
+        filename: &T,
+        version: i32,
+        chunk: i64,
+    ) -> AppResult<Option<u64>> {
+        let filename = filename.filename()?;
+        let source = filename.chunk_refs().source(version, chunk);
+        if source != version {
+            return self
+                .chunk_size_v(&filename.without_chunk_refs(), source, chunk)
+                .await;
+        }
+
+        if self.should_use_legacy(&filename, version).await {
+            return self.chunk_size(&filename, chunk).await;
+        }
+
+        file_size(&self.versioned_chunk_path(&filename, version, chunk)).await
+    }
+
     async fn get_uploaded_chunks_v<T: IntoFilename>(
         &self,
         filename: &T,
@@ -519,7 +622,12 @@ impl FsProviderContract for FsProvider<'_> {
         if self.should_use_legacy(&filename, version).await {
             return self.get_uploaded_chunks(&filename).await;
         }
-        self.list_versioned_chunks(&filename, version).await
+
+        let mut chunks = self.list_versioned_chunks(&filename, version).await?;
+        chunks.extend(self.present_borrowed_chunks(&filename, version).await?);
+        chunks.sort();
+        chunks.dedup();
+        Ok(chunks)
     }
 
     async fn stream_v<T: IntoFilename>(
@@ -537,36 +645,35 @@ impl FsProviderContract for FsProvider<'_> {
         // open-one-at-a-time pattern, just using versioned chunk paths.
         let chunk_indices: Vec<i64> = match chunk {
             Some(c) => vec![c],
-            None => self.list_versioned_chunks(&filename, version).await?,
+            None => self.version_chunks(&filename, version).await?,
         };
 
-        let mut paths: Vec<String> = chunk_indices
+        let mut paths: Vec<String> = self
+            .chunk_paths(&filename, version, chunk_indices)
+            .await
             .into_iter()
-            .map(|c| self.versioned_chunk_path(&filename, version, c))
+            .map(|(_, path)| path)
             .collect();
         paths.reverse();
 
-        let stream = futures_util::stream::unfold(
-            paths,
-            |mut paths: Vec<String>| async move {
-                let path = paths.pop()?;
-                let mut file = match File::open(&path).await {
-                    Ok(f) => f,
-                    Err(e) => {
-                        log::error!("Failed to open chunk file {}: {:#?}", path, e);
-                        return Some((Err(Error::from(e)), paths));
-                    }
-                };
-                let mut data = vec![];
-                match file.read_to_end(&mut data).await {
-                    Ok(_) => Some((Ok(Bytes::from(data)), paths)),
-                    Err(e) => {
-                        log::error!("Failed to read chunk file {}: {:#?}", path, e);
-                        Some((Err(Error::from(e)), paths))
-                    }
+        let stream = futures_util::stream::unfold(paths, |mut paths: Vec<String>| async move {
+            let path = paths.pop()?;
+            let mut file = match File::open(&path).await {
+                Ok(f) => f,
+                Err(e) => {
+                    log::error!("Failed to open chunk file {}: {:#?}", path, e);
+                    return Some((Err(Error::from(e)), paths));
                 }
-            },
-        );
+            };
+            let mut data = vec![];
+            match file.read_to_end(&mut data).await {
+                Ok(_) => Some((Ok(Bytes::from(data)), paths)),
+                Err(e) => {
+                    log::error!("Failed to read chunk file {}: {:#?}", path, e);
+                    Some((Err(Error::from(e)), paths))
+                }
+            }
+        });
 
         Ok(Streamer::new(stream))
     }
@@ -582,14 +689,12 @@ impl FsProviderContract for FsProvider<'_> {
         }
 
         // Build the entries upfront (name + path), then stream lazily.
-        let chunks = self.list_versioned_chunks(&filename, version).await?;
-        let mut entries: Vec<(String, String)> = chunks
-            .iter()
-            .map(|idx| {
-                let path = self.versioned_chunk_path(&filename, version, *idx);
-                let name = format!("{:06}.enc", idx);
-                (name, path)
-            })
+        let chunks = self.version_chunks(&filename, version).await?;
+        let mut entries: Vec<(String, String)> = self
+            .chunk_paths(&filename, version, chunks)
+            .await
+            .into_iter()
+            .map(|(idx, path)| (format!("{:06}.enc", idx), path))
             .collect();
         entries.reverse();
 
@@ -683,10 +788,9 @@ impl FsProviderContract for FsProvider<'_> {
             return self.tar_content_length(&filename).await;
         }
 
-        let chunks = self.list_versioned_chunks(&filename, version).await?;
+        let chunks = self.version_chunks(&filename, version).await?;
         let mut total: u64 = 0;
-        for idx in &chunks {
-            let path = self.versioned_chunk_path(&filename, version, *idx);
+        for (_, path) in self.chunk_paths(&filename, version, chunks).await {
             let file = File::open(&path).await?;
             let size = file.metadata().await?.len();
             total += 512 + size + tar::tar_padding_len(size) as u64;
@@ -695,11 +799,7 @@ impl FsProviderContract for FsProvider<'_> {
         Ok(total)
     }
 
-    async fn purge_version<T: IntoFilename>(
-        &self,
-        filename: &T,
-        version: i32,
-    ) -> AppResult<()> {
+    async fn purge_version<T: IntoFilename>(&self, filename: &T, version: i32) -> AppResult<()> {
         let filename = filename.filename()?;
         let dir = self.version_dir(&filename, version);
         match remove_dir_all(&dir).await {
@@ -721,28 +821,44 @@ impl FsProviderContract for FsProvider<'_> {
         let src = src.filename()?;
         let dst = dst.filename()?;
 
-        let src_is_legacy = self.should_use_legacy(&src, src_version).await;
-        let src_chunks = if src_is_legacy {
-            self.get_uploaded_chunks(&src).await?
+        // Borrowed chunks are copied from their source, the destination
+        // never shares chunks with the source version.
+        let src_chunks: Vec<(i64, String)> = if self.should_use_legacy(&src, src_version).await {
+            self.get_uploaded_chunks(&src)
+                .await?
+                .into_iter()
+                .map(|idx| (idx, self.full_path(&src.clone().with_chunk(idx))))
+                .collect()
         } else {
-            self.list_versioned_chunks(&src, src_version).await?
+            let chunks = self.version_chunks(&src, src_version).await?;
+            self.chunk_paths(&src, src_version, chunks).await
         };
 
         let dst_dir = self.version_dir(&dst, dst_version);
         create_dir_all(&dst_dir).await?;
 
-        for idx in src_chunks {
-            let src_path = if src_is_legacy {
-                self.full_path(&src.clone().with_chunk(idx))
-            } else {
-                self.versioned_chunk_path(&src, src_version, idx)
-            };
+        for (idx, src_path) in src_chunks {
             let dst_path = self.versioned_chunk_path(&dst, dst_version, idx);
             fs_copy(&src_path, &dst_path).await?;
         }
         Ok(())
     }
 
+    async fn copy<S: IntoFilename, D: IntoFilename>(&self, src: &S, dst: &D) -> AppResult<()> {
+        let src = src.filename()?;
+        let dst = dst.filename()?;
+
+        let chunks = self.get_uploaded_chunks(&src).await?;
+        for idx in chunks {
+            fs_copy(
+                self.full_path(&src.clone().with_chunk(idx)),
+                self.full_path(&dst.clone().with_chunk(idx)),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
     async fn purge_all<T: IntoFilename>(&self, filename: &T) -> AppResult<()> {
         let filename = filename.filename()?;
         // Versioned layout: drop the whole {file_id}/ tree if present.
@@ -757,6 +873,41 @@ impl FsProviderContract for FsProvider<'_> {
         // are still cleaned up on full deletion.
         self.purge(&filename).await
     }
+
+    async fn inventory(&self) -> AppResult<Vec<StoredFile>> {
+        let mut inventory = Inventory::default();
+        let mut entries = read_dir(self.data_dir).await?;
+
+        while let Some(entry) = entries.next_entry().await? {
+            let name = entry.file_name().to_string_lossy().to_string();
+
+            if !entry.file_type().await?.is_dir() {
+                inventory.add(&name);
+                continue;
+            }
+
+            // Only `v{N}` children matter; an empty version directory still
+            // counts so a half-purged version shows up.
+            let mut versions = read_dir(entry.path()).await?;
+            while let Some(version) = versions.next_entry().await? {
+                if version.file_type().await?.is_dir() {
+                    let dir = version.file_name().to_string_lossy().to_string();
+                    inventory.add(&format!("{}/{}/", name, dir));
+                }
+            }
+        }
+
+        Ok(inventory.into_files())
+    }
+}
+
+/// Size of a file on disk, `None` when it does not exist.
+async fn file_size(path: &str) -> AppResult<Option<u64>> {
+    match metadata(path).await {
+        Ok(metadata) => Ok(Some(metadata.len())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(Error::from(e)),
+    }
 }
 
 #[cfg(test)]
@@ -834,7 +985,10 @@ mod tests {
         let filename = Filename::new("dual-uuid").with_timestamp(42);
 
         provider.push(&filename, 0, b"legacy").await.unwrap();
-        provider.push_v(&filename, 1, 0, b"versioned").await.unwrap();
+        provider
+            .push_v(&filename, 1, 0, b"versioned")
+            .await
+            .unwrap();
 
         let bytes = provider.pull_v(&filename, 1, 0).await.unwrap();
         assert_eq!(bytes, b"versioned");
@@ -853,7 +1007,11 @@ mod tests {
 
         provider.purge_version(&filename, 1).await.unwrap();
 
-        assert!(provider.get_uploaded_chunks_v(&filename, 1).await.unwrap().is_empty());
+        assert!(provider
+            .get_uploaded_chunks_v(&filename, 1)
+            .await
+            .unwrap()
+            .is_empty());
         assert_eq!(
             provider.get_uploaded_chunks_v(&filename, 2).await.unwrap(),
             vec![0]
@@ -884,7 +1042,10 @@ mod tests {
         provider.push_v(&filename, 3, 0, b"a").await.unwrap();
         provider.push_v(&filename, 3, 1, b"b").await.unwrap();
 
-        provider.copy_version(&filename, 3, &filename, 4).await.unwrap();
+        provider
+            .copy_version(&filename, 3, &filename, 4)
+            .await
+            .unwrap();
 
         assert_eq!(
             provider.get_uploaded_chunks_v(&filename, 3).await.unwrap(),
@@ -938,7 +1099,10 @@ mod tests {
         provider.push(&filename, 0, b"x").await.unwrap();
         provider.push(&filename, 1, b"y").await.unwrap();
 
-        provider.copy_version(&filename, 1, &filename, 2).await.unwrap();
+        provider
+            .copy_version(&filename, 1, &filename, 2)
+            .await
+            .unwrap();
 
         let dst = provider.get_uploaded_chunks_v(&filename, 2).await.unwrap();
         assert_eq!(dst, vec![0, 1]);
@@ -946,6 +1110,33 @@ mod tests {
         assert_eq!(provider.pull_v(&filename, 2, 1).await.unwrap(), b"y");
     }
 
+    /// `copy` duplicates flat-layout chunks under the destination's own
+    /// timestamped name — the server-side copy path for write-once files.
+    #[tokio::test]
+    async fn copy_flat_chunks_across_files() {
+        let dir = tempdir().unwrap();
+        let provider = FsProvider::new(dir.path().to_str().unwrap());
+        let src = Filename::new("flat-src-uuid").with_timestamp(10);
+        let dst = Filename::new("flat-dst-uuid").with_timestamp(20);
+
+        provider.push(&src, 0, b"one").await.unwrap();
+        provider.push(&src, 1, b"two").await.unwrap();
+
+        provider.copy(&src, &dst).await.unwrap();
+
+        assert_eq!(
+            provider.get_uploaded_chunks(&src).await.unwrap(),
+            vec![0, 1]
+        );
+        assert_eq!(
+            provider.get_uploaded_chunks(&dst).await.unwrap(),
+            vec![0, 1]
+        );
+        assert_eq!(provider.pull(&dst, 0).await.unwrap(), b"one");
+        assert_eq!(provider.pull(&dst, 1).await.unwrap(), b"two");
+        assert!(!std::path::Path::new(&provider.file_root(&dst)).exists());
+    }
+
     /// `purge_all` wipes both the versioned tree and any legacy chunks.
     #[tokio::test]
     async fn purge_all_removes_versions_and_legacy() {
@@ -954,12 +1145,166 @@ mod tests {
         let filename = Filename::new("nuke-uuid").with_timestamp(7);
 
         provider.push(&filename, 0, b"legacy").await.unwrap();
-        provider.push_v(&filename, 2, 0, b"versioned").await.unwrap();
+        provider
+            .push_v(&filename, 2, 0, b"versioned")
+            .await
+            .unwrap();
 
         provider.purge_all(&filename).await.unwrap();
 
-        assert!(provider.get_uploaded_chunks_v(&filename, 2).await.unwrap().is_empty());
+        assert!(provider
+            .get_uploaded_chunks_v(&filename, 2)
+            .await
+            .unwrap()
+            .is_empty());
         // Legacy listing should also be empty after purge_all.
-        assert!(provider.get_uploaded_chunks(&filename).await.unwrap().is_empty());
+        assert!(provider
+            .get_uploaded_chunks(&filename)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    /// `inventory` finds files in both layouts and skips unrelated entries
+    /// such as the sqlite database sitting in the data directory.
+    #[tokio::test]
+    async fn inventory_lists_both_layouts() {
+        let dir = tempdir().unwrap();
+        let provider = FsProvider::new(dir.path().to_str().unwrap());
+        let legacy = Filename::new("legacy-uuid").with_timestamp(7);
+        let versioned = Filename::new("versioned-uuid");
+
+        provider.push(&legacy, 0, b"legacy").await.unwrap();
+        provider.push_v(&versioned, 3, 0, b"three").await.unwrap();
+        provider.push_v(&versioned, 1, 0, b"one").await.unwrap();
+        provider
+            .write(&Filename::new("sqlite.db"), b"db")
+            .await
+            .unwrap();
+
+        let inventory = provider.inventory().await.unwrap();
+
+        assert_eq!(inventory.len(), 2);
+        assert_eq!(inventory[0].name, "legacy-uuid");
+        assert_eq!(inventory[0].legacy, vec!["7".to_string()]);
+        assert_eq!(inventory[1].name, "versioned-uuid");
+        assert_eq!(inventory[1].versions, vec![1, 3]);
+    }
+
+    /// `chunk_size` and `chunk_size_v` report stored bytes and `None` for
+    /// chunks that were never written.
+    #[tokio::test]
+    async fn chunk_size_reports_bytes_or_none() {
+        let dir = tempdir().unwrap();
+        let provider = FsProvider::new(dir.path().to_str().unwrap());
+        let filename = Filename::new("size-uuid").with_timestamp(7);
+
+        provider.push(&filename, 0, b"legacy").await.unwrap();
+        provider.push_v(&filename, 2, 0, b"abc").await.unwrap();
+
+        assert_eq!(provider.chunk_size(&filename, 0).await.unwrap(), Some(6));
+        assert_eq!(provider.chunk_size(&filename, 1).await.unwrap(), None);
+        assert_eq!(
+            provider.chunk_size_v(&filename, 2, 0).await.unwrap(),
+            Some(3)
+        );
+        assert_eq!(provider.chunk_size_v(&filename, 2, 1).await.unwrap(), None);
+        // v1 has no directory, so it falls back to the legacy chunk.
+        assert_eq!(
+            provider.chunk_size_v(&filename, 1, 0).await.unwrap(),
+            Some(6)
+        );
+    }
+
+    /// Chunks a version borrows are read from the version storing them,
+    /// and only count as uploaded while that version still has them.
+    #[tokio::test]
+    async fn borrowed_chunks_resolve_to_their_source() {
+        use crate::chunk_refs::ChunkRefs;
+        use futures_util::StreamExt;
+
+        let dir = tempdir().unwrap();
+        let provider = FsProvider::new(dir.path().to_str().unwrap());
+        let stored = Filename::new("borrow-uuid");
+
+        provider.push_v(&stored, 1, 0, b"aa").await.unwrap();
+        provider.push_v(&stored, 1, 1, b"bb").await.unwrap();
+        provider.push_v(&stored, 2, 1, b"BB").await.unwrap();
+
+        // v2 borrows chunk 0 and a chunk 2 that v1 never had.
+        let filename = stored
+            .clone()
+            .with_chunk_refs(ChunkRefs::from_iter([(2, 0, 1), (2, 2, 1)]));
+
+        assert_eq!(
+            provider.get_uploaded_chunks_v(&filename, 2).await.unwrap(),
+            vec![0, 1]
+        );
+        assert_eq!(
+            provider.get_uploaded_chunks_v(&stored, 2).await.unwrap(),
+            vec![1]
+        );
+        assert_eq!(provider.pull_v(&filename, 2, 0).await.unwrap(), b"aa");
+        assert_eq!(
+            provider.chunk_size_v(&filename, 2, 0).await.unwrap(),
+            Some(2)
+        );
+        assert!(!provider.exists_v(&filename, 2, 2).await.unwrap());
+        assert!(
+            provider.tar_content_length_v(&filename, 2).await.is_err(),
+            "a chunk missing at its source fails the read"
+        );
+
+        provider.push_v(&stored, 1, 2, b"cc").await.unwrap();
+        let streamed: Vec<u8> = provider
+            .stream_v(&filename, 2, None)
+            .await
+            .unwrap()
+            .stream()
+            .map(|bytes| bytes.unwrap().to_vec())
+            .concat()
+            .await;
+        assert_eq!(streamed, b"aaBBcc");
+
+        provider
+            .copy_version(&filename, 2, &stored, 3)
+            .await
+            .unwrap();
+        assert_eq!(
+            provider.get_uploaded_chunks_v(&stored, 3).await.unwrap(),
+            vec![0, 1, 2]
+        );
+    }
+
+    /// A chunk borrowed from a `v1` still in the legacy flat layout is read
+    /// from there.
+    #[tokio::test]
+    async fn borrowed_chunks_read_from_a_legacy_v1() {
+        use crate::chunk_refs::ChunkRefs;
+
+        let dir = tempdir().unwrap();
+        let provider = FsProvider::new(dir.path().to_str().unwrap());
+        let filename = Filename::new("legacy-borrow-uuid")
+            .with_timestamp(7)
+            .with_chunk_refs(ChunkRefs::from_iter([(2, 0, 1)]));
+
+        provider.push(&filename, 0, b"legacy").await.unwrap();
+        provider.push_v(&filename, 2, 1, b"new").await.unwrap();
+
+        assert_eq!(provider.pull_v(&filename, 2, 0).await.unwrap(), b"legacy");
+        assert_eq!(
+            provider.get_uploaded_chunks_v(&filename, 2).await.unwrap(),
+            vec![0, 1]
+        );
+        assert_eq!(
+            provider.tar_content_length_v(&filename, 2).await.unwrap(),
+            provider
+                .tar_content_length_v(&filename.without_chunk_refs(), 1)
+                .await
+                .unwrap()
+                + 512
+                + 3
+                + tar::tar_padding_len(3) as u64
+        );
     }
 }
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use actix_web::web::Bytes;
 use async_trait::async_trait;
 use error::{AppResult, Error};
@@ -7,6 +9,7 @@ use s3::error::S3Error;
 use crate::{
     contract::FsProviderContract,
     filename::{Filename, IntoFilename},
+    inventory::{Inventory, StoredFile},
     streamer::Streamer,
     tar,
 };
@@ -40,13 +43,12 @@ impl S3Provider {
         )
         .expect("Invalid S3 credentials. Check S3_ACCESS_KEY and S3_SECRET_KEY.");
 
-        let mut bucket = s3::Bucket::new(&config.bucket, region, credentials)
-            .unwrap_or_else(|e| {
-                panic!(
-                    "Failed to create S3 bucket handle for '{}': {}",
-                    &config.bucket, e
-                )
-            });
+        let mut bucket = s3::Bucket::new(&config.bucket, region, credentials).unwrap_or_else(|e| {
+            panic!(
+                "Failed to create S3 bucket handle for '{}': {}",
+                &config.bucket, e
+            )
+        });
 
         if config.path_style {
             bucket.set_path_style();
@@ -85,7 +87,11 @@ impl S3Provider {
     /// Full key of one versioned chunk:
     /// `{prefix}{inner_name}/v{N}/{chunk:06}.chunk`.
     fn versioned_chunk_key(&self, filename: &Filename, version: i32, chunk: i64) -> String {
-        format!("{}{:06}.chunk", self.version_prefix(filename, version), chunk)
+        format!(
+            "{}{:06}.chunk",
+            self.version_prefix(filename, version),
+            chunk
+        )
     }
 
     /// Prefix covering every version and legacy-versioned key for a file:
@@ -104,11 +110,60 @@ impl S3Provider {
             .next()
             .and_then(|s| s.parse::<i64>().ok())
             .ok_or_else(|| {
-                Error::InternalError(format!(
-                    "Failed to parse chunk number from S3 key: {}",
-                    key
-                ))
+                Error::InternalError(format!("Failed to parse chunk number from S3 key: {}", key))
+            })
+    }
+
+    /// Server-side copy of every `(src, dst)` key pair with CopyObject, so
+    /// the chunk bytes never leave the bucket.
+    async fn copy_objects(&self, pairs: Vec<(String, String)>) -> AppResult<()> {
+        if pairs.is_empty() {
+            return Ok(());
+        }
+
+        // Defensive: refuse to copy a chunk larger than the CopyObject
+        // single-op limit. MAX_CHUNK_SIZE_BYTES is statically under this
+        // at the library level, but data written by an older server —
+        // or manually prepared fixtures — could in theory exceed it.
+        // Multipart-copy isn't wired up, so surface a clear error.
+        const COPY_OBJECT_MAX_BYTES: i64 = 5 * 1024 * 1024 * 1024;
+        for (key, _) in &pairs {
+            let (head, _) = self.bucket.head_object(key).await.map_err(|e| {
+                Error::StorageError(format!("S3 head_object failed for '{}': {}", key, e))
+            })?;
+            if let Some(size) = head.content_length {
+                if size > COPY_OBJECT_MAX_BYTES {
+                    return Err(Error::InternalError(format!(
+                        "S3 copy source chunk '{}' is {} bytes, \
+                         exceeding the CopyObject single-op limit of 5 GiB",
+                        key, size
+                    )));
+                }
+            }
+        }
+
+        let bucket = self.bucket.clone();
+        futures::stream::iter(pairs)
+            .map(|(src_key, dst_key)| {
+                let bucket = bucket.clone();
+                async move {
+                    bucket
+                        .copy_object_internal(&src_key, &dst_key)
+                        .await
+                        .map_err(|e| {
+                            Error::StorageError(format!(
+                                "S3 copy_object failed for '{}' -> '{}': {}",
+                                src_key, dst_key, e
+                            ))
+                        })?;
+                    Ok::<(), Error>(())
+                }
             })
+            .buffer_unordered(8)
+            .try_collect::<Vec<()>>()
+            .await?;
+
+        Ok(())
     }
 
     /// Parse chunk index from a versioned S3 key ending in `{idx:06}.chunk`.
@@ -150,6 +205,83 @@ impl S3Provider {
         s.contains("404") || s.contains("NoSuchKey") || s.contains("Not Found")
     }
 
+    /// Chunk indices stored under a version's own prefix, ascending.
+    async fn list_versioned_chunks(
+        &self,
+        filename: &Filename,
+        version: i32,
+    ) -> AppResult<Vec<i64>> {
+        let prefix = self.version_prefix(filename, version);
+        let objects = self.list_objects(&prefix).await?;
+
+        let mut chunks = Vec::with_capacity(objects.len());
+        for o in objects {
+            if o.key.ends_with(".chunk") {
+                chunks.push(Self::parse_versioned_chunk_index(&o.key)?);
+            }
+        }
+        chunks.sort();
+        Ok(chunks)
+    }
+
+    /// Stored chunks of a version plus every chunk it borrows, whether or
+    /// not its source still has it. Reads go through this so a missing
+    /// source chunk fails the read instead of silently leaving a gap.
+    async fn version_chunks(&self, filename: &Filename, version: i32) -> AppResult<Vec<i64>> {
+        let mut chunks = self.list_versioned_chunks(filename, version).await?;
+        chunks.extend(filename.chunk_refs().borrowed(version).map(|(c, _)| c));
+        chunks.sort();
+        chunks.dedup();
+        Ok(chunks)
+    }
+
+    /// Borrowed chunks of a version whose source version stores them, one
+    /// listing per source version.
+    async fn present_borrowed_chunks(
+        &self,
+        filename: &Filename,
+        version: i32,
+    ) -> AppResult<Vec<i64>> {
+        let mut by_source: BTreeMap<i32, Vec<i64>> = BTreeMap::new();
+        for (chunk, source) in filename.chunk_refs().borrowed(version) {
+            by_source.entry(source).or_default().push(chunk);
+        }
+
+        let stored = filename.without_chunk_refs();
+        let mut present = Vec::new();
+        for (source, chunks) in by_source {
+            let available = self.get_uploaded_chunks_v(&stored, source).await?;
+            present.extend(chunks.into_iter().filter(|c| available.contains(c)));
+        }
+        Ok(present)
+    }
+
+    /// Key holding the bytes of each chunk of a version, following the
+    /// chunk references of the filename without touching the bucket,
+    /// except to check once for a legacy `v1` when chunks are borrowed
+    /// from it.
+    async fn chunk_keys(
+        &self,
+        filename: &Filename,
+        version: i32,
+        chunks: Vec<i64>,
+    ) -> Vec<(i64, String)> {
+        let refs = filename.chunk_refs();
+        let legacy = refs.borrowed(version).any(|(_, source)| source == 1)
+            && self.should_use_legacy(filename, 1).await;
+
+        chunks
+            .into_iter()
+            .map(|chunk| {
+                let key = match refs.source(version, chunk) {
+                    1 if legacy => self.object_key(&filename.clone().with_chunk(chunk)),
+                    source => self.versioned_chunk_key(filename, source, chunk),
+                };
+                (chunk, key)
+            })
+            .collect()
+    }
+
     /// True when `version == 1` and the versioned directory is empty. Used
     /// by every read-side `_v` method to transparently fall back to the
     /// legacy flat layout for pre-migration files.
@@ -208,6 +340,15 @@ impl FsProviderContract for S3Provider {
         head_exists(&self.bucket, &key).await
     }
 
+    async fn chunk_size<T: IntoFilename>(
+        &self,
+        filename: &T,
+        chunk: i64,
+    ) -> AppResult<Option<u64>> {
+        let key = self.object_key(&filename.filename()?.with_chunk(chunk));
+        head_size(&self.bucket, &key).await
+    }
+
     async fn push<T: IntoFilename>(&self, filename: &T, chunk: i64, data: &[u8]) -> AppResult<()> {
         let key = self.object_key(&filename.filename()?.with_chunk(chunk));
 
@@ -284,7 +425,10 @@ impl FsProviderContract for S3Provider {
             })
             .collect();
 
-        Ok(Streamer::new(tar_entry_stream(self.bucket.clone(), entries)))
+        Ok(Streamer::new(tar_entry_stream(
+            self.bucket.clone(),
+            entries,
+        )))
     }
 
     async fn tar_content_length<T: IntoFilename>(&self, filename: &T) -> AppResult<u64> {
@@ -309,9 +453,10 @@ impl FsProviderContract for S3Provider {
         data: &[u8],
     ) -> AppResult<()> {
         let key = self.versioned_chunk_key(&filename.filename()?, version, chunk);
-        self.bucket.put_object(&key, data).await.map_err(|e| {
-            Error::StorageError(format!("S3 push_v failed for '{}': {}", key, e))
-        })?;
+        self.bucket
+            .put_object(&key, data)
+            .await
+            .map_err(|e| Error::StorageError(format!("S3 push_v failed for '{}': {}", key, e)))?;
         Ok(())
     }
 
@@ -322,6 +467,13 @@ impl FsProviderContract for S3Provider {
         chunk: i64,
     ) -> AppResult<Vec<u8>> {
         let filename = filename.filename()?;
+        let source = filename.chunk_refs().source(version, chunk);
+        if source != version {
+            return self
+                .pull_v(&filename.without_chunk_refs(), source, chunk)
+                .await;
+        }
+
         if self.should_use_legacy(&filename, version).await {
             return self.pull(&filename, chunk).await;
         }
@@ -337,6 +489,13 @@ impl FsProviderContract for S3Provider {
         chunk: i64,
     ) -> AppResult<bool> {
         let filename = filename.filename()?;
+        let source = filename.chunk_refs().source(version, chunk);
+        if source != version {
+            return self
+                .exists_v(&filename.without_chunk_refs(), source, chunk)
+                .await;
+        }
+
         if self.should_use_legacy(&filename, version).await {
             return self.exists(&filename, chunk).await;
         }
@@ -345,6 +504,28 @@ impl FsProviderContract for S3Provider {
         head_exists(&self.bucket, &key).await
     }
 
+    async fn chunk_size_v<T: IntoFilename>(
+        &self,
+        filename: &T,
+        version: i32,
+        chunk: i64,
+    ) -> AppResult<Option<u64>> {
+        let filename = filename.filename()?;
+        let source = filename.chunk_refs().source(version, chunk);
+        if source != version {
+            return self
+                .chunk_size_v(&filename.without_chunk_refs(), source, chunk)
+                .await;
+        }
+
+        if self.should_use_legacy(&filename, version).await {
+            return self.chunk_size(&filename, chunk).await;
+        }
+
+        let key = self.versioned_chunk_key(&filename, version, chunk);
+        head_size(&self.bucket, &key).await
+    }
+
     async fn get_uploaded_chunks_v<T: IntoFilename>(
         &self,
         filename: &T,
@@ -355,16 +536,10 @@ impl FsProviderContract for S3Provider {
             return self.get_uploaded_chunks(&filename).await;
         }
 
-        let prefix = self.version_prefix(&filename, version);
-        let objects = self.list_objects(&prefix).await?;
-
-        let mut chunks = Vec::with_capacity(objects.len());
-        for o in objects {
-            if o.key.ends_with(".chunk") {
-                chunks.push(Self::parse_versioned_chunk_index(&o.key)?);
-            }
-        }
+        let mut chunks = self.list_versioned_chunks(&filename, version).await?;
+        chunks.extend(self.present_borrowed_chunks(&filename, version).await?);
         chunks.sort();
+        chunks.dedup();
         Ok(chunks)
     }
 
@@ -381,12 +556,14 @@ impl FsProviderContract for S3Provider {
 
         let chunk_indices: Vec<i64> = match chunk {
             Some(c) => vec![c],
-            None => self.get_uploaded_chunks_v(&filename, version).await?,
+            None => self.version_chunks(&filename, version).await?,
         };
 
-        let mut keys: Vec<String> = chunk_indices
+        let mut keys: Vec<String> = self
+            .chunk_keys(&filename, version, chunk_indices)
+            .await
             .into_iter()
-            .map(|c| self.versioned_chunk_key(&filename, version, c))
+            .map(|(_, key)| key)
             .collect();
         keys.reverse();
 
@@ -403,17 +580,18 @@ impl FsProviderContract for S3Provider {
             return self.stream_tar(&filename).await;
         }
 
-        let chunks = self.get_uploaded_chunks_v(&filename, version).await?;
-        let entries: Vec<(String, String)> = chunks
-            .iter()
-            .map(|idx| {
-                let key = self.versioned_chunk_key(&filename, version, *idx);
-                let name = format!("{:06}.enc", idx);
-                (name, key)
-            })
+        let chunks = self.version_chunks(&filename, version).await?;
+        let entries: Vec<(String, String)> = self
+            .chunk_keys(&filename, version, chunks)
+            .await
+            .into_iter()
+            .map(|(idx, key)| (format!("{:06}.enc", idx), key))
             .collect();
 
-        Ok(Streamer::new(tar_entry_stream(self.bucket.clone(), entries)))
+        Ok(Streamer::new(tar_entry_stream(
+            self.bucket.clone(),
+            entries,
+        )))
     }
 
     async fn tar_content_length_v<T: IntoFilename>(
@@ -426,20 +604,18 @@ impl FsProviderContract for S3Provider {
             return self.tar_content_length(&filename).await;
         }
 
-        let chunks = self.get_uploaded_chunks_v(&filename, version).await?;
-        let keys: Vec<String> = chunks
-            .iter()
-            .map(|idx| self.versioned_chunk_key(&filename, version, *idx))
+        let chunks = self.version_chunks(&filename, version).await?;
+        let keys: Vec<String> = self
+            .chunk_keys(&filename, version, chunks)
+            .await
+            .into_iter()
+            .map(|(_, key)| key)
             .collect();
 
         tar_total_length(&self.bucket, keys).await
     }
 
-    async fn purge_version<T: IntoFilename>(
-        &self,
-        filename: &T,
-        version: i32,
-    ) -> AppResult<()> {
+    async fn purge_version<T: IntoFilename>(&self, filename: &T, version: i32) -> AppResult<()> {
         let prefix = self.version_prefix(&filename.filename()?, version);
         let objects = self.list_objects(&prefix).await?;
 
@@ -478,73 +654,42 @@ impl FsProviderContract for S3Provider {
             pairs.sort_by_key(|(_, i)| *i);
             pairs.into_iter().unzip()
         } else {
-            let prefix = self.version_prefix(&src, src_version);
-            let mut pairs: Vec<(String, i64)> = self
-                .list_objects(&prefix)
-                .await?
+            // Borrowed chunks are copied from their source, the destination
+            // never shares chunks with the source version.
+            let chunks = self.version_chunks(&src, src_version).await?;
+            self.chunk_keys(&src, src_version, chunks)
+                .await
                 .into_iter()
-                .filter(|o| o.key.ends_with(".chunk"))
-                .map(|o| {
-                    let idx = Self::parse_versioned_chunk_index(&o.key)?;
-                    Ok::<_, Error>((o.key, idx))
-                })
-                .collect::<AppResult<Vec<_>>>()?;
-            pairs.sort_by_key(|(_, i)| *i);
-            pairs.into_iter().unzip()
+                .map(|(idx, key)| (key, idx))
+                .unzip()
         };
 
-        if src_keys.is_empty() {
-            return Ok(());
-        }
-
-        // Defensive: refuse to copy a chunk larger than the CopyObject
-        // single-op limit. MAX_CHUNK_SIZE_BYTES is statically under this
-        // at the library level, but data written by an older server —
-        // or manually prepared fixtures — could in theory exceed it.
-        // Multipart-copy isn't wired up, so surface a clear error.
-        const COPY_OBJECT_MAX_BYTES: i64 = 5 * 1024 * 1024 * 1024;
-        for key in &src_keys {
-            let (head, _) = self.bucket.head_object(key).await.map_err(|e| {
-                Error::StorageError(format!("S3 head_object failed for '{}': {}", key, e))
-            })?;
-            if let Some(size) = head.content_length {
-                if size > COPY_OBJECT_MAX_BYTES {
-                    return Err(Error::InternalError(format!(
-                        "S3 copy_version source chunk '{}' is {} bytes, \
-                         exceeding the CopyObject single-op limit of 5 GiB",
-                        key, size
-                    )));
-                }
-            }
-        }
-
         let dst_keys: Vec<String> = src_indices
             .iter()
             .map(|idx| self.versioned_chunk_key(&dst, dst_version, *idx))
             .collect();
 
-        let bucket = self.bucket.clone();
-        futures::stream::iter(src_keys.into_iter().zip(dst_keys))
-            .map(|(src_key, dst_key)| {
-                let bucket = bucket.clone();
-                async move {
-                    bucket
-                        .copy_object_internal(&src_key, &dst_key)
-                        .await
-                        .map_err(|e| {
-                            Error::StorageError(format!(
-                                "S3 copy_object failed for '{}' -> '{}': {}",
-                                src_key, dst_key, e
-                            ))
-                        })?;
-                    Ok::<(), Error>(())
-                }
+        self.copy_objects(src_keys.into_iter().zip(dst_keys).collect())
+            .await
+    }
+
+    async fn copy<S: IntoFilename, D: IntoFilename>(&self, src: &S, dst: &D) -> AppResult<()> {
+        let src = src.filename()?;
+        let dst = dst.filename()?;
+
+        let pairs = self
+            .get_uploaded_chunks(&src)
+            .await?
+            .into_iter()
+            .map(|idx| {
+                (
+                    self.object_key(&src.clone().with_chunk(idx)),
+                    self.object_key(&dst.clone().with_chunk(idx)),
+                )
             })
-            .buffer_unordered(8)
-            .try_collect::<Vec<()>>()
-            .await?;
+            .collect();
 
-        Ok(())
+        self.copy_objects(pairs).await
     }
 
     async fn purge_all<T: IntoFilename>(&self, filename: &T) -> AppResult<()> {
@@ -561,6 +706,18 @@ impl FsProviderContract for S3Provider {
 
         self.purge(&filename).await
     }
+
+    async fn inventory(&self) -> AppResult<Vec<StoredFile>> {
+        let mut inventory = Inventory::default();
+
+        for object in self.list_objects(&self.prefix).await? {
+            if let Some(key) = object.key.strip_prefix(self.prefix.as_str()) {
+                inventory.add(key);
+            }
+        }
+
+        Ok(inventory.into_files())
+    }
 }
 
 /// Build a lazy stream that fetches each S3 key one at a time and emits the
@@ -570,23 +727,18 @@ fn chunk_key_stream(
     bucket: s3::Bucket,
     keys: Vec<String>,
 ) -> impl futures_util::Stream<Item = AppResult<Bytes>> {
-    futures_util::stream::unfold(
-        (bucket, keys),
-        |(bucket, mut keys)| async move {
-            let key = keys.pop()?;
-            match bucket.get_object(&key).await {
-                Ok(response) => Some((Ok(Bytes::from(response.to_vec())), (bucket, keys))),
-                Err(e) => {
-                    let err = Error::StorageError(format!(
-                        "S3 stream read failed for '{}': {}",
-                        key, e
-                    ));
-                    log::error!("{}", err);
-                    Some((Err(err), (bucket, keys)))
-                }
+    futures_util::stream::unfold((bucket, keys), |(bucket, mut keys)| async move {
+        let key = keys.pop()?;
+        match bucket.get_object(&key).await {
+            Ok(response) => Some((Ok(Bytes::from(response.to_vec())), (bucket, keys))),
+            Err(e) => {
+                let err =
+                    Error::StorageError(format!("S3 stream read failed for '{}': {}", key, e));
+                log::error!("{}", err);
+                Some((Err(err), (bucket, keys)))
             }
-        },
-    )
+        }
+    })
 }
 
 /// Build a lazy tar stream over a list of (entry_name, s3_key) pairs. Each
@@ -749,6 +901,25 @@ async fn head_exists(bucket: &s3::Bucket, key: &str) -> AppResult<bool> {
     }
 }
 
+/// Content length of an object, `None` when the key does not exist.
+async fn head_size(bucket: &s3::Bucket, key: &str) -> AppResult<Option<u64>> {
+    match bucket.head_object(key).await {
+        Ok((head, status)) if (200..300).contains(&status) => {
+            Ok(Some(head.content_length.unwrap_or_default().max(0) as u64))
+        }
+        Ok((_, 404)) => Ok(None),
+        Ok((_, status)) => Err(Error::StorageError(format!(
+            "S3 head_object for '{}' returned unexpected status {}",
+            key, status
+        ))),
+        Err(e) if S3Provider::is_not_found(&e) => Ok(None),
+        Err(e) => Err(Error::StorageError(format!(
+            "S3 head_object failed for '{}': {}",
+            key, e
+        ))),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -756,10 +927,8 @@ mod tests {
     #[test]
     fn parse_legacy_chunk_index() {
         assert_eq!(
-            S3Provider::parse_chunk_index(
-                "1712345600-550e8400-e29b-41d4-a716-446655440000.part.0"
-            )
-            .unwrap(),
+            S3Provider::parse_chunk_index("1712345600-550e8400-e29b-41d4-a716-446655440000.part.0")
+                .unwrap(),
             0
         );
         assert_eq!(
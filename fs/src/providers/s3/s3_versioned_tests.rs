@@ -12,6 +12,7 @@
 //! bucket so runs don't collide, and the `TestScope` RAII guard tears the
 //! prefix down on drop.
 
+use crate::chunk_refs::ChunkRefs;
 use crate::filename::Filename;
 use crate::providers::s3::S3Provider;
 use crate::{contract::FsProviderContract, MAX_CHUNK_SIZE_BYTES};
@@ -179,7 +180,10 @@ async fn s3_copy_version_in_place() {
     s.p().push_v(&filename, 3, 0, b"a").await.unwrap();
     s.p().push_v(&filename, 3, 1, b"b").await.unwrap();
 
-    s.p().copy_version(&filename, 3, &filename, 4).await.unwrap();
+    s.p()
+        .copy_version(&filename, 3, &filename, 4)
+        .await
+        .unwrap();
 
     assert_eq!(
         s.p().get_uploaded_chunks_v(&filename, 3).await.unwrap(),
@@ -228,7 +232,10 @@ async fn s3_copy_version_from_legacy_source() {
     s.p().push(&filename, 0, b"x").await.unwrap();
     s.p().push(&filename, 1, b"y").await.unwrap();
 
-    s.p().copy_version(&filename, 1, &filename, 2).await.unwrap();
+    s.p()
+        .copy_version(&filename, 1, &filename, 2)
+        .await
+        .unwrap();
 
     assert_eq!(
         s.p().get_uploaded_chunks_v(&filename, 2).await.unwrap(),
@@ -240,6 +247,42 @@ async fn s3_copy_version_from_legacy_source() {
     s.clean().await;
 }
 
+#[tokio::test]
+async fn s3_borrowed_chunks_resolve_to_their_source() {
+    let s = scope().await;
+    let stored = fname();
+
+    s.p().push_v(&stored, 1, 0, b"aa").await.unwrap();
+    s.p().push_v(&stored, 1, 1, b"bb").await.unwrap();
+    s.p().push_v(&stored, 2, 1, b"BB").await.unwrap();
+
+    // v2 borrows chunk 0 and a chunk 2 that v1 never had.
+    let filename = stored
+        .clone()
+        .with_chunk_refs(ChunkRefs::from_iter([(2, 0, 1), (2, 2, 1)]));
+
+    assert_eq!(
+        s.p().get_uploaded_chunks_v(&filename, 2).await.unwrap(),
+        vec![0, 1]
+    );
+    assert_eq!(s.p().pull_v(&filename, 2, 0).await.unwrap(), b"aa");
+    assert!(!s.p().exists_v(&filename, 2, 2).await.unwrap());
+
+    assert!(
+        s.p().copy_version(&filename, 2, &stored, 3).await.is_err(),
+        "a chunk missing at its source fails the copy"
+    );
+    s.p().push_v(&stored, 1, 2, b"cc").await.unwrap();
+    s.p().copy_version(&filename, 2, &stored, 3).await.unwrap();
+    assert_eq!(
+        s.p().get_uploaded_chunks_v(&stored, 3).await.unwrap(),
+        vec![0, 1, 2]
+    );
+    assert_eq!(s.p().pull_v(&stored, 3, 0).await.unwrap(), b"aa");
+
+    s.clean().await;
+}
+
 #[tokio::test]
 async fn s3_purge_version_isolated() {
     let s = scope().await;
@@ -321,7 +364,11 @@ async fn s3_purge_version_batch_gt_1000_chunks() {
     s.p().purge_version(&filename, 7).await.unwrap();
 
     let after = s.p().get_uploaded_chunks_v(&filename, 7).await.unwrap();
-    assert!(after.is_empty(), "{} chunks survived purge_version", after.len());
+    assert!(
+        after.is_empty(),
+        "{} chunks survived purge_version",
+        after.len()
+    );
 
     s.clean().await;
 }
@@ -145,9 +145,7 @@ mod tests {
         let header = tar_header("test.enc", 1024);
 
         // Verify checksum: parse stored checksum, recompute with spaces, compare.
-        let stored = std::str::from_utf8(&header[148..154])
-            .unwrap()
-            .trim();
+        let stored = std::str::from_utf8(&header[148..154]).unwrap().trim();
         let stored_checksum = u32::from_str_radix(stored, 8).unwrap();
 
         let mut check_header = header;
@@ -252,9 +250,7 @@ mod tests {
         let body = vec![0xABu8; 512];
         let archive = one_entry_tar("aligned.enc", &body);
         match parse_next_entry(&archive) {
-            TarStep::Entry {
-                data, consumed, ..
-            } => {
+            TarStep::Entry { data, consumed, .. } => {
                 assert_eq!(data.len(), 512);
                 assert_eq!(consumed, 1024);
             }
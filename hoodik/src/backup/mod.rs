@@ -0,0 +1,420 @@
+//! `hoodik backup` and `hoodik restore`.
+//!
+//! A backup is a directory:
+//!
+//! * `manifest.json` — format, versions and row counts, written last so a
+//!   backup without one never finished
+//! * `database/{table}.jsonl` — every table, one JSON object per row
+//! * `settings.json` — the instance settings from the data directory
+//! * `chunks/` — every stored chunk, in the local storage layout
+//!
+//! The database is always dumped in full, it is small next to the chunks.
+//! Chunks are only copied for files created or uploaded since the previous
+//! backup into the same directory, and chunks of deleted files are pruned.
+
+mod table;
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use chrono::Utc;
+use config::Config;
+use context::Context;
+use entity::{
+    files, AccessMode, ColumnTrait, ConnectionTrait, DbBackend, EntityName, EntityTrait,
+    IsolationLevel, QueryFilter, QuerySelect, TransactionTrait, Uuid,
+};
+use error::{AppResult, Error};
+use fs::prelude::*;
+use futures::{stream, StreamExt};
+use migration::{Migrator, MigratorTrait};
+use serde::{Deserialize, Serialize};
+
+use crate::migrate::{copy_file, Migrated};
+
+/// Version of the backup layout, bumped on incompatible changes.
+pub const FORMAT: u32 = 1;
+
+/// How many files have their chunks copied at the same time.
+const CONCURRENCY: usize = 8;
+
+/// Tables left out of the backup on purpose: short-lived state that is
+/// worthless once the instance restarts elsewhere, and the server's signing
+/// secrets, which are stored in the clear. A restored instance signs with a
+/// key of its own, so everyone logs in again after a restore and unlocks
+/// password-protected links again.
+pub const EXCLUDED: &[&str] = &[
+    "webauthn_challenges",
+    "rate_limit_hits",
+    "jwt_signing_keys",
+    "link_config",
+];
+
+/// Every table, parents before the tables referencing them, so a restore
+/// can insert them in this order with foreign keys enforced.
+macro_rules! for_each_table {
+    ($entity:ident => $body:block) => {
+        for_each_table!(@each $entity $body:
+            users,
+            webauthn_credentials,
+            tfa_recovery_codes,
+            opaque_config,
+            sessions,
+            user_actions,
+            invitations,
+            tokens,
+            files,
+            user_files,
+            file_tokens,
+            personal_access_tokens,
+            links,
+            link_files,
+            file_versions,
+            file_chunk_refs,
+            copy_jobs,
+            copy_job_files,
+            version_policies,
+            key_transitions,
+            share_events,
+            share_groups,
+            share_group_members,
+            opaque_login_sessions,
+            oidc_logins,
+            migration_rewrap_staging,
+            used_nonces,
+            job_runs
+        )
+    };
+    (@each $entity:ident $body:block: $($table:ident),*) => {
+        $({
+            type $entity = entity::$table::Entity;
+            $body
+        })*
+    };
+}
+
+/// Contents of `manifest.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub format: u32,
+    /// Version of hoodik that wrote the backup.
+    pub version: String,
+    /// Database migrations applied when the backup was taken. A restore
+    /// refuses backups with migrations it does not know.
+    pub migrations: Vec<String>,
+    pub started_at: i64,
+    pub finished_at: i64,
+    /// Start of the previous backup when only files changed since then had
+    /// their chunks copied, `None` for a full copy.
+    pub since: Option<i64>,
+    /// Rows dumped per table.
+    pub tables: BTreeMap<String, u64>,
+}
+
+/// Outcome of [`backup`] and [`restore`].
+#[derive(Debug, Clone)]
+pub struct Summary {
+    pub manifest: Manifest,
+    /// Rows dumped or restored, over all tables.
+    pub rows: u64,
+    /// Chunks copied to or from the backup.
+    pub copied: Migrated,
+    /// Stored files removed from the backup because their file is gone.
+    pub pruned: u64,
+}
+
+/// Write a consistent copy of the instance into `path`.
+///
+/// The database is read inside a single transaction, repeatable read on
+/// Postgres, so every table is dumped from the same snapshot. Unless `full`
+/// is set, only the chunks of files created or finished since the previous
+/// backup in `path` are copied; running it nightly into the same directory
+/// keeps a complete, current backup without copying everything each time.
+pub async fn backup(context: &Context, path: &Path, full: bool) -> AppResult<Summary> {
+    let started_at = Utc::now().timestamp();
+    tokio::fs::create_dir_all(path).await?;
+
+    let previous = read_manifest(path).await?;
+    let since = previous
+        .filter(|previous| !full && previous.format == FORMAT)
+        .map(|previous| previous.started_at);
+
+    match since {
+        Some(since) => println!(
+            "Backing up into {}, files changed since {since}...",
+            path.display()
+        ),
+        None => println!("Backing up into {}, all files...", path.display()),
+    }
+
+    let staging = path.join("database.new");
+    if tokio::fs::try_exists(&staging).await? {
+        tokio::fs::remove_dir_all(&staging).await?;
+    }
+    tokio::fs::create_dir_all(&staging).await?;
+
+    let txn = match context.db.get_database_backend() {
+        DbBackend::Postgres => {
+            context
+                .db
+                .begin_with_config(
+                    Some(IsolationLevel::RepeatableRead),
+                    Some(AccessMode::ReadOnly),
+                )
+                .await?
+        }
+        _ => context.db.begin().await?,
+    };
+
+    let mut tables = BTreeMap::new();
+    let mut rows = 0;
+    for_each_table!(E => {
+        let dumped = table::dump::<E, _>(&txn, &staging).await?;
+        tables.insert(E::default().table_name().to_string(), dumped);
+        rows += dumped;
+    });
+
+    let migrations = Migrator::get_applied_migrations(&txn)
+        .await?
+        .iter()
+        .map(|migration| migration.name().to_string())
+        .collect();
+
+    let stored: Vec<(Uuid, i64, Option<i64>)> = files::Entity::find()
+        .select_only()
+        .column(files::Column::Id)
+        .column(files::Column::CreatedAt)
+        .column(files::Column::FinishedUploadAt)
+        .filter(files::Column::Mime.ne("dir"))
+        .into_tuple()
+        .all(&txn)
+        .await?;
+
+    txn.commit().await?;
+
+    println!("Dumped {rows} rows from {} tables.", tables.len());
+
+    let existing: HashSet<String> = stored.iter().map(|(id, ..)| id.to_string()).collect();
+    let changed: HashSet<String> = stored
+        .iter()
+        .filter(|(_, created_at, finished_upload_at)| match since {
+            Some(since) => *created_at >= since || finished_upload_at.is_some_and(|f| f >= since),
+            None => true,
+        })
+        .map(|(id, ..)| id.to_string())
+        .collect();
+
+    let source = Fs::new(&context.config);
+    let chunks_config = chunks_config(&context.config, path);
+    tokio::fs::create_dir_all(&chunks_config.app.data_dir).await?;
+    let destination = Fs::new(&chunks_config);
+
+    let files: Vec<StoredFile> = source
+        .inventory()
+        .await?
+        .into_iter()
+        .filter(|file| changed.contains(&file.name))
+        .collect();
+    let copied = copy_files(&source, &destination, &files).await?;
+
+    let mut pruned = 0;
+    for file in destination.inventory().await? {
+        if existing.contains(&file.name) {
+            continue;
+        }
+
+        for filename in file.filenames() {
+            destination.purge_all(&filename).await?;
+        }
+        pruned += 1;
+    }
+
+    let database = path.join("database");
+    if tokio::fs::try_exists(&database).await? {
+        tokio::fs::remove_dir_all(&database).await?;
+    }
+    tokio::fs::rename(&staging, &database).await?;
+
+    match Fs::new(&context.config)
+        .local()
+        .read(&settings::Settings::filename())
+        .await
+    {
+        Ok(data) => tokio::fs::write(path.join("settings.json"), data).await?,
+        Err(e) => log::warn!("No settings backed up: {e}"),
+    }
+
+    let manifest = Manifest {
+        format: FORMAT,
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        migrations,
+        started_at,
+        finished_at: Utc::now().timestamp(),
+        since,
+        tables,
+    };
+    write_manifest(path, &manifest).await?;
+
+    println!(
+        "Done: {} files, {} chunks copied, {} chunks already in the backup, {} files pruned.",
+        copied.files, copied.chunks, copied.skipped, pruned
+    );
+
+    Ok(Summary {
+        manifest,
+        rows,
+        copied,
+        pruned,
+    })
+}
+
+/// Rebuild an instance from the backup in `path`.
+///
+/// The database and the storage provider must both be empty; restore
+/// never merges a backup into an instance already in use. Rows are
+/// inserted in one transaction, so a failed restore leaves the database
+/// empty again.
+pub async fn restore(context: &Context, path: &Path) -> AppResult<Summary> {
+    let manifest = read_manifest(path).await?.ok_or_else(|| {
+        Error::InternalError(format!(
+            "No finished backup in {}, manifest.json is missing",
+            path.display()
+        ))
+    })?;
+
+    if manifest.format != FORMAT {
+        return Err(Error::InternalError(format!(
+            "Backup format {} is not supported, expected {}",
+            manifest.format, FORMAT
+        )));
+    }
+
+    let known: HashSet<String> = Migrator::migrations()
+        .iter()
+        .map(|migration| migration.name().to_string())
+        .collect();
+    if let Some(unknown) = manifest.migrations.iter().find(|m| !known.contains(*m)) {
+        return Err(Error::InternalError(format!(
+            "Backup was written by hoodik {} and has migration {} this version does not know",
+            manifest.version, unknown
+        )));
+    }
+
+    let destination = Fs::new(&context.config);
+    if !destination.inventory().await?.is_empty() {
+        return Err(Error::InternalError(
+            "Storage provider already holds files, restore needs empty storage".to_string(),
+        ));
+    }
+
+    println!("Restoring backup from {}...", path.display());
+
+    let database = path.join("database");
+    let txn = context.db.begin().await?;
+
+    for_each_table!(E => {
+        table::ensure_empty::<E, _>(&txn).await?;
+    });
+
+    let mut rows = 0;
+    for_each_table!(E => {
+        let entity = E::default();
+        let name = entity.table_name();
+        let restored = table::restore::<E, _>(&txn, &database).await?;
+        let expected = manifest.tables.get(name).copied().unwrap_or_default();
+
+        if restored != expected {
+            return Err(Error::InternalError(format!(
+                "Backup of table '{name}' has {restored} rows, the manifest lists {expected}"
+            )));
+        }
+        rows += restored;
+    });
+
+    txn.commit().await?;
+
+    println!("Restored {rows} rows.");
+
+    let chunks_config = chunks_config(&context.config, path);
+    let source = Fs::new(&chunks_config);
+    let files = source.inventory().await?;
+    let copied = copy_files(&source, &destination, &files).await?;
+
+    match tokio::fs::read(path.join("settings.json")).await {
+        Ok(data) => {
+            destination
+                .local()
+                .write(&settings::Settings::filename(), &data)
+                .await?
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(Error::from(e)),
+    }
+
+    println!(
+        "Done: {} files, {} chunks restored.",
+        copied.files, copied.chunks
+    );
+
+    Ok(Summary {
+        manifest,
+        rows,
+        copied,
+        pruned: 0,
+    })
+}
+
+/// Config that points [`Fs`] at the `chunks/` directory of a backup.
+fn chunks_config(base: &Config, path: &Path) -> Config {
+    let mut config = base.clone();
+    config.app.storage_provider = "local".to_string();
+    config.app.data_dir = path.join("chunks").to_string_lossy().to_string();
+
+    config
+}
+
+async fn copy_files(
+    source: &Fs<'_>,
+    destination: &Fs<'_>,
+    files: &[StoredFile],
+) -> AppResult<Migrated> {
+    let mut copies = stream::iter(files)
+        .map(|file| copy_file(source, destination, file))
+        .buffer_unordered(CONCURRENCY);
+
+    let mut total = Migrated::default();
+    while let Some(copied) = copies.next().await {
+        let copied = copied?;
+
+        total.files += copied.files;
+        total.chunks += copied.chunks;
+        total.bytes += copied.bytes;
+        total.skipped += copied.skipped;
+    }
+
+    Ok(total)
+}
+
+fn manifest_path(path: &Path) -> PathBuf {
+    path.join("manifest.json")
+}
+
+async fn read_manifest(path: &Path) -> AppResult<Option<Manifest>> {
+    match tokio::fs::read(manifest_path(path)).await {
+        Ok(data) => Ok(Some(serde_json::from_slice(&data)?)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(Error::from(e)),
+    }
+}
+
+/// Write the manifest next to its final place and move it there, a crash
+/// never leaves a half written manifest behind.
+async fn write_manifest(path: &Path, manifest: &Manifest) -> AppResult<()> {
+    let temporary = path.join("manifest.json.tmp");
+    tokio::fs::write(&temporary, serde_json::to_vec_pretty(manifest)?).await?;
+    tokio::fs::rename(&temporary, manifest_path(path)).await?;
+
+    Ok(())
+}
@@ -0,0 +1,297 @@
+//! Portable dump of a single table: one JSON object per row, keyed by
+//! column name. Values go through the entity's column types rather than
+//! the models' serde implementation, which leaves out secrets such as
+//! password hashes and session keys.
+
+use std::path::Path;
+
+use cryptfns::base64;
+use entity::{
+    ActiveModelTrait, ColumnTrait, ColumnType, ConnectionTrait, EntityTrait, Expr, IdenStatic,
+    IntoActiveModel, Iterable, JsonValue, PaginatorTrait, PrimaryKeyToColumn, Query, QueryOrder,
+    Uuid, Value,
+};
+use error::{AppResult, Error};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, BufWriter};
+
+const PAGE_SIZE: u64 = 1000;
+const INSERT_BATCH: usize = 100;
+
+/// Columns that point at rows of the same table. They are restored as
+/// `NULL` first and set once every row of the table is in place, so the
+/// order of the rows in the dump does not matter.
+fn deferred(table: &str) -> &'static [&'static str] {
+    match table {
+        "files" => &["file_id"],
+        _ => &[],
+    }
+}
+
+fn path<E: EntityTrait>(dir: &Path) -> std::path::PathBuf {
+    dir.join(format!("{}.jsonl", E::default().table_name()))
+}
+
+/// Write every row of the table into `{dir}/{table}.jsonl`, returning the
+/// number of rows written.
+pub(crate) async fn dump<E, C>(db: &C, dir: &Path) -> AppResult<u64>
+where
+    E: EntityTrait,
+    E::Model: IntoActiveModel<E::ActiveModel> + Sync,
+    C: ConnectionTrait,
+{
+    let mut select = E::find();
+    for key in E::PrimaryKey::iter() {
+        select = select.order_by_asc(key.into_column());
+    }
+
+    let file = tokio::fs::File::create(path::<E>(dir)).await?;
+    let mut writer = BufWriter::new(file);
+    let mut pages = select.paginate(db, PAGE_SIZE);
+    let mut rows = 0;
+
+    while let Some(models) = pages.fetch_and_next().await? {
+        for model in models {
+            let active = model.into_active_model();
+            let mut row = serde_json::Map::new();
+
+            for column in E::Column::iter() {
+                let value = match active.get(column).into_value() {
+                    Some(value) => to_json(value)?,
+                    None => JsonValue::Null,
+                };
+                row.insert(column.as_str().to_string(), value);
+            }
+
+            writer
+                .write_all(serde_json::to_string(&row)?.as_bytes())
+                .await?;
+            writer.write_all(b"\n").await?;
+            rows += 1;
+        }
+    }
+
+    writer.flush().await?;
+
+    Ok(rows)
+}
+
+/// Fail unless the table has no rows, restore never merges into live data.
+pub(crate) async fn ensure_empty<E, C>(db: &C) -> AppResult<()>
+where
+    E: EntityTrait,
+    E::Model: Sync,
+    C: ConnectionTrait,
+{
+    if E::find().count(db).await? > 0 {
+        return Err(Error::InternalError(format!(
+            "Table '{}' is not empty, restore needs an empty database",
+            E::default().table_name()
+        )));
+    }
+
+    Ok(())
+}
+
+/// Insert every row from `{dir}/{table}.jsonl`, returning the number of
+/// rows inserted. A table without a dump file is left empty.
+pub(crate) async fn restore<E, C>(db: &C, dir: &Path) -> AppResult<u64>
+where
+    E: EntityTrait,
+    C: ConnectionTrait,
+{
+    let file = match tokio::fs::File::open(path::<E>(dir)).await {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(Error::from(e)),
+    };
+
+    let entity = E::default();
+    let table = entity.table_name();
+    let deferred = deferred(table);
+    let mut lines = BufReader::new(file).lines();
+    let mut columns: Option<Vec<E::Column>> = None;
+    let mut batch = Vec::with_capacity(INSERT_BATCH);
+    let mut updates = Vec::new();
+    let mut rows = 0;
+
+    while let Some(line) = lines.next_line().await? {
+        if line.is_empty() {
+            continue;
+        }
+
+        let row: serde_json::Map<String, JsonValue> = serde_json::from_str(&line)?;
+
+        // Columns a newer version of the entity has but the dump does not
+        // are left to their database defaults.
+        let columns = columns.get_or_insert_with(|| {
+            E::Column::iter()
+                .filter(|column| row.contains_key(column.as_str()))
+                .collect()
+        });
+
+        let mut values = Vec::with_capacity(columns.len());
+        for column in columns.iter() {
+            let json = row.get(column.as_str()).unwrap_or(&JsonValue::Null);
+            let value = from_json(table, *column, json)?;
+
+            if deferred.contains(&column.as_str()) && !json.is_null() {
+                updates.push((*column, value, primary_key::<E>(table, &row)?));
+                values.push(from_json(table, *column, &JsonValue::Null)?);
+            } else {
+                values.push(value);
+            }
+        }
+
+        batch.push(values);
+        rows += 1;
+
+        if batch.len() == INSERT_BATCH {
+            insert::<E, C>(db, columns, &mut batch).await?;
+        }
+    }
+
+    if let Some(columns) = &columns {
+        insert::<E, C>(db, columns, &mut batch).await?;
+    }
+
+    for (column, value, key) in updates {
+        let mut update = Query::update();
+        update.table(E::default()).value(column, value);
+        for (key, value) in key {
+            update.and_where(Expr::col(key).eq(value));
+        }
+
+        db.execute(db.get_database_backend().build(&update)).await?;
+    }
+
+    Ok(rows)
+}
+
+async fn insert<E, C>(db: &C, columns: &[E::Column], batch: &mut Vec<Vec<Value>>) -> AppResult<()>
+where
+    E: EntityTrait,
+    C: ConnectionTrait,
+{
+    if batch.is_empty() {
+        return Ok(());
+    }
+
+    let mut insert = Query::insert();
+    insert.into_table(E::default()).columns(columns.to_vec());
+
+    for values in batch.drain(..) {
+        insert
+            .values(values.into_iter().map(Into::into))
+            .map_err(|e| Error::InternalError(e.to_string()))?;
+    }
+
+    db.execute(db.get_database_backend().build(&insert)).await?;
+
+    Ok(())
+}
+
+fn primary_key<E: EntityTrait>(
+    table: &str,
+    row: &serde_json::Map<String, JsonValue>,
+) -> AppResult<Vec<(E::Column, Value)>> {
+    E::PrimaryKey::iter()
+        .map(|key| {
+            let column = key.into_column();
+            let json = row.get(column.as_str()).unwrap_or(&JsonValue::Null);
+            Ok((column, from_json(table, column, json)?))
+        })
+        .collect()
+}
+
+fn to_json(value: Value) -> AppResult<JsonValue> {
+    let json = match value {
+        Value::Bool(v) => JsonValue::from(v),
+        Value::TinyInt(v) => JsonValue::from(v),
+        Value::SmallInt(v) => JsonValue::from(v),
+        Value::Int(v) => JsonValue::from(v),
+        Value::BigInt(v) => JsonValue::from(v),
+        Value::Unsigned(v) => JsonValue::from(v),
+        Value::BigUnsigned(v) => JsonValue::from(v),
+        Value::Float(v) => JsonValue::from(v),
+        Value::Double(v) => JsonValue::from(v),
+        Value::String(v) => JsonValue::from(v.map(|v| *v)),
+        Value::Bytes(v) => JsonValue::from(v.map(|v| base64::encode(*v))),
+        Value::Uuid(v) => JsonValue::from(v.map(|v| v.to_string())),
+        Value::Json(v) => v.map(|v| *v).unwrap_or(JsonValue::Null),
+        other => {
+            return Err(Error::InternalError(format!(
+                "Cannot back up value {other:?}"
+            )))
+        }
+    };
+
+    Ok(json)
+}
+
+/// Turn a dumped value back into a typed value for the column. Nulls keep
+/// the column's type as well, Postgres refuses an untyped one.
+fn from_json<T: ColumnTrait>(table: &str, column: T, json: &JsonValue) -> AppResult<Value> {
+    let invalid = || {
+        Error::InternalError(format!(
+            "Invalid value {json} for {table}.{} in the backup",
+            column.as_str()
+        ))
+    };
+
+    let null = json.is_null();
+    let value = match column.def().get_column_type() {
+        ColumnType::Boolean => Value::Bool(if null {
+            None
+        } else {
+            Some(json.as_bool().ok_or_else(invalid)?)
+        }),
+        ColumnType::TinyInteger | ColumnType::SmallInteger | ColumnType::Integer => {
+            Value::Int(if null {
+                None
+            } else {
+                let v = json.as_i64().ok_or_else(invalid)?;
+                Some(i32::try_from(v).map_err(|_| invalid())?)
+            })
+        }
+        ColumnType::BigInteger => Value::BigInt(if null {
+            None
+        } else {
+            Some(json.as_i64().ok_or_else(invalid)?)
+        }),
+        ColumnType::Float | ColumnType::Double => Value::Double(if null {
+            None
+        } else {
+            Some(json.as_f64().ok_or_else(invalid)?)
+        }),
+        ColumnType::String(_) | ColumnType::Text | ColumnType::Char(_) => Value::String(if null {
+            None
+        } else {
+            Some(Box::new(json.as_str().ok_or_else(invalid)?.to_string()))
+        }),
+        ColumnType::Uuid => Value::Uuid(if null {
+            None
+        } else {
+            let v = json.as_str().ok_or_else(invalid)?;
+            Some(Box::new(Uuid::parse_str(v).map_err(|_| invalid())?))
+        }),
+        ColumnType::Binary(_) | ColumnType::VarBinary(_) | ColumnType::Blob => {
+            Value::Bytes(if null {
+                None
+            } else {
+                let v = json.as_str().ok_or_else(invalid)?;
+                Some(Box::new(base64::decode(v).map_err(|_| invalid())?))
+            })
+        }
+        ColumnType::Json | ColumnType::JsonBinary => {
+            Value::Json((!null).then(|| Box::new(json.clone())))
+        }
+        other => {
+            return Err(Error::InternalError(format!(
+                "Cannot restore {table}.{} of type {other:?}",
+                column.as_str()
+            )))
+        }
+    };
+
+    Ok(value)
+}
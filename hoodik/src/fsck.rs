@@ -0,0 +1,52 @@
+use context::Context;
+use error::AppResult;
+use storage::fsck::Report;
+
+/// Compare the database with the configured storage provider and print
+/// what disagrees. Nothing changes unless `repair` is set, in which case
+/// orphans are deleted and damaged files are flagged.
+pub async fn fsck(context: &Context, repair: bool) -> AppResult<Report> {
+    println!("Checking files against the storage provider...");
+
+    let report = storage::fsck::fsck(context, repair).await?;
+
+    for orphan in &report.orphans {
+        println!("  orphan: {} has chunks but no file row", orphan.name);
+    }
+
+    for (file_id, version) in &report.stray_versions {
+        println!("  stray version: {file_id}/v{version} is not referenced");
+    }
+
+    for (file_id, version) in &report.missing_versions {
+        println!("  missing version: {file_id}/v{version} is recorded but gone");
+    }
+
+    for damaged in &report.damaged {
+        println!(
+            "  damaged: {}/v{} is missing {} chunk(s): {:?}",
+            damaged.file_id,
+            damaged.version,
+            damaged.missing.len(),
+            damaged.missing
+        );
+    }
+
+    println!();
+    println!(
+        "Checked {} files: {} orphaned, {} stray versions, {} missing versions, {} damaged.",
+        report.checked,
+        report.orphans.len(),
+        report.stray_versions.len(),
+        report.missing_versions.len(),
+        report.damaged.len()
+    );
+
+    if report.repaired {
+        println!("Orphans and stray versions deleted, damaged files flagged.");
+    } else if !report.is_clean() {
+        println!("Dry run, nothing was changed. Re-run with --repair to fix.");
+    }
+
+    Ok(report)
+}
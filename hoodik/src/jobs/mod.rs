@@ -0,0 +1,243 @@
+//! # Background housekeeping
+//!
+//! A small in-process scheduler that runs named jobs on fixed intervals next
+//! to the HTTP server. Each job is a plain async function returning how much
+//! it cleaned up; the scheduler records every run in `job_runs` so a restart
+//! picks up where the previous process left off instead of running everything
+//! at once. Operators can run a single job by hand with `hoodik run-job <name>`.
+
+use std::{future::Future, pin::Pin, time::Duration};
+
+use chrono::Utc;
+use config::Config;
+use context::Context;
+use entity::{job_runs, ActiveValue, EntityTrait, OnConflict};
+use error::{AppResult, Error};
+
+mod purge;
+
+/// Future returned by a job: what it cleaned up.
+pub type JobFuture<'ctx> = Pin<Box<dyn Future<Output = AppResult<Outcome>> + 'ctx>>;
+
+/// What a single job run cleaned up, recorded in `job_runs`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Outcome {
+    /// Rows (or uploads, copies, versions, depending on the job) removed.
+    pub affected: u64,
+    /// Storage space freed, for the jobs that remove chunks and can tell.
+    pub reclaimed_bytes: Option<u64>,
+}
+
+impl From<u64> for Outcome {
+    fn from(affected: u64) -> Self {
+        Self {
+            affected,
+            reclaimed_bytes: None,
+        }
+    }
+}
+
+/// A named housekeeping job and how often the scheduler runs it.
+#[derive(Clone, Copy)]
+pub struct Job {
+    pub name: &'static str,
+    /// Seconds between runs, `0` keeps the scheduler from ever starting the
+    /// job; it can still be run by hand.
+    pub interval: u64,
+    run: for<'ctx> fn(&'ctx Context) -> JobFuture<'ctx>,
+}
+
+impl Job {
+    fn new(
+        name: &'static str,
+        interval: u64,
+        run: for<'ctx> fn(&'ctx Context) -> JobFuture<'ctx>,
+    ) -> Self {
+        Self {
+            name,
+            interval,
+            run,
+        }
+    }
+}
+
+/// Every job the application knows about, with the intervals from config.
+pub fn registry(config: &Config) -> Vec<Job> {
+    let jobs = &config.jobs;
+
+    vec![
+        Job::new(
+            "purge-expired-links",
+            jobs.purge_expired_links_interval_seconds,
+            purge::expired_links,
+        ),
+        Job::new(
+            "purge-expired-shares",
+            jobs.purge_expired_shares_interval_seconds,
+            purge::expired_shares,
+        ),
+        Job::new(
+            "purge-stale-sessions",
+            jobs.purge_stale_sessions_interval_seconds,
+            purge::stale_sessions,
+        ),
+        Job::new(
+            "purge-expired-invitations",
+            jobs.purge_expired_invitations_interval_seconds,
+            purge::expired_invitations,
+        ),
+        Job::new(
+            "purge-used-nonces",
+            jobs.purge_used_nonces_interval_seconds,
+            purge::used_nonces,
+        ),
+        Job::new(
+            "purge-opaque-login-sessions",
+            jobs.purge_opaque_login_sessions_interval_seconds,
+            purge::opaque_login_sessions,
+        ),
+        Job::new(
+            "purge-rate-limit-hits",
+            jobs.purge_rate_limit_hits_interval_seconds,
+            purge::rate_limit_hits,
+        ),
+        Job::new(
+            "purge-rewrap-staging",
+            jobs.purge_rewrap_staging_interval_seconds,
+            purge::rewrap_staging,
+        ),
+        Job::new(
+            "purge-trash",
+            jobs.purge_trash_interval_seconds,
+            purge::trash,
+        ),
+        Job::new(
+            "reap-abandoned-uploads",
+            jobs.reap_abandoned_uploads_interval_seconds,
+            purge::abandoned_uploads,
+        ),
+        Job::new(
+            "prune-file-versions",
+            jobs.prune_file_versions_interval_seconds,
+            purge::file_versions,
+        ),
+    ]
+}
+
+/// Run a single job by name right now, regardless of when it is due, and
+/// record the run in `job_runs`.
+pub async fn run(context: &Context, name: &str) -> AppResult<u64> {
+    let job = registry(&context.config)
+        .into_iter()
+        .find(|job| job.name == name)
+        .ok_or_else(|| Error::NotFound(format!("job_not_found:{name}")))?;
+
+    Ok(execute(context, &job).await?.affected)
+}
+
+/// Run every job whose `next_run_at` has passed. Jobs that never ran are due
+/// immediately. Returns the names of the jobs that were started.
+pub async fn run_due(context: &Context) -> AppResult<Vec<&'static str>> {
+    let now = Utc::now().timestamp();
+    let mut started = vec![];
+
+    for job in registry(&context.config) {
+        if job.interval == 0 {
+            continue;
+        }
+
+        let due = job_runs::Entity::find_by_id(job.name.to_string())
+            .one(&context.db)
+            .await?
+            .is_none_or(|status| status.next_run_at <= now);
+
+        if !due {
+            continue;
+        }
+
+        started.push(job.name);
+
+        // One failing job must not starve the others; the failure is already
+        // recorded in its status row.
+        if let Err(e) = execute(context, &job).await {
+            log::warn!("Job '{}' failed: {}", job.name, e);
+        }
+    }
+
+    Ok(started)
+}
+
+/// Start the scheduler on the current runtime. It wakes up every
+/// `JOBS_TICK_SECONDS` and runs whatever is due, one job at a time.
+pub fn spawn(context: Context) {
+    if !context.config.jobs.enabled {
+        log::info!("Background jobs are disabled (JOBS_ENABLED=false)");
+        return;
+    }
+
+    let tick = Duration::from_secs(context.config.jobs.tick_seconds);
+
+    actix_web::rt::spawn(async move {
+        let mut interval = actix_web::rt::time::interval(tick);
+
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = run_due(&context).await {
+                log::error!("Background job scheduler failed to check due jobs: {}", e);
+            }
+        }
+    });
+}
+
+async fn execute(context: &Context, job: &Job) -> AppResult<Outcome> {
+    let started_at = Utc::now().timestamp();
+
+    log::debug!("Running job '{}'", job.name);
+
+    let result = (job.run)(context).await;
+    let finished_at = Utc::now().timestamp();
+
+    let (status, error, outcome) = match &result {
+        Ok(outcome) => ("ok", None, Some(*outcome)),
+        Err(e) => ("error", Some(e.to_string()), None),
+    };
+
+    job_runs::Entity::insert(job_runs::ActiveModel {
+        name: ActiveValue::Set(job.name.to_string()),
+        last_started_at: ActiveValue::Set(Some(started_at)),
+        last_finished_at: ActiveValue::Set(Some(finished_at)),
+        last_status: ActiveValue::Set(Some(status.to_string())),
+        last_error: ActiveValue::Set(error),
+        last_affected: ActiveValue::Set(outcome.map(|o| o.affected as i64)),
+        last_reclaimed_bytes: ActiveValue::Set(
+            outcome.and_then(|o| o.reclaimed_bytes).map(|b| b as i64),
+        ),
+        next_run_at: ActiveValue::Set(finished_at + job.interval as i64),
+    })
+    .on_conflict(
+        OnConflict::column(job_runs::Column::Name)
+            .update_columns([
+                job_runs::Column::LastStartedAt,
+                job_runs::Column::LastFinishedAt,
+                job_runs::Column::LastStatus,
+                job_runs::Column::LastError,
+                job_runs::Column::LastAffected,
+                job_runs::Column::LastReclaimedBytes,
+                job_runs::Column::NextRunAt,
+            ])
+            .to_owned(),
+    )
+    .exec_without_returning(&context.db)
+    .await?;
+
+    if let Ok(outcome) = &result {
+        log::info!(
+            "Job '{}' finished, cleaned up {}",
+            job.name,
+            outcome.affected
+        );
+    }
+
+    result
+}
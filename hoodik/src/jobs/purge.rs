@@ -0,0 +1,177 @@
+//! Jobs that delete rows which stopped being useful. Most of these tables are
+//! also purged on write by the code that fills them; the jobs make sure they
+//! drain on an idle instance too.
+
+use chrono::{Duration, Utc};
+use context::Context;
+use entity::{
+    invitations, links, migration_rewrap_staging, oidc_logins, opaque_login_sessions,
+    rate_limit_hits, sessions, used_nonces, ColumnTrait, EntityTrait, QueryFilter,
+};
+
+use super::{JobFuture, Outcome};
+
+/// Links past their `expires_at`. The owner can no longer hand them out and
+/// the recipient can no longer download through them, so the encrypted file
+/// key they carry is dropped as well.
+pub(super) fn expired_links(context: &Context) -> JobFuture<'_> {
+    Box::pin(async move {
+        let result = links::Entity::delete_many()
+            .filter(links::Column::ExpiresAt.is_not_null())
+            .filter(links::Column::ExpiresAt.lt(Utc::now().timestamp()))
+            .exec(&context.db)
+            .await?;
+
+        Ok(result.rows_affected.into())
+    })
+}
+
+/// Time-limited shares past their `expires_at`. Each expiry is recorded in
+/// the share audit log before the recipient's rows are dropped.
+pub(super) fn expired_shares(context: &Context) -> JobFuture<'_> {
+    Box::pin(async move {
+        let purged = shares::purge_expired_shares(&context.db, Utc::now().timestamp()).await?;
+
+        Ok(purged.into())
+    })
+}
+
+/// Sessions that expired more than `JOBS_HISTORY_RETENTION_DAYS` ago. Recently
+/// expired ones are kept so the account activity still shows them.
+pub(super) fn stale_sessions(context: &Context) -> JobFuture<'_> {
+    Box::pin(async move {
+        let cutoff = history_cutoff(context);
+
+        let result = sessions::Entity::delete_many()
+            .filter(sessions::Column::ExpiresAt.lt(cutoff))
+            .exec(&context.db)
+            .await?;
+
+        Ok(result.rows_affected.into())
+    })
+}
+
+/// Invitations that expired more than `JOBS_HISTORY_RETENTION_DAYS` ago.
+pub(super) fn expired_invitations(context: &Context) -> JobFuture<'_> {
+    Box::pin(async move {
+        let cutoff = history_cutoff(context);
+
+        let result = invitations::Entity::delete_many()
+            .filter(invitations::Column::ExpiresAt.lt(cutoff))
+            .exec(&context.db)
+            .await?;
+
+        Ok(result.rows_affected.into())
+    })
+}
+
+/// Signature-login nonces whose acceptance window has closed.
+pub(super) fn used_nonces(context: &Context) -> JobFuture<'_> {
+    Box::pin(async move {
+        let result = used_nonces::Entity::delete_many()
+            .filter(used_nonces::Column::ExpiresAt.lt(Utc::now().timestamp()))
+            .exec(&context.db)
+            .await?;
+
+        Ok(result.rows_affected.into())
+    })
+}
+
+/// OPAQUE login states of logins that were started and never finished,
+/// along with OpenID Connect sign-ins that were never used to log in.
+pub(super) fn opaque_login_sessions(context: &Context) -> JobFuture<'_> {
+    Box::pin(async move {
+        let now = Utc::now().timestamp();
+
+        let opaque = opaque_login_sessions::Entity::delete_many()
+            .filter(opaque_login_sessions::Column::ExpiresAt.lt(now))
+            .exec(&context.db)
+            .await?;
+
+        let oidc = oidc_logins::Entity::delete_many()
+            .filter(oidc_logins::Column::ExpiresAt.lt(now))
+            .exec(&context.db)
+            .await?;
+
+        Ok((opaque.rows_affected + oidc.rows_affected).into())
+    })
+}
+
+/// Rate-limit hits kept by the database backend whose window has passed.
+pub(super) fn rate_limit_hits(context: &Context) -> JobFuture<'_> {
+    Box::pin(async move {
+        let result = rate_limit_hits::Entity::delete_many()
+            .filter(rate_limit_hits::Column::ExpiresAt.lt(Utc::now().timestamp()))
+            .exec(&context.db)
+            .await?;
+
+        Ok(result.rows_affected.into())
+    })
+}
+
+/// Re-wrapped keys staged by account migrations that were abandoned midway.
+pub(super) fn rewrap_staging(context: &Context) -> JobFuture<'_> {
+    Box::pin(async move {
+        let cutoff = Utc::now().timestamp() - auth::REWRAP_STAGING_TTL_SECONDS;
+
+        let result = migration_rewrap_staging::Entity::delete_many()
+            .filter(migration_rewrap_staging::Column::CreatedAt.lt(cutoff))
+            .exec(&context.db)
+            .await?;
+
+        Ok(result.rows_affected.into())
+    })
+}
+
+/// Files and folders that stayed in the trash longer than the retention
+/// window from the admin settings. Their chunks are removed as well.
+pub(super) fn trash(context: &Context) -> JobFuture<'_> {
+    Box::pin(async move {
+        let purged = storage::housekeeping::purge_trash(context).await?;
+
+        Ok(purged.into())
+    })
+}
+
+/// Partial chunks of uploads and edits whose client went away, and copies
+/// a server stopped in the middle of. The number of reaped uploads and
+/// copies goes into `job_runs` along with the bytes the uploads held.
+pub(super) fn abandoned_uploads(context: &Context) -> JobFuture<'_> {
+    Box::pin(async move {
+        let idle_before =
+            Utc::now().timestamp() - context.config.jobs.abandoned_upload_idle_seconds;
+
+        let reaped = storage::housekeeping::reap_abandoned_uploads(context, idle_before).await?;
+
+        if reaped.uploads > 0 {
+            log::info!(
+                "Reaped {} abandoned uploads, reclaimed {} bytes",
+                reaped.uploads,
+                reaped.bytes
+            );
+        }
+
+        if reaped.copies > 0 {
+            log::info!("Removed {} interrupted copies", reaped.copies);
+        }
+
+        Ok(Outcome {
+            affected: reaped.uploads + reaped.copies,
+            reclaimed_bytes: Some(reaped.bytes),
+        })
+    })
+}
+
+/// Versions of editable files their retention policy no longer keeps,
+/// chunks included.
+pub(super) fn file_versions(context: &Context) -> JobFuture<'_> {
+    Box::pin(async move {
+        let pruned = storage::housekeeping::prune_file_versions(context).await?;
+
+        Ok(pruned.into())
+    })
+}
+
+fn history_cutoff(context: &Context) -> i64 {
+    (Utc::now() - Duration::days(context.config.jobs.history_retention_days)).timestamp()
+}
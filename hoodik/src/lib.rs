@@ -1,4 +1,7 @@
+pub mod backup;
 mod client;
+pub mod fsck;
+pub mod jobs;
 pub mod migrate;
 pub mod server;
 
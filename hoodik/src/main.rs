@@ -23,8 +23,81 @@ async fn main() -> AppResult<()> {
 
     // Handle subcommands before starting the server
     if config.subcommand.as_deref() == Some("migrate-storage") {
+        if config.subcommand.as_deref() == Some("rotate-jwt-key") {
+            let context = Context::new(config).await?;
+            Migrator::up(&context.db, None).await?;
+            env_logger::init();
+
+            let kid = auth::rotate_jwt_signing_key(&context).await?;
+            println!("Tokens are now signed with key {kid}");
+
+            return Ok(());
+        }
+
         config.announce();
-        return hoodik::migrate::migrate_storage(&config).await;
+        let options = hoodik::migrate::Options::from_config(&config)?;
+        hoodik::migrate::migrate_storage(&config, &options).await?;
+
+        return Ok(());
+    }
+
+    if config.subcommand.as_deref() == Some("fsck") {
+        let repair = config
+            .subcommand_matches
+            .as_ref()
+            .is_some_and(|m| m.get_flag("repair"));
+
+        let context = Context::new(config).await?;
+        Migrator::up(&context.db, None).await?;
+        env_logger::init();
+
+        let report = hoodik::fsck::fsck(&context, repair).await?;
+        if !report.is_clean() && !report.repaired {
+            std::process::exit(1);
+        }
+
+        return Ok(());
+    }
+
+    if matches!(config.subcommand.as_deref(), Some("backup" | "restore")) {
+        let matches = config.subcommand_matches.clone();
+        let path = matches
+            .as_ref()
+            .and_then(|m| m.get_one::<String>("path"))
+            .map(std::path::PathBuf::from)
+            .unwrap_or_default();
+        let backup = config.subcommand.as_deref() == Some("backup");
+        let full = backup && matches.as_ref().is_some_and(|m| m.get_flag("full"));
+
+        let context = Context::new(config).await?;
+        Migrator::up(&context.db, None).await?;
+        env_logger::init();
+
+        if backup {
+            hoodik::backup::backup(&context, &path, full).await?;
+        } else {
+            hoodik::backup::restore(&context, &path).await?;
+        }
+
+        return Ok(());
+    }
+
+    if config.subcommand.as_deref() == Some("run-job") {
+        let name = config
+            .subcommand_matches
+            .as_ref()
+            .and_then(|m| m.get_one::<String>("name"))
+            .cloned()
+            .unwrap_or_default();
+
+        let context = Context::new(config).await?;
+        Migrator::up(&context.db, None).await?;
+        env_logger::init();
+
+        let affected = hoodik::jobs::run(&context, &name).await?;
+        println!("Job '{name}' finished, cleaned up {affected}");
+
+        return Ok(());
     }
 
     config.announce();
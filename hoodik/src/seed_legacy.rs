@@ -93,6 +93,8 @@ async fn main() {
         created_at: ActiveValue::Set(now),
         updated_at: ActiveValue::Set(now),
         share_notifications_enabled: ActiveValue::Set(true),
+        login_alerts_enabled: ActiveValue::Set(true),
+        password_score: ActiveValue::Set(None),
     })
     .exec_without_returning(&db)
     .await
@@ -133,6 +135,10 @@ async fn main() {
         members_list_signature: ActiveValue::NotSet,
         members_list_signed_at: ActiveValue::NotSet,
         members_list_signed_by_user_id: ActiveValue::NotSet,
+        deleted_at: ActiveValue::NotSet,
+        deleted_parent_id: ActiveValue::NotSet,
+        last_chunk_at: ActiveValue::NotSet,
+        damaged_at: ActiveValue::NotSet,
     })
     .exec_without_returning(&db)
     .await
@@ -186,6 +192,8 @@ async fn main() {
         encrypted_file_key: ActiveValue::Set(Some(encrypt_hex(&link_key, file_key_hex.as_bytes()))),
         created_at: ActiveValue::Set(now),
         expires_at: ActiveValue::Set(None),
+        max_downloads: ActiveValue::Set(None),
+        password_hash: ActiveValue::Set(None),
     })
     .exec_without_returning(&db)
     .await
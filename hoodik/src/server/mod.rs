@@ -104,6 +104,8 @@ pub async fn engage(context: Context) -> AppResult<()> {
     let disabled = context.config.ssl.disabled;
     let app_url = context.config.get_app_url();
     let workers = context.config.app.workers;
+
+    crate::jobs::spawn(context.clone());
     let rustls_config = if disabled {
         None
     } else {
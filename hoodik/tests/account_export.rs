@@ -0,0 +1,212 @@
+//! Export of an account from one instance and import into another.
+
+#[path = "./helpers.rs"]
+mod helpers;
+
+use actix_web::{http::StatusCode, test};
+use entity::{user_files, ColumnTrait, EntityTrait, QueryFilter, Uuid};
+use hoodik::server;
+use links::data::{app_link::AppLink, create_link::CreateLink};
+use storage::data::{account::Imported, app_file::AppFile, create_file::CreateFile};
+
+use crate::helpers::{calculate_checksum, create_byte_chunks};
+
+/// A link on `file_id` signed the way the client signs it.
+fn create_link(file_id: Uuid, ed_private: &str) -> CreateLink {
+    CreateLink {
+        file_id: Some(file_id.to_string()),
+        signature: Some(
+            cryptfns::ed25519::private::sign(&file_id.to_string(), ed_private).unwrap(),
+        ),
+        encrypted_name: Some("link-name".to_string()),
+        encrypted_link_key: Some("link-key".to_string()),
+        encrypted_thumbnail: None,
+        encrypted_file_key: Some("link-file-key".to_string()),
+        expires_at: None,
+        max_downloads: None,
+        password: None,
+        files: None,
+    }
+}
+
+fn create_file(name: &str, mime: &str, file_id: Option<Uuid>, data: &[Vec<u8>]) -> CreateFile {
+    let is_dir = mime == "dir";
+
+    CreateFile {
+        encrypted_key: Some(format!("{name}-key")),
+        encrypted_name: Some(name.to_string()),
+        encrypted_thumbnail: None,
+        search_tokens_hashed: Some(vec![format!("{name}:1")]),
+        name_hash: Some(name.to_string()),
+        mime: Some(mime.to_string()),
+        size: (!is_dir).then(|| data.iter().map(|chunk| chunk.len() as i64).sum()),
+        chunks: (!is_dir).then_some(data.len() as i64),
+        file_id: file_id.map(|id| id.to_string()),
+        file_modified_at: None,
+        md5: None,
+        sha1: None,
+        sha256: None,
+        blake2b: None,
+        cipher: None,
+        editable: None,
+    }
+}
+
+#[actix_web::test]
+async fn exports_an_account_and_imports_it_elsewhere() {
+    let source =
+        context::Context::mock_with_data_dir(Some("../data-test-account-export".to_string())).await;
+    let app = test::init_service(server::app(source.clone())).await;
+    let ed_private = cryptfns::ed25519::private::generate().unwrap();
+    let x_private = cryptfns::ecdh::private::generate().unwrap();
+    let exporter =
+        helpers::register_curve25519_with_keys(&app, "export@test.com", &ed_private, &x_private)
+            .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/storage")
+        .cookie(exporter.jwt.clone())
+        .set_json(create_file("folder", "dir", None, &[]))
+        .to_request();
+    let folder: AppFile = test::call_and_read_body_json(&app, req).await;
+
+    let (data, _, _) = create_byte_chunks();
+    let checksum = calculate_checksum(data.clone());
+    let req = test::TestRequest::post()
+        .uri("/api/storage")
+        .cookie(exporter.jwt.clone())
+        .set_json(create_file("file", "text/plain", Some(folder.id), &data))
+        .to_request();
+    let file: AppFile = test::call_and_read_body_json(&app, req).await;
+
+    for (i, chunk) in data.iter().enumerate() {
+        let req = test::TestRequest::post()
+            .uri(&format!(
+                "/api/storage/{}?checksum={}&chunk={}",
+                file.id,
+                cryptfns::sha256::digest(chunk.as_slice()),
+                i
+            ))
+            .cookie(exporter.jwt.clone())
+            .append_header(("Content-Type", "application/octet-stream"))
+            .set_payload(chunk.clone())
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+    }
+
+    let req = test::TestRequest::post()
+        .uri("/api/links")
+        .cookie(exporter.jwt.clone())
+        .set_json(create_link(file.id, &ed_private))
+        .to_request();
+    let exported_link: AppLink = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/account/export")
+        .cookie(exporter.jwt.clone())
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let archive = test::read_body(resp).await;
+
+    let target =
+        context::Context::mock_with_data_dir(Some("../data-test-account-import".to_string())).await;
+    let app = test::init_service(server::app(target.clone())).await;
+
+    // Someone with a key pair of their own cannot take the archive in.
+    let stranger = helpers::register_curve25519(&app, "stranger@test.com").await;
+    let req = test::TestRequest::post()
+        .uri("/api/account/import")
+        .cookie(stranger.jwt.clone())
+        .set_payload(archive.clone())
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::BAD_REQUEST
+    );
+
+    // The same person, registered on the new instance with the same keys.
+    let importer =
+        helpers::register_curve25519_with_keys(&app, "export@test.com", &ed_private, &x_private)
+            .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/account/import")
+        .cookie(importer.jwt.clone())
+        .set_payload(archive)
+        .to_request();
+    let imported: Imported = test::call_and_read_body_json(&app, req).await;
+
+    let owned = user_files::Entity::find()
+        .filter(user_files::Column::UserId.eq(importer.user_id))
+        .filter(user_files::Column::IsOwner.eq(true))
+        .all(&target.db)
+        .await
+        .unwrap();
+    let imported_file = owned
+        .iter()
+        .find(|row| row.encrypted_key == "file-key")
+        .unwrap();
+    assert_ne!(imported_file.file_id, file.id);
+    assert_eq!(
+        imported,
+        Imported {
+            files: 2,
+            chunks: data.len() as u64,
+            versions: 0,
+            skipped_links: vec![imported_file.file_id],
+        }
+    );
+
+    // The owner signed the old file id, a link carried over would fail the
+    // client's signature check, so none is and the owner links it again.
+    assert!(cryptfns::ed25519::public::verify(
+        &imported_file.file_id.to_string(),
+        &exported_link.signature,
+        &exported_link.owner_pubkey
+    )
+    .is_err());
+    assert!(entity::links::Entity::find()
+        .filter(entity::links::Column::UserId.eq(importer.user_id))
+        .all(&target.db)
+        .await
+        .unwrap()
+        .is_empty());
+
+    let req = test::TestRequest::post()
+        .uri("/api/links")
+        .cookie(importer.jwt.clone())
+        .set_json(create_link(imported_file.file_id, &ed_private))
+        .to_request();
+    let link: AppLink = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/links/{}/metadata", link.id))
+        .to_request();
+    let link: AppLink = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(link.file_id, imported_file.file_id);
+    cryptfns::ed25519::public::verify(
+        &link.file_id.to_string(),
+        &link.signature,
+        &link.owner_pubkey,
+    )
+    .unwrap();
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/storage/{}/metadata", imported_file.file_id))
+        .cookie(importer.jwt.clone())
+        .to_request();
+    let metadata: AppFile = test::call_and_read_body_json(&app, req).await;
+    assert!(metadata.file_id.is_some());
+    assert_ne!(metadata.file_id, Some(folder.id));
+    assert!(metadata.finished_upload_at.is_some());
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/storage/{}", imported_file.file_id))
+        .cookie(importer.jwt.clone())
+        .to_request();
+    let contents = test::call_and_read_body(&app, req).await;
+    assert_eq!(cryptfns::sha256::digest(contents.as_ref()), checksum);
+
+    source.config.app.cleanup();
+    target.config.app.cleanup();
+}
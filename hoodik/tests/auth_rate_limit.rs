@@ -1,8 +1,8 @@
 //! Login lockout coverage across the credential-guessing surfaces. Only failed
 //! authentications are charged, so tests drive real wrong-password attempts to
 //! trip the limiter and correct ones to prove they never do. Each test draws
-//! its own email and source IP so the process-global windows never alias under
-//! the parallel runner.
+//! its own email and source IP, so results never depend on another test's
+//! windows even where a backend is shared.
 
 #[path = "./helpers.rs"]
 mod helpers;
@@ -168,7 +168,7 @@ async fn a_full_source_ip_window_refuses_the_next_login() {
     // for a single host spraying one password across the whole user table (which
     // as real HTTP calls would be IP_LIMIT slow bcrypt verifications).
     for _ in 0..IP_LIMIT {
-        charge_failure(None, ip, now);
+        charge_failure(&context, None, ip, now).await.unwrap();
     }
 
     // A brand-new account from that address is refused on the IP window alone.
@@ -182,18 +182,51 @@ async fn a_full_source_ip_window_refuses_the_next_login() {
 #[actix_web::test]
 async fn the_window_slides_forward() {
     use auth::test_support::{
-        auth_rate_limit_charge_failure as charge_failure, auth_rate_limit_check as check,
+        auth_rate_limit_attempt as attempt, auth_rate_limit_charge_failure as charge_failure,
     };
 
+    let context = context::Context::mock_sqlite().await;
     let id = Some("slide-identity");
     let ip = "slide-source";
 
     for _ in 0..IDENTITY_LIMIT {
-        charge_failure(id, ip, 0);
+        charge_failure(&context, id, ip, 0).await.unwrap();
     }
-    assert!(check(id, ip, 0).is_err());
+    assert!(attempt(&context, id, ip, 0).await.is_err());
     // A full window later, every earlier failure has aged out.
-    assert!(check(id, ip, 301).is_ok());
+    assert!(attempt(&context, id, ip, 301).await.is_ok());
+}
+
+#[actix_web::test]
+async fn the_database_backend_shares_a_lockout_between_servers() {
+    let mut first = context::Context::mock_sqlite().await;
+    first.rate_limits =
+        context::rate_limit::from_config("database", context::clone_connection(&first.db));
+
+    // A second server over the same database, with a backend of its own: only
+    // the rows both write to stand between them.
+    let mut second = first.clone();
+    second.rate_limits =
+        context::rate_limit::from_config("database", context::clone_connection(&first.db));
+
+    let first_app = test::init_service(server::app(first.clone())).await;
+    let second_app = test::init_service(server::app(second.clone())).await;
+
+    let email = "rl-replicas@example.com";
+    helpers::seed_legacy_user(&first.db, email).await;
+    let ip = "10.8.0.1";
+
+    for _ in 0..IDENTITY_LIMIT {
+        assert_ne!(
+            legacy_login(&first_app, email, "wrong-password", ip).await,
+            StatusCode::TOO_MANY_REQUESTS
+        );
+    }
+
+    assert_eq!(
+        legacy_login(&second_app, email, helpers::LEGACY_PASSWORD, ip).await,
+        StatusCode::TOO_MANY_REQUESTS
+    );
 }
 
 async fn drive_past_limit(app: &impl TestApp, email: &str, ip: &str) -> (StatusCode, Value) {
@@ -230,3 +263,23 @@ async fn unknown_and_known_emails_are_indistinguishable_at_the_limit() {
     assert_eq!(known_body, unknown_body);
     let _ = context;
 }
+
+#[actix_web::test]
+async fn concurrent_failures_cannot_overshoot_the_database_window() {
+    use auth::test_support::auth_rate_limit_attempt as attempt;
+
+    let mut context = context::Context::mock_sqlite().await;
+    context.rate_limits =
+        context::rate_limit::from_config("database", context::clone_connection(&context.db));
+    let id = Some("rl-concurrent");
+    let ip = "10.9.0.1";
+
+    let attempts = (0..IDENTITY_LIMIT * 3).map(|_| attempt(&context, id, ip, 0));
+    let admitted = futures::future::join_all(attempts)
+        .await
+        .into_iter()
+        .filter(Result::is_ok)
+        .count();
+
+    assert_eq!(admitted, IDENTITY_LIMIT);
+}
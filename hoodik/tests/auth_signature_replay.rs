@@ -11,10 +11,7 @@
 mod helpers;
 
 use actix_web::{http::StatusCode, test};
-use auth::{
-    data::signature::Signature,
-    mock::generate_fingerprint_nonce,
-};
+use auth::{data::signature::Signature, mock::generate_fingerprint_nonce};
 use hoodik::server;
 
 /// The wire format upgraded clients sign, pinned independently of the server's
@@ -0,0 +1,199 @@
+//! Backup of a whole instance and restore into an empty one.
+
+use chrono::Utc;
+use context::Context;
+use entity::{
+    files, users, ColumnTrait, ConnectionTrait, EntityTrait, Expr, QueryFilter, QueryOrder,
+    Statement, Uuid,
+};
+use fs::prelude::*;
+use hoodik::backup::{backup, restore, EXCLUDED};
+use storage::mock::create_file;
+
+/// Backup and restore walk the whole data directory, so each context gets
+/// one of its own.
+async fn context_with_own_data_dir() -> Context {
+    let mut context = Context::mock_sqlite().await;
+    context.config.app.data_dir = temp_dir("backup-data");
+
+    context
+}
+
+fn temp_dir(name: &str) -> String {
+    let dir = std::env::temp_dir().join(format!("hoodik-{}-{}", name, Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir.to_string_lossy().to_string()
+}
+
+async fn finished(context: &Context, id: Uuid, at: i64) -> files::Model {
+    files::Entity::update_many()
+        .col_expr(files::Column::CreatedAt, Expr::value(at))
+        .col_expr(files::Column::FinishedUploadAt, Expr::value(at))
+        .filter(files::Column::Id.eq(id))
+        .exec(&context.db)
+        .await
+        .unwrap();
+
+    files::Entity::find_by_id(id)
+        .one(&context.db)
+        .await
+        .unwrap()
+        .unwrap()
+}
+
+async fn all_files(context: &Context) -> Vec<files::Model> {
+    files::Entity::find()
+        .order_by_asc(files::Column::Id)
+        .all(&context.db)
+        .await
+        .unwrap()
+}
+
+#[actix_web::test]
+async fn backs_up_incrementally_and_restores() {
+    let context = context_with_own_data_dir().await;
+    let user = entity::mock::create_user(&context.db, "first@test.com", None).await;
+    let storage = Fs::new(&context.config);
+    let yesterday = Utc::now().timestamp() - 86_400;
+    // Ahead of any backup started during the test, whatever the clock says.
+    let later = Utc::now().timestamp() + 60;
+
+    let folder = create_file(&context, &user, "folder", None, Some("dir"))
+        .await
+        .unwrap();
+    let old = create_file(
+        &context,
+        &user,
+        "old.json",
+        Some(folder.id),
+        Some("application/json"),
+    )
+    .await
+    .unwrap();
+    let old = finished(&context, old.id, yesterday).await;
+    storage.push(&old, 0, b"old-0").await.unwrap();
+    storage.push(&old, 1, b"old-1").await.unwrap();
+
+    std::fs::write(
+        format!("{}/settings.json", context.config.app.data_dir),
+        br#"{"users":{}}"#,
+    )
+    .unwrap();
+
+    let path = temp_dir("backup");
+    let first = backup(&context, path.as_ref(), false).await.unwrap();
+
+    assert_eq!(first.manifest.since, None);
+    assert_eq!(first.manifest.tables["files"], 2);
+    assert_eq!(first.manifest.tables["users"], 1);
+    assert_eq!(first.copied.files, 1);
+    assert_eq!(first.copied.chunks, 2);
+
+    // Only files uploaded since the previous backup are looked at again,
+    // and the chunks of a deleted file are dropped from the backup.
+    let new = create_file(&context, &user, "new.md", None, Some("text/markdown"))
+        .await
+        .unwrap();
+    let new = finished(&context, new.id, later).await;
+    storage.push(&new, 0, b"new-0").await.unwrap();
+
+    let gone = create_file(&context, &user, "gone.md", None, Some("text/markdown"))
+        .await
+        .unwrap();
+    let gone = finished(&context, gone.id, later).await;
+    storage.push(&gone, 0, b"gone-0").await.unwrap();
+    let between = backup(&context, path.as_ref(), false).await.unwrap();
+    assert_eq!(between.copied.files, 2);
+    files::Entity::delete_by_id(gone.id)
+        .exec(&context.db)
+        .await
+        .unwrap();
+
+    let second = backup(&context, path.as_ref(), false).await.unwrap();
+
+    assert_eq!(second.manifest.since, Some(between.manifest.started_at));
+    assert_eq!(second.manifest.tables["files"], 3);
+    assert_eq!(second.copied.files, 1);
+    assert_eq!(second.copied.chunks, 0);
+    assert_eq!(second.copied.skipped, 1);
+    assert_eq!(second.pruned, 1);
+
+    let full = backup(&context, path.as_ref(), true).await.unwrap();
+    assert_eq!(full.manifest.since, None);
+    assert_eq!(full.copied.files, 2);
+    assert_eq!(full.copied.skipped, 3);
+
+    let restored = context_with_own_data_dir().await;
+    let summary = restore(&restored, path.as_ref()).await.unwrap();
+
+    assert_eq!(summary.rows, full.rows);
+    assert_eq!(all_files(&restored).await, all_files(&context).await);
+    assert_eq!(
+        users::Entity::find().all(&restored.db).await.unwrap(),
+        users::Entity::find().all(&context.db).await.unwrap()
+    );
+
+    let restored_storage = Fs::new(&restored.config);
+    assert_eq!(restored_storage.pull(&old, 0).await.unwrap(), b"old-0");
+    assert_eq!(restored_storage.pull(&old, 1).await.unwrap(), b"old-1");
+    assert_eq!(restored_storage.pull(&new, 0).await.unwrap(), b"new-0");
+    assert!(!restored_storage.exists(&gone, 0).await.unwrap());
+    assert_eq!(
+        std::fs::read(format!("{}/settings.json", restored.config.app.data_dir)).unwrap(),
+        br#"{"users":{}}"#
+    );
+
+    // Restore never merges into an instance that is already in use.
+    assert!(restore(&restored, path.as_ref()).await.is_err());
+
+    for dir in [
+        path,
+        context.config.app.data_dir.clone(),
+        restored.config.app.data_dir.clone(),
+    ] {
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
+
+#[actix_web::test]
+async fn restore_needs_a_finished_backup() {
+    let context = context_with_own_data_dir().await;
+    let path = temp_dir("backup-empty");
+
+    assert!(restore(&context, path.as_ref()).await.is_err());
+
+    for dir in [path, context.config.app.data_dir.clone()] {
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
+
+#[actix_web::test]
+async fn every_table_is_backed_up_or_excluded() {
+    let context = context_with_own_data_dir().await;
+    let target = temp_dir("backup-tables");
+
+    let summary = backup(&context, target.as_ref(), false).await.unwrap();
+
+    let rows = context
+        .db
+        .query_all(Statement::from_string(
+            context.db.get_database_backend(),
+            "SELECT name FROM sqlite_master WHERE type = 'table' \
+             AND name NOT LIKE 'sqlite_%' AND name <> 'seaql_migrations'",
+        ))
+        .await
+        .unwrap();
+
+    let missing = rows
+        .iter()
+        .map(|row| row.try_get::<String>("", "name").unwrap())
+        .filter(|name| {
+            !summary.manifest.tables.contains_key(name) && !EXCLUDED.contains(&name.as_str())
+        })
+        .collect::<Vec<_>>();
+
+    assert!(
+        missing.is_empty(),
+        "tables left out of the backup: {missing:?}"
+    );
+}
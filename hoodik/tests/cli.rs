@@ -0,0 +1,195 @@
+//! `hoodik-cli` against a running instance, over real HTTP.
+
+#[path = "./helpers.rs"]
+mod helpers;
+
+use std::{net::TcpListener, path::Path, str::FromStr};
+
+use actix_web::{test, HttpServer};
+use client::Session;
+use cryptfns::cipher::Cipher;
+use hoodik::server;
+
+async fn cli(base_url: &str, args: &[&str]) -> client::Result<()> {
+    let matches = cli::command()
+        .try_get_matches_from(
+            [
+                "hoodik-cli",
+                "--url",
+                base_url,
+                "--email",
+                "cli@test.com",
+                "--password",
+                helpers::LEGACY_PASSWORD,
+            ]
+            .iter()
+            .chain(args),
+        )
+        .unwrap();
+
+    cli::run(&matches).await
+}
+
+fn write(path: &Path, content: &[u8]) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, content).unwrap();
+}
+
+#[actix_web::test]
+async fn uploads_downloads_and_shares_with_a_password_login() {
+    let context = context::Context::mock_with_data_dir(Some("../data-test-cli".to_string())).await;
+    let app = test::init_service(server::app(context.clone())).await;
+
+    helpers::register_curve25519(&app, "cli@test.com").await;
+    helpers::register_curve25519(&app, "recipient@test.com").await;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let server_context = context.clone();
+    let server = HttpServer::new(move || server::app(server_context.clone()))
+        .listen(listener)
+        .unwrap()
+        .run();
+    actix_web::rt::spawn(server);
+
+    let local = std::env::temp_dir().join(format!("hoodik-cli-{}", entity::Uuid::new_v4()));
+    let big: Vec<u8> = (0..5 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+    write(&local.join("build/notes.txt"), b"release notes");
+    write(&local.join("build/bin/app.bin"), &big);
+    write(&local.join("build/empty"), b"");
+
+    let build = local.join("build");
+    cli(
+        &base_url,
+        &["upload", build.to_str().unwrap(), "/ci/artifacts"],
+    )
+    .await
+    .unwrap();
+
+    let session =
+        Session::login_with_password(&base_url, "cli@test.com", helpers::LEGACY_PASSWORD, None)
+            .await
+            .unwrap();
+    let app_bin = session
+        .find("/ci/artifacts/build/bin/app.bin")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(app_bin.size, big.len() as u64);
+    assert_eq!(app_bin.chunks, 2);
+    assert!(session
+        .find("/ci/artifacts/build/empty")
+        .await
+        .unwrap()
+        .is_none());
+
+    let uploaded = cli(
+        &base_url,
+        &["upload", build.to_str().unwrap(), "/ci/artifacts"],
+    )
+    .await;
+    assert!(uploaded.is_err(), "existing files are not overwritten");
+
+    write(&local.join("build/notes.txt"), b"updated release notes");
+    cli(
+        &base_url,
+        &[
+            "upload",
+            "--replace",
+            build.to_str().unwrap(),
+            "/ci/artifacts",
+        ],
+    )
+    .await
+    .unwrap();
+
+    let out = local.join("out");
+    std::fs::create_dir_all(&out).unwrap();
+    cli(
+        &base_url,
+        &["download", "/ci/artifacts/build", out.to_str().unwrap()],
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        std::fs::read(out.join("build/notes.txt")).unwrap(),
+        b"updated release notes"
+    );
+    assert_eq!(std::fs::read(out.join("build/bin/app.bin")).unwrap(), big);
+
+    cli(&base_url, &["mkdir", "/ci/releases"]).await.unwrap();
+    cli(
+        &base_url,
+        &[
+            "mv",
+            "/ci/artifacts/build/notes.txt",
+            "/ci/releases/NOTES.txt",
+        ],
+    )
+    .await
+    .unwrap();
+    let notes = session
+        .find("/ci/releases/NOTES.txt")
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(session
+        .find("/ci/artifacts/build/notes.txt")
+        .await
+        .unwrap()
+        .is_none());
+
+    cli(
+        &base_url,
+        &[
+            "share",
+            "/ci/releases",
+            "recipient@test.com",
+            "--role",
+            "editor",
+        ],
+    )
+    .await
+    .unwrap();
+    let recipient = Session::login_with_password(
+        &base_url,
+        "recipient@test.com",
+        helpers::LEGACY_PASSWORD,
+        None,
+    )
+    .await
+    .unwrap();
+    let shared = recipient.metadata(&notes.id).await.unwrap();
+    assert_eq!(shared.name, "NOTES.txt");
+    assert_eq!(shared.key, notes.key);
+
+    cli(&base_url, &["link", "create", "/ci/releases/NOTES.txt"])
+        .await
+        .unwrap();
+    let link = session
+        .create_link(&notes, &Default::default())
+        .await
+        .unwrap();
+    let link_key = cryptfns::hex::decode(link.url.split_once('#').unwrap().1).unwrap();
+    let metadata: serde_json::Value =
+        reqwest::get(format!("{base_url}/api/links/{}/metadata", link.id))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+    let name = Cipher::from_str("ascon128a")
+        .unwrap()
+        .decrypt_string(
+            link_key,
+            cryptfns::hex::decode(metadata["encrypted_name"].as_str().unwrap()).unwrap(),
+        )
+        .unwrap();
+    assert_eq!(name, b"NOTES.txt");
+
+    cli(&base_url, &["rm", "/ci/artifacts"]).await.unwrap();
+    assert!(session.find("/ci/artifacts").await.unwrap().is_none());
+
+    std::fs::remove_dir_all(&local).unwrap();
+    context.config.app.cleanup();
+}
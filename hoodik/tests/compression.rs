@@ -64,12 +64,17 @@ async fn test_json_compresses_and_ciphertext_stays_identity() {
         .cookie(jwt.clone())
         .set_json(&create)
         .to_request();
-    let file: AppFile =
-        serde_json::from_slice(&test::call_and_read_body(&app, req).await).unwrap();
+    let file: AppFile = serde_json::from_slice(&test::call_and_read_body(&app, req).await).unwrap();
 
     let chunk_checksum = cryptfns::sha256::digest(chunk.as_slice());
     let req = test::TestRequest::post()
-        .uri(format!("/api/storage/{}?checksum={}&chunk=0", &file.id, chunk_checksum).as_str())
+        .uri(
+            format!(
+                "/api/storage/{}?checksum={}&chunk=0",
+                &file.id, chunk_checksum
+            )
+            .as_str(),
+        )
         .cookie(jwt.clone())
         .append_header(("Content-Type", "application/octet-stream"))
         .set_payload(chunk.clone())
@@ -0,0 +1,167 @@
+//! The WebDAV gateway against a running instance, over real HTTP.
+
+#[path = "./helpers.rs"]
+mod helpers;
+
+use std::{net::TcpListener, time::Duration};
+
+use actix_web::{test, HttpServer};
+use client::{KeyPair, Session};
+use hoodik::server;
+use reqwest::{Method, StatusCode};
+
+const USERNAME: &str = "dav";
+const PASSWORD: &str = "secret";
+
+#[actix_web::test]
+async fn serves_the_account_over_webdav() {
+    let context = context::Context::mock_with_data_dir(Some("../data-test-dav".to_string())).await;
+    let app = test::init_service(server::app(context.clone())).await;
+
+    let ed_private = cryptfns::ed25519::private::generate().unwrap();
+    let x_private = cryptfns::ecdh::private::generate().unwrap();
+    let body = helpers::build_curve25519_register_body_with_keys(
+        &app,
+        "dav@test.com",
+        &ed_private,
+        &x_private,
+    )
+    .await;
+    let req = test::TestRequest::post()
+        .uri("/api/auth/register")
+        .set_json(&body)
+        .to_request();
+    assert!(test::call_service(&app, req).await.status().is_success());
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let server_context = context.clone();
+    let server = HttpServer::new(move || server::app(server_context.clone()))
+        .listen(listener)
+        .unwrap()
+        .run();
+    actix_web::rt::spawn(server);
+
+    let keys = KeyPair::from_material(&format!("v1|ed:{ed_private}|x:{x_private}")).unwrap();
+    let session = Session::login_with_key(&base_url, keys).await.unwrap();
+    assert_eq!(session.user().email, "dav@test.com");
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let dav_url = format!("http://{}", listener.local_addr().unwrap());
+    let gateway = dav::serve(
+        dav::handler(session.clone(), Duration::from_secs(5)),
+        listener,
+        Some(dav::Credentials {
+            username: USERNAME.to_string(),
+            password: PASSWORD.to_string(),
+        }),
+    );
+    actix_web::rt::spawn(gateway);
+
+    let http = reqwest::Client::new();
+    let request = |method: &str, path: &str| {
+        http.request(
+            Method::from_bytes(method.as_bytes()).unwrap(),
+            format!("{dav_url}{path}"),
+        )
+        .basic_auth(USERNAME, Some(PASSWORD))
+    };
+
+    let response = http.get(format!("{dav_url}/")).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = request("MKCOL", "/docs").send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    // Spans two chunks, so reads have to cross a chunk boundary.
+    let content: Vec<u8> = (0..5 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+    let response = request("PUT", "/docs/hello%20world.txt")
+        .body(content.clone())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let response = request("PROPFIND", "/docs")
+        .header("Depth", "1")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::MULTI_STATUS);
+    let listing = response.text().await.unwrap();
+    assert!(listing.contains("hello%20world.txt"), "{listing}");
+    assert!(listing.contains(&format!("<D:getcontentlength>{}<", content.len())));
+
+    let response = request("GET", "/docs/hello%20world.txt")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.bytes().await.unwrap().as_ref(), content.as_slice());
+
+    let response = request("GET", "/docs/hello%20world.txt")
+        .header("Range", "bytes=4194300-4194310")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(
+        response.bytes().await.unwrap().as_ref(),
+        &content[4194300..=4194310]
+    );
+
+    let response = request("PUT", "/docs/hello%20world.txt")
+        .body("replaced")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let response = request("MOVE", "/docs/hello%20world.txt")
+        .header("Destination", format!("{dav_url}/renamed.txt"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let response = request("GET", "/docs/hello%20world.txt")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = request("GET", "/renamed.txt").send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text().await.unwrap(), "replaced");
+
+    // Everything the server holds is encrypted, the names only make sense
+    // to a client with the keys.
+    let root = session.list(None).await.unwrap();
+    let mut names: Vec<&str> = root.iter().map(|entry| entry.name.as_str()).collect();
+    names.sort();
+    assert_eq!(names, vec!["docs", "renamed.txt"]);
+    let docs = root.iter().find(|entry| entry.name == "docs").unwrap();
+    assert!(session.list(Some(&docs.id)).await.unwrap().is_empty());
+
+    let response = request("PUT", "/empty.txt").send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let response = request("GET", "/empty.txt").send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.bytes().await.unwrap().is_empty());
+
+    for path in ["/docs", "/renamed.txt", "/empty.txt"] {
+        let response = request("DELETE", path).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT, "{path}");
+    }
+
+    let response = request("PROPFIND", "/")
+        .header("Depth", "1")
+        .send()
+        .await
+        .unwrap();
+    let listing = response.text().await.unwrap();
+    assert!(!listing.contains("renamed.txt"), "{listing}");
+    assert!(!listing.contains("docs"), "{listing}");
+
+    context.config.app.cleanup();
+}
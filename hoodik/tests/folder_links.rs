@@ -0,0 +1,243 @@
+//! Public links on a whole folder: every file under it is listed in the link
+//! metadata and can be downloaded through the link, nothing outside it can.
+
+#[path = "./helpers.rs"]
+mod helpers;
+
+use actix_web::{cookie::Cookie, http::StatusCode, test};
+use entity::{files, ActiveValue, EntityTrait, Uuid};
+use hoodik::server;
+use links::data::{
+    app_link::AppLink,
+    create_link::{CreateLink, CreateLinkFile},
+};
+use serde_json::{json, Value};
+use storage::data::{app_file::AppFile, create_file::CreateFile};
+
+async fn login(app: &impl helpers::TestApp) -> Cookie<'static> {
+    let req = test::TestRequest::post()
+        .uri("/api/auth/login")
+        .set_json(json!({ "email": "owner@example.com", "password": helpers::LEGACY_PASSWORD }))
+        .to_request();
+    let (jwt, _) = helpers::extract_cookies(test::call_service(app, req).await.headers());
+
+    jwt.unwrap()
+}
+
+/// Create a directory, or a one-chunk file with `contents`, under `parent`.
+async fn create(
+    app: &impl helpers::TestApp,
+    jwt: &Cookie<'static>,
+    name: &str,
+    parent: Option<Uuid>,
+    contents: Option<&[u8]>,
+) -> AppFile {
+    let create_file = CreateFile {
+        encrypted_key: Some("encrypted-key".to_string()),
+        encrypted_name: Some(name.to_string()),
+        encrypted_thumbnail: None,
+        search_tokens_hashed: None,
+        name_hash: Some(format!("{name}-hash")),
+        mime: Some(
+            if contents.is_some() {
+                "text/plain"
+            } else {
+                "dir"
+            }
+            .to_string(),
+        ),
+        size: contents.map(|c| c.len() as i64),
+        chunks: contents.map(|_| 1),
+        file_id: parent.map(|id| id.to_string()),
+        file_modified_at: None,
+        md5: None,
+        sha1: None,
+        sha256: None,
+        blake2b: None,
+        cipher: None,
+        editable: None,
+    };
+    let req = test::TestRequest::post()
+        .uri("/api/storage")
+        .cookie(jwt.clone())
+        .set_json(&create_file)
+        .to_request();
+    let file: AppFile = serde_json::from_slice(&test::call_and_read_body(app, req).await).unwrap();
+
+    if let Some(contents) = contents {
+        let checksum = cryptfns::sha256::digest(contents);
+        let req = test::TestRequest::post()
+            .uri(&format!(
+                "/api/storage/{}?checksum={checksum}&chunk=0",
+                file.id
+            ))
+            .cookie(jwt.clone())
+            .append_header(("Content-Type", "application/octet-stream"))
+            .set_payload(contents.to_vec())
+            .to_request();
+        assert_eq!(test::call_service(app, req).await.status(), StatusCode::OK);
+    }
+
+    file
+}
+
+fn link_file(file: &AppFile) -> CreateLinkFile {
+    CreateLinkFile {
+        file_id: Some(file.id.to_string()),
+        encrypted_name: Some(format!("link-{}", file.id)),
+        encrypted_file_key: (!file.is_dir()).then(|| format!("key-{}", file.id)),
+    }
+}
+
+fn create_link(
+    folder: &AppFile,
+    rsa_private: &str,
+    files: Option<Vec<CreateLinkFile>>,
+) -> CreateLink {
+    CreateLink {
+        file_id: Some(folder.id.to_string()),
+        signature: Some(cryptfns::rsa::private::sign(&folder.id.to_string(), rsa_private).unwrap()),
+        encrypted_name: Some("encrypted-name".to_string()),
+        encrypted_link_key: Some("encrypted-link-key".to_string()),
+        encrypted_thumbnail: None,
+        encrypted_file_key: None,
+        expires_at: None,
+        max_downloads: None,
+        password: None,
+        files,
+    }
+}
+
+async fn post_link(
+    app: &impl helpers::TestApp,
+    jwt: &Cookie<'static>,
+    create_link: CreateLink,
+) -> (StatusCode, Vec<u8>) {
+    let req = test::TestRequest::post()
+        .uri("/api/links")
+        .cookie(jwt.clone())
+        .set_json(create_link)
+        .to_request();
+    let resp = test::call_service(app, req).await;
+    let status = resp.status();
+
+    (status, test::read_body(resp).await.to_vec())
+}
+
+async fn download(app: &impl helpers::TestApp, uri: &str) -> (StatusCode, Vec<u8>) {
+    let req = test::TestRequest::post().uri(uri).to_request();
+    let resp = test::call_service(app, req).await;
+    let status = resp.status();
+
+    (status, test::read_body(resp).await.to_vec())
+}
+
+#[actix_web::test]
+async fn test_folder_link_lists_and_serves_only_files_under_the_folder() {
+    let context =
+        context::Context::mock_with_data_dir(Some("../data-test-folder-links".to_string())).await;
+    let app = test::init_service(server::app(context.clone())).await;
+
+    let owner = helpers::seed_legacy_user(&context.db, "owner@example.com").await;
+    let jwt = login(&app).await;
+
+    let folder = create(&app, &jwt, "folder", None, None).await;
+    let nested = create(&app, &jwt, "nested", Some(folder.id), None).await;
+    let top = create(&app, &jwt, "top", Some(folder.id), Some(b"top-chunk")).await;
+    let deep = create(&app, &jwt, "deep", Some(nested.id), Some(b"deep-chunk")).await;
+    let outside = create(&app, &jwt, "outside", None, Some(b"outside-chunk")).await;
+
+    // A folder link has to carry the files under it, and only those.
+    let (status, _) = post_link(&app, &jwt, create_link(&folder, &owner.rsa_private, None)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let (status, body) = post_link(
+        &app,
+        &jwt,
+        create_link(
+            &folder,
+            &owner.rsa_private,
+            Some(vec![link_file(&top), link_file(&outside)]),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(
+        body["message"],
+        format!("link_file_not_in_folder:{}", outside.id)
+    );
+
+    let (status, body) = post_link(
+        &app,
+        &jwt,
+        create_link(
+            &folder,
+            &owner.rsa_private,
+            Some(vec![link_file(&nested), link_file(&top), link_file(&deep)]),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let link: AppLink = serde_json::from_slice(&body).unwrap();
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/links/{}/metadata", link.id))
+        .to_request();
+    let metadata: AppLink =
+        serde_json::from_slice(&test::call_and_read_body(&app, req).await).unwrap();
+    let files = metadata.files.unwrap();
+    assert_eq!(files.len(), 3);
+    let listed_deep = files.iter().find(|file| file.id == deep.id).unwrap();
+    assert_eq!(listed_deep.parent_id, Some(nested.id));
+    assert_eq!(listed_deep.encrypted_name, format!("link-{}", deep.id));
+    assert_eq!(
+        listed_deep.encrypted_file_key,
+        Some(format!("key-{}", deep.id))
+    );
+
+    let (status, body) = download(
+        &app,
+        &format!("/api/links/{}?chunk=0&file_id={}", link.id, deep.id),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, b"deep-chunk");
+
+    // The folder itself needs a file picked, and nothing outside it is served.
+    let (status, _) = download(&app, &format!("/api/links/{}?chunk=0", link.id)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = download(
+        &app,
+        &format!("/api/links/{}?chunk=0&file_id={}", link.id, outside.id),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // A file moved out of the folder after the link was created drops out.
+    files::Entity::update(files::ActiveModel {
+        id: ActiveValue::Unchanged(top.id),
+        file_id: ActiveValue::Set(None),
+        ..Default::default()
+    })
+    .exec(&context.db)
+    .await
+    .unwrap();
+
+    let (status, _) = download(
+        &app,
+        &format!("/api/links/{}?chunk=0&file_id={}", link.id, top.id),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/links/{}/metadata", link.id))
+        .to_request();
+    let metadata: AppLink =
+        serde_json::from_slice(&test::call_and_read_body(&app, req).await).unwrap();
+    assert_eq!(metadata.files.unwrap().len(), 2);
+
+    context.config.app.cleanup();
+}
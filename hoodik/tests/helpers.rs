@@ -66,6 +66,8 @@ pub(crate) async fn seed_legacy_user<C: entity::ConnectionTrait>(
         created_at: ActiveValue::Set(now),
         updated_at: ActiveValue::Set(now),
         share_notifications_enabled: ActiveValue::Set(true),
+        login_alerts_enabled: ActiveValue::Set(true),
+        password_score: ActiveValue::Set(None),
     })
     .exec_without_returning(db)
     .await
@@ -128,6 +130,10 @@ pub(crate) async fn seed_owned_files<C: entity::ConnectionTrait>(
             members_list_signature: ActiveValue::Set(None),
             members_list_signed_at: ActiveValue::Set(None),
             members_list_signed_by_user_id: ActiveValue::Set(None),
+            deleted_at: ActiveValue::Set(None),
+            deleted_parent_id: ActiveValue::Set(None),
+            last_chunk_at: ActiveValue::Set(None),
+            damaged_at: ActiveValue::Set(None),
         });
 
         user_file_rows.push(user_files::ActiveModel {
@@ -206,16 +212,30 @@ pub(crate) struct RegisteredCurve {
 pub(crate) async fn build_curve25519_register_body(
     app: &impl TestApp,
     email: &str,
+) -> serde_json::Value {
+    let ed_private = cryptfns::ed25519::private::generate().unwrap();
+    let x_private = cryptfns::ecdh::private::generate().unwrap();
+
+    build_curve25519_register_body_with_keys(app, email, &ed_private, &x_private).await
+}
+
+/// [`build_curve25519_register_body`] for keys the test holds on to, e.g.
+/// to sign in with them the way a native client would.
+#[allow(dead_code)]
+pub(crate) async fn build_curve25519_register_body_with_keys(
+    app: &impl TestApp,
+    email: &str,
+    ed_private: &str,
+    x_private: &str,
 ) -> serde_json::Value {
     use actix_web::test;
 
-    let ed_private = cryptfns::ed25519::private::generate().unwrap();
-    let ed_public = cryptfns::ed25519::public::from_private(&ed_private).unwrap();
+    let ed_public = cryptfns::ed25519::public::from_private(ed_private).unwrap();
     let fingerprint = cryptfns::spki::fingerprint(&ed_public).unwrap();
-    let x_private = cryptfns::ecdh::private::generate().unwrap();
-    let x_public = cryptfns::ecdh::public::from_private(&x_private).unwrap();
+    let x_public = cryptfns::ecdh::public::from_private(x_private).unwrap();
 
-    let reg_start = cryptfns::opaque::client_registration_start(LEGACY_PASSWORD.as_bytes()).unwrap();
+    let reg_start =
+        cryptfns::opaque::client_registration_start(LEGACY_PASSWORD.as_bytes()).unwrap();
     let req = test::TestRequest::post()
         .uri("/api/auth/register/pake/start")
         .set_json(serde_json::json!({ "email": email, "registration_request": reg_start.message }))
@@ -231,11 +251,9 @@ pub(crate) async fn build_curve25519_register_body(
 
     let export_key = cryptfns::base64::decode(&reg_finish.export_key).unwrap();
     let kek = cryptfns::envelope::derive_kek(&export_key).unwrap();
-    let envelope = cryptfns::envelope::seal(
-        &kek,
-        format!("v1|ed:{ed_private}|x:{x_private}").as_bytes(),
-    )
-    .unwrap();
+    let envelope =
+        cryptfns::envelope::seal(&kek, format!("v1|ed:{ed_private}|x:{x_private}").as_bytes())
+            .unwrap();
 
     serde_json::json!({
         "email": email,
@@ -253,14 +271,34 @@ pub(crate) async fn build_curve25519_register_body(
 /// user id and session cookie for tests that just need a working account.
 #[allow(dead_code)]
 pub(crate) async fn register_curve25519(app: &impl TestApp, email: &str) -> RegisteredCurve {
+    let ed_private = cryptfns::ed25519::private::generate().unwrap();
+    let x_private = cryptfns::ecdh::private::generate().unwrap();
+
+    register_curve25519_with_keys(app, email, &ed_private, &x_private).await
+}
+
+/// [`register_curve25519`] for keys the test holds on to, e.g. to register
+/// the same person on a second instance.
+#[allow(dead_code)]
+pub(crate) async fn register_curve25519_with_keys(
+    app: &impl TestApp,
+    email: &str,
+    ed_private: &str,
+    x_private: &str,
+) -> RegisteredCurve {
     use actix_web::test;
 
+    let body = build_curve25519_register_body_with_keys(app, email, ed_private, x_private).await;
     let req = test::TestRequest::post()
         .uri("/api/auth/register")
-        .set_json(&build_curve25519_register_body(app, email).await)
+        .set_json(&body)
         .to_request();
     let resp = test::call_service(app, req).await;
-    assert!(resp.status().is_success(), "register {email} failed: {:?}", resp.status());
+    assert!(
+        resp.status().is_success(),
+        "register {email} failed: {:?}",
+        resp.status()
+    );
     let (jwt, _) = extract_cookies(resp.headers());
     let jwt = jwt.expect("register response missing JWT cookie");
     let body: serde_json::Value = test::read_body_json(resp).await;
@@ -0,0 +1,202 @@
+//! Background housekeeping jobs.
+//!
+//! Each purge job deletes only the rows that stopped being useful, every run
+//! is recorded in `job_runs`, and the scheduler only starts jobs that are due.
+
+use chrono::{Duration, Utc};
+use context::Context;
+use entity::{
+    files, invitations, job_runs, links, sessions, used_nonces, ActiveValue, ColumnTrait,
+    EntityTrait, Expr, PaginatorTrait, QueryFilter, Uuid,
+};
+use fs::prelude::*;
+
+async fn create_link(context: &Context, user: &entity::users::Model, expires_at: Option<i64>) {
+    let (file, _) =
+        entity::mock::create_file(&context.db, user, "file", "application/json", None).await;
+
+    links::Entity::insert(links::ActiveModel {
+        id: ActiveValue::Set(Uuid::new_v4()),
+        user_id: ActiveValue::Set(user.id),
+        file_id: ActiveValue::Set(file.id),
+        signature: ActiveValue::Set("signature".to_string()),
+        downloads: ActiveValue::Set(0),
+        encrypted_name: ActiveValue::Set("name".to_string()),
+        encrypted_link_key: ActiveValue::Set("link-key".to_string()),
+        encrypted_thumbnail: ActiveValue::Set(None),
+        encrypted_file_key: ActiveValue::Set(Some("file-key".to_string())),
+        created_at: ActiveValue::Set(Utc::now().timestamp()),
+        expires_at: ActiveValue::Set(expires_at),
+        max_downloads: ActiveValue::Set(None),
+        password_hash: ActiveValue::Set(None),
+    })
+    .exec_without_returning(&context.db)
+    .await
+    .unwrap();
+}
+
+#[actix_web::test]
+async fn purge_expired_links_keeps_live_ones() {
+    let context = Context::mock_sqlite().await;
+    let user = entity::mock::create_user(&context.db, "links@test.com", None).await;
+
+    let now = Utc::now().timestamp();
+    create_link(&context, &user, Some(now - 60)).await;
+    create_link(&context, &user, Some(now + 3600)).await;
+    create_link(&context, &user, None).await;
+
+    let purged = hoodik::jobs::run(&context, "purge-expired-links")
+        .await
+        .unwrap();
+
+    assert_eq!(purged, 1);
+    assert_eq!(links::Entity::find().count(&context.db).await.unwrap(), 2);
+
+    let status = job_runs::Entity::find_by_id("purge-expired-links".to_string())
+        .one(&context.db)
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(status.last_status.as_deref(), Some("ok"));
+    assert_eq!(status.last_affected, Some(1));
+    assert!(status.next_run_at > now);
+}
+
+#[actix_web::test]
+async fn stale_sessions_and_invitations_outlive_the_history_retention() {
+    let context = Context::mock_sqlite().await;
+    let user = entity::mock::create_user(&context.db, "sessions@test.com", None).await;
+
+    // Expired yesterday: still part of the activity history.
+    entity::mock::create_session(&context.db, &user, None, None, true).await;
+
+    let stale = entity::mock::create_session(&context.db, &user, None, None, true).await;
+    let long_ago = (Utc::now() - Duration::days(90)).timestamp();
+    sessions::Entity::update(sessions::ActiveModel {
+        id: ActiveValue::Unchanged(stale.id),
+        expires_at: ActiveValue::Set(long_ago),
+        ..Default::default()
+    })
+    .exec(&context.db)
+    .await
+    .unwrap();
+
+    let invitation = entity::mock::create_invitation(&context.db, "invited@test.com").await;
+    invitations::Entity::update(invitations::ActiveModel {
+        id: ActiveValue::Unchanged(invitation.id),
+        expires_at: ActiveValue::Set(long_ago),
+        ..Default::default()
+    })
+    .exec(&context.db)
+    .await
+    .unwrap();
+
+    assert_eq!(
+        hoodik::jobs::run(&context, "purge-stale-sessions")
+            .await
+            .unwrap(),
+        1
+    );
+    assert_eq!(
+        sessions::Entity::find().count(&context.db).await.unwrap(),
+        1
+    );
+
+    assert_eq!(
+        hoodik::jobs::run(&context, "purge-expired-invitations")
+            .await
+            .unwrap(),
+        1
+    );
+    assert_eq!(
+        invitations::Entity::find()
+            .count(&context.db)
+            .await
+            .unwrap(),
+        0
+    );
+}
+
+#[actix_web::test]
+async fn reaper_records_reclaimed_bytes() {
+    let context = Context::mock_sqlite().await;
+    let user = entity::mock::create_user(&context.db, "reaper@test.com", None).await;
+
+    let (file, _) =
+        entity::mock::create_file(&context.db, &user, "upload", "application/json", None).await;
+    let idle_since = (Utc::now() - Duration::days(2)).timestamp();
+    files::Entity::update_many()
+        .col_expr(files::Column::FinishedUploadAt, Expr::value(None::<i64>))
+        .col_expr(files::Column::CreatedAt, Expr::value(idle_since))
+        .col_expr(files::Column::LastChunkAt, Expr::value(idle_since))
+        .filter(files::Column::Id.eq(file.id))
+        .exec(&context.db)
+        .await
+        .unwrap();
+    // Chunks are stored under a name derived from `created_at`.
+    let file = files::Entity::find_by_id(file.id)
+        .one(&context.db)
+        .await
+        .unwrap()
+        .unwrap();
+    Fs::new(&context.config)
+        .push(&file, 0, b"partial")
+        .await
+        .unwrap();
+
+    assert_eq!(
+        hoodik::jobs::run(&context, "reap-abandoned-uploads")
+            .await
+            .unwrap(),
+        1
+    );
+
+    let status = job_runs::Entity::find_by_id("reap-abandoned-uploads".to_string())
+        .one(&context.db)
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(status.last_affected, Some(1));
+    assert_eq!(status.last_reclaimed_bytes, Some(100));
+}
+
+#[actix_web::test]
+async fn unknown_job_is_not_found() {
+    let context = Context::mock_sqlite().await;
+
+    let result = hoodik::jobs::run(&context, "no-such-job").await;
+
+    assert!(matches!(result, Err(error::Error::NotFound(_))));
+}
+
+#[actix_web::test]
+async fn scheduler_only_starts_due_jobs() {
+    let context = Context::mock_sqlite().await;
+    let now = Utc::now().timestamp();
+
+    used_nonces::Entity::insert(used_nonces::ActiveModel {
+        fingerprint: ActiveValue::Set("fingerprint".to_string()),
+        nonce: ActiveValue::Set("nonce".to_string()),
+        expires_at: ActiveValue::Set(now - 60),
+    })
+    .exec_without_returning(&context.db)
+    .await
+    .unwrap();
+
+    // Nothing ran yet, so every scheduled job is due.
+    let started = hoodik::jobs::run_due(&context).await.unwrap();
+    assert_eq!(started.len(), hoodik::jobs::registry(&context.config).len());
+    assert_eq!(
+        used_nonces::Entity::find()
+            .count(&context.db)
+            .await
+            .unwrap(),
+        0
+    );
+
+    // Every job just pushed its next run into the future.
+    let started = hoodik::jobs::run_due(&context).await.unwrap();
+    assert!(started.is_empty());
+}
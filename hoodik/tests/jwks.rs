@@ -0,0 +1,163 @@
+//! Session tokens signed with rotating asymmetric keys, verifiable by anyone
+//! holding the published JWKS.
+
+#[path = "./helpers.rs"]
+mod helpers;
+
+use actix_web::{cookie::Cookie, http::StatusCode, test};
+use hoodik::server;
+use jsonwebtoken::{jwk::JwkSet, DecodingKey, Validation};
+use serde_json::Value;
+
+async fn jwks(app: &impl helpers::TestApp) -> JwkSet {
+    let req = test::TestRequest::get()
+        .uri("/.well-known/jwks.json")
+        .to_request();
+
+    test::call_and_read_body_json(app, req).await
+}
+
+fn kid(jwt: &Cookie<'_>) -> String {
+    jsonwebtoken::decode_header(jwt.value())
+        .unwrap()
+        .kid
+        .unwrap()
+}
+
+async fn status_with(app: &impl helpers::TestApp, jwt: Cookie<'static>) -> StatusCode {
+    let req = test::TestRequest::post()
+        .uri("/api/auth/self")
+        .cookie(jwt)
+        .to_request();
+
+    test::call_service(app, req).await.status()
+}
+
+#[actix_web::test]
+async fn test_tokens_verify_against_jwks_across_rotation() {
+    let context = context::Context::mock_sqlite().await;
+    let app = test::init_service(server::app(context.clone())).await;
+    let first = helpers::register_curve25519(&app, "first@example.com").await;
+
+    // A sidecar needs nothing but the published keys to verify a session.
+    let set = jwks(&app).await;
+    assert_eq!(set.keys.len(), 1);
+    let jwk = set
+        .find(&kid(&first.jwt))
+        .expect("signing key is published");
+    let header = jsonwebtoken::decode_header(first.jwt.value()).unwrap();
+    let claims = jsonwebtoken::decode::<Value>(
+        first.jwt.value(),
+        &DecodingKey::from_jwk(jwk).unwrap(),
+        &Validation::new(header.alg),
+    )
+    .unwrap()
+    .claims;
+    assert_eq!(claims["sub"], first.user_id.to_string());
+
+    let new_kid = auth::rotate_jwt_signing_key(&context).await.unwrap();
+    assert_ne!(new_kid.to_string(), kid(&first.jwt));
+
+    // The retired key stays published and keeps its sessions alive.
+    assert_eq!(jwks(&app).await.keys.len(), 2);
+    assert_eq!(status_with(&app, first.jwt.clone()).await, StatusCode::OK);
+
+    let second = helpers::register_curve25519(&app, "second@example.com").await;
+    assert_eq!(kid(&second.jwt), new_kid.to_string());
+    assert_eq!(status_with(&app, second.jwt).await, StatusCode::OK);
+}
+
+#[actix_web::test]
+async fn test_key_rotated_elsewhere_verifies_before_cache_expires() {
+    let context = context::Context::mock_sqlite().await;
+    let app = test::init_service(server::app(context.clone())).await;
+    helpers::register_curve25519(&app, "cached@example.com").await;
+
+    // Another process on the same database, with its own key cache.
+    let mut other = context.clone();
+    other.jwt_keys = context::JwtKeys::default();
+    let new_kid = auth::rotate_jwt_signing_key(&other).await.unwrap();
+    let other_app = test::init_service(server::app(other)).await;
+    let account = helpers::register_curve25519(&other_app, "rotated@example.com").await;
+    assert_eq!(kid(&account.jwt), new_kid.to_string());
+
+    assert_eq!(status_with(&app, account.jwt).await, StatusCode::OK);
+}
+
+#[actix_web::test]
+async fn test_legacy_secret_tokens_are_refused_by_default() {
+    let context = context::Context::mock_sqlite().await;
+    let app = test::init_service(server::app(context.clone())).await;
+    let account = helpers::register_curve25519(&app, "forged@example.com").await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/self")
+        .cookie(account.jwt.clone())
+        .to_request();
+    let authenticated: Value = test::call_and_read_body_json(&app, req).await;
+
+    // Whoever holds the old shared secret signs a fresh token for anyone.
+    let now = chrono::Utc::now().timestamp();
+    let claims = serde_json::json!({
+        "iss": "forged",
+        "sub": account.user_id,
+        "exp": now + 86_400 * 365,
+        "iat": now,
+        "device": authenticated["session"]["device_id"],
+        "role": null,
+        "quota": null,
+    });
+    let token = jsonwebtoken::encode(
+        &jsonwebtoken::Header::default(),
+        &claims,
+        &jsonwebtoken::EncodingKey::from_secret(context.config.auth.jwt_secret.as_bytes()),
+    )
+    .unwrap();
+    let cookie = Cookie::new(context.config.auth.session_cookie.clone(), token);
+
+    assert_eq!(status_with(&app, cookie).await, StatusCode::UNAUTHORIZED);
+}
+
+#[actix_web::test]
+async fn test_legacy_secret_tokens_verify_when_accepted() {
+    let mut context = context::Context::mock_sqlite().await;
+    context.config.auth.jwt_accept_legacy = true;
+    let app = test::init_service(server::app(context.clone())).await;
+    let account = helpers::register_curve25519(&app, "legacy@example.com").await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/self")
+        .cookie(account.jwt.clone())
+        .to_request();
+    let authenticated: Value = test::call_and_read_body_json(&app, req).await;
+
+    let now = chrono::Utc::now().timestamp();
+    let mut claims = serde_json::json!({
+        "iss": "legacy",
+        "sub": account.user_id,
+        "exp": now + 60,
+        "iat": now,
+        "device": authenticated["session"]["device_id"],
+        "role": null,
+        "quota": null,
+    });
+
+    let sign = |claims: &Value| {
+        let token = jsonwebtoken::encode(
+            &jsonwebtoken::Header::default(),
+            claims,
+            &jsonwebtoken::EncodingKey::from_secret(context.config.auth.jwt_secret.as_bytes()),
+        )
+        .unwrap();
+
+        Cookie::new(context.config.auth.session_cookie.clone(), token)
+    };
+
+    assert_eq!(status_with(&app, sign(&claims)).await, StatusCode::OK);
+
+    claims["exp"] = (now - 60).into();
+    assert_eq!(
+        status_with(&app, sign(&claims)).await,
+        StatusCode::UNAUTHORIZED
+    );
+}
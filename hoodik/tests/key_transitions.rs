@@ -51,6 +51,8 @@ async fn test_key_transition_certificate_persists() {
         created_at: ActiveValue::Set(Utc::now().timestamp()),
         updated_at: ActiveValue::Set(Utc::now().timestamp()),
         share_notifications_enabled: ActiveValue::Set(true),
+        login_alerts_enabled: ActiveValue::Set(true),
+        password_score: ActiveValue::Set(None),
     })
     .exec_without_returning(&context.db)
     .await
@@ -82,8 +84,12 @@ async fn test_key_transition_certificate_persists() {
         new_fingerprint: ActiveValue::Set(ed_fingerprint.clone()),
         new_identity_key_pem: ActiveValue::Set(ed_public.clone()),
         new_wrapping_key_pem: ActiveValue::Set(x_public.clone()),
-        old_signature: ActiveValue::Set(cryptfns::base64::decode(&signatures.old_signature).unwrap()),
-        new_signature: ActiveValue::Set(cryptfns::base64::decode(&signatures.new_signature).unwrap()),
+        old_signature: ActiveValue::Set(
+            cryptfns::base64::decode(&signatures.old_signature).unwrap(),
+        ),
+        new_signature: ActiveValue::Set(
+            cryptfns::base64::decode(&signatures.new_signature).unwrap(),
+        ),
         issued_at: ActiveValue::Set(issued_at),
         created_at: ActiveValue::Set(Utc::now().timestamp()),
     })
@@ -149,6 +155,8 @@ async fn test_old_fingerprint_is_unique() {
         created_at: ActiveValue::Set(Utc::now().timestamp()),
         updated_at: ActiveValue::Set(Utc::now().timestamp()),
         share_notifications_enabled: ActiveValue::Set(true),
+        login_alerts_enabled: ActiveValue::Set(true),
+        password_score: ActiveValue::Set(None),
     })
     .exec_without_returning(&context.db)
     .await
@@ -0,0 +1,306 @@
+//! Public links limited to a number of downloads and protected with an extra
+//! password on top of the key in the URL fragment.
+
+#[path = "./helpers.rs"]
+mod helpers;
+
+use actix_web::{cookie::Cookie, http::StatusCode, test};
+use entity::EntityTrait;
+use hoodik::server;
+use links::data::{app_link::AppLink, unlock::Unlocked};
+use serde_json::{json, Value};
+use storage::data::app_file::AppFile;
+
+/// Upload a one-chunk file as a legacy owner and create a link to it with
+/// the given restrictions.
+async fn create_link(
+    app: &impl helpers::TestApp,
+    context: &context::Context,
+    max_downloads: Option<i32>,
+    password: Option<&str>,
+) -> (AppLink, Cookie<'static>) {
+    let owner = helpers::seed_legacy_user(&context.db, "owner@example.com").await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/login")
+        .set_json(json!({ "email": "owner@example.com", "password": helpers::LEGACY_PASSWORD }))
+        .to_request();
+    let (jwt, _) = helpers::extract_cookies(test::call_service(app, req).await.headers());
+    let jwt = jwt.unwrap();
+
+    let chunk = b"encrypted-chunk-contents".to_vec();
+    let create_file = storage::data::create_file::CreateFile {
+        encrypted_key: Some("encrypted-key".to_string()),
+        encrypted_name: Some("name".to_string()),
+        encrypted_thumbnail: None,
+        search_tokens_hashed: None,
+        name_hash: Some("name-hash".to_string()),
+        mime: Some("text/plain".to_string()),
+        size: Some(chunk.len() as i64),
+        chunks: Some(1),
+        file_id: None,
+        file_modified_at: None,
+        md5: None,
+        sha1: None,
+        sha256: None,
+        blake2b: None,
+        cipher: None,
+        editable: None,
+    };
+    let req = test::TestRequest::post()
+        .uri("/api/storage")
+        .cookie(jwt.clone())
+        .set_json(&create_file)
+        .to_request();
+    let file: AppFile = serde_json::from_slice(&test::call_and_read_body(app, req).await).unwrap();
+
+    let checksum = cryptfns::sha256::digest(chunk.as_slice());
+    let req = test::TestRequest::post()
+        .uri(&format!(
+            "/api/storage/{}?checksum={checksum}&chunk=0",
+            file.id
+        ))
+        .cookie(jwt.clone())
+        .append_header(("Content-Type", "application/octet-stream"))
+        .set_payload(chunk)
+        .to_request();
+    assert_eq!(test::call_service(app, req).await.status(), StatusCode::OK);
+
+    let signature = cryptfns::rsa::private::sign(&file.id.to_string(), &owner.rsa_private).unwrap();
+    let create_link = links::data::create_link::CreateLink {
+        file_id: Some(file.id.to_string()),
+        signature: Some(signature),
+        encrypted_name: Some("encrypted-name".to_string()),
+        encrypted_link_key: Some("encrypted-link-key".to_string()),
+        encrypted_thumbnail: None,
+        encrypted_file_key: Some("encrypted-file-key".to_string()),
+        expires_at: None,
+        max_downloads,
+        password: password.map(str::to_string),
+        files: None,
+    };
+    let req = test::TestRequest::post()
+        .uri("/api/links")
+        .cookie(jwt.clone())
+        .set_json(create_link)
+        .to_request();
+    let link = serde_json::from_slice(&test::call_and_read_body(app, req).await).unwrap();
+
+    (link, jwt)
+}
+
+async fn download(app: &impl helpers::TestApp, uri: &str) -> (StatusCode, Vec<u8>) {
+    let req = test::TestRequest::post().uri(uri).to_request();
+    let resp = test::call_service(app, req).await;
+    let status = resp.status();
+
+    (status, test::read_body(resp).await.to_vec())
+}
+
+async fn head(app: &impl helpers::TestApp, uri: &str) -> StatusCode {
+    let req = test::TestRequest::default()
+        .method(actix_web::http::Method::HEAD)
+        .uri(uri)
+        .to_request();
+
+    test::call_service(app, req).await.status()
+}
+
+async fn unlock(app: &impl helpers::TestApp, link: &AppLink, password: &str) -> StatusCode {
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/links/{}/unlock", link.id))
+        .set_json(json!({ "password": password }))
+        .to_request();
+
+    test::call_service(app, req).await.status()
+}
+
+#[actix_web::test]
+async fn test_link_stops_serving_after_its_maximum_downloads() {
+    let context =
+        context::Context::mock_with_data_dir(Some("../data-test-link-max-downloads".to_string()))
+            .await;
+    let app = test::init_service(server::app(context.clone())).await;
+
+    let (link, _) = create_link(&app, &context, Some(2), None).await;
+    assert_eq!(link.max_downloads, Some(2));
+
+    for _ in 0..2 {
+        let (status, _) = download(&app, &format!("/api/links/{}?chunk=0", link.id)).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let (status, body) = download(&app, &format!("/api/links/{}?chunk=0", link.id)).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["message"], "link_exhausted");
+    assert_eq!(
+        head(&app, &format!("/api/links/{}", link.id)).await,
+        StatusCode::UNAUTHORIZED
+    );
+
+    context.config.app.cleanup();
+}
+
+#[actix_web::test]
+async fn test_password_protected_link_streams_only_after_unlocking() {
+    let context =
+        context::Context::mock_with_data_dir(Some("../data-test-link-password".to_string())).await;
+    let app = test::init_service(server::app(context.clone())).await;
+
+    let (link, jwt) = create_link(&app, &context, None, Some("correct horse")).await;
+    assert!(link.has_password);
+
+    // Without the password, neither the keys nor the ciphertext are given out.
+    let (status, _) = download(&app, &format!("/api/links/{}?chunk=0", link.id)).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        head(&app, &format!("/api/links/{}", link.id)).await,
+        StatusCode::UNAUTHORIZED
+    );
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/links/{}/metadata", link.id))
+        .to_request();
+    let locked: AppLink =
+        serde_json::from_slice(&test::call_and_read_body(&app, req).await).unwrap();
+    assert!(locked.encrypted_file_key.is_none());
+    assert!(locked.encrypted_name.is_empty());
+
+    // The owner still sees their own link.
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/links/{}/metadata", link.id))
+        .cookie(jwt)
+        .to_request();
+    let owned: AppLink =
+        serde_json::from_slice(&test::call_and_read_body(&app, req).await).unwrap();
+    assert!(owned.encrypted_file_key.is_some());
+
+    assert_eq!(unlock(&app, &link, "wrong").await, StatusCode::UNAUTHORIZED);
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/links/{}/unlock", link.id))
+        .set_json(json!({ "password": "correct horse" }))
+        .to_request();
+    let unlocked: Unlocked =
+        serde_json::from_slice(&test::call_and_read_body(&app, req).await).unwrap();
+
+    let req = test::TestRequest::get()
+        .uri(&format!(
+            "/api/links/{}/metadata?access_token={}",
+            link.id, unlocked.access_token
+        ))
+        .to_request();
+    let metadata: AppLink =
+        serde_json::from_slice(&test::call_and_read_body(&app, req).await).unwrap();
+    assert_eq!(
+        metadata.encrypted_file_key.as_deref(),
+        Some("encrypted-file-key")
+    );
+
+    let (status, body) = download(
+        &app,
+        &format!(
+            "/api/links/{}?chunk=0&access_token={}",
+            link.id, unlocked.access_token
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, b"encrypted-chunk-contents");
+    assert_eq!(
+        head(
+            &app,
+            &format!(
+                "/api/links/{}?access_token={}",
+                link.id, unlocked.access_token
+            )
+        )
+        .await,
+        StatusCode::NO_CONTENT
+    );
+
+    // A forged token is refused, even one built from the stored password hash
+    // by someone who read the database.
+    let hash = entity::links::Entity::find_by_id(link.id)
+        .one(&context.db)
+        .await
+        .unwrap()
+        .unwrap()
+        .password_hash
+        .unwrap();
+    let expires_at = unlocked.expires_at;
+    let digest = cryptfns::sha256::digest(format!("{}\0{hash}\0{expires_at}", link.id));
+    for forged in [
+        format!("{expires_at}.{}", "0".repeat(64)),
+        format!("{expires_at}.{digest}"),
+    ] {
+        let (status, _) = download(
+            &app,
+            &format!("/api/links/{}?chunk=0&access_token={forged}", link.id),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    context.config.app.cleanup();
+}
+
+#[actix_web::test]
+async fn test_link_access_token_is_accepted_by_another_server() {
+    let context =
+        context::Context::mock_with_data_dir(Some("../data-test-link-access-secret".to_string()))
+            .await;
+    let app = test::init_service(server::app(context.clone())).await;
+
+    let (link, _) = create_link(&app, &context, None, Some("correct horse")).await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/links/{}/unlock", link.id))
+        .set_json(json!({ "password": "correct horse" }))
+        .to_request();
+    let unlocked: Unlocked =
+        serde_json::from_slice(&test::call_and_read_body(&app, req).await).unwrap();
+
+    // A replica, or the same server after a restart, has a JWT secret of its
+    // own but reads the link secret from the same database.
+    let mut replica = context.clone();
+    replica.config.auth.jwt_secret = "another-jwt-secret".to_string();
+    let replica_app = test::init_service(server::app(replica)).await;
+
+    let (status, body) = download(
+        &replica_app,
+        &format!(
+            "/api/links/{}?chunk=0&access_token={}",
+            link.id, unlocked.access_token
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, b"encrypted-chunk-contents");
+
+    context.config.app.cleanup();
+}
+
+#[actix_web::test]
+async fn test_guessing_a_link_password_is_rate_limited() {
+    let context = context::Context::mock_with_data_dir(Some(
+        "../data-test-link-password-guessing".to_string(),
+    ))
+    .await;
+    let app = test::init_service(server::app(context.clone())).await;
+
+    let (link, _) = create_link(&app, &context, None, Some("correct horse")).await;
+
+    for _ in 0..10 {
+        assert_eq!(unlock(&app, &link, "wrong").await, StatusCode::UNAUTHORIZED);
+    }
+
+    // Locked out, even with the right password.
+    assert_eq!(
+        unlock(&app, &link, "correct horse").await,
+        StatusCode::TOO_MANY_REQUESTS
+    );
+
+    context.config.app.cleanup();
+}
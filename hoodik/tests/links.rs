@@ -11,7 +11,8 @@ use crate::helpers::{create_byte_chunks, CHUNK_SIZE_BYTES};
 
 #[actix_web::test]
 async fn test_creating_and_downloading_link() {
-    let context = context::Context::mock_with_data_dir(Some("../data-test-links".to_string())).await;
+    let context =
+        context::Context::mock_with_data_dir(Some("../data-test-links".to_string())).await;
 
     let app = test::init_service(server::app(context.clone())).await;
 
@@ -74,7 +75,10 @@ async fn test_creating_and_downloading_link() {
         // println!("chunk: {}", i);
         let encrypted = cryptfns::aes::encrypt(file_key.clone(), chunk).unwrap();
         let checksum = cryptfns::sha256::digest(encrypted.as_slice());
-        let uri = format!("/api/storage/{}?checksum={}&chunk={}", &file.id, checksum, i);
+        let uri = format!(
+            "/api/storage/{}?checksum={}&chunk={}",
+            &file.id, checksum, i
+        );
 
         let req = test::TestRequest::post()
             .uri(uri.as_str())
@@ -119,6 +123,9 @@ async fn test_creating_and_downloading_link() {
         encrypted_thumbnail: None,
         encrypted_file_key: Some(file_key_hex_aes_enc_hex),
         expires_at: None,
+        max_downloads: None,
+        password: None,
+        files: None,
     };
     let req = test::TestRequest::post()
         .uri("/api/links")
@@ -147,7 +154,10 @@ async fn test_creating_and_downloading_link() {
     // Ciphertext size is >= plaintext (AEAD overhead); exact depends on chunking.
     assert!(content_len >= size as usize);
     // The body must NOT be the plaintext (server never decrypts for public link content).
-    assert_ne!(received_checksum, checksum, "public link content download must return ciphertext");
+    assert_ne!(
+        received_checksum, checksum,
+        "public link content download must return ciphertext"
+    );
 
     let req = test::TestRequest::get()
         .uri(format!("/api/storage/{}/metadata", &file.id).as_str())
@@ -262,6 +272,9 @@ async fn test_link_download_decrypts_aegis256_file() {
         encrypted_thumbnail: None,
         encrypted_file_key: Some(cryptfns::hex::encode(file_key_hex_enc)),
         expires_at: None,
+        max_downloads: None,
+        password: None,
+        files: None,
     };
     let req = test::TestRequest::post()
         .uri("/api/links")
@@ -282,7 +295,10 @@ async fn test_link_download_decrypts_aegis256_file() {
     // Ciphertext size >= plaintext.
     assert!(contents.len() >= size as usize);
     let received = cryptfns::sha256::digest(contents.as_slice());
-    assert_ne!(received, checksum, "public link content must be ciphertext only");
+    assert_ne!(
+        received, checksum,
+        "public link content must be ciphertext only"
+    );
 
     // Per-chunk download must return exactly one chunk's ciphertext, and the
     // chunks concatenated in order must reproduce the whole-file stream — this
@@ -301,7 +317,10 @@ async fn test_link_download_decrypts_aegis256_file() {
             .to_request();
         per_chunk.extend(test::call_and_read_body(&app, req).await.to_vec());
     }
-    assert_eq!(per_chunk, contents, "chunked link download must match the full stream");
+    assert_eq!(
+        per_chunk, contents,
+        "chunked link download must match the full stream"
+    );
 
     // One recipient fetching every chunk is one download, not one per chunk:
     // only the final chunk request increments the owner-visible counter.
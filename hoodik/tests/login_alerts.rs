@@ -0,0 +1,193 @@
+//! New device alerts: a login from a browser, system or network the account
+//! has no session from is announced by email, with a link that ends every session.
+//! The MockSender records subject lines, the link's action is read from the
+//! database.
+
+#[path = "./helpers.rs"]
+mod helpers;
+
+use actix_web::{http::StatusCode, test};
+use context::SenderContract;
+use entity::{
+    sessions, user_actions, ColumnTrait, EntityTrait, Expr, PaginatorTrait, QueryFilter, Uuid,
+};
+use hoodik::server;
+use serde_json::{json, Value};
+use settings::{data::Users, factory::Factory};
+
+const SUBJECT: &str = "New sign-in to your account";
+
+async fn alerting_context() -> context::Context {
+    let context = context::Context::add_mock_sender(context::Context::mock_sqlite().await);
+
+    let mut settings = context.settings.inner().await.clone();
+    settings.users = serde_json::from_value::<Users>(json!({
+        "allow_register": true,
+        "enforce_email_activation": false
+    }))
+    .unwrap();
+    context.settings.replace_inner(settings).await;
+
+    context
+}
+
+/// OPAQUE login with the helpers' password from the given user agent.
+async fn login(app: &impl helpers::TestApp, email: &str, user_agent: &str) -> StatusCode {
+    let password = helpers::LEGACY_PASSWORD.as_bytes();
+    let start = cryptfns::opaque::client_login_start(password).unwrap();
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/login/start")
+        .set_json(json!({ "email": email, "credential_request": start.message }))
+        .to_request();
+    let body: Value = test::call_and_read_body_json(app, req).await;
+    let ksf = &body["ksf"];
+
+    let finish = cryptfns::opaque::client_login_finish_with_params(
+        &start.state,
+        body["credential_response"].as_str().unwrap(),
+        password,
+        ksf["m_cost"].as_u64().unwrap() as u32,
+        ksf["t_cost"].as_u64().unwrap() as u32,
+        ksf["p_cost"].as_u64().unwrap() as u32,
+    )
+    .unwrap();
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/login/finish")
+        .insert_header(("user-agent", user_agent))
+        .set_json(json!({
+            "login_id": body["login_id"],
+            "credential_finalization": finish.finalization,
+        }))
+        .to_request();
+
+    test::call_service(app, req).await.status()
+}
+
+#[actix_web::test]
+async fn test_new_device_is_announced_and_can_end_every_session() {
+    let context = alerting_context().await;
+    let app = test::init_service(server::app(context.clone())).await;
+    let mailer = context.sender.as_ref().unwrap();
+
+    let user = helpers::register_curve25519(&app, "ada@example.com").await;
+    assert!(!mailer.has(SUBJECT), "the first session is not news");
+
+    // Registering went without a user agent header.
+    assert_eq!(
+        login(&app, "ada@example.com", "missing-header").await,
+        StatusCode::OK
+    );
+    assert!(!mailer.has(SUBJECT), "a known device is not news");
+
+    assert_eq!(
+        login(&app, "ada@example.com", "Stranger/1.0").await,
+        StatusCode::OK
+    );
+    assert!(mailer.has(SUBJECT), "new device alert was not sent");
+
+    let action = user_actions::Entity::find()
+        .filter(user_actions::Column::UserId.eq(user.user_id))
+        .filter(user_actions::Column::Action.eq("kill-sessions"))
+        .one(&context.db)
+        .await
+        .unwrap()
+        .expect("the alert links to a kill-sessions action");
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/auth/action/kill-sessions/{}", action.id))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+    let alive = sessions::Entity::find()
+        .filter(sessions::Column::UserId.eq(user.user_id))
+        .filter(sessions::Column::Refresh.is_not_null())
+        .count(&context.db)
+        .await
+        .unwrap();
+    assert_eq!(alive, 0);
+
+    // The link is single use.
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/auth/action/kill-sessions/{}", action.id))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    // Nor is an unknown one.
+    let req = test::TestRequest::post()
+        .uri(&format!(
+            "/api/auth/action/kill-sessions/{}",
+            Uuid::new_v4()
+        ))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn test_browser_update_is_not_a_new_device() {
+    let context = alerting_context().await;
+    let app = test::init_service(server::app(context.clone())).await;
+    let mailer = context.sender.as_ref().unwrap();
+
+    let user = helpers::register_curve25519(&app, "ada@example.com").await;
+
+    sessions::Entity::update_many()
+        .col_expr(
+            sessions::Column::UserAgent,
+            Expr::value("Mozilla/5.0 (X11; Linux x86_64; rv:130.0) Gecko/20100101 Firefox/130.0"),
+        )
+        .filter(sessions::Column::UserId.eq(user.user_id))
+        .exec(&context.db)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        login(
+            &app,
+            "ada@example.com",
+            "Mozilla/5.0 (X11; Linux x86_64; rv:131.0) Gecko/20100101 Firefox/131.0"
+        )
+        .await,
+        StatusCode::OK
+    );
+    assert!(!mailer.has(SUBJECT), "a browser update is not news");
+
+    assert_eq!(
+        login(
+            &app,
+            "ada@example.com",
+            "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:131.0) Gecko/20100101 Firefox/131.0"
+        )
+        .await,
+        StatusCode::OK
+    );
+    assert!(mailer.has(SUBJECT), "another system is a new device");
+}
+
+#[actix_web::test]
+async fn test_opted_out_user_is_not_alerted() {
+    let context = alerting_context().await;
+    let app = test::init_service(server::app(context.clone())).await;
+    let mailer = context.sender.as_ref().unwrap();
+
+    let user = helpers::register_curve25519(&app, "ada@example.com").await;
+
+    let req = test::TestRequest::patch()
+        .uri("/api/users/me")
+        .cookie(user.jwt.clone())
+        .set_json(json!({ "login_alerts_enabled": false }))
+        .to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["login_alerts_enabled"], false);
+    assert_eq!(body["share_notifications_enabled"], true);
+
+    assert_eq!(
+        login(&app, "ada@example.com", "Stranger/1.0").await,
+        StatusCode::OK
+    );
+    assert!(!mailer.has(SUBJECT), "alert sent despite opt-out");
+}
@@ -0,0 +1,129 @@
+//! Storage migration between providers.
+//!
+//! Both sides are local directories here; the copy only goes through the
+//! provider contract, so S3 takes the same path.
+
+use std::path::PathBuf;
+
+use context::Context;
+use entity::Uuid;
+use fs::prelude::*;
+use hoodik::migrate::{migrate_storage, Endpoint, Options};
+
+fn temp_dir(name: &str) -> String {
+    let dir = std::env::temp_dir().join(format!("hoodik-{}-{}", name, Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir.to_string_lossy().to_string()
+}
+
+fn options(from: &str, to: &str, checkpoint: &str) -> Options {
+    Options {
+        from: Endpoint::Local(Some(from.to_string())),
+        to: Endpoint::Local(Some(to.to_string())),
+        checkpoint: PathBuf::from(checkpoint).join("migrate-storage.checkpoint"),
+        concurrency: 2,
+    }
+}
+
+#[test]
+fn endpoints_parse() {
+    assert_eq!("local".parse::<Endpoint>().unwrap(), Endpoint::Local(None));
+    assert_eq!(
+        "local:/mnt/new".parse::<Endpoint>().unwrap(),
+        Endpoint::Local(Some("/mnt/new".to_string()))
+    );
+    assert_eq!(
+        "s3".parse::<Endpoint>().unwrap(),
+        Endpoint::S3 {
+            bucket: None,
+            prefix: None
+        }
+    );
+    assert_eq!(
+        "s3:archive/hoodik/".parse::<Endpoint>().unwrap(),
+        Endpoint::S3 {
+            bucket: Some("archive".to_string()),
+            prefix: Some("hoodik/".to_string())
+        }
+    );
+    assert!("ftp:somewhere".parse::<Endpoint>().is_err());
+}
+
+#[actix_web::test]
+async fn copies_both_layouts_and_resumes() {
+    let context = Context::mock_sqlite().await;
+    let (from, to, work) = (temp_dir("from"), temp_dir("to"), temp_dir("work"));
+
+    let mut source_config = context.config.clone();
+    source_config.app.data_dir = from.clone();
+    let source = Fs::new(&source_config);
+
+    let mut destination_config = context.config.clone();
+    destination_config.app.data_dir = to.clone();
+    let destination = Fs::new(&destination_config);
+
+    let legacy = Filename::new(Uuid::new_v4()).with_timestamp(17);
+    source.push(&legacy, 0, b"legacy-0").await.unwrap();
+    source.push(&legacy, 1, b"legacy-1").await.unwrap();
+
+    let versioned = Filename::new(Uuid::new_v4());
+    source.push_v(&versioned, 1, 0, b"one").await.unwrap();
+    source.push_v(&versioned, 2, 0, b"two").await.unwrap();
+
+    // A chunk cut short by an earlier, interrupted copy is copied again.
+    destination.push(&legacy, 1, b"leg").await.unwrap();
+
+    let migrated = migrate_storage(&context.config, &options(&from, &to, &work))
+        .await
+        .unwrap();
+
+    assert_eq!(migrated.files, 2);
+    assert_eq!(migrated.chunks, 4);
+    assert_eq!(migrated.skipped, 0);
+    assert_eq!(destination.pull(&legacy, 0).await.unwrap(), b"legacy-0");
+    assert_eq!(destination.pull(&legacy, 1).await.unwrap(), b"legacy-1");
+    assert_eq!(destination.pull_v(&versioned, 1, 0).await.unwrap(), b"one");
+    assert_eq!(destination.pull_v(&versioned, 2, 0).await.unwrap(), b"two");
+    assert!(source.exists(&legacy, 0).await.unwrap());
+    assert!(!PathBuf::from(&work)
+        .join("migrate-storage.checkpoint")
+        .exists());
+
+    // Files listed in the checkpoint are not looked at again, and chunks
+    // already in place are not copied twice.
+    source.push_v(&versioned, 2, 1, b"two-more").await.unwrap();
+    std::fs::write(
+        PathBuf::from(&work).join("migrate-storage.checkpoint"),
+        format!("{}\n", legacy.inner_name()),
+    )
+    .unwrap();
+
+    let migrated = migrate_storage(&context.config, &options(&from, &to, &work))
+        .await
+        .unwrap();
+
+    assert_eq!(migrated.resumed, 1);
+    assert_eq!(migrated.files, 1);
+    assert_eq!(migrated.chunks, 1);
+    assert_eq!(migrated.skipped, 2);
+    assert_eq!(
+        destination.pull_v(&versioned, 2, 1).await.unwrap(),
+        b"two-more"
+    );
+
+    for dir in [from, to, work] {
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
+
+#[actix_web::test]
+async fn refuses_to_copy_onto_itself() {
+    let context = Context::mock_sqlite().await;
+    let dir = temp_dir("same");
+
+    assert!(migrate_storage(&context.config, &options(&dir, &dir, &dir))
+        .await
+        .is_err());
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -27,11 +27,7 @@ trait TestApp:
 impl<S, B> TestApp for S
 where
     B: actix_web::body::MessageBody,
-    S: Service<
-        actix_http::Request,
-        Response = ServiceResponse<B>,
-        Error = actix_web::Error,
-    >,
+    S: Service<actix_http::Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
 {
     type Body = B;
 }
@@ -129,6 +125,8 @@ async fn seed_link<C: entity::ConnectionTrait>(
         encrypted_file_key: ActiveValue::Set(None),
         created_at: ActiveValue::Set(now),
         expires_at: ActiveValue::Set(None),
+        max_downloads: ActiveValue::Set(None),
+        password_hash: ActiveValue::Set(None),
     })
     .exec_without_returning(db)
     .await
@@ -190,7 +188,9 @@ async fn build_migration_parts(app: &impl TestApp, user: &LegacyUser) -> Migrati
     let mut offset = 0i64;
     loop {
         let req = test::TestRequest::get()
-            .uri(&format!("/api/auth/migration/keys?offset={offset}&limit=500"))
+            .uri(&format!(
+                "/api/auth/migration/keys?offset={offset}&limit=500"
+            ))
             .cookie(user.jwt.clone())
             .to_request();
         let page: Value = test::read_body_json(test::call_service(app, req).await).await;
@@ -279,12 +279,7 @@ async fn build_migration_parts(app: &impl TestApp, user: &LegacyUser) -> Migrati
 /// Stage the re-wrapped keys through `migration/rewrap`, chunking the combined
 /// file-then-link sequence to respect the server's per-request cap — exactly how
 /// the real client batches. Asserts each batch is accepted.
-async fn stage_rewraps(
-    app: &impl TestApp,
-    user: &LegacyUser,
-    keys: &[Value],
-    link_keys: &[Value],
-) {
+async fn stage_rewraps(app: &impl TestApp, user: &LegacyUser, keys: &[Value], link_keys: &[Value]) {
     const REWRAP_BATCH: usize = 500;
     let total = keys.len() + link_keys.len();
     let mut start = 0;
@@ -382,7 +377,10 @@ async fn test_full_migration_flips_account_and_rewraps_keys() {
         .await
         .unwrap()
         .expect("a transition row was written");
-    assert_eq!(transition.new_identity_key_pem, migrated["pubkey"].as_str().unwrap());
+    assert_eq!(
+        transition.new_identity_key_pem,
+        migrated["pubkey"].as_str().unwrap()
+    );
     assert_eq!(
         transition.new_wrapping_key_pem,
         migrated["wrapping_pubkey"].as_str().unwrap()
@@ -416,7 +414,10 @@ async fn test_full_migration_flips_account_and_rewraps_keys() {
         .set_json(json!({ "email": EMAIL, "credential_request": start.message }))
         .to_request();
     let body: Value = test::read_body_json(test::call_service(&app, req).await).await;
-    assert_eq!(body["method"], "opaque", "migrated account authenticates via OPAQUE");
+    assert_eq!(
+        body["method"], "opaque",
+        "migrated account authenticates via OPAQUE"
+    );
 
     // Signature login with the NEW key resolves through the new fingerprint.
     assert_eq!(new_fingerprint.len(), 64);
@@ -615,7 +616,10 @@ async fn test_migration_aborts_on_foreign_link_key() {
         .await
         .unwrap()
         .unwrap();
-    assert_eq!(migrator.security_version, 0, "account stays legacy after abort");
+    assert_eq!(
+        migrator.security_version, 0,
+        "account stays legacy after abort"
+    );
 
     let file_row = entity::user_files::Entity::find()
         .filter(entity::user_files::Column::FileId.eq(file_id))
@@ -780,7 +784,11 @@ async fn test_migration_appends_verifying_key_rotation_event() {
         &audit_row,
     )
     .unwrap();
-    assert_eq!(row.this_event_hash, recomputed.to_vec(), "chain hash matches");
+    assert_eq!(
+        row.this_event_hash,
+        recomputed.to_vec(),
+        "chain hash matches"
+    );
 
     let sig_b64 = cryptfns::base64::encode(row.sender_signature.as_ref().unwrap());
     cryptfns::transition::verify_key_rotation_audit(
@@ -864,7 +872,10 @@ async fn test_migration_resigns_link_under_new_key() {
         .await
         .unwrap()
         .unwrap();
-    assert_ne!(row.signature, "link-signature", "the RSA-era signature is gone");
+    assert_ne!(
+        row.signature, "link-signature",
+        "the RSA-era signature is gone"
+    );
     cryptfns::identity::KeyType::Curve25519
         .verify(&file_id.to_string(), &row.signature, new_pubkey)
         .expect("the stored link signature verifies under the new identity key");
@@ -940,7 +951,11 @@ async fn test_large_account_that_overflowed_the_old_post_migrates() {
     helpers::seed_owned_files(&context.db, user_id, FILES).await;
 
     let parts = build_migration_parts(&app, &user).await;
-    assert_eq!(parts.rewrap_keys.len(), FILES, "every seeded file is offered for re-wrap");
+    assert_eq!(
+        parts.rewrap_keys.len(),
+        FILES,
+        "every seeded file is offered for re-wrap"
+    );
 
     // The body the pre-fix client would have sent in one shot — prove it clears
     // 2 MB, which is exactly what made the old design 413 for this account.
@@ -983,7 +998,10 @@ async fn test_large_account_that_overflowed_the_old_post_migrates() {
         .unwrap()
         .unwrap();
     let recovered = cryptfns::ecdh::unwrap(&sample.encrypted_key, &parts.x_private).unwrap();
-    assert_eq!(recovered, parts.file_key, "file key survives the batched re-wrap");
+    assert_eq!(
+        recovered, parts.file_key,
+        "file key survives the batched re-wrap"
+    );
 }
 
 /// A 600-file account needs more than one `rewrap` batch (the per-request cap is
@@ -1048,7 +1066,11 @@ async fn test_rewrap_batch_is_idempotent() {
     let parts = build_migration_parts(&app, &user).await;
 
     stage_rewraps(&app, &user, &parts.rewrap_keys, &parts.rewrap_link_keys).await;
-    assert_eq!(staging_count(&context.db, user_id).await, 3, "first stage lands 3 rows");
+    assert_eq!(
+        staging_count(&context.db, user_id).await,
+        3,
+        "first stage lands 3 rows"
+    );
 
     stage_rewraps(&app, &user, &parts.rewrap_keys, &parts.rewrap_link_keys).await;
     assert_eq!(
@@ -1109,7 +1131,11 @@ async fn test_complete_with_empty_staging_succeeds() {
     let user_id = current_user_id(&app, &user.jwt).await;
 
     let (resp, _x, _fk, _l) = migrate(&app, &user).await;
-    assert_eq!(resp.status(), StatusCode::OK, "a zero-file account still migrates");
+    assert_eq!(
+        resp.status(),
+        StatusCode::OK,
+        "a zero-file account still migrates"
+    );
     let migrated: Value = test::read_body_json(resp).await;
     assert_eq!(migrated["security_version"], 1);
     assert_eq!(staging_count(&context.db, user_id).await, 0);
@@ -1193,15 +1219,24 @@ async fn test_retry_after_failure_between_staging_and_complete() {
         .await
         .unwrap()
         .unwrap();
-    assert_eq!(legacy.security_version, 0, "account stays legacy after the failure");
+    assert_eq!(
+        legacy.security_version, 0,
+        "account stays legacy after the failure"
+    );
     let file_row = entity::user_files::Entity::find()
         .filter(entity::user_files::Column::FileId.eq(file_id))
         .one(&context.db)
         .await
         .unwrap()
         .unwrap();
-    assert_eq!(file_row.encrypted_key, "old-rsa-wrapped-key", "key untouched");
-    assert!(staging_count(&context.db, user_id).await > 0, "staging survives for the retry");
+    assert_eq!(
+        file_row.encrypted_key, "old-rsa-wrapped-key",
+        "key untouched"
+    );
+    assert!(
+        staging_count(&context.db, user_id).await > 0,
+        "staging survives for the retry"
+    );
 
     // The retry completes from the same staged set.
     let req = test::TestRequest::post()
@@ -1224,5 +1259,8 @@ async fn test_retry_after_failure_between_staging_and_complete() {
         .await
         .unwrap()
         .unwrap();
-    assert_ne!(file_row.encrypted_key, "old-rsa-wrapped-key", "key re-wrapped on retry");
+    assert_ne!(
+        file_row.encrypted_key, "old-rsa-wrapped-key",
+        "key re-wrapped on retry"
+    );
 }
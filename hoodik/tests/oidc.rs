@@ -0,0 +1,455 @@
+//! OpenID Connect login gate, run against a small identity provider served
+//! from the test itself: discovery, a JWKS and a token endpoint checking the
+//! PKCE verifier. The browser leg of the authorization request is skipped,
+//! the test hands the provider the code it would have issued.
+
+#[path = "./helpers.rs"]
+mod helpers;
+
+use std::{
+    collections::HashMap,
+    net::TcpListener,
+    sync::{Arc, Mutex},
+};
+
+use actix_web::{cookie::Cookie, http::StatusCode, test, web, App, HttpResponse, HttpServer};
+use config::oidc::OidcConfig;
+use entity::{
+    invitations, sessions, ActiveValue, ColumnTrait, EntityTrait, Expr, QueryFilter, Uuid,
+};
+use hoodik::server;
+use serde_json::{json, Value};
+use settings::{data::Users, factory::Factory};
+
+const CLIENT_ID: &str = "hoodik";
+const KID: &str = "mock-key";
+
+/// Codes the provider handed out: the PKCE challenge they were issued for
+/// and the claims of the ID token they are traded for.
+type Codes = Arc<Mutex<HashMap<String, (String, Value)>>>;
+
+struct Issuer {
+    url: String,
+    codes: Codes,
+}
+
+#[derive(Clone)]
+struct IssuerState {
+    url: String,
+    private_key: String,
+    jwk: Value,
+    codes: Codes,
+}
+
+async fn discovery(state: web::Data<IssuerState>) -> HttpResponse {
+    HttpResponse::Ok().json(json!({
+        "issuer": state.url,
+        "authorization_endpoint": format!("{}/authorize", state.url),
+        "token_endpoint": format!("{}/token", state.url),
+        "jwks_uri": format!("{}/jwks", state.url),
+    }))
+}
+
+async fn jwks(state: web::Data<IssuerState>) -> HttpResponse {
+    HttpResponse::Ok().json(json!({ "keys": [state.jwk] }))
+}
+
+async fn token(
+    state: web::Data<IssuerState>,
+    form: web::Form<HashMap<String, String>>,
+) -> HttpResponse {
+    let issued = form
+        .get("code")
+        .and_then(|code| state.codes.lock().unwrap().remove(code));
+    let Some((challenge, claims)) = issued else {
+        return HttpResponse::BadRequest().json(json!({ "error": "invalid_grant" }));
+    };
+
+    let verifier = form.get("code_verifier").cloned().unwrap_or_default();
+    if cryptfns::pkce::challenge(&verifier) != challenge
+        || form.get("client_id").map(String::as_str) != Some(CLIENT_ID)
+    {
+        return HttpResponse::BadRequest().json(json!({ "error": "invalid_grant" }));
+    }
+
+    let mut header = jsonwebtoken::Header::new(jsonwebtoken::Algorithm::ES256);
+    header.kid = Some(KID.to_string());
+    let id_token = jsonwebtoken::encode(
+        &header,
+        &claims,
+        &jsonwebtoken::EncodingKey::from_ec_pem(state.private_key.as_bytes()).unwrap(),
+    )
+    .unwrap();
+
+    HttpResponse::Ok().json(json!({ "id_token": id_token, "token_type": "Bearer" }))
+}
+
+fn start_issuer() -> Issuer {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let pair = cryptfns::jws::generate_es256().unwrap();
+    let codes = Codes::default();
+
+    let state = IssuerState {
+        url: url.clone(),
+        private_key: pair.private_key,
+        jwk: json!({
+            "kty": "EC",
+            "crv": "P-256",
+            "x": pair.x,
+            "y": pair.y,
+            "kid": KID,
+            "alg": "ES256",
+            "use": "sig",
+        }),
+        codes: codes.clone(),
+    };
+
+    let server = HttpServer::new(move || {
+        App::new()
+            .app_data(web::Data::new(state.clone()))
+            .route(
+                "/.well-known/openid-configuration",
+                web::get().to(discovery),
+            )
+            .route("/jwks", web::get().to(jwks))
+            .route("/token", web::post().to(token))
+    })
+    .workers(1)
+    .listen(listener)
+    .unwrap()
+    .run();
+    actix_web::rt::spawn(server);
+
+    Issuer { url, codes }
+}
+
+fn oidc_config(issuer: &Issuer) -> OidcConfig {
+    OidcConfig {
+        issuer: issuer.url.clone(),
+        client_id: CLIENT_ID.to_string(),
+        client_secret: None,
+        redirect_url: "http://localhost/api/auth/oidc/callback".to_string(),
+        scopes: "openid email".to_string(),
+        role_claim: Some("groups".to_string()),
+        admin_role: "hoodik-admins".to_string(),
+        session_lifetime_hours: 24,
+        allow_personal_access_tokens: true,
+    }
+}
+
+async fn oidc_context(issuer: &Issuer) -> context::Context {
+    let mut context = context::Context::mock_sqlite().await;
+    context.config.oidc = Some(oidc_config(issuer));
+
+    context
+}
+
+/// Sign `email` in at the provider, returning where the callback sent the
+/// browser and the ticket cookie it set, if any.
+async fn sign_in(
+    app: &impl helpers::TestApp,
+    issuer: &Issuer,
+    email: &str,
+    groups: &[&str],
+) -> (url::Url, Option<Cookie<'static>>) {
+    let req = test::TestRequest::get()
+        .uri("/api/auth/oidc/authorize")
+        .to_request();
+    let resp = test::call_service(app, req).await;
+    assert_eq!(resp.status(), StatusCode::FOUND);
+    let location =
+        url::Url::parse(resp.headers().get("location").unwrap().to_str().unwrap()).unwrap();
+    assert!(location
+        .as_str()
+        .starts_with(&format!("{}/authorize", issuer.url)));
+
+    let query: HashMap<_, _> = location.query_pairs().into_owned().collect();
+    assert_eq!(query["code_challenge_method"], "S256");
+
+    let code = Uuid::new_v4().to_string();
+    let now = chrono::Utc::now().timestamp();
+    issuer.codes.lock().unwrap().insert(
+        code.clone(),
+        (
+            query["code_challenge"].clone(),
+            json!({
+                "iss": issuer.url,
+                "aud": CLIENT_ID,
+                "sub": email,
+                "email": email,
+                "email_verified": true,
+                "groups": groups,
+                "nonce": query["nonce"],
+                "iat": now,
+                "exp": now + 300,
+            }),
+        ),
+    );
+
+    let req = test::TestRequest::get()
+        .uri(&format!(
+            "/api/auth/oidc/callback?code={code}&state={}",
+            query["state"]
+        ))
+        .to_request();
+    let resp = test::call_service(app, req).await;
+    assert_eq!(resp.status(), StatusCode::FOUND);
+
+    let redirect =
+        url::Url::parse(resp.headers().get("location").unwrap().to_str().unwrap()).unwrap();
+    let ticket = resp
+        .headers()
+        .get_all("set-cookie")
+        .map(|h| Cookie::parse(h.to_str().unwrap().to_string()).unwrap())
+        .find(|c| c.name() == "hoodik_oidc");
+
+    (redirect, ticket)
+}
+
+/// OPAQUE login with the helpers' password, returning the login/finish status
+/// and body.
+async fn login(
+    app: &impl helpers::TestApp,
+    email: &str,
+    ticket: Option<Cookie<'static>>,
+) -> (StatusCode, Value) {
+    let password = helpers::LEGACY_PASSWORD.as_bytes();
+    let start = cryptfns::opaque::client_login_start(password).unwrap();
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/login/start")
+        .set_json(json!({ "email": email, "credential_request": start.message }))
+        .to_request();
+    let body: Value = test::call_and_read_body_json(app, req).await;
+    let ksf = &body["ksf"];
+
+    let finish = cryptfns::opaque::client_login_finish_with_params(
+        &start.state,
+        body["credential_response"].as_str().unwrap(),
+        password,
+        ksf["m_cost"].as_u64().unwrap() as u32,
+        ksf["t_cost"].as_u64().unwrap() as u32,
+        ksf["p_cost"].as_u64().unwrap() as u32,
+    )
+    .unwrap();
+
+    let mut req = test::TestRequest::post()
+        .uri("/api/auth/login/finish")
+        .set_json(json!({
+            "login_id": body["login_id"],
+            "credential_finalization": finish.finalization,
+        }));
+    if let Some(ticket) = ticket {
+        req = req.cookie(ticket);
+    }
+    let resp = test::call_service(app, req.to_request()).await;
+
+    (resp.status(), test::read_body_json(resp).await)
+}
+
+#[actix_web::test]
+async fn test_registration_and_login_go_through_the_provider() {
+    let issuer = start_issuer();
+    let context = oidc_context(&issuer).await;
+    let app = test::init_service(server::app(context.clone())).await;
+    let email = "ada@example.com";
+
+    let body = helpers::build_curve25519_register_body(&app, email).await;
+    let req = test::TestRequest::post()
+        .uri("/api/auth/register")
+        .set_json(&body)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    let refused: Value = test::read_body_json(resp).await;
+    assert_eq!(refused["message"], "oidc_login_required");
+
+    let (redirect, ticket) = sign_in(&app, &issuer, email, &["hoodik-admins"]).await;
+    assert_eq!(redirect.path(), "/auth/register");
+    let ticket = ticket.expect("callback sets the ticket cookie");
+
+    let body = helpers::build_curve25519_register_body(&app, email).await;
+    let req = test::TestRequest::post()
+        .uri("/api/auth/register")
+        .cookie(ticket.clone())
+        .set_json(&body)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let registered: Value = test::read_body_json(resp).await;
+    assert_eq!(registered["user"]["role"], "admin");
+
+    // The password alone no longer opens the account, nor does a used ticket.
+    for ticket in [None, Some(ticket)] {
+        let (status, body) = login(&app, email, ticket).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["message"], "oidc_login_required");
+    }
+
+    // Dropped from the admin group at the provider, the next login demotes.
+    let (redirect, ticket) = sign_in(&app, &issuer, email, &[]).await;
+    assert_eq!(redirect.path(), "/auth/login");
+    let (status, body) = login(&app, email, ticket).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["user"]["role"].is_null());
+}
+
+#[actix_web::test]
+async fn test_only_invited_or_allowed_users_are_provisioned() {
+    let issuer = start_issuer();
+    let context = oidc_context(&issuer).await;
+
+    let mut settings = context.settings.inner().await.clone();
+    settings.users = serde_json::from_value::<Users>(json!({
+        "allow_register": false,
+        "enforce_email_activation": false,
+        "email_whitelist": { "rules": ["*@staff.example.com"] }
+    }))
+    .unwrap();
+    context.settings.replace_inner(settings).await;
+
+    let app = test::init_service(server::app(context.clone())).await;
+
+    let (redirect, ticket) = sign_in(&app, &issuer, "eve@example.com", &[]).await;
+    assert_eq!(redirect.path(), "/auth/login");
+    let error = redirect
+        .query_pairs()
+        .find(|(k, _)| k == "oidc_error")
+        .map(|(_, v)| v.to_string());
+    assert_eq!(error.as_deref(), Some("oidc_not_provisioned"));
+    assert!(ticket.is_none());
+
+    let (redirect, ticket) = sign_in(&app, &issuer, "bob@staff.example.com", &[]).await;
+    assert_eq!(redirect.path(), "/auth/register");
+    assert!(ticket.is_some());
+
+    // An invitation lets the user in, and registering applies it.
+    let now = chrono::Utc::now().timestamp();
+    invitations::Entity::insert(invitations::ActiveModel {
+        id: ActiveValue::Set(Uuid::new_v4()),
+        user_id: ActiveValue::Set(None),
+        email: ActiveValue::Set("eve@example.com".to_string()),
+        role: ActiveValue::Set(None),
+        quota: ActiveValue::Set(Some(1024)),
+        created_at: ActiveValue::Set(now),
+        expires_at: ActiveValue::Set(now + 3600),
+    })
+    .exec_without_returning(&context.db)
+    .await
+    .unwrap();
+
+    let (redirect, ticket) = sign_in(&app, &issuer, "eve@example.com", &[]).await;
+    assert_eq!(redirect.path(), "/auth/register");
+
+    let body = helpers::build_curve25519_register_body(&app, "eve@example.com").await;
+    let req = test::TestRequest::post()
+        .uri("/api/auth/register")
+        .cookie(ticket.unwrap())
+        .set_json(&body)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let registered: Value = test::read_body_json(resp).await;
+    assert_eq!(registered["user"]["quota"], 1024);
+}
+
+#[actix_web::test]
+async fn test_sessions_and_tokens_do_not_outlive_the_provider() {
+    // An instance that had accounts and tokens before the gate was turned on.
+    let context = context::Context::mock_sqlite().await;
+    let app = test::init_service(server::app(context.clone())).await;
+    let email = "grace@example.com";
+
+    let body = helpers::build_curve25519_register_body(&app, email).await;
+    let req = test::TestRequest::post()
+        .uri("/api/auth/register")
+        .set_json(&body)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let (jwt, refresh) = helpers::extract_cookies(resp.headers());
+    let (jwt, refresh) = (jwt.unwrap(), refresh.unwrap());
+    let registered: Value = test::read_body_json(resp).await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/account/tokens")
+        .cookie(jwt.clone())
+        .set_json(json!({ "name": "nightly", "scopes": ["metadata:read"] }))
+        .to_request();
+    let created: Value = test::call_and_read_body_json(&app, req).await;
+    let token = created["token"].as_str().unwrap().to_string();
+
+    let issuer = start_issuer();
+    let mut gated = context.clone();
+    gated.config.oidc = Some(oidc_config(&issuer));
+    let app = test::init_service(server::app(gated.clone())).await;
+
+    // Tokens keep working by default...
+    let req = test::TestRequest::get()
+        .uri("/api/storage")
+        .append_header(("Authorization", format!("Bearer {token}")))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    // ...but never go back to the provider, so an instance can refuse them.
+    if let Some(oidc) = gated.config.oidc.as_mut() {
+        oidc.allow_personal_access_tokens = false;
+    }
+    let app = test::init_service(server::app(gated)).await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/storage")
+        .append_header(("Authorization", format!("Bearer {token}")))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    let refused: Value = test::read_body_json(resp).await;
+    assert_eq!(refused["message"], "oidc_personal_access_tokens_disabled");
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/account/tokens")
+        .cookie(jwt.clone())
+        .set_json(json!({ "name": "another", "scopes": ["metadata:read"] }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    // A session refreshes within the lifetime of its sign-in...
+    let req = test::TestRequest::post()
+        .uri("/api/auth/refresh")
+        .cookie(jwt)
+        .cookie(refresh)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let (jwt, refresh) = helpers::extract_cookies(resp.headers());
+
+    // ...but not past it, the user has to go through the provider again.
+    sessions::Entity::update_many()
+        .col_expr(
+            sessions::Column::CreatedAt,
+            Expr::value(chrono::Utc::now().timestamp() - 25 * 60 * 60),
+        )
+        .filter(
+            sessions::Column::UserId.eq(registered["user"]["id"]
+                .as_str()
+                .unwrap()
+                .parse::<Uuid>()
+                .unwrap()),
+        )
+        .exec(&context.db)
+        .await
+        .unwrap();
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/refresh")
+        .cookie(jwt.unwrap())
+        .cookie(refresh.unwrap())
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    let refused: Value = test::read_body_json(resp).await;
+    assert_eq!(refused["message"], "oidc_login_required");
+}
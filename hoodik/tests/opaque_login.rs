@@ -24,11 +24,7 @@ trait TestApp:
 impl<S, B> TestApp for S
 where
     B: actix_web::body::MessageBody,
-    S: Service<
-        actix_http::Request,
-        Response = ServiceResponse<B>,
-        Error = actix_web::Error,
-    >,
+    S: Service<actix_http::Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
 {
     type Body = B;
 }
@@ -68,7 +64,10 @@ async fn opaque_login(
     let resp = test::call_service(app, req).await;
     assert_eq!(resp.status(), StatusCode::OK);
     let body: Value = test::read_body_json(resp).await;
-    assert_eq!(body["method"], "opaque", "migrated account must offer OPAQUE");
+    assert_eq!(
+        body["method"], "opaque",
+        "migrated account must offer OPAQUE"
+    );
 
     let login_id = body["login_id"].as_str().unwrap();
     let credential_response = body["credential_response"].as_str().unwrap();
@@ -98,9 +97,16 @@ async fn test_opaque_register_then_login() {
     let resp = opaque_login(&app, PASSWORD)
         .await
         .expect("correct password completes the client KE");
-    assert_eq!(resp.status(), StatusCode::OK, "correct password logs in via OPAQUE");
+    assert_eq!(
+        resp.status(),
+        StatusCode::OK,
+        "correct password logs in via OPAQUE"
+    );
     let (jwt, refresh) = helpers::extract_cookies(resp.headers());
-    assert!(jwt.is_some() && refresh.is_some(), "OPAQUE login issues session cookies");
+    assert!(
+        jwt.is_some() && refresh.is_some(),
+        "OPAQUE login issues session cookies"
+    );
 }
 
 #[actix_web::test]
@@ -138,7 +144,10 @@ async fn test_legacy_account_offers_password_method() {
     let resp = test::call_service(&app, req).await;
     assert_eq!(resp.status(), StatusCode::OK);
     let body: Value = test::read_body_json(resp).await;
-    assert_eq!(body["method"], "password", "legacy account routes to password login");
+    assert_eq!(
+        body["method"], "password",
+        "legacy account routes to password login"
+    );
 }
 
 #[actix_web::test]
@@ -154,7 +163,10 @@ async fn test_unknown_email_does_not_leak_existence() {
     let resp = test::call_service(&app, req).await;
     assert_eq!(resp.status(), StatusCode::OK);
     let body: Value = test::read_body_json(resp).await;
-    assert_eq!(body["method"], "password", "unknown email is indistinguishable from legacy");
+    assert_eq!(
+        body["method"], "password",
+        "unknown email is indistinguishable from legacy"
+    );
 }
 
 /// `login/start` for a migrated account returns its stored KSF parameters so
@@ -206,7 +218,10 @@ async fn test_unknown_and_legacy_ksf_are_indistinguishable() {
 
     assert_eq!(legacy["method"], "password");
     assert_eq!(unknown["method"], "password");
-    assert_eq!(legacy["ksf"], unknown["ksf"], "same KSF for legacy and unknown");
+    assert_eq!(
+        legacy["ksf"], unknown["ksf"],
+        "same KSF for legacy and unknown"
+    );
     assert_eq!(legacy["ksf"]["m_cost"], 65536);
 }
 
@@ -245,7 +260,11 @@ async fn test_login_finish_with_returned_params_succeeds() {
         .set_json(json!({ "login_id": login_id, "credential_finalization": finish.finalization }))
         .to_request();
     let resp = test::call_service(&app, req).await;
-    assert_eq!(resp.status(), StatusCode::OK, "login with returned KSF params succeeds");
+    assert_eq!(
+        resp.status(),
+        StatusCode::OK,
+        "login with returned KSF params succeeds"
+    );
 }
 
 /// Every account row carries the current KSF constants — the column default
@@ -29,11 +29,7 @@ trait TestApp:
 impl<S, B> TestApp for S
 where
     B: actix_web::body::MessageBody,
-    S: Service<
-        actix_http::Request,
-        Response = ServiceResponse<B>,
-        Error = actix_web::Error,
-    >,
+    S: Service<actix_http::Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
 {
     type Body = B;
 }
@@ -89,13 +85,22 @@ async fn register_account(app: &impl TestApp, email: &str) -> Account {
         }))
         .to_request();
     let resp = test::call_service(app, req).await;
-    assert!(resp.status().is_success(), "register failed: {:?}", resp.status());
+    assert!(
+        resp.status().is_success(),
+        "register failed: {:?}",
+        resp.status()
+    );
     let (jwt, _) = helpers::extract_cookies(resp.headers());
     let jwt = jwt.expect("register response missing JWT cookie");
     let body: Value = test::read_body_json(resp).await;
     let user_id = entity::Uuid::parse_str(body["user"]["id"].as_str().unwrap()).unwrap();
 
-    Account { jwt, user_id, ed_private, bundle }
+    Account {
+        jwt,
+        user_id,
+        ed_private,
+        bundle,
+    }
 }
 
 /// Run the client half of a v2 password change: OPAQUE-register `NEW_PASSWORD`
@@ -227,9 +232,11 @@ async fn stored_envelope(db: &entity::DbConn, user_id: entity::Uuid) -> String {
 /// Sign the ownership proof with the account's identity key at `now`.
 fn sign_now(account: &Account, registration_upload: &str) -> (String, i64) {
     let issued_at = chrono::Utc::now().timestamp();
-    let signature =
-        cryptfns::ed25519::private::sign(&change_canonical(registration_upload, issued_at), &account.ed_private)
-            .unwrap();
+    let signature = cryptfns::ed25519::private::sign(
+        &change_canonical(registration_upload, issued_at),
+        &account.ed_private,
+    )
+    .unwrap();
     (signature, issued_at)
 }
 
@@ -242,7 +249,16 @@ async fn v2_password_change_rotates_login_and_rekeys_envelope() {
 
     let (upload, sealed) = prepare_change(&app, &account).await;
     let (signature, issued_at) = sign_now(&account, &upload);
-    let resp = post_finish(&app, &account.jwt, &upload, &sealed, &signature, issued_at, None).await;
+    let resp = post_finish(
+        &app,
+        &account.jwt,
+        &upload,
+        &sealed,
+        &signature,
+        issued_at,
+        None,
+    )
+    .await;
     assert_eq!(
         resp.status(),
         StatusCode::NO_CONTENT,
@@ -270,7 +286,11 @@ async fn v2_password_change_rotates_login_and_rekeys_envelope() {
     let (resp, export_key) = opaque_login(&app, NEW_PASSWORD)
         .await
         .expect("the new password completes the client KE");
-    assert_eq!(resp.status(), StatusCode::OK, "the new password logs in via OPAQUE");
+    assert_eq!(
+        resp.status(),
+        StatusCode::OK,
+        "the new password logs in via OPAQUE"
+    );
 
     let kek = cryptfns::envelope::derive_kek(&export_key).unwrap();
     let recovered = cryptfns::envelope::open(&kek, &sealed).unwrap();
@@ -292,7 +312,11 @@ async fn v2_password_change_requires_valid_ownership_signature() {
     // No signature at all.
     let (upload, sealed) = prepare_change(&app, &account).await;
     let resp = post_finish(&app, &account.jwt, &upload, &sealed, "", issued_at, None).await;
-    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED, "an empty signature proves nothing");
+    assert_eq!(
+        resp.status(),
+        StatusCode::UNAUTHORIZED,
+        "an empty signature proves nothing"
+    );
     let msg: Value = test::read_body_json(resp).await;
     assert_eq!(msg["message"], "ownership_proof_required");
 
@@ -301,18 +325,46 @@ async fn v2_password_change_requires_valid_ownership_signature() {
     let (upload, sealed) = prepare_change(&app, &account).await;
     let forged =
         cryptfns::ed25519::private::sign(&change_canonical(&upload, issued_at), &attacker).unwrap();
-    let resp = post_finish(&app, &account.jwt, &upload, &sealed, &forged, issued_at, None).await;
-    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED, "a signature by the wrong key is refused");
+    let resp = post_finish(
+        &app,
+        &account.jwt,
+        &upload,
+        &sealed,
+        &forged,
+        issued_at,
+        None,
+    )
+    .await;
+    assert_eq!(
+        resp.status(),
+        StatusCode::UNAUTHORIZED,
+        "a signature by the wrong key is refused"
+    );
 
     // A signature over a different upload than the one submitted: the server
     // rebuilds the canonical from the request's own upload, so the proof must
     // bind to it.
     let (upload, sealed) = prepare_change(&app, &account).await;
-    let mismatched =
-        cryptfns::ed25519::private::sign(&change_canonical("a-different-upload", issued_at), &account.ed_private)
-            .unwrap();
-    let resp = post_finish(&app, &account.jwt, &upload, &sealed, &mismatched, issued_at, None).await;
-    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED, "the signature must bind to the actual upload");
+    let mismatched = cryptfns::ed25519::private::sign(
+        &change_canonical("a-different-upload", issued_at),
+        &account.ed_private,
+    )
+    .unwrap();
+    let resp = post_finish(
+        &app,
+        &account.jwt,
+        &upload,
+        &sealed,
+        &mismatched,
+        issued_at,
+        None,
+    )
+    .await;
+    assert_eq!(
+        resp.status(),
+        StatusCode::UNAUTHORIZED,
+        "the signature must bind to the actual upload"
+    );
 
     assert_eq!(
         stored_envelope(&context.db, account.user_id).await,
@@ -332,9 +384,21 @@ async fn v2_password_change_rejects_stale_timestamp() {
     // Correctly signed, but the timestamp is well outside the ±300s window.
     let (upload, sealed) = prepare_change(&app, &account).await;
     let issued_at = chrono::Utc::now().timestamp() - 600;
-    let signature =
-        cryptfns::ed25519::private::sign(&change_canonical(&upload, issued_at), &account.ed_private).unwrap();
-    let resp = post_finish(&app, &account.jwt, &upload, &sealed, &signature, issued_at, None).await;
+    let signature = cryptfns::ed25519::private::sign(
+        &change_canonical(&upload, issued_at),
+        &account.ed_private,
+    )
+    .unwrap();
+    let resp = post_finish(
+        &app,
+        &account.jwt,
+        &upload,
+        &sealed,
+        &signature,
+        issued_at,
+        None,
+    )
+    .await;
     assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
     let msg: Value = test::read_body_json(resp).await;
     assert_eq!(msg["message"], "signature_timestamp_skew");
@@ -359,7 +423,16 @@ async fn v2_password_change_requires_totp_when_2fa_enabled() {
     // reject the change before it lands.
     let (upload, sealed) = prepare_change(&app, &account).await;
     let (signature, issued_at) = sign_now(&account, &upload);
-    let resp = post_finish(&app, &account.jwt, &upload, &sealed, &signature, issued_at, None).await;
+    let resp = post_finish(
+        &app,
+        &account.jwt,
+        &upload,
+        &sealed,
+        &signature,
+        issued_at,
+        None,
+    )
+    .await;
     assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
     let msg: Value = test::read_body_json(resp).await;
     assert_eq!(msg["message"], "invalid_otp_token");
@@ -393,7 +466,16 @@ async fn v2_password_change_rejects_legacy_account() {
     // The version gate fires before any signature or OPAQUE work, so dummy
     // artifacts are enough to reach it.
     let issued_at = chrono::Utc::now().timestamp();
-    let resp = post_finish(&app, &jwt, "upload", "envelope", "signature", issued_at, None).await;
+    let resp = post_finish(
+        &app,
+        &jwt,
+        "upload",
+        "envelope",
+        "signature",
+        issued_at,
+        None,
+    )
+    .await;
     assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
     let msg: Value = test::read_body_json(resp).await;
     assert_eq!(msg["message"], "password_change_requires_migration");
@@ -0,0 +1,177 @@
+//! Personal access tokens: scoped, folder-restricted and revocable
+//! credentials for scripts, sent as a bearer token.
+
+#[path = "./helpers.rs"]
+mod helpers;
+
+use actix_web::{http::StatusCode, test};
+use entity::Uuid;
+use hoodik::server;
+use serde_json::{json, Value};
+use storage::data::{app_file::AppFile, create_file::CreateFile};
+
+fn create_file(name: &str, mime: &str, file_id: Option<Uuid>) -> CreateFile {
+    let is_dir = mime == "dir";
+
+    CreateFile {
+        encrypted_key: Some(format!("{name}-key")),
+        encrypted_name: Some(name.to_string()),
+        encrypted_thumbnail: None,
+        search_tokens_hashed: Some(vec![format!("{name}:1")]),
+        name_hash: Some(name.to_string()),
+        mime: Some(mime.to_string()),
+        size: (!is_dir).then_some(5),
+        chunks: (!is_dir).then_some(1),
+        file_id: file_id.map(|id| id.to_string()),
+        file_modified_at: None,
+        md5: None,
+        sha1: None,
+        sha256: None,
+        blake2b: None,
+        cipher: None,
+        editable: None,
+    }
+}
+
+fn bearer(token: &str) -> (&'static str, String) {
+    ("Authorization", format!("Bearer {token}"))
+}
+
+/// The error message of a refused request. Forbidden and unauthorized share
+/// a status, so the message is what tells them apart.
+async fn refusal(app: &impl helpers::TestApp, req: actix_http::Request) -> String {
+    let resp = test::call_service(app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    let body: Value = test::read_body_json(resp).await;
+
+    body["message"].as_str().unwrap().to_string()
+}
+
+#[actix_web::test]
+async fn test_token_is_held_to_its_scopes_and_folder() {
+    let context = context::Context::mock_sqlite().await;
+    let app = test::init_service(server::app(context.clone())).await;
+    let account = helpers::register_curve25519(&app, "tokens@example.com").await;
+
+    let mut folders = vec![];
+    for name in ["backups", "private"] {
+        let req = test::TestRequest::post()
+            .uri("/api/storage")
+            .cookie(account.jwt.clone())
+            .set_json(create_file(name, "dir", None))
+            .to_request();
+        let folder: AppFile = test::call_and_read_body_json(&app, req).await;
+        folders.push(folder.id);
+    }
+    let (backups, private) = (folders[0], folders[1]);
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/account/tokens")
+        .cookie(account.jwt.clone())
+        .set_json(json!({ "name": "nightly", "scopes": ["upload"], "folder_id": Uuid::new_v4() }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/account/tokens")
+        .cookie(account.jwt.clone())
+        .set_json(json!({
+            "name": "nightly",
+            "scopes": ["upload", "metadata:read"],
+            "folder_id": backups,
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let created: Value = test::read_body_json(resp).await;
+    let token = created["token"].as_str().unwrap().to_string();
+    assert!(token.starts_with("hdk_"));
+    assert_eq!(created["scopes"], json!(["upload", "metadata:read"]));
+
+    // Uploads land in the token's folder and nowhere else.
+    let req = test::TestRequest::post()
+        .uri("/api/storage")
+        .append_header(bearer(&token))
+        .set_json(create_file("dump", "text/plain", Some(backups)))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+    for parent in [Some(private), None] {
+        let req = test::TestRequest::post()
+            .uri("/api/storage")
+            .append_header(bearer(&token))
+            .set_json(create_file("dump", "text/plain", parent))
+            .to_request();
+        assert_eq!(refusal(&app, req).await, "token_folder_restricted");
+    }
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/storage?dir_id={backups}"))
+        .append_header(bearer(&token))
+        .to_request();
+    let listing: Value = test::call_and_read_body_json(&app, req).await;
+    let file_id = listing["children"][0]["id"].as_str().unwrap().to_string();
+
+    let chunk = b"hello".to_vec();
+    let req = test::TestRequest::post()
+        .uri(&format!(
+            "/api/storage/{file_id}?checksum={}&chunk=0",
+            cryptfns::sha256::digest(chunk.as_slice())
+        ))
+        .append_header(bearer(&token))
+        .append_header(("Content-Type", "application/octet-stream"))
+        .set_payload(chunk)
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+    // Without the scope, or outside what tokens may reach at all, it is refused.
+    for (req, message) in [
+        (
+            test::TestRequest::get().uri(&format!("/api/storage/{file_id}")),
+            "token_scope_missing:download",
+        ),
+        (
+            test::TestRequest::get().uri("/api/storage"),
+            "token_folder_restricted",
+        ),
+        (
+            test::TestRequest::get().uri("/api/auth/account/tokens"),
+            "token_not_allowed",
+        ),
+        (
+            test::TestRequest::delete().uri(&format!("/api/storage/{file_id}")),
+            "token_not_allowed",
+        ),
+    ] {
+        let req = req.append_header(bearer(&token)).to_request();
+        assert_eq!(refusal(&app, req).await, message);
+    }
+
+    let req = test::TestRequest::get()
+        .uri("/api/auth/account/tokens")
+        .cookie(account.jwt.clone())
+        .to_request();
+    let tokens: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(tokens.as_array().unwrap().len(), 1);
+    assert!(tokens[0].get("token").is_none());
+    assert!(tokens[0]["last_used_at"].is_i64());
+
+    let req = test::TestRequest::post()
+        .uri(&format!(
+            "/api/auth/account/tokens/{}/revoke",
+            tokens[0]["id"].as_str().unwrap()
+        ))
+        .cookie(account.jwt.clone())
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::NO_CONTENT
+    );
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/storage?dir_id={backups}"))
+        .append_header(bearer(&token))
+        .to_request();
+    assert_eq!(refusal(&app, req).await, "invalid_token");
+}
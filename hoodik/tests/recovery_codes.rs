@@ -0,0 +1,110 @@
+//! Two factor recovery codes: issued when TOTP is enabled, accepted once in
+//! place of a token at login, and announced by email when used.
+
+#[path = "./helpers.rs"]
+mod helpers;
+
+use actix_web::{http::StatusCode, test};
+use context::SenderContract;
+use google_authenticator::GoogleAuthenticator;
+use hoodik::server;
+use serde_json::{json, Value};
+
+const EMAIL: &str = "recovery@example.com";
+const SUBJECT: &str = "Two factor recovery code used";
+
+/// Run an OPAQUE login with the given second factor and return the status.
+async fn login(app: &impl helpers::TestApp, token: &str) -> StatusCode {
+    let password = helpers::LEGACY_PASSWORD.as_bytes();
+    let start = cryptfns::opaque::client_login_start(password).unwrap();
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/login/start")
+        .set_json(json!({ "email": EMAIL, "credential_request": start.message }))
+        .to_request();
+    let body: Value = test::read_body_json(test::call_service(app, req).await).await;
+
+    let finish = cryptfns::opaque::client_login_finish(
+        &start.state,
+        body["credential_response"].as_str().unwrap(),
+        password,
+    )
+    .unwrap();
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/login/finish")
+        .set_json(json!({
+            "login_id": body["login_id"],
+            "credential_finalization": finish.finalization,
+            "token": token,
+        }))
+        .to_request();
+
+    test::call_service(app, req).await.status()
+}
+
+fn recovery_codes(body: &Value) -> Vec<String> {
+    body["recovery_codes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|code| code.as_str().unwrap().to_string())
+        .collect()
+}
+
+#[actix_web::test]
+async fn test_recovery_code_replaces_the_token_once() {
+    let context = context::Context::add_mock_sender(context::Context::mock_sqlite().await);
+    let app = test::init_service(server::app(context.clone())).await;
+    let account = helpers::register_curve25519(&app, EMAIL).await;
+
+    let secret = util::generate::generate_secret();
+    let totp = || GoogleAuthenticator::new().get_code(&secret, 0).unwrap();
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/two-factor")
+        .cookie(account.jwt.clone())
+        .set_json(json!({ "secret": secret, "token": totp() }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let codes = recovery_codes(&test::read_body_json(resp).await);
+    assert_eq!(codes.len(), 10);
+
+    let mailer = context.sender.as_ref().unwrap();
+    assert!(!mailer.has(SUBJECT));
+
+    assert_eq!(login(&app, &codes[0]).await, StatusCode::OK);
+    assert!(mailer.has(SUBJECT), "recovery code use was not announced");
+
+    // Burned.
+    assert_eq!(login(&app, &codes[0]).await, StatusCode::UNAUTHORIZED);
+    // Codes are not case or separator sensitive.
+    assert_eq!(
+        login(&app, &codes[1].to_uppercase().replace('-', " ")).await,
+        StatusCode::OK
+    );
+
+    // Regenerating replaces every code that is left.
+    let req = test::TestRequest::post()
+        .uri("/api/auth/two-factor/recovery-codes")
+        .cookie(account.jwt.clone())
+        .set_json(json!({ "token": totp() }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let fresh = recovery_codes(&test::read_body_json(resp).await);
+    assert_eq!(fresh.len(), 10);
+
+    assert_eq!(login(&app, &codes[2]).await, StatusCode::UNAUTHORIZED);
+    assert_eq!(login(&app, &fresh[0]).await, StatusCode::OK);
+
+    // A recovery code cannot mint new codes.
+    let req = test::TestRequest::post()
+        .uri("/api/auth/two-factor/recovery-codes")
+        .cookie(account.jwt.clone())
+        .set_json(json!({ "token": fresh[1] }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+}
@@ -18,11 +18,7 @@ trait TestApp:
 impl<S, B> TestApp for S
 where
     B: actix_web::body::MessageBody,
-    S: Service<
-        actix_http::Request,
-        Response = ServiceResponse<B>,
-        Error = actix_web::Error,
-    >,
+    S: Service<actix_http::Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
 {
     type Body = B;
 }
@@ -50,9 +46,12 @@ async fn signup_v2(
         .to_request();
     let body: Value = test::read_body_json(test::call_service(app, req).await).await;
     let reg_response = body["registration_response"].as_str().unwrap();
-    let reg_finish =
-        cryptfns::opaque::client_registration_finish(&reg_start.state, reg_response, password.as_bytes())
-            .unwrap();
+    let reg_finish = cryptfns::opaque::client_registration_finish(
+        &reg_start.state,
+        reg_response,
+        password.as_bytes(),
+    )
+    .unwrap();
 
     // Seal the private-key bundle under the KEK derived from the export key.
     let export_key = cryptfns::base64::decode(&reg_finish.export_key).unwrap();
@@ -84,7 +83,11 @@ async fn test_v2_signup_creates_a_migrated_account_and_opaque_login_works() {
     let email = "v2signup@example.com";
 
     let (resp, _x_private) = signup_v2(&app, email, PASSWORD).await;
-    assert_eq!(resp.status(), StatusCode::CREATED, "v2 signup authenticates");
+    assert_eq!(
+        resp.status(),
+        StatusCode::CREATED,
+        "v2 signup authenticates"
+    );
     let body: Value = test::read_body_json(resp).await;
     assert_eq!(body["user"]["security_version"], 1, "born migrated");
     assert_eq!(body["user"]["key_type"], "curve25519");
@@ -97,7 +100,10 @@ async fn test_v2_signup_creates_a_migrated_account_and_opaque_login_works() {
         .unwrap()
         .unwrap();
     assert_eq!(row.security_version, 1);
-    assert!(row.opaque_password_file.is_some(), "OPAQUE password file stored");
+    assert!(
+        row.opaque_password_file.is_some(),
+        "OPAQUE password file stored"
+    );
     assert!(row.password.is_none(), "no bcrypt password on a v2 account");
 
     // The password never crossed the wire, yet OPAQUE login completes.
@@ -107,12 +113,18 @@ async fn test_v2_signup_creates_a_migrated_account_and_opaque_login_works() {
         .set_json(json!({ "email": email, "credential_request": start.message }))
         .to_request();
     let body: Value = test::read_body_json(test::call_service(&app, req).await).await;
-    assert_eq!(body["method"], "opaque", "v2 account authenticates via OPAQUE");
+    assert_eq!(
+        body["method"], "opaque",
+        "v2 account authenticates via OPAQUE"
+    );
     let login_id = body["login_id"].as_str().unwrap();
     let credential_response = body["credential_response"].as_str().unwrap();
-    let finish =
-        cryptfns::opaque::client_login_finish(&start.state, credential_response, PASSWORD.as_bytes())
-            .unwrap();
+    let finish = cryptfns::opaque::client_login_finish(
+        &start.state,
+        credential_response,
+        PASSWORD.as_bytes(),
+    )
+    .unwrap();
     let req = test::TestRequest::post()
         .uri("/api/auth/login/finish")
         .set_json(json!({ "login_id": login_id, "credential_finalization": finish.finalization }))
@@ -133,7 +145,8 @@ async fn test_curve_signup_requires_opaque_upload_and_forbids_password() {
     let ed_public = cryptfns::ed25519::public::from_private(&ed_private).unwrap();
     let fingerprint = cryptfns::spki::fingerprint(&ed_public).unwrap();
     let x_public =
-        cryptfns::ecdh::public::from_private(&cryptfns::ecdh::private::generate().unwrap()).unwrap();
+        cryptfns::ecdh::public::from_private(&cryptfns::ecdh::private::generate().unwrap())
+            .unwrap();
 
     // A curve signup with no OPAQUE upload is rejected.
     let req = test::TestRequest::post()
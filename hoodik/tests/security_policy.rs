@@ -0,0 +1,205 @@
+//! Security policy from the admin settings: required two-factor with its
+//! grace period, the attested password score and the maximum session
+//! lifetime. Sessions that fall short of a changed policy are confined to
+//! enrollment rather than ended.
+
+#[path = "./helpers.rs"]
+mod helpers;
+
+use actix_web::{cookie::Cookie, http::StatusCode, test};
+use entity::{sessions, ColumnTrait, EntityTrait, QueryFilter};
+use google_authenticator::GoogleAuthenticator;
+use hoodik::server;
+use serde_json::{json, Value};
+use settings::{
+    data::{Security, TwoFactorRequirement},
+    factory::Factory,
+};
+
+async fn set_security(context: &context::Context, apply: impl FnOnce(&mut Security)) {
+    let mut settings = context.settings.inner().await.clone();
+    let previous = settings.security.clone();
+    apply(&mut settings.security);
+    settings
+        .security
+        .carry_enforcement(&previous, chrono::Utc::now().timestamp());
+    context.settings.replace_inner(settings).await;
+}
+
+/// Register with the given attested password score, returning the response
+/// status, body and session cookies.
+async fn register(
+    app: &impl helpers::TestApp,
+    email: &str,
+    score: u8,
+) -> (
+    StatusCode,
+    Value,
+    Option<(Cookie<'static>, Cookie<'static>)>,
+) {
+    let mut body = helpers::build_curve25519_register_body(app, email).await;
+    body["password_score"] = json!(score);
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/register")
+        .set_json(&body)
+        .to_request();
+    let resp = test::call_service(app, req).await;
+    let status = resp.status();
+    let (jwt, refresh) = helpers::extract_cookies(resp.headers());
+    let body = test::read_body_json(resp).await;
+
+    (status, body, jwt.zip(refresh))
+}
+
+async fn refresh(
+    app: &impl helpers::TestApp,
+    (jwt, refresh): &(Cookie<'static>, Cookie<'static>),
+) -> (
+    StatusCode,
+    Value,
+    Option<(Cookie<'static>, Cookie<'static>)>,
+) {
+    let req = test::TestRequest::post()
+        .uri("/api/auth/refresh")
+        .cookie(jwt.clone())
+        .cookie(refresh.clone())
+        .to_request();
+    let resp = test::call_service(app, req).await;
+    let status = resp.status();
+    let (jwt, refresh) = helpers::extract_cookies(resp.headers());
+    let body = test::read_body_json(resp).await;
+
+    (status, body, jwt.zip(refresh))
+}
+
+async fn list_files(app: &impl helpers::TestApp, jwt: &Cookie<'static>) -> StatusCode {
+    let req = test::TestRequest::get()
+        .uri("/api/storage")
+        .cookie(jwt.clone())
+        .to_request();
+
+    test::call_service(app, req).await.status()
+}
+
+#[actix_web::test]
+async fn test_required_two_factor_confines_the_session_to_enrollment() {
+    let context = context::Context::mock_sqlite().await;
+    let app = test::init_service(server::app(context.clone())).await;
+
+    let (status, _, cookies) = register(&app, "enroll@example.com", 4).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let cookies = cookies.unwrap();
+
+    set_security(&context, |security| {
+        security.set_two_factor(TwoFactorRequirement::All);
+        security.set_two_factor_grace_days(0);
+    })
+    .await;
+
+    // The live session is not ended, its next token is confined.
+    let (status, body, cookies) = refresh(&app, &cookies).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["enrollment"]["required"], json!(["two_factor"]));
+    let cookies = cookies.unwrap();
+
+    let req = test::TestRequest::get()
+        .uri("/api/storage")
+        .cookie(cookies.0.clone())
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    let refused: Value = test::read_body_json(resp).await;
+    assert_eq!(refused["message"], "enrollment_required");
+
+    let secret = util::generate::generate_secret();
+    let token = GoogleAuthenticator::new().get_code(&secret, 0).unwrap();
+    let req = test::TestRequest::post()
+        .uri("/api/auth/two-factor")
+        .cookie(cookies.0.clone())
+        .set_json(json!({ "secret": secret, "token": token }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+    let (status, body, cookies) = refresh(&app, &cookies).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.get("enrollment").is_none());
+    assert_eq!(list_files(&app, &cookies.unwrap().0).await, StatusCode::OK);
+}
+
+#[actix_web::test]
+async fn test_two_factor_is_only_announced_during_the_grace_period() {
+    let context = context::Context::mock_sqlite().await;
+    let app = test::init_service(server::app(context.clone())).await;
+
+    set_security(&context, |security| {
+        security.set_two_factor(TwoFactorRequirement::All);
+    })
+    .await;
+
+    let (status, body, cookies) = register(&app, "grace@example.com", 4).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(body["enrollment"]["required"], json!([]));
+    assert!(body["enrollment"]["two_factor_due_at"].as_i64().unwrap() > 0);
+    assert_eq!(list_files(&app, &cookies.unwrap().0).await, StatusCode::OK);
+}
+
+#[actix_web::test]
+async fn test_password_score_is_held_to_the_minimum() {
+    let context = context::Context::mock_sqlite().await;
+    let app = test::init_service(server::app(context.clone())).await;
+
+    let (status, _, cookies) = register(&app, "older@example.com", 2).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let cookies = cookies.unwrap();
+
+    set_security(&context, |security| security.set_min_password_score(3)).await;
+
+    let (status, body, _) = register(&app, "weak@example.com", 2).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(body.to_string().contains("password_too_weak"));
+
+    let (status, _, _) = register(&app, "strong@example.com", 3).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    // The account registered before the minimum has to change its password.
+    let (status, body, _) = refresh(&app, &cookies).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["enrollment"]["required"], json!(["password"]));
+}
+
+#[actix_web::test]
+async fn test_sessions_end_after_the_maximum_lifetime() {
+    let context = context::Context::mock_sqlite().await;
+    let app = test::init_service(server::app(context.clone())).await;
+
+    let (_, body, cookies) = register(&app, "lifetime@example.com", 4).await;
+    let cookies = cookies.unwrap();
+
+    set_security(&context, |security| {
+        security.set_max_session_lifetime_days(1)
+    })
+    .await;
+
+    let (status, _, fresh) = refresh(&app, &cookies).await;
+    assert_eq!(status, StatusCode::OK);
+    let cookies = fresh.unwrap();
+
+    let two_days_ago = chrono::Utc::now().timestamp() - 2 * 24 * 60 * 60;
+    sessions::Entity::update_many()
+        .col_expr(
+            sessions::Column::CreatedAt,
+            entity::Expr::value(two_days_ago),
+        )
+        .filter(
+            sessions::Column::Id
+                .eq(entity::Uuid::parse_str(body["session"]["id"].as_str().unwrap()).unwrap()),
+        )
+        .exec(&context.db)
+        .await
+        .unwrap();
+
+    let (status, body, _) = refresh(&app, &cookies).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["message"], "session_lifetime_exceeded");
+}
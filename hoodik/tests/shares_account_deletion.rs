@@ -15,9 +15,7 @@ mod shares_common;
 
 use actix_web::{http::StatusCode, test};
 use cryptfns::asn1::ShareRoleEnum;
-use entity::{
-    share_events, user_files, users, ColumnTrait, EntityTrait, QueryFilter,
-};
+use entity::{share_events, user_files, users, ColumnTrait, EntityTrait, QueryFilter};
 use hoodik::server;
 
 use crate::shares_common::*;
@@ -136,7 +134,10 @@ async fn test_account_deletion_emits_audit_rows_with_system_attribution() {
         random_nonce(),
         now_secs(),
     );
-    assert_eq!(post_share!(app, bob, envelope).status(), StatusCode::CREATED);
+    assert_eq!(
+        post_share!(app, bob, envelope).status(),
+        StatusCode::CREATED
+    );
 
     let before_count = share_events::Entity::find()
         .all(&context.db)
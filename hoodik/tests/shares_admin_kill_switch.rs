@@ -57,8 +57,7 @@ async fn test_endpoints_return_503_when_disabled() {
     let body: Value = test::read_body_json(resp).await;
     assert_eq!(body["message"], "sharing_disabled");
 
-    let revoke_body =
-        build_revoke_body(&alice, &bob, file.id, ShareRoleEnum::Reader, now_secs());
+    let revoke_body = build_revoke_body(&alice, &bob, file.id, ShareRoleEnum::Reader, now_secs());
     let resp = delete_share!(app, alice, file.id, bob.user_id, revoke_body);
     assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
 
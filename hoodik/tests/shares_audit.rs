@@ -11,9 +11,7 @@ use cryptfns::asn1::{
     encode_audit_event_sig_input_v1, AuditEventActionEnum, AuditEventSigInputV1, ShareRoleEnum,
     AUDIT_EVENT_SIG_V1_PREFIX,
 };
-use entity::{
-    share_events, ActiveValue, ColumnTrait, EntityTrait, QueryFilter,
-};
+use entity::{share_events, ActiveValue, ColumnTrait, EntityTrait, QueryFilter};
 use hoodik::server;
 use serde_json::Value;
 use sha2::{Digest, Sha256};
@@ -45,7 +43,10 @@ async fn test_audit_row_inserted_on_grant() {
     assert_eq!(row.share_role_before, None);
     assert_eq!(row.share_role_after.as_deref(), Some("reader"));
     assert!(
-        row.sender_signature.as_ref().map(|v| !v.is_empty()).unwrap_or(false),
+        row.sender_signature
+            .as_ref()
+            .map(|v| !v.is_empty())
+            .unwrap_or(false),
         "grant rows must carry a non-empty sender signature"
     );
     assert_eq!(row.this_event_hash.len(), 32);
@@ -110,18 +111,14 @@ async fn test_event_signature_mismatch_rejected_400() {
     let mut sig_bytes = cryptfns::base64::decode(&sig_b64).unwrap();
     let last_idx = sig_bytes.len() - 1;
     sig_bytes[last_idx] ^= 0x01;
-    tampered["event_signature"] =
-        Value::String(cryptfns::base64::encode(&sig_bytes));
+    tampered["event_signature"] = Value::String(cryptfns::base64::encode(&sig_bytes));
 
     let resp = post_share!(app, alice, tampered);
     assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
     let body: Value = test::read_body_json(resp).await;
     assert_eq!(body["message"], "event_signature_invalid");
 
-    let rows = share_events::Entity::find()
-        .all(&context.db)
-        .await
-        .unwrap();
+    let rows = share_events::Entity::find().all(&context.db).await.unwrap();
     assert!(
         rows.is_empty(),
         "rolled-back transaction must leave share_events untouched"
@@ -131,7 +128,10 @@ async fn test_event_signature_mismatch_rejected_400() {
         .all(&context.db)
         .await
         .unwrap();
-    assert!(touched.is_empty(), "rolled-back transaction must leave user_files untouched");
+    assert!(
+        touched.is_empty(),
+        "rolled-back transaction must leave user_files untouched"
+    );
 }
 
 #[actix_web::test]
@@ -161,8 +161,10 @@ async fn test_per_sender_chain_continuous_across_multiple_events() {
     // Walk the chain by following prev_event_hash — created_at ties give an
     // ambiguous read order, but the chain itself is a linked list with one
     // head (prev_event_hash IS NULL) and unique back-pointers.
-    let by_hash: std::collections::HashMap<Vec<u8>, &share_events::Model> =
-        rows.iter().map(|r| (r.this_event_hash.clone(), r)).collect();
+    let by_hash: std::collections::HashMap<Vec<u8>, &share_events::Model> = rows
+        .iter()
+        .map(|r| (r.this_event_hash.clone(), r))
+        .collect();
     let head = rows
         .iter()
         .find(|r| r.prev_event_hash.is_none())
@@ -177,7 +179,11 @@ async fn test_per_sender_chain_continuous_across_multiple_events() {
         next_prev = row.this_event_hash.clone();
     }
     assert_eq!(walked.len(), rows.len(), "chain walk must cover every row");
-    assert_eq!(by_hash.len(), rows.len(), "this_event_hash values must be unique");
+    assert_eq!(
+        by_hash.len(),
+        rows.len(),
+        "this_event_hash values must be unique"
+    );
 
     // Walking the chain forward and recomputing each row's hash must match.
     let mut prev: Vec<u8> = vec![0u8; 32];
@@ -187,15 +193,12 @@ async fn test_per_sender_chain_continuous_across_multiple_events() {
             recipient_id: row.recipient_id.unwrap_or(entity::Uuid::nil()).into_bytes(),
             file_id: row.file_id.unwrap_or(entity::Uuid::nil()).into_bytes(),
             action: row.action.clone(),
-            share_role: row
-                .share_role_after
-                .as_deref()
-                .and_then(|s| match s {
-                    "reader" => Some(ShareRoleEnum::Reader),
-                    "editor" => Some(ShareRoleEnum::Editor),
-                    "co-owner" => Some(ShareRoleEnum::CoOwner),
-                    _ => None,
-                }),
+            share_role: row.share_role_after.as_deref().and_then(|s| match s {
+                "reader" => Some(ShareRoleEnum::Reader),
+                "editor" => Some(ShareRoleEnum::Editor),
+                "co-owner" => Some(ShareRoleEnum::CoOwner),
+                _ => None,
+            }),
             created_at: row.created_at,
         };
         let der = cryptfns::asn1::encode_audit_event_v1(&row_input).unwrap();
@@ -455,7 +458,10 @@ async fn test_cascade_revoke_audit_rows_have_null_sender_signature() {
         .unwrap();
     assert_eq!(cascade_rows.len(), 1);
     let cascade = &cascade_rows[0];
-    assert!(cascade.sender_id.is_none(), "cascade rows have NULL sender_id");
+    assert!(
+        cascade.sender_id.is_none(),
+        "cascade rows have NULL sender_id"
+    );
     assert!(
         cascade.sender_signature.is_none(),
         "cascade rows have NULL sender_signature"
@@ -511,7 +517,10 @@ async fn test_chain_walk_over_events_route_matches_client_verifier() {
     assert_eq!(resp.status(), StatusCode::OK);
     let body: Value = test::read_body_json(resp).await;
     let events = body["events"].as_array().expect("events array").clone();
-    assert!(events.len() >= 3, "alice sees grant + reshare + cascade rows");
+    assert!(
+        events.len() >= 3,
+        "alice sees grant + reshare + cascade rows"
+    );
 
     // Bucket by sender_id (string or "__system__" for NULL) and walk
     // oldest-first inside each bucket, mirroring verifyChain().
@@ -575,8 +584,8 @@ async fn test_chain_walk_over_events_route_matches_client_verifier() {
             hasher.update(&recompute_against);
             hasher.update(&der);
             let computed = hasher.finalize().to_vec();
-            let stored = cryptfns::base64::decode(row["this_event_hash"].as_str().unwrap())
-                .unwrap();
+            let stored =
+                cryptfns::base64::decode(row["this_event_hash"].as_str().unwrap()).unwrap();
             assert_eq!(
                 computed, stored,
                 "row {} (action={}) failed client-style chain verification",
@@ -11,7 +11,9 @@ mod shares_common;
 
 use actix_web::{http::StatusCode, test};
 use cryptfns::asn1::ShareRoleEnum;
-use entity::{links, share_events, user_files, users, ActiveValue, ColumnTrait, EntityTrait, QueryFilter};
+use entity::{
+    links, share_events, user_files, users, ActiveValue, ColumnTrait, EntityTrait, QueryFilter,
+};
 use hoodik::server;
 use serde_json::Value;
 use shares::data::app_share::AppShare;
@@ -56,7 +58,10 @@ async fn test_owner_grants_reader_to_recipient_then_revokes() {
         .all(&context.db)
         .await
         .unwrap();
-    assert!(remaining.is_empty(), "bob's row should be gone after revoke");
+    assert!(
+        remaining.is_empty(),
+        "bob's row should be gone after revoke"
+    );
 }
 
 #[actix_web::test]
@@ -149,7 +154,10 @@ async fn test_owner_can_share_with_unverified_recipient() {
         .one(&context.db)
         .await
         .unwrap();
-    assert!(row.is_some(), "unverified recipient should have a user_files row after share");
+    assert!(
+        row.is_some(),
+        "unverified recipient should have a user_files row after share"
+    );
 }
 
 #[actix_web::test]
@@ -324,6 +332,8 @@ async fn test_links_index_is_owner_only() {
         encrypted_file_key: ActiveValue::Set(Some("encrypted-file-key".to_string())),
         created_at: ActiveValue::Set(now_secs()),
         expires_at: ActiveValue::Set(None),
+        max_downloads: ActiveValue::Set(None),
+        password_hash: ActiveValue::Set(None),
     })
     .exec_without_returning(&context.db)
     .await
@@ -393,6 +403,8 @@ async fn test_recipient_cannot_revoke_owner_link() {
         encrypted_file_key: ActiveValue::Set(Some("encrypted-file-key".to_string())),
         created_at: ActiveValue::Set(now_secs()),
         expires_at: ActiveValue::Set(None),
+        max_downloads: ActiveValue::Set(None),
+        password_hash: ActiveValue::Set(None),
     })
     .exec_without_returning(&context.db)
     .await
@@ -430,13 +442,8 @@ async fn test_create_share_persists_supplied_member_signature() {
         random_nonce(),
         timestamp,
     );
-    let envelope = inject_member_signature(
-        envelope,
-        &alice,
-        &bob,
-        ShareRoleEnum::Reader,
-        timestamp,
-    );
+    let envelope =
+        inject_member_signature(envelope, &alice, &bob, ShareRoleEnum::Reader, timestamp);
     let resp = post_share!(app, alice, envelope);
     assert_eq!(resp.status(), StatusCode::CREATED);
 
@@ -492,13 +499,7 @@ async fn test_create_share_rejects_member_signature_signed_by_stranger() {
         timestamp,
     );
     // Sign with Eve's key but submit through Alice's session.
-    let envelope = inject_member_signature(
-        envelope,
-        &eve,
-        &bob,
-        ShareRoleEnum::Reader,
-        timestamp,
-    );
+    let envelope = inject_member_signature(envelope, &eve, &bob, ShareRoleEnum::Reader, timestamp);
     let resp = post_share!(app, alice, envelope);
     assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
     let body: Value = test::read_body_json(resp).await;
@@ -530,13 +531,8 @@ async fn test_co_owner_reshare_persists_co_owner_signed_member_signature() {
         random_nonce(),
         timestamp,
     );
-    let envelope = inject_member_signature(
-        envelope,
-        &bob,
-        &carol,
-        ShareRoleEnum::Reader,
-        timestamp,
-    );
+    let envelope =
+        inject_member_signature(envelope, &bob, &carol, ShareRoleEnum::Reader, timestamp);
     let resp = post_share!(app, bob, envelope);
     assert_eq!(resp.status(), StatusCode::CREATED);
 
@@ -616,7 +612,10 @@ async fn test_co_owner_can_revoke_recipient_granted_by_owner() {
         .all(&context.db)
         .await
         .unwrap();
-    assert!(remaining.is_empty(), "carol's row should be gone after co-owner revoke");
+    assert!(
+        remaining.is_empty(),
+        "carol's row should be gone after co-owner revoke"
+    );
 }
 
 #[actix_web::test]
@@ -843,7 +842,10 @@ async fn test_get_mine_returns_only_roots_not_descendants() {
     assert_eq!(page.total, 2, "list should only carry the two roots");
     assert!(file_ids.contains(&folder.id), "folder root present");
     assert!(file_ids.contains(&standalone.id), "standalone file present");
-    assert!(!file_ids.contains(&child_one.id), "descendants must not flatten");
+    assert!(
+        !file_ids.contains(&child_one.id),
+        "descendants must not flatten"
+    );
 
     // Bob still has a `user_files` row for the descendant — needed for
     // navigation + key fan-out — it just doesn't surface at the root.
@@ -956,7 +958,10 @@ async fn test_recipient_self_removes_from_shared_file() {
         .all(&context.db)
         .await
         .unwrap();
-    assert!(bob_rows.is_empty(), "bob's user_files row is gone after self-remove");
+    assert!(
+        bob_rows.is_empty(),
+        "bob's user_files row is gone after self-remove"
+    );
 
     let alice_owner_row = user_files::Entity::find()
         .filter(user_files::Column::FileId.eq(file.id))
@@ -976,7 +981,10 @@ async fn test_recipient_self_removes_from_shared_file() {
     assert_eq!(events.len(), 1);
     assert_eq!(events[0].sender_id, Some(bob.user_id));
     assert_eq!(events[0].recipient_id, Some(bob.user_id));
-    assert!(events[0].sender_signature.is_some(), "self-remove is signed by the recipient");
+    assert!(
+        events[0].sender_signature.is_some(),
+        "self-remove is signed by the recipient"
+    );
 }
 
 #[actix_web::test]
@@ -1000,7 +1008,10 @@ async fn test_co_owner_self_remove_cascades_their_grants() {
         random_nonce(),
         now_secs(),
     );
-    assert_eq!(post_share!(app, bob, envelope).status(), StatusCode::CREATED);
+    assert_eq!(
+        post_share!(app, bob, envelope).status(),
+        StatusCode::CREATED
+    );
 
     let revoke = build_revoke_body(&bob, &bob, file.id, ShareRoleEnum::CoOwner, now_secs());
     let resp = delete_share!(app, bob, file.id, bob.user_id, revoke);
@@ -1012,7 +1023,10 @@ async fn test_co_owner_self_remove_cascades_their_grants() {
         .all(&context.db)
         .await
         .unwrap();
-    assert!(remaining.is_empty(), "bob's row and carol's downstream row both gone");
+    assert!(
+        remaining.is_empty(),
+        "bob's row and carol's downstream row both gone"
+    );
 
     let cascade = share_events::Entity::find()
         .filter(share_events::Column::Action.eq("shared_by_co_owner_revoked"))
@@ -1021,7 +1035,10 @@ async fn test_co_owner_self_remove_cascades_their_grants() {
         .unwrap();
     assert_eq!(cascade.len(), 1);
     assert_eq!(cascade[0].recipient_id, Some(carol.user_id));
-    assert!(cascade[0].sender_id.is_none(), "cascade is system-attributed");
+    assert!(
+        cascade[0].sender_id.is_none(),
+        "cascade is system-attributed"
+    );
 }
 
 #[actix_web::test]
@@ -1063,7 +1080,10 @@ async fn test_recipient_self_removes_from_shared_folder_without_list_signature()
         &members_after,
         &alice,
     );
-    assert_eq!(post_share!(app, alice, envelope).status(), StatusCode::CREATED);
+    assert_eq!(
+        post_share!(app, alice, envelope).status(),
+        StatusCode::CREATED
+    );
 
     // The leaving recipient can't sign the post-mutation roster, so the
     // revoke body omits members_list_signature. Folder self-remove must
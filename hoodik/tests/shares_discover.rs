@@ -11,7 +11,6 @@ use entity::{users, ActiveValue, EntityTrait};
 use hoodik::server;
 use shares::data::discover::DiscoveredUser;
 
-
 #[actix_web::test]
 async fn test_discover_returns_user_pubkey_fingerprint() {
     let context = context::Context::mock_sqlite().await;
@@ -19,7 +18,6 @@ async fn test_discover_returns_user_pubkey_fingerprint() {
 
     register_user!(app, context, alice, "alice@example.com");
     register_user!(app, context, bob, "bob@example.com");
-    shares::test_support::reset_discover_rate_limit();
 
     let req = test::TestRequest::get()
         .uri("/api/users/discover?email=bob@example.com")
@@ -41,7 +39,6 @@ async fn test_discover_self_returns_400_cannot_discover_self() {
     let app = test::init_service(server::app(context.clone())).await;
 
     register_user!(app, context, alice, "alice@example.com");
-    shares::test_support::reset_discover_rate_limit();
 
     let req = test::TestRequest::get()
         .uri("/api/users/discover?email=alice@example.com")
@@ -60,7 +57,6 @@ async fn test_discover_unknown_email_returns_404() {
     let app = test::init_service(server::app(context.clone())).await;
 
     register_user!(app, context, alice, "alice@example.com");
-    shares::test_support::reset_discover_rate_limit();
 
     let req = test::TestRequest::get()
         .uri("/api/users/discover?email=nobody@example.com")
@@ -86,7 +82,6 @@ async fn test_discover_unverified_user_is_discoverable() {
 
     register_user!(app, context, alice, "alice@example.com");
     register_user!(app, context, ghost, "ghost@example.com");
-    shares::test_support::reset_discover_rate_limit();
 
     // Strip the ghost's verification stamp to model the unverified-but-
     // registered state. The discover endpoint still returns the user.
@@ -117,7 +112,6 @@ async fn test_discover_rate_limit_21st_request_returns_429() {
 
     register_user!(app, context, alice, "alice@example.com");
     register_user!(app, context, _bob, "bob@example.com");
-    shares::test_support::reset_discover_rate_limit();
 
     for _ in 0..20 {
         let req = test::TestRequest::get()
@@ -145,7 +139,6 @@ async fn test_discover_rate_limit_increments_on_both_hit_and_miss() {
 
     register_user!(app, context, alice, "alice@example.com");
     register_user!(app, context, _bob, "bob@example.com");
-    shares::test_support::reset_discover_rate_limit();
 
     // Twenty 404 misses: each one increments the bucket.
     for _ in 0..20 {
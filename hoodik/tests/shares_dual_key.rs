@@ -126,8 +126,11 @@ async fn test_registration_rejects_bad_curve25519_input() {
 
     // The RSA key wraps and signs; a second key is a client bug.
     let (_, rsa_public_pem, rsa_fingerprint) = generate_keypair();
-    let mut rsa_with_wrapping =
-        make_create_user("rsa-with-wrapping@example.com", &rsa_public_pem, &rsa_fingerprint);
+    let mut rsa_with_wrapping = make_create_user(
+        "rsa-with-wrapping@example.com",
+        &rsa_public_pem,
+        &rsa_fingerprint,
+    );
     rsa_with_wrapping.wrapping_pubkey = Some(wrapping_public_pem.clone());
     register_expecting_422!(app, rsa_with_wrapping);
 
@@ -168,7 +171,10 @@ async fn test_share_matrix_all_four_combos() {
         random_nonce(),
         now_secs(),
     );
-    assert_eq!(post_share!(app, alice, envelope).status(), StatusCode::CREATED);
+    assert_eq!(
+        post_share!(app, alice, envelope).status(),
+        StatusCode::CREATED
+    );
     let row = share_row(&context.db, alice_file.id, bob.user_id).await;
     assert_eq!(row.encrypted_key, bob_wrap);
     assert_eq!(
@@ -184,17 +190,26 @@ async fn test_share_matrix_all_four_combos() {
         &carol,
         ShareRoleEnum::Reader,
         alice_file.id,
-        vec![(alice_file.id, cryptfns::base64::decode(&carol_wrap).unwrap())],
+        vec![(
+            alice_file.id,
+            cryptfns::base64::decode(&carol_wrap).unwrap(),
+        )],
         random_nonce(),
         now_secs(),
     );
-    assert_eq!(post_share!(app, alice, envelope).status(), StatusCode::CREATED);
+    assert_eq!(
+        post_share!(app, alice, envelope).status(),
+        StatusCode::CREATED
+    );
     let row = share_row(&context.db, alice_file.id, carol.user_id).await;
     assert_eq!(row.encrypted_key, carol_wrap);
     assert_eq!(
-        cryptfns::ecdh::unwrap(&row.encrypted_key, carol.wrapping_private_pem.as_ref().unwrap())
-            .unwrap()
-            .as_slice(),
+        cryptfns::ecdh::unwrap(
+            &row.encrypted_key,
+            carol.wrapping_private_pem.as_ref().unwrap()
+        )
+        .unwrap()
+        .as_slice(),
         file_key
     );
 
@@ -224,7 +239,10 @@ async fn test_share_matrix_all_four_combos() {
         random_nonce(),
         now_secs(),
     );
-    assert_eq!(post_share!(app, carol, envelope).status(), StatusCode::CREATED);
+    assert_eq!(
+        post_share!(app, carol, envelope).status(),
+        StatusCode::CREATED
+    );
     let row = share_row(&context.db, carol_file.id, bob.user_id).await;
     assert_eq!(row.encrypted_key, bob_wrap_from_carol);
     assert_eq!(
@@ -244,13 +262,19 @@ async fn test_share_matrix_all_four_combos() {
         random_nonce(),
         now_secs(),
     );
-    assert_eq!(post_share!(app, carol, envelope).status(), StatusCode::CREATED);
+    assert_eq!(
+        post_share!(app, carol, envelope).status(),
+        StatusCode::CREATED
+    );
     let row = share_row(&context.db, carol_file.id, dave.user_id).await;
     assert_eq!(row.encrypted_key, dave_wrap);
     assert_eq!(
-        cryptfns::ecdh::unwrap(&row.encrypted_key, dave.wrapping_private_pem.as_ref().unwrap())
-            .unwrap()
-            .as_slice(),
+        cryptfns::ecdh::unwrap(
+            &row.encrypted_key,
+            dave.wrapping_private_pem.as_ref().unwrap()
+        )
+        .unwrap()
+        .as_slice(),
         file_key
     );
 }
@@ -298,7 +322,9 @@ async fn test_member_signature_binds_curve25519_recipient() {
     let timestamp = now_secs();
     let wrapped = cryptfns::ecdh::wrap(
         b"file-key-for-dave",
-        dave.wrapping_public_pem.as_ref().expect("dave wrapping key"),
+        dave.wrapping_public_pem
+            .as_ref()
+            .expect("dave wrapping key"),
     )
     .unwrap();
     let envelope = build_share_envelope(
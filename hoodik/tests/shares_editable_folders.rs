@@ -63,7 +63,10 @@ async fn test_editor_uploads_into_shared_folder_via_multikey_endpoint() {
         &members_after,
         &alice,
     );
-    assert_eq!(post_share!(app, alice, envelope).status(), StatusCode::CREATED);
+    assert_eq!(
+        post_share!(app, alice, envelope).status(),
+        StatusCode::CREATED
+    );
 
     let new_file_id = Uuid::new_v4();
     let timestamp = now_secs();
@@ -141,7 +144,10 @@ async fn test_owner_multikey_upload_fans_out_to_existing_members() {
         &members_after,
         &alice,
     );
-    assert_eq!(post_share!(app, alice, envelope).status(), StatusCode::CREATED);
+    assert_eq!(
+        post_share!(app, alice, envelope).status(),
+        StatusCode::CREATED
+    );
 
     // Alice (the folder owner) uploads through the same multi-key
     // endpoint a recipient would use, claiming `is_owner_of_file=true`
@@ -203,8 +209,7 @@ async fn test_multikey_upload_then_chunk_upload_finalizes() {
     // triggers `finish()`, and `finished_upload_at` is stamped. Without
     // this the UI shows "forever uploading" even though the bytes are
     // on disk.
-    let context =
-        context::Context::mock_with_data_dir(Some("../data-test".to_string())).await;
+    let context = context::Context::mock_with_data_dir(Some("../data-test".to_string())).await;
     let app = test::init_service(server::app(context.clone())).await;
 
     register_user!(app, context, alice, "alice@example.com");
@@ -222,7 +227,10 @@ async fn test_multikey_upload_then_chunk_upload_finalizes() {
         &members_after,
         &alice,
     );
-    assert_eq!(post_share!(app, alice, envelope).status(), StatusCode::CREATED);
+    assert_eq!(
+        post_share!(app, alice, envelope).status(),
+        StatusCode::CREATED
+    );
 
     let new_file_id = Uuid::new_v4();
     let timestamp = now_secs();
@@ -250,7 +258,10 @@ async fn test_multikey_upload_then_chunk_upload_finalizes() {
         .cookie(bob.jwt.clone())
         .set_json(&body)
         .to_request();
-    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::CREATED
+    );
 
     // Push the only chunk and assert the chunk endpoint stamps
     // `finished_upload_at` in the response — this is what tells the
@@ -300,7 +311,10 @@ async fn test_co_owner_uploads_into_shared_folder() {
         &members_after,
         &alice,
     );
-    assert_eq!(post_share!(app, alice, envelope).status(), StatusCode::CREATED);
+    assert_eq!(
+        post_share!(app, alice, envelope).status(),
+        StatusCode::CREATED
+    );
 
     let new_file_id = Uuid::new_v4();
     let timestamp = now_secs();
@@ -353,7 +367,10 @@ async fn test_reader_upload_into_shared_folder_returns_403() {
         &members_after,
         &alice,
     );
-    assert_eq!(post_share!(app, alice, envelope).status(), StatusCode::CREATED);
+    assert_eq!(
+        post_share!(app, alice, envelope).status(),
+        StatusCode::CREATED
+    );
 
     let new_file_id = Uuid::new_v4();
     let timestamp = now_secs();
@@ -408,7 +425,10 @@ async fn test_upload_multikey_missing_member_key_returns_409_share_membership_ch
         &members_after,
         &alice,
     );
-    assert_eq!(post_share!(app, alice, envelope).status(), StatusCode::CREATED);
+    assert_eq!(
+        post_share!(app, alice, envelope).status(),
+        StatusCode::CREATED
+    );
 
     let new_file_id = Uuid::new_v4();
     let timestamp = now_secs();
@@ -464,7 +484,10 @@ async fn test_upload_multikey_extra_member_key_returns_409_share_membership_chan
         &members_after,
         &alice,
     );
-    assert_eq!(post_share!(app, alice, envelope).status(), StatusCode::CREATED);
+    assert_eq!(
+        post_share!(app, alice, envelope).status(),
+        StatusCode::CREATED
+    );
 
     let new_file_id = Uuid::new_v4();
     let timestamp = now_secs();
@@ -520,7 +543,10 @@ async fn test_upload_multikey_stale_snapshot_returns_409() {
         &members_after,
         &alice,
     );
-    assert_eq!(post_share!(app, alice, envelope).status(), StatusCode::CREATED);
+    assert_eq!(
+        post_share!(app, alice, envelope).status(),
+        StatusCode::CREATED
+    );
 
     let new_file_id = Uuid::new_v4();
     let timestamp = now_secs();
@@ -575,7 +601,10 @@ async fn test_upload_multikey_uploader_is_owner_of_new_file() {
         &members_after,
         &alice,
     );
-    assert_eq!(post_share!(app, alice, envelope).status(), StatusCode::CREATED);
+    assert_eq!(
+        post_share!(app, alice, envelope).status(),
+        StatusCode::CREATED
+    );
 
     let new_file_id = Uuid::new_v4();
     let timestamp = now_secs();
@@ -613,7 +642,10 @@ async fn test_upload_multikey_uploader_is_owner_of_new_file() {
         .one(&context.db)
         .await
         .unwrap();
-    assert!(bob_row.is_some(), "Bob (uploader) must own the new file row");
+    assert!(
+        bob_row.is_some(),
+        "Bob (uploader) must own the new file row"
+    );
     let _ = context;
 }
 
@@ -637,7 +669,10 @@ async fn test_upload_multikey_event_signature_invalid_returns_400() {
         &members_after,
         &alice,
     );
-    assert_eq!(post_share!(app, alice, envelope).status(), StatusCode::CREATED);
+    assert_eq!(
+        post_share!(app, alice, envelope).status(),
+        StatusCode::CREATED
+    );
 
     let new_file_id = Uuid::new_v4();
     let timestamp = now_secs();
@@ -696,9 +731,24 @@ async fn test_co_owner_adds_member_to_folder_with_own_signature() {
         alice
     );
     let members_after_yvonne = vec![
-        FolderListMemberSpec { user: &alice, share_role: ShareRoleEnum::CoOwner, is_owner: true, signed_by: &alice },
-        FolderListMemberSpec { user: &bob, share_role: ShareRoleEnum::CoOwner, is_owner: false, signed_by: &alice },
-        FolderListMemberSpec { user: &yvonne, share_role: ShareRoleEnum::Reader, is_owner: false, signed_by: &bob },
+        FolderListMemberSpec {
+            user: &alice,
+            share_role: ShareRoleEnum::CoOwner,
+            is_owner: true,
+            signed_by: &alice,
+        },
+        FolderListMemberSpec {
+            user: &bob,
+            share_role: ShareRoleEnum::CoOwner,
+            is_owner: false,
+            signed_by: &alice,
+        },
+        FolderListMemberSpec {
+            user: &yvonne,
+            share_role: ShareRoleEnum::Reader,
+            is_owner: false,
+            signed_by: &bob,
+        },
     ];
     let envelope = build_co_owner_folder_share_envelope(
         &bob,
@@ -749,9 +799,24 @@ async fn test_co_owner_revoke_cascades_their_grants_under_folder() {
         alice
     );
     let members_after_yvonne = vec![
-        FolderListMemberSpec { user: &alice, share_role: ShareRoleEnum::CoOwner, is_owner: true, signed_by: &alice },
-        FolderListMemberSpec { user: &bob, share_role: ShareRoleEnum::CoOwner, is_owner: false, signed_by: &alice },
-        FolderListMemberSpec { user: &yvonne, share_role: ShareRoleEnum::Reader, is_owner: false, signed_by: &bob },
+        FolderListMemberSpec {
+            user: &alice,
+            share_role: ShareRoleEnum::CoOwner,
+            is_owner: true,
+            signed_by: &alice,
+        },
+        FolderListMemberSpec {
+            user: &bob,
+            share_role: ShareRoleEnum::CoOwner,
+            is_owner: false,
+            signed_by: &alice,
+        },
+        FolderListMemberSpec {
+            user: &yvonne,
+            share_role: ShareRoleEnum::Reader,
+            is_owner: false,
+            signed_by: &bob,
+        },
     ];
     let envelope = build_co_owner_folder_share_envelope(
         &bob,
@@ -764,7 +829,10 @@ async fn test_co_owner_revoke_cascades_their_grants_under_folder() {
         &members_after_yvonne,
         &bob,
     );
-    assert_eq!(post_share!(app, bob, envelope).status(), StatusCode::CREATED);
+    assert_eq!(
+        post_share!(app, bob, envelope).status(),
+        StatusCode::CREATED
+    );
 
     // Alice revokes Bob → Yvonne's row must also go (cascade on
     // shared_by_user_id) in the same transaction.
@@ -824,7 +892,10 @@ async fn test_member_list_endpoint_returns_signed_response() {
         &members_after,
         &alice,
     );
-    assert_eq!(post_share!(app, alice, envelope).status(), StatusCode::CREATED);
+    assert_eq!(
+        post_share!(app, alice, envelope).status(),
+        StatusCode::CREATED
+    );
 
     let req = test::TestRequest::get()
         .uri(&format!("/api/shares/folder/{}/members", folder.id))
@@ -868,7 +939,10 @@ async fn test_move_into_shared_folder_re_wraps_for_all_members() {
         &members_after,
         &alice,
     );
-    assert_eq!(post_share!(app, alice, envelope).status(), StatusCode::CREATED);
+    assert_eq!(
+        post_share!(app, alice, envelope).status(),
+        StatusCode::CREATED
+    );
 
     // Alice has a private file she wants to move into the shared folder.
     let private_file = create_file!(app, alice, "alice-private-move");
@@ -883,10 +957,7 @@ async fn test_move_into_shared_folder_re_wraps_for_all_members() {
     let body = build_move_into_shared_body(
         private_file.id,
         folder.id,
-        vec![
-            (alice.user_id, "rewrap-a"),
-            (bob.user_id, "rewrap-b"),
-        ],
+        vec![(alice.user_id, "rewrap-a"), (bob.user_id, "rewrap-b")],
         timestamp,
         None,
         event_signature,
@@ -935,7 +1006,10 @@ async fn test_move_folder_into_shared_cascade_rewraps_all_descendants() {
         &members_after,
         &alice,
     );
-    assert_eq!(post_share!(app, alice, envelope).status(), StatusCode::CREATED);
+    assert_eq!(
+        post_share!(app, alice, envelope).status(),
+        StatusCode::CREATED
+    );
 
     // Alice's private folder: root + two descendants (a child file and a
     // grandchild file under a sub-folder).
@@ -960,7 +1034,10 @@ async fn test_move_folder_into_shared_cascade_rewraps_all_descendants() {
         ),
         (
             child_file.id,
-            vec![(alice.user_id, "wrap-child-a"), (bob.user_id, "wrap-child-b")],
+            vec![
+                (alice.user_id, "wrap-child-a"),
+                (bob.user_id, "wrap-child-b"),
+            ],
         ),
         (
             sub_folder.id,
@@ -1053,7 +1130,10 @@ async fn test_move_folder_into_shared_rejects_incomplete_subtree() {
         &members_after,
         &alice,
     );
-    assert_eq!(post_share!(app, alice, envelope).status(), StatusCode::CREATED);
+    assert_eq!(
+        post_share!(app, alice, envelope).status(),
+        StatusCode::CREATED
+    );
 
     let moved_root = create_folder!(app, alice, "incomplete-root");
     let child_file = create_child_file!(app, alice, "incomplete-child", moved_root.id);
@@ -1112,7 +1192,10 @@ async fn test_move_folder_into_shared_flat_shape_rejected() {
         &members_after,
         &alice,
     );
-    assert_eq!(post_share!(app, alice, envelope).status(), StatusCode::CREATED);
+    assert_eq!(
+        post_share!(app, alice, envelope).status(),
+        StatusCode::CREATED
+    );
 
     // A folder posted with the flat single-file shape (no `entries`) must
     // be refused — re-parenting it would leave descendants encrypted for
@@ -1166,7 +1249,10 @@ async fn test_move_into_shared_non_owner_rejected() {
         &members_after,
         &alice,
     );
-    assert_eq!(post_share!(app, alice, envelope).status(), StatusCode::CREATED);
+    assert_eq!(
+        post_share!(app, alice, envelope).status(),
+        StatusCode::CREATED
+    );
 
     // Bob (Editor on F, can_write) shares one of his OWN files with alice,
     // then alice — a non-owner of that file but writer on the dest — tries
@@ -1270,7 +1356,10 @@ async fn test_move_out_of_shared_drops_member_rows() {
         &members_after,
         &alice,
     );
-    assert_eq!(post_share!(app, alice, envelope).status(), StatusCode::CREATED);
+    assert_eq!(
+        post_share!(app, alice, envelope).status(),
+        StatusCode::CREATED
+    );
 
     // Alice moves a private file into the shared folder first (bob gets a
     // row), then moves it back out — bob's row must be dropped.
@@ -1385,7 +1474,10 @@ async fn test_move_out_non_owner_blocked() {
         &members_after,
         &alice,
     );
-    assert_eq!(post_share!(app, alice, envelope).status(), StatusCode::CREATED);
+    assert_eq!(
+        post_share!(app, alice, envelope).status(),
+        StatusCode::CREATED
+    );
 
     // Alice owns a file inside the shared folder; bob (Editor member) tries
     // to move it out. A non-owner has no move-out authority — only
@@ -1494,7 +1586,10 @@ async fn test_folder_share_stores_list_signature_on_create() {
         &members_after,
         &alice,
     );
-    assert_eq!(post_share!(app, alice, envelope).status(), StatusCode::CREATED);
+    assert_eq!(
+        post_share!(app, alice, envelope).status(),
+        StatusCode::CREATED
+    );
 
     // GET /api/shares/folder/{F}/members returns the stamped signature.
     let req = test::TestRequest::get()
@@ -1506,7 +1601,10 @@ async fn test_folder_share_stores_list_signature_on_create() {
     let body: Value = test::read_body_json(resp).await;
     assert!(body["members_list_signature"].is_string());
     assert!(body["members_signed_at"].is_i64());
-    assert_eq!(body["members_list_signed_by_user_id"], alice.user_id.to_string());
+    assert_eq!(
+        body["members_list_signed_by_user_id"],
+        alice.user_id.to_string()
+    );
     let _ = context;
 }
 
@@ -1524,9 +1622,24 @@ async fn test_folder_share_tampered_list_signature_rejected_400() {
     // own DB and finds the bytes the client signed differ from the
     // canonical projection.
     let tampered_members = vec![
-        FolderListMemberSpec { user: &alice, share_role: ShareRoleEnum::CoOwner, is_owner: true, signed_by: &alice },
-        FolderListMemberSpec { user: &bob, share_role: ShareRoleEnum::Editor, is_owner: false, signed_by: &alice },
-        FolderListMemberSpec { user: &eve, share_role: ShareRoleEnum::Reader, is_owner: false, signed_by: &alice },
+        FolderListMemberSpec {
+            user: &alice,
+            share_role: ShareRoleEnum::CoOwner,
+            is_owner: true,
+            signed_by: &alice,
+        },
+        FolderListMemberSpec {
+            user: &bob,
+            share_role: ShareRoleEnum::Editor,
+            is_owner: false,
+            signed_by: &alice,
+        },
+        FolderListMemberSpec {
+            user: &eve,
+            share_role: ShareRoleEnum::Reader,
+            is_owner: false,
+            signed_by: &alice,
+        },
     ];
     let envelope = build_folder_share_envelope(
         &alice,
@@ -1595,7 +1708,10 @@ async fn test_folder_revoke_requires_list_signature_400_if_missing() {
         &members_after,
         &alice,
     );
-    assert_eq!(post_share!(app, alice, envelope).status(), StatusCode::CREATED);
+    assert_eq!(
+        post_share!(app, alice, envelope).status(),
+        StatusCode::CREATED
+    );
 
     // build_revoke_body omits the list signature; folder revoke must 400.
     let revoke = build_revoke_body(&alice, &bob, folder.id, ShareRoleEnum::Editor, now_secs());
@@ -1629,9 +1745,24 @@ async fn test_co_owner_reshare_signs_list_with_co_owner_key() {
     );
     // Bob (Co-owner) re-shares to Carol. Bob signs the new list.
     let members_with_carol = vec![
-        FolderListMemberSpec { user: &alice, share_role: ShareRoleEnum::CoOwner, is_owner: true, signed_by: &alice },
-        FolderListMemberSpec { user: &bob, share_role: ShareRoleEnum::CoOwner, is_owner: false, signed_by: &alice },
-        FolderListMemberSpec { user: &carol, share_role: ShareRoleEnum::Reader, is_owner: false, signed_by: &bob },
+        FolderListMemberSpec {
+            user: &alice,
+            share_role: ShareRoleEnum::CoOwner,
+            is_owner: true,
+            signed_by: &alice,
+        },
+        FolderListMemberSpec {
+            user: &bob,
+            share_role: ShareRoleEnum::CoOwner,
+            is_owner: false,
+            signed_by: &alice,
+        },
+        FolderListMemberSpec {
+            user: &carol,
+            share_role: ShareRoleEnum::Reader,
+            is_owner: false,
+            signed_by: &bob,
+        },
     ];
     let envelope = build_co_owner_folder_share_envelope(
         &bob,
@@ -1654,7 +1785,10 @@ async fn test_co_owner_reshare_signs_list_with_co_owner_key() {
         .to_request();
     let resp = test::call_service(&app, req).await;
     let body: Value = test::read_body_json(resp).await;
-    assert_eq!(body["members_list_signed_by_user_id"], bob.user_id.to_string());
+    assert_eq!(
+        body["members_list_signed_by_user_id"],
+        bob.user_id.to_string()
+    );
     let _ = context;
 }
 
@@ -1679,7 +1813,10 @@ async fn test_role_change_on_folder_requires_fresh_list_signature() {
         &members_editor,
         &alice,
     );
-    assert_eq!(post_share!(app, alice, envelope).status(), StatusCode::CREATED);
+    assert_eq!(
+        post_share!(app, alice, envelope).status(),
+        StatusCode::CREATED
+    );
 
     // Upgrade bob to co-owner: server requires a fresh list sig over
     // the new role AND an audit signature over the `role_change`
@@ -1724,7 +1861,10 @@ async fn test_folder_share_create_default_exposes_emails_to_every_member() {
         &members_after,
         &alice,
     );
-    assert_eq!(post_share!(app, alice, envelope).status(), StatusCode::CREATED);
+    assert_eq!(
+        post_share!(app, alice, envelope).status(),
+        StatusCode::CREATED
+    );
 
     let req = test::TestRequest::get()
         .uri(&format!("/api/shares/folder/{}/members", folder.id))
@@ -1762,7 +1902,10 @@ async fn test_folder_owner_sees_full_member_roster() {
         &members_after,
         &alice,
     );
-    assert_eq!(post_share!(app, alice, envelope).status(), StatusCode::CREATED);
+    assert_eq!(
+        post_share!(app, alice, envelope).status(),
+        StatusCode::CREATED
+    );
 
     let req = test::TestRequest::get()
         .uri(&format!("/api/shares/folder/{}/members", folder.id))
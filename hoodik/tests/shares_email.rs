@@ -19,8 +19,7 @@ const SUBJECT: &str = "You have a new shared file on Hoodik";
 
 #[actix_web::test]
 async fn test_share_dispatches_notification_email_to_recipient() {
-    let context =
-        context::Context::add_mock_sender(context::Context::mock_sqlite().await);
+    let context = context::Context::add_mock_sender(context::Context::mock_sqlite().await);
     let app = test::init_service(server::app(context.clone())).await;
 
     register_user!(app, context, alice, "alice@example.com");
@@ -41,8 +40,7 @@ async fn test_share_dispatches_notification_email_to_recipient() {
 
 #[actix_web::test]
 async fn test_share_skips_email_when_recipient_opted_out() {
-    let context =
-        context::Context::add_mock_sender(context::Context::mock_sqlite().await);
+    let context = context::Context::add_mock_sender(context::Context::mock_sqlite().await);
     let app = test::init_service(server::app(context.clone())).await;
 
     register_user!(app, context, alice, "alice@example.com");
@@ -76,8 +74,7 @@ async fn test_co_owner_reshare_dispatches_notification() {
     // Bob (Co-owner) re-shares Alice's file to Carol — the email goes
     // to Carol regardless of who issued the grant; she's the new
     // recipient and notifications honour her preference.
-    let context =
-        context::Context::add_mock_sender(context::Context::mock_sqlite().await);
+    let context = context::Context::add_mock_sender(context::Context::mock_sqlite().await);
     let app = test::init_service(server::app(context.clone())).await;
 
     register_user!(app, context, alice, "alice@example.com");
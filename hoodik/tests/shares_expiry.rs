@@ -0,0 +1,363 @@
+//! Time-limited shares: the expiry travels with the grant, access ends at
+//! the expiry second, and the `purge-expired-shares` job drops the rows
+//! and records a system `expire` event in the audit chain.
+
+#[macro_use]
+#[path = "./shares_common.rs"]
+mod shares_common;
+
+use actix_web::{http::StatusCode, test};
+use cryptfns::asn1::ShareRoleEnum;
+use entity::{
+    permission::{permission, SharePermission},
+    share_events, user_files, ActiveValue, ColumnTrait, EntityTrait, QueryFilter,
+};
+use hoodik::server;
+use serde_json::Value;
+use shares::data::app_share::AppShare;
+use shares::data::incoming::IncomingSharePage;
+
+use crate::shares_common::*;
+
+#[actix_web::test]
+async fn test_expired_share_loses_access_and_is_purged() {
+    let context = context::Context::mock_sqlite().await;
+    let app = test::init_service(server::app(context.clone())).await;
+
+    register_user!(app, context, alice, "alice@example.com");
+    register_user!(app, context, bob, "bob@example.com");
+    let file = create_file!(app, alice, "contract-file");
+
+    let expires_at = now_secs() + 3600;
+    let mut envelope = build_share_envelope(
+        &alice,
+        &bob,
+        ShareRoleEnum::Editor,
+        file.id,
+        vec![(file.id, b"wrapped-for-bob".to_vec())],
+        random_nonce(),
+        now_secs(),
+    );
+    envelope["expires_at"] = expires_at.into();
+
+    let resp = post_share!(app, alice, envelope);
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let body: Value = test::read_body_json(resp).await;
+    let shares: Vec<AppShare> = serde_json::from_value(body["shares"].clone()).unwrap();
+    assert_eq!(shares[0].expires_at, Some(expires_at));
+
+    assert_eq!(
+        permission(&context.db, file.id, bob.user_id).await.unwrap(),
+        SharePermission::Editor
+    );
+
+    // Nothing to purge while the share is live.
+    assert_eq!(
+        hoodik::jobs::run(&context, "purge-expired-shares")
+            .await
+            .unwrap(),
+        0
+    );
+
+    // Move the expiry into the past instead of waiting for it.
+    let row = user_files::Entity::find()
+        .filter(user_files::Column::FileId.eq(file.id))
+        .filter(user_files::Column::UserId.eq(bob.user_id))
+        .one(&context.db)
+        .await
+        .unwrap()
+        .unwrap();
+    user_files::Entity::update(user_files::ActiveModel {
+        id: ActiveValue::Unchanged(row.id),
+        expires_at: ActiveValue::Set(Some(now_secs() - 1)),
+        ..Default::default()
+    })
+    .exec(&context.db)
+    .await
+    .unwrap();
+
+    // Access ends right away, before the job has run.
+    assert_eq!(
+        permission(&context.db, file.id, bob.user_id).await.unwrap(),
+        SharePermission::None
+    );
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/storage/{}/metadata", file.id))
+        .cookie(bob.jwt.clone())
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    let req = test::TestRequest::get()
+        .uri("/api/shares/mine")
+        .cookie(bob.jwt.clone())
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let page: IncomingSharePage = test::read_body_json(resp).await;
+    assert_eq!(page.total, 0);
+
+    assert_eq!(
+        hoodik::jobs::run(&context, "purge-expired-shares")
+            .await
+            .unwrap(),
+        1
+    );
+
+    let remaining = user_files::Entity::find()
+        .filter(user_files::Column::FileId.eq(file.id))
+        .filter(user_files::Column::UserId.eq(bob.user_id))
+        .all(&context.db)
+        .await
+        .unwrap();
+    assert!(remaining.is_empty());
+
+    let expired = share_events::Entity::find()
+        .filter(share_events::Column::Action.eq("expire"))
+        .all(&context.db)
+        .await
+        .unwrap();
+    assert_eq!(expired.len(), 1);
+    assert_eq!(expired[0].sender_id, None);
+    assert_eq!(expired[0].recipient_id, Some(bob.user_id));
+    assert_eq!(expired[0].file_id, Some(file.id));
+    assert_eq!(expired[0].share_role_before.as_deref(), Some("editor"));
+    assert!(expired[0].sender_signature.is_none());
+
+    // The owner keeps their file.
+    assert_eq!(
+        permission(&context.db, file.id, alice.user_id)
+            .await
+            .unwrap(),
+        SharePermission::Owner
+    );
+}
+
+#[actix_web::test]
+async fn test_folder_share_expiry_is_logged_once_per_share() {
+    let context = context::Context::mock_sqlite().await;
+    let app = test::init_service(server::app(context.clone())).await;
+
+    register_user!(app, context, alice, "alice@example.com");
+    register_user!(app, context, bob, "bob@example.com");
+    let folder = create_folder!(app, alice, "engagement");
+    let child = create_child_file!(app, alice, "report", folder.id);
+
+    let members_after = [
+        FolderListMemberSpec {
+            user: &alice,
+            share_role: ShareRoleEnum::CoOwner,
+            is_owner: true,
+            signed_by: &alice,
+        },
+        FolderListMemberSpec {
+            user: &bob,
+            share_role: ShareRoleEnum::Reader,
+            is_owner: false,
+            signed_by: &alice,
+        },
+    ];
+    let mut envelope = build_folder_share_envelope_with_entries(
+        &alice,
+        &bob,
+        ShareRoleEnum::Reader,
+        folder.id,
+        alice.user_id,
+        vec![
+            (folder.id, b"wrapped-folder".to_vec()),
+            (child.id, b"wrapped-child".to_vec()),
+        ],
+        random_nonce(),
+        now_secs(),
+        &members_after,
+        &alice,
+    );
+    envelope["expires_at"] = (now_secs() + 60).into();
+
+    let resp = post_share!(app, alice, envelope);
+    assert_eq!(resp.status(), StatusCode::CREATED);
+
+    let rows = user_files::Entity::find()
+        .filter(user_files::Column::UserId.eq(bob.user_id))
+        .all(&context.db)
+        .await
+        .unwrap();
+    assert_eq!(rows.len(), 2);
+    assert!(rows.iter().all(|row| row.expires_at.is_some()));
+
+    // Run the purge a minute after the expiry instead of waiting for it.
+    let purged = shares::purge_expired_shares(&context.db, now_secs() + 120)
+        .await
+        .unwrap();
+    assert_eq!(purged, 2);
+
+    let expired = share_events::Entity::find()
+        .filter(share_events::Column::Action.eq("expire"))
+        .all(&context.db)
+        .await
+        .unwrap();
+    assert_eq!(expired.len(), 1);
+    assert_eq!(expired[0].file_id, Some(folder.id));
+}
+
+#[actix_web::test]
+async fn test_share_with_past_expiry_is_rejected() {
+    let context = context::Context::mock_sqlite().await;
+    let app = test::init_service(server::app(context.clone())).await;
+
+    register_user!(app, context, alice, "alice@example.com");
+    register_user!(app, context, bob, "bob@example.com");
+    let file = create_file!(app, alice, "late-file");
+
+    let mut envelope = build_share_envelope(
+        &alice,
+        &bob,
+        ShareRoleEnum::Reader,
+        file.id,
+        vec![(file.id, b"wrapped-for-bob".to_vec())],
+        random_nonce(),
+        now_secs(),
+    );
+    envelope["expires_at"] = (now_secs() - 10).into();
+
+    let resp = post_share!(app, alice, envelope);
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["message"], "expires_at_in_past");
+
+    let rows = user_files::Entity::find()
+        .filter(user_files::Column::UserId.eq(bob.user_id))
+        .all(&context.db)
+        .await
+        .unwrap();
+    assert!(rows.is_empty());
+}
+
+#[actix_web::test]
+async fn test_co_owner_reshare_never_outlasts_their_own_share() {
+    let context = context::Context::mock_sqlite().await;
+    let app = test::init_service(server::app(context.clone())).await;
+
+    register_user!(app, context, alice, "alice@example.com");
+    register_user!(app, context, bob, "bob@example.com");
+    register_user!(app, context, carol, "carol@example.com");
+    register_user!(app, context, dave, "dave@example.com");
+    let file = create_file!(app, alice, "engagement-file");
+
+    let bob_expires_at = now_secs() + 3600;
+    let mut envelope = build_share_envelope(
+        &alice,
+        &bob,
+        ShareRoleEnum::CoOwner,
+        file.id,
+        vec![(file.id, b"wrapped-for-bob".to_vec())],
+        random_nonce(),
+        now_secs(),
+    );
+    envelope["expires_at"] = bob_expires_at.into();
+    let resp = post_share!(app, alice, envelope);
+    assert_eq!(resp.status(), StatusCode::CREATED);
+
+    // Without an expiry, and with one past Bob's, the share ends with his.
+    for (recipient, expires_at) in [(&carol, None), (&dave, Some(bob_expires_at + 3600))] {
+        let mut envelope = build_co_owner_share_envelope(
+            &bob,
+            recipient,
+            ShareRoleEnum::Reader,
+            file.id,
+            vec![(file.id, b"wrapped".to_vec())],
+            random_nonce(),
+            now_secs(),
+        );
+        if let Some(expires_at) = expires_at {
+            envelope["expires_at"] = expires_at.into();
+        }
+        let resp = post_share!(app, bob, envelope);
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        let body: Value = test::read_body_json(resp).await;
+        let shares: Vec<AppShare> = serde_json::from_value(body["shares"].clone()).unwrap();
+        assert_eq!(shares[0].expires_at, Some(bob_expires_at));
+    }
+
+    // An earlier expiry is kept as asked.
+    let mut envelope = build_co_owner_share_envelope(
+        &bob,
+        &carol,
+        ShareRoleEnum::Reader,
+        file.id,
+        vec![(file.id, b"wrapped".to_vec())],
+        random_nonce(),
+        now_secs(),
+    );
+    envelope["expires_at"] = (bob_expires_at - 600).into();
+    let resp = post_share!(app, bob, envelope);
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let row = user_files::Entity::find()
+        .filter(user_files::Column::FileId.eq(file.id))
+        .filter(user_files::Column::UserId.eq(carol.user_id))
+        .one(&context.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(row.expires_at, Some(bob_expires_at - 600));
+}
+
+#[actix_web::test]
+async fn test_moving_a_share_expiry_is_recorded_in_the_audit_log() {
+    let context = context::Context::mock_sqlite().await;
+    let app = test::init_service(server::app(context.clone())).await;
+
+    register_user!(app, context, alice, "alice@example.com");
+    register_user!(app, context, bob, "bob@example.com");
+    let file = create_file!(app, alice, "contract-file");
+
+    let grants = || async {
+        share_events::Entity::find()
+            .filter(share_events::Column::Action.eq("grant"))
+            .filter(share_events::Column::RecipientId.eq(bob.user_id))
+            .all(&context.db)
+            .await
+            .unwrap()
+    };
+
+    for expires_at in [now_secs() + 60, now_secs() + 3600] {
+        let mut envelope = build_share_envelope(
+            &alice,
+            &bob,
+            ShareRoleEnum::Reader,
+            file.id,
+            vec![(file.id, b"wrapped-for-bob".to_vec())],
+            random_nonce(),
+            now_secs(),
+        );
+        envelope["expires_at"] = expires_at.into();
+        let resp = post_share!(app, alice, envelope);
+        assert_eq!(resp.status(), StatusCode::CREATED);
+    }
+
+    let row = user_files::Entity::find()
+        .filter(user_files::Column::FileId.eq(file.id))
+        .filter(user_files::Column::UserId.eq(bob.user_id))
+        .one(&context.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(row.expires_at > Some(now_secs() + 60));
+    assert_eq!(grants().await.len(), 2);
+
+    // Sharing again with the same role and expiry changes nothing.
+    let mut envelope = build_share_envelope(
+        &alice,
+        &bob,
+        ShareRoleEnum::Reader,
+        file.id,
+        vec![(file.id, b"wrapped-for-bob".to_vec())],
+        random_nonce(),
+        now_secs(),
+    );
+    envelope["expires_at"] = row.expires_at.into();
+    let resp = post_share!(app, alice, envelope);
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    assert_eq!(grants().await.len(), 2);
+}
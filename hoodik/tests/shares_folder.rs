@@ -88,8 +88,18 @@ async fn test_folder_share_eager_recursion_creates_rows_for_all_descendants() {
         (child_two.id, b"wrap-c2".to_vec()),
     ];
     let members = vec![
-        FolderListMemberSpec { user: &alice, share_role: ShareRoleEnum::CoOwner, is_owner: true, signed_by: &alice },
-        FolderListMemberSpec { user: &bob, share_role: ShareRoleEnum::Editor, is_owner: false, signed_by: &alice },
+        FolderListMemberSpec {
+            user: &alice,
+            share_role: ShareRoleEnum::CoOwner,
+            is_owner: true,
+            signed_by: &alice,
+        },
+        FolderListMemberSpec {
+            user: &bob,
+            share_role: ShareRoleEnum::Editor,
+            is_owner: false,
+            signed_by: &alice,
+        },
     ];
     let envelope = build_folder_share_envelope_with_entries(
         &alice,
@@ -236,10 +246,7 @@ async fn test_folder_share_role_inherited_on_each_descendant_row() {
         &bob,
         ShareRoleEnum::CoOwner,
         folder.id,
-        vec![
-            (folder.id, b"wrap".to_vec()),
-            (child.id, b"wrap2".to_vec()),
-        ],
+        vec![(folder.id, b"wrap".to_vec()), (child.id, b"wrap2".to_vec())],
         random_nonce(),
         now_secs(),
     );
@@ -280,10 +287,7 @@ async fn test_folder_share_revoke_cascade_drops_descendants_for_recipient() {
         &bob,
         ShareRoleEnum::Editor,
         folder.id,
-        vec![
-            (folder.id, b"wrap".to_vec()),
-            (child.id, b"wrap2".to_vec()),
-        ],
+        vec![(folder.id, b"wrap".to_vec()), (child.id, b"wrap2".to_vec())],
         random_nonce(),
         now_secs(),
     );
@@ -300,13 +304,8 @@ async fn test_folder_share_revoke_cascade_drops_descendants_for_recipient() {
 
     let revoke_ts = now_secs();
     let revoke = build_revoke_body(&alice, &bob, folder.id, ShareRoleEnum::Editor, revoke_ts);
-    let revoke = inject_owner_plus_recipient_sig_with_revoke(
-        revoke,
-        folder.id,
-        &alice,
-        &alice,
-        revoke_ts,
-    );
+    let revoke =
+        inject_owner_plus_recipient_sig_with_revoke(revoke, folder.id, &alice, &alice, revoke_ts);
     let resp = delete_share!(app, alice, folder.id, bob.user_id, revoke);
     assert_eq!(resp.status(), StatusCode::NO_CONTENT);
 
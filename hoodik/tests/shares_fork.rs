@@ -12,9 +12,7 @@ mod shares_common;
 
 use actix_web::{http::StatusCode, test};
 use cryptfns::asn1::ShareRoleEnum;
-use entity::{
-    share_events, user_files, ColumnTrait, EntityTrait, QueryFilter, Uuid,
-};
+use entity::{share_events, user_files, ColumnTrait, EntityTrait, QueryFilter, Uuid};
 use hoodik::server;
 use serde_json::{json, Value};
 
@@ -235,4 +233,3 @@ async fn test_fork_persists_after_source_revoke() {
         .expect("Bob's fork row should survive revoke of source");
     assert!(bob_on_fork.is_owner);
 }
-
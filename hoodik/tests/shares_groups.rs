@@ -279,7 +279,10 @@ async fn test_add_member_rejects_nonce_reuse() {
         .cookie(alice.jwt.clone())
         .set_json(&body)
         .to_request();
-    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::NO_CONTENT);
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::NO_CONTENT
+    );
 
     let req = test::TestRequest::post()
         .uri(&format!("/api/shares/groups/{}/members", group.id))
@@ -409,7 +412,9 @@ async fn test_group_members_returns_owner_and_members_with_keys() {
     assert_eq!(bob_entry["pubkey"], bob.public_pem);
     assert_eq!(bob_entry["fingerprint"], bob.fingerprint);
 
-    let carol_entry = by_id.get(&carol.user_id.to_string()).expect("carol present");
+    let carol_entry = by_id
+        .get(&carol.user_id.to_string())
+        .expect("carol present");
     assert_eq!(carol_entry["group_role"], "reader");
 
     // A non-member cannot read the roster.
@@ -457,7 +462,10 @@ async fn test_add_unverified_user_to_group_succeeds() {
         .one(&context.db)
         .await
         .unwrap();
-    assert!(membership.is_some(), "unverified user should be added to the group");
+    assert!(
+        membership.is_some(),
+        "unverified user should be added to the group"
+    );
 }
 
 #[actix_web::test]
@@ -16,8 +16,8 @@ mod shares_common;
 use actix_web::{http::StatusCode, test};
 use chrono::Utc;
 use cryptfns::asn1::{
-    encode_audit_event_sig_input_v1, AuditEventActionEnum, AuditEventSigInputV1,
-    ShareRoleEnum, AUDIT_EVENT_SIG_V1_PREFIX,
+    encode_audit_event_sig_input_v1, AuditEventActionEnum, AuditEventSigInputV1, ShareRoleEnum,
+    AUDIT_EVENT_SIG_V1_PREFIX,
 };
 use cryptfns::identity::KeyType;
 use cryptfns::transition::Certificate;
@@ -70,8 +70,12 @@ async fn migrate_user_to_curve<C: ConnectionTrait>(db: &C, user: &TestUser) -> M
         new_fingerprint: ActiveValue::Set(ed_fingerprint.clone()),
         new_identity_key_pem: ActiveValue::Set(ed_public.clone()),
         new_wrapping_key_pem: ActiveValue::Set(x_public.clone()),
-        old_signature: ActiveValue::Set(cryptfns::base64::decode(&signatures.old_signature).unwrap()),
-        new_signature: ActiveValue::Set(cryptfns::base64::decode(&signatures.new_signature).unwrap()),
+        old_signature: ActiveValue::Set(
+            cryptfns::base64::decode(&signatures.old_signature).unwrap(),
+        ),
+        new_signature: ActiveValue::Set(
+            cryptfns::base64::decode(&signatures.new_signature).unwrap(),
+        ),
         issued_at: ActiveValue::Set(issued_at),
         created_at: ActiveValue::Set(Utc::now().timestamp()),
     })
@@ -92,7 +96,10 @@ async fn migrate_user_to_curve<C: ConnectionTrait>(db: &C, user: &TestUser) -> M
     .await
     .unwrap();
 
-    MigratedTo { ed_public, ed_fingerprint }
+    MigratedTo {
+        ed_public,
+        ed_fingerprint,
+    }
 }
 
 /// Rotate `user` through two hops: RSA→Curve25519, then Curve25519→Curve25519,
@@ -106,7 +113,9 @@ async fn migrate_user_twice<C: ConnectionTrait>(db: &C, user: &TestUser) {
     let ed1_private = cryptfns::ed25519::private::generate().unwrap();
     let ed1_public = cryptfns::ed25519::public::from_private(&ed1_private).unwrap();
     let f1_fingerprint = cryptfns::spki::fingerprint(&ed1_public).unwrap();
-    let x1_public = cryptfns::ecdh::public::from_private(&cryptfns::ecdh::private::generate().unwrap()).unwrap();
+    let x1_public =
+        cryptfns::ecdh::public::from_private(&cryptfns::ecdh::private::generate().unwrap())
+            .unwrap();
     insert_chain_hop(
         db,
         user.user_id,
@@ -125,7 +134,9 @@ async fn migrate_user_twice<C: ConnectionTrait>(db: &C, user: &TestUser) {
     let ed2_private = cryptfns::ed25519::private::generate().unwrap();
     let ed2_public = cryptfns::ed25519::public::from_private(&ed2_private).unwrap();
     let f2_fingerprint = cryptfns::spki::fingerprint(&ed2_public).unwrap();
-    let x2_public = cryptfns::ecdh::public::from_private(&cryptfns::ecdh::private::generate().unwrap()).unwrap();
+    let x2_public =
+        cryptfns::ecdh::public::from_private(&cryptfns::ecdh::private::generate().unwrap())
+            .unwrap();
     insert_chain_hop(
         db,
         user.user_id,
@@ -192,8 +203,12 @@ async fn insert_chain_hop<C: ConnectionTrait>(
         new_fingerprint: ActiveValue::Set(new_fingerprint.to_string()),
         new_identity_key_pem: ActiveValue::Set(new_ed_public.to_string()),
         new_wrapping_key_pem: ActiveValue::Set(new_wrapping_public.to_string()),
-        old_signature: ActiveValue::Set(cryptfns::base64::decode(&signatures.old_signature).unwrap()),
-        new_signature: ActiveValue::Set(cryptfns::base64::decode(&signatures.new_signature).unwrap()),
+        old_signature: ActiveValue::Set(
+            cryptfns::base64::decode(&signatures.old_signature).unwrap(),
+        ),
+        new_signature: ActiveValue::Set(
+            cryptfns::base64::decode(&signatures.new_signature).unwrap(),
+        ),
         issued_at: ActiveValue::Set(issued_at),
         created_at: ActiveValue::Set(Utc::now().timestamp()),
     })
@@ -218,10 +233,29 @@ async fn test_roster_signature_verifies_after_owner_migration() {
 
     // alice makes carol a Co-owner. Roster signed by alice (RSA), pre-migration.
     let after_carol = vec![
-        FolderListMemberSpec { user: &alice, share_role: ShareRoleEnum::CoOwner, is_owner: true, signed_by: &alice },
-        FolderListMemberSpec { user: &carol, share_role: ShareRoleEnum::CoOwner, is_owner: false, signed_by: &alice },
+        FolderListMemberSpec {
+            user: &alice,
+            share_role: ShareRoleEnum::CoOwner,
+            is_owner: true,
+            signed_by: &alice,
+        },
+        FolderListMemberSpec {
+            user: &carol,
+            share_role: ShareRoleEnum::CoOwner,
+            is_owner: false,
+            signed_by: &alice,
+        },
     ];
-    grant_folder!(app, alice, carol, ShareRoleEnum::CoOwner, folder.id, alice, &after_carol, alice);
+    grant_folder!(
+        app,
+        alice,
+        carol,
+        ShareRoleEnum::CoOwner,
+        folder.id,
+        alice,
+        &after_carol,
+        alice
+    );
 
     // alice migrates RSA → curve. The stored roster signature is now stranded.
     migrate_user_to_curve(&context.db, &alice).await;
@@ -230,9 +264,24 @@ async fn test_roster_signature_verifies_after_owner_migration() {
     // share-request + audit event with her current key, but the roster is still
     // endorsed by owner alice with her OLD RSA key.
     let after_dave = vec![
-        FolderListMemberSpec { user: &alice, share_role: ShareRoleEnum::CoOwner, is_owner: true, signed_by: &alice },
-        FolderListMemberSpec { user: &carol, share_role: ShareRoleEnum::CoOwner, is_owner: false, signed_by: &alice },
-        FolderListMemberSpec { user: &dave, share_role: ShareRoleEnum::Editor, is_owner: false, signed_by: &carol },
+        FolderListMemberSpec {
+            user: &alice,
+            share_role: ShareRoleEnum::CoOwner,
+            is_owner: true,
+            signed_by: &alice,
+        },
+        FolderListMemberSpec {
+            user: &carol,
+            share_role: ShareRoleEnum::CoOwner,
+            is_owner: false,
+            signed_by: &alice,
+        },
+        FolderListMemberSpec {
+            user: &dave,
+            share_role: ShareRoleEnum::Editor,
+            is_owner: false,
+            signed_by: &carol,
+        },
     ];
     let envelope = build_co_owner_folder_share_envelope(
         &carol,
@@ -272,19 +321,53 @@ async fn test_roster_signature_verifies_after_two_owner_migrations() {
     let folder = create_folder!(app, alice, "chain2-folder");
 
     let after_carol = vec![
-        FolderListMemberSpec { user: &alice, share_role: ShareRoleEnum::CoOwner, is_owner: true, signed_by: &alice },
-        FolderListMemberSpec { user: &carol, share_role: ShareRoleEnum::CoOwner, is_owner: false, signed_by: &alice },
+        FolderListMemberSpec {
+            user: &alice,
+            share_role: ShareRoleEnum::CoOwner,
+            is_owner: true,
+            signed_by: &alice,
+        },
+        FolderListMemberSpec {
+            user: &carol,
+            share_role: ShareRoleEnum::CoOwner,
+            is_owner: false,
+            signed_by: &alice,
+        },
     ];
-    grant_folder!(app, alice, carol, ShareRoleEnum::CoOwner, folder.id, alice, &after_carol, alice);
+    grant_folder!(
+        app,
+        alice,
+        carol,
+        ShareRoleEnum::CoOwner,
+        folder.id,
+        alice,
+        &after_carol,
+        alice
+    );
 
     // alice rotates twice: RSA → curve → curve. Her stored roster signature is
     // now two hops behind her current identity.
     migrate_user_twice(&context.db, &alice).await;
 
     let after_dave = vec![
-        FolderListMemberSpec { user: &alice, share_role: ShareRoleEnum::CoOwner, is_owner: true, signed_by: &alice },
-        FolderListMemberSpec { user: &carol, share_role: ShareRoleEnum::CoOwner, is_owner: false, signed_by: &alice },
-        FolderListMemberSpec { user: &dave, share_role: ShareRoleEnum::Editor, is_owner: false, signed_by: &carol },
+        FolderListMemberSpec {
+            user: &alice,
+            share_role: ShareRoleEnum::CoOwner,
+            is_owner: true,
+            signed_by: &alice,
+        },
+        FolderListMemberSpec {
+            user: &carol,
+            share_role: ShareRoleEnum::CoOwner,
+            is_owner: false,
+            signed_by: &alice,
+        },
+        FolderListMemberSpec {
+            user: &dave,
+            share_role: ShareRoleEnum::Editor,
+            is_owner: false,
+            signed_by: &carol,
+        },
     ];
     let envelope = build_co_owner_folder_share_envelope(
         &carol,
@@ -319,10 +402,29 @@ async fn test_folder_members_response_exposes_owner_transition() {
     register_user!(app, context, bob, "bob@example.com");
     let folder = create_folder!(app, alice, "expose-folder");
     let members_after = vec![
-        FolderListMemberSpec { user: &alice, share_role: ShareRoleEnum::CoOwner, is_owner: true, signed_by: &alice },
-        FolderListMemberSpec { user: &bob, share_role: ShareRoleEnum::Editor, is_owner: false, signed_by: &alice },
+        FolderListMemberSpec {
+            user: &alice,
+            share_role: ShareRoleEnum::CoOwner,
+            is_owner: true,
+            signed_by: &alice,
+        },
+        FolderListMemberSpec {
+            user: &bob,
+            share_role: ShareRoleEnum::Editor,
+            is_owner: false,
+            signed_by: &alice,
+        },
     ];
-    grant_folder!(app, alice, bob, ShareRoleEnum::Editor, folder.id, alice, &members_after, alice);
+    grant_folder!(
+        app,
+        alice,
+        bob,
+        ShareRoleEnum::Editor,
+        folder.id,
+        alice,
+        &members_after,
+        alice
+    );
 
     let migrated = migrate_user_to_curve(&context.db, &alice).await;
 
@@ -346,7 +448,10 @@ async fn test_folder_members_response_exposes_owner_transition() {
 
     let transition = &alice_member["key_transition"];
     assert_eq!(transition["old_key_type"], "rsa");
-    assert!(transition["old_key_pem"].as_str().unwrap().contains("BEGIN RSA PUBLIC KEY"));
+    assert!(transition["old_key_pem"]
+        .as_str()
+        .unwrap()
+        .contains("BEGIN RSA PUBLIC KEY"));
     assert!(transition["old_signature"].is_string());
     assert!(transition["new_signature"].is_string());
     assert!(transition["issued_at"].is_i64());
@@ -388,7 +493,10 @@ async fn test_audit_events_response_exposes_signer_transition() {
         random_nonce(),
         timestamp,
     );
-    assert_eq!(post_share!(app, alice, envelope).status(), StatusCode::CREATED);
+    assert_eq!(
+        post_share!(app, alice, envelope).status(),
+        StatusCode::CREATED
+    );
 
     let migrated = migrate_user_to_curve(&context.db, &alice).await;
 
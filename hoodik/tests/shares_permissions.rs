@@ -8,7 +8,7 @@ mod shares_common;
 
 use actix_web::{http::StatusCode, test};
 use cryptfns::asn1::ShareRoleEnum;
-use entity::{user_files, ColumnTrait, EntityTrait, QueryFilter};
+use entity::{files, user_files, ColumnTrait, EntityTrait, Expr, QueryFilter};
 use hoodik::server;
 use serde_json::Value;
 use shares::data::capabilities::Capabilities;
@@ -495,16 +495,19 @@ async fn test_owner_delete_folder_cascades() {
         ShareRoleEnum::Editor,
         folder.id,
         alice.user_id,
-        vec![
-            (folder.id, b"wrap".to_vec()),
-            (child.id, b"wrap2".to_vec()),
-        ],
+        vec![(folder.id, b"wrap".to_vec()), (child.id, b"wrap2".to_vec())],
         random_nonce(),
         now_secs(),
         &members,
         &alice,
     );
-    assert_eq!(post_share!(app, alice, envelope).status(), StatusCode::CREATED);
+    assert_eq!(
+        post_share!(app, alice, envelope).status(),
+        StatusCode::CREATED
+    );
+
+    // With the trash turned off an owner delete is final.
+    context.settings.inner().await.trash.set_retention_days(0);
 
     let req = test::TestRequest::delete()
         .uri(&format!("/api/storage/{}", folder.id))
@@ -524,6 +527,85 @@ async fn test_owner_delete_folder_cascades() {
     let _ = context;
 }
 
+#[actix_web::test]
+async fn test_owner_delete_folder_goes_to_trash() {
+    let context = context::Context::mock_sqlite().await;
+    let app = test::init_service(server::app(context.clone())).await;
+
+    register_user!(app, context, alice, "alice@example.com");
+    register_user!(app, context, bob, "bob@example.com");
+    let folder = create_folder!(app, alice, "owner-trash-folder");
+    let child = create_child_file!(app, alice, "owner-trash-child", folder.id);
+    grant_role_to!(app, alice, bob, ShareRoleEnum::Reader, child.id);
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/api/storage/{}", folder.id))
+        .cookie(alice.jwt.clone())
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+    // The rows survive, but nobody reaches the file until it is restored.
+    let rows = user_files::Entity::find()
+        .filter(user_files::Column::FileId.is_in(vec![folder.id, child.id]))
+        .all(&context.db)
+        .await
+        .unwrap();
+    assert_eq!(rows.len(), 3);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/storage/{}/metadata", child.id))
+        .cookie(bob.jwt.clone())
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert!(!resp.status().is_success());
+
+    let req = test::TestRequest::get()
+        .uri("/api/storage/trash")
+        .cookie(alice.jwt.clone())
+        .to_request();
+    let trash: Vec<Value> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(trash.len(), 1);
+    assert_eq!(trash[0]["id"], folder.id.to_string());
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/storage/trash/{}/restore", folder.id))
+        .cookie(alice.jwt.clone())
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/storage/{}/metadata", child.id))
+        .cookie(bob.jwt.clone())
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    // Back in the trash, then purged for good once the retention ran out.
+    let req = test::TestRequest::delete()
+        .uri(&format!("/api/storage/{}", folder.id))
+        .cookie(alice.jwt.clone())
+        .to_request();
+    test::call_service(&app, req).await;
+    files::Entity::update_many()
+        .col_expr(files::Column::DeletedAt, Expr::value(0))
+        .filter(files::Column::Id.is_in(vec![folder.id, child.id]))
+        .exec(&context.db)
+        .await
+        .unwrap();
+
+    let purged = hoodik::jobs::run(&context, "purge-trash").await.unwrap();
+    assert_eq!(purged, 2);
+
+    let rows = user_files::Entity::find()
+        .filter(user_files::Column::FileId.is_in(vec![folder.id, child.id]))
+        .all(&context.db)
+        .await
+        .unwrap();
+    assert!(rows.is_empty());
+}
+
 #[actix_web::test]
 async fn test_non_owner_delete_folder_self_removes_recursively() {
     let context = context::Context::mock_sqlite().await;
@@ -554,16 +636,16 @@ async fn test_non_owner_delete_folder_self_removes_recursively() {
         ShareRoleEnum::Editor,
         folder.id,
         alice.user_id,
-        vec![
-            (folder.id, b"wrap".to_vec()),
-            (child.id, b"wrap2".to_vec()),
-        ],
+        vec![(folder.id, b"wrap".to_vec()), (child.id, b"wrap2".to_vec())],
         random_nonce(),
         now_secs(),
         &members,
         &alice,
     );
-    assert_eq!(post_share!(app, alice, envelope).status(), StatusCode::CREATED);
+    assert_eq!(
+        post_share!(app, alice, envelope).status(),
+        StatusCode::CREATED
+    );
 
     let req = test::TestRequest::delete()
         .uri(&format!("/api/storage/{}", folder.id))
@@ -586,7 +668,10 @@ async fn test_non_owner_delete_folder_self_removes_recursively() {
         .all(&context.db)
         .await
         .unwrap();
-    assert!(alice_rows.len() >= 2, "alice still owns the folder and child");
+    assert!(
+        alice_rows.len() >= 2,
+        "alice still owns the folder and child"
+    );
     let _ = context;
 }
 
@@ -10,9 +10,7 @@ mod shares_common;
 
 use actix_web::{http::StatusCode, test};
 use cryptfns::asn1::ShareRoleEnum;
-use entity::{
-    user_files, users, ActiveValue, ColumnTrait, EntityTrait, QueryFilter, Uuid,
-};
+use entity::{user_files, users, ActiveValue, ColumnTrait, EntityTrait, QueryFilter, Uuid};
 use hoodik::server;
 use serde_json::{json, Value};
 
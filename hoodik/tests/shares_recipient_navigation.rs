@@ -92,7 +92,10 @@ async fn test_recipient_can_fetch_metadata_for_shared_folder() {
     );
     let body: AppFile = test::read_body_json(resp).await;
     assert_eq!(body.id, folder.id);
-    assert!(!body.is_owner, "metadata reports the row as a non-owner row");
+    assert!(
+        !body.is_owner,
+        "metadata reports the row as a non-owner row"
+    );
     assert_eq!(body.mime, "dir");
     let _ = context;
 }
@@ -326,10 +329,7 @@ async fn test_explicit_is_owner_false_query_still_works() {
     assert!(post_share!(app, alice, envelope).status().is_success());
 
     let req = test::TestRequest::get()
-        .uri(&format!(
-            "/api/storage?dir_id={}&is_owner=false",
-            folder.id
-        ))
+        .uri(&format!("/api/storage?dir_id={}&is_owner=false", folder.id))
         .cookie(bob.jwt.clone())
         .to_request();
     let resp = test::call_service(&app, req).await;
@@ -123,7 +123,10 @@ async fn test_search_finds_shared_file_for_every_recipient_role_but_not_stranger
     assert!(
         alice_hits.iter().any(|f| f.id == file.id && f.is_owner),
         "owner alice should still find her own file via search; got {:?}",
-        alice_hits.iter().map(|f| (f.id, f.is_owner)).collect::<Vec<_>>()
+        alice_hits
+            .iter()
+            .map(|f| (f.id, f.is_owner))
+            .collect::<Vec<_>>()
     );
 
     for recipient in [&reader, &editor, &co_owner] {
@@ -15,7 +15,9 @@ async fn test_creating_file_and_uploading_chunks() {
     let app = test::init_service(server::app(context.clone())).await;
 
     let jwt = helpers::register_curve25519(&app, "john@doe.com").await.jwt;
-    let second_jwt = helpers::register_curve25519(&app, "john2@doe.com").await.jwt;
+    let second_jwt = helpers::register_curve25519(&app, "john2@doe.com")
+        .await
+        .jwt;
 
     let (mut data, mut size, _) = create_byte_chunks();
     assert_eq!(data.len(), size as usize / CHUNK_SIZE_BYTES as usize);
@@ -154,7 +156,9 @@ async fn test_transfer_token_upload_and_download() {
 
     let app = test::init_service(server::app(context.clone())).await;
 
-    let jwt = helpers::register_curve25519(&app, "transfer@test.com").await.jwt;
+    let jwt = helpers::register_curve25519(&app, "transfer@test.com")
+        .await
+        .jwt;
 
     // Create a file.
     let (data, size, _) = create_byte_chunks();
@@ -220,12 +224,7 @@ async fn test_transfer_token_upload_and_download() {
             .to_request();
 
         let resp = test::call_service(&app, req).await;
-        assert_eq!(
-            resp.status(),
-            StatusCode::OK,
-            "Upload chunk {} failed",
-            i
-        );
+        assert_eq!(resp.status(), StatusCode::OK, "Upload chunk {} failed", i);
     }
 
     // ── Verify upload token cannot be used for download ──────────
@@ -333,8 +332,7 @@ async fn test_transfer_token_upload_and_download() {
 
 #[actix_web::test]
 async fn test_download_tar_archive() {
-    let context =
-        context::Context::mock_with_data_dir(Some("../data-test-tar".to_string())).await;
+    let context = context::Context::mock_with_data_dir(Some("../data-test-tar".to_string())).await;
 
     let app = test::init_service(server::app(context.clone())).await;
 
@@ -502,7 +500,9 @@ async fn test_replace_content_atomic_edit() {
 
     let app = test::init_service(server::app(context.clone())).await;
 
-    let jwt = helpers::register_curve25519(&app, "editor@test.com").await.jwt;
+    let jwt = helpers::register_curve25519(&app, "editor@test.com")
+        .await
+        .jwt;
 
     // ── Create the editable file with v1 content ────────────────
     let v1_data = vec![b"version-one-content".to_vec()];
@@ -541,9 +541,14 @@ async fn test_replace_content_atomic_edit() {
 
     // Upload the v1 chunk and finalize.
     let req = test::TestRequest::post()
-        .uri(format!("/api/storage/{}?chunk=0&checksum={}",
-            file.id,
-            cryptfns::sha256::digest(v1_data[0].as_slice())).as_str())
+        .uri(
+            format!(
+                "/api/storage/{}?chunk=0&checksum={}",
+                file.id,
+                cryptfns::sha256::digest(v1_data[0].as_slice())
+            )
+            .as_str(),
+        )
         .cookie(jwt.clone())
         .append_header(("Content-Type", "application/octet-stream"))
         .set_payload(v1_data[0].clone())
@@ -552,7 +557,10 @@ async fn test_replace_content_atomic_edit() {
     let file: AppFile = serde_json::from_slice(&body).unwrap();
     assert!(file.finished_upload_at.is_some());
     assert_eq!(file.active_version, 1);
-    assert!(file.pending_version.is_none(), "first commit leaves no pending");
+    assert!(
+        file.pending_version.is_none(),
+        "first commit leaves no pending"
+    );
 
     // ── Edit: replaceContent with v2 metadata ────────────────────
     let v2_data = [b"version-two-totally-different-content!".to_vec()];
@@ -573,7 +581,11 @@ async fn test_replace_content_atomic_edit() {
     assert_eq!(file.pending_version, Some(2));
     assert_eq!(file.pending_chunks, Some(1));
     assert_eq!(file.pending_size, Some(v2_size));
-    assert_eq!(file.size, Some(v1_size), "size still describes the active v1");
+    assert_eq!(
+        file.size,
+        Some(v1_size),
+        "size still describes the active v1"
+    );
 
     // Download mid-edit must still serve v1 content unchanged.
     let req = test::TestRequest::get()
@@ -581,13 +593,21 @@ async fn test_replace_content_atomic_edit() {
         .cookie(jwt.clone())
         .to_request();
     let body_mid = test::call_and_read_body(&app, req).await.to_vec();
-    assert_eq!(body_mid, v1_data[0], "mid-edit reads see the previous version");
+    assert_eq!(
+        body_mid, v1_data[0],
+        "mid-edit reads see the previous version"
+    );
 
     // Upload the v2 chunk → triggers auto-finalize (pointer swap).
     let req = test::TestRequest::post()
-        .uri(format!("/api/storage/{}?chunk=0&checksum={}",
-            file.id,
-            cryptfns::sha256::digest(v2_data[0].as_slice())).as_str())
+        .uri(
+            format!(
+                "/api/storage/{}?chunk=0&checksum={}",
+                file.id,
+                cryptfns::sha256::digest(v2_data[0].as_slice())
+            )
+            .as_str(),
+        )
         .cookie(jwt.clone())
         .append_header(("Content-Type", "application/octet-stream"))
         .set_payload(v2_data[0].clone())
@@ -595,7 +615,10 @@ async fn test_replace_content_atomic_edit() {
     let body = test::call_and_read_body(&app, req).await;
     let file: AppFile = serde_json::from_slice(&body).unwrap();
     assert_eq!(file.active_version, 2, "active flips to v2 after finalize");
-    assert!(file.pending_version.is_none(), "pending cleared after finalize");
+    assert!(
+        file.pending_version.is_none(),
+        "pending cleared after finalize"
+    );
     assert_eq!(file.size, Some(v2_size), "size now describes v2");
 
     // Download after edit must serve v2.
@@ -627,12 +650,13 @@ async fn test_replace_content_atomic_edit() {
 /// edits from a second device.
 #[actix_web::test]
 async fn test_replace_content_concurrent_returns_409() {
-    let context =
-        context::Context::mock_with_data_dir(Some("../data-test-409".to_string())).await;
+    let context = context::Context::mock_with_data_dir(Some("../data-test-409".to_string())).await;
 
     let app = test::init_service(server::app(context.clone())).await;
 
-    let jwt = helpers::register_curve25519(&app, "conflict@test.com").await.jwt;
+    let jwt = helpers::register_curve25519(&app, "conflict@test.com")
+        .await
+        .jwt;
 
     let data = b"initial-content".to_vec();
     let create = storage::data::create_file::CreateFile {
@@ -663,9 +687,14 @@ async fn test_replace_content_concurrent_returns_409() {
     let file: AppFile = serde_json::from_slice(&body).unwrap();
 
     let req = test::TestRequest::post()
-        .uri(format!("/api/storage/{}?chunk=0&checksum={}",
-            file.id,
-            cryptfns::sha256::digest(data.as_slice())).as_str())
+        .uri(
+            format!(
+                "/api/storage/{}?chunk=0&checksum={}",
+                file.id,
+                cryptfns::sha256::digest(data.as_slice())
+            )
+            .as_str(),
+        )
         .cookie(jwt.clone())
         .append_header(("Content-Type", "application/octet-stream"))
         .set_payload(data.clone())
@@ -726,11 +755,14 @@ async fn test_versions_list_and_restore() {
     use entity::{ColumnTrait, EntityTrait, QueryFilter};
 
     let context =
-        context::Context::mock_with_data_dir(Some("../data-test-versions-restore".to_string())).await;
+        context::Context::mock_with_data_dir(Some("../data-test-versions-restore".to_string()))
+            .await;
 
     let app = test::init_service(server::app(context.clone())).await;
 
-    let jwt = helpers::register_curve25519(&app, "history@test.com").await.jwt;
+    let jwt = helpers::register_curve25519(&app, "history@test.com")
+        .await
+        .jwt;
 
     let create = storage::data::create_file::CreateFile {
         encrypted_key: Some("encrypted-key".to_string()),
@@ -755,23 +787,23 @@ async fn test_versions_list_and_restore() {
         .cookie(jwt.clone())
         .set_json(&create)
         .to_request();
-    let file: AppFile =
-        serde_json::from_slice(&test::call_and_read_body(&app, req).await).unwrap();
-
-    let upload_chunk = |jwt: actix_web::cookie::Cookie<'static>, fid: entity::Uuid, data: Vec<u8>| {
-        let app = &app;
-        async move {
-            let cs = cryptfns::sha256::digest(data.as_slice());
-            let req = test::TestRequest::post()
-                .uri(format!("/api/storage/{}?chunk=0&checksum={}", fid, cs).as_str())
-                .cookie(jwt)
-                .append_header(("Content-Type", "application/octet-stream"))
-                .set_payload(data)
-                .to_request();
-            let body = test::call_and_read_body(app, req).await;
-            serde_json::from_slice::<AppFile>(&body).unwrap()
-        }
-    };
+    let file: AppFile = serde_json::from_slice(&test::call_and_read_body(&app, req).await).unwrap();
+
+    let upload_chunk =
+        |jwt: actix_web::cookie::Cookie<'static>, fid: entity::Uuid, data: Vec<u8>| {
+            let app = &app;
+            async move {
+                let cs = cryptfns::sha256::digest(data.as_slice());
+                let req = test::TestRequest::post()
+                    .uri(format!("/api/storage/{}?chunk=0&checksum={}", fid, cs).as_str())
+                    .cookie(jwt)
+                    .append_header(("Content-Type", "application/octet-stream"))
+                    .set_payload(data)
+                    .to_request();
+                let body = test::call_and_read_body(app, req).await;
+                serde_json::from_slice::<AppFile>(&body).unwrap()
+            }
+        };
 
     let v1_bytes = b"AAA".to_vec();
     let v2_bytes = b"BBB".to_vec();
@@ -795,8 +827,7 @@ async fn test_versions_list_and_restore() {
         .cookie(jwt.clone())
         .to_request();
     let body = test::call_and_read_body(&app, req).await;
-    let versions: Vec<entity::file_versions::Model> =
-        serde_json::from_slice(&body).unwrap();
+    let versions: Vec<entity::file_versions::Model> = serde_json::from_slice(&body).unwrap();
     let history_versions: Vec<i32> = versions.iter().map(|v| v.version).collect();
     assert_eq!(
         history_versions,
@@ -822,7 +853,10 @@ async fn test_versions_list_and_restore() {
         .cookie(jwt.clone())
         .to_request();
     let downloaded = test::call_and_read_body(&app, req).await.to_vec();
-    assert_eq!(downloaded, v1_bytes, "restored content matches the v1 source");
+    assert_eq!(
+        downloaded, v1_bytes,
+        "restored content matches the v1 source"
+    );
 
     let history_count = entity::file_versions::Entity::find()
         .filter(entity::file_versions::Column::FileId.eq(file.id))
@@ -845,12 +879,13 @@ async fn test_versions_list_and_restore() {
 /// version stays untouched.
 #[actix_web::test]
 async fn test_fork_creates_independent_copy() {
-    let context =
-        context::Context::mock_with_data_dir(Some("../data-test-fork".to_string())).await;
+    let context = context::Context::mock_with_data_dir(Some("../data-test-fork".to_string())).await;
 
     let app = test::init_service(server::app(context.clone())).await;
 
-    let jwt = helpers::register_curve25519(&app, "fork@test.com").await.jwt;
+    let jwt = helpers::register_curve25519(&app, "fork@test.com")
+        .await
+        .jwt;
 
     // Source: a 1-chunk editable file with v1 content, then edited to v2.
     let v1_bytes = b"original-snapshot".to_vec();
@@ -883,11 +918,14 @@ async fn test_fork_creates_independent_copy() {
         serde_json::from_slice(&test::call_and_read_body(&app, req).await).unwrap();
 
     let req = test::TestRequest::post()
-        .uri(format!(
-            "/api/storage/{}?chunk=0&checksum={}",
-            source.id,
-            cryptfns::sha256::digest(v1_bytes.as_slice())
-        ).as_str())
+        .uri(
+            format!(
+                "/api/storage/{}?chunk=0&checksum={}",
+                source.id,
+                cryptfns::sha256::digest(v1_bytes.as_slice())
+            )
+            .as_str(),
+        )
         .cookie(jwt.clone())
         .append_header(("Content-Type", "application/octet-stream"))
         .set_payload(v1_bytes.clone())
@@ -902,11 +940,14 @@ async fn test_fork_creates_independent_copy() {
     let _ = test::call_and_read_body(&app, req).await;
 
     let req = test::TestRequest::post()
-        .uri(format!(
-            "/api/storage/{}?chunk=0&checksum={}",
-            source.id,
-            cryptfns::sha256::digest(v2_bytes.as_slice())
-        ).as_str())
+        .uri(
+            format!(
+                "/api/storage/{}?chunk=0&checksum={}",
+                source.id,
+                cryptfns::sha256::digest(v2_bytes.as_slice())
+            )
+            .as_str(),
+        )
         .cookie(jwt.clone())
         .append_header(("Content-Type", "application/octet-stream"))
         .set_payload(v2_bytes.clone())
@@ -932,7 +973,11 @@ async fn test_fork_creates_independent_copy() {
     let body = test::call_and_read_body(&app, req).await;
     let forked: AppFile = serde_json::from_slice(&body).unwrap();
     assert_ne!(forked.id, source.id);
-    assert_eq!(forked.size, Some(v1_bytes.len() as i64), "fork uses source v1 size");
+    assert_eq!(
+        forked.size,
+        Some(v1_bytes.len() as i64),
+        "fork uses source v1 size"
+    );
     assert_eq!(forked.active_version, 1);
     assert!(forked.finished_upload_at.is_some());
 
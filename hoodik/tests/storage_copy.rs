@@ -0,0 +1,363 @@
+//! Server-side copy: `POST /api/storage/copy` duplicates the rows of a file
+//! or a folder tree and copies the chunks on the storage provider in the
+//! background, so the copies download byte for byte without the client
+//! re-uploading anything.
+
+#[path = "./helpers.rs"]
+mod helpers;
+
+use actix_web::{http::StatusCode, test};
+use entity::{file_tokens, ColumnTrait, EntityTrait, QueryFilter};
+use hoodik::server;
+use serde_json::{json, Value};
+use storage::data::{app_file::AppFile, response::Response};
+
+/// Create a file or folder and parse the response. Macros rather than
+/// functions because the service `init_service` returns has a type that
+/// doesn't survive a plain `impl Service<...>` bound.
+macro_rules! create {
+    ($app:expr, $jwt:expr, $body:expr $(,)?) => {{
+        let req = test::TestRequest::post()
+            .uri("/api/storage")
+            .cookie($jwt.clone())
+            .set_json(&$body)
+            .to_request();
+        let resp = test::call_service($app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        test::read_body_json::<AppFile, _>(resp).await
+    }};
+}
+
+macro_rules! upload {
+    ($app:expr, $jwt:expr, $file:expr, $data:expr) => {{
+        let req = test::TestRequest::post()
+            .uri(&format!(
+                "/api/storage/{}?chunk=0&checksum={}",
+                $file.id,
+                cryptfns::sha256::digest($data)
+            ))
+            .cookie($jwt.clone())
+            .append_header(("Content-Type", "application/octet-stream"))
+            .set_payload($data.to_vec())
+            .to_request();
+        let resp = test::call_service($app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }};
+}
+
+macro_rules! download {
+    ($app:expr, $jwt:expr, $id:expr) => {{
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/storage/{}", $id))
+            .cookie($jwt.clone())
+            .to_request();
+        test::call_and_read_body($app, req).await.to_vec()
+    }};
+}
+
+/// Wait for a copy to end, returning its final state.
+macro_rules! wait_for_copy {
+    ($app:expr, $jwt:expr, $job:expr) => {{
+        let mut job: Value = $job;
+        for _ in 0..500 {
+            if job["status"] != "running" {
+                break;
+            }
+            actix_web::rt::time::sleep(std::time::Duration::from_millis(10)).await;
+
+            let req = test::TestRequest::get()
+                .uri(&format!(
+                    "/api/storage/copy/{}",
+                    job["id"].as_str().unwrap()
+                ))
+                .cookie($jwt.clone())
+                .to_request();
+            job = test::call_and_read_body_json($app, req).await;
+        }
+        job
+    }};
+}
+
+/// Start a copy, wait until it is done and return the copied root.
+macro_rules! copy {
+    ($app:expr, $jwt:expr, $body:expr) => {{
+        let req = test::TestRequest::post()
+            .uri("/api/storage/copy")
+            .cookie($jwt.clone())
+            .set_json(&$body)
+            .to_request();
+        let resp = test::call_service($app, req).await;
+        assert_eq!(resp.status(), StatusCode::ACCEPTED);
+        let job: Value = test::read_body_json(resp).await;
+
+        let job = wait_for_copy!($app, $jwt, job);
+        assert_eq!(job["status"], "done");
+        assert_eq!(job["files_copied"], job["files_total"]);
+
+        let req = test::TestRequest::get()
+            .uri(&format!(
+                "/api/storage/{}/metadata",
+                job["file_id"].as_str().unwrap()
+            ))
+            .cookie($jwt.clone())
+            .to_request();
+        test::call_and_read_body_json::<_, _, AppFile>($app, req).await
+    }};
+}
+
+fn node(id: entity::Uuid, name: &str) -> Value {
+    json!({
+        "id": id,
+        "encrypted_key": format!("rewrapped-{name}"),
+        "name_hash": format!("hash-{name}"),
+        "encrypted_name": name,
+        "search_tokens_hashed": [format!("{name}-token:1")],
+    })
+}
+
+#[actix_web::test]
+async fn test_copy_folder_tree_without_reupload() {
+    let context = context::Context::mock_with_data_dir(Some("../data-test-copy".to_string())).await;
+    let app = test::init_service(server::app(context.clone())).await;
+
+    let jwt = helpers::register_curve25519(&app, "copy@test.com")
+        .await
+        .jwt;
+
+    let folder = create!(
+        &app,
+        &jwt,
+        json!({
+            "encrypted_key": "folder-key",
+            "encrypted_name": "folder",
+            "name_hash": "folder-hash",
+            "mime": "dir",
+        }),
+    );
+    let sub = create!(
+        &app,
+        &jwt,
+        json!({
+            "encrypted_key": "sub-key",
+            "encrypted_name": "sub",
+            "name_hash": "sub-hash",
+            "mime": "dir",
+            "file_id": folder.id,
+        }),
+    );
+
+    // One write-once file in the flat layout, one editable note in the
+    // versioned layout with an edit behind it.
+    let binary = b"write-once-bytes".to_vec();
+    let file = create!(
+        &app,
+        &jwt,
+        json!({
+            "encrypted_key": "file-key",
+            "encrypted_name": "file",
+            "name_hash": "file-hash",
+            "mime": "application/octet-stream",
+            "size": binary.len(),
+            "chunks": 1,
+            "file_id": folder.id,
+        }),
+    );
+    upload!(&app, &jwt, &file, &binary);
+
+    let first = b"first-draft".to_vec();
+    let note = create!(
+        &app,
+        &jwt,
+        json!({
+            "encrypted_key": "note-key",
+            "encrypted_name": "note",
+            "name_hash": "note-hash",
+            "mime": "text/markdown",
+            "size": first.len(),
+            "chunks": 1,
+            "file_id": sub.id,
+            "editable": true,
+        }),
+    );
+    upload!(&app, &jwt, &note, &first);
+
+    let second = b"second-draft!".to_vec();
+    let req = test::TestRequest::put()
+        .uri(&format!("/api/storage/{}/content", note.id))
+        .cookie(jwt.clone())
+        .set_json(json!({ "size": second.len(), "chunks": 1 }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+    upload!(&app, &jwt, &note, &second);
+
+    // Leaving a node out is rejected before anything is copied.
+    let req = test::TestRequest::post()
+        .uri("/api/storage/copy")
+        .cookie(jwt.clone())
+        .set_json(json!({
+            "id": folder.id,
+            "nodes": [node(folder.id, "folder-copy"), node(sub.id, "sub-copy")],
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["message"], "nodes_do_not_match_tree");
+
+    // A folder can't be copied into itself.
+    let req = test::TestRequest::post()
+        .uri("/api/storage/copy")
+        .cookie(jwt.clone())
+        .set_json(json!({
+            "id": folder.id,
+            "file_id": sub.id,
+            "nodes": [
+                node(folder.id, "folder-copy"),
+                node(sub.id, "sub-copy"),
+                node(file.id, "file-copy"),
+                node(note.id, "note-copy"),
+            ],
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let nodes = json!([
+        node(folder.id, "folder-copy"),
+        node(sub.id, "sub-copy"),
+        node(file.id, "file-copy"),
+        node(note.id, "note-copy"),
+    ]);
+    let root = copy!(&app, &jwt, json!({ "id": folder.id, "nodes": nodes }));
+    assert_ne!(root.id, folder.id);
+    assert!(root.is_dir());
+    assert_eq!(root.file_id, None);
+    assert_eq!(root.encrypted_name, "folder-copy");
+    assert_eq!(root.encrypted_key, "rewrapped-folder-copy");
+
+    let list = |dir_id: entity::Uuid| {
+        test::TestRequest::get()
+            .uri(&format!("/api/storage?dir_id={dir_id}"))
+            .cookie(jwt.clone())
+            .to_request()
+    };
+    let listing: Response = test::call_and_read_body_json(&app, list(root.id)).await;
+    assert_eq!(listing.children.len(), 2);
+    let sub_copy = listing
+        .children
+        .iter()
+        .find(|f| f.encrypted_name == "sub-copy")
+        .unwrap();
+    let file_copy = listing
+        .children
+        .iter()
+        .find(|f| f.encrypted_name == "file-copy")
+        .unwrap();
+
+    let listing: Response = test::call_and_read_body_json(&app, list(sub_copy.id)).await;
+    assert_eq!(listing.children.len(), 1);
+    let note_copy = &listing.children[0];
+    assert_eq!(note_copy.encrypted_name, "note-copy");
+    assert!(note_copy.editable);
+    assert_eq!(note_copy.active_version, 1);
+    assert_eq!(note_copy.size, Some(second.len() as i64));
+    assert!(note_copy.finished_upload_at.is_some());
+
+    // Both layouts round-trip, and the note copy carries the active version.
+    assert_eq!(download!(&app, &jwt, file_copy.id), binary);
+    assert_eq!(download!(&app, &jwt, note_copy.id), second);
+    assert_eq!(download!(&app, &jwt, file.id), binary);
+    assert_eq!(download!(&app, &jwt, note.id), second);
+
+    let tokens = file_tokens::Entity::find()
+        .filter(file_tokens::Column::FileId.eq(file_copy.id))
+        .all(&context.db)
+        .await
+        .unwrap();
+    assert_eq!(tokens.len(), 1);
+
+    // The same names again at the top level collide with the first copy.
+    let req = test::TestRequest::post()
+        .uri("/api/storage/copy")
+        .cookie(jwt.clone())
+        .set_json(json!({ "id": folder.id, "nodes": nodes }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["message"], "file_or_directory_exists");
+
+    // A single file copies into another folder.
+    let single = copy!(
+        &app,
+        &jwt,
+        json!({
+            "id": file.id,
+            "file_id": sub.id,
+            "nodes": [node(file.id, "single-copy")],
+        })
+    );
+    assert_eq!(single.file_id, Some(sub.id));
+    assert_eq!(download!(&app, &jwt, single.id), binary);
+
+    context.config.app.cleanup();
+}
+
+#[actix_web::test]
+async fn test_copy_requires_ownership() {
+    let context =
+        context::Context::mock_with_data_dir(Some("../data-test-copy-owner".to_string())).await;
+    let app = test::init_service(server::app(context.clone())).await;
+
+    let owner = helpers::register_curve25519(&app, "copy-owner@test.com")
+        .await
+        .jwt;
+    let other = helpers::register_curve25519(&app, "copy-other@test.com")
+        .await
+        .jwt;
+
+    let data = b"private".to_vec();
+    let file = create!(
+        &app,
+        &owner,
+        json!({
+            "encrypted_key": "file-key",
+            "encrypted_name": "file",
+            "name_hash": "file-hash",
+            "mime": "text/plain",
+            "size": data.len(),
+            "chunks": 1,
+        }),
+    );
+    upload!(&app, &owner, &file, &data);
+
+    let req = test::TestRequest::post()
+        .uri("/api/storage/copy")
+        .cookie(other.clone())
+        .set_json(json!({ "id": file.id, "nodes": [node(file.id, "stolen")] }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    // Nor can they follow the owner's copies.
+    let req = test::TestRequest::post()
+        .uri("/api/storage/copy")
+        .cookie(owner.clone())
+        .set_json(json!({ "id": file.id, "nodes": [node(file.id, "mine")] }))
+        .to_request();
+    let job: Value = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get()
+        .uri(&format!(
+            "/api/storage/copy/{}",
+            job["id"].as_str().unwrap()
+        ))
+        .cookie(other.clone())
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    let job = wait_for_copy!(&app, &owner, job);
+    assert_eq!(job["status"], "done");
+
+    context.config.app.cleanup();
+}
@@ -0,0 +1,236 @@
+//! Incremental edits: `replaceContent` with `reuse_chunks` links unchanged
+//! chunks to the version holding them, so only the changed chunks are
+//! uploaded and every version still downloads in full.
+
+#[path = "./helpers.rs"]
+mod helpers;
+
+use actix_web::{http::StatusCode, test};
+use entity::{file_chunk_refs, ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+use hoodik::server;
+use serde_json::json;
+use storage::data::app_file::AppFile;
+
+/// `(version, chunk, source_version)` of every reference the file has.
+async fn refs(db: &entity::DbConn, file: &AppFile) -> Vec<(i32, i64, i32)> {
+    file_chunk_refs::Entity::find()
+        .filter(file_chunk_refs::Column::FileId.eq(file.id))
+        .order_by_asc(file_chunk_refs::Column::Version)
+        .order_by_asc(file_chunk_refs::Column::Chunk)
+        .all(db)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|r| (r.version, r.chunk, r.source_version))
+        .collect()
+}
+
+/// Macros rather than functions because the service `init_service` returns
+/// has a type that doesn't survive a plain `impl Service<...>` bound.
+macro_rules! upload {
+    ($app:expr, $jwt:expr, $file:expr, $chunk:expr, $data:expr) => {{
+        let req = test::TestRequest::post()
+            .uri(&format!(
+                "/api/storage/{}?chunk={}&checksum={}",
+                $file.id,
+                $chunk,
+                cryptfns::sha256::digest($data)
+            ))
+            .cookie($jwt.clone())
+            .append_header(("Content-Type", "application/octet-stream"))
+            .set_payload($data.to_vec())
+            .to_request();
+        let resp = test::call_service($app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        test::read_body_json::<AppFile, _>(resp).await
+    }};
+}
+
+macro_rules! replace_content {
+    ($app:expr, $jwt:expr, $file:expr, $body:expr) => {{
+        let req = test::TestRequest::put()
+            .uri(&format!("/api/storage/{}/content", $file.id))
+            .cookie($jwt.clone())
+            .set_json(&$body)
+            .to_request();
+        test::call_service($app, req).await
+    }};
+}
+
+macro_rules! download {
+    ($app:expr, $jwt:expr, $uri:expr) => {{
+        let req = test::TestRequest::get()
+            .uri(&$uri)
+            .cookie($jwt.clone())
+            .to_request();
+        test::call_and_read_body($app, req).await.to_vec()
+    }};
+}
+
+#[actix_web::test]
+async fn test_replace_content_reuses_unchanged_chunks() {
+    let context =
+        context::Context::mock_with_data_dir(Some("../data-test-incremental".to_string())).await;
+    let app = test::init_service(server::app(context.clone())).await;
+
+    let jwt = helpers::register_curve25519(&app, "incremental@test.com")
+        .await
+        .jwt;
+
+    let req = test::TestRequest::post()
+        .uri("/api/storage")
+        .cookie(jwt.clone())
+        .set_json(json!({
+            "encrypted_key": "note-key",
+            "encrypted_name": "note",
+            "name_hash": "note-hash",
+            "mime": "text/markdown",
+            "size": 6,
+            "chunks": 3,
+            "editable": true,
+        }))
+        .to_request();
+    let note: AppFile = test::call_and_read_body_json(&app, req).await;
+
+    upload!(&app, &jwt, &note, 0, b"a0");
+    upload!(&app, &jwt, &note, 1, b"a1");
+    upload!(&app, &jwt, &note, 2, b"a2");
+
+    // Only the middle chunk changed, so it is the only one uploaded.
+    let resp = replace_content!(
+        &app,
+        &jwt,
+        note,
+        json!({
+            "size": 6,
+            "chunks": 3,
+            "reuse_chunks": [{ "chunk": 0, "version": 1 }, { "chunk": 2, "version": 1 }],
+        })
+    );
+    assert_eq!(resp.status(), StatusCode::OK);
+    let pending: AppFile = test::read_body_json(resp).await;
+    assert_eq!(pending.pending_version, Some(2));
+    assert_eq!(pending.chunks_stored, Some(2));
+
+    let edited = upload!(&app, &jwt, &note, 1, b"b1");
+    assert_eq!(edited.active_version, 2);
+    assert_eq!(
+        download!(&app, &jwt, format!("/api/storage/{}", note.id)),
+        b"a0b1a2"
+    );
+
+    // Reusing every chunk leaves nothing to upload, the edit is final as
+    // soon as the request returns.
+    let resp = replace_content!(
+        &app,
+        &jwt,
+        note,
+        json!({
+            "size": 6,
+            "chunks": 3,
+            "reuse_chunks": [
+                { "chunk": 0, "version": 2 },
+                { "chunk": 1, "version": 2 },
+                { "chunk": 2, "version": 2 },
+            ],
+        })
+    );
+    assert_eq!(resp.status(), StatusCode::OK);
+    let finished: AppFile = test::read_body_json(resp).await;
+    assert_eq!(finished.active_version, 3);
+    assert_eq!(finished.pending_version, None);
+
+    // References name the version storing the chunk, never a borrower.
+    assert_eq!(
+        refs(&context.db, &note).await,
+        vec![(2, 0, 1), (2, 2, 1), (3, 0, 1), (3, 1, 2), (3, 2, 1)]
+    );
+
+    assert_eq!(
+        download!(&app, &jwt, format!("/api/storage/{}/versions/1", note.id)),
+        b"a0a1a2"
+    );
+
+    // Dropping the versions that hold the bytes keeps the chunks that the
+    // active version still borrows.
+    for version in [1, 2] {
+        let req = test::TestRequest::delete()
+            .uri(&format!("/api/storage/{}/versions/{}", note.id, version))
+            .cookie(jwt.clone())
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::NO_CONTENT
+        );
+    }
+    // Every chunk moved into the active version, nothing is borrowed.
+    assert!(refs(&context.db, &note).await.is_empty());
+    assert_eq!(
+        download!(&app, &jwt, format!("/api/storage/{}", note.id)),
+        b"a0b1a2"
+    );
+
+    context.config.app.cleanup();
+}
+
+#[actix_web::test]
+async fn test_replace_content_rejects_unknown_reused_chunks() {
+    let context =
+        context::Context::mock_with_data_dir(Some("../data-test-incremental-reject".to_string()))
+            .await;
+    let app = test::init_service(server::app(context.clone())).await;
+
+    let jwt = helpers::register_curve25519(&app, "incremental-reject@test.com")
+        .await
+        .jwt;
+
+    let req = test::TestRequest::post()
+        .uri("/api/storage")
+        .cookie(jwt.clone())
+        .set_json(json!({
+            "encrypted_key": "note-key",
+            "encrypted_name": "note",
+            "name_hash": "note-hash",
+            "mime": "text/markdown",
+            "size": 2,
+            "chunks": 1,
+            "editable": true,
+        }))
+        .to_request();
+    let note: AppFile = test::call_and_read_body_json(&app, req).await;
+    upload!(&app, &jwt, &note, 0, b"a0");
+
+    // No such version in the file's history.
+    let resp = replace_content!(
+        &app,
+        &jwt,
+        note,
+        json!({ "size": 4, "chunks": 2, "reuse_chunks": [{ "chunk": 0, "version": 7 }] })
+    );
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    // The active version has a single chunk.
+    let resp = replace_content!(
+        &app,
+        &jwt,
+        note,
+        json!({ "size": 4, "chunks": 2, "reuse_chunks": [{ "chunk": 1, "version": 1 }] })
+    );
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    // Past the end of the new content, and listed twice.
+    for reuse in [
+        json!([{ "chunk": 2, "version": 1 }]),
+        json!([{ "chunk": 0, "version": 1 }, { "chunk": 0, "version": 1 }]),
+    ] {
+        let resp = replace_content!(
+            &app,
+            &jwt,
+            note,
+            json!({ "size": 4, "chunks": 2, "reuse_chunks": reuse })
+        );
+        assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    context.config.app.cleanup();
+}
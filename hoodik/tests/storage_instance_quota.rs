@@ -168,10 +168,8 @@ async fn instance_quota_shapes_admin_available_space() {
 
 #[actix_web::test]
 async fn instance_quota_rejects_tar_pre_read() {
-    let mut context = context::Context::mock_with_data_dir(Some(
-        "../data-test-instq-preread".to_string(),
-    ))
-    .await;
+    let mut context =
+        context::Context::mock_with_data_dir(Some("../data-test-instq-preread".to_string())).await;
     context.config.app.storage_instance_quota_bytes = Some(2048);
     let app = test::init_service(server::app(context.clone())).await;
     register!(app, jwt, "alice@example.com");
@@ -182,8 +180,8 @@ async fn instance_quota_rejects_tar_pre_read() {
         .cookie(jwt.clone())
         .set_json(sized_create("tar-preread", &[1024, 1024]))
         .to_request();
-    let file: AppFile = serde_json::from_slice(&test::call_and_read_body(&app, req).await)
-        .expect("create AppFile");
+    let file: AppFile =
+        serde_json::from_slice(&test::call_and_read_body(&app, req).await).expect("create AppFile");
 
     // A Content-Length advertises the incoming bytes, so the pre-read gate
     // rejects before a single chunk is streamed.
@@ -205,10 +203,9 @@ async fn instance_quota_rejects_tar_pre_read() {
 
 #[actix_web::test]
 async fn instance_quota_rejects_tar_mid_stream() {
-    let mut context = context::Context::mock_with_data_dir(Some(
-        "../data-test-instq-midstream".to_string(),
-    ))
-    .await;
+    let mut context =
+        context::Context::mock_with_data_dir(Some("../data-test-instq-midstream".to_string()))
+            .await;
     context.config.app.storage_instance_quota_bytes = Some(3000);
     let app = test::init_service(server::app(context.clone())).await;
     register!(app, jwt, "alice@example.com");
@@ -219,8 +216,8 @@ async fn instance_quota_rejects_tar_mid_stream() {
         .cookie(jwt.clone())
         .set_json(sized_create("tar-midstream", &[1024, 1024]))
         .to_request();
-    let file: AppFile = serde_json::from_slice(&test::call_and_read_body(&app, req).await)
-        .expect("create AppFile");
+    let file: AppFile =
+        serde_json::from_slice(&test::call_and_read_body(&app, req).await).expect("create AppFile");
 
     // No Content-Length, so the pre-read gate is skipped and the running-total
     // check inside the stream is the one that must catch the overflow: 3000 -
@@ -48,7 +48,9 @@ async fn test_non_editable_file_uses_legacy_layout_on_disk() {
     .await;
     let app = test::init_service(server::app(context.clone())).await;
 
-    let jwt = helpers::register_curve25519(&app, "legacy-routing-1@test.com").await.jwt;
+    let jwt = helpers::register_curve25519(&app, "legacy-routing-1@test.com")
+        .await
+        .jwt;
 
     let (data, size, _) = create_byte_chunks();
     let checksum = calculate_checksum(data.clone());
@@ -132,7 +134,9 @@ async fn test_editable_note_uses_versioned_layout() {
     .await;
     let app = test::init_service(server::app(context.clone())).await;
 
-    let jwt = helpers::register_curve25519(&app, "legacy-routing-2@test.com").await.jwt;
+    let jwt = helpers::register_curve25519(&app, "legacy-routing-2@test.com")
+        .await
+        .jwt;
 
     let payload = b"# hello world\n\nediting notes is nice".to_vec();
     let size = payload.len() as i64;
@@ -215,7 +219,9 @@ async fn test_set_editable_true_preserves_legacy_then_first_edit_snapshots_as_v1
     .await;
     let app = test::init_service(server::app(context.clone())).await;
 
-    let jwt = helpers::register_curve25519(&app, "legacy-routing-3@test.com").await.jwt;
+    let jwt = helpers::register_curve25519(&app, "legacy-routing-3@test.com")
+        .await
+        .jwt;
 
     let v1_payload = b"original legacy content v1".to_vec();
     let v1_size = v1_payload.len() as i64;
@@ -266,7 +272,9 @@ async fn test_set_editable_true_preserves_legacy_then_first_edit_snapshots_as_v1
     let req = test::TestRequest::put()
         .uri(format!("/api/storage/{}/editable", file.id).as_str())
         .cookie(jwt.clone())
-        .set_json(&SetEditable { editable: Some(true) })
+        .set_json(&SetEditable {
+            editable: Some(true),
+        })
         .to_request();
     let body = test::call_and_read_body(&app, req).await;
     let file: AppFile = serde_json::from_slice(&body).unwrap();
@@ -360,7 +368,9 @@ async fn test_set_editable_false_rejects_with_history() {
     .await;
     let app = test::init_service(server::app(context.clone())).await;
 
-    let jwt = helpers::register_curve25519(&app, "legacy-routing-4@test.com").await.jwt;
+    let jwt = helpers::register_curve25519(&app, "legacy-routing-4@test.com")
+        .await
+        .jwt;
 
     // Create editable and commit v1.
     let v1 = b"v1 payload".to_vec();
@@ -427,7 +437,9 @@ async fn test_set_editable_false_rejects_with_history() {
     let req = test::TestRequest::put()
         .uri(format!("/api/storage/{}/editable", file.id).as_str())
         .cookie(jwt.clone())
-        .set_json(&SetEditable { editable: Some(false) })
+        .set_json(&SetEditable {
+            editable: Some(false),
+        })
         .to_request();
     let resp = test::call_service(&app, req).await;
     assert_eq!(resp.status(), StatusCode::CONFLICT);
@@ -450,7 +462,9 @@ async fn test_set_editable_false_rejects_with_history() {
     let req = test::TestRequest::put()
         .uri(format!("/api/storage/{}/editable", file.id).as_str())
         .cookie(jwt.clone())
-        .set_json(&SetEditable { editable: Some(false) })
+        .set_json(&SetEditable {
+            editable: Some(false),
+        })
         .to_request();
     let resp = test::call_service(&app, req).await;
     assert_eq!(
@@ -474,7 +488,9 @@ async fn test_set_editable_during_pending_edit_is_409() {
     .await;
     let app = test::init_service(server::app(context.clone())).await;
 
-    let jwt = helpers::register_curve25519(&app, "legacy-routing-5@test.com").await.jwt;
+    let jwt = helpers::register_curve25519(&app, "legacy-routing-5@test.com")
+        .await
+        .jwt;
 
     let v1 = b"v1 payload".to_vec();
     let create = storage::data::create_file::CreateFile {
@@ -528,7 +544,9 @@ async fn test_set_editable_during_pending_edit_is_409() {
     let req = test::TestRequest::put()
         .uri(format!("/api/storage/{}/editable", file.id).as_str())
         .cookie(jwt.clone())
-        .set_json(&SetEditable { editable: Some(false) })
+        .set_json(&SetEditable {
+            editable: Some(false),
+        })
         .to_request();
     let resp = test::call_service(&app, req).await;
     assert_eq!(resp.status(), StatusCode::CONFLICT);
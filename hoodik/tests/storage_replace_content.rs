@@ -16,7 +16,9 @@ async fn test_replace_content_rejects_non_editable_file() {
 
     let app = test::init_service(server::app(context.clone())).await;
 
-    let jwt = helpers::register_curve25519(&app, "noeditable@test.com").await.jwt;
+    let jwt = helpers::register_curve25519(&app, "noeditable@test.com")
+        .await
+        .jwt;
 
     let create = storage::data::create_file::CreateFile {
         encrypted_key: Some("encrypted-key".to_string()),
@@ -67,7 +69,9 @@ async fn test_replace_content_rejects_directory() {
 
     let app = test::init_service(server::app(context.clone())).await;
 
-    let jwt = helpers::register_curve25519(&app, "dirtest@test.com").await.jwt;
+    let jwt = helpers::register_curve25519(&app, "dirtest@test.com")
+        .await
+        .jwt;
 
     let create = storage::data::create_file::CreateFile {
         encrypted_key: Some("encrypted-key".to_string()),
@@ -120,7 +124,9 @@ async fn test_replace_content_validates_size_and_chunks() {
 
     let app = test::init_service(server::app(context.clone())).await;
 
-    let jwt = helpers::register_curve25519(&app, "validate@test.com").await.jwt;
+    let jwt = helpers::register_curve25519(&app, "validate@test.com")
+        .await
+        .jwt;
 
     let create = storage::data::create_file::CreateFile {
         encrypted_key: Some("encrypted-key".to_string()),
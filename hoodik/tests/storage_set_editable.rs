@@ -11,12 +11,13 @@ use crate::helpers::create_byte_chunks;
 #[actix_web::test]
 async fn test_set_editable_converts_file_to_note() {
     let context =
-        context::Context::mock_with_data_dir(Some("../data-test-set-editable-1".to_string()))
-            .await;
+        context::Context::mock_with_data_dir(Some("../data-test-set-editable-1".to_string())).await;
 
     let app = test::init_service(server::app(context.clone())).await;
 
-    let jwt = helpers::register_curve25519(&app, "editable-1@test.com").await.jwt;
+    let jwt = helpers::register_curve25519(&app, "editable-1@test.com")
+        .await
+        .jwt;
 
     // Create a non-editable markdown file
     let (data, size, _) = create_byte_chunks();
@@ -81,12 +82,13 @@ async fn test_set_editable_converts_file_to_note() {
 #[actix_web::test]
 async fn test_set_editable_can_revert_note_to_regular_file() {
     let context =
-        context::Context::mock_with_data_dir(Some("../data-test-set-editable-2".to_string()))
-            .await;
+        context::Context::mock_with_data_dir(Some("../data-test-set-editable-2".to_string())).await;
 
     let app = test::init_service(server::app(context.clone())).await;
 
-    let jwt = helpers::register_curve25519(&app, "editable-2@test.com").await.jwt;
+    let jwt = helpers::register_curve25519(&app, "editable-2@test.com")
+        .await
+        .jwt;
 
     // Create an editable file
     let (data, size, _) = create_byte_chunks();
@@ -140,12 +142,13 @@ async fn test_set_editable_can_revert_note_to_regular_file() {
 #[actix_web::test]
 async fn test_set_editable_rejects_directory() {
     let context =
-        context::Context::mock_with_data_dir(Some("../data-test-set-editable-3".to_string()))
-            .await;
+        context::Context::mock_with_data_dir(Some("../data-test-set-editable-3".to_string())).await;
 
     let app = test::init_service(server::app(context.clone())).await;
 
-    let jwt = helpers::register_curve25519(&app, "editable-3@test.com").await.jwt;
+    let jwt = helpers::register_curve25519(&app, "editable-3@test.com")
+        .await
+        .jwt;
 
     // Create a directory
     let create = storage::data::create_file::CreateFile {
@@ -194,12 +197,13 @@ async fn test_set_editable_rejects_directory() {
 #[actix_web::test]
 async fn test_set_editable_requires_editable_field() {
     let context =
-        context::Context::mock_with_data_dir(Some("../data-test-set-editable-4".to_string()))
-            .await;
+        context::Context::mock_with_data_dir(Some("../data-test-set-editable-4".to_string())).await;
 
     let app = test::init_service(server::app(context.clone())).await;
 
-    let jwt = helpers::register_curve25519(&app, "editable-4@test.com").await.jwt;
+    let jwt = helpers::register_curve25519(&app, "editable-4@test.com")
+        .await
+        .jwt;
 
     // Create a file
     let (data, size, _) = create_byte_chunks();
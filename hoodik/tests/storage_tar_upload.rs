@@ -60,8 +60,7 @@ fn create_file_json(chunks: &[Vec<u8>], name: &str) -> storage::data::create_fil
 /// with this so the per-test body stays focused on the scenario.
 macro_rules! setup {
     ($ctx:ident, $app:ident, $jwt:ident, $data_dir:expr, $email:expr) => {
-        let $ctx =
-            context::Context::mock_with_data_dir(Some($data_dir.to_string())).await;
+        let $ctx = context::Context::mock_with_data_dir(Some($data_dir.to_string())).await;
         let $app = test::init_service(server::app($ctx.clone())).await;
 
         let $jwt = helpers::register_curve25519(&$app, $email).await.jwt;
@@ -134,7 +133,13 @@ macro_rules! download_bytes {
 
 #[actix_web::test]
 async fn test_upload_tar_happy_path() {
-    setup!(context, app, jwt, "../data-test-tar-upload-happy", "tar-happy@test.com");
+    setup!(
+        context,
+        app,
+        jwt,
+        "../data-test-tar-upload-happy",
+        "tar-happy@test.com"
+    );
 
     let chunks = mock_chunks(4, 1024);
     let file = create_file!(app, jwt, create_file_json(&chunks, "happy.enc"));
@@ -156,7 +161,13 @@ async fn test_upload_tar_happy_path() {
 
 #[actix_web::test]
 async fn test_upload_tar_idempotent_replay() {
-    setup!(context, app, jwt, "../data-test-tar-upload-replay", "tar-replay@test.com");
+    setup!(
+        context,
+        app,
+        jwt,
+        "../data-test-tar-upload-replay",
+        "tar-replay@test.com"
+    );
 
     let chunks = mock_chunks(3, 512);
     let file = create_file!(app, jwt, create_file_json(&chunks, "replay.enc"));
@@ -179,7 +190,13 @@ async fn test_upload_tar_idempotent_replay() {
 
 #[actix_web::test]
 async fn test_upload_tar_partial_then_complete() {
-    setup!(context, app, jwt, "../data-test-tar-upload-split", "tar-split@test.com");
+    setup!(
+        context,
+        app,
+        jwt,
+        "../data-test-tar-upload-split",
+        "tar-split@test.com"
+    );
 
     let chunks = mock_chunks(5, 256);
     let file = create_file!(app, jwt, create_file_json(&chunks, "split.enc"));
@@ -213,7 +230,13 @@ async fn test_upload_tar_partial_then_complete() {
 
 #[actix_web::test]
 async fn test_upload_tar_malformed_rejected() {
-    setup!(context, app, jwt, "../data-test-tar-upload-malformed", "tar-malformed@test.com");
+    setup!(
+        context,
+        app,
+        jwt,
+        "../data-test-tar-upload-malformed",
+        "tar-malformed@test.com"
+    );
 
     let chunks = mock_chunks(1, 128);
     let file = create_file!(app, jwt, create_file_json(&chunks, "malformed.enc"));
@@ -237,7 +260,13 @@ async fn test_upload_tar_malformed_rejected() {
 
 #[actix_web::test]
 async fn test_upload_tar_out_of_range_chunk_rejected() {
-    setup!(context, app, jwt, "../data-test-tar-upload-oor", "tar-oor@test.com");
+    setup!(
+        context,
+        app,
+        jwt,
+        "../data-test-tar-upload-oor",
+        "tar-oor@test.com"
+    );
 
     let chunks = mock_chunks(2, 64);
     let file = create_file!(app, jwt, create_file_json(&chunks, "oor.enc"));
@@ -250,7 +279,13 @@ async fn test_upload_tar_out_of_range_chunk_rejected() {
 
 #[actix_web::test]
 async fn test_upload_tar_duplicate_index_rejected() {
-    setup!(context, app, jwt, "../data-test-tar-upload-dup", "tar-dup@test.com");
+    setup!(
+        context,
+        app,
+        jwt,
+        "../data-test-tar-upload-dup",
+        "tar-dup@test.com"
+    );
 
     let chunks = mock_chunks(3, 32);
     let file = create_file!(app, jwt, create_file_json(&chunks, "dup.enc"));
@@ -262,10 +297,9 @@ async fn test_upload_tar_duplicate_index_rejected() {
 
 #[actix_web::test]
 async fn test_upload_tar_unauthenticated() {
-    let context = context::Context::mock_with_data_dir(Some(
-        "../data-test-tar-upload-unauth".to_string(),
-    ))
-    .await;
+    let context =
+        context::Context::mock_with_data_dir(Some("../data-test-tar-upload-unauth".to_string()))
+            .await;
     let app = test::init_service(server::app(context.clone())).await;
 
     let req = test::TestRequest::post()
@@ -280,9 +314,17 @@ async fn test_upload_tar_unauthenticated() {
 
 #[actix_web::test]
 async fn test_upload_tar_not_owned() {
-    setup!(context, app, owner_jwt, "../data-test-tar-upload-notowned", "tar-owner@test.com");
+    setup!(
+        context,
+        app,
+        owner_jwt,
+        "../data-test-tar-upload-notowned",
+        "tar-owner@test.com"
+    );
 
-    let stranger_jwt = helpers::register_curve25519(&app, "tar-stranger@test.com").await.jwt;
+    let stranger_jwt = helpers::register_curve25519(&app, "tar-stranger@test.com")
+        .await
+        .jwt;
 
     let chunks = mock_chunks(2, 32);
     let file = create_file!(app, owner_jwt, create_file_json(&chunks, "private.enc"));
@@ -364,7 +406,13 @@ async fn test_upload_tar_three_chunk_aegis_encrypted_file() {
 
 #[actix_web::test]
 async fn test_upload_tar_matches_per_chunk_final_state() {
-    setup!(context, app, jwt, "../data-test-tar-upload-parity", "tar-parity@test.com");
+    setup!(
+        context,
+        app,
+        jwt,
+        "../data-test-tar-upload-parity",
+        "tar-parity@test.com"
+    );
 
     let chunks = mock_chunks(4, 2048);
     let file_tar = create_file!(
@@ -0,0 +1,276 @@
+//! Version retention: per-file and per-user policies thin the history of an
+//! editable file after each edit and in the `prune-file-versions` sweep,
+//! and pinned versions survive both.
+
+#[path = "./helpers.rs"]
+mod helpers;
+
+use actix_web::{http::StatusCode, test};
+use entity::file_versions;
+use hoodik::server;
+use serde_json::{json, Value};
+use storage::data::app_file::AppFile;
+
+/// Macros rather than functions because the service `init_service` returns
+/// has a type that doesn't survive a plain `impl Service<...>` bound.
+macro_rules! upload {
+    ($app:expr, $jwt:expr, $file:expr, $data:expr) => {{
+        let req = test::TestRequest::post()
+            .uri(&format!(
+                "/api/storage/{}?chunk=0&checksum={}",
+                $file.id,
+                cryptfns::sha256::digest($data)
+            ))
+            .cookie($jwt.clone())
+            .append_header(("Content-Type", "application/octet-stream"))
+            .set_payload($data.to_vec())
+            .to_request();
+        let resp = test::call_service($app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }};
+}
+
+/// Replace the content of a single-chunk note and upload it, committing a
+/// new version.
+macro_rules! edit {
+    ($app:expr, $jwt:expr, $file:expr, $data:expr) => {{
+        let req = test::TestRequest::put()
+            .uri(&format!("/api/storage/{}/content", $file.id))
+            .cookie($jwt.clone())
+            .set_json(json!({ "size": $data.len(), "chunks": 1 }))
+            .to_request();
+        let resp = test::call_service($app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        upload!($app, $jwt, $file, $data);
+    }};
+}
+
+macro_rules! history {
+    ($app:expr, $jwt:expr, $file:expr) => {{
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/storage/{}/versions", $file.id))
+            .cookie($jwt.clone())
+            .to_request();
+        let versions: Vec<file_versions::Model> = test::call_and_read_body_json($app, req).await;
+        versions.iter().map(|v| v.version).collect::<Vec<_>>()
+    }};
+}
+
+macro_rules! request {
+    ($app:expr, $jwt:expr, $method:ident, $uri:expr) => {{
+        let req = test::TestRequest::$method()
+            .uri(&$uri)
+            .cookie($jwt.clone())
+            .to_request();
+        test::call_service($app, req).await
+    }};
+    ($app:expr, $jwt:expr, $method:ident, $uri:expr, $body:expr) => {{
+        let req = test::TestRequest::$method()
+            .uri(&$uri)
+            .cookie($jwt.clone())
+            .set_json(&$body)
+            .to_request();
+        test::call_service($app, req).await
+    }};
+}
+
+fn policy(keep_all_days: i32, max_versions: Option<i32>) -> Value {
+    json!({
+        "keep_all_days": keep_all_days,
+        "keep_hourly": 0,
+        "keep_daily": 0,
+        "keep_weekly": 0,
+        "keep_monthly": 0,
+        "max_versions": max_versions,
+    })
+}
+
+#[actix_web::test]
+async fn test_version_policies_prune_history_except_pinned() {
+    let context =
+        context::Context::mock_with_data_dir(Some("../data-test-version-retention".to_string()))
+            .await;
+    let app = test::init_service(server::app(context.clone())).await;
+
+    let jwt = helpers::register_curve25519(&app, "retention@test.com")
+        .await
+        .jwt;
+
+    let req = test::TestRequest::post()
+        .uri("/api/storage")
+        .cookie(jwt.clone())
+        .set_json(json!({
+            "encrypted_key": "note-key",
+            "encrypted_name": "note",
+            "name_hash": "note-hash",
+            "mime": "text/markdown",
+            "size": 2,
+            "chunks": 1,
+            "editable": true,
+        }))
+        .to_request();
+    let note: AppFile = test::call_and_read_body_json(&app, req).await;
+    upload!(&app, &jwt, &note, b"v1");
+
+    let resp = request!(
+        &app,
+        &jwt,
+        put,
+        format!("/api/storage/{}/version-policy", note.id),
+        policy(30, Some(2))
+    );
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["source"], "file");
+    assert_eq!(body["max_versions"], 2);
+
+    for data in [b"v2", b"v3", b"v4", b"v5"] {
+        edit!(&app, &jwt, &note, data);
+    }
+    assert_eq!(history!(&app, &jwt, &note), vec![4, 3]);
+
+    let resp = request!(
+        &app,
+        &jwt,
+        post,
+        format!("/api/storage/{}/versions/3/pin", note.id)
+    );
+    assert_eq!(resp.status(), StatusCode::OK);
+    let pinned: file_versions::Model = test::read_body_json(resp).await;
+    assert!(pinned.pinned_at.is_some());
+
+    // The pinned version is kept on top of the cap.
+    edit!(&app, &jwt, &note, b"v6");
+    edit!(&app, &jwt, &note, b"v7");
+    assert_eq!(history!(&app, &jwt, &note), vec![6, 5, 3]);
+
+    // Without its own policy the file follows the owner's.
+    let resp = request!(
+        &app,
+        &jwt,
+        delete,
+        format!("/api/storage/{}/version-policy", note.id)
+    );
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+    let resp = request!(
+        &app,
+        &jwt,
+        get,
+        format!("/api/storage/{}/version-policy", note.id)
+    );
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["source"], "default");
+
+    let resp = request!(
+        &app,
+        &jwt,
+        put,
+        "/api/storage/version-policy",
+        policy(0, None)
+    );
+    assert_eq!(resp.status(), StatusCode::OK);
+    let resp = request!(
+        &app,
+        &jwt,
+        get,
+        format!("/api/storage/{}/version-policy", note.id)
+    );
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["source"], "user");
+
+    // The sweep applies the new policy without waiting for another edit.
+    let pruned = hoodik::jobs::run(&context, "prune-file-versions")
+        .await
+        .unwrap();
+    assert_eq!(pruned, 2);
+    assert_eq!(history!(&app, &jwt, &note), vec![3]);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/storage/{}/versions/3", note.id))
+        .cookie(jwt.clone())
+        .to_request();
+    assert_eq!(test::call_and_read_body(&app, req).await.to_vec(), b"v3");
+
+    // Unpinned, the last of the history goes with the next sweep.
+    let resp = request!(
+        &app,
+        &jwt,
+        delete,
+        format!("/api/storage/{}/versions/3/pin", note.id)
+    );
+    assert_eq!(resp.status(), StatusCode::OK);
+    let pruned = hoodik::jobs::run(&context, "prune-file-versions")
+        .await
+        .unwrap();
+    assert_eq!(pruned, 1);
+    assert!(history!(&app, &jwt, &note).is_empty());
+
+    context.config.app.cleanup();
+}
+
+#[actix_web::test]
+async fn test_version_policies_are_validated_and_owner_only() {
+    let context = context::Context::mock_with_data_dir(Some(
+        "../data-test-version-retention-owner".to_string(),
+    ))
+    .await;
+    let app = test::init_service(server::app(context.clone())).await;
+
+    let owner = helpers::register_curve25519(&app, "retention-owner@test.com")
+        .await
+        .jwt;
+    let other = helpers::register_curve25519(&app, "retention-other@test.com")
+        .await
+        .jwt;
+
+    let resp = request!(&app, &owner, get, "/api/storage/version-policy");
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["source"], "default");
+
+    for invalid in [
+        policy(-1, None),
+        policy(7, Some(0)),
+        json!({ "keep_all_days": 7 }),
+    ] {
+        let resp = request!(&app, &owner, put, "/api/storage/version-policy", invalid);
+        assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    let req = test::TestRequest::post()
+        .uri("/api/storage")
+        .cookie(owner.clone())
+        .set_json(json!({
+            "encrypted_key": "note-key",
+            "encrypted_name": "note",
+            "name_hash": "note-hash",
+            "mime": "text/markdown",
+            "size": 2,
+            "chunks": 1,
+            "editable": true,
+        }))
+        .to_request();
+    let note: AppFile = test::call_and_read_body_json(&app, req).await;
+    upload!(&app, &owner, &note, b"v1");
+
+    let resp = request!(
+        &app,
+        &other,
+        put,
+        format!("/api/storage/{}/version-policy", note.id),
+        policy(1, None)
+    );
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    // The active version isn't in the history, there is nothing to pin.
+    let resp = request!(
+        &app,
+        &owner,
+        post,
+        format!("/api/storage/{}/versions/1/pin", note.id)
+    );
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    context.config.app.cleanup();
+}
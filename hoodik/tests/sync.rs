@@ -0,0 +1,185 @@
+//! `hoodik-sync` passes against a running instance, over real HTTP.
+
+#[path = "./helpers.rs"]
+mod helpers;
+
+use std::{net::TcpListener, path::Path};
+
+use actix_web::{test, HttpServer};
+use client::Session;
+use hoodik::server;
+use reqwest::Method;
+use sync::{Report, Syncer};
+
+fn write(path: &Path, content: &[u8]) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, content).unwrap();
+}
+
+async fn read(session: &Session, path: &str) -> Vec<u8> {
+    let entry = session.find(path).await.unwrap().unwrap();
+    let mut content = vec![];
+    for index in 0..entry.chunks {
+        content.extend(session.read_chunk(&entry, index).await.unwrap());
+    }
+
+    content
+}
+
+#[actix_web::test]
+async fn mirrors_a_directory_both_ways() {
+    let context = context::Context::mock_with_data_dir(Some("../data-test-sync".to_string())).await;
+    let app = test::init_service(server::app(context.clone())).await;
+    helpers::register_curve25519(&app, "sync@test.com").await;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let server_context = context.clone();
+    let server = HttpServer::new(move || server::app(server_context.clone()))
+        .listen(listener)
+        .unwrap()
+        .run();
+    actix_web::rt::spawn(server);
+
+    let session =
+        Session::login_with_password(&base_url, "sync@test.com", helpers::LEGACY_PASSWORD, None)
+            .await
+            .unwrap();
+
+    let local = std::env::temp_dir().join(format!("hoodik-sync-{}", entity::Uuid::new_v4()));
+    write(&local.join("notes.txt"), b"first notes");
+    write(&local.join("docs/report.txt"), b"quarterly report");
+    write(&local.join("docs/old.txt"), b"to be deleted");
+
+    let syncer = Syncer::new(session.clone(), &local, "/synced", None)
+        .await
+        .unwrap();
+    let report = syncer.run_once().await.unwrap();
+    assert_eq!(report.uploaded, 3);
+    assert_eq!(
+        read(&session, "/synced/docs/report.txt").await,
+        b"quarterly report"
+    );
+
+    // Nothing changed, nothing to do.
+    assert_eq!(syncer.run_once().await.unwrap(), Report::default());
+
+    // Remote: a new file, a rename and a delete.
+    let docs = session.find_dir("/synced/docs").await.unwrap();
+    let remote_file = local.join("remote.txt");
+    write(&remote_file, b"from the web");
+    session
+        .upload(docs.as_deref(), "web.txt", &remote_file)
+        .await
+        .unwrap();
+    std::fs::remove_file(&remote_file).unwrap();
+    let report_entry = session
+        .find("/synced/docs/report.txt")
+        .await
+        .unwrap()
+        .unwrap();
+    session.rename(&report_entry, "q1.txt").await.unwrap();
+    let old = session.find("/synced/docs/old.txt").await.unwrap().unwrap();
+    session.delete(&old.id).await.unwrap();
+
+    // Local: a rename, which has to keep the remote id.
+    let notes = session.find("/synced/notes.txt").await.unwrap().unwrap();
+    std::fs::rename(local.join("notes.txt"), local.join("docs/notes.txt")).unwrap();
+
+    let report = syncer.run_once().await.unwrap();
+    assert_eq!(report.downloaded, 1);
+    assert_eq!(report.renamed, 2);
+    assert_eq!(report.deleted, 1);
+    assert_eq!(report.failed, 0);
+    assert_eq!(
+        std::fs::read(local.join("docs/web.txt")).unwrap(),
+        b"from the web"
+    );
+    assert_eq!(
+        std::fs::read(local.join("docs/q1.txt")).unwrap(),
+        b"quarterly report"
+    );
+    assert!(!local.join("docs/report.txt").exists());
+    assert!(!local.join("docs/old.txt").exists());
+    let moved = session
+        .find("/synced/docs/notes.txt")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(moved.id, notes.id);
+    assert!(session.find("/synced/notes.txt").await.unwrap().is_none());
+
+    // Editable files keep their history when the content changes here.
+    session
+        .send(
+            Method::PUT,
+            &format!("/api/storage/{}/editable", moved.id),
+            |request| request.json(&serde_json::json!({ "editable": true })),
+        )
+        .await
+        .unwrap();
+    syncer.run_once().await.unwrap();
+    write(&local.join("docs/notes.txt"), b"second notes");
+    let report = syncer.run_once().await.unwrap();
+    assert_eq!(report.uploaded, 1);
+    let edited = session
+        .find("/synced/docs/notes.txt")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(edited.id, notes.id);
+    assert_eq!(
+        read(&session, "/synced/docs/notes.txt").await,
+        b"second notes"
+    );
+    let versions: serde_json::Value = session
+        .send(
+            Method::GET,
+            &format!("/api/storage/{}/versions", edited.id),
+            |r| r,
+        )
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(!versions.as_array().unwrap().is_empty(), "{versions}");
+
+    // Both sides changed: the remote content keeps the name.
+    write(&local.join("docs/notes.txt"), b"local notes");
+    let remote_file = local.join("remote.txt");
+    write(&remote_file, b"remote notes");
+    session.replace(&edited, &remote_file).await.unwrap();
+    std::fs::remove_file(&remote_file).unwrap();
+
+    let report = syncer.run_once().await.unwrap();
+    assert_eq!(report.conflicts, 1);
+    assert_eq!(
+        std::fs::read(local.join("docs/notes.txt")).unwrap(),
+        b"remote notes"
+    );
+    let copies: Vec<_> = std::fs::read_dir(local.join("docs"))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .filter(|name| name.starts_with("notes (conflict "))
+        .collect();
+    assert_eq!(copies.len(), 1, "{copies:?}");
+    assert_eq!(
+        std::fs::read(local.join("docs").join(&copies[0])).unwrap(),
+        b"local notes"
+    );
+    assert_eq!(
+        read(&session, &format!("/synced/docs/{}", copies[0])).await,
+        b"local notes"
+    );
+
+    // A folder deleted here goes remotely too.
+    std::fs::remove_dir_all(local.join("docs")).unwrap();
+    let report = syncer.run_once().await.unwrap();
+    assert_eq!(report.failed, 0);
+    assert!(session.find("/synced/docs").await.unwrap().is_none());
+    assert_eq!(syncer.run_once().await.unwrap(), Report::default());
+
+    std::fs::remove_dir_all(&local).unwrap();
+    context.config.app.cleanup();
+}
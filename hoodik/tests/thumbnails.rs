@@ -53,8 +53,7 @@ async fn test_storage_compact_listing_and_thumbnail_route() {
         .set_json(create_file_with_thumbnail("hash-1"))
         .to_request();
 
-    let file: AppFile =
-        serde_json::from_slice(&test::call_and_read_body(&app, req).await).unwrap();
+    let file: AppFile = serde_json::from_slice(&test::call_and_read_body(&app, req).await).unwrap();
     assert!(file.has_thumbnail);
 
     // Without `compact` the listing ships full rows — the compatible
@@ -176,8 +175,7 @@ async fn test_links_compact_listing_and_metadata_blob() {
         .cookie(jwt.clone())
         .set_json(create_file_with_thumbnail("hash-2"))
         .to_request();
-    let file: AppFile =
-        serde_json::from_slice(&test::call_and_read_body(&app, req).await).unwrap();
+    let file: AppFile = serde_json::from_slice(&test::call_and_read_body(&app, req).await).unwrap();
 
     let signature =
         cryptfns::rsa::private::sign(file.id.to_string().as_str(), &owner.rsa_private).unwrap();
@@ -192,14 +190,16 @@ async fn test_links_compact_listing_and_metadata_blob() {
         encrypted_thumbnail: Some(THUMBNAIL.to_string()),
         encrypted_file_key: Some("encrypted-file-key".to_string()),
         expires_at: None,
+        max_downloads: None,
+        password: None,
+        files: None,
     };
     let req = test::TestRequest::post()
         .uri("/api/links")
         .cookie(jwt.clone())
         .set_json(create_link)
         .to_request();
-    let link: AppLink =
-        serde_json::from_slice(&test::call_and_read_body(&app, req).await).unwrap();
+    let link: AppLink = serde_json::from_slice(&test::call_and_read_body(&app, req).await).unwrap();
     assert!(link.has_thumbnail);
 
     // Without `compact` the owner's listing ships full rows.
@@ -272,8 +272,7 @@ async fn test_search_compact_withholds_thumbnail_blob() {
         .cookie(jwt.clone())
         .set_json(&payload)
         .to_request();
-    let file: AppFile =
-        serde_json::from_slice(&test::call_and_read_body(&app, req).await).unwrap();
+    let file: AppFile = serde_json::from_slice(&test::call_and_read_body(&app, req).await).unwrap();
     assert!(file.has_thumbnail);
 
     // Default: the blob rides along, as it did before the flag existed.
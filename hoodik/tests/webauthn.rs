@@ -0,0 +1,206 @@
+//! Security keys as a second factor, driven end to end with the software
+//! authenticator from `cryptfns::webauthn::mock`.
+
+#[path = "./helpers.rs"]
+mod helpers;
+
+use actix_web::cookie::Cookie;
+use actix_web::{http::StatusCode, test};
+use cryptfns::webauthn::{
+    mock::{Assertion, Authenticator},
+    RelyingParty,
+};
+use hoodik::server;
+use serde_json::{json, Value};
+
+const EMAIL: &str = "webauthn@example.com";
+
+fn challenge(options: &Value) -> Vec<u8> {
+    cryptfns::base64::decode_url(options["public_key"]["challenge"].as_str().unwrap()).unwrap()
+}
+
+fn assertion_json(assertion: &Assertion) -> Value {
+    json!({
+        "credential_id": cryptfns::base64::encode_url(&assertion.credential_id),
+        "client_data_json": cryptfns::base64::encode_url(&assertion.client_data_json),
+        "authenticator_data": cryptfns::base64::encode_url(&assertion.authenticator_data),
+        "signature": cryptfns::base64::encode_url(&assertion.signature),
+    })
+}
+
+async fn enroll(
+    app: &impl helpers::TestApp,
+    jwt: &Cookie<'static>,
+    rp: RelyingParty<'_>,
+    authenticator: &mut Authenticator,
+    name: &str,
+) -> Value {
+    let req = test::TestRequest::post()
+        .uri("/api/auth/webauthn/register/start")
+        .cookie(jwt.clone())
+        .to_request();
+    let resp = test::call_service(app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let options: Value = test::read_body_json(resp).await;
+
+    let (client_data, attestation) = authenticator.register(rp, &challenge(&options));
+    let req = test::TestRequest::post()
+        .uri("/api/auth/webauthn/register/finish")
+        .cookie(jwt.clone())
+        .set_json(json!({
+            "registration_id": options["registration_id"],
+            "name": name,
+            "response": {
+                "client_data_json": cryptfns::base64::encode_url(&client_data),
+                "attestation_object": cryptfns::base64::encode_url(&attestation),
+            },
+        }))
+        .to_request();
+    let resp = test::call_service(app, req).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+
+    test::read_body_json(resp).await
+}
+
+/// Run the OPAQUE half of the login and return the `login/finish` body.
+async fn login_finish(app: &impl helpers::TestApp) -> Value {
+    let password = helpers::LEGACY_PASSWORD.as_bytes();
+    let start = cryptfns::opaque::client_login_start(password).unwrap();
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/login/start")
+        .set_json(json!({ "email": EMAIL, "credential_request": start.message }))
+        .to_request();
+    let body: Value = test::read_body_json(test::call_service(app, req).await).await;
+
+    let finish = cryptfns::opaque::client_login_finish(
+        &start.state,
+        body["credential_response"].as_str().unwrap(),
+        password,
+    )
+    .unwrap();
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/login/finish")
+        .set_json(json!({
+            "login_id": body["login_id"],
+            "credential_finalization": finish.finalization,
+        }))
+        .to_request();
+    let resp = test::call_service(app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let (jwt, _) = helpers::extract_cookies(resp.headers());
+    assert!(jwt.is_none(), "no session before the key answers");
+
+    test::read_body_json(resp).await
+}
+
+#[actix_web::test]
+async fn test_login_asks_for_any_enrolled_key() {
+    let context = context::Context::mock_sqlite().await;
+    let app = test::init_service(server::app(context.clone())).await;
+    let rp = RelyingParty {
+        id: &context.config.auth.webauthn_rp_id,
+        origin: &context.config.auth.webauthn_origin,
+    };
+
+    let account = helpers::register_curve25519(&app, EMAIL).await;
+    let mut first = Authenticator::new();
+    let mut second = Authenticator::new();
+    let enrolled = enroll(&app, &account.jwt, rp, &mut first, "Desk key").await;
+    enroll(&app, &account.jwt, rp, &mut second, "Backup key").await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/auth/webauthn/credentials")
+        .cookie(account.jwt.clone())
+        .to_request();
+    let credentials: Value = test::read_body_json(test::call_service(&app, req).await).await;
+    let credentials = credentials.as_array().unwrap();
+    assert_eq!(credentials.len(), 2);
+    assert_eq!(credentials[0]["name"], "Desk key");
+    assert!(credentials[0].get("public_key").is_none());
+
+    // Either key passes the login.
+    for authenticator in [&mut first, &mut second] {
+        let body = login_finish(&app).await;
+        let login = &body["webauthn"];
+        assert_eq!(
+            login["public_key"]["allowCredentials"]
+                .as_array()
+                .unwrap()
+                .len(),
+            2
+        );
+
+        let assertion = authenticator.assert(rp, &challenge(login));
+        let req = test::TestRequest::post()
+            .uri("/api/auth/login/webauthn")
+            .set_json(
+                json!({ "login_id": login["login_id"], "response": assertion_json(&assertion) }),
+            )
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let (jwt, refresh) = helpers::extract_cookies(resp.headers());
+        assert!(jwt.is_some() && refresh.is_some());
+
+        // The challenge is single-use.
+        let req = test::TestRequest::post()
+            .uri("/api/auth/login/webauthn")
+            .set_json(
+                json!({ "login_id": login["login_id"], "response": assertion_json(&assertion) }),
+            )
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    // A key signing someone else's challenge is refused.
+    let body = login_finish(&app).await;
+    let assertion = first.assert(rp, b"not the challenge");
+    let req = test::TestRequest::post()
+        .uri("/api/auth/login/webauthn")
+        .set_json(json!({ "login_id": body["webauthn"]["login_id"], "response": assertion_json(&assertion) }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    // Removing a key takes another factor, a session is not enough.
+    let delete_uri = format!(
+        "/api/auth/webauthn/credentials/{}/delete",
+        enrolled["id"].as_str().unwrap()
+    );
+    let req = test::TestRequest::post()
+        .uri(&delete_uri)
+        .cookie(account.jwt.clone())
+        .set_json(json!({}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/webauthn/assertion/start")
+        .cookie(account.jwt.clone())
+        .to_request();
+    let confirm: Value = test::read_body_json(test::call_service(&app, req).await).await;
+    let assertion = second.assert(rp, &challenge(&confirm));
+    let req = test::TestRequest::post()
+        .uri(&delete_uri)
+        .cookie(account.jwt.clone())
+        .set_json(json!({
+            "challenge_id": confirm["challenge_id"],
+            "response": assertion_json(&assertion),
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+    let body = login_finish(&app).await;
+    assert_eq!(
+        body["webauthn"]["public_key"]["allowCredentials"]
+            .as_array()
+            .unwrap()
+            .len(),
+        1
+    );
+}
@@ -3,6 +3,8 @@ use error::AppResult;
 use fs::{prelude::Filename, IntoFilename};
 use serde::{Deserialize, Serialize};
 
+use super::link_file::AppLinkFile;
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct AppLink {
     pub id: Uuid,
@@ -63,6 +65,18 @@ pub struct AppLink {
     /// will periodically empty out the expired links of all the
     /// file metadata and encrypted file key.
     pub expires_at: Option<i64>,
+    /// Completed downloads after which the link stops serving the file.
+    pub max_downloads: Option<i32>,
+    /// Whether a recipient has to unlock the link with its password before
+    /// the metadata keys or any ciphertext are handed out.
+    #[serde(default)]
+    pub has_password: bool,
+    #[serde(skip)]
+    pub(crate) password_hash: Option<String>,
+    /// Everything under a linked folder, with names and keys encrypted
+    /// with the link key. Only the metadata route loads it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub files: Option<Vec<AppLinkFile>>,
 }
 
 impl AppLink {
@@ -74,6 +88,74 @@ impl AppLink {
             .map(|expires_at| expires_at < now)
             .unwrap_or(false)
     }
+
+    /// Whether the link shares a whole folder rather than a single file.
+    pub fn is_folder(&self) -> bool {
+        self.file_mime == "dir"
+    }
+
+    /// Whether the link has served all the downloads it was created for.
+    pub fn is_exhausted(&self) -> bool {
+        self.max_downloads
+            .is_some_and(|max_downloads| self.downloads >= max_downloads)
+    }
+
+    /// Token proving the link's password was given, valid until `expires_at`.
+    /// It is an HMAC under a key derived from `secret`, so only the server
+    /// can make one, and it covers the password hash, so a link without a
+    /// password has none and a changed password invalidates every token
+    /// given out.
+    pub(crate) fn access_token(&self, secret: &str, expires_at: i64) -> Option<String> {
+        let message = self.access_message(expires_at)?;
+        let tag = cryptfns::hmac::sign(&access_key(secret), message.as_bytes());
+
+        Some(format!("{expires_at}.{tag}"))
+    }
+
+    /// Whether the link may be used with the given access token: always when
+    /// it has no password, otherwise only with a token that is still valid.
+    pub(crate) fn is_unlocked(&self, secret: &str, token: Option<&str>, now: i64) -> bool {
+        if self.password_hash.is_none() {
+            return true;
+        }
+
+        let Some((expires_at, tag)) = token.and_then(|t| t.split_once('.')) else {
+            return false;
+        };
+        let Ok(expires_at) = expires_at.parse::<i64>() else {
+            return false;
+        };
+
+        expires_at >= now
+            && self.access_message(expires_at).is_some_and(|message| {
+                cryptfns::hmac::verify(&access_key(secret), message.as_bytes(), tag)
+            })
+    }
+
+    fn access_message(&self, expires_at: i64) -> Option<String> {
+        let hash = self.password_hash.as_deref()?;
+
+        Some(format!("{}\0{hash}\0{expires_at}", self.id))
+    }
+
+    /// The metadata a recipient who has not unlocked the link gets: enough
+    /// to ask for the password, none of the encrypted name, thumbnail or
+    /// file key.
+    pub(crate) fn locked(self) -> Self {
+        Self {
+            encrypted_name: String::new(),
+            encrypted_thumbnail: None,
+            encrypted_file_key: None,
+            files: None,
+            ..self
+        }
+    }
+}
+
+/// Key of the link access tokens, derived from the server secret so it is
+/// good for nothing else.
+fn access_key(secret: &str) -> Vec<u8> {
+    cryptfns::hmac::sign(secret.as_bytes(), b"hoodik link access token").into_bytes()
 }
 
 impl FromQueryResult for AppLink {
@@ -104,6 +186,10 @@ impl FromQueryResult for AppLink {
             file_active_version: file.active_version,
             file_editable: file.editable,
             expires_at: link.expires_at,
+            max_downloads: link.max_downloads,
+            has_password: link.password_hash.is_some(),
+            password_hash: link.password_hash,
+            files: None,
             owner_id: user.id,
             owner_email: user.email,
             owner_pubkey: user.pubkey,
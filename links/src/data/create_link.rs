@@ -1,6 +1,6 @@
 use ::error::AppResult;
 use chrono::Utc;
-use entity::{links::ActiveModel, ActiveValue, Uuid};
+use entity::{link_files, links::ActiveModel, ActiveValue, Uuid};
 use serde::{Deserialize, Serialize};
 use validr::*;
 
@@ -21,11 +21,25 @@ pub struct CreateLink {
     /// If the file has a thumbnail it is encrypted with the link key.
     pub encrypted_thumbnail: Option<String>,
 
-    /// AES key for the file encrypted with a link AES key.
+    /// AES key for the file encrypted with a link AES key. A folder has no
+    /// content of its own, so a folder link leaves it out.
     pub encrypted_file_key: Option<String>,
 
+    /// Every file and directory under a linked folder, each with its name
+    /// and key encrypted with the link key. Required when `file_id` is a
+    /// folder, refused otherwise.
+    pub files: Option<Vec<CreateLinkFile>>,
+
     /// Optional date when the link will expire.
     pub expires_at: Option<i64>,
+
+    /// Optional number of completed downloads after which the link stops
+    /// serving the file.
+    pub max_downloads: Option<i32>,
+
+    /// Optional password a recipient has to give before downloading, on top
+    /// of the key in the URL fragment. Only its bcrypt hash is stored.
+    pub password: Option<String>,
 }
 
 impl Validation for CreateLink {
@@ -34,13 +48,67 @@ impl Validation for CreateLink {
             rule_required!(file_id),
             rule_required!(encrypted_name),
             rule_required!(encrypted_link_key),
-            rule_required!(encrypted_file_key),
+            Rule::new("encrypted_file_key", |obj: &Self, error| {
+                if obj.files.is_none() && obj.encrypted_file_key.is_none() {
+                    error.add("required")
+                }
+            }),
+            Rule::new("max_downloads", |obj: &Self, error| {
+                if obj.max_downloads.is_some_and(|v| v < 1) {
+                    error.add("min:1")
+                }
+            }),
+            Rule::new("password", |obj: &Self, error| {
+                if obj.password.as_deref().is_some_and(str::is_empty) {
+                    error.add("required")
+                }
+            }),
         ]
     }
 }
 
+/// A descendant of the folder a link is created for.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateLinkFile {
+    pub file_id: Option<String>,
+
+    /// Name of the file encrypted with the link key.
+    pub encrypted_name: Option<String>,
+
+    /// AES key for the file encrypted with the link key, absent for
+    /// directories.
+    pub encrypted_file_key: Option<String>,
+}
+
+impl Validation for CreateLinkFile {
+    fn rules(&self) -> Vec<Rule<Self>> {
+        vec![rule_required!(file_id), rule_required!(encrypted_name)]
+    }
+}
+
+impl CreateLinkFile {
+    pub fn into_active_model(self, link_id: Uuid) -> AppResult<link_files::ActiveModel> {
+        let data = self.validate()?;
+        let file_id = Uuid::parse_str(data.file_id.as_deref().unwrap_or_default())?;
+
+        Ok(link_files::ActiveModel {
+            id: ActiveValue::Set(Uuid::new_v4()),
+            link_id: ActiveValue::Set(link_id),
+            file_id: ActiveValue::Set(file_id),
+            encrypted_name: ActiveValue::Set(data.encrypted_name.unwrap()),
+            encrypted_file_key: ActiveValue::Set(data.encrypted_file_key),
+        })
+    }
+}
+
 impl CreateLink {
-    pub fn into_active_model(self, user_id: Uuid) -> AppResult<(ActiveModel, String, Uuid)> {
+    /// Split into the link row, the signature over the file id, the file
+    /// id and, for a folder link, the descendants to store with it.
+    #[allow(clippy::type_complexity)]
+    pub fn into_active_model(
+        self,
+        user_id: Uuid,
+    ) -> AppResult<(ActiveModel, String, Uuid, Option<Vec<CreateLinkFile>>)> {
         let data = self.validate()?;
 
         let file_id = match data.file_id.as_deref() {
@@ -63,9 +131,12 @@ impl CreateLink {
                 encrypted_file_key: ActiveValue::Set(data.encrypted_file_key),
                 created_at: ActiveValue::Set(Utc::now().timestamp()),
                 expires_at: ActiveValue::Set(data.expires_at),
+                max_downloads: ActiveValue::Set(data.max_downloads),
+                password_hash: ActiveValue::Set(data.password.map(util::password::hash)),
             },
             data.signature.unwrap(),
             file_id,
+            data.files,
         ))
     }
 }
@@ -0,0 +1,78 @@
+use entity::{DbErr, FromQueryResult, QueryResult, Uuid};
+use error::AppResult;
+use fs::{prelude::Filename, IntoFilename};
+use serde::{Deserialize, Serialize};
+
+use super::app_link::AppLink;
+
+/// A file or directory reachable through a folder link. The listing is
+/// flat; `parent_id` lets the recipient rebuild the tree under the linked
+/// folder.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AppLinkFile {
+    pub id: Uuid,
+    pub parent_id: Option<Uuid>,
+    pub mime: String,
+    pub size: Option<i64>,
+    pub chunks: Option<i64>,
+    /// Name of the file encrypted with the link key, as it was named when
+    /// the link was created.
+    pub encrypted_name: String,
+    /// The file's content key encrypted with the link key, absent for
+    /// directories.
+    pub encrypted_file_key: Option<String>,
+    pub file_modified_at: i64,
+    pub cipher: String,
+    pub active_version: i32,
+    pub editable: bool,
+}
+
+impl AppLinkFile {
+    pub fn is_dir(&self) -> bool {
+        self.mime == "dir"
+    }
+}
+
+impl FromQueryResult for AppLinkFile {
+    fn from_query_result(res: &QueryResult, pre: &str) -> Result<Self, DbErr> {
+        Ok(Self {
+            id: res.try_get(pre, "id")?,
+            parent_id: res.try_get(pre, "parent_id")?,
+            mime: res.try_get(pre, "mime")?,
+            size: res.try_get(pre, "size")?,
+            chunks: res.try_get(pre, "chunks")?,
+            encrypted_name: res.try_get(pre, "encrypted_name")?,
+            encrypted_file_key: res.try_get(pre, "encrypted_file_key")?,
+            file_modified_at: res.try_get(pre, "file_modified_at")?,
+            cipher: res.try_get(pre, "cipher")?,
+            active_version: res.try_get(pre, "active_version")?,
+            editable: res.try_get(pre, "editable")?,
+        })
+    }
+}
+
+/// The file a plain link points at, so downloads treat it the same way as
+/// a file under a linked folder.
+impl From<&AppLink> for AppLinkFile {
+    fn from(link: &AppLink) -> Self {
+        Self {
+            id: link.file_id,
+            parent_id: None,
+            mime: link.file_mime.clone(),
+            size: link.file_size,
+            chunks: None,
+            encrypted_name: link.encrypted_name.clone(),
+            encrypted_file_key: link.encrypted_file_key.clone(),
+            file_modified_at: link.file_modified_at,
+            cipher: link.file_cipher.clone(),
+            active_version: link.file_active_version,
+            editable: link.file_editable,
+        }
+    }
+}
+
+impl IntoFilename for AppLinkFile {
+    fn filename(&self) -> AppResult<Filename> {
+        Ok(Filename::new(self.id).with_timestamp(self.file_modified_at))
+    }
+}
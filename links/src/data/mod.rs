@@ -1,4 +1,6 @@
 pub mod app_link;
 pub mod create_link;
 pub mod find;
+pub mod link_file;
+pub mod unlock;
 pub mod update;
@@ -0,0 +1,34 @@
+use ::error::AppResult;
+use serde::{Deserialize, Serialize};
+use validr::*;
+
+/// How long an access token from unlocking a link stays valid, long enough
+/// to finish a large download.
+pub const ACCESS_TOKEN_TTL_SECONDS: i64 = 6 * 60 * 60;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Unlock {
+    pub password: Option<String>,
+}
+
+impl Validation for Unlock {
+    fn rules(&self) -> Vec<Rule<Self>> {
+        vec![rule_required!(password)]
+    }
+}
+
+impl Unlock {
+    pub fn into_password(self) -> AppResult<String> {
+        let data = self.validate()?;
+
+        Ok(data.password.unwrap_or_default())
+    }
+}
+
+/// Sent as the `access_token` query parameter on the link's metadata and
+/// download requests.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Unlocked {
+    pub access_token: String,
+    pub expires_at: i64,
+}
@@ -1,15 +1,49 @@
-use std::str::FromStr;
+use std::{collections::HashSet, str::FromStr};
 
 use context::Context;
 use entity::{
-    files,
+    files, link_config, link_files,
     links::{self},
-    user_files, users, ColumnTrait, ConnectionTrait, EntityTrait, Expr, IntoCondition, JoinType,
-    QueryFilter, QuerySelect, RelationTrait, Statement, Uuid,
+    user_files, users, ActiveValue, ColumnTrait, ConnectionTrait, EntityTrait, Expr,
+    FromQueryResult, IntoCondition, JoinType, OnConflict, QueryFilter, QuerySelect, RelationTrait,
+    Statement, TransactionTrait, Uuid,
 };
 use error::{AppResult, Error};
 
-use crate::data::{app_link::AppLink, create_link::CreateLink};
+use crate::data::{app_link::AppLink, create_link::CreateLink, link_file::AppLinkFile};
+
+/// Most files a single folder link may carry, the same cap a folder share
+/// has.
+const LINK_FILES_CAP: usize = 5000;
+
+/// Files of a folder link that are still under the linked folder and not in
+/// the trash. A file moved out of the folder after the link was created
+/// drops out of the link with it.
+fn link_files_sql() -> String {
+    format!("{} {LINK_FILES_SELECT}", files::FILE_TREE_CTE)
+}
+
+/// What [`link_files_sql`] selects from the folder's `file_tree`.
+const LINK_FILES_SELECT: &str = r#"
+    SELECT
+        files.id AS id,
+        files.file_id AS parent_id,
+        files.mime AS mime,
+        files.size AS size,
+        files.chunks AS chunks,
+        link_files.encrypted_name AS encrypted_name,
+        link_files.encrypted_file_key AS encrypted_file_key,
+        files.created_at AS file_modified_at,
+        files.cipher AS cipher,
+        files.active_version AS active_version,
+        files.editable AS editable
+    FROM link_files
+    JOIN file_tree ON file_tree.id = link_files.file_id
+    JOIN files ON files.id = link_files.file_id
+    WHERE link_files.link_id = $2
+      AND files.id <> $1
+      AND files.deleted_at IS NULL
+"#;
 
 pub(crate) struct Repository<'ctx> {
     context: &'ctx Context,
@@ -20,16 +54,50 @@ impl<'ctx> Repository<'ctx> {
         Self { context }
     }
 
-    /// Create a shareable link for a file.
+    /// Read the secret link access tokens are keyed with, generating and
+    /// persisting it on first use. Every replica reads the same one, so a
+    /// token given out by one is accepted by the others and after a restart.
+    pub(crate) async fn access_secret(&self) -> AppResult<String> {
+        if let Some(row) = link_config::Entity::find_by_id(link_config::Model::SINGLETON_ID)
+            .one(&self.context.db)
+            .await?
+        {
+            return Ok(row.access_secret);
+        }
+
+        let access_secret = cryptfns::hex::encode(cryptfns::rand::random::<[u8; 32]>());
+        link_config::Entity::insert(link_config::ActiveModel {
+            id: ActiveValue::Set(link_config::Model::SINGLETON_ID),
+            access_secret: ActiveValue::Set(access_secret),
+        })
+        .on_conflict(
+            OnConflict::column(link_config::Column::Id)
+                .do_nothing()
+                .to_owned(),
+        )
+        .exec_without_returning(&self.context.db)
+        .await?;
+
+        // Re-read: a racing request may have inserted first, and both must
+        // end up with the same persisted secret.
+        link_config::Entity::find_by_id(link_config::Model::SINGLETON_ID)
+            .one(&self.context.db)
+            .await?
+            .map(|row| row.access_secret)
+            .ok_or_else(|| Error::InternalError("link_access_secret_missing".to_string()))
+    }
+
+    /// Create a shareable link for a file or a folder.
     /// Before creating:
     /// - verify the passed signature is valid.
     /// - verify the user is the owner of the file.
+    /// - for a folder, verify every file sent along is under it.
     pub(crate) async fn create(
         &self,
         create_link: CreateLink,
         user: &entity::users::Model,
     ) -> AppResult<AppLink> {
-        let (data, signature, file_id) = create_link.into_active_model(user.id)?;
+        let (data, signature, file_id, files) = create_link.into_active_model(user.id)?;
 
         cryptfns::identity::KeyType::from_str(&user.key_type)?.verify(
             file_id.to_string().as_str(),
@@ -37,7 +105,7 @@ impl<'ctx> Repository<'ctx> {
             &user.pubkey,
         )?;
 
-        let (_file, user_file) = self.get_file_with_owner(file_id).await?;
+        let (file, user_file) = self.get_file_with_owner(file_id).await?;
 
         if user_file.user_id != user.id {
             return Err(Error::Forbidden("cannot_share_not_owner".to_string()));
@@ -45,13 +113,85 @@ impl<'ctx> Repository<'ctx> {
 
         let id = entity::active_value_to_uuid(data.id.clone()).ok_or(Error::as_wrong_id("link"))?;
 
+        let files = match (file.mime.as_str() == "dir", files) {
+            (true, Some(files)) => {
+                let subtree: HashSet<Uuid> = files::subtree_ids(&self.context.db, file_id)
+                    .await?
+                    .into_iter()
+                    .collect();
+
+                if files.len() > LINK_FILES_CAP {
+                    return Err(Error::BadRequest("link_files_too_many".to_string()));
+                }
+
+                let mut rows = Vec::with_capacity(files.len());
+                for link_file in files {
+                    let row = link_file.into_active_model(id)?;
+                    let child_id = entity::active_value_to_uuid(row.file_id.clone())
+                        .ok_or(Error::as_wrong_id("file"))?;
+
+                    if child_id == file_id || !subtree.contains(&child_id) {
+                        return Err(Error::BadRequest(format!(
+                            "link_file_not_in_folder:{child_id}"
+                        )));
+                    }
+
+                    rows.push(row);
+                }
+
+                rows
+            }
+            (true, None) => return Err(Error::BadRequest("link_files_required".to_string())),
+            (false, Some(_)) => return Err(Error::BadRequest("link_not_folder".to_string())),
+            (false, None) => vec![],
+        };
+
+        let tx = self.context.db.begin().await?;
+
         links::Entity::insert(data)
-            .exec_without_returning(&self.context.db)
+            .exec_without_returning(&tx)
             .await?;
 
+        if !files.is_empty() {
+            link_files::Entity::insert_many(files)
+                .exec_without_returning(&tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+
         self.get_by_id(id).await
     }
 
+    /// Everything reachable through a folder link.
+    pub(crate) async fn files(&self, link: &AppLink) -> AppResult<Vec<AppLinkFile>> {
+        let files = AppLinkFile::find_by_statement(Statement::from_sql_and_values(
+            self.context.db.get_database_backend(),
+            link_files_sql().as_str(),
+            [link.file_id.into(), link.id.into()],
+        ))
+        .all(&self.context.db)
+        .await?;
+
+        Ok(files)
+    }
+
+    /// A single file reachable through a folder link. Anything else, even a
+    /// file that was under the folder when the link was created, is not
+    /// found.
+    pub(crate) async fn file(&self, link: &AppLink, file_id: Uuid) -> AppResult<AppLinkFile> {
+        let sql = format!("{} AND files.id = $3", link_files_sql());
+
+        AppLinkFile::find_by_statement(Statement::from_sql_and_values(
+            self.context.db.get_database_backend(),
+            sql.as_str(),
+            [link.file_id.into(), link.id.into(), file_id.into()],
+        ))
+        .one(&self.context.db)
+        .await?
+        .ok_or_else(|| Error::NotFound(format!("file_not_found:{file_id}")))
+    }
+
     /// Get a link by id and verify it is not expired.
     pub(crate) async fn get(&self, id: Uuid) -> AppResult<AppLink> {
         let app_link = self.get_by_id(id).await?;
@@ -103,19 +243,25 @@ impl<'ctx> Repository<'ctx> {
         self.get_by_id(id).await
     }
 
-    /// Increment file downloads counter.
-    pub(crate) async fn increment_downloads(&self, id: Uuid) -> AppResult<()> {
-        self.context
+    /// Increment file downloads counter, unless the link has already served
+    /// its maximum downloads. The check and the increment are one statement,
+    /// so transfers finishing at the same time cannot both take the last
+    /// download. Returns whether the download was counted.
+    pub(crate) async fn increment_downloads(&self, id: Uuid) -> AppResult<bool> {
+        let result = self
+            .context
             .db
             .execute(Statement::from_sql_and_values(
                 self.context.db.get_database_backend(),
                 r"UPDATE links
                     SET downloads = downloads + 1
-                    WHERE id = $1;",
+                    WHERE id = $1
+                      AND (max_downloads IS NULL OR downloads < max_downloads);",
                 [id.into()],
             ))
             .await?;
-        Ok(())
+
+        Ok(result.rows_affected() > 0)
     }
 
     /// Get all the links for a user.
@@ -168,6 +314,7 @@ impl<'ctx> Repository<'ctx> {
             .filter(links::Column::UserId.eq(user_id))
             .join(JoinType::InnerJoin, links::Relation::Users.def())
             .join(JoinType::InnerJoin, links::Relation::Files.def())
+            .filter(files::Column::DeletedAt.is_null())
             .into_model::<AppLink>()
             .all(&self.context.db)
             .await?;
@@ -187,6 +334,7 @@ impl<'ctx> Repository<'ctx> {
             .filter(links::Column::Id.eq(id))
             .join(JoinType::InnerJoin, links::Relation::Users.def())
             .join(JoinType::InnerJoin, links::Relation::Files.def())
+            .filter(files::Column::DeletedAt.is_null())
             .into_model::<AppLink>()
             .one(&self.context.db)
             .await?
@@ -200,6 +348,7 @@ impl<'ctx> Repository<'ctx> {
     async fn get_file_with_owner(&self, id: Uuid) -> AppResult<(files::Model, user_files::Model)> {
         let (file, user_file) = files::Entity::find()
             .filter(files::Column::Id.eq(id))
+            .filter(files::Column::DeletedAt.is_null())
             .join(
                 JoinType::InnerJoin,
                 files::Relation::UserFiles
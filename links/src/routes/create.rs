@@ -1,5 +1,5 @@
 use actix_web::{route, web, HttpResponse};
-use auth::data::authenticated::Authenticated;
+use auth::data::claims::Claims;
 use context::Context;
 use error::AppResult;
 
@@ -11,15 +11,22 @@ use crate::{data::create_link::CreateLink, repository::Repository};
 #[route("/api/links", method = "POST")]
 pub(crate) async fn create(
     context: web::Data<Context>,
-    authenticated: Authenticated,
+    claims: Claims,
     create_link: web::Json<CreateLink>,
 ) -> AppResult<HttpResponse> {
     let context = context.into_inner();
     let repository = Repository::new(&context);
+    let create_link = create_link.into_inner();
 
-    let app_link = repository
-        .create(create_link.into_inner(), &authenticated.user)
+    claims
+        .require_folder(
+            &context,
+            entity::option_string_to_uuid(create_link.file_id.clone()),
+        )
         .await?;
+    let user = claims.user(&context).await?;
+
+    let app_link = repository.create(create_link, &user).await?;
 
     Ok(HttpResponse::Created().json(app_link))
 }
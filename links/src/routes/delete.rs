@@ -1,5 +1,5 @@
 use actix_web::{route, web, HttpRequest, HttpResponse};
-use auth::data::authenticated::Authenticated;
+use auth::data::claims::Claims;
 use context::Context;
 use entity::Uuid;
 use error::AppResult;
@@ -13,14 +13,14 @@ use crate::repository::Repository;
 pub(crate) async fn delete(
     req: HttpRequest,
     context: web::Data<Context>,
-    authenticated: Authenticated,
+    claims: Claims,
 ) -> AppResult<HttpResponse> {
     let context = context.into_inner();
     let repository = Repository::new(&context);
 
     let id: Uuid = util::actix::path_var(&req, "link_id")?;
 
-    repository.delete(id, authenticated.user.id).await?;
+    repository.delete(id, claims.sub).await?;
 
     Ok(HttpResponse::NoContent().finish())
 }
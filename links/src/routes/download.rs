@@ -1,15 +1,14 @@
 use actix_web::http::header::ContentEncoding;
-use actix_web::{
-    route,
-    web,
-    HttpRequest, HttpResponse,
-};
+use actix_web::{route, web, HttpRequest, HttpResponse};
 use context::Context;
-use entity::Uuid;
+use entity::{file_chunk_refs, Uuid};
 use error::{AppResult, Error};
 use fs::prelude::*;
 
-use crate::repository::Repository;
+use crate::{
+    data::{app_link::AppLink, link_file::AppLinkFile},
+    repository::Repository,
+};
 
 /// Download file from a shareable link (raw ciphertext only).
 ///
@@ -18,8 +17,17 @@ use crate::repository::Repository;
 /// the file key via the link metadata + link_key (from URL fragment) and
 /// decrypt client-side. This closes the last E2EE exception for public links.
 ///
+/// A link with a password only streams to callers holding an access token
+/// from [`crate::routes::unlock::unlock`], and a link that has served its
+/// maximum downloads streams no more.
+///
+/// A folder link streams any file that was linked with the folder and is
+/// still under it, picked with `file_id`.
+///
 /// Request:
 ///  - Query: chunk: i64 - if omitted, every chunk is streamed back to back
+///  - Query: access_token: String - required when the link has a password
+///  - Query: file_id: Uuid - required when the link is for a folder
 ///
 /// Response: raw ciphertext bytes (Content-Type and generic disposition)
 #[route("/api/links/{link_id}", method = "POST")]
@@ -33,46 +41,52 @@ pub(crate) async fn download(
     let chunk = util::actix::query_var::<i64>(&req, "chunk").ok();
 
     let link = repository.get(link_id).await?;
+    ensure_usable(&req, &repository, &link).await?;
 
-    if link.is_expired() {
-        return Err(Error::Unauthorized("link_expired".to_string()));
-    }
+    let file = linked_file(&req, &repository, &link).await?;
 
     // Count one download per completed transfer, not per chunk request. The
     // client fetches the file as N chunks (`?chunk=i`); only the final index
     // closes a download, and a whole-file request (`chunk` omitted) is a single
     // one. A preview or abandoned transfer that never reaches the last chunk
-    // correctly does not count.
-    let last_chunk = link
-        .file_size
+    // correctly does not count. On a link with a maximum, the final chunk is
+    // refused once another transfer took the last download. Every file
+    // fetched through a folder link counts as a download of its own.
+    let last_chunk = file
+        .size
         .map(|size| (size as u64).div_ceil(fs::MAX_CHUNK_SIZE_BYTES).max(1) - 1)
         .unwrap_or(0);
-    if chunk.is_none_or(|i| i as u64 == last_chunk) {
-        repository.increment_downloads(link.id).await?;
+    if chunk.is_none_or(|i| i as u64 == last_chunk)
+        && !repository.increment_downloads(link.id).await?
+    {
+        return Err(Error::Unauthorized("link_exhausted".to_string()));
     }
 
     let fs = Fs::new(&context.config);
-    let streamer = if link.file_editable {
-        fs.stream_v(&link, link.file_active_version, chunk).await?
+    let streamer = if file.editable {
+        let name = file_chunk_refs::filename(&context.db, file.id, &file).await?;
+        fs.stream_v(&name, file.active_version, chunk).await?
     } else {
-        fs.stream(&link, chunk).await?
+        fs.stream(&file, chunk).await?
     };
 
     // The name lives in the link's encrypted metadata; the client decrypts it
     // and renames the saved blob itself, so a generic disposition is enough.
     Ok(HttpResponse::Ok()
         .insert_header(ContentEncoding::Identity)
-        .insert_header(("Content-Type", link.file_mime))
-        .insert_header((
-            "Content-Disposition",
-            "attachment; filename=\"download\"",
-        ))
+        .insert_header(("Content-Type", file.mime))
+        .insert_header(("Content-Disposition", "attachment; filename=\"download\""))
         .streaming(streamer.stream()))
 }
 
-/// Size + mime for the linked file. The encrypted file name is never resolved
+/// Size + mime for the linked file, or for `file_id` under a linked folder. The encrypted file name is never resolved
 /// server-side — the client decrypts the link metadata with the fragment key
-/// and applies the real name itself.
+/// and applies the real name itself. Held to the same checks as
+/// [`download`], so it tells nothing about a link that would not stream.
+///
+/// Request:
+///  - Query: access_token: String - required when the link has a password
+///  - Query: file_id: Uuid - required when the link is for a folder
 ///
 /// Response: No Content
 #[route("/api/links/{link_id}", method = "HEAD")]
@@ -82,14 +96,64 @@ pub(crate) async fn head(req: HttpRequest, context: web::Data<Context>) -> AppRe
     let repository = Repository::new(&context);
 
     let link = repository.get(link_id).await?;
+    ensure_usable(&req, &repository, &link).await?;
 
-    if link.is_expired() {
-        return Err(Error::Unauthorized("link_expired".to_string()));
-    }
+    let file = linked_file(&req, &repository, &link).await?;
 
     Ok(HttpResponse::NoContent()
-        .insert_header(("Content-Type", link.file_mime))
-        .insert_header(("Content-Length", link.file_size.unwrap_or(0).to_string()))
+        .insert_header(("Content-Type", file.mime))
+        .insert_header(("Content-Length", file.size.unwrap_or(0).to_string()))
         .insert_header(("Content-Disposition", "attachment; filename=\"download\""))
         .finish())
 }
+
+/// Refuse a link that has expired, is locked without the `access_token`
+/// query parameter unlocking it, or has served its maximum downloads.
+async fn ensure_usable(
+    req: &HttpRequest,
+    repository: &Repository<'_>,
+    link: &AppLink,
+) -> AppResult<()> {
+    let access_token = util::actix::query_var::<String>(req, "access_token").ok();
+
+    if link.is_expired() {
+        return Err(Error::Unauthorized("link_expired".to_string()));
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    if !link.is_unlocked(
+        &repository.access_secret().await?,
+        access_token.as_deref(),
+        now,
+    ) {
+        return Err(Error::Unauthorized("link_locked".to_string()));
+    }
+
+    if link.is_exhausted() {
+        return Err(Error::Unauthorized("link_exhausted".to_string()));
+    }
+
+    Ok(())
+}
+
+/// The file a request is for: the linked file itself, or the `file_id`
+/// under a linked folder after checking it is still one of its descendants.
+async fn linked_file(
+    req: &HttpRequest,
+    repository: &Repository<'_>,
+    link: &AppLink,
+) -> AppResult<AppLinkFile> {
+    if !link.is_folder() {
+        return Ok(AppLinkFile::from(link));
+    }
+
+    let file_id = util::actix::query_var::<Uuid>(req, "file_id")
+        .map_err(|_| Error::BadRequest("file_id_required".to_string()))?;
+    let file = repository.file(link, file_id).await?;
+
+    if file.is_dir() {
+        return Err(Error::BadRequest("cannot_download_dir".to_string()));
+    }
+
+    Ok(file)
+}
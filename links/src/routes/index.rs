@@ -1,5 +1,5 @@
 use actix_web::{route, web, HttpResponse};
-use auth::data::authenticated::Authenticated;
+use auth::data::claims::Claims;
 use context::Context;
 use error::AppResult;
 use validr::Validation;
@@ -14,7 +14,7 @@ use crate::{data::find::Find, repository::Repository};
 #[route("/api/links", method = "GET")]
 pub(crate) async fn index(
     context: web::Data<Context>,
-    authenticated: Authenticated,
+    claims: Claims,
     data: web::Query<Find>,
 ) -> AppResult<HttpResponse> {
     let context = context.into_inner();
@@ -23,9 +23,7 @@ pub(crate) async fn index(
     let with_expired = data.with_expired.unwrap_or(false);
     let compact = data.compact.unwrap_or(false);
 
-    let response = repository
-        .links(authenticated.user.id, with_expired, compact)
-        .await?;
+    let response = repository.links(claims.sub, with_expired, compact).await?;
 
     Ok(HttpResponse::Ok().json(response))
 }
@@ -1,4 +1,5 @@
 use actix_web::{route, web, HttpRequest, HttpResponse};
+use auth::data::claims::Claims;
 use context::Context;
 use entity::Uuid;
 use error::AppResult;
@@ -7,17 +8,39 @@ use crate::repository::Repository;
 
 /// Get application link by its id, return encrypted metadata
 /// that will be handled by frontend to display its description and information.
+/// A link with a password hides its encrypted name, thumbnail and file key
+/// unless the `access_token` query parameter unlocks it or the caller is
+/// signed in as the link's owner. A folder link also lists every file under
+/// the folder, with names and keys encrypted with the link key.
 ///
 /// Response: [crate::data::app_link::AppLink]
 #[route("/api/links/{link_id}/metadata", method = "GET")]
 pub(crate) async fn metadata(
     req: HttpRequest,
     context: web::Data<Context>,
+    claims: Option<Claims>,
 ) -> AppResult<HttpResponse> {
     let context = context.into_inner();
     let link_id: Uuid = util::actix::path_var(&req, "link_id")?;
     let repository = Repository::new(&context);
-    let link = repository.get(link_id).await?;
+    let access_token = util::actix::query_var::<String>(&req, "access_token").ok();
+    let mut link = repository.get(link_id).await?;
+
+    let is_owner = claims.is_some_and(|claims| claims.sub == link.owner_id);
+
+    let unlocked = link.is_unlocked(
+        &repository.access_secret().await?,
+        access_token.as_deref(),
+        chrono::Utc::now().timestamp(),
+    );
+
+    if !is_owner && !unlocked {
+        return Ok(HttpResponse::Ok().json(link.locked()));
+    }
+
+    if link.is_folder() {
+        link.files = Some(repository.files(&link).await?);
+    }
 
     Ok(HttpResponse::Ok().json(link))
 }
@@ -3,6 +3,7 @@ pub mod delete;
 pub mod download;
 pub mod index;
 pub mod metadata;
+pub mod unlock;
 pub mod update;
 
 /// Register the links routes
@@ -14,5 +15,6 @@ pub fn configure(cfg: &mut actix_web::web::ServiceConfig) {
     cfg.service(metadata::metadata);
     cfg.service(download::head);
     cfg.service(index::index);
+    cfg.service(unlock::unlock);
     cfg.service(update::update);
 }
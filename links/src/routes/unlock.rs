@@ -0,0 +1,61 @@
+use actix_web::{route, web, HttpRequest, HttpResponse};
+use context::Context;
+use entity::Uuid;
+use error::{AppResult, Error};
+
+use crate::{
+    data::unlock::{Unlock, Unlocked, ACCESS_TOKEN_TTL_SECONDS},
+    repository::Repository,
+};
+
+/// Give the password of a protected link in exchange for an access token.
+///
+/// This route is not authenticated. Wrong passwords are charged to the login
+/// rate limiter under the link and the caller's address, so a leaked URL
+/// cannot be used to guess the password offline or at speed.
+///
+/// Request: [crate::data::unlock::Unlock]
+///
+/// Response: [crate::data::unlock::Unlocked]
+#[route("/api/links/{link_id}/unlock", method = "POST")]
+pub(crate) async fn unlock(
+    req: HttpRequest,
+    context: web::Data<Context>,
+    data: web::Json<Unlock>,
+) -> AppResult<HttpResponse> {
+    let context = context.into_inner();
+    let link_id: Uuid = util::actix::path_var(&req, "link_id")?;
+    let (_, ip) = util::actix::extract_ip_ua(&req);
+    let password = data.into_inner().into_password()?;
+
+    let repository = Repository::new(&context);
+    let link = repository.get(link_id).await?;
+
+    if link.is_expired() {
+        return Err(Error::Unauthorized("link_expired".to_string()));
+    }
+
+    let Some(hash) = link.password_hash.as_deref() else {
+        return Err(Error::BadRequest("link_not_protected".to_string()));
+    };
+
+    let identity = format!("link:{link_id}");
+    let now = chrono::Utc::now().timestamp();
+    let attempt = auth::rate_limit::attempt(&context, Some(&identity), &ip, now).await?;
+
+    if !util::password::verify(&password, hash) {
+        return Err(Error::Unauthorized("invalid_link_password".to_string()));
+    }
+
+    attempt.succeeded(&context).await?;
+
+    let expires_at = now + ACCESS_TOKEN_TTL_SECONDS;
+    let access_token = link
+        .access_token(&repository.access_secret().await?, expires_at)
+        .ok_or_else(|| Error::InternalError("link_access_token".to_string()))?;
+
+    Ok(HttpResponse::Ok().json(Unlocked {
+        access_token,
+        expires_at,
+    }))
+}
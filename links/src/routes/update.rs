@@ -1,5 +1,5 @@
 use actix_web::{route, web, HttpRequest, HttpResponse};
-use auth::data::authenticated::Authenticated;
+use auth::data::claims::Claims;
 use context::Context;
 use entity::Uuid;
 use error::AppResult;
@@ -13,7 +13,7 @@ use crate::{data::update::Update, repository::Repository};
 pub(crate) async fn update(
     req: HttpRequest,
     context: web::Data<Context>,
-    authenticated: Authenticated,
+    claims: Claims,
     data: web::Json<Update>,
 ) -> AppResult<HttpResponse> {
     let context = context.into_inner();
@@ -23,7 +23,7 @@ pub(crate) async fn update(
     let id: Uuid = util::actix::path_var(&req, "link_id")?;
 
     let response = repository
-        .update_expires_at(id, authenticated.user.id, expires_at)
+        .update_expires_at(id, claims.sub, expires_at)
         .await?;
 
     Ok(HttpResponse::Ok().json(response))
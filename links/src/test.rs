@@ -26,6 +26,9 @@ async fn create_link(
         encrypted_thumbnail: None,
         encrypted_file_key: Some("test-file-key".to_string()),
         expires_at: None,
+        max_downloads: None,
+        password: None,
+        files: None,
     };
 
     repository.create(create_link, user).await.unwrap()
@@ -61,6 +64,9 @@ async fn test_create_link() {
         encrypted_thumbnail: None,
         encrypted_file_key: Some("test-file-key".to_string()),
         expires_at: None,
+        max_downloads: None,
+        password: None,
+        files: None,
     };
 
     let res = repository.create(create_link, &user).await;
@@ -139,6 +145,9 @@ async fn test_getting_multiple_links_from_the_all_method() {
         encrypted_thumbnail: None,
         encrypted_file_key: Some("test-file-key".to_string()),
         expires_at: None,
+        max_downloads: None,
+        password: None,
+        files: None,
     };
 
     let res = repository.create(create_link, &user).await;
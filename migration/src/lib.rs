@@ -34,6 +34,27 @@ pub(crate) mod m20260710_000001_create_migration_rewrap_staging;
 pub(crate) mod m20260710_000002_alter_key_transitions_new_keys;
 pub(crate) mod m20260715_000001_alter_user_files_member_signed_at;
 pub(crate) mod m20260716_000001_create_used_nonces;
+pub(crate) mod m20260801_000001_create_job_runs;
+pub(crate) mod m20260802_000001_alter_files_add_trash;
+pub(crate) mod m20260803_000001_alter_files_add_last_chunk_at;
+pub(crate) mod m20260804_000001_alter_files_add_damaged_at;
+pub(crate) mod m20261018_000001_create_webauthn_tables;
+pub(crate) mod m20261018_000002_create_tfa_recovery_codes;
+pub(crate) mod m20261018_000003_create_personal_access_tokens;
+pub(crate) mod m20261018_000004_create_jwt_signing_keys;
+pub(crate) mod m20261018_000005_create_oidc_logins;
+pub(crate) mod m20261018_000006_alter_users_login_alerts;
+pub(crate) mod m20261018_000007_create_rate_limit_hits;
+pub(crate) mod m20261018_000008_alter_users_password_score;
+pub(crate) mod m20261018_000009_alter_links_access_limits;
+pub(crate) mod m20261018_000010_create_link_files;
+pub(crate) mod m20261019_000001_create_version_policies;
+pub(crate) mod m20261019_000002_alter_file_versions_pinned_at;
+pub(crate) mod m20261019_000003_create_copy_jobs;
+pub(crate) mod m20261019_000004_create_file_chunk_refs;
+pub(crate) mod m20261019_000005_alter_job_runs_reclaimed_bytes;
+pub(crate) mod m20261019_000006_create_copy_job_files;
+pub(crate) mod m20261019_000007_create_link_config;
 
 #[cfg(test)]
 mod share_events_rebuild_test;
@@ -78,6 +99,27 @@ impl MigratorTrait for Migrator {
             Box::new(m20260710_000002_alter_key_transitions_new_keys::Migration),
             Box::new(m20260715_000001_alter_user_files_member_signed_at::Migration),
             Box::new(m20260716_000001_create_used_nonces::Migration),
+            Box::new(m20260801_000001_create_job_runs::Migration),
+            Box::new(m20260802_000001_alter_files_add_trash::Migration),
+            Box::new(m20260803_000001_alter_files_add_last_chunk_at::Migration),
+            Box::new(m20260804_000001_alter_files_add_damaged_at::Migration),
+            Box::new(m20261018_000001_create_webauthn_tables::Migration),
+            Box::new(m20261018_000002_create_tfa_recovery_codes::Migration),
+            Box::new(m20261018_000003_create_personal_access_tokens::Migration),
+            Box::new(m20261018_000004_create_jwt_signing_keys::Migration),
+            Box::new(m20261018_000005_create_oidc_logins::Migration),
+            Box::new(m20261018_000006_alter_users_login_alerts::Migration),
+            Box::new(m20261018_000007_create_rate_limit_hits::Migration),
+            Box::new(m20261018_000008_alter_users_password_score::Migration),
+            Box::new(m20261018_000009_alter_links_access_limits::Migration),
+            Box::new(m20261018_000010_create_link_files::Migration),
+            Box::new(m20261019_000001_create_version_policies::Migration),
+            Box::new(m20261019_000002_alter_file_versions_pinned_at::Migration),
+            Box::new(m20261019_000003_create_copy_jobs::Migration),
+            Box::new(m20261019_000004_create_file_chunk_refs::Migration),
+            Box::new(m20261019_000005_alter_job_runs_reclaimed_bytes::Migration),
+            Box::new(m20261019_000006_create_copy_job_files::Migration),
+            Box::new(m20261019_000007_create_link_config::Migration),
         ]
     }
 }
@@ -9,47 +9,39 @@ pub(crate) struct Migration;
 impl MigrationTrait for Migration {
     async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
         manager
-            .alter_table(Table::alter()
-            .table(Files::Table)
-            .add_column(
-                ColumnDef::new(Alias::new("md5"))
-                    .string()
-                    .null()
+            .alter_table(
+                Table::alter()
+                    .table(Files::Table)
+                    .add_column(ColumnDef::new(Alias::new("md5")).string().null())
+                    .to_owned(),
             )
-            .to_owned())
             .await?;
 
-            manager
-            .alter_table(Table::alter()
-            .table(Files::Table)
-            .add_column(
-                ColumnDef::new(Alias::new("sha1"))
-                    .string()
-                    .null()
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Files::Table)
+                    .add_column(ColumnDef::new(Alias::new("sha1")).string().null())
+                    .to_owned(),
             )
-            .to_owned())
             .await?;
 
-            manager
-            .alter_table(Table::alter()
-            .table(Files::Table)
-            .add_column(
-                ColumnDef::new(Alias::new("sha256"))
-                    .string()
-                    .null()
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Files::Table)
+                    .add_column(ColumnDef::new(Alias::new("sha256")).string().null())
+                    .to_owned(),
             )
-            .to_owned())
             .await?;
 
-            manager
-            .alter_table(Table::alter()
-            .table(Files::Table)
-            .add_column(
-                ColumnDef::new(Alias::new("blake2b"))
-                    .string()
-                    .null()
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Files::Table)
+                    .add_column(ColumnDef::new(Alias::new("blake2b")).string().null())
+                    .to_owned(),
             )
-            .to_owned())
             .await?;
 
         Ok(())
@@ -57,31 +49,39 @@ impl MigrationTrait for Migration {
 
     async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
         manager
-            .alter_table(Table::alter()
-            .table(Files::Table)
-            .drop_column(Alias::new("md5"))
-            .to_owned())
+            .alter_table(
+                Table::alter()
+                    .table(Files::Table)
+                    .drop_column(Alias::new("md5"))
+                    .to_owned(),
+            )
             .await?;
 
         manager
-            .alter_table(Table::alter()
-            .table(Files::Table)
-            .drop_column(Alias::new("sha1"))
-            .to_owned())
+            .alter_table(
+                Table::alter()
+                    .table(Files::Table)
+                    .drop_column(Alias::new("sha1"))
+                    .to_owned(),
+            )
             .await?;
 
         manager
-            .alter_table(Table::alter()
-            .table(Files::Table)
-            .drop_column(Alias::new("sha256"))
-            .to_owned())
+            .alter_table(
+                Table::alter()
+                    .table(Files::Table)
+                    .drop_column(Alias::new("sha256"))
+                    .to_owned(),
+            )
             .await?;
 
         manager
-            .alter_table(Table::alter()
-            .table(Files::Table)
-            .drop_column(Alias::new("blake2b"))
-            .to_owned())
+            .alter_table(
+                Table::alter()
+                    .table(Files::Table)
+                    .drop_column(Alias::new("blake2b"))
+                    .to_owned(),
+            )
             .await?;
 
         Ok(())
@@ -50,7 +50,11 @@ impl MigrationTrait for Migration {
                             .default(false),
                     )
                     .col(ColumnDef::new(FileVersions::Size).big_integer().not_null())
-                    .col(ColumnDef::new(FileVersions::Chunks).big_integer().not_null())
+                    .col(
+                        ColumnDef::new(FileVersions::Chunks)
+                            .big_integer()
+                            .not_null(),
+                    )
                     // Per-version sha256 enables exact restore (overwrites
                     // files.sha256 when the active_version pointer flips back
                     // to this row). Other hash families are deprecated; only
@@ -42,7 +42,11 @@ impl MigrationTrait for Migration {
             .alter_table(
                 Table::alter()
                     .table(Users::Table)
-                    .add_column(ColumnDef::new(Alias::new("opaque_password_file")).text().null())
+                    .add_column(
+                        ColumnDef::new(Alias::new("opaque_password_file"))
+                            .text()
+                            .null(),
+                    )
                     .to_owned(),
             )
             .await?;
@@ -52,7 +56,12 @@ impl MigrationTrait for Migration {
                 Table::create()
                     .table(KeyTransitions::Table)
                     .if_not_exists()
-                    .col(ColumnDef::new(KeyTransitions::Id).uuid().not_null().primary_key())
+                    .col(
+                        ColumnDef::new(KeyTransitions::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
                     .col(ColumnDef::new(KeyTransitions::UserId).uuid().not_null())
                     .col(
                         ColumnDef::new(KeyTransitions::OldFingerprint)
@@ -60,12 +69,36 @@ impl MigrationTrait for Migration {
                             .not_null()
                             .unique_key(),
                     )
-                    .col(ColumnDef::new(KeyTransitions::OldKeySpki).binary().not_null())
-                    .col(ColumnDef::new(KeyTransitions::NewFingerprint).string_len(64).not_null())
-                    .col(ColumnDef::new(KeyTransitions::OldSignature).binary().not_null())
-                    .col(ColumnDef::new(KeyTransitions::NewSignature).binary().not_null())
-                    .col(ColumnDef::new(KeyTransitions::IssuedAt).big_integer().not_null())
-                    .col(ColumnDef::new(KeyTransitions::CreatedAt).big_integer().not_null())
+                    .col(
+                        ColumnDef::new(KeyTransitions::OldKeySpki)
+                            .binary()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(KeyTransitions::NewFingerprint)
+                            .string_len(64)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(KeyTransitions::OldSignature)
+                            .binary()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(KeyTransitions::NewSignature)
+                            .binary()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(KeyTransitions::IssuedAt)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(KeyTransitions::CreatedAt)
+                            .big_integer()
+                            .not_null(),
+                    )
                     .foreign_key(
                         ForeignKey::create()
                             .name("fk_key_transitions_user_id")
@@ -22,7 +22,12 @@ impl MigrationTrait for Migration {
                 Table::create()
                     .table(OpaqueConfig::Table)
                     .if_not_exists()
-                    .col(ColumnDef::new(OpaqueConfig::Id).integer().not_null().primary_key())
+                    .col(
+                        ColumnDef::new(OpaqueConfig::Id)
+                            .integer()
+                            .not_null()
+                            .primary_key(),
+                    )
                     .col(ColumnDef::new(OpaqueConfig::ServerSetup).text().not_null())
                     .to_owned(),
             )
@@ -39,7 +44,11 @@ impl MigrationTrait for Migration {
                             .not_null()
                             .primary_key(),
                     )
-                    .col(ColumnDef::new(OpaqueLoginSessions::UserId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(OpaqueLoginSessions::UserId)
+                            .uuid()
+                            .not_null(),
+                    )
                     .col(
                         ColumnDef::new(OpaqueLoginSessions::ServerLoginState)
                             .text()
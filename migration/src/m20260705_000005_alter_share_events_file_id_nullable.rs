@@ -81,16 +81,33 @@ async fn rebuild_sqlite(manager: &SchemaManager<'_>, file_id_not_null: bool) ->
         .create_table(
             Table::create()
                 .table(rebuild.clone())
-                .col(ColumnDef::new(ShareEvents::Id).uuid().not_null().primary_key())
+                .col(
+                    ColumnDef::new(ShareEvents::Id)
+                        .uuid()
+                        .not_null()
+                        .primary_key(),
+                )
                 .col(ColumnDef::new(ShareEvents::SenderId).uuid())
                 .col(ColumnDef::new(ShareEvents::RecipientId).uuid())
                 .col(&mut file_id)
-                .col(ColumnDef::new(ShareEvents::Action).string_len(48).not_null())
+                .col(
+                    ColumnDef::new(ShareEvents::Action)
+                        .string_len(48)
+                        .not_null(),
+                )
                 .col(ColumnDef::new(ShareEvents::ShareRoleBefore).string_len(16))
                 .col(ColumnDef::new(ShareEvents::ShareRoleAfter).string_len(16))
-                .col(ColumnDef::new(ShareEvents::CreatedAt).big_integer().not_null())
+                .col(
+                    ColumnDef::new(ShareEvents::CreatedAt)
+                        .big_integer()
+                        .not_null(),
+                )
                 .col(ColumnDef::new(ShareEvents::PrevEventHash).binary())
-                .col(ColumnDef::new(ShareEvents::ThisEventHash).binary().not_null())
+                .col(
+                    ColumnDef::new(ShareEvents::ThisEventHash)
+                        .binary()
+                        .not_null(),
+                )
                 .col(ColumnDef::new(ShareEvents::SenderSignature).binary())
                 .foreign_key(
                     ForeignKey::create()
@@ -132,16 +149,17 @@ async fn rebuild_sqlite(manager: &SchemaManager<'_>, file_id_not_null: bool) ->
         .map_err(|e| DbErr::Migration(e.to_string()))?
         .to_owned();
     let backend = manager.get_database_backend();
-    manager.get_connection().execute(backend.build(&copy)).await?;
+    manager
+        .get_connection()
+        .execute(backend.build(&copy))
+        .await?;
 
     manager
         .drop_table(Table::drop().table(ShareEvents::Table).to_owned())
         .await?;
     manager
         .get_connection()
-        .execute(backend.build(
-            Table::rename().table(rebuild, ShareEvents::Table),
-        ))
+        .execute(backend.build(Table::rename().table(rebuild, ShareEvents::Table)))
         .await?;
 
     for (name, extra) in [
@@ -25,7 +25,11 @@ impl MigrationTrait for Migration {
                             .not_null()
                             .primary_key(),
                     )
-                    .col(ColumnDef::new(MigrationRewrapStaging::UserId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(MigrationRewrapStaging::UserId)
+                            .uuid()
+                            .not_null(),
+                    )
                     .col(ColumnDef::new(MigrationRewrapStaging::FileId).uuid().null())
                     .col(ColumnDef::new(MigrationRewrapStaging::LinkId).uuid().null())
                     .col(
@@ -33,7 +37,11 @@ impl MigrationTrait for Migration {
                             .text()
                             .not_null(),
                     )
-                    .col(ColumnDef::new(MigrationRewrapStaging::Signature).text().null())
+                    .col(
+                        ColumnDef::new(MigrationRewrapStaging::Signature)
+                            .text()
+                            .null(),
+                    )
                     .col(
                         ColumnDef::new(MigrationRewrapStaging::CreatedAt)
                             .big_integer()
@@ -42,7 +50,10 @@ impl MigrationTrait for Migration {
                     .foreign_key(
                         ForeignKey::create()
                             .name("fk_migration_rewrap_staging_user_id")
-                            .from(MigrationRewrapStaging::Table, MigrationRewrapStaging::UserId)
+                            .from(
+                                MigrationRewrapStaging::Table,
+                                MigrationRewrapStaging::UserId,
+                            )
                             .to(Users::Table, Users::Id)
                             .on_delete(ForeignKeyAction::Cascade)
                             .on_update(ForeignKeyAction::NoAction),
@@ -78,7 +89,11 @@ impl MigrationTrait for Migration {
 
     async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
         manager
-            .drop_table(Table::drop().table(MigrationRewrapStaging::Table).to_owned())
+            .drop_table(
+                Table::drop()
+                    .table(MigrationRewrapStaging::Table)
+                    .to_owned(),
+            )
             .await
     }
 }
@@ -23,9 +23,15 @@ impl MigrationTrait for Migration {
                     .if_not_exists()
                     .col(ColumnDef::new(UsedNonces::Fingerprint).text().not_null())
                     .col(ColumnDef::new(UsedNonces::Nonce).text().not_null())
-                    .col(ColumnDef::new(UsedNonces::ExpiresAt).big_integer().not_null())
+                    .col(
+                        ColumnDef::new(UsedNonces::ExpiresAt)
+                            .big_integer()
+                            .not_null(),
+                    )
                     .primary_key(
-                        Index::create().col(UsedNonces::Fingerprint).col(UsedNonces::Nonce),
+                        Index::create()
+                            .col(UsedNonces::Fingerprint)
+                            .col(UsedNonces::Nonce),
                     )
                     .to_owned(),
             )
@@ -33,7 +39,9 @@ impl MigrationTrait for Migration {
     }
 
     async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
-        manager.drop_table(Table::drop().table(UsedNonces::Table).to_owned()).await
+        manager
+            .drop_table(Table::drop().table(UsedNonces::Table).to_owned())
+            .await
     }
 }
 
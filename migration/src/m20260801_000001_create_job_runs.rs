@@ -0,0 +1,52 @@
+use sea_orm_migration::prelude::*;
+
+/// Last-run / next-run bookkeeping for the in-process housekeeping scheduler,
+/// one row per named job. The scheduler reads `next_run_at` on startup so a
+/// restart does not re-run every job at once, and operators can see from the
+/// table when a sweep last ran and whether it failed.
+#[derive(DeriveMigrationName)]
+pub(crate) struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(JobRuns::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(JobRuns::Name)
+                            .text()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(JobRuns::LastStartedAt).big_integer().null())
+                    .col(ColumnDef::new(JobRuns::LastFinishedAt).big_integer().null())
+                    .col(ColumnDef::new(JobRuns::LastStatus).text().null())
+                    .col(ColumnDef::new(JobRuns::LastError).text().null())
+                    .col(ColumnDef::new(JobRuns::LastAffected).big_integer().null())
+                    .col(ColumnDef::new(JobRuns::NextRunAt).big_integer().not_null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(JobRuns::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub(crate) enum JobRuns {
+    Table,
+    Name,
+    LastStartedAt,
+    LastFinishedAt,
+    LastStatus,
+    LastError,
+    LastAffected,
+    NextRunAt,
+}
@@ -0,0 +1,73 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230409_091730_create_files::Files;
+
+/// Soft delete for files and folders.
+///
+/// * `deleted_at` — when the owner moved the row to the trash. Every row of
+///   a trashed subtree carries the same stamp so the whole tree is restored
+///   or purged together. NULL for live rows.
+/// * `deleted_parent_id` — the parent the trashed root was detached from.
+///   The root itself gets `file_id = NULL` so purging its old parent later
+///   can not cascade into the trash; restore puts it back under this id.
+///   No foreign key: the parent may be gone by the time the user restores.
+#[derive(DeriveMigrationName)]
+pub(crate) struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Files::Table)
+                    .add_column(ColumnDef::new(Alias::new("deleted_at")).big_integer())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Files::Table)
+                    .add_column(ColumnDef::new(Alias::new("deleted_parent_id")).uuid())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_files_deleted_at")
+                    .table(Files::Table)
+                    .col(Alias::new("deleted_at"))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_files_deleted_at")
+                    .table(Files::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        for column in ["deleted_parent_id", "deleted_at"] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Files::Table)
+                        .drop_column(Alias::new(column))
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,34 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230409_091730_create_files::Files;
+
+/// `last_chunk_at` — when the last chunk of an upload in progress landed,
+/// either the initial upload or a pending edit. The abandoned-upload reaper
+/// measures idleness from it. NULL until the first chunk arrives.
+#[derive(DeriveMigrationName)]
+pub(crate) struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Files::Table)
+                    .add_column(ColumnDef::new(Alias::new("last_chunk_at")).big_integer())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Files::Table)
+                    .drop_column(Alias::new("last_chunk_at"))
+                    .to_owned(),
+            )
+            .await
+    }
+}
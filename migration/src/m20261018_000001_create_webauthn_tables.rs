@@ -0,0 +1,160 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20220101_000001_create_users::Users;
+
+/// WebAuthn as a second factor next to TOTP.
+///
+/// `webauthn_credentials` holds every security key a user enrolled, several
+/// per user; `credential_id` is unique because the authenticator names the
+/// credential it signs with and the server looks it up by that id alone.
+///
+/// `webauthn_challenges` holds the challenge of a ceremony between its two
+/// round trips, the same way `opaque_login_sessions` holds the OPAQUE state.
+#[derive(DeriveMigrationName)]
+pub(crate) struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(WebauthnCredentials::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(WebauthnCredentials::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(WebauthnCredentials::UserId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(WebauthnCredentials::CredentialId)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(WebauthnCredentials::PublicKey)
+                            .text()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(WebauthnCredentials::SignCount)
+                            .big_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(WebauthnCredentials::Name)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(WebauthnCredentials::CreatedAt)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(WebauthnCredentials::LastUsedAt)
+                            .big_integer()
+                            .null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_webauthn_credentials_user_id")
+                            .from(WebauthnCredentials::Table, WebauthnCredentials::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_webauthn_credentials_user_id")
+                    .table(WebauthnCredentials::Table)
+                    .col(WebauthnCredentials::UserId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(WebauthnChallenges::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(WebauthnChallenges::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(WebauthnChallenges::UserId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(WebauthnChallenges::Ceremony)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(WebauthnChallenges::Challenge)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(WebauthnChallenges::ExpiresAt)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_webauthn_challenges_user_id")
+                            .from(WebauthnChallenges::Table, WebauthnChallenges::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(WebauthnChallenges::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(WebauthnCredentials::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub(crate) enum WebauthnCredentials {
+    Table,
+    Id,
+    UserId,
+    CredentialId,
+    PublicKey,
+    SignCount,
+    Name,
+    CreatedAt,
+    LastUsedAt,
+}
+
+#[derive(Iden)]
+pub(crate) enum WebauthnChallenges {
+    Table,
+    Id,
+    UserId,
+    Ceremony,
+    Challenge,
+    ExpiresAt,
+}
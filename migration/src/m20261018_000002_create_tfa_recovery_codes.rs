@@ -0,0 +1,80 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20220101_000001_create_users::Users;
+
+/// Single-use recovery codes issued when TOTP is enabled. Only a hash of each
+/// code is kept; `used_at` marks a burned one so the count of what is left
+/// can be reported without deleting history.
+#[derive(DeriveMigrationName)]
+pub(crate) struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(TfaRecoveryCodes::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TfaRecoveryCodes::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(TfaRecoveryCodes::UserId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(TfaRecoveryCodes::CodeHash)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TfaRecoveryCodes::UsedAt)
+                            .big_integer()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(TfaRecoveryCodes::CreatedAt)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_tfa_recovery_codes_user_id")
+                            .from(TfaRecoveryCodes::Table, TfaRecoveryCodes::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_tfa_recovery_codes_user_id_code_hash")
+                    .table(TfaRecoveryCodes::Table)
+                    .col(TfaRecoveryCodes::UserId)
+                    .col(TfaRecoveryCodes::CodeHash)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(TfaRecoveryCodes::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub(crate) enum TfaRecoveryCodes {
+    Table,
+    Id,
+    UserId,
+    CodeHash,
+    UsedAt,
+    CreatedAt,
+}
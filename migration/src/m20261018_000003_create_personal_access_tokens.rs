@@ -0,0 +1,113 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20220101_000001_create_users::Users;
+use crate::m20230409_091730_create_files::Files;
+
+/// Personal access tokens for automation. Tokens are looked up by the hash of
+/// the secret, so `token_hash` is unique; a token confined to a folder goes
+/// away with the folder.
+#[derive(DeriveMigrationName)]
+pub(crate) struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(PersonalAccessTokens::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PersonalAccessTokens::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(PersonalAccessTokens::UserId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PersonalAccessTokens::Name)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PersonalAccessTokens::TokenHash)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(PersonalAccessTokens::Scopes)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(PersonalAccessTokens::FolderId).uuid().null())
+                    .col(
+                        ColumnDef::new(PersonalAccessTokens::ExpiresAt)
+                            .big_integer()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(PersonalAccessTokens::LastUsedAt)
+                            .big_integer()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(PersonalAccessTokens::CreatedAt)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_personal_access_tokens_user_id")
+                            .from(PersonalAccessTokens::Table, PersonalAccessTokens::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_personal_access_tokens_folder_id")
+                            .from(PersonalAccessTokens::Table, PersonalAccessTokens::FolderId)
+                            .to(Files::Table, Files::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_personal_access_tokens_user_id")
+                    .table(PersonalAccessTokens::Table)
+                    .col(PersonalAccessTokens::UserId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PersonalAccessTokens::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub(crate) enum PersonalAccessTokens {
+    Table,
+    Id,
+    UserId,
+    Name,
+    TokenHash,
+    Scopes,
+    FolderId,
+    ExpiresAt,
+    LastUsedAt,
+    CreatedAt,
+}
@@ -0,0 +1,61 @@
+use sea_orm_migration::prelude::*;
+
+/// Asymmetric keys for signing JWTs in place of the shared `JWT_SECRET`.
+/// Several rows are live at once: the newest unretired key signs, retired
+/// ones only verify until the tokens they signed have expired.
+#[derive(DeriveMigrationName)]
+pub(crate) struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(JwtSigningKeys::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(JwtSigningKeys::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(JwtSigningKeys::Algorithm)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(JwtSigningKeys::PrivateKey).text().not_null())
+                    .col(ColumnDef::new(JwtSigningKeys::Jwk).text().not_null())
+                    .col(
+                        ColumnDef::new(JwtSigningKeys::CreatedAt)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(JwtSigningKeys::RetiredAt)
+                            .big_integer()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(JwtSigningKeys::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub(crate) enum JwtSigningKeys {
+    Table,
+    Id,
+    Algorithm,
+    PrivateKey,
+    Jwk,
+    CreatedAt,
+    RetiredAt,
+}
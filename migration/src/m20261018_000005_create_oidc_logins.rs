@@ -0,0 +1,68 @@
+use sea_orm_migration::prelude::*;
+
+/// Sign-ins at the OpenID Connect provider: the PKCE verifier and nonce of a
+/// pending authorization request, then the ticket that lets the user through
+/// the login gate once the provider answered.
+#[derive(DeriveMigrationName)]
+pub(crate) struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(OidcLogins::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(OidcLogins::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(OidcLogins::CodeVerifier).string().not_null())
+                    .col(ColumnDef::new(OidcLogins::Nonce).string().not_null())
+                    .col(
+                        ColumnDef::new(OidcLogins::Ticket)
+                            .uuid()
+                            .null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(OidcLogins::Email).string().null())
+                    .col(ColumnDef::new(OidcLogins::Role).string().null())
+                    .col(ColumnDef::new(OidcLogins::InvitationId).uuid().null())
+                    .col(
+                        ColumnDef::new(OidcLogins::CreatedAt)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(OidcLogins::ExpiresAt)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(OidcLogins::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub(crate) enum OidcLogins {
+    Table,
+    Id,
+    CodeVerifier,
+    Nonce,
+    Ticket,
+    Email,
+    Role,
+    InvitationId,
+    CreatedAt,
+    ExpiresAt,
+}
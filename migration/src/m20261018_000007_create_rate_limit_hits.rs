@@ -0,0 +1,75 @@
+use sea_orm_migration::prelude::*;
+
+/// Hits counted by the rate limiters when `RATE_LIMIT_BACKEND=database`, so
+/// every server process sharing the database sees the same windows. A row
+/// is dead once `expires_at` passes and is purged by the
+/// `purge-rate-limit-hits` job.
+#[derive(DeriveMigrationName)]
+pub(crate) struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(RateLimitHits::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(RateLimitHits::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(RateLimitHits::Key).string().not_null())
+                    .col(
+                        ColumnDef::new(RateLimitHits::CreatedAt)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(RateLimitHits::ExpiresAt)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_rate_limit_hits_key_created_at")
+                    .table(RateLimitHits::Table)
+                    .col(RateLimitHits::Key)
+                    .col(RateLimitHits::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_rate_limit_hits_expires_at")
+                    .table(RateLimitHits::Table)
+                    .col(RateLimitHits::ExpiresAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RateLimitHits::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub(crate) enum RateLimitHits {
+    Table,
+    Id,
+    Key,
+    CreatedAt,
+    ExpiresAt,
+}
@@ -0,0 +1,52 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230521_074334_create_links::Links;
+
+/// Restrictions on a public link besides its expiry: how many completed
+/// downloads it serves, and a bcrypt hash of the extra password a recipient
+/// has to give before anything is streamed.
+#[derive(DeriveMigrationName)]
+pub(crate) struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Links::Table)
+                    .add_column(ColumnDef::new(Alias::new("max_downloads")).integer())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Links::Table)
+                    .add_column(ColumnDef::new(Alias::new("password_hash")).string())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Links::Table)
+                    .drop_column(Alias::new("password_hash"))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Links::Table)
+                    .drop_column(Alias::new("max_downloads"))
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,79 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230409_091730_create_files::Files;
+use crate::m20230521_074334_create_links::Links;
+
+/// Descendants of a folder shared through a public link. Every row carries
+/// the file's name and content key encrypted under the link key, so the
+/// recipient can list and decrypt the tree with nothing but the key in the
+/// URL fragment. Rows go away with either the link or the file.
+#[derive(DeriveMigrationName)]
+pub(crate) struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(LinkFiles::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(LinkFiles::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(LinkFiles::LinkId).uuid().not_null())
+                    .col(ColumnDef::new(LinkFiles::FileId).uuid().not_null())
+                    .col(ColumnDef::new(LinkFiles::EncryptedName).string().not_null())
+                    .col(ColumnDef::new(LinkFiles::EncryptedFileKey).string().null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_link_files_link_id")
+                            .from(LinkFiles::Table, LinkFiles::LinkId)
+                            .to(Links::Table, Links::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_link_files_file_id")
+                            .from(LinkFiles::Table, LinkFiles::FileId)
+                            .to(Files::Table, Files::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_link_files_link_id_file_id")
+                    .table(LinkFiles::Table)
+                    .col(LinkFiles::LinkId)
+                    .col(LinkFiles::FileId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(LinkFiles::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub(crate) enum LinkFiles {
+    Table,
+    Id,
+    LinkId,
+    FileId,
+    EncryptedName,
+    EncryptedFileKey,
+}
@@ -0,0 +1,132 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20220101_000001_create_users::Users;
+use crate::m20230409_091730_create_files::Files;
+
+/// Retention of the version history of editable files. A row without a
+/// `file_id` is the user's own default for every file they own, a row with
+/// one applies to that file only. `max_versions` is NULL when there is no
+/// hard cap on top of the thinning.
+#[derive(DeriveMigrationName)]
+pub(crate) struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(VersionPolicies::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(VersionPolicies::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(VersionPolicies::UserId).uuid().not_null())
+                    .col(ColumnDef::new(VersionPolicies::FileId).uuid().null())
+                    .col(
+                        ColumnDef::new(VersionPolicies::KeepAllDays)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(VersionPolicies::KeepHourly)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(VersionPolicies::KeepDaily)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(VersionPolicies::KeepWeekly)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(VersionPolicies::KeepMonthly)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(VersionPolicies::MaxVersions)
+                            .integer()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(VersionPolicies::CreatedAt)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(VersionPolicies::UpdatedAt)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_version_policies_user_id")
+                            .from(VersionPolicies::Table, VersionPolicies::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_version_policies_file_id")
+                            .from(VersionPolicies::Table, VersionPolicies::FileId)
+                            .to(Files::Table, Files::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_version_policies_user_id")
+                    .table(VersionPolicies::Table)
+                    .col(VersionPolicies::UserId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_version_policies_file_id")
+                    .table(VersionPolicies::Table)
+                    .col(VersionPolicies::FileId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(VersionPolicies::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub(crate) enum VersionPolicies {
+    Table,
+    Id,
+    UserId,
+    FileId,
+    KeepAllDays,
+    KeepHourly,
+    KeepDaily,
+    KeepWeekly,
+    KeepMonthly,
+    MaxVersions,
+    CreatedAt,
+    UpdatedAt,
+}
@@ -0,0 +1,33 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20260418_000002_create_file_versions::FileVersions;
+
+/// When the owner pinned a version to keep it forever. Retention pruning
+/// skips pinned versions and they don't count towards any cap.
+#[derive(DeriveMigrationName)]
+pub(crate) struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(FileVersions::Table)
+                    .add_column(ColumnDef::new(Alias::new("pinned_at")).big_integer())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(FileVersions::Table)
+                    .drop_column(Alias::new("pinned_at"))
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,82 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20220101_000001_create_users::Users;
+
+/// Server-side copies running in the background. The rows of the copy are
+/// written when it starts, `file_id` is the root of them; the job tracks how
+/// far the chunks got. `updated_at` moves while the copy makes progress, a
+/// running job whose `updated_at` stops moving was interrupted.
+#[derive(DeriveMigrationName)]
+pub(crate) struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(CopyJobs::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(CopyJobs::Id).uuid().not_null().primary_key())
+                    .col(ColumnDef::new(CopyJobs::UserId).uuid().not_null())
+                    .col(ColumnDef::new(CopyJobs::FileId).uuid().not_null())
+                    .col(ColumnDef::new(CopyJobs::Status).string().not_null())
+                    .col(
+                        ColumnDef::new(CopyJobs::FilesTotal)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CopyJobs::FilesCopied)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(CopyJobs::Error).string().null())
+                    .col(ColumnDef::new(CopyJobs::CreatedAt).big_integer().not_null())
+                    .col(ColumnDef::new(CopyJobs::UpdatedAt).big_integer().not_null())
+                    .col(ColumnDef::new(CopyJobs::FinishedAt).big_integer().null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_copy_jobs_user_id")
+                            .from(CopyJobs::Table, CopyJobs::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_copy_jobs_status_updated_at")
+                    .table(CopyJobs::Table)
+                    .col(CopyJobs::Status)
+                    .col(CopyJobs::UpdatedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(CopyJobs::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub(crate) enum CopyJobs {
+    Table,
+    Id,
+    UserId,
+    FileId,
+    Status,
+    FilesTotal,
+    FilesCopied,
+    Error,
+    CreatedAt,
+    UpdatedAt,
+    FinishedAt,
+}
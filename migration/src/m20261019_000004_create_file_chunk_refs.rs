@@ -0,0 +1,70 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230409_091730_create_files::Files;
+
+/// Chunks a version of a file borrows from an older version instead of
+/// storing its own copy. `source_version` always names the version whose
+/// directory holds the bytes. Rows go with the file.
+#[derive(DeriveMigrationName)]
+pub(crate) struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(FileChunkRefs::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(FileChunkRefs::FileId).uuid().not_null())
+                    .col(ColumnDef::new(FileChunkRefs::Version).integer().not_null())
+                    .col(
+                        ColumnDef::new(FileChunkRefs::Chunk)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(FileChunkRefs::SourceVersion)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(FileChunkRefs::CreatedAt)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(FileChunkRefs::FileId)
+                            .col(FileChunkRefs::Version)
+                            .col(FileChunkRefs::Chunk),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_file_chunk_refs_file_id")
+                            .from(FileChunkRefs::Table, FileChunkRefs::FileId)
+                            .to(Files::Table, Files::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(FileChunkRefs::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub(crate) enum FileChunkRefs {
+    Table,
+    FileId,
+    Version,
+    Chunk,
+    SourceVersion,
+    CreatedAt,
+}
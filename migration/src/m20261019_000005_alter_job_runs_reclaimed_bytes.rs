@@ -0,0 +1,33 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20260801_000001_create_job_runs::JobRuns;
+
+/// Storage space the last run of a job freed, for the jobs that remove
+/// chunks and can tell how much they took with them.
+#[derive(DeriveMigrationName)]
+pub(crate) struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(JobRuns::Table)
+                    .add_column(ColumnDef::new(Alias::new("last_reclaimed_bytes")).big_integer())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(JobRuns::Table)
+                    .drop_column(Alias::new("last_reclaimed_bytes"))
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,59 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{m20230409_091730_create_files::Files, m20261019_000003_create_copy_jobs::CopyJobs};
+
+/// Rows a copy job wrote. A copy that fails or is interrupted removes only
+/// these, whatever was uploaded or moved into the copied tree in the
+/// meantime stays. Rows go with the job or with the file.
+#[derive(DeriveMigrationName)]
+pub(crate) struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(CopyJobFiles::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(CopyJobFiles::JobId).uuid().not_null())
+                    .col(ColumnDef::new(CopyJobFiles::FileId).uuid().not_null())
+                    .primary_key(
+                        Index::create()
+                            .col(CopyJobFiles::JobId)
+                            .col(CopyJobFiles::FileId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_copy_job_files_job_id")
+                            .from(CopyJobFiles::Table, CopyJobFiles::JobId)
+                            .to(CopyJobs::Table, CopyJobs::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_copy_job_files_file_id")
+                            .from(CopyJobFiles::Table, CopyJobFiles::FileId)
+                            .to(Files::Table, Files::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(CopyJobFiles::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub(crate) enum CopyJobFiles {
+    Table,
+    JobId,
+    FileId,
+}
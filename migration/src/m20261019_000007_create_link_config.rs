@@ -0,0 +1,41 @@
+use sea_orm_migration::prelude::*;
+
+/// Singleton holding the secret password-protected links key their access
+/// tokens with. It is made on first use and kept in the database, so every
+/// replica hands out and accepts the same tokens and they outlive a restart.
+#[derive(DeriveMigrationName)]
+pub(crate) struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(LinkConfig::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(LinkConfig::Id)
+                            .integer()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(LinkConfig::AccessSecret).text().not_null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(LinkConfig::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub(crate) enum LinkConfig {
+    Table,
+    Id,
+    AccessSecret,
+}
@@ -46,7 +46,9 @@ fn target_index() -> usize {
 async fn connect(name: &str) -> DatabaseConnection {
     let mut opt = ConnectOptions::new(format!("sqlite:file:{name}?mode=memory&cache=shared"));
     opt.max_connections(1).min_connections(1);
-    let db = Database::connect(opt).await.expect("in-memory sqlite connect");
+    let db = Database::connect(opt)
+        .await
+        .expect("in-memory sqlite connect");
     db.execute_unprepared("PRAGMA foreign_keys = ON;")
         .await
         .expect("enable foreign keys");
@@ -198,7 +200,9 @@ async fn foreign_keys(
         (
             r.try_get::<String>("", "from").unwrap(),
             r.try_get::<String>("", "table").unwrap(),
-            r.try_get::<Option<String>>("", "to").unwrap().unwrap_or_default(),
+            r.try_get::<Option<String>>("", "to")
+                .unwrap()
+                .unwrap_or_default(),
             r.try_get::<String>("", "on_delete").unwrap(),
             r.try_get::<String>("", "on_update").unwrap(),
         )
@@ -246,7 +250,10 @@ async fn up_preserves_rows_and_schema_and_relaxes_file_id() {
     );
 
     let chained = fetch_events(&db, "ORDER BY created_at").await;
-    assert_eq!(chained[0].prev_hash, None, "genesis event has no predecessor");
+    assert_eq!(
+        chained[0].prev_hash, None,
+        "genesis event has no predecessor"
+    );
     assert_eq!(
         chained[1].prev_hash, chained[0].this_hash,
         "hash chain link E1->E2 must survive the rebuild"
@@ -266,7 +273,10 @@ async fn up_preserves_rows_and_schema_and_relaxes_file_id() {
         let after = &cols_after[name];
         if name == "file_id" {
             assert_eq!(before.0, after.0, "file_id declared type must not change");
-            assert_eq!(before.2, after.2, "file_id primary-key flag must not change");
+            assert_eq!(
+                before.2, after.2,
+                "file_id primary-key flag must not change"
+            );
             assert_eq!(after.1, 0, "file_id must be nullable after the rebuild");
         } else {
             assert_eq!(before, after, "column {name} must be unchanged");
@@ -310,9 +320,15 @@ async fn down_preserves_rows_and_restores_not_null() {
     let fk_before = foreign_keys(&db).await;
     let idx_before = named_indexes(&db).await;
     assert_eq!(before.len(), 3);
-    assert_eq!(columns(&db).await["file_id"].1, 0, "file_id nullable before down()");
+    assert_eq!(
+        columns(&db).await["file_id"].1,
+        0,
+        "file_id nullable before down()"
+    );
 
-    Migrator::down(&db, Some(1)).await.expect("revert the rebuild");
+    Migrator::down(&db, Some(1))
+        .await
+        .expect("revert the rebuild");
 
     assert_eq!(
         before,
@@ -324,8 +340,16 @@ async fn down_preserves_rows_and_restores_not_null() {
         1,
         "down() must restore file_id NOT NULL"
     );
-    assert_eq!(idx_before, named_indexes(&db).await, "down() must keep the indexes");
-    assert_eq!(fk_before, foreign_keys(&db).await, "down() must keep the foreign keys");
+    assert_eq!(
+        idx_before,
+        named_indexes(&db).await,
+        "down() must keep the indexes"
+    );
+    assert_eq!(
+        fk_before,
+        foreign_keys(&db).await,
+        "down() must keep the foreign keys"
+    );
     assert_eq!(foreign_key_violations(&db).await, 0);
 
     let null_file = db
@@ -372,8 +396,14 @@ fn postgres_branch_is_metadata_only_not_a_rebuild() {
         .modify_column(ColumnDef::new(ShareEvents::FileId).uuid().null())
         .to_string(PostgresQueryBuilder);
     let up_upper = up.to_uppercase();
-    assert!(up_upper.contains("ALTER COLUMN"), "PG up must alter in place: {up}");
-    assert!(up_upper.contains("DROP NOT NULL"), "PG up must drop NOT NULL: {up}");
+    assert!(
+        up_upper.contains("ALTER COLUMN"),
+        "PG up must alter in place: {up}"
+    );
+    assert!(
+        up_upper.contains("DROP NOT NULL"),
+        "PG up must drop NOT NULL: {up}"
+    );
 
     let down = Table::alter()
         .table(ShareEvents::Table)
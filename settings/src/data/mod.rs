@@ -1,10 +1,14 @@
 mod blacklist;
+mod security;
 mod sharing;
+mod trash;
 mod users;
 mod whitelist;
 
 pub use blacklist::Blacklist;
+pub use security::{Security, TwoFactorRequirement};
 pub use sharing::Sharing;
+pub use trash::Trash;
 pub use users::Users;
 pub use whitelist::Whitelist;
 
@@ -16,6 +20,10 @@ pub struct Data {
     pub users: Users,
     #[serde(default)]
     pub sharing: Sharing,
+    #[serde(default)]
+    pub trash: Trash,
+    #[serde(default)]
+    pub security: Security,
 }
 
 impl Data {
@@ -0,0 +1,177 @@
+use serde::{Deserialize, Serialize};
+
+const DAY_SECONDS: i64 = 24 * 60 * 60;
+
+/// Who has to sign in with a second factor, a TOTP app or a security key.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TwoFactorRequirement {
+    #[default]
+    Off,
+    Admins,
+    All,
+}
+
+/// Account security policy. Users who fall short of it after it changes are
+/// not locked out: their sessions are confined to the routes that let them
+/// set up a second factor or change their password until they do.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Security {
+    #[serde(default)]
+    two_factor: TwoFactorRequirement,
+    /// Days a user who is newly required to use two-factor has to set it
+    /// up before their sessions are confined to the enrollment.
+    #[serde(default = "default_two_factor_grace_days")]
+    two_factor_grace_days: u64,
+    /// When the two-factor requirement last grew to cover more users, the
+    /// start of their grace period. Kept by the server, never taken from an
+    /// update.
+    #[serde(default)]
+    two_factor_enforced_at: Option<i64>,
+    /// Lowest zxcvbn score (0-4) the client has to attest the password
+    /// scored when it is set. `0` asks nothing.
+    #[serde(default)]
+    min_password_score: u8,
+    /// Days a session may live from the login that created it, however
+    /// active it is. `0` leaves sessions unbounded.
+    #[serde(default)]
+    max_session_lifetime_days: u64,
+}
+
+fn default_two_factor_grace_days() -> u64 {
+    7
+}
+
+impl Default for Security {
+    fn default() -> Self {
+        Self {
+            two_factor: TwoFactorRequirement::Off,
+            two_factor_grace_days: default_two_factor_grace_days(),
+            two_factor_enforced_at: None,
+            min_password_score: 0,
+            max_session_lifetime_days: 0,
+        }
+    }
+}
+
+impl Security {
+    pub fn two_factor(&self) -> TwoFactorRequirement {
+        self.two_factor
+    }
+
+    pub fn set_two_factor(&mut self, two_factor: TwoFactorRequirement) {
+        self.two_factor = two_factor;
+    }
+
+    pub fn two_factor_grace_days(&self) -> u64 {
+        self.two_factor_grace_days
+    }
+
+    pub fn set_two_factor_grace_days(&mut self, days: u64) {
+        self.two_factor_grace_days = days;
+    }
+
+    pub fn min_password_score(&self) -> u8 {
+        self.min_password_score
+    }
+
+    pub fn set_min_password_score(&mut self, score: u8) {
+        self.min_password_score = score;
+    }
+
+    pub fn max_session_lifetime_days(&self) -> u64 {
+        self.max_session_lifetime_days
+    }
+
+    pub fn set_max_session_lifetime_days(&mut self, days: u64) {
+        self.max_session_lifetime_days = days;
+    }
+
+    /// Take over the enforcement start from the settings this update
+    /// replaces, restarting it at `now` when the requirement now covers
+    /// users it did not before.
+    pub fn carry_enforcement(&mut self, previous: &Security, now: i64) {
+        self.two_factor_enforced_at = if self.two_factor == TwoFactorRequirement::Off {
+            None
+        } else if self.two_factor > previous.two_factor {
+            Some(now)
+        } else {
+            previous.two_factor_enforced_at
+        };
+    }
+
+    /// Whether a user with or without the admin role has to use two-factor.
+    pub fn requires_two_factor(&self, is_admin: bool) -> bool {
+        match self.two_factor {
+            TwoFactorRequirement::Off => false,
+            TwoFactorRequirement::Admins => is_admin,
+            TwoFactorRequirement::All => true,
+        }
+    }
+
+    /// When the grace period to set up two-factor runs out for a user who
+    /// registered at `created_at`. It starts with the requirement, or with
+    /// the account when that came later.
+    pub fn two_factor_due_at(&self, created_at: i64) -> i64 {
+        let start = self
+            .two_factor_enforced_at
+            .map_or(created_at, |enforced_at| enforced_at.max(created_at));
+
+        start + self.two_factor_grace_days as i64 * DAY_SECONDS
+    }
+
+    /// Whether a password that scored `score` is strong enough. A password
+    /// whose score was never attested only passes while nothing is asked.
+    pub fn password_score_valid(&self, score: Option<u8>) -> bool {
+        self.min_password_score == 0 || score.is_some_and(|s| s >= self.min_password_score)
+    }
+
+    /// Whether a session created at `created_at` has outlived the maximum
+    /// session lifetime.
+    pub fn session_outlived(&self, created_at: i64, now: i64) -> bool {
+        self.max_session_lifetime_days > 0
+            && created_at + self.max_session_lifetime_days as i64 * DAY_SECONDS < now
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Security, TwoFactorRequirement, DAY_SECONDS};
+
+    #[test]
+    fn settings_written_before_the_policy_existed_ask_nothing() {
+        let security: Security = serde_json::from_str("{}").unwrap();
+        assert!(!security.requires_two_factor(true));
+        assert!(security.password_score_valid(None));
+        assert!(!security.session_outlived(0, i64::MAX));
+    }
+
+    #[test]
+    fn grace_starts_when_the_requirement_grows() {
+        let previous = Security::default();
+        let mut security = Security::default();
+        security.set_two_factor(TwoFactorRequirement::Admins);
+        security.carry_enforcement(&previous, 1000);
+        assert_eq!(security.two_factor_due_at(0), 1000 + 7 * DAY_SECONDS);
+        assert_eq!(security.two_factor_due_at(2000), 2000 + 7 * DAY_SECONDS);
+
+        // Saving again, or narrowing it, keeps the running grace period.
+        let previous = security.clone();
+        security.carry_enforcement(&previous, 5000);
+        assert_eq!(security.two_factor_due_at(0), 1000 + 7 * DAY_SECONDS);
+
+        let previous = security.clone();
+        security.set_two_factor(TwoFactorRequirement::All);
+        security.carry_enforcement(&previous, 5000);
+        assert_eq!(security.two_factor_due_at(0), 5000 + 7 * DAY_SECONDS);
+    }
+
+    #[test]
+    fn an_unattested_password_fails_any_minimum() {
+        let mut security = Security::default();
+        security.set_min_password_score(3);
+        assert!(!security.password_score_valid(None));
+        assert!(!security.password_score_valid(Some(2)));
+        assert!(security.password_score_valid(Some(3)));
+    }
+}
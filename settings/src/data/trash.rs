@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+
+/// Recycle bin policy. Deleting a file or folder moves it to the owner's
+/// trash, where it can be restored until `retention_days` have passed; the
+/// `purge-trash` background job then deletes the rows and their chunks for
+/// real. `0` turns the trash off and deletes immediately, the behavior
+/// before the trash existed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Trash {
+    #[serde(default = "default_retention_days")]
+    retention_days: u64,
+}
+
+fn default_retention_days() -> u64 {
+    30
+}
+
+impl Default for Trash {
+    fn default() -> Self {
+        Self {
+            retention_days: default_retention_days(),
+        }
+    }
+}
+
+impl Trash {
+    /// Days a trashed file stays restorable before it is purged.
+    pub fn retention_days(&self) -> u64 {
+        self.retention_days
+    }
+
+    pub fn set_retention_days(&mut self, retention_days: u64) {
+        self.retention_days = retention_days;
+    }
+
+    /// Whether deletes go to the trash at all.
+    pub fn enabled(&self) -> bool {
+        self.retention_days > 0
+    }
+
+    /// Timestamp before which trashed rows are due for purging.
+    pub fn purge_before(&self, now: i64) -> i64 {
+        now - (self.retention_days as i64) * 24 * 60 * 60
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Trash;
+
+    #[test]
+    fn settings_written_before_trash_existed_get_the_default_retention() {
+        let trash: Trash = serde_json::from_str("{}").unwrap();
+        assert_eq!(trash.retention_days(), 30);
+        assert!(trash.enabled());
+    }
+
+    #[test]
+    fn zero_retention_disables_the_trash() {
+        let mut trash = Trash::default();
+        trash.set_retention_days(0);
+        assert!(!trash.enabled());
+        assert_eq!(trash.purge_before(1000), 1000);
+    }
+}
@@ -15,6 +15,9 @@ pub struct AppShare {
     pub share_role: String,
     pub created_at: i64,
     pub shared_at: Option<i64>,
+    /// When the recipient loses access on their own, `None` for shares
+    /// that last until revoked.
+    pub expires_at: Option<i64>,
     pub shared_by_user_id: Option<Uuid>,
     pub shared_by_email: Option<String>,
 }
@@ -33,6 +36,7 @@ impl FromQueryResult for AppShare {
             share_role: row.share_role,
             created_at: row.created_at,
             shared_at: row.shared_at,
+            expires_at: row.expires_at,
             shared_by_user_id: row.shared_by_user_id,
             shared_by_email,
         })
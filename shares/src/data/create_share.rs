@@ -38,6 +38,14 @@ pub struct CreateShareEnvelope {
     /// re-encodes the payload from `(recipient, role, signed_at)` and
     /// verifies the signature against the granting actor's pubkey.
     pub member_signed_at: Option<i64>,
+    /// Unix-seconds moment the recipient loses access on their own, for
+    /// shares that should end with an engagement. Applies to every entry
+    /// in the request; `None` keeps the share until it is revoked. A
+    /// co-owner's share never outlasts their own: the server moves a later
+    /// or missing expiry to theirs. Sent alongside the signed payload
+    /// rather than inside it: the server already decides who can read a
+    /// row, and an expiry only narrows that.
+    pub expires_at: Option<i64>,
 }
 
 /// Embedded folder-list signature carrier — shared shape across
@@ -55,6 +55,8 @@ pub struct IncomingShare {
     pub encrypted_key: String,
     pub created_at: i64,
     pub shared_at: Option<i64>,
+    /// When the share ends on its own, `None` until revoked.
+    pub expires_at: Option<i64>,
     pub owner_id: Uuid,
     pub owner_email: String,
     pub owner_pubkey: String,
@@ -104,6 +106,7 @@ impl FromQueryResult for IncomingShare {
             encrypted_key: row.encrypted_key,
             created_at: row.created_at,
             shared_at: row.shared_at,
+            expires_at: row.expires_at,
             owner_id: owner.id,
             owner_email: owner.email,
             owner_pubkey: owner.pubkey,
@@ -75,7 +75,6 @@ impl Validation for MemberKey {
     }
 }
 
-
 /// Body for `POST /api/storage/{file_id}/evict-from-folder`. The folder
 /// owner authorises the eviction; the file_id in the URL is the
 /// contributor's file to be detached. The signed audit input carries
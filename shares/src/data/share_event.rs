@@ -52,7 +52,10 @@ impl AppShareEvent {
             created_at: row.created_at,
             prev_event_hash: row.prev_event_hash.as_deref().map(cryptfns::base64::encode),
             this_event_hash: cryptfns::base64::encode(&row.this_event_hash),
-            sender_signature: row.sender_signature.as_deref().map(cryptfns::base64::encode),
+            sender_signature: row
+                .sender_signature
+                .as_deref()
+                .map(cryptfns::base64::encode),
             encrypted_name,
             cipher,
             encrypted_key,
@@ -11,11 +11,7 @@ pub(crate) mod repository;
 /// audit chain primitives.
 pub use repository::account_deletion::pre_emit_for_user_delete;
 
-/// Test-only state-reset hooks. Hidden behind `test-support` so they
-/// never link into production binaries.
-#[cfg(feature = "test-support")]
-pub mod test_support {
-    pub fn reset_discover_rate_limit() {
-        crate::repository::discover_rate_limit::reset_for_tests();
-    }
-}
+/// Housekeeping pass behind the `purge-expired-shares` job: drops
+/// time-limited shares past their `expires_at` and records each expiry
+/// in the system audit chain.
+pub use repository::expiry::purge_expired_shares;
@@ -19,7 +19,7 @@ impl Repository<'_> {
         now: i64,
     ) -> AppResult<DiscoveredUser> {
         // Bump the counter on every call.
-        if discover_rate_limit::over_limit(caller.id, now) {
+        if discover_rate_limit::over_limit(self.context, caller.id, now).await? {
             return Err(Error::TooManyRequests("rate_limited".to_string()));
         }
 
@@ -0,0 +1,94 @@
+//! Drop time-limited shares once their `expires_at` has passed.
+//!
+//! `permission()` already resolves an expired row to `None`, so access
+//! ends at the expiry second; this pass removes the rows and records the
+//! expiry in the audit log. Expiry has no human actor, so the audit rows
+//! are system-attributed (`sender_id = NULL`, no signature) and land on
+//! the NULL-sender chain next to the other cascade rows.
+
+use std::collections::{HashMap, HashSet};
+
+use entity::{
+    files, user_files, ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QuerySelect,
+    TransactionTrait, Uuid,
+};
+use error::AppResult;
+
+use crate::{
+    contracts::audit::NewAuditEvent,
+    repository::{audit, share_sig::static_role_str},
+};
+
+/// Delete every non-owner `user_files` row whose `expires_at` is at or
+/// before `now` and emit one `expire` audit row per expired share. A
+/// folder share expires as a whole, so only the topmost expired row of
+/// each recipient's subtree gets an audit row, the same way a revoke is
+/// recorded once on the share root. Returns the number of rows deleted.
+pub async fn purge_expired_shares<C>(db: &C, now: i64) -> AppResult<u64>
+where
+    C: ConnectionTrait + TransactionTrait,
+{
+    let tx = db.begin().await?;
+
+    let expired: Vec<user_files::Model> = user_files::Entity::find()
+        .filter(user_files::Column::IsOwner.eq(false))
+        .filter(user_files::Column::ExpiresAt.is_not_null())
+        .filter(user_files::Column::ExpiresAt.lte(now))
+        .all(&tx)
+        .await?;
+
+    if expired.is_empty() {
+        return Ok(0);
+    }
+
+    let expired_pairs: HashSet<(Uuid, Uuid)> = expired
+        .iter()
+        .map(|row| (row.user_id, row.file_id))
+        .collect();
+
+    let parents: HashMap<Uuid, Option<Uuid>> = files::Entity::find()
+        .select_only()
+        .column(files::Column::Id)
+        .column(files::Column::FileId)
+        .filter(files::Column::Id.is_in(expired.iter().map(|row| row.file_id)))
+        .into_tuple::<(Uuid, Option<Uuid>)>()
+        .all(&tx)
+        .await?
+        .into_iter()
+        .collect();
+
+    for row in &expired {
+        let parent_expired = parents
+            .get(&row.file_id)
+            .copied()
+            .flatten()
+            .is_some_and(|parent_id| expired_pairs.contains(&(row.user_id, parent_id)));
+        if parent_expired {
+            continue;
+        }
+
+        audit::append_event(
+            &tx,
+            NewAuditEvent {
+                sender_id: None,
+                recipient_id: Some(row.user_id),
+                file_id: row.file_id,
+                action_str: "expire",
+                share_role_before: static_role_str(&row.share_role),
+                share_role_after: None,
+                created_at: now,
+                event_signature: None,
+            },
+        )
+        .await?;
+    }
+
+    let result = user_files::Entity::delete_many()
+        .filter(user_files::Column::Id.is_in(expired.iter().map(|row| row.id)))
+        .exec(&tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(result.rows_affected)
+}
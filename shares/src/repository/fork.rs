@@ -144,6 +144,10 @@ impl Repository<'_> {
             members_list_signature: ActiveValue::Set(None),
             members_list_signed_at: ActiveValue::Set(None),
             members_list_signed_by_user_id: ActiveValue::Set(None),
+            deleted_at: ActiveValue::Set(None),
+            deleted_parent_id: ActiveValue::Set(None),
+            last_chunk_at: ActiveValue::Set(None),
+            damaged_at: ActiveValue::Set(None),
         };
         files::Entity::insert(file_active)
             .exec_without_returning(&tx)
@@ -198,4 +202,3 @@ impl Repository<'_> {
         })
     }
 }
-
@@ -130,10 +130,7 @@ impl Repository<'_> {
     /// role) plus the groups they're a member of (with the owner's email
     /// and the caller's own group role, but no peer roster — peers in
     /// someone else's group are not the caller's data to enumerate).
-    pub(crate) async fn list_groups(
-        &self,
-        caller: &users::Model,
-    ) -> AppResult<GroupsResponse> {
+    pub(crate) async fn list_groups(&self, caller: &users::Model) -> AppResult<GroupsResponse> {
         let owned_rows = share_groups::Entity::find()
             .filter(share_groups::Column::OwnerId.eq(caller.id))
             .order_by(share_groups::Column::CreatedAt, Order::Asc)
@@ -249,7 +246,10 @@ impl Repository<'_> {
             .one(&self.context.db)
             .await?
             .ok_or_else(|| Error::NotFound("user_not_found".to_string()))?;
-        if !member.fingerprint.eq_ignore_ascii_case(&claimed_fingerprint) {
+        if !member
+            .fingerprint
+            .eq_ignore_ascii_case(&claimed_fingerprint)
+        {
             return Err(Error::Conflict("recipient_pubkey_changed".to_string()));
         }
 
@@ -324,7 +324,12 @@ impl Repository<'_> {
             group_id: ActiveValue::Unchanged(group_id),
             user_id: ActiveValue::Unchanged(member_id),
             added_at: ActiveValue::Unchanged(membership.added_at),
-            role: ActiveValue::Set(new_role.as_member_role_str().unwrap_or("reader").to_string()),
+            role: ActiveValue::Set(
+                new_role
+                    .as_member_role_str()
+                    .unwrap_or("reader")
+                    .to_string(),
+            ),
         })
         .exec(&self.context.db)
         .await?;
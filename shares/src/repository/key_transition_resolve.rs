@@ -182,7 +182,10 @@ mod tests {
     /// `migration_complete` would: build the certificate, sign it with both keys,
     /// and persist the components plus the keys rotated to.
     async fn insert_hop<C: ConnectionTrait>(db: &C, user_id: Uuid, old: &ChainKey, new: &ChainKey) {
-        let new_wrapping = new.wrapping_pem.as_deref().expect("a hop rotates to a curve key");
+        let new_wrapping = new
+            .wrapping_pem
+            .as_deref()
+            .expect("a hop rotates to a curve key");
         let issued_at = chrono::Utc::now().timestamp();
         let cert = Certificate {
             user_id: user_id.into_bytes(),
@@ -206,8 +209,12 @@ mod tests {
             new_fingerprint: ActiveValue::Set(new.fingerprint.clone()),
             new_identity_key_pem: ActiveValue::Set(new.public.clone()),
             new_wrapping_key_pem: ActiveValue::Set(new_wrapping.to_string()),
-            old_signature: ActiveValue::Set(cryptfns::base64::decode(&signatures.old_signature).unwrap()),
-            new_signature: ActiveValue::Set(cryptfns::base64::decode(&signatures.new_signature).unwrap()),
+            old_signature: ActiveValue::Set(
+                cryptfns::base64::decode(&signatures.old_signature).unwrap(),
+            ),
+            new_signature: ActiveValue::Set(
+                cryptfns::base64::decode(&signatures.new_signature).unwrap(),
+            ),
             issued_at: ActiveValue::Set(issued_at),
             created_at: ActiveValue::Set(issued_at),
         })
@@ -240,11 +247,17 @@ mod tests {
             created_at: ActiveValue::Set(now),
             updated_at: ActiveValue::Set(now),
             share_notifications_enabled: ActiveValue::Set(true),
+            login_alerts_enabled: ActiveValue::Set(true),
+            password_score: ActiveValue::Set(None),
         })
         .exec_without_returning(db)
         .await
         .unwrap();
-        users::Entity::find_by_id(id).one(db).await.unwrap().unwrap()
+        users::Entity::find_by_id(id)
+            .one(db)
+            .await
+            .unwrap()
+            .unwrap()
     }
 
     /// Flip a byte of the stored `old`- or `new`-signature of the hop whose old
@@ -260,7 +273,11 @@ mod tests {
             .await
             .unwrap()
             .unwrap();
-        let mut bytes = if which_new { row.new_signature } else { row.old_signature };
+        let mut bytes = if which_new {
+            row.new_signature
+        } else {
+            row.old_signature
+        };
         let last = bytes.len() - 1;
         bytes[last] ^= 0x01;
         let column = if which_new {
@@ -281,7 +298,10 @@ mod tests {
         let db = context::Context::mock_sqlite().await.db;
         let signer = seed_signer(&db, "deadbeef").await;
         let resolved = resolve_superseded_key(&db, &signer).await.unwrap();
-        assert!(resolved.is_none(), "an account that never rotated has no superseded key");
+        assert!(
+            resolved.is_none(),
+            "an account that never rotated has no superseded key"
+        );
     }
 
     #[actix_web::test]
@@ -319,7 +339,10 @@ mod tests {
         corrupt_hop_signature(&db, &f0.fingerprint, true).await;
 
         let resolved = resolve_superseded_key(&db, &signer).await;
-        assert!(resolved.is_err(), "a bad intermediate hop must abort, not resolve");
+        assert!(
+            resolved.is_err(),
+            "a bad intermediate hop must abort, not resolve"
+        );
     }
 
     #[actix_web::test]
@@ -352,7 +375,10 @@ mod tests {
         }
 
         let resolved = resolve_superseded_key(&db, &signer).await;
-        assert!(resolved.is_err(), "a chain past the hop cap is rejected, not walked forever");
+        assert!(
+            resolved.is_err(),
+            "a chain past the hop cap is rejected, not walked forever"
+        );
     }
 
     #[actix_web::test]
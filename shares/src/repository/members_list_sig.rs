@@ -53,17 +53,13 @@ pub(crate) async fn verify_post_mutation_signature<C: ConnectionTrait>(
     now: i64,
 ) -> AppResult<Vec<u8>> {
     if (now - sig.signed_at).abs() > REPLAY_WINDOW_SECONDS {
-        return Err(Error::BadRequest(
-            "members_list_signature_skew".to_string(),
-        ));
+        return Err(Error::BadRequest("members_list_signature_skew".to_string()));
     }
 
     let signer = users::Entity::find_by_id(sig.signed_by_user_id)
         .one(tx)
         .await?
-        .ok_or_else(|| {
-            Error::BadRequest("members_list_signer_not_found".to_string())
-        })?;
+        .ok_or_else(|| Error::BadRequest("members_list_signer_not_found".to_string()))?;
 
     // Only the folder owner or one of the post-mutation Co-owners is
     // authorized to sign. We accept the owner regardless of role
@@ -80,7 +76,13 @@ pub(crate) async fn verify_post_mutation_signature<C: ConnectionTrait>(
         ));
     }
 
-    let der = encode_roster(folder_id, folder_owner_id, members_after, sig.signed_at, None)?;
+    let der = encode_roster(
+        folder_id,
+        folder_owner_id,
+        members_after,
+        sig.signed_at,
+        None,
+    )?;
     let signer_type = KeyType::from_str(&signer.key_type)?;
     if signer_type
         .verify_bytes(&roster_input(&der), &sig.signature_b64, &signer.pubkey)
@@ -111,7 +113,9 @@ pub(crate) async fn verify_post_mutation_signature<C: ConnectionTrait>(
         }
     }
 
-    Err(Error::BadRequest("members_list_signature_invalid".to_string()))
+    Err(Error::BadRequest(
+        "members_list_signature_invalid".to_string(),
+    ))
 }
 
 /// Write the verified signature onto the folder's `files` row inside
@@ -179,7 +183,10 @@ fn encode_roster(
     let payload = FolderMemberListV1 {
         folder_id: folder_id.into_bytes(),
         folder_owner_id: folder_owner_id.into_bytes(),
-        members: members.iter().map(|m| m.to_list(fingerprint_override)).collect(),
+        members: members
+            .iter()
+            .map(|m| m.to_list(fingerprint_override))
+            .collect(),
         members_signed_at: signed_at,
     };
     encode_folder_member_list_v1(&payload).map_err(|e| Error::CryptoError(Box::new(e)))
@@ -261,4 +268,3 @@ fn parse_share_role(role: &str) -> AppResult<ShareRoleEnum> {
         other => Err(Error::InternalError(format!("unknown_share_role:{other}"))),
     }
 }
-
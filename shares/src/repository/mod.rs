@@ -2,6 +2,7 @@ pub(crate) mod account_deletion;
 pub(crate) mod audit;
 pub(crate) mod discover;
 pub(crate) mod discover_rate_limit;
+pub(crate) mod expiry;
 pub(crate) mod folder_members;
 pub(crate) mod fork;
 pub(crate) mod groups;
@@ -64,6 +64,10 @@ pub(super) async fn rebind_node_to_roster<C: ConnectionTrait>(
         .iter()
         .map(|r| (r.user_id, r.member_signature.clone()))
         .collect();
+    // A member whose folder share ends on a date loses the moved-in files
+    // on that same date.
+    let inherited_expiry_by_user: HashMap<Uuid, Option<i64>> =
+        roster.iter().map(|r| (r.user_id, r.expires_at)).collect();
 
     let existing_rows: Vec<user_files::Model> = user_files::Entity::find()
         .filter(user_files::Column::FileId.eq(file_id))
@@ -105,6 +109,10 @@ pub(super) async fn rebind_node_to_roster<C: ConnectionTrait>(
             .get(&entry.user_id)
             .cloned()
             .flatten();
+        let inherited_expires_at = inherited_expiry_by_user
+            .get(&entry.user_id)
+            .copied()
+            .flatten();
         if let Some(prev) = existing_by_user.get(&entry.user_id) {
             user_files::Entity::update(user_files::ActiveModel {
                 id: ActiveValue::Unchanged(prev.id),
@@ -113,10 +121,12 @@ pub(super) async fn rebind_node_to_roster<C: ConnectionTrait>(
                 shared_at: ActiveValue::Set(Some(now)),
                 shared_by_user_id: ActiveValue::Set(Some(caller_id)),
                 member_signed_at: ActiveValue::Set(
-                    inherited_member_signature.as_ref().map(|_| signed_timestamp),
+                    inherited_member_signature
+                        .as_ref()
+                        .map(|_| signed_timestamp),
                 ),
                 member_signature: ActiveValue::Set(inherited_member_signature),
-                expires_at: ActiveValue::NotSet,
+                expires_at: ActiveValue::Set(inherited_expires_at),
                 is_owner: ActiveValue::NotSet,
                 file_id: ActiveValue::Unchanged(prev.file_id),
                 user_id: ActiveValue::Unchanged(prev.user_id),
@@ -132,12 +142,14 @@ pub(super) async fn rebind_node_to_roster<C: ConnectionTrait>(
                 encrypted_key: ActiveValue::Set(entry.encrypted_key.clone()),
                 is_owner: ActiveValue::Set(false),
                 created_at: ActiveValue::Set(now),
-                expires_at: ActiveValue::Set(None),
+                expires_at: ActiveValue::Set(inherited_expires_at),
                 share_role: ActiveValue::Set(inherited_role),
                 shared_at: ActiveValue::Set(Some(now)),
                 shared_by_user_id: ActiveValue::Set(Some(caller_id)),
                 member_signed_at: ActiveValue::Set(
-                    inherited_member_signature.as_ref().map(|_| signed_timestamp),
+                    inherited_member_signature
+                        .as_ref()
+                        .map(|_| signed_timestamp),
                 ),
                 member_signature: ActiveValue::Set(inherited_member_signature),
             })
@@ -195,8 +207,15 @@ pub(super) async fn move_folder_into_shared(
     .await?;
 
     for (file_id, keys) in &parsed {
-        rebind_node_to_roster(&tx, *file_id, keys, roster.to_vec(), caller.id, signed_timestamp)
-            .await?;
+        rebind_node_to_roster(
+            &tx,
+            *file_id,
+            keys,
+            roster.to_vec(),
+            caller.id,
+            signed_timestamp,
+        )
+        .await?;
     }
 
     audit::append_event(
@@ -430,13 +449,18 @@ async fn assert_entries_match_subtree<'a, C: ConnectionTrait, I>(
 where
     I: Iterator<Item = &'a Uuid>,
 {
-    let subtree: HashSet<Uuid> = queries::file_tree_ids(db, root_id).await?.into_iter().collect();
+    let subtree: HashSet<Uuid> = queries::file_tree_ids(db, root_id)
+        .await?
+        .into_iter()
+        .collect();
     if subtree.is_empty() {
         return Err(Error::NotFound("file_not_found".to_string()));
     }
     let supplied: HashSet<Uuid> = supplied_ids.copied().collect();
     if supplied != subtree {
-        return Err(Error::BadRequest("entries_do_not_match_subtree".to_string()));
+        return Err(Error::BadRequest(
+            "entries_do_not_match_subtree".to_string(),
+        ));
     }
     Ok(())
 }
@@ -13,9 +13,7 @@ use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 
 use chrono::Utc;
-use cryptfns::asn1::{
-    encode_audit_event_sig_input_v1, AuditEventActionEnum, AuditEventSigInputV1,
-};
+use cryptfns::asn1::{encode_audit_event_sig_input_v1, AuditEventActionEnum, AuditEventSigInputV1};
 use cryptfns::identity::KeyType;
 use entity::{
     file_tokens, files,
@@ -113,13 +111,7 @@ impl Repository<'_> {
 
         let member_keys = parse_member_keys(&raw_keys)?;
         let current_member_rows = current_member_rows(self, parent_id).await?;
-        verify_member_keys_match(
-            self,
-            parent_id,
-            &member_keys,
-            &current_member_rows,
-        )
-        .await?;
+        verify_member_keys_match(self, parent_id, &member_keys, &current_member_rows).await?;
         verify_snapshot_freshness(self, parent_id, &folder, &snapshot).await?;
         verify_ownership_claim(caller.id, &member_keys)?;
 
@@ -184,7 +176,13 @@ impl Repository<'_> {
                 encrypted_key: ActiveValue::Set(entry.encrypted_key.clone()),
                 is_owner: ActiveValue::Set(entry.is_owner_of_file),
                 created_at: ActiveValue::Set(now),
-                expires_at: ActiveValue::Set(None),
+                // Members with a time-limited folder share lose the new
+                // file together with the folder.
+                expires_at: ActiveValue::Set(if entry.is_owner_of_file {
+                    None
+                } else {
+                    by_user.get(&entry.user_id).and_then(|r| r.expires_at)
+                }),
                 share_role: ActiveValue::Set(inherited),
                 shared_at: ActiveValue::Set(if entry.is_owner_of_file {
                     None
@@ -199,7 +197,9 @@ impl Repository<'_> {
                 member_signed_at: ActiveValue::Set(if entry.is_owner_of_file {
                     None
                 } else {
-                    inherited_member_signature.as_ref().map(|_| signed_timestamp)
+                    inherited_member_signature
+                        .as_ref()
+                        .map(|_| signed_timestamp)
                 }),
                 member_signature: ActiveValue::Set(if entry.is_owner_of_file {
                     None
@@ -413,7 +413,9 @@ impl Repository<'_> {
         // move route guards the same way with `cannot_move_to_itself`.
         let source_subtree = queries::file_tree_ids(&self.context.db, file_id).await?;
         if source_subtree.contains(&dest_id) {
-            return Err(Error::BadRequest("cannot_move_into_own_subtree".to_string()));
+            return Err(Error::BadRequest(
+                "cannot_move_into_own_subtree".to_string(),
+            ));
         }
 
         let current_member_rows = current_member_rows(self, dest_id).await?;
@@ -464,8 +466,7 @@ impl Repository<'_> {
                     return Err(Error::BadRequest("member_keys_empty".to_string()));
                 }
                 let member_keys = parse_member_keys(&raw_keys)?;
-                verify_member_keys_match(self, dest_id, &member_keys, &current_member_rows)
-                    .await?;
+                verify_member_keys_match(self, dest_id, &member_keys, &current_member_rows).await?;
 
                 let tx = self.context.db.begin().await?;
                 files::Entity::update(files::ActiveModel {
@@ -525,7 +526,9 @@ pub(super) fn parse_member_keys(raw: &[MemberKey]) -> AppResult<Vec<ParsedMember
             return Err(Error::BadRequest("member_key_duplicate".to_string()));
         }
         if encrypted_key.is_empty() {
-            return Err(Error::BadRequest("member_key_encrypted_key_empty".to_string()));
+            return Err(Error::BadRequest(
+                "member_key_encrypted_key_empty".to_string(),
+            ));
         }
         out.push(ParsedMemberKey {
             user_id,
@@ -549,8 +552,8 @@ pub(super) fn verify_event_signature(
     signature_b64: &str,
     signer: &users::Model,
 ) -> AppResult<()> {
-    let der = encode_audit_event_sig_input_v1(sig_input)
-        .map_err(|e| Error::CryptoError(Box::new(e)))?;
+    let der =
+        encode_audit_event_sig_input_v1(sig_input).map_err(|e| Error::CryptoError(Box::new(e)))?;
     let mut signing_input =
         Vec::with_capacity(cryptfns::asn1::AUDIT_EVENT_SIG_V1_PREFIX.len() + der.len());
     signing_input.extend_from_slice(cryptfns::asn1::AUDIT_EVENT_SIG_V1_PREFIX);
@@ -671,7 +674,11 @@ fn build_new_file(
         name_hash: ActiveValue::Set(body.name_hash.clone().unwrap_or_default()),
         encrypted_name: ActiveValue::Set(body.encrypted_name.clone().unwrap_or_default()),
         encrypted_thumbnail: ActiveValue::Set(body.encrypted_thumbnail.clone()),
-        mime: ActiveValue::Set(body.mime.clone().unwrap_or_else(|| "text/plain".to_string())),
+        mime: ActiveValue::Set(
+            body.mime
+                .clone()
+                .unwrap_or_else(|| "text/plain".to_string()),
+        ),
         size: ActiveValue::Set(body.size),
         chunks: ActiveValue::Set(body.chunks),
         chunks_stored: ActiveValue::Set(Some(0)),
@@ -680,9 +687,16 @@ fn build_new_file(
         sha1: ActiveValue::Set(body.sha1.clone()),
         sha256: ActiveValue::Set(body.sha256.clone()),
         blake2b: ActiveValue::Set(body.blake2b.clone()),
-        cipher: ActiveValue::Set(body.cipher.clone().unwrap_or_else(|| "ascon128a".to_string())),
+        cipher: ActiveValue::Set(
+            body.cipher
+                .clone()
+                .unwrap_or_else(|| "ascon128a".to_string()),
+        ),
         editable: ActiveValue::Set(body.editable.unwrap_or(false)),
-        file_modified_at: ActiveValue::Set(parse_modified_at(body.file_modified_at.as_deref(), now)),
+        file_modified_at: ActiveValue::Set(parse_modified_at(
+            body.file_modified_at.as_deref(),
+            now,
+        )),
         created_at: ActiveValue::Set(now),
         finished_upload_at: ActiveValue::Set(None),
         active_version: ActiveValue::Set(1),
@@ -693,6 +707,10 @@ fn build_new_file(
         members_list_signature: ActiveValue::Set(None),
         members_list_signed_at: ActiveValue::Set(None),
         members_list_signed_by_user_id: ActiveValue::Set(None),
+        deleted_at: ActiveValue::Set(None),
+        deleted_parent_id: ActiveValue::Set(None),
+        last_chunk_at: ActiveValue::Set(None),
+        damaged_at: ActiveValue::Set(None),
     }
 }
 
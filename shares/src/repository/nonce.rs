@@ -12,8 +12,7 @@ type Cache = Mutex<TimedSizedCache<(Uuid, [u8; 16]), i64>>;
 static NONCE_CACHE: OnceLock<Cache> = OnceLock::new();
 
 fn cache() -> &'static Cache {
-    NONCE_CACHE
-        .get_or_init(|| Mutex::new(TimedSizedCache::with_size_and_lifespan(100_000, 600)))
+    NONCE_CACHE.get_or_init(|| Mutex::new(TimedSizedCache::with_size_and_lifespan(100_000, 600)))
 }
 
 /// True iff the `(sender, nonce)` pair was seen within the 10-minute
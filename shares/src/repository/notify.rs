@@ -38,28 +38,16 @@ const TEMPLATE: &str = r#"
 /// recipient's preference allow it. Failures are logged and swallowed —
 /// the share row is already committed and a flaky SMTP relay must not
 /// roll it back.
-pub(crate) async fn share_created(
-    ctx: &Context,
-    sender: &users::Model,
-    recipient: &users::Model,
-) {
+pub(crate) async fn share_created(ctx: &Context, sender: &users::Model, recipient: &users::Model) {
     if !recipient.share_notifications_enabled {
         return;
     }
     if let Err(e) = dispatch(ctx, sender, recipient).await {
-        log::warn!(
-            "share notification to {} failed: {}",
-            recipient.email,
-            e
-        );
+        log::warn!("share notification to {} failed: {}", recipient.email, e);
     }
 }
 
-async fn dispatch(
-    ctx: &Context,
-    sender: &users::Model,
-    recipient: &users::Model,
-) -> AppResult<()> {
+async fn dispatch(ctx: &Context, sender: &users::Model, recipient: &users::Model) -> AppResult<()> {
     let mailer = match &ctx.sender {
         Some(s) => s,
         None => return Ok(()),
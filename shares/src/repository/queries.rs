@@ -7,8 +7,8 @@ use std::collections::{HashMap, HashSet};
 
 use entity::{
     files, key_transitions, share_events, user_files, users, ColumnTrait, ConnectionTrait, DbErr,
-    EntityTrait, Expr, FromQueryResult, IntoCondition, JoinType, Order, PaginatorTrait, QueryFilter,
-    QueryOrder, QueryResult, QuerySelect, RelationTrait, Uuid,
+    EntityTrait, Expr, FromQueryResult, IntoCondition, JoinType, Order, PaginatorTrait,
+    QueryFilter, QueryOrder, QueryResult, QuerySelect, RelationTrait, Uuid,
 };
 use error::AppResult;
 
@@ -59,6 +59,7 @@ pub(crate) async fn recipient_list<C: ConnectionTrait>(
                 share_role: row.share_role,
                 created_at: row.created_at,
                 shared_at: row.shared_at,
+                expires_at: row.expires_at,
                 shared_by_user_id: row.shared_by_user_id,
                 shared_by_email: granter_email,
             }
@@ -86,9 +87,19 @@ pub(crate) async fn incoming_for_recipient<C: ConnectionTrait>(
     offset: u64,
     compact: bool,
 ) -> AppResult<(Vec<IncomingShare>, u64)> {
+    // Files the owner moved to the trash drop out of the recipient's view
+    // until they are restored, expired shares for good.
+    let now = chrono::Utc::now().timestamp();
     let mut all_query = user_files::Entity::find()
+        .inner_join(files::Entity)
         .filter(user_files::Column::UserId.eq(recipient_id))
-        .filter(user_files::Column::IsOwner.eq(false));
+        .filter(user_files::Column::IsOwner.eq(false))
+        .filter(files::Column::DeletedAt.is_null())
+        .filter(
+            user_files::Column::ExpiresAt
+                .is_null()
+                .or(user_files::Column::ExpiresAt.gt(now)),
+        );
     if let Some(sender) = sender_filter {
         all_query = all_query.filter(user_files::Column::SharedByUserId.eq(sender));
     }
@@ -99,8 +110,7 @@ pub(crate) async fn incoming_for_recipient<C: ConnectionTrait>(
         .await?;
 
     let recipient_file_ids: HashSet<Uuid> = all_rows.iter().map(|r| r.file_id).collect();
-    let parents_by_file =
-        parent_pointers_for_files(db, recipient_file_ids.iter().copied()).await?;
+    let parents_by_file = parent_pointers_for_files(db, recipient_file_ids.iter().copied()).await?;
 
     let mut root_rows: Vec<user_files::Model> = all_rows
         .into_iter()
@@ -160,14 +170,13 @@ pub(crate) async fn incoming_for_recipient<C: ConnectionTrait>(
                 encrypted_key: row.encrypted_key,
                 created_at: row.created_at,
                 shared_at: row.shared_at,
+                expires_at: row.expires_at,
                 owner_id: owner.map(|u| u.id).unwrap_or(Uuid::nil()),
                 owner_email: owner.map(|u| u.email.clone()).unwrap_or_default(),
                 owner_pubkey: owner.map(|u| u.pubkey.clone()).unwrap_or_default(),
                 owner_key_type: owner.map(|u| u.key_type.clone()).unwrap_or_default(),
                 owner_wrapping_pubkey: owner.and_then(|u| u.wrapping_pubkey.clone()),
-                owner_pubkey_fingerprint: owner
-                    .map(|u| u.fingerprint.clone())
-                    .unwrap_or_default(),
+                owner_pubkey_fingerprint: owner.map(|u| u.fingerprint.clone()).unwrap_or_default(),
                 shared_by_user_id: row.shared_by_user_id,
                 shared_by_email: granter_email,
             }
@@ -219,7 +228,9 @@ pub(crate) async fn events_for_user<C: ConnectionTrait>(
     // join's ON clause — pushing it into WHERE would drop rows whose
     // caller has no wrap (revoked recipient), which is exactly the
     // bare-id fallback we want the client to render.
-    let mut query = share_events::Entity::find().filter(visibility).select_only();
+    let mut query = share_events::Entity::find()
+        .filter(visibility)
+        .select_only();
     entity::join::add_columns_with_prefix::<_, share_events::Entity>(&mut query, "evt");
     query = query
         .column_as(files::Column::EncryptedName, "file_encrypted_name")
@@ -283,12 +294,7 @@ pub(crate) async fn events_for_user<C: ConnectionTrait>(
     Ok((
         rows.into_iter()
             .map(|r| {
-                AppShareEvent::from_parts(
-                    r.model,
-                    r.encrypted_name,
-                    r.cipher,
-                    r.encrypted_key,
-                )
+                AppShareEvent::from_parts(r.model, r.encrypted_name, r.cipher, r.encrypted_key)
             })
             .collect(),
         users,
@@ -497,42 +503,9 @@ pub(crate) async fn file_tree_owner_ids<C: ConnectionTrait>(
 
 /// File subtree rooted at `root_file_id`, used for revoke cascade. Returns
 /// every descendant file id (folders + leaves) plus the root itself.
-///
-/// The `depth` guard bounds the recursion: callers reject parent cycles
-/// before they can be written (see `move_into_shared`), and this cap is the
-/// backstop so a cycle that ever did exist degrades to a bounded walk on
-/// both SQLite and Postgres instead of looping forever. Real folder nesting
-/// is nowhere near 10000 levels deep.
 pub(crate) async fn file_tree_ids<C: ConnectionTrait>(
     db: &C,
     root_file_id: Uuid,
 ) -> AppResult<Vec<Uuid>> {
-    let sql = r#"
-        WITH RECURSIVE file_tree(id, file_id, depth) AS (
-            SELECT id, file_id, 0 FROM files WHERE id = $1
-            UNION ALL
-            SELECT child.id, child.file_id, parent.depth + 1 FROM files child
-            JOIN file_tree parent ON parent.id = child.file_id
-            WHERE parent.depth < 10000
-        )
-        SELECT id FROM file_tree;
-    "#;
-    let rows = files::Entity::find()
-        .from_raw_sql(entity::Statement::from_sql_and_values(
-            db.get_database_backend(),
-            sql,
-            [root_file_id.into()],
-        ))
-        .into_json()
-        .all(db)
-        .await?;
-    let mut ids = Vec::with_capacity(rows.len());
-    for row in rows {
-        if let Some(id_str) = row.get("id").and_then(|v| v.as_str()) {
-            if let Ok(id) = Uuid::parse_str(id_str) {
-                ids.push(id);
-            }
-        }
-    }
-    Ok(ids)
+    files::subtree_ids(db, root_file_id).await
 }
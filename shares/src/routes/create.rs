@@ -1,12 +1,10 @@
 use actix_web::{route, web, HttpResponse};
-use auth::data::authenticated::Authenticated;
+use auth::data::claims::Claims;
 use context::Context;
 use error::AppResult;
 use serde_json::json;
 
-use crate::{
-    data::create_share::CreateShareEnvelope, repository::Repository, routes::gate,
-};
+use crate::{data::create_share::CreateShareEnvelope, repository::Repository, routes::gate};
 
 /// `POST /api/shares` — idempotent grant or role change. Body is the
 /// JSON envelope: `{ payload_der, signature,
@@ -14,16 +12,25 @@ use crate::{
 #[route("/api/shares", method = "POST")]
 pub(crate) async fn create(
     context: web::Data<Context>,
-    authenticated: Authenticated,
+    claims: Claims,
     body: web::Json<CreateShareEnvelope>,
 ) -> AppResult<HttpResponse> {
     let context = context.into_inner();
     gate::ensure_enabled(&context).await?;
 
+    let body = body.into_inner();
+    for entry in body.entries.iter().flatten() {
+        claims
+            .require_folder(
+                &context,
+                entity::option_string_to_uuid(entry.file_id.clone()),
+            )
+            .await?;
+    }
+    let user = claims.user(&context).await?;
+
     let repository = Repository::new(&context);
-    let result = repository
-        .create_share(body.into_inner(), &authenticated.user)
-        .await?;
+    let result = repository.create_share(body, &user).await?;
 
     Ok(HttpResponse::Created().json(json!({ "shares": result.shares })))
 }
@@ -1,12 +1,10 @@
 use actix_web::{route, web, HttpRequest, HttpResponse};
-use auth::data::authenticated::Authenticated;
+use auth::data::claims::Claims;
 use context::Context;
 use entity::Uuid;
 use error::AppResult;
 
-use crate::{
-    data::create_share::RevokeShareBody, repository::Repository, routes::gate,
-};
+use crate::{data::create_share::RevokeShareBody, repository::Repository, routes::gate};
 
 /// `DELETE /api/shares/{file_id}/{user_id}` — revoke. Co-owner / owner
 /// only. Body carries the signed audit-event proof; the cascade fan-out
@@ -16,7 +14,7 @@ use crate::{
 pub(crate) async fn delete(
     req: HttpRequest,
     context: web::Data<Context>,
-    authenticated: Authenticated,
+    claims: Claims,
     body: Option<web::Json<RevokeShareBody>>,
 ) -> AppResult<HttpResponse> {
     let context = context.into_inner();
@@ -26,9 +24,10 @@ pub(crate) async fn delete(
     let recipient_id: Uuid = util::actix::path_var(&req, "user_id")?;
     let body = body.map(|b| b.into_inner()).unwrap_or_default();
 
+    let user = claims.user(&context).await?;
     let repository = Repository::new(&context);
     repository
-        .revoke_share(body, &authenticated.user, file_id, recipient_id)
+        .revoke_share(body, &user, file_id, recipient_id)
         .await?;
 
     Ok(HttpResponse::NoContent().finish())
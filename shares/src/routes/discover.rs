@@ -1,5 +1,5 @@
 use actix_web::{route, web, HttpResponse};
-use auth::data::authenticated::Authenticated;
+use auth::data::claims::Claims;
 use context::Context;
 use error::AppResult;
 
@@ -11,7 +11,7 @@ use crate::{data::discover::DiscoverQuery, repository::Repository, routes::gate}
 #[route("/api/users/discover", method = "GET")]
 pub(crate) async fn discover(
     context: web::Data<Context>,
-    authenticated: Authenticated,
+    claims: Claims,
     query: web::Query<DiscoverQuery>,
 ) -> AppResult<HttpResponse> {
     let context = context.into_inner();
@@ -19,10 +19,9 @@ pub(crate) async fn discover(
 
     let email = query.into_inner().email.unwrap_or_default();
     let now = chrono::Utc::now().timestamp();
+    let user = claims.user(&context).await?;
     let repository = Repository::new(&context);
-    let user = repository
-        .discover_user(&authenticated.user, &email, now)
-        .await?;
+    let user = repository.discover_user(&user, &email, now).await?;
 
     Ok(HttpResponse::Ok().json(user))
 }
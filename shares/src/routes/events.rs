@@ -32,8 +32,7 @@ pub(crate) async fn events(
 
     let file_id = match query.file_id.as_deref() {
         Some(value) => Some(
-            Uuid::parse_str(value)
-                .map_err(|_| Error::BadRequest("file_id_invalid".to_string()))?,
+            Uuid::parse_str(value).map_err(|_| Error::BadRequest("file_id_invalid".to_string()))?,
         ),
         None => None,
     };
@@ -5,9 +5,7 @@ use entity::Uuid;
 use error::AppResult;
 use serde_json::json;
 
-use crate::{
-    data::multikey_upload::EvictFromFolderBody, repository::Repository, routes::gate,
-};
+use crate::{data::multikey_upload::EvictFromFolderBody, repository::Repository, routes::gate};
 
 /// `POST /api/storage/{file_id}/evict-from-folder`.
 /// Folder-owner-only. The contributor keeps the file in their drive
@@ -1,5 +1,5 @@
 use actix_web::{route, web, HttpRequest, HttpResponse};
-use auth::data::authenticated::Authenticated;
+use auth::data::claims::Claims;
 use context::Context;
 use entity::Uuid;
 use error::AppResult;
@@ -12,16 +12,15 @@ use crate::{repository::Repository, routes::gate};
 pub(crate) async fn list(
     req: HttpRequest,
     context: web::Data<Context>,
-    authenticated: Authenticated,
+    claims: Claims,
 ) -> AppResult<HttpResponse> {
     let context = context.into_inner();
     gate::ensure_enabled(&context).await?;
 
     let file_id: Uuid = util::actix::path_var(&req, "file_id")?;
+    let user = claims.user(&context).await?;
     let repository = Repository::new(&context);
-    let shares = repository
-        .recipient_list(&authenticated.user, file_id)
-        .await?;
+    let shares = repository.recipient_list(&user, file_id).await?;
 
     Ok(HttpResponse::Ok().json(shares))
 }
@@ -6,7 +6,7 @@ use error::AppResult;
 
 use crate::{
     contracts::shares::IncomingQueryExt,
-    data::incoming::{IncomingShareQuery, IncomingSharePage},
+    data::incoming::{IncomingSharePage, IncomingShareQuery},
     repository::queries,
     routes::gate,
 };
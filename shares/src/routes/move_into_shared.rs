@@ -4,9 +4,7 @@ use context::Context;
 use error::AppResult;
 use serde_json::json;
 
-use crate::{
-    data::multikey_upload::MoveIntoSharedBody, repository::Repository, routes::gate,
-};
+use crate::{data::multikey_upload::MoveIntoSharedBody, repository::Repository, routes::gate};
 
 /// `POST /api/storage/move-into-shared`. Relocates
 /// a file the caller owns into a shared folder, re-wrapping the file
@@ -4,9 +4,7 @@ use context::Context;
 use error::AppResult;
 use serde_json::json;
 
-use crate::{
-    data::multikey_upload::MoveOutOfSharedBody, repository::Repository, routes::gate,
-};
+use crate::{data::multikey_upload::MoveOutOfSharedBody, repository::Repository, routes::gate};
 
 /// `POST /api/storage/move-out-of-shared`. The file
 /// owner detaches their own file or folder subtree from a shared folder,
@@ -4,9 +4,7 @@ use context::Context;
 use error::AppResult;
 use serde_json::json;
 
-use crate::{
-    data::multikey_upload::UploadMultikeyBody, repository::Repository, routes::gate,
-};
+use crate::{data::multikey_upload::UploadMultikeyBody, repository::Repository, routes::gate};
 
 /// `POST /api/storage/upload-multikey`. Lives on the
 /// shares route surface because the validation is sharing-aware and the
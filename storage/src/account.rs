@@ -0,0 +1,719 @@
+//! Account export and import, moving everything a user owns from one
+//! instance to another. The archive layout is described in
+//! [`crate::data::account`].
+//!
+//! Import gives every file and version a new id, so an archive can be
+//! imported next to existing data. The rows are only inserted once all
+//! chunks are stored; a failed import removes the chunks it wrote. Links
+//! are not imported: the owner's signature on a link covers the file id,
+//! so the import reports the files that had links for the owner to create
+//! them again.
+
+use std::{
+    collections::{BTreeSet, HashMap, HashSet, VecDeque},
+    fmt::Display,
+    sync::Arc,
+};
+
+use actix_web::web::Bytes;
+use chrono::Utc;
+use context::Context;
+use entity::{file_chunk_refs, files, users, EntityTrait, TransactionTrait, Uuid};
+use error::{AppResult, Error};
+use fs::{
+    prelude::*,
+    tar::{parse_next_entry, tar_header, tar_padding_len, TarStep, TAR_END_OF_ARCHIVE_LEN},
+    MAX_CHUNK_PAYLOAD_BYTES, MAX_CHUNK_SIZE_BYTES,
+};
+use futures::{Stream, StreamExt};
+
+use crate::{
+    data::account::{Account, Archive, ExportedFile, Imported, ARCHIVE_MANIFEST, FORMAT},
+    repository::Repository,
+};
+
+/// Largest `account.json` an import accepts. It only holds metadata, but
+/// for every file the account owns, thumbnails included.
+const MAX_MANIFEST_BYTES: usize = 256 * 1024 * 1024;
+
+/// Largest buffer held while reading chunk entries, one padded chunk with
+/// headroom for a header straddling two reads.
+const MAX_BUFFER_BYTES: usize = (MAX_CHUNK_PAYLOAD_BYTES as usize) + 16 * 1024;
+
+/// Bytes a stored chunk may hold beyond its share of the declared size:
+/// sizes are of the plaintext, chunks carry the cipher's tag and nonce on
+/// top. Same slack the upload routes allow per chunk.
+const CHUNK_OVERHEAD_BYTES: u64 = MAX_CHUNK_PAYLOAD_BYTES - MAX_CHUNK_SIZE_BYTES;
+
+/// What the archive declares for one version of a file. An import refuses
+/// chunks past `chunks` and bytes past `size`.
+#[derive(Debug, Clone, Copy, Default)]
+struct Declared {
+    chunks: i64,
+    size: i64,
+}
+
+impl Declared {
+    fn new(chunks: Option<i64>, size: Option<i64>) -> Self {
+        Self {
+            chunks: chunks.unwrap_or(0).max(0),
+            size: size.unwrap_or(0).max(0),
+        }
+    }
+
+    /// Most bytes the chunks of the version can hold.
+    fn max_bytes(&self) -> u64 {
+        (self.size as u64).saturating_add((self.chunks as u64).saturating_mul(CHUNK_OVERHEAD_BYTES))
+    }
+}
+
+/// The rows of an archive under their new ids, keyed by the file id in the
+/// archive.
+#[derive(Debug, Default)]
+struct Renumbered {
+    files: HashMap<Uuid, files::Model>,
+    /// Versions each file may have chunks for.
+    versions: HashMap<Uuid, HashMap<i32, Declared>>,
+    /// Files that were in the trash when the account was exported. They
+    /// are left out of the import, their chunks are skipped.
+    trashed: HashSet<Uuid>,
+}
+
+/// Chunks an import wrote, keyed by the file id in the archive and the
+/// version, `active_version` for files in the legacy layout.
+#[derive(Debug, Default)]
+struct Stored {
+    chunks: HashMap<(Uuid, i32), HashSet<i64>>,
+    bytes: HashMap<(Uuid, i32), u64>,
+    total_chunks: u64,
+    total_bytes: u64,
+}
+
+/// One chunk entry of the export stream.
+struct Chunk {
+    name: String,
+    /// Shared by every chunk of the file, with its chunk references
+    /// attached when it is versioned.
+    file: Arc<Filename>,
+    version: Option<i32>,
+    chunk: i64,
+}
+
+/// Stream everything `user_id` owns as an account archive. The metadata is
+/// read up front, chunks are read from storage as the stream is consumed.
+pub(crate) async fn export(context: Arc<Context>, user_id: Uuid) -> AppResult<Streamer> {
+    let user = users::Entity::find_by_id(user_id)
+        .one(&context.db)
+        .await?
+        .ok_or_else(|| Error::NotFound("user_not_found".to_string()))?;
+
+    let repository = Repository::new(&context.db);
+    let files = repository.account_files(user_id).await?;
+    let versions = repository.account_versions(user_id).await?;
+    let links = repository.account_links(user_id).await?;
+
+    let storage = Fs::new(&context.config);
+    let mut chunks = VecDeque::new();
+
+    for ExportedFile { file, .. } in &files {
+        if file.mime == "dir" {
+            continue;
+        }
+
+        if file.editable {
+            let mut numbers: BTreeSet<i32> = versions
+                .iter()
+                .filter(|version| version.file_id == file.id)
+                .map(|version| version.version)
+                .collect();
+            numbers.insert(file.active_version);
+
+            let name = Arc::new(file_chunk_refs::filename(&context.db, file.id, file).await?);
+            for version in numbers {
+                for chunk in storage.get_uploaded_chunks_v(&*name, version).await? {
+                    chunks.push_back(Chunk {
+                        name: format!("chunks/{}/v{}/{:06}.enc", file.id, version, chunk),
+                        file: name.clone(),
+                        version: Some(version),
+                        chunk,
+                    });
+                }
+            }
+        } else {
+            let name = Arc::new(file.filename()?);
+            for chunk in storage.get_uploaded_chunks(&*name).await? {
+                chunks.push_back(Chunk {
+                    name: format!("chunks/{}/{:06}.enc", file.id, chunk),
+                    file: name.clone(),
+                    version: None,
+                    chunk,
+                });
+            }
+        }
+    }
+
+    let archive = Archive {
+        format: FORMAT,
+        exported_at: Utc::now().timestamp(),
+        account: Account::from(&user),
+        files,
+        versions,
+        links,
+    };
+    let manifest = serde_json::to_vec(&archive)?;
+
+    struct State {
+        context: Arc<Context>,
+        manifest: Option<Vec<u8>>,
+        chunks: VecDeque<Chunk>,
+        finished: bool,
+    }
+
+    let state = State {
+        context,
+        manifest: Some(manifest),
+        chunks,
+        finished: false,
+    };
+
+    let stream = futures::stream::unfold(state, |mut state| async move {
+        if let Some(manifest) = state.manifest.take() {
+            return Some((Ok(tar_entry(ARCHIVE_MANIFEST, &manifest)), state));
+        }
+
+        match state.chunks.pop_front() {
+            Some(entry) => {
+                let storage = Fs::new(&state.context.config);
+                let data = match entry.version {
+                    Some(version) => storage.pull_v(&*entry.file, version, entry.chunk).await,
+                    None => storage.pull(&*entry.file, entry.chunk).await,
+                };
+
+                Some((data.map(|data| tar_entry(&entry.name, &data)), state))
+            }
+            None if !state.finished => {
+                state.finished = true;
+                Some((Ok(Bytes::from(vec![0u8; TAR_END_OF_ARCHIVE_LEN])), state))
+            }
+            None => None,
+        }
+    });
+
+    Ok(Streamer::new(stream))
+}
+
+/// Header, data and padding of a single tar entry.
+fn tar_entry(name: &str, data: &[u8]) -> Bytes {
+    let padding = tar_padding_len(data.len() as u64);
+    let mut entry = Vec::with_capacity(512 + data.len() + padding);
+    entry.extend_from_slice(&tar_header(name, data.len() as u64));
+    entry.extend_from_slice(data);
+    entry.resize(entry.len() + padding, 0);
+
+    Bytes::from(entry)
+}
+
+/// Read an account archive from `body` and recreate its files and versions
+/// for `user_id` under new ids.
+pub(crate) async fn import<S, E>(
+    context: &Context,
+    user_id: Uuid,
+    quota: Option<u64>,
+    mut body: S,
+) -> AppResult<Imported>
+where
+    S: Stream<Item = Result<Bytes, E>> + Unpin,
+    E: Display,
+{
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut archive = loop {
+        match parse_next_entry(&buffer) {
+            TarStep::Entry {
+                name,
+                data,
+                consumed,
+            } => {
+                if name != ARCHIVE_MANIFEST {
+                    return Err(Error::BadRequest(format!(
+                        "account_archive_missing_manifest: {name}"
+                    )));
+                }
+
+                let archive = serde_json::from_slice::<Archive>(data)
+                    .map_err(|e| Error::BadRequest(format!("account_archive_malformed: {e}")))?;
+                buffer.drain(..consumed);
+
+                break archive;
+            }
+            TarStep::End => {
+                return Err(Error::BadRequest(
+                    "account_archive_missing_manifest".to_string(),
+                ))
+            }
+            TarStep::Malformed(reason) => {
+                return Err(Error::BadRequest(format!("tar_malformed: {reason}")));
+            }
+            TarStep::NeedMoreData => {
+                if buffer.len() > MAX_MANIFEST_BYTES {
+                    return Err(Error::BadRequest(
+                        "account_archive_manifest_too_large".to_string(),
+                    ));
+                }
+
+                match body.next().await {
+                    Some(bytes) => buffer.extend_from_slice(
+                        &bytes
+                            .map_err(|e| Error::BadRequest(format!("payload_read_failed: {e}")))?,
+                    ),
+                    None => return Err(Error::BadRequest("tar_truncated".to_string())),
+                }
+            }
+        }
+    };
+
+    if archive.format != FORMAT {
+        return Err(Error::BadRequest(format!(
+            "account_archive_unsupported_format: {}",
+            archive.format
+        )));
+    }
+
+    let user = users::Entity::find_by_id(user_id)
+        .one(&context.db)
+        .await?
+        .ok_or_else(|| Error::NotFound("user_not_found".to_string()))?;
+
+    // File keys are wrapped to the exported account's public key, they
+    // are only of use to an account holding the same key pair.
+    if user.fingerprint != archive.account.fingerprint {
+        return Err(Error::BadRequest("account_key_mismatch".to_string()));
+    }
+
+    let budget = enforce_quota(context, user_id, quota, &archive).await?;
+
+    let renumbered = renumber(&mut archive, user_id);
+    let storage = Fs::new(&context.config);
+
+    let imported = match store_chunks(&storage, &renumbered, budget, buffer, body).await {
+        Ok(stored) => insert(context, user_id, archive, &renumbered, stored).await,
+        Err(e) => Err(e),
+    };
+
+    if imported.is_err() {
+        for file in renumbered.files.values() {
+            if file.mime == "dir" {
+                continue;
+            }
+
+            if let Err(e) = storage.purge_all(file).await {
+                log::warn!(
+                    "Failed to remove chunks of failed import for file {}: {}",
+                    file.id,
+                    e
+                );
+            }
+        }
+    }
+
+    imported
+}
+
+/// Refuse an archive whose declared sizes do not fit the quotas. Returns
+/// the space left under the tightest quota, `None` without one; the bytes
+/// actually written are held against it as the chunks come in, the
+/// declared sizes are only the client's word.
+async fn enforce_quota(
+    context: &Context,
+    user_id: Uuid,
+    quota: Option<u64>,
+    archive: &Archive,
+) -> AppResult<Option<u64>> {
+    let size: i64 = archive
+        .files
+        .iter()
+        .filter_map(|f| f.file.size)
+        .map(|size| size.max(0))
+        .fold(0, i64::saturating_add);
+    let repository = Repository::new(&context.db);
+    let mut budget: Option<u64> = None;
+
+    if let Some(quota) = quota {
+        let used = repository.query(user_id).used_space().await?;
+        if used.saturating_add(size) > quota as i64 {
+            return Err(Error::BadRequest("quota_exceeded".to_string()));
+        }
+
+        budget = Some(quota.saturating_sub(used.max(0) as u64));
+    }
+
+    if let Some(instance_quota) = context.config.app.storage_instance_quota_bytes {
+        let used = repository.instance_used_space().await?;
+        if used.saturating_add(size) > instance_quota as i64 {
+            return Err(Error::BadRequest("quota_exceeded".to_string()));
+        }
+
+        let left = instance_quota.saturating_sub(used.max(0) as u64);
+        budget = Some(budget.map_or(left, |budget| budget.min(left)));
+    }
+
+    Ok(budget)
+}
+
+/// Give every file, version and link a new id and point the references
+/// between them at the new ids. References to anything outside the archive,
+/// a parent folder someone else owns or another user, are dropped, and so
+/// is whatever was in the trash.
+fn renumber(archive: &mut Archive, user_id: Uuid) -> Renumbered {
+    let mut renumbered = Renumbered::default();
+
+    archive.files.retain(|f| {
+        if f.file.deleted_at.is_some() {
+            renumbered.trashed.insert(f.file.id);
+        }
+
+        f.file.deleted_at.is_none()
+    });
+
+    let ids: HashMap<Uuid, Uuid> = archive
+        .files
+        .iter()
+        .map(|f| (f.file.id, Uuid::new_v4()))
+        .collect();
+    let old_user_id = archive.account.id;
+
+    for ExportedFile { file, .. } in archive.files.iter_mut() {
+        let old_id = file.id;
+
+        file.id = ids[&old_id];
+        file.file_id = file.file_id.and_then(|id| ids.get(&id).copied());
+
+        renumbered
+            .versions
+            .entry(old_id)
+            .or_default()
+            .insert(file.active_version, Declared::new(file.chunks, file.size));
+        renumbered.files.insert(old_id, file.clone());
+    }
+
+    archive
+        .versions
+        .retain(|version| ids.contains_key(&version.file_id));
+    for version in archive.versions.iter_mut() {
+        renumbered
+            .versions
+            .entry(version.file_id)
+            .or_default()
+            .entry(version.version)
+            .or_insert_with(|| Declared::new(Some(version.chunks), Some(version.size)));
+
+        version.id = Uuid::new_v4();
+        version.file_id = ids[&version.file_id];
+        version.user_id = version
+            .user_id
+            .filter(|id| *id == old_user_id)
+            .map(|_| user_id);
+    }
+
+    archive.links.retain(|link| ids.contains_key(&link.file_id));
+    for link in archive.links.iter_mut() {
+        link.file_id = ids[&link.file_id];
+    }
+
+    renumbered
+}
+
+/// Write the chunk entries following `account.json` into storage under the
+/// new file ids. Every chunk must belong to a version the archive declares
+/// and fall within its chunk count, and the bytes written may not outgrow
+/// the declared size of the version nor `budget` overall. Chunks of
+/// trashed files are skipped.
+async fn store_chunks<S, E>(
+    storage: &Fs<'_>,
+    renumbered: &Renumbered,
+    budget: Option<u64>,
+    mut buffer: Vec<u8>,
+    mut body: S,
+) -> AppResult<Stored>
+where
+    S: Stream<Item = Result<Bytes, E>> + Unpin,
+    E: Display,
+{
+    let mut stored = Stored::default();
+
+    loop {
+        match parse_next_entry(&buffer) {
+            TarStep::Entry {
+                name,
+                data,
+                consumed,
+            } => {
+                let (old_id, version, chunk) = parse_chunk_name(&name)?;
+                if renumbered.trashed.contains(&old_id) {
+                    buffer.drain(..consumed);
+                    continue;
+                }
+
+                let file = renumbered
+                    .files
+                    .get(&old_id)
+                    .filter(|file| file.mime != "dir")
+                    .ok_or_else(|| {
+                        Error::BadRequest(format!("account_archive_unknown_file: {name}"))
+                    })?;
+
+                if data.len() as u64 > MAX_CHUNK_PAYLOAD_BYTES {
+                    return Err(Error::as_validation("chunk", "chunk_size_exceeds_max"));
+                }
+
+                let unexpected =
+                    || Error::BadRequest(format!("account_archive_unexpected_chunk: {name}"));
+                let key_version = match version {
+                    Some(version) if file.editable => version,
+                    None if !file.editable => file.active_version,
+                    _ => return Err(unexpected()),
+                };
+                let declared = renumbered
+                    .versions
+                    .get(&old_id)
+                    .and_then(|versions| versions.get(&key_version))
+                    .ok_or_else(unexpected)?;
+
+                if chunk >= declared.chunks {
+                    return Err(unexpected());
+                }
+
+                let key = (old_id, key_version);
+                if !stored.chunks.entry(key).or_default().insert(chunk) {
+                    return Err(Error::BadRequest(format!(
+                        "account_archive_duplicate_chunk: {name}"
+                    )));
+                }
+
+                let bytes = stored.bytes.entry(key).or_default();
+                *bytes += data.len() as u64;
+                if *bytes > declared.max_bytes() {
+                    return Err(Error::BadRequest(format!(
+                        "account_archive_size_mismatch: {name}"
+                    )));
+                }
+
+                stored.total_bytes += data.len() as u64;
+                if budget.is_some_and(|budget| stored.total_bytes > budget) {
+                    return Err(Error::BadRequest("quota_exceeded".to_string()));
+                }
+
+                match version {
+                    Some(version) => storage.push_v(file, version, chunk, data).await?,
+                    None => storage.push(file, chunk, data).await?,
+                }
+
+                stored.total_chunks += 1;
+                buffer.drain(..consumed);
+            }
+            TarStep::End => return Ok(stored),
+            TarStep::Malformed(reason) => {
+                return Err(Error::BadRequest(format!("tar_malformed: {reason}")));
+            }
+            TarStep::NeedMoreData => {
+                if buffer.len() > MAX_BUFFER_BYTES {
+                    return Err(Error::BadRequest(
+                        "tar_entry_exceeds_max_chunk_size".to_string(),
+                    ));
+                }
+
+                match body.next().await {
+                    Some(bytes) => buffer.extend_from_slice(
+                        &bytes
+                            .map_err(|e| Error::BadRequest(format!("payload_read_failed: {e}")))?,
+                    ),
+                    None => return Err(Error::BadRequest("tar_truncated".to_string())),
+                }
+            }
+        }
+    }
+}
+
+/// Parse `chunks/{file_id}/{chunk:06}.enc` or
+/// `chunks/{file_id}/v{version}/{chunk:06}.enc`.
+fn parse_chunk_name(name: &str) -> AppResult<(Uuid, Option<i32>, i64)> {
+    let invalid = || Error::BadRequest(format!("account_archive_invalid_entry: {name}"));
+
+    let rest = name.strip_prefix("chunks/").ok_or_else(invalid)?;
+    let mut parts = rest.split('/');
+    let file_id = parts.next().ok_or_else(invalid)?;
+    let file_id = Uuid::parse_str(file_id).map_err(|_| invalid())?;
+
+    let (version, chunk) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(chunk), None, None) => (None, chunk),
+        (Some(version), Some(chunk), None) => {
+            let version = version
+                .strip_prefix('v')
+                .and_then(|v| v.parse::<i32>().ok())
+                .filter(|v| *v > 0)
+                .ok_or_else(invalid)?;
+            (Some(version), chunk)
+        }
+        _ => return Err(invalid()),
+    };
+
+    let chunk = chunk
+        .strip_suffix(".enc")
+        .and_then(|c| c.parse::<i64>().ok())
+        .filter(|c| *c >= 0)
+        .ok_or_else(invalid)?;
+
+    Ok((file_id, version, chunk))
+}
+
+/// Insert the renumbered rows in one transaction. Files only count as
+/// uploaded when every chunk of their active version was stored.
+async fn insert(
+    context: &Context,
+    user_id: Uuid,
+    archive: Archive,
+    renumbered: &Renumbered,
+    stored: Stored,
+) -> AppResult<Imported> {
+    let uploaded: HashMap<Uuid, i64> = renumbered
+        .files
+        .iter()
+        .map(|(old_id, file)| {
+            let chunks = stored
+                .chunks
+                .get(&(*old_id, file.active_version))
+                .map_or(0, |chunks| chunks.len() as i64);
+
+            (file.id, chunks)
+        })
+        .collect();
+
+    let txn = context.db.begin().await?;
+    let repository = Repository::new(&txn);
+
+    let files = repository
+        .import_files(user_id, archive.files, &uploaded)
+        .await?;
+    let versions = repository.import_versions(archive.versions).await?;
+
+    txn.commit().await?;
+
+    let skipped_links = archive
+        .links
+        .iter()
+        .map(|link| link.file_id)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+
+    Ok(Imported {
+        files,
+        chunks: stored.total_chunks,
+        versions,
+        skipped_links,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn chunk_names_parse() {
+        let id = Uuid::new_v4();
+
+        assert_eq!(
+            parse_chunk_name(&format!("chunks/{id}/000003.enc")).unwrap(),
+            (id, None, 3)
+        );
+        assert_eq!(
+            parse_chunk_name(&format!("chunks/{id}/v2/000000.enc")).unwrap(),
+            (id, Some(2), 0)
+        );
+        assert!(parse_chunk_name(&format!("chunks/{id}/v0/000000.enc")).is_err());
+        assert!(parse_chunk_name(&format!("chunks/{id}/../000000.enc")).is_err());
+        assert!(parse_chunk_name("chunks/not-a-uuid/000000.enc").is_err());
+        assert!(parse_chunk_name(&format!("{id}/000000.enc")).is_err());
+    }
+
+    /// Feed `entries` to [`store_chunks`] as the tail of an archive.
+    async fn store(
+        context: &Context,
+        file: &files::Model,
+        declared: Declared,
+        budget: Option<u64>,
+        entries: &[(String, Vec<u8>)],
+    ) -> AppResult<Stored> {
+        let mut body = Vec::new();
+        for (name, data) in entries {
+            body.extend_from_slice(&tar_entry(name, data));
+        }
+        body.extend_from_slice(&[0u8; TAR_END_OF_ARCHIVE_LEN]);
+
+        let renumbered = Renumbered {
+            files: HashMap::from([(file.id, file.clone())]),
+            versions: HashMap::from([(file.id, HashMap::from([(1, declared)]))]),
+            trashed: HashSet::new(),
+        };
+        let body = futures::stream::iter([Ok::<_, String>(Bytes::from(body))]);
+
+        let storage = Fs::new(&context.config);
+        let stored = store_chunks(&storage, &renumbered, budget, vec![], body).await;
+        storage.purge_all(file).await.unwrap();
+
+        stored
+    }
+
+    #[actix_web::test]
+    async fn chunks_are_held_to_what_the_archive_declares() {
+        let context = Context::mock_sqlite().await;
+        let user = entity::mock::create_user(&context.db, "first@test.com", None).await;
+        let (file, _) =
+            entity::mock::create_file(&context.db, &user, "file", "text/plain", None).await;
+        let chunk = |i: i64| format!("chunks/{}/{:06}.enc", file.id, i);
+        let declared = Declared::new(Some(2), Some(10));
+
+        let stored = store(
+            &context,
+            &file,
+            declared,
+            None,
+            &[(chunk(0), vec![0; 8]), (chunk(1), vec![0; 8])],
+        )
+        .await
+        .unwrap();
+        assert_eq!(stored.total_chunks, 2);
+        assert_eq!(stored.total_bytes, 16);
+
+        // Past the declared chunk count.
+        assert!(
+            store(&context, &file, declared, None, &[(chunk(2), vec![0; 8])])
+                .await
+                .is_err()
+        );
+
+        // The same chunk twice.
+        assert!(store(
+            &context,
+            &file,
+            declared,
+            None,
+            &[(chunk(0), vec![0; 8]), (chunk(0), vec![0; 8])]
+        )
+        .await
+        .is_err());
+
+        // More bytes than the declared size and the cipher overhead allow.
+        let oversized = vec![0; 10 + 2 * CHUNK_OVERHEAD_BYTES as usize + 1];
+        assert!(
+            store(&context, &file, declared, None, &[(chunk(0), oversized)])
+                .await
+                .is_err()
+        );
+
+        // More bytes than the quota has room for.
+        assert!(matches!(
+            store(&context, &file, declared, Some(12), &[(chunk(0), vec![0; 8]), (chunk(1), vec![0; 8])]).await,
+            Err(Error::BadRequest(e)) if e == "quota_exceeded"
+        ));
+    }
+}
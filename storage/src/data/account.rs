@@ -0,0 +1,141 @@
+//! Contents of an account export archive.
+//!
+//! The archive is a tar stream. Its first entry is `account.json`
+//! ([`Archive`]), followed by one entry per stored chunk:
+//!
+//! * `chunks/{file_id}/{chunk:06}.enc` for files in the legacy layout
+//! * `chunks/{file_id}/v{version}/{chunk:06}.enc` for editable files
+//!
+//! Everything in it stays encrypted: file keys are wrapped to the account's
+//! public key, and the private key travels only in its encrypted form.
+
+use entity::{file_versions, files, link_files, links, users, Uuid};
+use serde::{Deserialize, Serialize};
+
+/// Version of the archive layout, bumped on incompatible changes.
+pub const FORMAT: u32 = 1;
+
+/// Name of the first entry of the archive.
+pub const ARCHIVE_MANIFEST: &str = "account.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Archive {
+    pub format: u32,
+    pub exported_at: i64,
+    pub account: Account,
+    pub files: Vec<ExportedFile>,
+    pub versions: Vec<file_versions::Model>,
+    pub links: Vec<ExportedLink>,
+}
+
+/// The key pair every file key in the archive is wrapped to. Import only
+/// accepts the archive into an account holding the same key pair.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Account {
+    /// Id of the account on the instance it was exported from.
+    pub id: Uuid,
+    pub pubkey: String,
+    pub fingerprint: String,
+    pub key_type: String,
+    pub wrapping_pubkey: Option<String>,
+    pub encrypted_private_key: Option<String>,
+}
+
+impl From<&users::Model> for Account {
+    fn from(user: &users::Model) -> Self {
+        Self {
+            id: user.id,
+            pubkey: user.pubkey.clone(),
+            fingerprint: user.fingerprint.clone(),
+            key_type: user.key_type.clone(),
+            wrapping_pubkey: user.wrapping_pubkey.clone(),
+            encrypted_private_key: user.encrypted_private_key.clone(),
+        }
+    }
+}
+
+/// A file or folder the account owns.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedFile {
+    #[serde(flatten)]
+    pub file: files::Model,
+    /// The owner's `user_files.encrypted_key`.
+    pub encrypted_key: String,
+    /// Hashed search tokens in the `{hash}:{weight}` form clients upload.
+    pub search_tokens: Vec<String>,
+}
+
+/// A public link on one of the exported files. Unlike [`links::Model`] it
+/// keeps the wrapped file key and the password hash, without them the link
+/// cannot serve downloads or would serve them to anyone.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedLink {
+    pub id: Uuid,
+    pub file_id: Uuid,
+    pub signature: String,
+    pub downloads: i32,
+    pub encrypted_name: String,
+    pub encrypted_link_key: String,
+    pub encrypted_thumbnail: Option<String>,
+    pub encrypted_file_key: Option<String>,
+    pub created_at: i64,
+    pub expires_at: Option<i64>,
+    #[serde(default)]
+    pub max_downloads: Option<i32>,
+    #[serde(default)]
+    pub password_hash: Option<String>,
+    /// Files under a linked folder with their names and keys wrapped with
+    /// the link key, empty for a link to a single file.
+    #[serde(default)]
+    pub files: Vec<ExportedLinkFile>,
+}
+
+/// A file under a linked folder.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedLinkFile {
+    pub file_id: Uuid,
+    pub encrypted_name: String,
+    pub encrypted_file_key: Option<String>,
+}
+
+impl From<link_files::Model> for ExportedLinkFile {
+    fn from(link_file: link_files::Model) -> Self {
+        Self {
+            file_id: link_file.file_id,
+            encrypted_name: link_file.encrypted_name,
+            encrypted_file_key: link_file.encrypted_file_key,
+        }
+    }
+}
+
+impl From<links::Model> for ExportedLink {
+    fn from(link: links::Model) -> Self {
+        Self {
+            id: link.id,
+            file_id: link.file_id,
+            signature: link.signature,
+            downloads: link.downloads,
+            encrypted_name: link.encrypted_name,
+            encrypted_link_key: link.encrypted_link_key,
+            encrypted_thumbnail: link.encrypted_thumbnail,
+            encrypted_file_key: link.encrypted_file_key,
+            created_at: link.created_at,
+            expires_at: link.expires_at,
+            max_downloads: link.max_downloads,
+            password_hash: link.password_hash,
+            files: vec![],
+        }
+    }
+}
+
+/// Response of the import endpoint.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Imported {
+    pub files: u64,
+    pub chunks: u64,
+    pub versions: u64,
+    /// New ids of the imported files that had public links. The links are
+    /// left out, their owner signature covers the old file id, and have to
+    /// be created again.
+    pub skipped_links: Vec<Uuid>,
+}
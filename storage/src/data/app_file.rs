@@ -47,6 +47,9 @@ pub struct AppFile {
     /// Total size in bytes for the in-flight upload (NULL when none).
     /// Copied to `size` on commit.
     pub pending_size: Option<i64>,
+    /// Set when `hoodik fsck --repair` found chunks of the active version
+    /// missing on the storage provider, so downloads of it will fail.
+    pub damaged_at: Option<i64>,
     pub is_new: bool,
     pub uploaded_chunks: Option<Vec<i64>>,
     pub link: Option<links::Model>,
@@ -181,6 +184,7 @@ impl FromQueryResult for AppFile {
             pending_version: file.pending_version,
             pending_chunks: file.pending_chunks,
             pending_size: file.pending_size,
+            damaged_at: file.damaged_at,
             is_new: false,
             uploaded_chunks: None,
             link,
@@ -0,0 +1,109 @@
+//! Copy a file or a whole folder tree on the server, without the client
+//! downloading and re-uploading the content.
+//!
+//! The chunks are duplicated on the storage provider as they are, so every
+//! copy keeps the symmetric key of its source. The client sends the new
+//! metadata for each node of the tree — the encrypted name, the name hash,
+//! the search tokens and the source's key wrapped again for the new row —
+//! the same way it would for `createFile`.
+use std::collections::HashSet;
+
+use ::error::AppResult;
+use entity::Uuid;
+use serde::{Deserialize, Serialize};
+use validr::*;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CopyFiles {
+    /// File or folder to copy
+    pub id: Option<Uuid>,
+    /// Destination folder id (empty for root)
+    pub file_id: Option<Uuid>,
+    /// New metadata for the copied file, or for every file and folder
+    /// of the copied folder tree, its root included
+    pub nodes: Option<Vec<CopyNode>>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CopyNode {
+    /// Id of the file or folder this node is a copy of
+    pub id: Option<Uuid>,
+    /// Source file key encrypted with users RSA key
+    pub encrypted_key: Option<String>,
+    /// Name of the copy hashed so we can guard
+    /// against duplicate files in directories
+    pub name_hash: Option<String>,
+    /// Name of the copy encrypted with the file key
+    pub encrypted_name: Option<String>,
+    /// Thumbnail of the copy encrypted with the file key, the source's
+    /// thumbnail is kept when absent
+    pub encrypted_thumbnail: Option<String>,
+    /// Tokens by which the copy will be searchable
+    pub search_tokens_hashed: Option<Vec<String>>,
+}
+
+impl Validation for CopyFiles {
+    fn rules(&self) -> Vec<Rule<Self>> {
+        vec![
+            rule_required!(id),
+            Rule::new("nodes", |obj: &CopyFiles, error| {
+                let nodes = match obj.nodes.as_ref() {
+                    Some(nodes) if !nodes.is_empty() => nodes,
+                    _ => return error.add("required"),
+                };
+
+                let incomplete = nodes.iter().any(|node| {
+                    node.id.is_none()
+                        || node.encrypted_key.is_none()
+                        || node.name_hash.is_none()
+                        || node.encrypted_name.is_none()
+                });
+                if incomplete {
+                    return error.add("incomplete_node");
+                }
+
+                let unique = nodes
+                    .iter()
+                    .filter_map(|node| node.id)
+                    .collect::<HashSet<_>>();
+                if unique.len() != nodes.len() {
+                    error.add("duplicate_node");
+                }
+            }),
+        ]
+    }
+}
+
+/// Metadata of one copied node after validation.
+pub struct CopyNodeData {
+    pub id: Uuid,
+    pub encrypted_key: String,
+    pub name_hash: String,
+    pub encrypted_name: String,
+    pub encrypted_thumbnail: Option<String>,
+    pub search_tokens_hashed: Vec<String>,
+}
+
+impl CopyFiles {
+    pub fn into_value(self) -> AppResult<(Uuid, Option<Uuid>, Vec<CopyNodeData>)> {
+        let data = self.validate()?;
+
+        // `validate()` enforces every unwrapped field, the unwraps are
+        // unreachable past that point.
+        let nodes = data
+            .nodes
+            .unwrap_or_default()
+            .into_iter()
+            .map(|node| CopyNodeData {
+                id: node.id.unwrap(),
+                encrypted_key: node.encrypted_key.unwrap(),
+                name_hash: node.name_hash.unwrap(),
+                encrypted_name: node.encrypted_name.unwrap(),
+                encrypted_thumbnail: node.encrypted_thumbnail,
+                search_tokens_hashed: node.search_tokens_hashed.unwrap_or_default(),
+            })
+            .collect();
+
+        Ok((data.id.unwrap(), data.file_id, nodes))
+    }
+}
@@ -167,7 +167,8 @@ impl CreateFile {
                                 .unwrap()
                         })
                         .unwrap_or(now)
-                        .and_utc().timestamp(),
+                        .and_utc()
+                        .timestamp(),
                 ),
                 md5: ActiveValue::Set(data.md5),
                 sha1: ActiveValue::Set(data.sha1),
@@ -189,6 +190,10 @@ impl CreateFile {
                 members_list_signature: ActiveValue::Set(None),
                 members_list_signed_at: ActiveValue::Set(None),
                 members_list_signed_by_user_id: ActiveValue::Set(None),
+                deleted_at: ActiveValue::Set(None),
+                deleted_parent_id: ActiveValue::Set(None),
+                last_chunk_at: ActiveValue::Set(None),
+                damaged_at: ActiveValue::Set(None),
             },
             data.encrypted_key.unwrap(),
             data.search_tokens_hashed.unwrap_or_default(),
@@ -0,0 +1,85 @@
+use entity::{files, Expr, Func, SimpleExpr, Uuid};
+use error::{AppResult, Error};
+use serde::{Deserialize, Serialize};
+
+use super::app_file::AppFile;
+
+/// Column a directory listing is ordered by. Every listing breaks ties on
+/// `files.id`, so the order is total and a page boundary never splits or
+/// repeats rows that share a sort value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum SortKey {
+    CreatedAt,
+    ModifiedAt,
+    Size,
+}
+
+impl SortKey {
+    /// Parse the `order_by` query parameter.
+    pub(crate) fn parse(order_by: &str) -> AppResult<Self> {
+        match order_by {
+            "modified_at" => Ok(Self::ModifiedAt),
+            "size" => Ok(Self::Size),
+            _ => Err(Error::BadRequest("invalid_order_by".to_string())),
+        }
+    }
+
+    /// Expression the listing is sorted on. Directories have no size, they
+    /// sort as `-1` so the comparison against a cursor value never meets a
+    /// `NULL`.
+    pub(crate) fn expr(self) -> SimpleExpr {
+        match self {
+            Self::CreatedAt => Expr::col((files::Entity, files::Column::CreatedAt)).into(),
+            Self::ModifiedAt => Expr::col((files::Entity, files::Column::FileModifiedAt)).into(),
+            Self::Size => Func::coalesce([
+                Expr::col((files::Entity, files::Column::Size)).into(),
+                Expr::val(-1i64).into(),
+            ])
+            .into(),
+        }
+    }
+
+    /// The value [`Self::expr`] has for `file`.
+    pub(crate) fn value(self, file: &AppFile) -> i64 {
+        match self {
+            Self::CreatedAt => file.created_at,
+            Self::ModifiedAt => file.file_modified_at,
+            Self::Size => file.size.unwrap_or(-1),
+        }
+    }
+}
+
+/// Position right after the last row of a listing page. Clients get it as
+/// an opaque `next_cursor` string and hand it back unchanged for the next
+/// page; it remembers the ordering it was made for so it can't be replayed
+/// against a different one.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Cursor {
+    pub sort: SortKey,
+    pub desc: bool,
+    pub value: i64,
+    pub id: Uuid,
+}
+
+impl Cursor {
+    pub(crate) fn after(file: &AppFile, sort: SortKey, desc: bool) -> Self {
+        Self {
+            sort,
+            desc,
+            value: sort.value(file),
+            id: file.id,
+        }
+    }
+
+    pub(crate) fn encode(&self) -> AppResult<String> {
+        Ok(cryptfns::base64::encode_url(serde_json::to_vec(self)?))
+    }
+
+    pub(crate) fn decode(cursor: &str) -> AppResult<Self> {
+        cryptfns::base64::decode_url(cursor)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .ok_or_else(|| Error::BadRequest("invalid_cursor".to_string()))
+    }
+}
@@ -1,5 +1,8 @@
+pub mod account;
 pub mod app_file;
+pub mod copy_files;
 pub mod create_file;
+pub(crate) mod cursor;
 pub mod delete_many;
 pub mod meta;
 pub mod move_many;
@@ -7,7 +10,8 @@ pub mod query;
 pub mod rename;
 pub mod replace_content;
 pub mod response;
-pub mod set_editable;
 pub mod search;
+pub mod set_editable;
 pub mod stats;
 pub mod update_hashes;
+pub mod version_policy;
@@ -13,6 +13,19 @@ pub struct Query {
     /// `has_thumbnail`. Absent means full rows — the compatible default
     /// for clients that predate the parameter.
     pub compact: Option<bool>,
+    /// Page size. Giving it (or a `cursor`) switches the listing to cursor
+    /// pagination; it is capped at [`MAX_PAGE_SIZE`]. Absent on both means
+    /// the whole directory in one response, as before.
+    pub limit: Option<u64>,
+    /// The `next_cursor` of the previous page, unchanged.
+    pub cursor: Option<String>,
+    /// Also count every row of the listing and report it as `total`. Costs
+    /// one more query, so it is only done on request.
+    pub with_total: Option<bool>,
 }
 
+/// Largest page a paginated listing returns, and the page size when only a
+/// `cursor` is given.
+pub const MAX_PAGE_SIZE: u64 = 1000;
+
 impl Validation for Query {}
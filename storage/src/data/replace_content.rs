@@ -14,6 +14,13 @@
 //!    `Manage::finish`, which atomically swaps `active_version =
 //!    pending_version` inside a transaction.
 //!
+//! `reuse_chunks` turns the edit incremental: each listed chunk is declared
+//! identical to the same chunk of an older version, and the new version
+//! borrows it through a `file_chunk_refs` row instead of storing it again.
+//! Chunk keys are derived per index, so only the client, which holds the
+//! plaintext, can tell whether a chunk is unchanged. When every chunk is
+//! reused the version is finalized right away.
+//!
 //! `force = true` is the recovery escape hatch when a previous edit died
 //! mid-way: it overrides the 409 conflict, abandons the orphaned pending
 //! directory on disk, and starts a fresh pending version. Without `force`
@@ -25,6 +32,8 @@
 //! cipher swap is ever needed, it has to land via a separate, full file
 //! re-upload path.
 
+use std::collections::HashSet;
+
 use ::error::AppResult;
 use entity::Uuid;
 use serde::{Deserialize, Serialize};
@@ -48,6 +57,19 @@ pub struct ReplaceContent {
     /// on disk and allocates a brand-new pending version. Without this
     /// flag, the request 409s when a pending upload exists.
     pub force: Option<bool>,
+    /// Chunks of the new content that are identical to the same chunk of
+    /// an older version, and will not be uploaded.
+    pub reuse_chunks: Option<Vec<ReusedChunk>>,
+}
+
+/// One chunk the new version borrows from an older one.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReusedChunk {
+    /// Index of the chunk, the same in both versions
+    pub chunk: i64,
+    /// Version holding the identical chunk, the active one or any
+    /// version kept in history
+    pub version: i32,
 }
 
 impl Validation for ReplaceContent {
@@ -69,6 +91,21 @@ impl Validation for ReplaceContent {
                     }
                 }
             }),
+            Rule::new("reuse_chunks", |obj: &ReplaceContent, error| {
+                let Some(reused) = obj.reuse_chunks.as_ref() else {
+                    return;
+                };
+
+                let chunks = obj.chunks.unwrap_or_default();
+                if reused.iter().any(|r| r.chunk < 0 || r.chunk >= chunks) {
+                    return error.add("chunk_out_of_range");
+                }
+
+                let unique = reused.iter().map(|r| r.chunk).collect::<HashSet<_>>();
+                if unique.len() != reused.len() {
+                    error.add("duplicate_chunk");
+                }
+            }),
         ]
     }
 
@@ -88,6 +125,8 @@ pub struct ValidatedReplaceContent {
     pub encrypted_thumbnail: Option<String>,
     pub search_tokens_hashed: Vec<String>,
     pub force: bool,
+    /// `(chunk, version)` pairs from [`ReplaceContent::reuse_chunks`].
+    pub reuse_chunks: Vec<(i64, i32)>,
 }
 
 impl ReplaceContent {
@@ -103,6 +142,12 @@ impl ReplaceContent {
             encrypted_thumbnail: data.encrypted_thumbnail,
             search_tokens_hashed: data.search_tokens_hashed.unwrap_or_default(),
             force: data.force.unwrap_or(false),
+            reuse_chunks: data
+                .reuse_chunks
+                .unwrap_or_default()
+                .into_iter()
+                .map(|r| (r.chunk, r.version))
+                .collect(),
         })
     }
 }
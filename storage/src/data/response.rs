@@ -9,4 +9,13 @@ pub struct Response {
 
     /// List of files in the current (last) directory in the list above
     pub children: Vec<AppFile>,
+
+    /// Cursor for the page after this one, `None` on the last page and on
+    /// listings that were not paginated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+
+    /// Number of rows in the whole listing, when `with_total` was asked for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total: Option<u64>,
 }
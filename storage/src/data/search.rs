@@ -52,8 +52,7 @@ impl Search {
             // behaved before tokenization moved to the client.
             None => {
                 let search = self.search.unwrap_or_default();
-                let tokens =
-                    cryptfns::tokenizer::into_hashed_tokens(&search).unwrap_or_default();
+                let tokens = cryptfns::tokenizer::into_hashed_tokens(&search).unwrap_or_default();
 
                 (Some(search), tokens)
             }
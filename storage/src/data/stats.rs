@@ -26,6 +26,9 @@ impl FromQueryResult for Stats {
 pub struct Response {
     pub stats: Vec<Stats>,
     /// Bytes stored by files the caller owns, counted against `quota`.
+    /// Includes the files in the caller's trash.
     pub used_space: i64,
+    /// The part of `used_space` taken by files in the caller's trash.
+    pub trashed_space: i64,
     pub quota: Option<u64>,
 }
@@ -0,0 +1,245 @@
+//! Retention policies for the version history of editable files.
+//!
+//! A file's history is thinned grandfather-father-son style: every version
+//! younger than `keep_all_days` is kept, and past that only the newest
+//! version of each of the last `keep_hourly` hours, `keep_daily` days,
+//! `keep_weekly` weeks and `keep_monthly` months that have any version.
+//! `max_versions` then caps whatever is left, oldest dropped first.
+//! Buckets are counted in UTC, weeks start on Monday.
+//!
+//! Pinned versions are never pruned and don't count towards the cap. The
+//! active version isn't in the history at all, so it is never pruned
+//! either.
+//!
+//! The policy of a file is its own one if it has any, otherwise the one
+//! its owner set for all of their files, otherwise the instance defaults.
+
+use std::cmp::Reverse;
+
+use ::error::AppResult;
+use chrono::{DateTime, Datelike};
+use config::versions::VersionsConfig;
+use entity::{file_versions, version_policies};
+use serde::{Deserialize, Serialize};
+use validr::*;
+
+const HOUR: i64 = 3600;
+const DAY: i64 = 86400;
+
+/// Body of `PUT /api/storage/version-policy` and
+/// `PUT /api/storage/{file_id}/version-policy`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VersionPolicy {
+    pub keep_all_days: Option<i32>,
+    pub keep_hourly: Option<i32>,
+    pub keep_daily: Option<i32>,
+    pub keep_weekly: Option<i32>,
+    pub keep_monthly: Option<i32>,
+    /// Hard cap on the unpinned versions kept, none when left out.
+    pub max_versions: Option<i32>,
+}
+
+impl Validation for VersionPolicy {
+    fn rules(&self) -> Vec<Rule<Self>> {
+        vec![
+            rule_required!(keep_all_days),
+            rule_required!(keep_hourly),
+            rule_required!(keep_daily),
+            rule_required!(keep_weekly),
+            rule_required!(keep_monthly),
+            Rule::new("keep_all_days", |obj: &VersionPolicy, error| {
+                if obj.keep_all_days.is_some_and(|v| v < 0) {
+                    error.add("min:0")
+                }
+            }),
+            Rule::new("keep_hourly", |obj: &VersionPolicy, error| {
+                if obj.keep_hourly.is_some_and(|v| v < 0) {
+                    error.add("min:0")
+                }
+            }),
+            Rule::new("keep_daily", |obj: &VersionPolicy, error| {
+                if obj.keep_daily.is_some_and(|v| v < 0) {
+                    error.add("min:0")
+                }
+            }),
+            Rule::new("keep_weekly", |obj: &VersionPolicy, error| {
+                if obj.keep_weekly.is_some_and(|v| v < 0) {
+                    error.add("min:0")
+                }
+            }),
+            Rule::new("keep_monthly", |obj: &VersionPolicy, error| {
+                if obj.keep_monthly.is_some_and(|v| v < 0) {
+                    error.add("min:0")
+                }
+            }),
+            Rule::new("max_versions", |obj: &VersionPolicy, error| {
+                if obj.max_versions.is_some_and(|v| v < 1) {
+                    error.add("min:1")
+                }
+            }),
+        ]
+    }
+
+    fn modifiers(&self) -> Vec<Modifier<Self>> {
+        vec![]
+    }
+}
+
+impl VersionPolicy {
+    pub fn validate_into(self) -> AppResult<RetentionPolicy> {
+        let data = self.validate()?;
+
+        // `validate()` enforces these via `rule_required!` and the minimums,
+        // so the unwraps and casts can't fail past this point.
+        Ok(RetentionPolicy {
+            keep_all_days: data.keep_all_days.unwrap() as u32,
+            keep_hourly: data.keep_hourly.unwrap() as u32,
+            keep_daily: data.keep_daily.unwrap() as u32,
+            keep_weekly: data.keep_weekly.unwrap() as u32,
+            keep_monthly: data.keep_monthly.unwrap() as u32,
+            max_versions: data.max_versions.map(|v| v as u32),
+        })
+    }
+}
+
+/// Retention policy in effect for a file, or for all files of a user.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    pub keep_all_days: u32,
+    pub keep_hourly: u32,
+    pub keep_daily: u32,
+    pub keep_weekly: u32,
+    pub keep_monthly: u32,
+    pub max_versions: Option<u32>,
+}
+
+/// Where a [`RetentionPolicy`] comes from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicySource {
+    /// Set on the file itself
+    File,
+    /// Set by the owner for all of their files
+    User,
+    /// Instance defaults from the config
+    Default,
+}
+
+/// Response of the version policy endpoints.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VersionPolicyResponse {
+    #[serde(flatten)]
+    pub policy: RetentionPolicy,
+    pub source: PolicySource,
+}
+
+impl From<&VersionsConfig> for RetentionPolicy {
+    fn from(config: &VersionsConfig) -> Self {
+        Self {
+            keep_all_days: config.keep_all_days,
+            keep_hourly: config.keep_hourly,
+            keep_daily: config.keep_daily,
+            keep_weekly: config.keep_weekly,
+            keep_monthly: config.keep_monthly,
+            max_versions: Some(config.max_file_versions).filter(|max| *max > 0),
+        }
+    }
+}
+
+impl From<&version_policies::Model> for RetentionPolicy {
+    fn from(model: &version_policies::Model) -> Self {
+        Self {
+            keep_all_days: model.keep_all_days.max(0) as u32,
+            keep_hourly: model.keep_hourly.max(0) as u32,
+            keep_daily: model.keep_daily.max(0) as u32,
+            keep_weekly: model.keep_weekly.max(0) as u32,
+            keep_monthly: model.keep_monthly.max(0) as u32,
+            max_versions: model.max_versions.map(|max| max.max(0) as u32),
+        }
+    }
+}
+
+impl RetentionPolicy {
+    /// Versions of `history` the policy no longer keeps at `now`.
+    pub(crate) fn prunable(&self, history: &[file_versions::Model], now: i64) -> Vec<i32> {
+        let mut candidates = history
+            .iter()
+            .filter(|v| v.pinned_at.is_none())
+            .collect::<Vec<_>>();
+        candidates.sort_by_key(|v| Reverse((v.created_at, v.version)));
+
+        let keep_all_since = now - i64::from(self.keep_all_days) * DAY;
+        let mut kept = candidates
+            .iter()
+            .map(|v| v.created_at > keep_all_since)
+            .collect::<Vec<_>>();
+
+        let thinning = [
+            (self.keep_hourly, hour as fn(i64) -> i64),
+            (self.keep_daily, day),
+            (self.keep_weekly, week),
+            (self.keep_monthly, month),
+        ];
+
+        // Newest first, so the first version seen in a bucket is the one
+        // that bucket keeps.
+        for (count, bucket) in thinning {
+            let mut buckets = 0;
+            let mut last = None;
+
+            for (i, version) in candidates.iter().enumerate() {
+                if version.created_at > keep_all_since {
+                    continue;
+                }
+
+                let current = bucket(version.created_at);
+                if last == Some(current) {
+                    continue;
+                }
+                if buckets == count {
+                    break;
+                }
+
+                last = Some(current);
+                buckets += 1;
+                kept[i] = true;
+            }
+        }
+
+        if let Some(max) = self.max_versions {
+            kept.iter_mut()
+                .filter(|keep| **keep)
+                .skip(max as usize)
+                .for_each(|keep| *keep = false);
+        }
+
+        candidates
+            .into_iter()
+            .zip(kept)
+            .filter(|(_, keep)| !keep)
+            .map(|(v, _)| v.version)
+            .collect()
+    }
+}
+
+/// Hours since the epoch.
+fn hour(timestamp: i64) -> i64 {
+    timestamp.div_euclid(HOUR)
+}
+
+/// Days since the epoch.
+fn day(timestamp: i64) -> i64 {
+    timestamp.div_euclid(DAY)
+}
+
+/// Weeks since the epoch, starting on Monday. The epoch was a Thursday.
+fn week(timestamp: i64) -> i64 {
+    (day(timestamp) + 3).div_euclid(7)
+}
+
+/// Months since the year 0.
+fn month(timestamp: i64) -> i64 {
+    DateTime::from_timestamp(timestamp, 0)
+        .map(|date| i64::from(date.year()) * 12 + i64::from(date.month0()))
+        .unwrap_or_default()
+}
@@ -0,0 +1,204 @@
+//! # Fsck
+//!
+//! Consistency check between the `files` table and the storage provider,
+//! run by the `hoodik fsck` subcommand. By default it only reports; with
+//! `repair` it deletes what nothing references and flags files that can no
+//! longer be downloaded.
+
+use std::collections::HashMap;
+
+use chrono::Utc;
+use context::Context;
+use entity::{file_chunk_refs, files, ConnectionTrait, TransactionTrait, Uuid};
+use error::AppResult;
+use fs::prelude::*;
+
+use crate::{housekeeping::purge_version, repository::Repository};
+
+/// How many file rows are checked per database round trip.
+const PAGE_SIZE: u64 = 500;
+
+/// Outcome of [`fsck`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Report {
+    /// File rows that were checked.
+    pub checked: u64,
+    /// Files on the storage provider without a file row.
+    pub orphans: Vec<StoredFile>,
+    /// `v{N}/` directories of existing files that are neither the active
+    /// nor the pending version and have no `file_versions` row.
+    pub stray_versions: Vec<(Uuid, i32)>,
+    /// Finished files missing chunks of their active version.
+    pub damaged: Vec<Damaged>,
+    /// `file_versions` rows whose `v{N}/` directory is gone.
+    pub missing_versions: Vec<(Uuid, i32)>,
+    /// Whether the problems above were repaired rather than only reported.
+    pub repaired: bool,
+}
+
+/// A file missing chunks of its active version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Damaged {
+    pub file_id: Uuid,
+    pub version: i32,
+    /// Indexes of the missing chunks.
+    pub missing: Vec<i64>,
+}
+
+impl Report {
+    /// True when the database and the storage provider agree.
+    pub fn is_clean(&self) -> bool {
+        self.orphans.is_empty()
+            && self.stray_versions.is_empty()
+            && self.damaged.is_empty()
+            && self.missing_versions.is_empty()
+    }
+}
+
+/// Walk every file row and compare it with what the configured storage
+/// provider holds. With `repair` set, orphaned files and stray versions are
+/// purged from the provider, `file_versions` rows pointing at a missing
+/// directory are dropped, and damaged files get `damaged_at` set (it is
+/// cleared again on files that turn out healthy). Unfinished uploads are
+/// not checked for missing chunks; the abandoned-upload reaper owns them.
+pub async fn fsck(context: &Context, repair: bool) -> AppResult<Report> {
+    let fs = Fs::new(&context.config);
+    let repository = Repository::new(&context.db);
+
+    // Take the inventory before reading any rows: a file row is always
+    // created before its first chunk, so whatever is listed here and has no
+    // row afterwards is a real orphan, not an upload that started mid-scan.
+    // Names that are not file ids were not written by us and are left alone.
+    let mut stored: HashMap<Uuid, StoredFile> = fs
+        .inventory()
+        .await?
+        .into_iter()
+        .filter_map(|file| Some((Uuid::parse_str(&file.name).ok()?, file)))
+        .collect();
+
+    let versions = repository.fsck_versions().await?;
+    let now = Utc::now().timestamp();
+
+    let mut report = Report {
+        repaired: repair,
+        ..Default::default()
+    };
+
+    let mut after = None;
+    loop {
+        let page = repository.fsck_files(after, PAGE_SIZE).await?;
+        let Some(last) = page.last() else {
+            break;
+        };
+        after = Some(last.id);
+
+        for file in &page {
+            let recorded = versions.get(&file.id).map(Vec::as_slice).unwrap_or(&[]);
+            let footprint = stored.remove(&file.id);
+
+            check_file(
+                &repository,
+                &fs,
+                file,
+                recorded,
+                footprint.as_ref(),
+                repair.then_some(now),
+                &mut report,
+            )
+            .await?;
+
+            report.checked += 1;
+        }
+    }
+
+    report.orphans = stored.into_values().collect();
+    report.orphans.sort_by(|a, b| a.name.cmp(&b.name));
+
+    if repair {
+        for orphan in &report.orphans {
+            for filename in orphan.filenames() {
+                fs.purge_all(&filename).await?;
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Check a single file row; `repair_at` is the repair timestamp, or `None`
+/// for a dry run.
+async fn check_file<T: ConnectionTrait + TransactionTrait>(
+    repository: &Repository<'_, T>,
+    fs: &Fs<'_>,
+    file: &files::Model,
+    recorded: &[i32],
+    footprint: Option<&StoredFile>,
+    repair_at: Option<i64>,
+    report: &mut Report,
+) -> AppResult<()> {
+    // Borrowed chunks only count while the version they point at still
+    // stores them.
+    let name = file_chunk_refs::filename(repository.connection(), file.id, file).await?;
+
+    for &version in footprint.map(|f| f.versions.as_slice()).unwrap_or(&[]) {
+        if version == file.active_version
+            || Some(version) == file.pending_version
+            || recorded.contains(&version)
+        {
+            continue;
+        }
+
+        report.stray_versions.push((file.id, version));
+
+        if repair_at.is_some() {
+            purge_version(repository, fs, file, file.id, version).await?;
+        }
+    }
+
+    for &version in recorded {
+        if version == file.active_version
+            || !fs.get_uploaded_chunks_v(&name, version).await?.is_empty()
+        {
+            continue;
+        }
+
+        report.missing_versions.push((file.id, version));
+
+        if repair_at.is_some() {
+            repository.fsck_forget_version(file.id, version).await?;
+        }
+    }
+
+    let chunks = match (file.finished_upload_at, file.chunks) {
+        (Some(_), Some(chunks)) if chunks > 0 => chunks,
+        _ => return Ok(()),
+    };
+
+    let uploaded = if file.editable {
+        fs.get_uploaded_chunks_v(&name, file.active_version).await?
+    } else {
+        fs.get_uploaded_chunks(file).await?
+    };
+
+    let missing: Vec<i64> = (0..chunks).filter(|c| !uploaded.contains(c)).collect();
+
+    match repair_at {
+        Some(now) if !missing.is_empty() && file.damaged_at.is_none() => {
+            repository.fsck_set_damaged(file.id, Some(now)).await?;
+        }
+        Some(_) if missing.is_empty() && file.damaged_at.is_some() => {
+            repository.fsck_set_damaged(file.id, None).await?;
+        }
+        _ => {}
+    }
+
+    if !missing.is_empty() {
+        report.damaged.push(Damaged {
+            file_id: file.id,
+            version: file.active_version,
+            missing,
+        });
+    }
+
+    Ok(())
+}
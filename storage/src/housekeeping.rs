@@ -0,0 +1,263 @@
+//! # Housekeeping
+//!
+//! Storage sweeps driven by the background job scheduler in the `hoodik`
+//! crate, kept here so they can reuse the repository.
+
+use chrono::Utc;
+use context::Context;
+use entity::{file_chunk_refs, files, ConnectionTrait, TransactionTrait, Uuid};
+use error::AppResult;
+use fs::prelude::*;
+
+use crate::{
+    data::version_policy::RetentionPolicy,
+    repository::{cached::evict_file, Repository},
+};
+
+/// Outcome of [`reap_abandoned_uploads`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Reaped {
+    /// Unfinished files deleted plus pending edits dropped.
+    pub uploads: u64,
+    /// Bytes of partial chunks removed from the storage provider, estimated
+    /// from the declared size of each upload and how many of its chunks
+    /// were stored.
+    pub bytes: u64,
+    /// Copies left running by a server that went away, removed with their
+    /// rows and chunks.
+    pub copies: u64,
+}
+
+/// Delete everything that has been in the trash longer than the retention
+/// window from the admin settings, chunks included. Does nothing while the
+/// trash is disabled. Returns the number of deleted rows.
+pub async fn purge_trash(context: &Context) -> AppResult<u64> {
+    let before = {
+        let settings = context.settings.inner().await;
+
+        if !settings.trash.enabled() {
+            return Ok(0);
+        }
+
+        settings.trash.purge_before(Utc::now().timestamp())
+    };
+
+    let connection = context.db.begin().await?;
+    let files = Repository::new(&connection)
+        .purge_expired_trash(before)
+        .await?;
+    connection.commit().await?;
+
+    purge_chunks(&Fs::new(&context.config), &files).await?;
+
+    Ok(files.len() as u64)
+}
+
+/// Reclaim uploads that saw no chunk since `idle_before`. A file whose
+/// initial upload never finished is deleted with its chunks; an edit that
+/// never finalized loses its `v{pending_version}/` directory and the file
+/// keeps serving its active version. Uploads that move again while the
+/// reaper runs are left alone. A file that fails is logged and skipped so
+/// one broken upload does not hold up the rest.
+///
+/// Server-side copies that stopped making progress are interrupted ones,
+/// they are removed whole and marked failed.
+pub async fn reap_abandoned_uploads(context: &Context, idle_before: i64) -> AppResult<Reaped> {
+    let fs = Fs::new(&context.config);
+    let repository = Repository::new(&context.db);
+
+    let mut reaped = Reaped::default();
+    for job in repository.stalled_copies(idle_before).await? {
+        // Ending the job first stops the copy if it is still running after
+        // all. Rows a failed discard leaves behind are unfinished uploads
+        // the sweep below picks up.
+        if !repository
+            .end_copy(job.id, Some("interrupted".to_string()))
+            .await?
+        {
+            continue;
+        }
+
+        match discard_copy(context, job.id).await {
+            Ok(()) => reaped.copies += 1,
+            Err(e) => log::warn!("Failed to reap interrupted copy {}: {}", job.id, e),
+        }
+    }
+
+    for file in repository.abandoned_uploads(idle_before).await? {
+        match reap_one(&repository, &fs, &file, idle_before).await {
+            Ok(Some(bytes)) => {
+                evict_file(file.id).await;
+                reaped.uploads += 1;
+                reaped.bytes += bytes;
+            }
+            Ok(None) => {}
+            Err(e) => log::warn!("Failed to reap abandoned upload {}: {}", file.id, e),
+        }
+    }
+
+    Ok(reaped)
+}
+
+/// Reap a single upload, returning the reclaimed bytes or `None` when the
+/// upload turned out to be alive after all.
+async fn reap_one<T: ConnectionTrait + TransactionTrait>(
+    repository: &Repository<'_, T>,
+    fs: &Fs<'_>,
+    file: &files::Model,
+    idle_before: i64,
+) -> AppResult<Option<u64>> {
+    if let Some(version) = file.pending_version {
+        let stored = fs.get_uploaded_chunks_v(file, version).await?.len();
+
+        if !repository
+            .reset_pending_upload(file.id, version, idle_before)
+            .await?
+        {
+            return Ok(None);
+        }
+
+        purge_version(repository, fs, file, file.id, version).await?;
+
+        return Ok(Some(partial_bytes(
+            file.pending_size,
+            file.pending_chunks,
+            stored,
+        )));
+    }
+
+    let stored = if file.editable {
+        fs.get_uploaded_chunks_v(file, file.active_version).await?
+    } else {
+        fs.get_uploaded_chunks(file).await?
+    }
+    .len();
+
+    if !repository
+        .delete_unfinished_upload(file.id, idle_before)
+        .await?
+    {
+        return Ok(None);
+    }
+
+    fs.purge_all(file).await?;
+
+    Ok(Some(partial_bytes(file.size, file.chunks, stored)))
+}
+
+fn partial_bytes(size: Option<i64>, chunks: Option<i64>, stored: usize) -> u64 {
+    match (size, chunks) {
+        (Some(size), Some(chunks)) if chunks > 0 => {
+            let stored = (stored as i64).min(chunks);
+            (size.max(0) as u64).saturating_mul(stored as u64) / chunks as u64
+        }
+        _ => 0,
+    }
+}
+
+/// Prune the version history of every file down to what its retention
+/// policy keeps, chunks included. History is also pruned after each edit;
+/// the sweep catches versions that aged out of their policy since, or that
+/// a changed policy no longer keeps. A file that fails is logged and
+/// skipped. Returns the number of pruned versions.
+pub async fn prune_file_versions(context: &Context) -> AppResult<u64> {
+    let fs = Fs::new(&context.config);
+    let defaults = RetentionPolicy::from(&context.config.versions);
+    let files = Repository::new(&context.db).files_with_history().await?;
+
+    let mut pruned = 0;
+    for file in files {
+        match prune_versions_of(context, &fs, &file, &defaults).await {
+            Ok(versions) => pruned += versions,
+            Err(e) => log::warn!("Failed to prune the versions of file {}: {}", file.id, e),
+        }
+    }
+
+    Ok(pruned)
+}
+
+/// Drop the rows first and the chunks after the commit, like the prune that
+/// follows an edit.
+async fn prune_versions_of(
+    context: &Context,
+    fs: &Fs<'_>,
+    file: &files::Model,
+    defaults: &RetentionPolicy,
+) -> AppResult<u64> {
+    let connection = context.db.begin().await?;
+    let versions = Repository::new(&connection)
+        .prune_versions(file.id, defaults)
+        .await?;
+    connection.commit().await?;
+
+    let repository = Repository::new(&context.db);
+    for version in &versions {
+        purge_version(&repository, fs, file, file.id, *version).await?;
+    }
+
+    Ok(versions.len() as u64)
+}
+
+/// Remove the chunks of one version of a file. Chunks that other versions
+/// borrow are copied into the oldest of them first and the references
+/// follow, so only chunks nothing points at are dropped. Run after the
+/// version's rows are gone, like [`purge_chunks`]. Reading the references,
+/// moving them and removing the chunks happen in one transaction holding
+/// the file's [`Repository::lock_chunk_refs`], so no edit on this or any
+/// other server starts borrowing from the version in the meantime.
+pub(crate) async fn purge_version<T: ConnectionTrait + TransactionTrait, F: IntoFilename>(
+    repository: &Repository<'_, T>,
+    fs: &Fs<'_>,
+    file: &F,
+    file_id: Uuid,
+    version: i32,
+) -> AppResult<()> {
+    let filename = file.filename()?;
+    let connection = repository.connection().begin().await?;
+    let locked = Repository::new(&connection);
+    locked.lock_chunk_refs(file_id).await?;
+
+    let refs = file_chunk_refs::chunk_refs(&connection, file_id).await?;
+    let relocations = refs.relocations(version);
+
+    for relocation in &relocations {
+        let data = fs.pull_v(&filename, version, relocation.chunk).await?;
+        fs.push_v(&filename, relocation.holder, relocation.chunk, &data)
+            .await?;
+    }
+
+    locked
+        .relocate_chunk_refs(file_id, version, &relocations)
+        .await?;
+
+    fs.purge_version(&filename, version).await?;
+    connection.commit().await?;
+
+    Ok(())
+}
+
+/// Remove the rows a copy wrote and whatever chunks they got, for a copy
+/// that failed or was interrupted. Anything uploaded or moved into the
+/// copied tree while the copy ran stays, in the copied folders holding it.
+pub(crate) async fn discard_copy(context: &Context, job_id: Uuid) -> AppResult<()> {
+    let files = Repository::new(&context.db)
+        .discard_copy_rows(job_id)
+        .await?;
+
+    for file in &files {
+        evict_file(file.id).await;
+    }
+
+    purge_chunks(&Fs::new(&context.config), &files).await
+}
+
+/// Remove the stored chunks of purged file rows. Run only after the rows
+/// are gone from the database, so a failed commit never leaves rows
+/// pointing at missing chunks.
+pub(crate) async fn purge_chunks(fs: &Fs<'_>, files: &[files::Model]) -> AppResult<()> {
+    for file in files.iter().filter(|f| f.mime != "dir") {
+        fs.purge_all(file).await?;
+    }
+
+    Ok(())
+}
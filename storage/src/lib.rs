@@ -1,7 +1,10 @@
+pub(crate) mod account;
 pub(crate) mod permission;
 pub(crate) mod repository;
 
 pub mod data;
+pub mod fsck;
+pub mod housekeeping;
 pub mod routes;
 
 #[cfg(test)]
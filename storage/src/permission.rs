@@ -56,4 +56,3 @@ pub(crate) async fn require_owner<C: ConnectionTrait>(
         _ => Err(Error::Forbidden("forbidden_not_owner".to_string())),
     }
 }
-
@@ -0,0 +1,87 @@
+//! Repository module for uploads whose client went away. An initial upload
+//! that never finished leaves a row with `finished_upload_at = NULL`, an edit
+//! that never finalized leaves `pending_version` set; both keep their partial
+//! chunks on the storage provider until the reaper picks them up.
+use entity::{
+    files, ColumnTrait, Condition, ConnectionTrait, EntityTrait, Expr, QueryFilter, Uuid,
+};
+use error::AppResult;
+
+use super::Repository;
+
+impl<T> Repository<'_, T>
+where
+    T: ConnectionTrait,
+{
+    /// Unfinished uploads and pending edits that saw no chunk since
+    /// `idle_before`.
+    pub(crate) async fn abandoned_uploads(&self, idle_before: i64) -> AppResult<Vec<files::Model>> {
+        files::Entity::find()
+            .filter(files::Column::Mime.ne("dir"))
+            .filter(
+                Condition::any()
+                    .add(files::Column::FinishedUploadAt.is_null())
+                    .add(files::Column::PendingVersion.is_not_null()),
+            )
+            .filter(idle_since(idle_before))
+            .all(self.connection)
+            .await
+            .map_err(From::from)
+    }
+
+    /// Drop an abandoned edit: clear the pending columns so the file keeps
+    /// serving its active version. Returns false when a chunk landed or the
+    /// edit moved on in the meantime, in which case nothing changed.
+    pub(crate) async fn reset_pending_upload(
+        &self,
+        id: Uuid,
+        version: i32,
+        idle_before: i64,
+    ) -> AppResult<bool> {
+        let result = files::Entity::update_many()
+            .col_expr(files::Column::PendingVersion, Expr::value(None::<i32>))
+            .col_expr(files::Column::PendingChunks, Expr::value(None::<i64>))
+            .col_expr(files::Column::PendingSize, Expr::value(None::<i64>))
+            .col_expr(
+                files::Column::ChunksStored,
+                Expr::col(files::Column::Chunks).into(),
+            )
+            .filter(files::Column::Id.eq(id))
+            .filter(files::Column::PendingVersion.eq(version))
+            .filter(idle_since(idle_before))
+            .exec(self.connection)
+            .await?;
+
+        Ok(result.rows_affected == 1)
+    }
+
+    /// Delete a file row whose initial upload never finished. Returns false
+    /// when a chunk landed or the upload finished in the meantime.
+    pub(crate) async fn delete_unfinished_upload(
+        &self,
+        id: Uuid,
+        idle_before: i64,
+    ) -> AppResult<bool> {
+        let result = files::Entity::delete_many()
+            .filter(files::Column::Id.eq(id))
+            .filter(files::Column::FinishedUploadAt.is_null())
+            .filter(files::Column::PendingVersion.is_null())
+            .filter(idle_since(idle_before))
+            .exec(self.connection)
+            .await?;
+
+        Ok(result.rows_affected == 1)
+    }
+}
+
+/// No chunk since `before`; uploads that never got a chunk count from the
+/// moment the row was created.
+fn idle_since(before: i64) -> Condition {
+    Condition::any()
+        .add(files::Column::LastChunkAt.lt(before))
+        .add(
+            Condition::all()
+                .add(files::Column::LastChunkAt.is_null())
+                .add(files::Column::CreatedAt.lt(before)),
+        )
+}
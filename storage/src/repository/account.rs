@@ -0,0 +1,257 @@
+//! Repository module backing the account export and import. Export reads
+//! everything the user owns, trash included; import inserts an archive the
+//! import route has already renumbered.
+use std::collections::{HashMap, HashSet};
+
+use chrono::Utc;
+use entity::{
+    file_tokens, file_versions, files, link_files, links, tokens, user_files, ActiveValue,
+    ColumnTrait, ConnectionTrait, EntityTrait, Expr, Query, QueryFilter, QueryOrder, QuerySelect,
+    SelectStatement, Uuid,
+};
+use error::{AppResult, Error};
+
+use crate::data::account::{ExportedFile, ExportedLink, ExportedLinkFile};
+
+use super::Repository;
+
+/// Ids of the files the user owns.
+fn owned_file_ids(user_id: Uuid) -> SelectStatement {
+    Query::select()
+        .column(user_files::Column::FileId)
+        .from(user_files::Entity)
+        .and_where(user_files::Column::UserId.eq(user_id))
+        .and_where(user_files::Column::IsOwner.eq(true))
+        .to_owned()
+}
+
+impl<T> Repository<'_, T>
+where
+    T: ConnectionTrait,
+{
+    /// Every file and folder the user owns with its wrapped key and search
+    /// tokens.
+    pub(crate) async fn account_files(&self, user_id: Uuid) -> AppResult<Vec<ExportedFile>> {
+        let keys: HashMap<Uuid, String> = user_files::Entity::find()
+            .select_only()
+            .column(user_files::Column::FileId)
+            .column(user_files::Column::EncryptedKey)
+            .filter(user_files::Column::UserId.eq(user_id))
+            .filter(user_files::Column::IsOwner.eq(true))
+            .into_tuple::<(Uuid, String)>()
+            .all(self.connection)
+            .await?
+            .into_iter()
+            .collect();
+
+        let mut search_tokens: HashMap<Uuid, Vec<String>> = HashMap::new();
+        let rows = file_tokens::Entity::find()
+            .select_only()
+            .column(file_tokens::Column::FileId)
+            .column(tokens::Column::Hash)
+            .column(file_tokens::Column::Weight)
+            .inner_join(tokens::Entity)
+            .filter(file_tokens::Column::FileId.in_subquery(owned_file_ids(user_id)))
+            .into_tuple::<(Uuid, String, i32)>()
+            .all(self.connection)
+            .await?;
+        for (file_id, hash, weight) in rows {
+            search_tokens
+                .entry(file_id)
+                .or_default()
+                .push(format!("{hash}:{weight}"));
+        }
+
+        let files = files::Entity::find()
+            .filter(files::Column::Id.in_subquery(owned_file_ids(user_id)))
+            .order_by_asc(files::Column::CreatedAt)
+            .all(self.connection)
+            .await?;
+
+        Ok(files
+            .into_iter()
+            .filter_map(|file| {
+                let encrypted_key = keys.get(&file.id)?.clone();
+                let search_tokens = search_tokens.remove(&file.id).unwrap_or_default();
+
+                Some(ExportedFile {
+                    file,
+                    encrypted_key,
+                    search_tokens,
+                })
+            })
+            .collect())
+    }
+
+    /// Version history of every file the user owns.
+    pub(crate) async fn account_versions(
+        &self,
+        user_id: Uuid,
+    ) -> AppResult<Vec<file_versions::Model>> {
+        file_versions::Entity::find()
+            .filter(file_versions::Column::FileId.in_subquery(owned_file_ids(user_id)))
+            .order_by_asc(file_versions::Column::FileId)
+            .order_by_asc(file_versions::Column::Version)
+            .all(self.connection)
+            .await
+            .map_err(From::from)
+    }
+
+    /// Links the user created on files they own, folder links with the
+    /// files under them.
+    pub(crate) async fn account_links(&self, user_id: Uuid) -> AppResult<Vec<ExportedLink>> {
+        let mut links: Vec<ExportedLink> = links::Entity::find()
+            .filter(links::Column::UserId.eq(user_id))
+            .filter(links::Column::FileId.in_subquery(owned_file_ids(user_id)))
+            .all(self.connection)
+            .await?
+            .into_iter()
+            .map(ExportedLink::from)
+            .collect();
+
+        let mut files: HashMap<Uuid, Vec<ExportedLinkFile>> = HashMap::new();
+        for link_file in link_files::Entity::find()
+            .filter(link_files::Column::LinkId.is_in(links.iter().map(|link| link.id)))
+            .all(self.connection)
+            .await?
+        {
+            files
+                .entry(link_file.link_id)
+                .or_default()
+                .push(link_file.into());
+        }
+
+        for link in links.iter_mut() {
+            link.files = files.remove(&link.id).unwrap_or_default();
+        }
+
+        Ok(links)
+    }
+
+    /// Insert imported files owned by `user_id`. Parents are set once every
+    /// row is in, so the order of `files` does not matter.
+    ///
+    /// Only what describes the file is taken from the archive; upload,
+    /// edit, trash and sharing state is the server's own. A file counts as
+    /// uploaded when `uploaded` has every chunk of it, keyed by file id.
+    ///
+    /// Errors with `BadRequest("file_or_directory_exists")` when two files
+    /// would end up with the same name in one folder, the destination root
+    /// included.
+    pub(crate) async fn import_files(
+        &self,
+        user_id: Uuid,
+        files: Vec<ExportedFile>,
+        uploaded: &HashMap<Uuid, i64>,
+    ) -> AppResult<u64> {
+        let mut parents = vec![];
+        let mut names = HashSet::new();
+        let imported = files.len() as u64;
+        let now = Utc::now().timestamp();
+        let manage = self.manage(user_id);
+
+        for ExportedFile {
+            file,
+            encrypted_key,
+            search_tokens,
+        } in files
+        {
+            if !names.insert((file.file_id, file.name_hash.clone()))
+                || (file.file_id.is_none() && manage.by_name(&file.name_hash, None).await.is_ok())
+            {
+                return Err(Error::BadRequest("file_or_directory_exists".to_string()));
+            }
+
+            if let Some(parent) = file.file_id {
+                parents.push((file.id, parent));
+            }
+
+            let is_dir = file.mime == "dir";
+            let chunks_stored = (!is_dir).then(|| uploaded.get(&file.id).copied().unwrap_or(0));
+            let finished =
+                !is_dir && chunks_stored.is_some_and(|stored| Some(stored) == file.chunks);
+
+            files::Entity::insert(files::ActiveModel {
+                id: ActiveValue::Set(file.id),
+                name_hash: ActiveValue::Set(file.name_hash),
+                encrypted_name: ActiveValue::Set(file.encrypted_name),
+                encrypted_thumbnail: ActiveValue::Set(file.encrypted_thumbnail),
+                mime: ActiveValue::Set(file.mime),
+                size: ActiveValue::Set(file.size.filter(|_| !is_dir)),
+                chunks: ActiveValue::Set(file.chunks.filter(|_| !is_dir)),
+                chunks_stored: ActiveValue::Set(chunks_stored),
+                file_id: ActiveValue::Set(None),
+                md5: ActiveValue::Set(file.md5),
+                sha1: ActiveValue::Set(file.sha1),
+                sha256: ActiveValue::Set(file.sha256),
+                blake2b: ActiveValue::Set(file.blake2b),
+                cipher: ActiveValue::Set(file.cipher),
+                editable: ActiveValue::Set(file.editable),
+                file_modified_at: ActiveValue::Set(file.file_modified_at),
+                created_at: ActiveValue::Set(file.created_at),
+                finished_upload_at: ActiveValue::Set(finished.then_some(now)),
+                active_version: ActiveValue::Set(file.active_version.max(1)),
+                pending_version: ActiveValue::Set(None),
+                pending_chunks: ActiveValue::Set(None),
+                pending_size: ActiveValue::Set(None),
+                last_membership_change_at: ActiveValue::Set(None),
+                members_list_signature: ActiveValue::Set(None),
+                members_list_signed_at: ActiveValue::Set(None),
+                members_list_signed_by_user_id: ActiveValue::Set(None),
+                deleted_at: ActiveValue::Set(None),
+                deleted_parent_id: ActiveValue::Set(None),
+                last_chunk_at: ActiveValue::Set(None),
+                damaged_at: ActiveValue::Set(None),
+            })
+            .exec_without_returning(self.connection)
+            .await?;
+
+            user_files::Entity::insert(user_files::ActiveModel {
+                id: ActiveValue::Set(Uuid::new_v4()),
+                file_id: ActiveValue::Set(file.id),
+                user_id: ActiveValue::Set(user_id),
+                is_owner: ActiveValue::Set(true),
+                encrypted_key: ActiveValue::Set(encrypted_key),
+                created_at: ActiveValue::Set(file.created_at),
+                expires_at: ActiveValue::NotSet,
+                share_role: ActiveValue::Set("co-owner".to_string()),
+                shared_at: ActiveValue::NotSet,
+                shared_by_user_id: ActiveValue::NotSet,
+                member_signature: ActiveValue::NotSet,
+                member_signed_at: ActiveValue::NotSet,
+            })
+            .exec_without_returning(self.connection)
+            .await?;
+
+            if !search_tokens.is_empty() {
+                self.tokens(user_id).upsert(file.id, search_tokens).await?;
+            }
+        }
+
+        for (id, parent) in parents {
+            files::Entity::update_many()
+                .col_expr(files::Column::FileId, Expr::value(parent))
+                .filter(files::Column::Id.eq(id))
+                .exec(self.connection)
+                .await?;
+        }
+
+        Ok(imported)
+    }
+
+    /// Insert imported version history rows.
+    pub(crate) async fn import_versions(
+        &self,
+        versions: Vec<file_versions::Model>,
+    ) -> AppResult<u64> {
+        let imported = versions.len() as u64;
+
+        for version in versions {
+            file_versions::Entity::insert(file_versions::ActiveModel::from(version))
+                .exec_without_returning(self.connection)
+                .await?;
+        }
+
+        Ok(imported)
+    }
+}
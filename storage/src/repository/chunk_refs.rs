@@ -0,0 +1,115 @@
+//! Repository module for the chunks a version borrows from an older version
+//! of the same file, see [`entity::file_chunk_refs`]. A reference always
+//! names the version storing the chunk, so linking to a chunk that is
+//! borrowed itself follows it to its holder.
+use chrono::Utc;
+use entity::{
+    file_chunk_refs, files, ActiveValue, ColumnTrait, ConnectionTrait, EntityTrait, Expr,
+    QueryFilter, Uuid,
+};
+use error::AppResult;
+use fs::prelude::Relocation;
+
+use super::Repository;
+
+impl<T> Repository<'_, T>
+where
+    T: ConnectionTrait,
+{
+    /// Hold the chunk references of a file until the transaction ends, so
+    /// a version purge and an edit borrowing from that version take turns
+    /// across every server sharing the database. Touching the file row takes
+    /// its row lock on Postgres and the write lock on SQLite, which has no
+    /// row-level `SELECT … FOR UPDATE`.
+    pub(crate) async fn lock_chunk_refs(&self, file_id: Uuid) -> AppResult<()> {
+        files::Entity::update_many()
+            .col_expr(files::Column::Id, Expr::col(files::Column::Id).into())
+            .filter(files::Column::Id.eq(file_id))
+            .exec(self.connection)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Make `version` borrow each `(chunk, source_version)` instead of
+    /// storing it, replacing whatever references the version had. Run in
+    /// the transaction that took [`Self::lock_chunk_refs`] and checked the
+    /// sources still exist.
+    pub(crate) async fn link_chunks(
+        &self,
+        file_id: Uuid,
+        version: i32,
+        chunks: &[(i64, i32)],
+    ) -> AppResult<()> {
+        file_chunk_refs::Entity::delete_many()
+            .filter(file_chunk_refs::Column::FileId.eq(file_id))
+            .filter(file_chunk_refs::Column::Version.eq(version))
+            .exec(self.connection)
+            .await?;
+
+        if chunks.is_empty() {
+            return Ok(());
+        }
+
+        let refs = file_chunk_refs::chunk_refs(self.connection, file_id).await?;
+        let now = Utc::now().timestamp();
+        let rows = chunks
+            .iter()
+            .map(|(chunk, source)| file_chunk_refs::ActiveModel {
+                file_id: ActiveValue::Set(file_id),
+                version: ActiveValue::Set(version),
+                chunk: ActiveValue::Set(*chunk),
+                source_version: ActiveValue::Set(refs.source(*source, *chunk)),
+                created_at: ActiveValue::Set(now),
+            });
+
+        file_chunk_refs::Entity::insert_many(rows)
+            .exec_without_returning(self.connection)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Record that the chunks of `purged` listed in `relocations` now live
+    /// in their new holder, and drop the references of `purged` itself.
+    /// Run once the bytes are copied and before the version is purged:
+    /// until then every reference still points at a stored chunk.
+    pub(crate) async fn relocate_chunk_refs(
+        &self,
+        file_id: Uuid,
+        purged: i32,
+        relocations: &[Relocation],
+    ) -> AppResult<()> {
+        for relocation in relocations {
+            file_chunk_refs::Entity::delete_many()
+                .filter(file_chunk_refs::Column::FileId.eq(file_id))
+                .filter(file_chunk_refs::Column::Version.eq(relocation.holder))
+                .filter(file_chunk_refs::Column::Chunk.eq(relocation.chunk))
+                .exec(self.connection)
+                .await?;
+
+            if relocation.repoint.is_empty() {
+                continue;
+            }
+
+            file_chunk_refs::Entity::update_many()
+                .col_expr(
+                    file_chunk_refs::Column::SourceVersion,
+                    Expr::value(relocation.holder),
+                )
+                .filter(file_chunk_refs::Column::FileId.eq(file_id))
+                .filter(file_chunk_refs::Column::Version.is_in(relocation.repoint.clone()))
+                .filter(file_chunk_refs::Column::Chunk.eq(relocation.chunk))
+                .exec(self.connection)
+                .await?;
+        }
+
+        file_chunk_refs::Entity::delete_many()
+            .filter(file_chunk_refs::Column::FileId.eq(file_id))
+            .filter(file_chunk_refs::Column::Version.eq(purged))
+            .exec(self.connection)
+            .await?;
+
+        Ok(())
+    }
+}
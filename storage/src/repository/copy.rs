@@ -0,0 +1,474 @@
+//! Server-side copy of a file or a folder tree the owner owns.
+//!
+//! [`Copies::plan`] checks the request against the tree and builds the new
+//! rows without writing anything. [`Copies::start`] writes them in one
+//! transaction, with the files left unfinished, together with the
+//! `copy_jobs` row that tracks the copy and the `copy_job_files` rows that
+//! say which files it wrote. The chunks are copied in the background
+//! afterwards, and each file is finished once its chunks are in place.
+use std::collections::{HashMap, HashSet};
+
+use chrono::Utc;
+use entity::{
+    copy_job_files, copy_jobs, files, user_files, ActiveModelTrait, ActiveValue, ColumnTrait,
+    ConnectionTrait, EntityTrait, Expr, IntoActiveModel, PaginatorTrait, Query, QueryFilter, Uuid,
+};
+use error::{AppResult, Error};
+
+use super::Repository;
+use crate::data::{app_file::AppFile, copy_files::CopyNodeData};
+
+pub(crate) struct Copies<'repository, T: ConnectionTrait> {
+    repository: &'repository Repository<'repository, T>,
+    owner_id: Uuid,
+}
+
+/// One row of a planned copy.
+pub(crate) struct PlannedCopy {
+    /// The row being copied.
+    pub source: AppFile,
+    /// The new row, parents always come before their children.
+    pub copy: files::Model,
+    pub encrypted_key: String,
+    pub search_tokens_hashed: Vec<String>,
+}
+
+/// Everything a copy is going to write. The first entry is the copy of
+/// the root.
+pub(crate) struct CopyPlan {
+    pub parent_id: Option<Uuid>,
+    pub entries: Vec<PlannedCopy>,
+}
+
+impl CopyPlan {
+    pub(crate) fn root_id(&self) -> Uuid {
+        self.entries[0].copy.id
+    }
+
+    /// Bytes the copy adds to the owner's used space.
+    pub(crate) fn size(&self) -> i64 {
+        self.entries
+            .iter()
+            .filter_map(|entry| entry.copy.size)
+            .sum()
+    }
+
+    /// Entries that carry chunks on the storage provider.
+    pub(crate) fn files(&self) -> impl Iterator<Item = &PlannedCopy> {
+        self.entries.iter().filter(|entry| entry.source.is_file())
+    }
+}
+
+/// Rows touched at once when keeping the files of a copy from looking
+/// abandoned.
+const TOUCH_BATCH: usize = 500;
+
+impl<'repository, T> Copies<'repository, T>
+where
+    T: ConnectionTrait,
+{
+    pub(crate) fn new(repository: &'repository Repository<'repository, T>, owner_id: Uuid) -> Self {
+        Self {
+            repository,
+            owner_id,
+        }
+    }
+
+    /// Check a copy of `id` into `parent_id` and build its rows. `nodes`
+    /// has to cover the copied file, or every live row of the copied
+    /// folder tree, and nothing else. Every one of those rows has to be
+    /// owned by the owner and fully uploaded.
+    pub(crate) async fn plan(
+        &self,
+        id: Uuid,
+        parent_id: Option<Uuid>,
+        nodes: Vec<CopyNodeData>,
+    ) -> AppResult<CopyPlan> {
+        let root = self.repository.by_id(id, self.owner_id).await?;
+
+        if !root.is_owner || root.user_id != self.owner_id {
+            return Err(Error::NotFound("file_not_found".to_string()));
+        }
+
+        let tree = match root.is_dir() {
+            true => {
+                self.repository
+                    .manage(self.owner_id)
+                    .file_tree(root.id)
+                    .await?
+            }
+            false => vec![root.clone()],
+        };
+
+        // `file_tree` only returns the owner's rows; a folder holding files
+        // someone else uploaded into it can't be copied whole.
+        let live = files::Entity::find()
+            .filter(files::Column::Id.is_in(self.repository.subtree_ids(root.id).await?))
+            .filter(files::Column::DeletedAt.is_null())
+            .count(self.repository.connection())
+            .await?;
+        if live != tree.len() as u64 {
+            return Err(Error::Forbidden("forbidden_not_owner".to_string()));
+        }
+
+        if let Some(parent_id) = parent_id {
+            let parent = self.repository.by_id(parent_id, self.owner_id).await?;
+
+            if !parent.is_owner || !parent.is_dir() {
+                return Err(Error::BadRequest("parent_directory_not_found".to_string()));
+            }
+
+            if tree.iter().any(|file| file.id == parent_id) {
+                return Err(Error::BadRequest("cannot_copy_into_itself".to_string()));
+            }
+        }
+
+        let mut nodes: HashMap<Uuid, CopyNodeData> =
+            nodes.into_iter().map(|node| (node.id, node)).collect();
+        let ids: HashSet<Uuid> = tree.iter().map(|file| file.id).collect();
+        if nodes.len() != ids.len() || nodes.keys().any(|id| !ids.contains(id)) {
+            return Err(Error::BadRequest("nodes_do_not_match_tree".to_string()));
+        }
+
+        if tree
+            .iter()
+            .any(|file| file.is_file() && file.finished_upload_at.is_none())
+        {
+            return Err(Error::BadRequest("file_upload_not_finished".to_string()));
+        }
+
+        let now = Utc::now().timestamp();
+        let mut children: HashMap<Uuid, Vec<AppFile>> = HashMap::new();
+        for file in tree {
+            if file.id != root.id {
+                if let Some(parent) = file.file_id {
+                    children.entry(parent).or_default().push(file);
+                }
+            }
+        }
+
+        // Walk the tree from the root so every parent is planned, and
+        // later inserted, before its children.
+        let mut entries = Vec::with_capacity(nodes.len());
+        let mut queue = vec![(root, parent_id)];
+        while let Some((source, copy_parent)) = queue.pop() {
+            let node = nodes
+                .remove(&source.id)
+                .ok_or_else(|| Error::BadRequest("nodes_do_not_match_tree".to_string()))?;
+            let copy = copy_of(&source, &node, copy_parent, now);
+
+            for child in children.remove(&source.id).unwrap_or_default() {
+                queue.push((child, Some(copy.id)));
+            }
+
+            entries.push(PlannedCopy {
+                source,
+                copy,
+                encrypted_key: node.encrypted_key,
+                search_tokens_hashed: node.search_tokens_hashed,
+            });
+        }
+
+        self.name_is_free(&entries[0].copy.name_hash, parent_id)
+            .await?;
+
+        Ok(CopyPlan { parent_id, entries })
+    }
+
+    async fn name_is_free(&self, name_hash: &str, parent_id: Option<Uuid>) -> AppResult<()> {
+        let manage = self.repository.manage(self.owner_id);
+
+        match manage.by_name(name_hash, parent_id).await {
+            Ok(_) => Err(Error::BadRequest("file_or_directory_exists".to_string())),
+            Err(_) => Ok(()),
+        }
+    }
+
+    /// Write the rows of a copy, its files still waiting for their chunks,
+    /// and the job that is going to copy them. The name is checked again at
+    /// the destination, something with the same name may have landed there
+    /// since the plan was made.
+    pub(crate) async fn start(&self, plan: &CopyPlan) -> AppResult<copy_jobs::Model> {
+        self.name_is_free(&plan.entries[0].copy.name_hash, plan.parent_id)
+            .await?;
+
+        let now = Utc::now().timestamp();
+        let id = Uuid::new_v4();
+        copy_jobs::Entity::insert(copy_jobs::ActiveModel {
+            id: ActiveValue::Set(id),
+            user_id: ActiveValue::Set(self.owner_id),
+            file_id: ActiveValue::Set(plan.root_id()),
+            status: ActiveValue::Set("running".to_string()),
+            files_total: ActiveValue::Set(plan.files().count() as i64),
+            files_copied: ActiveValue::Set(0),
+            error: ActiveValue::Set(None),
+            created_at: ActiveValue::Set(now),
+            updated_at: ActiveValue::Set(now),
+            finished_at: ActiveValue::Set(None),
+        })
+        .exec_without_returning(self.repository.connection())
+        .await?;
+
+        for entry in &plan.entries {
+            files::Entity::insert(entry.copy.clone().into_active_model().reset_all())
+                .exec_without_returning(self.repository.connection())
+                .await?;
+
+            self.repository
+                .tokens(self.owner_id)
+                .upsert(entry.copy.id, entry.search_tokens_hashed.clone())
+                .await?;
+
+            user_files::Entity::insert(user_files::ActiveModel {
+                id: ActiveValue::Set(Uuid::new_v4()),
+                file_id: ActiveValue::Set(entry.copy.id),
+                user_id: ActiveValue::Set(self.owner_id),
+                is_owner: ActiveValue::Set(true),
+                encrypted_key: ActiveValue::Set(entry.encrypted_key.clone()),
+                created_at: ActiveValue::Set(now),
+                expires_at: ActiveValue::NotSet,
+                share_role: ActiveValue::Set("co-owner".to_string()),
+                shared_at: ActiveValue::NotSet,
+                shared_by_user_id: ActiveValue::NotSet,
+                member_signature: ActiveValue::NotSet,
+                member_signed_at: ActiveValue::NotSet,
+            })
+            .exec_without_returning(self.repository.connection())
+            .await?;
+
+            copy_job_files::Entity::insert(copy_job_files::ActiveModel {
+                job_id: ActiveValue::Set(id),
+                file_id: ActiveValue::Set(entry.copy.id),
+            })
+            .exec_without_returning(self.repository.connection())
+            .await?;
+        }
+
+        self.job(id).await
+    }
+
+    /// One of the owner's copies.
+    pub(crate) async fn job(&self, id: Uuid) -> AppResult<copy_jobs::Model> {
+        copy_jobs::Entity::find_by_id(id)
+            .filter(copy_jobs::Column::UserId.eq(self.owner_id))
+            .one(self.repository.connection())
+            .await?
+            .ok_or_else(|| Error::NotFound("copy_not_found".to_string()))
+    }
+}
+
+impl<T> Repository<'_, T>
+where
+    T: ConnectionTrait,
+{
+    /// A file of a copy has all of its chunks: finish it and count it.
+    /// Returns `false`, leaving the file alone, when the copy is no longer
+    /// running because it was ended elsewhere.
+    pub(crate) async fn copied_file(&self, job_id: Uuid, file_id: Uuid) -> AppResult<bool> {
+        let now = Utc::now().timestamp();
+
+        let counted = copy_jobs::Entity::update_many()
+            .col_expr(
+                copy_jobs::Column::FilesCopied,
+                Expr::col(copy_jobs::Column::FilesCopied).add(1),
+            )
+            .col_expr(copy_jobs::Column::UpdatedAt, Expr::value(now))
+            .filter(copy_jobs::Column::Id.eq(job_id))
+            .filter(copy_jobs::Column::Status.eq("running"))
+            .exec(self.connection())
+            .await?;
+
+        if counted.rows_affected == 0 {
+            return Ok(false);
+        }
+
+        files::Entity::update_many()
+            .col_expr(
+                files::Column::ChunksStored,
+                Expr::col(files::Column::Chunks).into(),
+            )
+            .col_expr(files::Column::FinishedUploadAt, Expr::value(now))
+            .filter(files::Column::Id.eq(file_id))
+            .exec(self.connection())
+            .await?;
+
+        Ok(true)
+    }
+
+    /// Stamp `updated_at` on a running copy and `last_chunk_at` on its
+    /// files still waiting for their chunks, so the abandoned-upload reaper
+    /// leaves them alone. Returns `false` when the copy is no longer
+    /// running.
+    pub(crate) async fn touch_copies(&self, job_id: Uuid, ids: &[Uuid]) -> AppResult<bool> {
+        let now = Utc::now().timestamp();
+
+        let touched = copy_jobs::Entity::update_many()
+            .col_expr(copy_jobs::Column::UpdatedAt, Expr::value(now))
+            .filter(copy_jobs::Column::Id.eq(job_id))
+            .filter(copy_jobs::Column::Status.eq("running"))
+            .exec(self.connection())
+            .await?;
+
+        if touched.rows_affected == 0 {
+            return Ok(false);
+        }
+
+        for ids in ids.chunks(TOUCH_BATCH) {
+            files::Entity::update_many()
+                .col_expr(files::Column::LastChunkAt, Expr::value(now))
+                .filter(files::Column::Id.is_in(ids.to_vec()))
+                .filter(files::Column::FinishedUploadAt.is_null())
+                .exec(self.connection())
+                .await?;
+        }
+
+        Ok(true)
+    }
+
+    /// Mark a running copy done, or failed with `error`. Returns `false`
+    /// when the copy had already ended, the copy itself and the reaper
+    /// race for it and only one of them gets to end it.
+    pub(crate) async fn end_copy(&self, job_id: Uuid, error: Option<String>) -> AppResult<bool> {
+        let now = Utc::now().timestamp();
+        let status = match error {
+            Some(_) => "failed",
+            None => "done",
+        };
+
+        let ended = copy_jobs::Entity::update_many()
+            .col_expr(copy_jobs::Column::Status, Expr::value(status))
+            .col_expr(copy_jobs::Column::Error, Expr::value(error))
+            .col_expr(copy_jobs::Column::UpdatedAt, Expr::value(now))
+            .col_expr(copy_jobs::Column::FinishedAt, Expr::value(now))
+            .filter(copy_jobs::Column::Id.eq(job_id))
+            .filter(copy_jobs::Column::Status.eq("running"))
+            .exec(self.connection())
+            .await?;
+
+        Ok(ended.rows_affected > 0)
+    }
+
+    /// Delete the rows a copy wrote, returning them. Folders of the copy
+    /// that hold anything the copy did not write, uploaded or moved there
+    /// while it ran, are kept together with that content.
+    pub(crate) async fn discard_copy_rows(&self, job_id: Uuid) -> AppResult<Vec<files::Model>> {
+        let ids = copy_job_files::Entity::find()
+            .filter(copy_job_files::Column::JobId.eq(job_id))
+            .all(self.connection())
+            .await?
+            .into_iter()
+            .map(|row| row.file_id)
+            .collect::<Vec<_>>();
+        let rows = files::Entity::find()
+            .filter(files::Column::Id.is_in(ids))
+            .all(self.connection())
+            .await?;
+
+        let (folders, files): (Vec<_>, Vec<_>) = rows.iter().partition(|row| row.mime == "dir");
+
+        files::Entity::delete_many()
+            .filter(files::Column::Id.is_in(files.iter().map(|row| row.id)))
+            .exec(self.connection())
+            .await?;
+
+        // Deepest folders first: a folder goes once nothing is left in it.
+        let folders = folders.iter().map(|row| row.id).collect::<Vec<_>>();
+        loop {
+            let deleted = files::Entity::delete_many()
+                .filter(files::Column::Id.is_in(folders.clone()))
+                .filter(
+                    files::Column::Id.not_in_subquery(
+                        Query::select()
+                            .column(files::Column::FileId)
+                            .from(files::Entity)
+                            .and_where(files::Column::FileId.is_not_null())
+                            .to_owned(),
+                    ),
+                )
+                .exec(self.connection())
+                .await?;
+
+            if deleted.rows_affected == 0 {
+                break;
+            }
+        }
+
+        let remaining = files::Entity::find()
+            .filter(files::Column::Id.is_in(folders))
+            .all(self.connection())
+            .await?
+            .into_iter()
+            .map(|row| row.id)
+            .collect::<HashSet<_>>();
+
+        Ok(rows
+            .into_iter()
+            .filter(|row| !remaining.contains(&row.id))
+            .collect())
+    }
+
+    /// Copies still running that made no progress since `idle_before`,
+    /// left behind by a server that went away in the middle of them.
+    pub(crate) async fn stalled_copies(
+        &self,
+        idle_before: i64,
+    ) -> AppResult<Vec<copy_jobs::Model>> {
+        copy_jobs::Entity::find()
+            .filter(copy_jobs::Column::Status.eq("running"))
+            .filter(copy_jobs::Column::UpdatedAt.lt(idle_before))
+            .all(self.connection())
+            .await
+            .map_err(From::from)
+    }
+}
+
+/// The new row for a copy of `source`. Content fields are taken from the
+/// source's active version, the copy starts its own history at v1 with
+/// no sharing or trash state carried over. A file starts out unfinished,
+/// it is finished once its chunks are copied. The copy keeps the
+/// source's key, so the source's thumbnail still decrypts when the client
+/// doesn't send one.
+fn copy_of(
+    source: &AppFile,
+    node: &CopyNodeData,
+    parent_id: Option<Uuid>,
+    now: i64,
+) -> files::Model {
+    let is_file = source.is_file();
+
+    files::Model {
+        id: Uuid::new_v4(),
+        name_hash: node.name_hash.clone(),
+        encrypted_name: node.encrypted_name.clone(),
+        encrypted_thumbnail: node
+            .encrypted_thumbnail
+            .clone()
+            .or_else(|| source.encrypted_thumbnail.clone()),
+        mime: source.mime.clone(),
+        size: source.size,
+        chunks: source.chunks,
+        chunks_stored: if is_file { Some(0) } else { None },
+        file_id: parent_id,
+        md5: source.md5.clone(),
+        sha1: source.sha1.clone(),
+        sha256: source.sha256.clone(),
+        blake2b: source.blake2b.clone(),
+        cipher: source.cipher.clone(),
+        editable: source.editable,
+        file_modified_at: source.file_modified_at,
+        created_at: now,
+        finished_upload_at: None,
+        active_version: 1,
+        pending_version: None,
+        pending_chunks: None,
+        pending_size: None,
+        last_membership_change_at: None,
+        members_list_signature: None,
+        members_list_signed_at: None,
+        members_list_signed_by_user_id: None,
+        deleted_at: None,
+        deleted_parent_id: None,
+        last_chunk_at: if is_file { Some(now) } else { None },
+        damaged_at: None,
+    }
+}
@@ -0,0 +1,82 @@
+//! Repository module backing `hoodik fsck`, which compares the database with
+//! what the storage provider actually holds. Everything here ignores the
+//! trash: trashed files keep their chunks until they are purged.
+use std::collections::HashMap;
+
+use entity::{
+    file_versions, files, ColumnTrait, ConnectionTrait, EntityTrait, Expr, QueryFilter, QueryOrder,
+    QuerySelect, Uuid,
+};
+use error::AppResult;
+
+use super::Repository;
+
+impl<T> Repository<'_, T>
+where
+    T: ConnectionTrait,
+{
+    /// Page of non-directory file rows ordered by id, starting after `after`.
+    pub(crate) async fn fsck_files(
+        &self,
+        after: Option<Uuid>,
+        limit: u64,
+    ) -> AppResult<Vec<files::Model>> {
+        let mut query = files::Entity::find().filter(files::Column::Mime.ne("dir"));
+
+        if let Some(after) = after {
+            query = query.filter(files::Column::Id.gt(after));
+        }
+
+        query
+            .order_by_asc(files::Column::Id)
+            .limit(limit)
+            .all(self.connection)
+            .await
+            .map_err(From::from)
+    }
+
+    /// Every recorded version number, grouped by file.
+    pub(crate) async fn fsck_versions(&self) -> AppResult<HashMap<Uuid, Vec<i32>>> {
+        let rows = file_versions::Entity::find()
+            .select_only()
+            .column(file_versions::Column::FileId)
+            .column(file_versions::Column::Version)
+            .into_tuple::<(Uuid, i32)>()
+            .all(self.connection)
+            .await?;
+
+        let mut versions: HashMap<Uuid, Vec<i32>> = HashMap::new();
+        for (file_id, version) in rows {
+            versions.entry(file_id).or_default().push(version);
+        }
+
+        Ok(versions)
+    }
+
+    /// Drop `file_versions` rows whose chunks are gone, so the history no
+    /// longer offers a restore that cannot work.
+    pub(crate) async fn fsck_forget_version(&self, file_id: Uuid, version: i32) -> AppResult<()> {
+        file_versions::Entity::delete_many()
+            .filter(file_versions::Column::FileId.eq(file_id))
+            .filter(file_versions::Column::Version.eq(version))
+            .exec(self.connection)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Set or clear `damaged_at` on a file.
+    pub(crate) async fn fsck_set_damaged(
+        &self,
+        id: Uuid,
+        damaged_at: Option<i64>,
+    ) -> AppResult<()> {
+        files::Entity::update_many()
+            .col_expr(files::Column::DamagedAt, Expr::value(damaged_at))
+            .filter(files::Column::Id.eq(id))
+            .exec(self.connection)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -4,17 +4,24 @@ use std::{collections::HashMap, fmt::Display, str::FromStr};
 
 use chrono::Utc;
 use entity::{
-    file_versions, files, user_files, ActiveModelTrait, ActiveValue, ColumnTrait, ConnectionTrait,
-    EntityTrait, Order, QueryFilter, QueryOrder, Statement, Uuid, Value,
+    file_versions, files, user_files, ActiveModelTrait, ActiveValue, ColumnTrait, Condition,
+    ConnectionTrait, EntityTrait, Expr, Order, PaginatorTrait, QueryFilter, QueryOrder,
+    QuerySelect, Statement, Uuid, Value,
 };
 use error::{AppResult, Error};
 use validr::Validation;
 
 use super::Repository;
 use crate::data::{
-    app_file::AppFile, query::Query as RequestQuery, rename::Rename,
-    replace_content::ValidatedReplaceContent, response::Response, set_editable::SetEditable,
+    app_file::AppFile,
+    cursor::{Cursor, SortKey},
+    query::{Query as RequestQuery, MAX_PAGE_SIZE},
+    rename::Rename,
+    replace_content::ValidatedReplaceContent,
+    response::Response,
+    set_editable::SetEditable,
     update_hashes::UpdateHashes,
+    version_policy::RetentionPolicy,
 };
 use futures::future::try_join_all;
 
@@ -98,39 +105,85 @@ where
             selector = selector.filter(files::Column::Editable.eq(editable));
         }
 
-        let mut order = Order::Asc;
-        if let Some(ord) = &request_query.order {
-            if ord == "desc" {
-                order = Order::Desc;
-            }
-        }
+        let desc = request_query.order.as_deref() == Some("desc");
+        let order = if desc { Order::Desc } else { Order::Asc };
 
-        if let Some(order_by) = request_query.order_by.as_ref() {
-            let column = match order_by.as_str() {
-                "modified_at" => files::Column::FileModifiedAt,
-                "size" => files::Column::Size,
-                _ => return Err(Error::BadRequest("invalid_order_by".to_string())),
-            };
+        let paginated = request_query.limit.is_some() || request_query.cursor.is_some();
+        let sort = match request_query.order_by.as_deref() {
+            Some(order_by) => Some(SortKey::parse(order_by)?),
+            // Pages need a total order, unsorted listings go by creation.
+            None if paginated => Some(SortKey::CreatedAt),
+            None => None,
+        };
+
+        let total = match request_query.with_total.unwrap_or(false) {
+            true => Some(selector.clone().count(self.repository.connection()).await?),
+            false => None,
+        };
 
-            selector = selector.order_by(column, order);
+        if let Some(sort) = sort {
+            selector = selector
+                .order_by(sort.expr(), order.clone())
+                .order_by(files::Column::Id, order);
         }
 
-        let mut children = selector
-            .into_model::<AppFile>()
-            .all(self.repository.connection())
-            .await
-            .map_err(Error::from)?;
+        let mut next_cursor = None;
+        let mut children = match (paginated, sort) {
+            (true, Some(sort)) => {
+                if let Some(cursor) = request_query.cursor.as_deref() {
+                    let cursor = Cursor::decode(cursor)?;
+                    if cursor.sort != sort || cursor.desc != desc {
+                        return Err(Error::BadRequest("cursor_order_mismatch".to_string()));
+                    }
+
+                    selector = selector.filter(after_cursor(&cursor));
+                }
+
+                let limit = request_query
+                    .limit
+                    .unwrap_or(MAX_PAGE_SIZE)
+                    .clamp(1, MAX_PAGE_SIZE);
+
+                // One extra row tells whether another page follows without
+                // a second query.
+                let mut page = selector
+                    .limit(limit + 1)
+                    .into_model::<AppFile>()
+                    .all(self.repository.connection())
+                    .await
+                    .map_err(Error::from)?;
+
+                if page.len() as u64 > limit {
+                    page.truncate(limit as usize);
+                    next_cursor = page
+                        .last()
+                        .map(|last| Cursor::after(last, sort, desc).encode())
+                        .transpose()?;
+                }
+
+                page
+            }
+            _ => selector
+                .into_model::<AppFile>()
+                .all(self.repository.connection())
+                .await
+                .map_err(Error::from)?,
+        };
+
         self.repository.enrich_owner_emails(&mut children).await?;
         self.repository
             .enrich_shared_with_counts(&mut children)
             .await?;
-        self.repository
-            .enrich_owner_emails(&mut parents)
-            .await?;
+        self.repository.enrich_owner_emails(&mut parents).await?;
         self.repository
             .enrich_shared_with_counts(&mut parents)
             .await?;
-        Ok(Response { parents, children })
+        Ok(Response {
+            parents,
+            children,
+            next_cursor,
+            total,
+        })
     }
 
     /// Breadcrumb trail from the given directory up to the highest
@@ -211,35 +264,7 @@ where
     /// Get the file or a directory, if we get a directory we will also
     /// recursively get all the files and directories inside it
     pub(crate) async fn file_tree(&self, id: Uuid) -> AppResult<Vec<AppFile>> {
-        let sql = r#"
-            WITH RECURSIVE file_tree(id, file_id) AS (
-            SELECT id, file_id FROM files WHERE id = $1
-            UNION ALL
-            SELECT child.id, child.file_id FROM files child
-            JOIN file_tree parent ON parent.id = child.file_id
-            )
-            SELECT id, file_id FROM file_tree;
-        "#;
-
-        let ids = files::Entity::find()
-            .from_raw_sql(Statement::from_sql_and_values(
-                self.repository.connection().get_database_backend(),
-                sql,
-                [id.into()],
-            ))
-            .into_json()
-            .all(self.repository.connection())
-            .await?
-            .into_iter()
-            .map(|json| {
-                let id = json.get("id").unwrap().as_str().unwrap_or_default();
-
-                match Uuid::from_str(id) {
-                    Ok(id) => id,
-                    Err(_) => Uuid::nil(),
-                }
-            })
-            .collect::<Vec<Uuid>>();
+        let ids = files::subtree_ids(self.repository.connection(), id).await?;
 
         let user_id = self.owner_id;
 
@@ -415,33 +440,7 @@ where
     /// ownership filter. Used by [`Self::self_remove_recursive`] which
     /// only needs the id set.
     pub(crate) async fn subtree_ids(&self, root_id: Uuid) -> AppResult<Vec<Uuid>> {
-        let sql = r#"
-            WITH RECURSIVE file_tree(id, file_id) AS (
-            SELECT id, file_id FROM files WHERE id = $1
-            UNION ALL
-            SELECT child.id, child.file_id FROM files child
-            JOIN file_tree parent ON parent.id = child.file_id
-            )
-            SELECT id FROM file_tree;
-        "#;
-        let rows = files::Entity::find()
-            .from_raw_sql(Statement::from_sql_and_values(
-                self.repository.connection().get_database_backend(),
-                sql,
-                [root_id.into()],
-            ))
-            .into_json()
-            .all(self.repository.connection())
-            .await?;
-        let mut ids = Vec::with_capacity(rows.len());
-        for row in rows {
-            if let Some(s) = row.get("id").and_then(|v| v.as_str()) {
-                if let Ok(id) = Uuid::from_str(s) {
-                    ids.push(id);
-                }
-            }
-        }
-        Ok(ids)
+        self.repository.subtree_ids(root_id).await
     }
 
     /// Create a file entry in the database and set the owner with the
@@ -505,11 +504,7 @@ where
     /// Route gates by `permission::require_write` — Editors and
     /// Co-owners can update hashes on shared files they just chunk-
     /// uploaded.
-    pub(crate) async fn update_hashes(
-        &self,
-        id: Uuid,
-        data: UpdateHashes,
-    ) -> AppResult<AppFile> {
+    pub(crate) async fn update_hashes(&self, id: Uuid, data: UpdateHashes) -> AppResult<AppFile> {
         let file = self.repository.by_id(id, self.owner_id).await?;
 
         if file.is_dir() {
@@ -541,6 +536,12 @@ where
     /// - `BadRequest("cannot_replace_directory")` for directories.
     /// - `BadRequest("file_not_editable")` when the file is not flagged
     ///   `editable`.
+    /// - `BadRequest("reused_chunk_not_found")` when `reuse_chunks` names a
+    ///   version the file no longer has, or a chunk past its end.
+    ///
+    /// Run it in a transaction: the file's chunk references are locked
+    /// before the reused versions are checked and stay locked until commit,
+    /// so the versions they come from are not purged in between.
     pub(crate) async fn replace_content(
         &self,
         id: Uuid,
@@ -552,6 +553,7 @@ where
         // to live here is gone. Whether the row is owner or non-owner,
         // the saver-attribution machinery in `finish` writes the
         // caller's id into `file_versions.user_id`.
+        self.repository.lock_chunk_refs(id).await?;
         let file = self.repository.by_id(id, self.owner_id).await?;
 
         if file.is_dir() {
@@ -566,15 +568,33 @@ where
         // `force = true`, abandoning the previous pending edit.
         let abandoned_pending = if let Some(pending) = file.pending_version {
             if !data.force {
-                return Err(Error::Conflict(
-                    "another_edit_is_in_progress".to_string(),
-                ));
+                return Err(Error::Conflict("another_edit_is_in_progress".to_string()));
             }
             Some(pending)
         } else {
             None
         };
 
+        // Reused chunks may only come from content the file still has: the
+        // active version or one kept in history.
+        if !data.reuse_chunks.is_empty() {
+            let mut chunks = HashMap::from([(file.active_version, file.chunks.unwrap_or(0))]);
+            let history = file_versions::Entity::find()
+                .filter(file_versions::Column::FileId.eq(id))
+                .all(self.repository.connection())
+                .await?;
+            chunks.extend(history.into_iter().map(|v| (v.version, v.chunks)));
+
+            let missing = data.reuse_chunks.iter().any(|(chunk, version)| {
+                chunks
+                    .get(version)
+                    .is_none_or(|version_chunks| chunk >= version_chunks)
+            });
+            if missing {
+                return Err(Error::BadRequest("reused_chunk_not_found".to_string()));
+            }
+        }
+
         // Allocate the next pending version. With `force`, take a slot
         // strictly above any abandoned pending so straggler chunk uploads
         // from the dying client can never accidentally land in the new
@@ -589,6 +609,7 @@ where
             pending_size: ActiveValue::Set(Some(data.size)),
             chunks_stored: ActiveValue::Set(Some(0)),
             file_modified_at: ActiveValue::Set(now),
+            last_chunk_at: ActiveValue::Set(Some(now)),
             ..Default::default()
         };
 
@@ -604,6 +625,10 @@ where
 
         active_model.update(self.repository.connection()).await?;
 
+        self.repository
+            .link_chunks(id, next, &data.reuse_chunks)
+            .await?;
+
         self.repository
             .tokens(self.owner_id)
             .rename(id, data.search_tokens_hashed)
@@ -613,6 +638,21 @@ where
         Ok((file, abandoned_pending))
     }
 
+    /// Stamp `last_chunk_at` after chunks landed, so the abandoned-upload
+    /// reaper leaves uploads that are still moving alone.
+    pub(crate) async fn touch_upload(&self, id: Uuid) -> AppResult<()> {
+        files::Entity::update_many()
+            .col_expr(
+                files::Column::LastChunkAt,
+                Expr::value(Utc::now().timestamp()),
+            )
+            .filter(files::Column::Id.eq(id))
+            .exec(self.repository.connection())
+            .await?;
+
+        Ok(())
+    }
+
     /// Toggle the `editable` flag on an existing file.
     /// Only the owner can convert a regular file into an editable note (or back).
     /// Directories are rejected — `editable` is a file-level concept.
@@ -642,7 +682,9 @@ where
         }
 
         if file.is_dir() {
-            return Err(Error::BadRequest("cannot_set_editable_on_directory".to_string()));
+            return Err(Error::BadRequest(
+                "cannot_set_editable_on_directory".to_string(),
+            ));
         }
 
         let validated = data.validate()?;
@@ -692,8 +734,10 @@ where
     /// idempotent — a retried last chunk that double-fires `finish`
     /// errors out cleanly instead of flipping pointers twice.
     ///
-    /// After the swap, prunes history over `max_file_versions` and
-    /// returns the version numbers that were dropped from the database.
+    /// After the swap, prunes the history down to what the file's retention
+    /// policy keeps, `defaults` when neither the file nor its owner set
+    /// one, and returns the version numbers that were dropped from the
+    /// database.
     /// The caller is expected to wipe their on-disk directories
     /// (`fs.purge_version`) post-commit; failures there are best-effort.
     ///
@@ -702,7 +746,7 @@ where
     pub(crate) async fn finish(
         &self,
         file: &AppFile,
-        max_file_versions: usize,
+        defaults: &RetentionPolicy,
     ) -> AppResult<(AppFile, Vec<i32>)> {
         // Caller must hold a `user_files` row for this file. Whether
         // they're owner or non-owner is up to the route gate; here we
@@ -726,6 +770,7 @@ where
                 chunks: ActiveValue::Set(file.chunks.unwrap_or(0)),
                 sha256: ActiveValue::Set(file.sha256.clone()),
                 created_at: ActiveValue::Set(now),
+                pinned_at: ActiveValue::Set(None),
             };
             file_versions::Entity::insert(snapshot)
                 .exec_without_returning(conn)
@@ -774,16 +819,28 @@ where
             .await?;
         }
 
-        // Prune oldest history beyond the cap. Inside the same transaction
-        // so a partial prune can never escape — either we drop these rows
-        // and the swap commits, or both roll back.
-        let pruned = self
-            .repository
-            .versions(self.owner_id)
-            .prune_over_cap(file.id, max_file_versions)
-            .await?;
+        // Prune what the retention policy no longer keeps. Inside the same
+        // transaction so a partial prune can never escape — either we drop
+        // these rows and the swap commits, or both roll back.
+        let pruned = self.repository.prune_versions(file.id, defaults).await?;
 
         let updated = self.repository.by_id(file.id, file.user_id).await?;
         Ok((updated, pruned))
     }
 }
+
+/// Rows that come after `cursor` in its own ordering: a later sort value,
+/// or the same value and a later id.
+fn after_cursor(cursor: &Cursor) -> Condition {
+    let sort = Expr::expr(cursor.sort.expr());
+    let id = Expr::col((files::Entity, files::Column::Id));
+
+    let (past_value, past_id) = match cursor.desc {
+        true => (sort.clone().lt(cursor.value), id.lt(cursor.id)),
+        false => (sort.clone().gt(cursor.value), id.gt(cursor.id)),
+    };
+
+    Condition::any()
+        .add(past_value)
+        .add(Condition::all().add(sort.eq(cursor.value)).add(past_id))
+}
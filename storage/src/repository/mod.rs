@@ -1,12 +1,22 @@
+pub(crate) mod abandoned;
+pub(crate) mod account;
 pub(crate) mod cached;
+pub(crate) mod chunk_refs;
+pub(crate) mod copy;
+pub(crate) mod fsck;
 pub(crate) mod manage;
 pub(crate) mod query;
 pub(crate) mod tokens;
+pub(crate) mod trash;
+pub(crate) mod version_policies;
 pub(crate) mod versions;
 
 use crate::data::app_file::AppFile;
 
-use self::{manage::Manage, query::Query, tokens::Tokens, versions::Versions};
+use self::{
+    copy::Copies, manage::Manage, query::Query, tokens::Tokens, trash::Trash,
+    version_policies::VersionPolicies, versions::Versions,
+};
 use entity::{
     files, links, numeric::Numeric, user_files, users, ColumnTrait, ConnectionTrait, EntityTrait,
     Expr, IntoCondition, JoinType, QueryFilter, QuerySelect, RelationTrait, Select, Uuid, Value,
@@ -58,6 +68,23 @@ where
         Tokens::<'repository>::new(self, user_id)
     }
 
+    /// Trash operations from the owners perspective: move to trash,
+    /// list, restore and purge.
+    pub(crate) fn trash<'repository>(&'repository self, owner_id: Uuid) -> Trash<'repository, T>
+    where
+        Self: 'repository,
+    {
+        Trash::<'repository>::new(self, owner_id)
+    }
+
+    /// Server-side copies of files and folder trees the owner owns.
+    pub(crate) fn copies<'repository>(&'repository self, owner_id: Uuid) -> Copies<'repository, T>
+    where
+        Self: 'repository,
+    {
+        Copies::<'repository>::new(self, owner_id)
+    }
+
     /// Versioned-chunks history operations: list/restore/fork/delete.
     pub(crate) fn versions<'repository>(
         &'repository self,
@@ -69,15 +96,27 @@ where
         Versions::<'repository>::new(self, owner_id)
     }
 
+    /// Retention policies of the owner's version history.
+    pub(crate) fn version_policies<'repository>(
+        &'repository self,
+        owner_id: Uuid,
+    ) -> VersionPolicies<'repository, T>
+    where
+        Self: 'repository,
+    {
+        VersionPolicies::<'repository>::new(self, owner_id)
+    }
+
     /// Get the inner database connection
-    pub(crate) fn connection(&self) -> &impl ConnectionTrait {
+    pub(crate) fn connection(&self) -> &T {
         self.connection
     }
 
     /// Total owner-attributed bytes stored across the whole instance. Mirrors
     /// the per-user [`query::Query::used_space`] aggregate with the per-user
     /// filter dropped, so the instance ceiling counts every owned file exactly
-    /// once regardless of who owns it.
+    /// once regardless of who owns it. Trashed files still count, their
+    /// chunks occupy the storage until the trash is purged.
     pub(crate) async fn instance_used_space(&self) -> AppResult<i64> {
         let bytes = user_files::Entity::find()
             .select_only()
@@ -94,6 +133,12 @@ where
             .unwrap_or(0))
     }
 
+    /// Ids of the file or directory and everything under it, with no
+    /// ownership or trash filter.
+    pub(crate) async fn subtree_ids(&self, root_id: Uuid) -> AppResult<Vec<Uuid>> {
+        files::subtree_ids(self.connection, root_id).await
+    }
+
     /// Load the file from the database by its id
     pub(crate) async fn by_id<V>(&self, id: V, user_id: Uuid) -> AppResult<AppFile>
     where
@@ -108,9 +153,11 @@ where
             .ok_or_else(|| Error::NotFound(format!("file_not_found:{id}")))
     }
 
-    /// Preset the selector for the given user, maybe check if the user is the owner
+    /// Preset the selector for the given user, maybe check if the user is the owner.
+    /// Rows in the trash are left out, see [`Self::trash_selector`].
     pub(crate) fn selector(&self, user_id: Uuid, check_is_owner: bool) -> Select<files::Entity> {
         self.build_selector(user_id, check_is_owner, false)
+            .filter(files::Column::DeletedAt.is_null())
     }
 
     /// Listing variant that leaves `files.encrypted_thumbnail` in the
@@ -124,6 +171,13 @@ where
         check_is_owner: bool,
     ) -> Select<files::Entity> {
         self.build_selector(user_id, check_is_owner, true)
+            .filter(files::Column::DeletedAt.is_null())
+    }
+
+    /// Only the rows the owner moved to the trash, in the compact shape.
+    pub(crate) fn trash_selector(&self, owner_id: Uuid) -> Select<files::Entity> {
+        self.build_selector(owner_id, true, true)
+            .filter(files::Column::DeletedAt.is_not_null())
     }
 
     fn build_selector(
@@ -163,13 +217,24 @@ where
                         .and(user_files::Column::IsOwner.eq(true))
                         .into_condition()
                 }),
-            false => files::Relation::UserFiles
-                .def()
-                .on_condition(move |_left, right| {
-                    Expr::col((right, user_files::Column::UserId))
-                        .eq(user_id)
-                        .into_condition()
-                }),
+            // Shares past their `expires_at` drop out of listings right
+            // away, the purge job removes the rows later.
+            false => {
+                let now = chrono::Utc::now().timestamp();
+
+                files::Relation::UserFiles
+                    .def()
+                    .on_condition(move |_left, right| {
+                        Expr::col((right.clone(), user_files::Column::UserId))
+                            .eq(user_id)
+                            .and(
+                                Expr::col((right.clone(), user_files::Column::ExpiresAt))
+                                    .is_null()
+                                    .or(Expr::col((right, user_files::Column::ExpiresAt)).gt(now)),
+                            )
+                            .into_condition()
+                    })
+            }
         };
 
         selector.join(JoinType::InnerJoin, rel).join(
@@ -189,11 +254,8 @@ where
     /// and surfacing it in the UI would clutter their own root. Runs at
     /// most two queries regardless of `files.len()`.
     pub(crate) async fn enrich_owner_emails(&self, files: &mut [AppFile]) -> AppResult<()> {
-        let target_file_ids: Vec<Uuid> = files
-            .iter()
-            .filter(|f| !f.is_owner)
-            .map(|f| f.id)
-            .collect();
+        let target_file_ids: Vec<Uuid> =
+            files.iter().filter(|f| !f.is_owner).map(|f| f.id).collect();
         if target_file_ids.is_empty() {
             return Ok(());
         }
@@ -232,7 +294,8 @@ where
     /// file browser to render an inline "shared with others" hint. Runs
     /// a single grouped count regardless of `files.len()`.
     pub(crate) async fn enrich_shared_with_counts(&self, files: &mut [AppFile]) -> AppResult<()> {
-        let target_file_ids: Vec<Uuid> = files.iter().filter(|f| f.is_owner).map(|f| f.id).collect();
+        let target_file_ids: Vec<Uuid> =
+            files.iter().filter(|f| f.is_owner).map(|f| f.id).collect();
         if target_file_ids.is_empty() {
             return Ok(());
         }
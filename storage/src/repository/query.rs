@@ -34,17 +34,31 @@ where
         Ok(file)
     }
 
-    /// Sum all of the used space for the user so we can check if the user is over the quota limit
+    /// Sum all of the used space for the user so we can check if the user is over the quota limit.
+    ///
+    /// Files in the trash still count: their chunks stay on disk until the
+    /// trash is purged, so emptying the trash is how a user frees up quota.
     pub(crate) async fn used_space(&self) -> AppResult<i64> {
-        self.used_bytes_where(true).await
+        self.used_bytes_where(true, false).await
     }
 
-    async fn used_bytes_where(&self, is_owner: bool) -> AppResult<i64> {
+    /// The part of [`Self::used_space`] taken by files in the user's trash.
+    pub(crate) async fn trashed_space(&self) -> AppResult<i64> {
+        self.used_bytes_where(true, true).await
+    }
+
+    async fn used_bytes_where(&self, is_owner: bool, trashed_only: bool) -> AppResult<i64> {
         let user_id = self.user_id;
 
-        let bytes = user_files::Entity::find()
+        let mut select = user_files::Entity::find()
             .select_only()
-            .filter(user_files::Column::UserId.eq(user_id))
+            .filter(user_files::Column::UserId.eq(user_id));
+
+        if trashed_only {
+            select = select.filter(files::Column::DeletedAt.is_not_null());
+        }
+
+        let bytes = select
             .join(
                 JoinType::InnerJoin,
                 user_files::Relation::Files
@@ -0,0 +1,216 @@
+//! Repository module for the owner's trash. Deleting a file or folder marks
+//! the whole subtree with `deleted_at` and detaches its root from the parent;
+//! the rows and chunks stay until the owner restores or purges them, or the
+//! retention window runs out.
+use chrono::Utc;
+use entity::{
+    files, ColumnTrait, ConnectionTrait, EntityTrait, Expr, Order, QueryFilter, QueryOrder, Uuid,
+};
+use error::{AppResult, Error};
+
+use super::Repository;
+use crate::data::app_file::AppFile;
+
+pub(crate) struct Trash<'repository, T: ConnectionTrait> {
+    repository: &'repository Repository<'repository, T>,
+    owner_id: Uuid,
+}
+
+impl<'repository, T> Trash<'repository, T>
+where
+    T: ConnectionTrait,
+{
+    pub(crate) fn new(repository: &'repository Repository<'repository, T>, owner_id: Uuid) -> Self {
+        Self {
+            repository,
+            owner_id,
+        }
+    }
+
+    /// Move files and folders the owner owns to the trash. Every row under
+    /// each root gets the same `deleted_at`, including rows co-owners
+    /// uploaded into a shared folder, so the subtree comes back whole on
+    /// restore. The root is detached from its parent and remembers it in
+    /// `deleted_parent_id`. Returns the ids of every row that went to the
+    /// trash.
+    pub(crate) async fn trash_many(&self, ids: Vec<Uuid>) -> AppResult<Vec<Uuid>> {
+        let now = Utc::now().timestamp();
+
+        let mut trashed = vec![];
+        for id in ids {
+            // Already went to the trash with a folder selected earlier.
+            if trashed.contains(&id) {
+                continue;
+            }
+
+            let root = self.repository.by_id(id, self.owner_id).await?;
+
+            if !root.is_owner {
+                return Err(Error::Forbidden("forbidden_not_owner".to_string()));
+            }
+
+            let subtree = self.repository.subtree_ids(root.id).await?;
+
+            files::Entity::update_many()
+                .col_expr(files::Column::DeletedAt, Expr::value(now))
+                .filter(files::Column::Id.is_in(subtree.clone()))
+                .filter(files::Column::DeletedAt.is_null())
+                .exec(self.repository.connection())
+                .await?;
+
+            files::Entity::update_many()
+                .col_expr(files::Column::DeletedParentId, Expr::value(root.file_id))
+                .col_expr(files::Column::FileId, Expr::value(None::<Uuid>))
+                .filter(files::Column::Id.eq(root.id))
+                .exec(self.repository.connection())
+                .await?;
+
+            trashed.extend(subtree);
+        }
+
+        Ok(trashed)
+    }
+
+    /// Roots of everything the owner has in the trash, most recently
+    /// deleted first. Descendants are not listed; they come back with
+    /// their root.
+    pub(crate) async fn list(&self) -> AppResult<Vec<AppFile>> {
+        self.repository
+            .trash_selector(self.owner_id)
+            .filter(files::Column::FileId.is_null())
+            .order_by(files::Column::DeletedAt, Order::Desc)
+            .into_model::<AppFile>()
+            .all(self.repository.connection())
+            .await
+            .map_err(Error::from)
+    }
+
+    /// Restore a trashed root and its whole subtree. The root goes back
+    /// under its original parent when that folder still exists outside the
+    /// trash, otherwise to the top level. A live file with the same name at
+    /// the destination is a conflict; the caller renames it first.
+    pub(crate) async fn restore(&self, id: Uuid) -> AppResult<AppFile> {
+        let root = self.root(id).await?;
+
+        let parent_id = match root.deleted_parent_id {
+            Some(parent_id) => self
+                .repository
+                .selector(self.owner_id, false)
+                .filter(files::Column::Id.eq(parent_id))
+                .filter(files::Column::Mime.eq("dir"))
+                .into_model::<AppFile>()
+                .one(self.repository.connection())
+                .await?
+                .map(|_| parent_id),
+            None => None,
+        };
+
+        let manage = self.repository.manage(self.owner_id);
+
+        if manage.by_name(&root.name_hash, parent_id).await.is_ok() {
+            return Err(Error::BadRequest("file_already_exists".to_string()));
+        }
+
+        let subtree = self.repository.subtree_ids(root.id).await?;
+
+        files::Entity::update_many()
+            .col_expr(files::Column::DeletedAt, Expr::value(None::<i64>))
+            .filter(files::Column::Id.is_in(subtree))
+            .exec(self.repository.connection())
+            .await?;
+
+        files::Entity::update_many()
+            .col_expr(files::Column::FileId, Expr::value(parent_id))
+            .col_expr(files::Column::DeletedParentId, Expr::value(None::<Uuid>))
+            .filter(files::Column::Id.eq(root.id))
+            .exec(self.repository.connection())
+            .await?;
+
+        self.repository.by_id(root.id, self.owner_id).await
+    }
+
+    /// Delete trashed roots and their subtrees for good. `None` empties the
+    /// whole trash. Returns every deleted file row so the caller can purge
+    /// the chunks once the transaction commits.
+    pub(crate) async fn purge(&self, ids: Option<Vec<Uuid>>) -> AppResult<Vec<files::Model>> {
+        let roots = match ids {
+            Some(ids) => {
+                let mut roots = Vec::with_capacity(ids.len());
+                for id in ids {
+                    roots.push(self.root(id).await?.id);
+                }
+                roots
+            }
+            None => self.list().await?.into_iter().map(|f| f.id).collect(),
+        };
+
+        purge_roots(self.repository, roots).await
+    }
+
+    /// Load a trashed root the owner owns.
+    async fn root(&self, id: Uuid) -> AppResult<files::Model> {
+        let file = self
+            .repository
+            .trash_selector(self.owner_id)
+            .filter(files::Column::Id.eq(id))
+            .filter(files::Column::FileId.is_null())
+            .into_model::<AppFile>()
+            .one(self.repository.connection())
+            .await?
+            .ok_or_else(|| Error::NotFound(format!("file_not_found:{id}")))?;
+
+        files::Entity::find_by_id(file.id)
+            .one(self.repository.connection())
+            .await?
+            .ok_or_else(|| Error::NotFound(format!("file_not_found:{id}")))
+    }
+}
+
+impl<T> Repository<'_, T>
+where
+    T: ConnectionTrait,
+{
+    /// Delete every trashed root, across all owners, that went to the
+    /// trash before `before`, together with its subtree. Returns the deleted
+    /// rows so the caller can purge their chunks.
+    pub(crate) async fn purge_expired_trash(&self, before: i64) -> AppResult<Vec<files::Model>> {
+        let roots = files::Entity::find()
+            .filter(files::Column::DeletedAt.lt(before))
+            .filter(files::Column::FileId.is_null())
+            .all(self.connection())
+            .await?
+            .into_iter()
+            .map(|f| f.id)
+            .collect();
+
+        purge_roots(self, roots).await
+    }
+}
+
+async fn purge_roots<T: ConnectionTrait>(
+    repository: &Repository<'_, T>,
+    roots: Vec<Uuid>,
+) -> AppResult<Vec<files::Model>> {
+    let mut ids = vec![];
+    for root in roots {
+        ids.extend(repository.subtree_ids(root).await?);
+    }
+    ids.sort();
+    ids.dedup();
+
+    if ids.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let files = files::Entity::find()
+        .filter(files::Column::Id.is_in(ids.clone()))
+        .all(repository.connection())
+        .await?;
+
+    files::Entity::delete_many()
+        .filter(files::Column::Id.is_in(ids))
+        .exec(repository.connection())
+        .await?;
+
+    Ok(files)
+}
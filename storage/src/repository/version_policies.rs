@@ -0,0 +1,269 @@
+//! Repository module for version-history retention: the policies users set
+//! for all of their files or for a single one, and pruning a file's history
+//! down to what its policy keeps.
+use chrono::Utc;
+use entity::{
+    file_versions, files, user_files, version_policies, ActiveModelTrait, ActiveValue, ColumnTrait,
+    ConnectionTrait, EntityTrait, Query, QueryFilter, Uuid,
+};
+use error::{AppResult, Error};
+
+use super::Repository;
+use crate::data::version_policy::{PolicySource, RetentionPolicy, VersionPolicyResponse};
+
+pub(crate) struct VersionPolicies<'repository, T: ConnectionTrait> {
+    repository: &'repository Repository<'repository, T>,
+    owner_id: Uuid,
+}
+
+impl<'repository, T> VersionPolicies<'repository, T>
+where
+    T: ConnectionTrait,
+{
+    pub(crate) fn new(repository: &'repository Repository<'repository, T>, owner_id: Uuid) -> Self {
+        Self {
+            repository,
+            owner_id,
+        }
+    }
+
+    /// Policy for all of the owner's files, `defaults` when they set none.
+    pub(crate) async fn user(
+        &self,
+        defaults: &RetentionPolicy,
+    ) -> AppResult<VersionPolicyResponse> {
+        Ok(match self.find(None).await? {
+            Some(model) => response(&model, PolicySource::User),
+            None => VersionPolicyResponse {
+                policy: defaults.clone(),
+                source: PolicySource::Default,
+            },
+        })
+    }
+
+    /// Set the policy for all of the owner's files.
+    pub(crate) async fn set_user(
+        &self,
+        policy: &RetentionPolicy,
+    ) -> AppResult<VersionPolicyResponse> {
+        let model = self.upsert(None, policy).await?;
+
+        Ok(response(&model, PolicySource::User))
+    }
+
+    /// Go back to the instance defaults for all of the owner's files.
+    pub(crate) async fn delete_user(&self) -> AppResult<()> {
+        self.delete(None).await
+    }
+
+    /// Policy in effect for one of the owner's files.
+    pub(crate) async fn file(
+        &self,
+        file_id: Uuid,
+        defaults: &RetentionPolicy,
+    ) -> AppResult<VersionPolicyResponse> {
+        self.owned_file(file_id).await?;
+
+        self.repository.version_policy(file_id, defaults).await
+    }
+
+    /// Set a policy for one of the owner's files, overriding their own.
+    pub(crate) async fn set_file(
+        &self,
+        file_id: Uuid,
+        policy: &RetentionPolicy,
+    ) -> AppResult<VersionPolicyResponse> {
+        self.owned_file(file_id).await?;
+        let model = self.upsert(Some(file_id), policy).await?;
+
+        Ok(response(&model, PolicySource::File))
+    }
+
+    /// Remove the policy of one of the owner's files, it follows the
+    /// owner's policy again.
+    pub(crate) async fn delete_file(&self, file_id: Uuid) -> AppResult<()> {
+        self.owned_file(file_id).await?;
+
+        self.delete(Some(file_id)).await
+    }
+
+    /// Only the owner sets the retention of a file, and directories have
+    /// no history to retain.
+    async fn owned_file(&self, file_id: Uuid) -> AppResult<()> {
+        let file = self.repository.by_id(file_id, self.owner_id).await?;
+
+        if !file.is_owner || file.is_dir() {
+            return Err(Error::NotFound("file_not_found".to_string()));
+        }
+
+        Ok(())
+    }
+
+    async fn find(&self, file_id: Option<Uuid>) -> AppResult<Option<version_policies::Model>> {
+        let query = version_policies::Entity::find()
+            .filter(version_policies::Column::UserId.eq(self.owner_id));
+
+        let query = match file_id {
+            Some(file_id) => query.filter(version_policies::Column::FileId.eq(file_id)),
+            None => query.filter(version_policies::Column::FileId.is_null()),
+        };
+
+        query
+            .one(self.repository.connection())
+            .await
+            .map_err(Error::from)
+    }
+
+    async fn upsert(
+        &self,
+        file_id: Option<Uuid>,
+        policy: &RetentionPolicy,
+    ) -> AppResult<version_policies::Model> {
+        let now = Utc::now().timestamp();
+        let existing = self.find(file_id).await?;
+        let exists = existing.is_some();
+
+        let mut model = match existing {
+            Some(existing) => version_policies::ActiveModel::from(existing),
+            None => version_policies::ActiveModel {
+                id: ActiveValue::Set(Uuid::new_v4()),
+                user_id: ActiveValue::Set(self.owner_id),
+                file_id: ActiveValue::Set(file_id),
+                created_at: ActiveValue::Set(now),
+                ..Default::default()
+            },
+        };
+
+        model.keep_all_days = ActiveValue::Set(policy.keep_all_days as i32);
+        model.keep_hourly = ActiveValue::Set(policy.keep_hourly as i32);
+        model.keep_daily = ActiveValue::Set(policy.keep_daily as i32);
+        model.keep_weekly = ActiveValue::Set(policy.keep_weekly as i32);
+        model.keep_monthly = ActiveValue::Set(policy.keep_monthly as i32);
+        model.max_versions = ActiveValue::Set(policy.max_versions.map(|max| max as i32));
+        model.updated_at = ActiveValue::Set(now);
+
+        let connection = self.repository.connection();
+        if exists {
+            return model.update(connection).await.map_err(Error::from);
+        }
+
+        version_policies::Entity::insert(model)
+            .exec_without_returning(connection)
+            .await?;
+
+        self.find(file_id)
+            .await?
+            .ok_or_else(|| Error::NotFound("version_policy_not_found".to_string()))
+    }
+
+    async fn delete(&self, file_id: Option<Uuid>) -> AppResult<()> {
+        let query = version_policies::Entity::delete_many()
+            .filter(version_policies::Column::UserId.eq(self.owner_id));
+
+        let query = match file_id {
+            Some(file_id) => query.filter(version_policies::Column::FileId.eq(file_id)),
+            None => query.filter(version_policies::Column::FileId.is_null()),
+        };
+
+        query.exec(self.repository.connection()).await?;
+
+        Ok(())
+    }
+}
+
+impl<T> Repository<'_, T>
+where
+    T: ConnectionTrait,
+{
+    /// Policy in effect for a file: its own, else the one its owner set for
+    /// all of their files, else `defaults`. No permission check.
+    pub(crate) async fn version_policy(
+        &self,
+        file_id: Uuid,
+        defaults: &RetentionPolicy,
+    ) -> AppResult<VersionPolicyResponse> {
+        let file_policy = version_policies::Entity::find()
+            .filter(version_policies::Column::FileId.eq(file_id))
+            .one(self.connection)
+            .await?;
+        if let Some(model) = file_policy {
+            return Ok(response(&model, PolicySource::File));
+        }
+
+        let owner_policy = version_policies::Entity::find()
+            .filter(version_policies::Column::FileId.is_null())
+            .filter(
+                version_policies::Column::UserId.in_subquery(
+                    Query::select()
+                        .column(user_files::Column::UserId)
+                        .from(user_files::Entity)
+                        .and_where(user_files::Column::FileId.eq(file_id))
+                        .and_where(user_files::Column::IsOwner.eq(true))
+                        .to_owned(),
+                ),
+            )
+            .one(self.connection)
+            .await?;
+
+        Ok(match owner_policy {
+            Some(model) => response(&model, PolicySource::User),
+            None => VersionPolicyResponse {
+                policy: defaults.clone(),
+                source: PolicySource::Default,
+            },
+        })
+    }
+
+    /// Drop the historical versions of a file that its policy no longer
+    /// keeps. Returns the version numbers that were pruned so the caller can
+    /// remove their on-disk directories.
+    pub(crate) async fn prune_versions(
+        &self,
+        file_id: Uuid,
+        defaults: &RetentionPolicy,
+    ) -> AppResult<Vec<i32>> {
+        let policy = self.version_policy(file_id, defaults).await?.policy;
+
+        let history = file_versions::Entity::find()
+            .filter(file_versions::Column::FileId.eq(file_id))
+            .all(self.connection)
+            .await?;
+
+        let pruned = policy.prunable(&history, Utc::now().timestamp());
+        if pruned.is_empty() {
+            return Ok(pruned);
+        }
+
+        file_versions::Entity::delete_many()
+            .filter(file_versions::Column::FileId.eq(file_id))
+            .filter(file_versions::Column::Version.is_in(pruned.clone()))
+            .exec(self.connection)
+            .await?;
+
+        Ok(pruned)
+    }
+
+    /// Files that have any version history.
+    pub(crate) async fn files_with_history(&self) -> AppResult<Vec<files::Model>> {
+        files::Entity::find()
+            .filter(
+                files::Column::Id.in_subquery(
+                    Query::select()
+                        .distinct()
+                        .column(file_versions::Column::FileId)
+                        .from(file_versions::Entity)
+                        .to_owned(),
+                ),
+            )
+            .all(self.connection)
+            .await
+            .map_err(From::from)
+    }
+}
+
+fn response(model: &version_policies::Model, source: PolicySource) -> VersionPolicyResponse {
+    VersionPolicyResponse {
+        policy: RetentionPolicy::from(model),
+        source,
+    }
+}
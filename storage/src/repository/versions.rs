@@ -72,11 +72,7 @@ where
 
     /// Look up a specific version's metadata. Used by the download route
     /// for `GET /versions/{version}?format=tar`.
-    pub(crate) async fn get(
-        &self,
-        file_id: Uuid,
-        version: i32,
-    ) -> AppResult<file_versions::Model> {
+    pub(crate) async fn get(&self, file_id: Uuid, version: i32) -> AppResult<file_versions::Model> {
         let _ = self.repository.by_id(file_id, self.owner_id).await?;
 
         file_versions::Entity::find()
@@ -111,16 +107,12 @@ where
         // existing 409-with-force flow on the in-flight edit if they
         // really mean to abandon it.
         if file.has_pending_upload() {
-            return Err(Error::Conflict(
-                "another_edit_is_in_progress".to_string(),
-            ));
+            return Err(Error::Conflict("another_edit_is_in_progress".to_string()));
         }
 
         // Self-restore is a no-op — bail before allocating a slot.
         if file.active_version == target_version {
-            return Err(Error::BadRequest(
-                "version_already_active".to_string(),
-            ));
+            return Err(Error::BadRequest("version_already_active".to_string()));
         }
 
         let target = self.get(file_id, target_version).await?;
@@ -148,6 +140,7 @@ where
             chunks: ActiveValue::Set(file.chunks.unwrap_or(0)),
             sha256: ActiveValue::Set(file.sha256.clone()),
             created_at: ActiveValue::Set(now),
+            pinned_at: ActiveValue::Set(None),
         };
         file_versions::Entity::insert(snapshot)
             .exec_without_returning(self.repository.connection())
@@ -295,37 +288,31 @@ where
         Ok(versions)
     }
 
-    /// Drop the oldest historical versions when the count exceeds `cap`.
-    /// Returns the version numbers that were pruned so the route can
-    /// remove their on-disk directories.
-    ///
-    /// Best-effort by design: we prune AFTER a successful finalize, and a
-    /// failure here doesn't roll back the commit. The next successful
-    /// commit will catch up.
-    pub(crate) async fn prune_over_cap(
+    /// Pin a historical version so retention pruning keeps it forever, or
+    /// unpin it. Pinning an already pinned version keeps its `pinned_at`.
+    pub(crate) async fn pin(
         &self,
         file_id: Uuid,
-        cap: usize,
-    ) -> AppResult<Vec<i32>> {
-        let mut rows = file_versions::Entity::find()
-            .filter(file_versions::Column::FileId.eq(file_id))
-            .order_by(file_versions::Column::Version, Order::Asc)
-            .all(self.repository.connection())
-            .await?;
+        version: i32,
+        pinned: bool,
+    ) -> AppResult<file_versions::Model> {
+        let file = self.repository.by_id(file_id, self.owner_id).await?;
 
-        if rows.len() <= cap {
-            return Ok(vec![]);
+        if !file.is_owner || file.user_id != self.owner_id {
+            return Err(Error::NotFound("file_not_found".to_string()));
         }
 
-        let to_drop = rows.drain(..rows.len() - cap).collect::<Vec<_>>();
-        let drop_ids: Vec<Uuid> = to_drop.iter().map(|v| v.id).collect();
-        let drop_versions: Vec<i32> = to_drop.iter().map(|v| v.version).collect();
+        let existing = self.get(file_id, version).await?;
+        if existing.pinned_at.is_some() == pinned {
+            return Ok(existing);
+        }
 
-        file_versions::Entity::delete_many()
-            .filter(file_versions::Column::Id.is_in(drop_ids))
-            .exec(self.repository.connection())
-            .await?;
+        let mut model = file_versions::ActiveModel::from(existing);
+        model.pinned_at = ActiveValue::Set(pinned.then(|| Utc::now().timestamp()));
 
-        Ok(drop_versions)
+        model
+            .update(self.repository.connection())
+            .await
+            .map_err(Error::from)
     }
 }
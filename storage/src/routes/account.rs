@@ -0,0 +1,43 @@
+use actix_web::http::header::ContentEncoding;
+use actix_web::{route, web, HttpResponse};
+use auth::data::claims::Claims;
+use context::Context;
+use error::AppResult;
+
+/// Export everything the caller owns as a tar archive: file, version and
+/// link rows, the wrapped file keys and search tokens, the encrypted
+/// private key, and the ciphertext chunks of every version.
+///
+/// Response: tar stream, see [crate::data::account]
+///  - Content-Type: application/x-tar
+#[route("/api/account/export", method = "GET")]
+pub(crate) async fn export(claims: Claims, context: web::Data<Context>) -> AppResult<HttpResponse> {
+    let streamer = crate::account::export(context.into_inner(), claims.sub).await?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header(ContentEncoding::Identity)
+        .insert_header(("Content-Type", "application/x-tar"))
+        .insert_header((
+            "Content-Disposition",
+            "attachment; filename=\"hoodik-account.tar\"",
+        ))
+        .streaming(streamer.stream()))
+}
+
+/// Import an archive made by [export] into the caller's account, under new
+/// ids. The account must hold the key pair the archive was exported with.
+///
+/// Request: tar stream, see [crate::data::account]
+///
+/// Response: [crate::data::account::Imported]
+#[route("/api/account/import", method = "POST")]
+pub(crate) async fn import(
+    claims: Claims,
+    context: web::Data<Context>,
+    body: web::Payload,
+) -> AppResult<HttpResponse> {
+    let quota = claims.get_quota(&context).await;
+    let imported = crate::account::import(&context, claims.sub, quota, body).await?;
+
+    Ok(HttpResponse::Ok().json(imported))
+}
@@ -0,0 +1,208 @@
+use std::time::Duration;
+
+use actix_web::{route, web, HttpResponse};
+use auth::data::claims::Claims;
+use context::Context;
+use entity::{copy_jobs, file_chunk_refs, TransactionTrait, Uuid};
+use error::{AppResult, Error};
+use fs::prelude::*;
+use futures::future::{self, Either};
+
+use super::create::instance_quota_lock;
+use crate::{
+    data::copy_files::CopyFiles,
+    housekeeping::discard_copy,
+    repository::{copy::CopyPlan, Repository},
+};
+
+/// How often a running copy keeps the files still waiting for their chunks
+/// from looking abandoned.
+const TOUCH_SECONDS: u64 = 300;
+
+/// Copy a file or a whole folder tree the caller owns, without the content
+/// leaving the server. The rows are planned and written right away, the
+/// files left unfinished, so the copy holds its share of the quota from the
+/// start. The chunks are then copied in the background — CopyObject on S3 —
+/// and every file is finished once its chunks are in place. If any step
+/// fails the copied rows and chunks are removed again, while whatever was
+/// put into the copied tree in the meantime stays.
+///
+/// Request: [crate::data::copy_files::CopyFiles]
+///
+/// Response: [entity::copy_jobs::Model] of the started copy, poll
+/// `/api/storage/copy/{id}` to follow it
+#[route("/api/storage/copy", method = "POST")]
+pub(crate) async fn copy(
+    claims: Claims,
+    context: web::Data<Context>,
+    data: web::Json<CopyFiles>,
+) -> AppResult<HttpResponse> {
+    let context = context.into_inner();
+    let (id, file_id, nodes) = data.into_inner().into_value()?;
+
+    claims.require_folder(&context, Some(id)).await?;
+    claims.require_folder(&context, file_id).await?;
+
+    let repository = Repository::new(&context.db);
+    let plan = repository
+        .copies(claims.sub)
+        .plan(id, file_id, nodes)
+        .await?;
+
+    if let Some(quota) = claims.get_quota(&context).await {
+        let used_space = repository.query(claims.sub).used_space().await? + plan.size();
+
+        if used_space > quota as i64 {
+            return Err(Error::BadRequest("quota_exceeded".to_string()));
+        }
+    }
+
+    let job = {
+        // Held until the rows are committed, same as for a create; once
+        // they are, the copy counts against the quota.
+        let _instance_guard = match context.config.app.storage_instance_quota_bytes {
+            Some(instance_quota) => {
+                let guard = instance_quota_lock().lock().await;
+                let used_space = repository.instance_used_space().await? + plan.size();
+
+                if used_space > instance_quota as i64 {
+                    return Err(Error::BadRequest("quota_exceeded".to_string()));
+                }
+
+                Some(guard)
+            }
+            None => None,
+        };
+
+        start(&context, claims.sub, &plan).await?
+    };
+
+    let background = context.clone();
+    let job_id = job.id;
+    actix_web::rt::spawn(async move { run(&background, job_id, plan).await });
+
+    Ok(HttpResponse::Accepted().json(job))
+}
+
+/// How far one of the caller's copies got.
+///
+/// Response: [entity::copy_jobs::Model]
+#[route("/api/storage/copy/{job_id}", method = "GET")]
+pub(crate) async fn status(
+    claims: Claims,
+    context: web::Data<Context>,
+    job_id: web::Path<Uuid>,
+) -> AppResult<HttpResponse> {
+    let job = Repository::new(&context.db)
+        .copies(claims.sub)
+        .job(job_id.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(job))
+}
+
+/// Write the planned rows and the job in one transaction.
+async fn start(context: &Context, user_id: Uuid, plan: &CopyPlan) -> AppResult<copy_jobs::Model> {
+    let connection = context.db.begin().await?;
+    let job = Repository::new(&connection)
+        .copies(user_id)
+        .start(plan)
+        .await?;
+    connection.commit().await?;
+
+    Ok(job)
+}
+
+/// Copy the chunks of a started copy and record how it ended. A copy that
+/// fails is removed again, rows and chunks. A copy the reaper ended in the
+/// meantime stops, its rows are the reaper's to remove.
+async fn run(context: &Context, job_id: Uuid, plan: CopyPlan) {
+    let repository = Repository::new(&context.db);
+
+    let error = match copy_chunks(context, job_id, &plan).await {
+        Ok(true) => None,
+        Ok(false) => {
+            log::warn!("Copy job {job_id} was ended elsewhere, stopping");
+            return;
+        }
+        Err(e) => {
+            log::warn!("Failed to copy {}: {}", plan.root_id(), e);
+            Some(e.to_string())
+        }
+    };
+
+    match repository.end_copy(job_id, error.clone()).await {
+        Ok(true) => {}
+        Ok(false) => {
+            log::warn!("Copy job {job_id} was ended elsewhere, stopping");
+            return;
+        }
+        Err(e) => log::error!("Failed to record the end of copy job {job_id}: {e}"),
+    }
+
+    if error.is_some() {
+        if let Err(e) = discard_copy(context, job_id).await {
+            log::warn!("Failed to discard copy {}: {}", plan.root_id(), e);
+        }
+    }
+}
+
+/// Copy the chunks of every planned file while keeping the copy from
+/// looking abandoned, a single large file can take longer to copy than the
+/// reaper waits. Returns `false` once the copy was ended elsewhere.
+async fn copy_chunks(context: &Context, job_id: Uuid, plan: &CopyPlan) -> AppResult<bool> {
+    let ids = plan.files().map(|entry| entry.copy.id).collect::<Vec<_>>();
+
+    let copying = copy_files(context, job_id, plan);
+    let heartbeat = keep_alive(context, job_id, &ids);
+    futures::pin_mut!(copying, heartbeat);
+
+    match future::select(copying, heartbeat).await {
+        Either::Left((result, _)) | Either::Right((result, _)) => result,
+    }
+}
+
+/// Touch a running copy every [`TOUCH_SECONDS`] until it is no longer
+/// running.
+async fn keep_alive(context: &Context, job_id: Uuid, ids: &[Uuid]) -> AppResult<bool> {
+    let repository = Repository::new(&context.db);
+
+    loop {
+        actix_web::rt::time::sleep(Duration::from_secs(TOUCH_SECONDS)).await;
+
+        if !repository.touch_copies(job_id, ids).await? {
+            return Ok(false);
+        }
+    }
+}
+
+/// Copy the active version of every planned file. Editable files keep
+/// using the versioned layout and start over at v1, write-once files stay
+/// in the flat layout they are read from.
+async fn copy_files(context: &Context, job_id: Uuid, plan: &CopyPlan) -> AppResult<bool> {
+    let storage = Fs::new(&context.config);
+    let repository = Repository::new(&context.db);
+
+    for entry in plan.files() {
+        if entry.source.use_versioned_layout() {
+            let source =
+                file_chunk_refs::filename(&context.db, entry.source.id, &entry.source).await?;
+            storage
+                .copy_version(
+                    &source,
+                    entry.source.active_version,
+                    &entry.copy,
+                    entry.copy.active_version,
+                )
+                .await?;
+        } else {
+            storage.copy(&entry.source, &entry.copy).await?;
+        }
+
+        if !repository.copied_file(job_id, entry.copy.id).await? {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
@@ -15,7 +15,7 @@ use crate::{data::create_file::CreateFile, repository::Repository};
 /// has no row-level `SELECT … FOR UPDATE`, so this is the portable equivalent.
 /// Only taken when an instance quota is configured, so the default self-hosted
 /// path is unaffected.
-fn instance_quota_lock() -> &'static Mutex<()> {
+pub(crate) fn instance_quota_lock() -> &'static Mutex<()> {
     static LOCK: OnceLock<Mutex<()>> = OnceLock::new();
     LOCK.get_or_init(|| Mutex::new(()))
 }
@@ -32,6 +32,13 @@ pub(crate) async fn create(
     data: web::Json<CreateFile>,
 ) -> AppResult<HttpResponse> {
     let context = context.into_inner();
+    claims
+        .require_folder(
+            &context,
+            entity::option_string_to_uuid(data.file_id.clone()),
+        )
+        .await?;
+
     let connection = context.db.begin().await?;
     let (create_file, encrypted_metadata, hashed_tokens, file_size, file_id) =
         data.into_inner().into_active_model()?;
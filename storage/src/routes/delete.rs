@@ -8,12 +8,12 @@ use entity::{
 use error::{AppResult, Error};
 use fs::prelude::*;
 
-use crate::repository::Repository;
+use crate::repository::{cached::evict_file, Repository};
 
-/// Delete a file or directory by id. Owner → cascade delete (file +
-/// chunks + recipients). Reader / Editor / Co-owner → self-remove only
-/// (drop caller's `user_files` row, recursively for folders). None →
-/// 403.
+/// Delete a file or directory by id. Owner → move to the trash, or
+/// cascade delete (file + chunks + recipients) when the admin turned the
+/// trash off. Reader / Editor / Co-owner → self-remove only (drop
+/// caller's `user_files` row, recursively for folders). None → 403.
 #[route("/api/storage/{file_id}", method = "DELETE")]
 pub(crate) async fn delete(
     req: HttpRequest,
@@ -26,8 +26,20 @@ pub(crate) async fn delete(
     let fs = Fs::new(&context.config);
     let connection = context.db.begin().await?;
 
+    let trash_enabled = context.settings.inner().await.trash.enabled();
+
     let perm = permission(&connection, file_id, claims.sub).await?;
     match perm {
+        SharePermission::Owner if trash_enabled => {
+            let trashed = Repository::new(&connection)
+                .trash(claims.sub)
+                .trash_many(vec![file_id])
+                .await?;
+            connection.commit().await?;
+            for id in trashed {
+                evict_file(id).await;
+            }
+        }
         SharePermission::Owner => {
             let mut files = Repository::new(&connection)
                 .manage(claims.sub)
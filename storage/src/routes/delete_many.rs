@@ -8,10 +8,14 @@ use entity::{
 use error::{AppResult, Error};
 use fs::prelude::*;
 
-use crate::{data::delete_many::DeleteMany, repository::Repository};
+use crate::{
+    data::delete_many::DeleteMany,
+    repository::{cached::evict_file, Repository},
+};
 
-/// Bulk delete. Each id is dispatched by ownership — owner ids
-/// cascade, non-owner ids self-remove, ids with no row are 403. All
+/// Bulk delete. Each id is dispatched by ownership — owner ids go to
+/// the trash (or cascade when the trash is off), non-owner ids
+/// self-remove, ids with no row are 403. All
 /// happens in one DB transaction so a partial failure rolls back the
 /// caller's view of every requested target.
 #[route("/api/storage/delete-many", method = "POST")]
@@ -41,8 +45,17 @@ pub(crate) async fn delete_many(
         }
     }
 
+    let trash_enabled = context.settings.inner().await.trash.enabled();
+
+    let mut trashed = Vec::new();
     let mut files = if to_cascade.is_empty() {
         Vec::new()
+    } else if trash_enabled {
+        trashed = Repository::new(&connection)
+            .trash(claims.sub)
+            .trash_many(to_cascade)
+            .await?;
+        Vec::new()
     } else {
         Repository::new(&connection)
             .manage(claims.sub)
@@ -57,6 +70,10 @@ pub(crate) async fn delete_many(
     }
     connection.commit().await?;
 
+    for id in trashed {
+        evict_file(id).await;
+    }
+
     for file in files.iter_mut() {
         if file.is_file() {
             // purge_all wipes every version directory AND any leftover
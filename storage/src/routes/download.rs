@@ -4,7 +4,7 @@ use actix_web::http::header::ContentEncoding;
 use actix_web::{route, web, HttpRequest, HttpResponse};
 use auth::data::transfer_claims::StorageClaims;
 use context::Context;
-use entity::Uuid;
+use entity::{file_chunk_refs, Uuid};
 use error::{AppResult, Error};
 use fs::prelude::*;
 
@@ -42,12 +42,19 @@ pub(crate) async fn download(
     // are write-once — their chunks are in the legacy flat layout and the
     // versioned path would add nothing.
     let versioned = file.use_versioned_layout();
+    let name = if versioned {
+        file_chunk_refs::filename(&context.db, file_id, &file).await?
+    } else {
+        file.filename()?
+    };
 
     if format.as_deref() == Some("tar") {
         let (content_length, streamer) = if versioned {
             (
-                storage.tar_content_length_v(&file, file.active_version).await?,
-                storage.stream_tar_v(&file, file.active_version).await?,
+                storage
+                    .tar_content_length_v(&name, file.active_version)
+                    .await?,
+                storage.stream_tar_v(&name, file.active_version).await?,
             )
         } else {
             (
@@ -69,7 +76,7 @@ pub(crate) async fn download(
     }
 
     let streamer = if versioned {
-        storage.stream_v(&file, file.active_version, chunk).await?
+        storage.stream_v(&name, file.active_version, chunk).await?
     } else {
         storage.stream(&file, chunk).await?
     };
@@ -3,6 +3,8 @@
 //! Routes for manipulating files and folders, plus the chunked upload and
 //! download endpoints. Sharing routes live in the `links` crate.
 
+pub mod account;
+pub mod copy;
 pub mod create;
 pub mod delete;
 pub mod delete_many;
@@ -17,14 +19,29 @@ pub mod search;
 pub mod set_editable;
 pub mod stats;
 pub mod thumbnail;
+pub mod trash;
 pub mod update_hashes;
 pub mod upload;
 pub(crate) mod upload_tar;
+pub mod version_policy;
 pub mod versions;
 
 /// Register the storage routes
 /// on to the application server
 pub fn configure(cfg: &mut actix_web::web::ServiceConfig) {
+    // Registered ahead of the `/api/storage/{file_id}` routes so `trash`
+    // and `version-policy` are not taken for a file id.
+    cfg.service(trash::list);
+    cfg.service(trash::restore);
+    cfg.service(trash::purge);
+    cfg.service(trash::empty);
+    cfg.service(version_policy::user);
+    cfg.service(version_policy::set_user);
+    cfg.service(version_policy::delete_user);
+    cfg.service(account::export);
+    cfg.service(account::import);
+    cfg.service(copy::copy);
+    cfg.service(copy::status);
     cfg.service(create::create);
     cfg.service(delete_many::delete_many);
     cfg.service(delete::delete);
@@ -48,4 +65,9 @@ pub fn configure(cfg: &mut actix_web::web::ServiceConfig) {
     cfg.service(versions::fork);
     cfg.service(versions::delete);
     cfg.service(versions::purge_all_history);
+    cfg.service(versions::pin);
+    cfg.service(versions::unpin);
+    cfg.service(version_policy::file);
+    cfg.service(version_policy::set_file);
+    cfg.service(version_policy::delete_file);
 }
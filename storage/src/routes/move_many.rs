@@ -26,7 +26,10 @@ pub(crate) async fn move_many(
     // (re-wrap cascade), not this one.
     let perms = permissions_for(&connection, &ids, claims.sub).await?;
     for id in &ids {
-        let perm = perms.get(id).copied().unwrap_or(entity::permission::SharePermission::None);
+        let perm = perms
+            .get(id)
+            .copied()
+            .unwrap_or(entity::permission::SharePermission::None);
         if matches!(perm, entity::permission::SharePermission::None) {
             return Err(Error::NotFound("file_not_found".to_string()));
         }
@@ -34,9 +34,7 @@ pub(crate) async fn rename(
     match perm {
         SharePermission::Owner => {}
         SharePermission::CoOwner | SharePermission::Editor => {
-            let target = repository
-                .by_id(file_id, claims.sub)
-                .await?;
+            let target = repository.by_id(file_id, claims.sub).await?;
             if target.mime == "dir" || !target.editable {
                 return Err(Error::Forbidden("forbidden_read_only".to_string()));
             }
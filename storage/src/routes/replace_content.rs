@@ -11,16 +11,25 @@
 //! exists; the client can retry with `force = true` to abandon the prior
 //! pending and start fresh. On force-recovery, the orphaned pending
 //! directory is purged from disk after the DB commit.
+//!
+//! Chunks listed in `reuse_chunks` are recorded in `file_chunk_refs` as
+//! borrowed from the version holding them instead of being uploaded, and
+//! when that covers every chunk the edit is finalized before responding.
+//! The edit is staged in one transaction holding the file's chunk reference
+//! lock, so a version being purged is either refused as a source or has the
+//! borrowed chunks kept by the purge.
 
 use actix_web::{route, web, HttpRequest, HttpResponse};
 use auth::data::claims::Claims;
 use context::Context;
-use entity::Uuid;
+use entity::{file_chunk_refs, TransactionTrait, Uuid};
 use error::AppResult;
 use fs::prelude::*;
 
+use super::upload_tar::finalize_file;
 use crate::{
     data::replace_content::ReplaceContent,
+    housekeeping::purge_version,
     repository::{cached::evict_file, Repository},
 };
 
@@ -38,20 +47,27 @@ pub(crate) async fn replace_content(
     let file_id: Uuid = util::actix::path_var(&req, "file_id")?;
     crate::permission::require_write(&context.db, file_id, claims.sub).await?;
     let validated = data.into_inner().validate_into(file_id)?;
+    let reuses_chunks = !validated.reuse_chunks.is_empty();
 
     let fs = Fs::new(&context.config);
 
-    let (file, abandoned_pending) = Repository::new(&context.db)
+    // Prunes delete a version's rows before purging its chunks under the
+    // same lock, so the version is either gone by the time it is validated
+    // here or the purge waits for the references this commits.
+    let connection = context.db.begin().await?;
+    let (mut file, abandoned_pending) = Repository::new(&connection)
         .manage(claims.sub)
         .replace_content(file_id, validated)
         .await?;
+    connection.commit().await?;
 
     // Force-recovery side effect — drop the orphaned pending dir from
     // disk now that the DB has committed the new pending version. Best
     // effort: a failure here leaves disk garbage that next purge_all will
     // sweep up, but the user's edit flow is unaffected.
     if let Some(old_pending) = abandoned_pending {
-        if let Err(e) = fs.purge_version(&file, old_pending).await {
+        let repository = Repository::new(&context.db);
+        if let Err(e) = purge_version(&repository, &fs, &file, file_id, old_pending).await {
             log::warn!(
                 "Failed to purge abandoned pending v{} for file {}: {}. \
                  Disk garbage left behind; cleaned up on next file delete.",
@@ -66,5 +82,20 @@ pub(crate) async fn replace_content(
     // so the next read sees the new state.
     evict_file(file_id).await;
 
+    if !reuses_chunks {
+        return Ok(HttpResponse::Ok().json(file));
+    }
+
+    let name = file_chunk_refs::filename(&context.db, file_id, &file).await?;
+    let stored = fs
+        .get_uploaded_chunks_v(&name, file.target_version())
+        .await?;
+    file.chunks_stored = Some(stored.len() as i64);
+    file.uploaded_chunks = Some(stored);
+
+    if file.chunks_stored == file.target_chunks() {
+        file = finalize_file(&context, &fs, claims.sub, &file, file_id).await?;
+    }
+
     Ok(HttpResponse::Ok().json(file))
 }
@@ -14,10 +14,12 @@ pub(crate) async fn stats(claims: Claims, context: web::Data<Context>) -> AppRes
     let repository = Repository::new(&context.db);
     let stats = repository.query(claims.sub).stats().await?;
     let used_space = repository.query(claims.sub).used_space().await?;
+    let trashed_space = repository.query(claims.sub).trashed_space().await?;
 
     Ok(HttpResponse::Ok().json(Response {
         stats,
         used_space,
+        trashed_space,
         quota: claims.get_quota(&context).await,
     }))
 }
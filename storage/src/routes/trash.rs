@@ -0,0 +1,93 @@
+//! Trash endpoints. Deleting a file the caller owns moves it here while the
+//! admin keeps a retention window; these routes list, restore and purge
+//! what is in it. Everything is owner-only: recipients lose access to a
+//! trashed file until the owner restores it.
+
+use actix_web::{route, web, HttpRequest, HttpResponse};
+use auth::data::claims::Claims;
+use context::Context;
+use entity::{TransactionTrait, Uuid};
+use error::AppResult;
+use fs::prelude::*;
+
+use crate::{housekeeping::purge_chunks, repository::Repository};
+
+/// `GET /api/storage/trash` — roots of everything in the caller's trash,
+/// most recently deleted first.
+///
+/// Response: [Vec<crate::data::app_file::AppFile>]
+#[route("/api/storage/trash", method = "GET")]
+pub(crate) async fn list(claims: Claims, context: web::Data<Context>) -> AppResult<HttpResponse> {
+    let context = context.into_inner();
+
+    let files = Repository::new(&context.db)
+        .trash(claims.sub)
+        .list()
+        .await?;
+
+    Ok(HttpResponse::Ok().json(files))
+}
+
+/// `POST /api/storage/trash/{file_id}/restore` — bring a trashed file or
+/// folder back, with everything that was in it, under the folder it was
+/// deleted from. Falls back to the top level when that folder is gone.
+///
+/// Response: [crate::data::app_file::AppFile]
+#[route("/api/storage/trash/{file_id}/restore", method = "POST")]
+pub(crate) async fn restore(
+    req: HttpRequest,
+    claims: Claims,
+    context: web::Data<Context>,
+) -> AppResult<HttpResponse> {
+    let context = context.into_inner();
+    let file_id: Uuid = util::actix::path_var(&req, "file_id")?;
+
+    let connection = context.db.begin().await?;
+    let file = Repository::new(&connection)
+        .trash(claims.sub)
+        .restore(file_id)
+        .await?;
+    connection.commit().await?;
+
+    Ok(HttpResponse::Ok().json(file))
+}
+
+/// `DELETE /api/storage/trash/{file_id}` — delete a trashed file or folder
+/// for good, chunks included.
+#[route("/api/storage/trash/{file_id}", method = "DELETE")]
+pub(crate) async fn purge(
+    req: HttpRequest,
+    claims: Claims,
+    context: web::Data<Context>,
+) -> AppResult<HttpResponse> {
+    let context = context.into_inner();
+    let file_id: Uuid = util::actix::path_var(&req, "file_id")?;
+
+    let connection = context.db.begin().await?;
+    let files = Repository::new(&connection)
+        .trash(claims.sub)
+        .purge(Some(vec![file_id]))
+        .await?;
+    connection.commit().await?;
+
+    purge_chunks(&Fs::new(&context.config), &files).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// `DELETE /api/storage/trash` — empty the caller's trash.
+#[route("/api/storage/trash", method = "DELETE")]
+pub(crate) async fn empty(claims: Claims, context: web::Data<Context>) -> AppResult<HttpResponse> {
+    let context = context.into_inner();
+
+    let connection = context.db.begin().await?;
+    let files = Repository::new(&connection)
+        .trash(claims.sub)
+        .purge(None)
+        .await?;
+    connection.commit().await?;
+
+    purge_chunks(&Fs::new(&context.config), &files).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -3,13 +3,14 @@ use std::str::FromStr;
 use actix_web::{route, web, HttpRequest, HttpResponse};
 use auth::data::transfer_claims::StorageClaims;
 use context::Context;
-use entity::Uuid;
+use entity::{file_chunk_refs, Uuid};
 use error::{AppResult, Error};
 use fs::{prelude::*, MAX_CHUNK_PAYLOAD_BYTES};
 use futures::StreamExt;
 
 use crate::{
-    data::{app_file::AppFile, meta::Meta},
+    data::{app_file::AppFile, meta::Meta, version_policy::RetentionPolicy},
+    housekeeping::purge_version,
     permission::require_write,
     repository::{
         cached::{evict_file, get_file},
@@ -38,7 +39,11 @@ pub(crate) async fn upload(
     context: web::Data<Context>,
     body: web::Payload,
 ) -> AppResult<HttpResponse> {
-    if util::actix::query_var::<String>(&req, "format").ok().as_deref() == Some("tar") {
+    if util::actix::query_var::<String>(&req, "format")
+        .ok()
+        .as_deref()
+        == Some("tar")
+    {
         return super::upload_tar::upload_tar(req, claims, context, body).await;
     }
 
@@ -89,6 +94,13 @@ async fn upload_one_chunk(
 
     let versioned = file.use_versioned_layout();
 
+    // Chunks an incremental edit borrows count as stored already.
+    let name = if versioned {
+        file_chunk_refs::filename(&context.db, file_id, &file).await?
+    } else {
+        file.filename()?
+    };
+
     // Target version only matters on the versioned path. Non-editable
     // files never snapshot/swap; their chunks live directly in the
     // legacy flat layout and finalize only stamps `finished_upload_at`.
@@ -101,7 +113,9 @@ async fn upload_one_chunk(
     }
 
     let chunk_exists = if versioned {
-        storage.exists_v(&file, file.target_version(), chunk).await?
+        storage
+            .exists_v(&name, file.target_version(), chunk)
+            .await?
     } else {
         storage.exists(&file, chunk).await?
     };
@@ -117,10 +131,15 @@ async fn upload_one_chunk(
         storage.push(&file, chunk, &request_body).await?;
     }
 
+    Repository::new(&context.db)
+        .manage(claims.sub())
+        .touch_upload(file_id)
+        .await?;
+
     if file.is_file() {
         let stored = if versioned {
             storage
-                .get_uploaded_chunks_v(&file, file.target_version())
+                .get_uploaded_chunks_v(&name, file.target_version())
                 .await?
         } else {
             storage.get_uploaded_chunks(&file).await?
@@ -140,7 +159,7 @@ async fn upload_one_chunk(
         let txn = context.db.begin().await?;
         let (mut finished_file, pruned) = Repository::new(&txn)
             .manage(claims.sub())
-            .finish(&file, context.config.app.max_file_versions)
+            .finish(&file, &RetentionPolicy::from(&context.config.versions))
             .await?;
         txn.commit().await?;
 
@@ -148,8 +167,11 @@ async fn upload_one_chunk(
         // pre-swap version.
         evict_file(file_id).await;
 
+        let repository = Repository::new(&context.db);
         for version in pruned {
-            if let Err(e) = storage.purge_version(&finished_file, version).await {
+            if let Err(e) =
+                purge_version(&repository, &storage, &finished_file, file_id, version).await
+            {
                 log::warn!(
                     "Failed to purge pruned v{} for file {}: {}. \
                      On-disk garbage; caught by next purge_all on file delete.",
@@ -177,10 +199,7 @@ async fn collect_chunk_body(mut body: web::Payload) -> AppResult<web::Bytes> {
     while let Some(chunk) = body.next().await {
         let chunk = chunk.map_err(|e| Error::BadRequest(format!("payload_read_failed: {e}")))?;
         if (buf.len() + chunk.len()) as u64 > MAX_CHUNK_PAYLOAD_BYTES {
-            return Err(Error::as_validation(
-                "chunk",
-                "chunk_size_exceeds_max",
-            ));
+            return Err(Error::as_validation("chunk", "chunk_size_exceeds_max"));
         }
         buf.extend_from_slice(&chunk);
     }
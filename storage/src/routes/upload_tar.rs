@@ -19,7 +19,7 @@
 use actix_web::{web, HttpRequest, HttpResponse};
 use auth::data::transfer_claims::StorageClaims;
 use context::Context;
-use entity::Uuid;
+use entity::{file_chunk_refs, Uuid};
 use error::{AppResult, Error};
 use fs::{
     prelude::*,
@@ -31,7 +31,8 @@ use std::collections::HashSet;
 use std::str::FromStr;
 
 use crate::{
-    data::app_file::AppFile,
+    data::{app_file::AppFile, version_policy::RetentionPolicy},
+    housekeeping::purge_version,
     permission::require_write,
     repository::{
         cached::{evict_file, get_file},
@@ -91,13 +92,20 @@ pub(crate) async fn upload_tar(
     )
     .await?;
 
+    // Mark the upload as still moving so the abandoned-upload reaper skips it.
+    Repository::new(&context.db)
+        .manage(claims.sub())
+        .touch_upload(file_id)
+        .await?;
+
     // Refresh stored-chunk bookkeeping from disk: the tar may have filled the
     // file completely, or only added a subset, and the legacy layout can't
     // track it in memory without a listing.
     if file.is_file() {
         let stored = if versioned {
+            let name = file_chunk_refs::filename(&context.db, file_id, &file).await?;
             storage
-                .get_uploaded_chunks_v(&file, file.target_version())
+                .get_uploaded_chunks_v(&name, file.target_version())
                 .await?
         } else {
             storage.get_uploaded_chunks(&file).await?
@@ -107,7 +115,7 @@ pub(crate) async fn upload_tar(
     }
 
     if file.chunks_stored == Some(target_chunks) {
-        file = finalize_file(&context, &storage, &claims, &file, file_id).await?;
+        file = finalize_file(&context, &storage, claims.sub(), &file, file_id).await?;
     }
 
     Ok(HttpResponse::Ok().json(file))
@@ -202,9 +210,9 @@ async fn stream_tar_into_storage(
 /// prefix — the parsed integer is the only part that ever leaves this
 /// function.
 fn parse_chunk_name(name: &str) -> AppResult<i64> {
-    let stem = name.strip_suffix(".enc").ok_or_else(|| {
-        Error::BadRequest(format!("tar_entry_not_enc_suffix: {name}"))
-    })?;
+    let stem = name
+        .strip_suffix(".enc")
+        .ok_or_else(|| Error::BadRequest(format!("tar_entry_not_enc_suffix: {name}")))?;
     stem.parse::<i64>()
         .map_err(|_| Error::BadRequest(format!("tar_entry_not_integer_index: {name}")))
 }
@@ -295,25 +303,29 @@ async fn remaining_quota_bytes(context: &Context, claims: &StorageClaims) -> Opt
 /// Same transactional commit the per-chunk path runs: snapshot + swap +
 /// prune in one DB transaction, then best-effort-purge the pruned version
 /// directories. See [`super::upload::upload`] for the long-form rationale.
-async fn finalize_file(
+/// Also finalizes an incremental edit whose chunks were all reused, see
+/// [`super::replace_content::replace_content`].
+pub(super) async fn finalize_file(
     context: &Context,
     storage: &Fs<'_>,
-    claims: &StorageClaims,
+    user_id: Uuid,
     file: &AppFile,
     file_id: Uuid,
 ) -> AppResult<AppFile> {
     use entity::TransactionTrait;
     let txn = context.db.begin().await?;
     let (mut finished_file, pruned) = Repository::new(&txn)
-        .manage(claims.sub())
-        .finish(file, context.config.app.max_file_versions)
+        .manage(user_id)
+        .finish(file, &RetentionPolicy::from(&context.config.versions))
         .await?;
     txn.commit().await?;
 
     evict_file(file_id).await;
 
+    let repository = Repository::new(&context.db);
     for version in pruned {
-        if let Err(e) = storage.purge_version(&finished_file, version).await {
+        if let Err(e) = purge_version(&repository, storage, &finished_file, file_id, version).await
+        {
             log::warn!(
                 "Failed to purge pruned v{} for file {}: {}. \
                  On-disk garbage; caught by next purge_all on file delete.",
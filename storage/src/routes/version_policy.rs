@@ -0,0 +1,134 @@
+//! Retention policies for the version history of editable files, see
+//! [crate::data::version_policy] for how a policy thins the history.
+//!
+//! The policy without a file applies to every file the caller owns; a
+//! file's own policy, which only its owner can set, wins over it.
+
+use actix_web::{route, web, HttpRequest, HttpResponse};
+use auth::data::claims::Claims;
+use context::Context;
+use entity::Uuid;
+use error::AppResult;
+
+use crate::{
+    data::version_policy::{RetentionPolicy, VersionPolicy},
+    permission::require_owner,
+    repository::Repository,
+};
+
+/// Policy for all of the caller's files, the instance defaults when they
+/// set none.
+///
+/// Response: [crate::data::version_policy::VersionPolicyResponse]
+#[route("/api/storage/version-policy", method = "GET")]
+pub(crate) async fn user(claims: Claims, context: web::Data<Context>) -> AppResult<HttpResponse> {
+    let defaults = RetentionPolicy::from(&context.config.versions);
+
+    let policy = Repository::new(&context.db)
+        .version_policies(claims.sub)
+        .user(&defaults)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(policy))
+}
+
+/// Set the policy for all of the caller's files.
+///
+/// Request: [crate::data::version_policy::VersionPolicy]
+///
+/// Response: [crate::data::version_policy::VersionPolicyResponse]
+#[route("/api/storage/version-policy", method = "PUT")]
+pub(crate) async fn set_user(
+    claims: Claims,
+    context: web::Data<Context>,
+    data: web::Json<VersionPolicy>,
+) -> AppResult<HttpResponse> {
+    let policy = data.into_inner().validate_into()?;
+
+    let policy = Repository::new(&context.db)
+        .version_policies(claims.sub)
+        .set_user(&policy)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(policy))
+}
+
+/// Go back to the instance defaults for all of the caller's files.
+#[route("/api/storage/version-policy", method = "DELETE")]
+pub(crate) async fn delete_user(
+    claims: Claims,
+    context: web::Data<Context>,
+) -> AppResult<HttpResponse> {
+    Repository::new(&context.db)
+        .version_policies(claims.sub)
+        .delete_user()
+        .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Policy in effect for a file, and where it comes from.
+///
+/// Response: [crate::data::version_policy::VersionPolicyResponse]
+#[route("/api/storage/{file_id}/version-policy", method = "GET")]
+pub(crate) async fn file(
+    req: HttpRequest,
+    claims: Claims,
+    context: web::Data<Context>,
+) -> AppResult<HttpResponse> {
+    let file_id: Uuid = util::actix::path_var(&req, "file_id")?;
+    let defaults = RetentionPolicy::from(&context.config.versions);
+
+    require_owner(&context.db, file_id, claims.sub).await?;
+
+    let policy = Repository::new(&context.db)
+        .version_policies(claims.sub)
+        .file(file_id, &defaults)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(policy))
+}
+
+/// Set a policy for a single file.
+///
+/// Request: [crate::data::version_policy::VersionPolicy]
+///
+/// Response: [crate::data::version_policy::VersionPolicyResponse]
+#[route("/api/storage/{file_id}/version-policy", method = "PUT")]
+pub(crate) async fn set_file(
+    req: HttpRequest,
+    claims: Claims,
+    context: web::Data<Context>,
+    data: web::Json<VersionPolicy>,
+) -> AppResult<HttpResponse> {
+    let file_id: Uuid = util::actix::path_var(&req, "file_id")?;
+
+    require_owner(&context.db, file_id, claims.sub).await?;
+
+    let policy = data.into_inner().validate_into()?;
+    let policy = Repository::new(&context.db)
+        .version_policies(claims.sub)
+        .set_file(file_id, &policy)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(policy))
+}
+
+/// Remove a file's own policy, it follows the owner's policy again.
+#[route("/api/storage/{file_id}/version-policy", method = "DELETE")]
+pub(crate) async fn delete_file(
+    req: HttpRequest,
+    claims: Claims,
+    context: web::Data<Context>,
+) -> AppResult<HttpResponse> {
+    let file_id: Uuid = util::actix::path_var(&req, "file_id")?;
+
+    require_owner(&context.db, file_id, claims.sub).await?;
+
+    Repository::new(&context.db)
+        .version_policies(claims.sub)
+        .delete_file(file_id)
+        .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
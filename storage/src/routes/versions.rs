@@ -9,13 +9,14 @@ use actix_web::{route, web, HttpRequest, HttpResponse};
 use auth::data::claims::Claims;
 use chrono::Utc;
 use context::Context;
-use entity::{ActiveValue, TransactionTrait, Uuid};
+use entity::{file_chunk_refs, ActiveValue, TransactionTrait, Uuid};
 use error::AppResult;
 use fs::prelude::*;
 use serde::{Deserialize, Serialize};
 
 use crate::{
     data::create_file::CreateFile,
+    housekeeping::purge_version,
     permission::{require_owner, require_read, require_write},
     repository::{cached::evict_file, Repository},
 };
@@ -71,10 +72,11 @@ pub(crate) async fn download(
         .await?;
 
     let storage = Fs::new(&context.config);
+    let name = file_chunk_refs::filename(&context.db, file_id, &file).await?;
 
     if format.as_deref() == Some("tar") {
-        let content_length = storage.tar_content_length_v(&file, version).await?;
-        let streamer = storage.stream_tar_v(&file, version).await?;
+        let content_length = storage.tar_content_length_v(&name, version).await?;
+        let streamer = storage.stream_tar_v(&name, version).await?;
         let filename = format!("{}.v{}.tar", file_id, version);
 
         return Ok(HttpResponse::Ok()
@@ -88,7 +90,7 @@ pub(crate) async fn download(
             .streaming(streamer.stream()));
     }
 
-    let streamer = storage.stream_v(&file, version, chunk).await?;
+    let streamer = storage.stream_v(&name, version, chunk).await?;
     Ok(HttpResponse::Ok()
         .insert_header(ContentEncoding::Identity)
         .insert_header(("Content-Type", "application/octet-stream"))
@@ -100,10 +102,7 @@ pub(crate) async fn download(
 /// chunks into it, and flips `active_version`. The previously-active
 /// version is snapshotted into history along the way, so the user can
 /// undo by restoring that one.
-#[route(
-    "/api/storage/{file_id}/versions/{version}/restore",
-    method = "POST"
-)]
+#[route("/api/storage/{file_id}/versions/{version}/restore", method = "POST")]
 pub(crate) async fn restore(
     req: HttpRequest,
     claims: Claims,
@@ -136,8 +135,9 @@ pub(crate) async fn restore(
     // copy fails the active pointer already moved — same recovery
     // story as a half-finished edit: the next purge_all on file delete
     // catches the dst dir, and a re-restore retries cleanly.
+    let name = file_chunk_refs::filename(&context.db, file_id, &file).await?;
     storage
-        .copy_version(&file, outcome.source_version, &file, outcome.new_version)
+        .copy_version(&name, outcome.source_version, &file, outcome.new_version)
         .await?;
 
     evict_file(file_id).await;
@@ -235,6 +235,7 @@ pub(crate) async fn fork(
 
     // Source chunks live under the original file's path; destination
     // is the brand-new file's v1.
+    let source = file_chunk_refs::filename(&context.db, source_file_id, &source).await?;
     storage
         .copy_version(
             &source,
@@ -250,10 +251,7 @@ pub(crate) async fn fork(
 /// `DELETE /api/storage/{file_id}/versions/{version}` — drop a single
 /// historical version. Deleting the active version is rejected; use
 /// regular file deletion for that.
-#[route(
-    "/api/storage/{file_id}/versions/{version}",
-    method = "DELETE"
-)]
+#[route("/api/storage/{file_id}/versions/{version}", method = "DELETE")]
 pub(crate) async fn delete(
     req: HttpRequest,
     claims: Claims,
@@ -276,7 +274,9 @@ pub(crate) async fn delete(
         .manage(claims.sub)
         .file(file_id)
         .await?;
-    if let Err(e) = Fs::new(&context.config).purge_version(&file, version).await {
+    let repository = Repository::new(&context.db);
+    let storage = Fs::new(&context.config);
+    if let Err(e) = purge_version(&repository, &storage, &file, file_id, version).await {
         log::warn!(
             "Failed to purge v{} dir for file {}: {}",
             version,
@@ -310,9 +310,10 @@ pub(crate) async fn purge_all_history(
         .manage(claims.sub)
         .file(file_id)
         .await?;
+    let repository = Repository::new(&context.db);
     let storage = Fs::new(&context.config);
     for version in pruned {
-        if let Err(e) = storage.purge_version(&file, version).await {
+        if let Err(e) = purge_version(&repository, &storage, &file, file_id, version).await {
             log::warn!(
                 "Failed to purge v{} dir for file {}: {}",
                 version,
@@ -325,3 +326,44 @@ pub(crate) async fn purge_all_history(
     Ok(HttpResponse::NoContent().finish())
 }
 
+/// `POST /api/storage/{file_id}/versions/{version}/pin` — keep a historical
+/// version forever. Retention pruning skips pinned versions.
+#[route("/api/storage/{file_id}/versions/{version}/pin", method = "POST")]
+pub(crate) async fn pin(
+    req: HttpRequest,
+    claims: Claims,
+    context: web::Data<Context>,
+) -> AppResult<HttpResponse> {
+    set_pinned(req, claims, context, true).await
+}
+
+/// `DELETE /api/storage/{file_id}/versions/{version}/pin` — let retention
+/// prune the version again.
+#[route("/api/storage/{file_id}/versions/{version}/pin", method = "DELETE")]
+pub(crate) async fn unpin(
+    req: HttpRequest,
+    claims: Claims,
+    context: web::Data<Context>,
+) -> AppResult<HttpResponse> {
+    set_pinned(req, claims, context, false).await
+}
+
+async fn set_pinned(
+    req: HttpRequest,
+    claims: Claims,
+    context: web::Data<Context>,
+    pinned: bool,
+) -> AppResult<HttpResponse> {
+    let context = context.into_inner();
+    let file_id: Uuid = util::actix::path_var(&req, "file_id")?;
+    let version: i32 = util::actix::path_var(&req, "version")?;
+
+    require_owner(&context.db, file_id, claims.sub).await?;
+
+    let version = Repository::new(&context.db)
+        .versions(claims.sub)
+        .pin(file_id, version, pinned)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(version))
+}
@@ -0,0 +1,273 @@
+use crate::{housekeeping::reap_abandoned_uploads, mock::create_file, repository::Repository};
+use chrono::Utc;
+use context::Context;
+use entity::{
+    copy_job_files, copy_jobs, files, ActiveValue, ColumnTrait, EntityTrait, Expr, QueryFilter,
+};
+use fs::prelude::*;
+
+async fn idle_since(context: &Context, id: entity::Uuid, timestamp: i64) {
+    files::Entity::update_many()
+        .col_expr(files::Column::CreatedAt, Expr::value(timestamp))
+        .col_expr(files::Column::LastChunkAt, Expr::value(timestamp))
+        .filter(files::Column::Id.eq(id))
+        .exec(&context.db)
+        .await
+        .unwrap();
+}
+
+#[actix_web::test]
+async fn unfinished_upload_is_deleted_once_idle() {
+    let context = Context::mock_sqlite().await;
+    let user = entity::mock::create_user(&context.db, "first@test.com", None).await;
+    let storage = Fs::new(&context.config);
+    let now = Utc::now().timestamp();
+
+    let abandoned = create_file(
+        &context,
+        &user,
+        "abandoned.json",
+        None,
+        Some("application/json"),
+    )
+    .await
+    .unwrap();
+    let moving = create_file(
+        &context,
+        &user,
+        "moving.json",
+        None,
+        Some("application/json"),
+    )
+    .await
+    .unwrap();
+
+    idle_since(&context, abandoned.id, now - 3600).await;
+    let abandoned = files::Entity::find_by_id(abandoned.id)
+        .one(&context.db)
+        .await
+        .unwrap()
+        .unwrap();
+    storage.push(&abandoned, 0, b"partial").await.unwrap();
+
+    let reaped = reap_abandoned_uploads(&context, now - 60).await.unwrap();
+
+    assert_eq!(reaped.uploads, 1);
+    assert_eq!(reaped.bytes, 100);
+    assert!(files::Entity::find_by_id(abandoned.id)
+        .one(&context.db)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(storage
+        .get_uploaded_chunks(&abandoned)
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(files::Entity::find_by_id(moving.id)
+        .one(&context.db)
+        .await
+        .unwrap()
+        .is_some());
+}
+
+#[actix_web::test]
+async fn unfinalized_edit_loses_its_pending_version_only() {
+    let context = Context::mock_sqlite().await;
+    let user = entity::mock::create_user(&context.db, "first@test.com", None).await;
+    let storage = Fs::new(&context.config);
+    let now = Utc::now().timestamp();
+
+    let file = create_file(&context, &user, "note.md", None, Some("text/markdown"))
+        .await
+        .unwrap();
+
+    files::Entity::update_many()
+        .col_expr(files::Column::Editable, Expr::value(true))
+        .col_expr(files::Column::FinishedUploadAt, Expr::value(now - 7200))
+        .col_expr(files::Column::PendingVersion, Expr::value(2))
+        .col_expr(files::Column::PendingChunks, Expr::value(2))
+        .col_expr(files::Column::PendingSize, Expr::value(300))
+        .col_expr(files::Column::ChunksStored, Expr::value(1))
+        .filter(files::Column::Id.eq(file.id))
+        .exec(&context.db)
+        .await
+        .unwrap();
+    idle_since(&context, file.id, now - 3600).await;
+
+    let file = files::Entity::find_by_id(file.id)
+        .one(&context.db)
+        .await
+        .unwrap()
+        .unwrap();
+    storage.push_v(&file, 2, 0, b"partial").await.unwrap();
+
+    let reaped = reap_abandoned_uploads(&context, now - 60).await.unwrap();
+
+    assert_eq!(reaped.uploads, 1);
+    assert_eq!(reaped.bytes, 150);
+    assert!(!storage.exists_v(&file, 2, 0).await.unwrap());
+
+    let file = files::Entity::find_by_id(file.id)
+        .one(&context.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(file.pending_version, None);
+    assert_eq!(file.pending_chunks, None);
+    assert_eq!(file.pending_size, None);
+    assert_eq!(file.chunks_stored, file.chunks);
+    assert_eq!(file.active_version, 1);
+}
+
+#[actix_web::test]
+async fn interrupted_copy_removes_only_what_it_wrote() {
+    let context = Context::mock_sqlite().await;
+    let user = entity::mock::create_user(&context.db, "first@test.com", None).await;
+    let storage = Fs::new(&context.config);
+    let now = Utc::now().timestamp();
+
+    // A folder copy a server stopped in the middle of: the folder, a
+    // subfolder and one half-copied file, the job still marked running.
+    let folder = create_file(&context, &user, "copy", None, Some("dir"))
+        .await
+        .unwrap();
+    let sub = create_file(&context, &user, "sub", Some(folder.id), Some("dir"))
+        .await
+        .unwrap();
+    let file = create_file(
+        &context,
+        &user,
+        "copy.json",
+        Some(sub.id),
+        Some("application/json"),
+    )
+    .await
+    .unwrap();
+    let file = files::Entity::find_by_id(file.id)
+        .one(&context.db)
+        .await
+        .unwrap()
+        .unwrap();
+    storage.push(&file, 0, b"partial").await.unwrap();
+
+    // Uploaded into the copied folder while the copy ran.
+    let upload = create_file(
+        &context,
+        &user,
+        "upload.json",
+        Some(folder.id),
+        Some("application/json"),
+    )
+    .await
+    .unwrap();
+
+    let job = entity::Uuid::new_v4();
+    copy_jobs::Entity::insert(copy_jobs::ActiveModel {
+        id: ActiveValue::Set(job),
+        user_id: ActiveValue::Set(user.id),
+        file_id: ActiveValue::Set(folder.id),
+        status: ActiveValue::Set("running".to_string()),
+        files_total: ActiveValue::Set(2),
+        files_copied: ActiveValue::Set(0),
+        error: ActiveValue::Set(None),
+        created_at: ActiveValue::Set(now - 3600),
+        updated_at: ActiveValue::Set(now - 3600),
+        finished_at: ActiveValue::Set(None),
+    })
+    .exec_without_returning(&context.db)
+    .await
+    .unwrap();
+    copy_job_files::Entity::insert_many([folder.id, sub.id, file.id].map(|id| {
+        copy_job_files::ActiveModel {
+            job_id: ActiveValue::Set(job),
+            file_id: ActiveValue::Set(id),
+        }
+    }))
+    .exec_without_returning(&context.db)
+    .await
+    .unwrap();
+
+    let reaped = reap_abandoned_uploads(&context, now - 60).await.unwrap();
+
+    assert_eq!(reaped.copies, 1);
+    for id in [sub.id, file.id] {
+        assert!(files::Entity::find_by_id(id)
+            .one(&context.db)
+            .await
+            .unwrap()
+            .is_none());
+    }
+    assert!(storage.get_uploaded_chunks(&file).await.unwrap().is_empty());
+
+    // The upload stays, and so does the folder holding it.
+    for id in [folder.id, upload.id] {
+        assert!(files::Entity::find_by_id(id)
+            .one(&context.db)
+            .await
+            .unwrap()
+            .is_some());
+    }
+
+    let job = copy_jobs::Entity::find_by_id(job)
+        .one(&context.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(job.status, "failed");
+    assert_eq!(job.error.as_deref(), Some("interrupted"));
+}
+
+#[actix_web::test]
+async fn running_copy_is_kept_alive_and_reaped_copy_cannot_end() {
+    let context = Context::mock_sqlite().await;
+    let user = entity::mock::create_user(&context.db, "first@test.com", None).await;
+    let repository = Repository::new(&context.db);
+    let now = Utc::now().timestamp();
+
+    let folder = create_file(&context, &user, "copy", None, Some("dir"))
+        .await
+        .unwrap();
+    let job = entity::Uuid::new_v4();
+    copy_jobs::Entity::insert(copy_jobs::ActiveModel {
+        id: ActiveValue::Set(job),
+        user_id: ActiveValue::Set(user.id),
+        file_id: ActiveValue::Set(folder.id),
+        status: ActiveValue::Set("running".to_string()),
+        files_total: ActiveValue::Set(1),
+        files_copied: ActiveValue::Set(0),
+        error: ActiveValue::Set(None),
+        created_at: ActiveValue::Set(now - 3600),
+        updated_at: ActiveValue::Set(now - 3600),
+        finished_at: ActiveValue::Set(None),
+    })
+    .exec_without_returning(&context.db)
+    .await
+    .unwrap();
+
+    // Still copying one large file: the heartbeat keeps it from the reaper.
+    assert!(repository.touch_copies(job, &[]).await.unwrap());
+    let reaped = reap_abandoned_uploads(&context, now - 60).await.unwrap();
+    assert_eq!(reaped.copies, 0);
+
+    copy_jobs::Entity::update_many()
+        .col_expr(copy_jobs::Column::UpdatedAt, Expr::value(now - 3600))
+        .filter(copy_jobs::Column::Id.eq(job))
+        .exec(&context.db)
+        .await
+        .unwrap();
+    let reaped = reap_abandoned_uploads(&context, now - 60).await.unwrap();
+    assert_eq!(reaped.copies, 1);
+
+    // The copy finds out it was reaped and stops instead of finishing.
+    assert!(!repository.touch_copies(job, &[]).await.unwrap());
+    assert!(!repository.end_copy(job, None).await.unwrap());
+
+    let job = copy_jobs::Entity::find_by_id(job)
+        .one(&context.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(job.status, "failed");
+    assert_eq!(job.error.as_deref(), Some("interrupted"));
+}
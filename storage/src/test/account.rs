@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+use context::Context;
+use entity::{files, EntityTrait, Uuid};
+use error::Error;
+
+use crate::{data::account::ExportedFile, mock::create_file, repository::Repository};
+
+fn exported(name: &str) -> ExportedFile {
+    let now = Utc::now().timestamp();
+
+    ExportedFile {
+        file: files::Model {
+            id: Uuid::new_v4(),
+            name_hash: cryptfns::sha256::digest(name.as_bytes()),
+            encrypted_name: name.to_string(),
+            encrypted_thumbnail: None,
+            mime: "text/plain".to_string(),
+            size: Some(100),
+            chunks: Some(2),
+            chunks_stored: Some(2),
+            file_id: None,
+            md5: None,
+            sha1: None,
+            sha256: None,
+            blake2b: None,
+            cipher: "ascon128a".to_string(),
+            editable: false,
+            file_modified_at: now,
+            created_at: now,
+            finished_upload_at: Some(now),
+            active_version: 1,
+            pending_version: Some(2),
+            pending_chunks: Some(2),
+            pending_size: Some(100),
+            last_membership_change_at: None,
+            members_list_signature: None,
+            members_list_signed_at: None,
+            members_list_signed_by_user_id: None,
+            deleted_at: Some(now),
+            deleted_parent_id: Some(Uuid::new_v4()),
+            last_chunk_at: Some(now),
+            damaged_at: Some(now),
+        },
+        encrypted_key: "key".to_string(),
+        search_tokens: vec![],
+    }
+}
+
+#[actix_web::test]
+async fn imported_files_take_no_server_state_from_the_archive() {
+    let context = Context::mock_sqlite().await;
+    let repository = Repository::new(&context.db);
+    let user = entity::mock::create_user(&context.db, "first@test.com", None).await;
+
+    let partial = exported("partial");
+    let complete = exported("complete");
+    let uploaded = HashMap::from([(partial.file.id, 1), (complete.file.id, 2)]);
+    let (partial_id, complete_id) = (partial.file.id, complete.file.id);
+
+    repository
+        .import_files(user.id, vec![partial, complete], &uploaded)
+        .await
+        .unwrap();
+
+    let partial = files::Entity::find_by_id(partial_id)
+        .one(&context.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(partial.chunks_stored, Some(1));
+    assert_eq!(partial.finished_upload_at, None);
+    assert_eq!(partial.pending_version, None);
+    assert_eq!(partial.deleted_at, None);
+    assert_eq!(partial.deleted_parent_id, None);
+    assert_eq!(partial.damaged_at, None);
+
+    let complete = files::Entity::find_by_id(complete_id)
+        .one(&context.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(complete.finished_upload_at.is_some());
+}
+
+#[actix_web::test]
+async fn imported_files_do_not_collide_by_name() {
+    let context = Context::mock_sqlite().await;
+    let repository = Repository::new(&context.db);
+    let user = entity::mock::create_user(&context.db, "first@test.com", None).await;
+
+    create_file(&context, &user, "taken", None, Some("text/plain"))
+        .await
+        .unwrap();
+
+    let result = repository
+        .import_files(user.id, vec![exported("taken")], &HashMap::new())
+        .await;
+    assert!(matches!(result, Err(Error::BadRequest(e)) if e == "file_or_directory_exists"));
+
+    let result = repository
+        .import_files(
+            user.id,
+            vec![exported("twice"), exported("twice")],
+            &HashMap::new(),
+        )
+        .await;
+    assert!(matches!(result, Err(Error::BadRequest(e)) if e == "file_or_directory_exists"));
+}
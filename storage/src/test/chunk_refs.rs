@@ -0,0 +1,117 @@
+use crate::{
+    data::replace_content::{ReplaceContent, ReusedChunk, ValidatedReplaceContent},
+    housekeeping::purge_version,
+    mock::create_file,
+    repository::Repository,
+};
+use chrono::Utc;
+use context::Context;
+use entity::{
+    file_chunk_refs, file_versions, files, ActiveValue, ColumnTrait, EntityTrait, Expr,
+    QueryFilter, TransactionTrait, Uuid,
+};
+use error::Error;
+use fs::prelude::*;
+
+fn reusing(file_id: Uuid, version: i32) -> ValidatedReplaceContent {
+    ReplaceContent {
+        size: Some(2),
+        chunks: Some(1),
+        encrypted_name: None,
+        encrypted_thumbnail: None,
+        search_tokens_hashed: None,
+        force: Some(true),
+        reuse_chunks: Some(vec![ReusedChunk { chunk: 0, version }]),
+    }
+    .validate_into(file_id)
+    .unwrap()
+}
+
+#[actix_web::test]
+async fn prune_keeps_chunks_an_edit_borrowed_meanwhile() {
+    let context = Context::mock_sqlite_shared().await;
+    let user = entity::mock::create_user(&context.db, "first@test.com", None).await;
+    let storage = Fs::new(&context.config);
+    let now = Utc::now().timestamp();
+
+    let file = create_file(&context, &user, "note.md", None, Some("text/markdown"))
+        .await
+        .unwrap();
+    files::Entity::update_many()
+        .col_expr(files::Column::Editable, Expr::value(true))
+        .col_expr(files::Column::FinishedUploadAt, Expr::value(now))
+        .col_expr(files::Column::ActiveVersion, Expr::value(2))
+        .filter(files::Column::Id.eq(file.id))
+        .exec(&context.db)
+        .await
+        .unwrap();
+    file_versions::Entity::insert(file_versions::ActiveModel {
+        id: ActiveValue::Set(Uuid::new_v4()),
+        file_id: ActiveValue::Set(file.id),
+        version: ActiveValue::Set(1),
+        user_id: ActiveValue::Set(None),
+        is_anonymous: ActiveValue::Set(false),
+        size: ActiveValue::Set(2),
+        chunks: ActiveValue::Set(1),
+        sha256: ActiveValue::Set(None),
+        created_at: ActiveValue::Set(now - 60),
+        pinned_at: ActiveValue::Set(None),
+    })
+    .exec_without_returning(&context.db)
+    .await
+    .unwrap();
+
+    let file = files::Entity::find_by_id(file.id)
+        .one(&context.db)
+        .await
+        .unwrap()
+        .unwrap();
+    storage.push_v(&file, 1, 0, b"v1").await.unwrap();
+    storage.push_v(&file, 2, 0, b"v2").await.unwrap();
+
+    // The edit validates and links version 1 first, the purge of version 1
+    // starts before the edit commits and has to wait for its references.
+    let connection = context.db.begin().await.unwrap();
+    let (edited, _) = Repository::new(&connection)
+        .manage(user.id)
+        .replace_content(file.id, reusing(file.id, 1))
+        .await
+        .unwrap();
+    let repository = Repository::new(&context.db);
+    let (purged, committed) = futures::join!(
+        purge_version(&repository, &storage, &file, file.id, 1),
+        connection.commit()
+    );
+    committed.unwrap();
+    purged.unwrap();
+
+    // The prune that purged the version drops its rows as well.
+    file_versions::Entity::delete_many()
+        .filter(file_versions::Column::FileId.eq(file.id))
+        .filter(file_versions::Column::Version.eq(1))
+        .exec(&context.db)
+        .await
+        .unwrap();
+
+    // The borrowed chunk moved into the edit before version 1 went away.
+    assert_eq!(edited.pending_version, Some(3));
+    assert_eq!(storage.pull_v(&file, 3, 0).await.unwrap(), b"v1");
+    assert!(!storage.exists_v(&file, 1, 0).await.unwrap());
+    assert!(file_chunk_refs::Entity::find()
+        .filter(file_chunk_refs::Column::FileId.eq(file.id))
+        .all(&context.db)
+        .await
+        .unwrap()
+        .is_empty());
+
+    // With its rows gone the pruned version is no source for later edits.
+    let connection = context.db.begin().await.unwrap();
+    let refused = Repository::new(&connection)
+        .manage(user.id)
+        .replace_content(file.id, reusing(file.id, 1))
+        .await;
+    assert!(matches!(
+        refused,
+        Err(Error::BadRequest(message)) if message == "reused_chunk_not_found"
+    ));
+}
@@ -0,0 +1,209 @@
+use crate::{fsck::fsck, mock::create_file};
+use chrono::Utc;
+use context::Context;
+use entity::{
+    file_chunk_refs, file_versions, files, ActiveValue, ColumnTrait, EntityTrait, Expr,
+    QueryFilter, Uuid,
+};
+use fs::prelude::*;
+
+/// fsck walks the whole data directory, so it gets one of its own instead
+/// of the directory every other test writes chunks into.
+async fn context_with_own_data_dir() -> Context {
+    let mut context = Context::mock_sqlite().await;
+    let data_dir = std::env::temp_dir().join(format!("hoodik-fsck-{}", Uuid::new_v4()));
+    std::fs::create_dir_all(&data_dir).unwrap();
+    context.config.app.data_dir = data_dir.to_string_lossy().to_string();
+
+    context
+}
+
+async fn finished(
+    context: &Context,
+    id: Uuid,
+    editable: bool,
+    active_version: i32,
+) -> files::Model {
+    files::Entity::update_many()
+        .col_expr(files::Column::Editable, Expr::value(editable))
+        .col_expr(files::Column::ActiveVersion, Expr::value(active_version))
+        .col_expr(
+            files::Column::FinishedUploadAt,
+            Expr::value(Utc::now().timestamp()),
+        )
+        .filter(files::Column::Id.eq(id))
+        .exec(&context.db)
+        .await
+        .unwrap();
+
+    files::Entity::find_by_id(id)
+        .one(&context.db)
+        .await
+        .unwrap()
+        .unwrap()
+}
+
+#[actix_web::test]
+async fn fsck_reports_then_repairs() {
+    let context = context_with_own_data_dir().await;
+    let user = entity::mock::create_user(&context.db, "first@test.com", None).await;
+    let storage = Fs::new(&context.config);
+
+    let healthy = create_file(
+        &context,
+        &user,
+        "healthy.json",
+        None,
+        Some("application/json"),
+    )
+    .await
+    .unwrap();
+    let healthy = finished(&context, healthy.id, false, 1).await;
+    storage.push(&healthy, 0, b"chunk").await.unwrap();
+
+    let damaged = create_file(
+        &context,
+        &user,
+        "damaged.json",
+        None,
+        Some("application/json"),
+    )
+    .await
+    .unwrap();
+    let damaged = finished(&context, damaged.id, false, 1).await;
+
+    let note = create_file(&context, &user, "note.md", None, Some("text/markdown"))
+        .await
+        .unwrap();
+    let note = finished(&context, note.id, true, 2).await;
+    storage.push_v(&note, 2, 0, b"active").await.unwrap();
+    storage.push_v(&note, 5, 0, b"stray").await.unwrap();
+    file_versions::Entity::insert(file_versions::ActiveModel {
+        id: ActiveValue::Set(Uuid::new_v4()),
+        file_id: ActiveValue::Set(note.id),
+        version: ActiveValue::Set(1),
+        user_id: ActiveValue::Set(Some(user.id)),
+        is_anonymous: ActiveValue::Set(false),
+        size: ActiveValue::Set(100),
+        chunks: ActiveValue::Set(1),
+        sha256: ActiveValue::Set(None),
+        created_at: ActiveValue::Set(Utc::now().timestamp()),
+        pinned_at: ActiveValue::Set(None),
+    })
+    .exec_without_returning(&context.db)
+    .await
+    .unwrap();
+
+    let orphan = Filename::new(Uuid::new_v4()).with_timestamp(1);
+    storage.push(&orphan, 0, b"orphan").await.unwrap();
+
+    let report = fsck(&context, false).await.unwrap();
+
+    assert_eq!(report.checked, 3);
+    assert_eq!(report.orphans.len(), 1);
+    assert_eq!(report.orphans[0].name, orphan.inner_name());
+    assert_eq!(report.stray_versions, vec![(note.id, 5)]);
+    assert_eq!(report.missing_versions, vec![(note.id, 1)]);
+    assert_eq!(report.damaged.len(), 1);
+    assert_eq!(report.damaged[0].file_id, damaged.id);
+    assert_eq!(report.damaged[0].missing, vec![0]);
+    assert!(storage.exists(&orphan, 0).await.unwrap());
+    assert!(storage.exists_v(&note, 5, 0).await.unwrap());
+
+    let report = fsck(&context, true).await.unwrap();
+
+    assert!(report.repaired);
+    assert!(!storage.exists(&orphan, 0).await.unwrap());
+    assert!(!storage.exists_v(&note, 5, 0).await.unwrap());
+    assert!(storage.exists_v(&note, 2, 0).await.unwrap());
+    assert!(storage.exists(&healthy, 0).await.unwrap());
+    assert!(file_versions::Entity::find()
+        .filter(file_versions::Column::FileId.eq(note.id))
+        .one(&context.db)
+        .await
+        .unwrap()
+        .is_none());
+
+    let flagged = files::Entity::find_by_id(damaged.id)
+        .one(&context.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(flagged.damaged_at.is_some());
+
+    let report = fsck(&context, false).await.unwrap();
+    assert_eq!(report.damaged.len(), 1);
+    assert!(report.orphans.is_empty());
+    assert!(report.stray_versions.is_empty());
+    assert!(report.missing_versions.is_empty());
+
+    storage.push(&damaged, 0, b"chunk").await.unwrap();
+    let report = fsck(&context, true).await.unwrap();
+    assert!(report.is_clean());
+
+    let healed = files::Entity::find_by_id(damaged.id)
+        .one(&context.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(healed.damaged_at, None);
+
+    std::fs::remove_dir_all(&context.config.app.data_dir).unwrap();
+}
+
+#[actix_web::test]
+async fn fsck_counts_borrowed_chunks_while_their_source_stores_them() {
+    let context = context_with_own_data_dir().await;
+    let user = entity::mock::create_user(&context.db, "first@test.com", None).await;
+    let storage = Fs::new(&context.config);
+
+    let note = create_file(&context, &user, "note.md", None, Some("text/markdown"))
+        .await
+        .unwrap();
+    let note = finished(&context, note.id, true, 2).await;
+    storage.push_v(&note, 1, 1, b"first").await.unwrap();
+    storage.push_v(&note, 2, 1, b"second").await.unwrap();
+    file_versions::Entity::insert(file_versions::ActiveModel {
+        id: ActiveValue::Set(Uuid::new_v4()),
+        file_id: ActiveValue::Set(note.id),
+        version: ActiveValue::Set(1),
+        user_id: ActiveValue::Set(Some(user.id)),
+        is_anonymous: ActiveValue::Set(false),
+        size: ActiveValue::Set(100),
+        chunks: ActiveValue::Set(2),
+        sha256: ActiveValue::Set(None),
+        created_at: ActiveValue::Set(Utc::now().timestamp()),
+        pinned_at: ActiveValue::Set(None),
+    })
+    .exec_without_returning(&context.db)
+    .await
+    .unwrap();
+    file_chunk_refs::Entity::insert(file_chunk_refs::ActiveModel {
+        file_id: ActiveValue::Set(note.id),
+        version: ActiveValue::Set(2),
+        chunk: ActiveValue::Set(0),
+        source_version: ActiveValue::Set(1),
+        created_at: ActiveValue::Set(Utc::now().timestamp()),
+    })
+    .exec_without_returning(&context.db)
+    .await
+    .unwrap();
+    files::Entity::update_many()
+        .col_expr(files::Column::Chunks, Expr::value(2))
+        .filter(files::Column::Id.eq(note.id))
+        .exec(&context.db)
+        .await
+        .unwrap();
+
+    // Version 1 never stored chunk 0, so the active version lacks it.
+    let report = fsck(&context, false).await.unwrap();
+    assert_eq!(report.damaged.len(), 1);
+    assert_eq!(report.damaged[0].file_id, note.id);
+    assert_eq!(report.damaged[0].missing, vec![0]);
+
+    storage.push_v(&note, 1, 0, b"first").await.unwrap();
+    let report = fsck(&context, false).await.unwrap();
+    assert!(report.is_clean());
+
+    std::fs::remove_dir_all(&context.config.app.data_dir).unwrap();
+}
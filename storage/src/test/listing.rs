@@ -0,0 +1,167 @@
+use std::collections::HashSet;
+
+use context::Context;
+use entity::Uuid;
+
+use crate::{
+    data::{
+        cursor::{Cursor, SortKey},
+        query::Query,
+    },
+    mock::create_file,
+    repository::Repository,
+};
+
+/// Walk every page of `dir_id` and return the ids in the order served.
+async fn walk(
+    repository: &Repository<'_, entity::DbConn>,
+    user_id: Uuid,
+    dir_id: Uuid,
+    order_by: Option<&str>,
+    order: Option<&str>,
+) -> Vec<Uuid> {
+    let mut ids = vec![];
+    let mut cursor = None;
+
+    loop {
+        let response = repository
+            .manage(user_id)
+            .find(Query {
+                dir_id: Some(dir_id.to_string()),
+                order_by: order_by.map(str::to_string),
+                order: order.map(str::to_string),
+                limit: Some(4),
+                cursor: cursor.clone(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        assert!(response.children.len() <= 4);
+        ids.extend(response.children.iter().map(|file| file.id));
+
+        match response.next_cursor {
+            Some(next) => cursor = Some(next),
+            None => break,
+        }
+    }
+
+    ids
+}
+
+#[actix_web::test]
+async fn paginated_listing_serves_every_row_once() {
+    let context = Context::mock_sqlite().await;
+    let repository = Repository::new(&context.db);
+    let user = entity::mock::create_user(&context.db, "first@test.com", None).await;
+
+    let dir = create_file(&context, &user, "dir", None, Some("dir"))
+        .await
+        .unwrap();
+
+    let mut expected = HashSet::new();
+    for i in 0..7 {
+        let file = create_file(
+            &context,
+            &user,
+            &format!("file-{i}"),
+            Some(dir.id),
+            Some("text/plain"),
+        )
+        .await
+        .unwrap();
+        expected.insert(file.id);
+    }
+    for i in 0..3 {
+        let sub = create_file(
+            &context,
+            &user,
+            &format!("sub-{i}"),
+            Some(dir.id),
+            Some("dir"),
+        )
+        .await
+        .unwrap();
+        expected.insert(sub.id);
+    }
+
+    // Every row shares its sort value with others, so only the id
+    // tie-break keeps the pages apart.
+    for (order_by, order) in [
+        (None, None),
+        (Some("size"), None),
+        (Some("size"), Some("desc")),
+        (Some("modified_at"), Some("desc")),
+    ] {
+        let ids = walk(&repository, user.id, dir.id, order_by, order).await;
+
+        assert_eq!(ids.len(), expected.len(), "{order_by:?} {order:?}");
+        assert_eq!(ids.iter().copied().collect::<HashSet<_>>(), expected);
+    }
+
+    let ids = walk(&repository, user.id, dir.id, Some("size"), Some("desc")).await;
+    let again = walk(&repository, user.id, dir.id, Some("size"), Some("desc")).await;
+    assert_eq!(ids, again);
+
+    let response = repository
+        .manage(user.id)
+        .find(Query {
+            dir_id: Some(dir.id.to_string()),
+            limit: Some(4),
+            with_total: Some(true),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(response.total, Some(10));
+    assert_eq!(response.children.len(), 4);
+    assert!(response.next_cursor.is_some());
+
+    // Without `limit` or `cursor` the listing stays whole.
+    let response = repository
+        .manage(user.id)
+        .find(Query {
+            dir_id: Some(dir.id.to_string()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(response.children.len(), 10);
+    assert!(response.next_cursor.is_none());
+    assert!(response.total.is_none());
+}
+
+#[actix_web::test]
+async fn cursor_is_bound_to_its_ordering() {
+    let context = Context::mock_sqlite().await;
+    let repository = Repository::new(&context.db);
+    let user = entity::mock::create_user(&context.db, "first@test.com", None).await;
+
+    let cursor = Cursor {
+        sort: SortKey::Size,
+        desc: false,
+        value: 100,
+        id: Uuid::new_v4(),
+    };
+    let encoded = cursor.encode().unwrap();
+    assert_eq!(Cursor::decode(&encoded).unwrap(), cursor);
+
+    let result = repository
+        .manage(user.id)
+        .find(Query {
+            order_by: Some("modified_at".to_string()),
+            cursor: Some(encoded),
+            ..Default::default()
+        })
+        .await;
+    assert!(result.is_err());
+
+    let result = repository
+        .manage(user.id)
+        .find(Query {
+            cursor: Some("not a cursor".to_string()),
+            ..Default::default()
+        })
+        .await;
+    assert!(result.is_err());
+}
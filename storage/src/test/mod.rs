@@ -1,5 +1,12 @@
+pub(crate) mod abandoned;
+pub(crate) mod account;
+pub(crate) mod chunk_refs;
 pub(crate) mod create;
 pub(crate) mod delete;
+pub(crate) mod fsck;
+pub(crate) mod listing;
 pub(crate) mod move_many;
 pub(crate) mod rename;
+pub(crate) mod retention;
 pub(crate) mod search;
+pub(crate) mod trash;
@@ -0,0 +1,139 @@
+use chrono::{TimeZone, Utc};
+use entity::{file_versions, Uuid};
+
+use crate::data::version_policy::RetentionPolicy;
+
+const HOUR: i64 = 3600;
+const DAY: i64 = 86400;
+
+fn at(year: i32, month: u32, day: u32, hour: u32) -> i64 {
+    Utc.with_ymd_and_hms(year, month, day, hour, 0, 0)
+        .unwrap()
+        .timestamp()
+}
+
+fn history(created_at: &[i64]) -> Vec<file_versions::Model> {
+    created_at
+        .iter()
+        .enumerate()
+        .map(|(i, created_at)| file_versions::Model {
+            id: Uuid::new_v4(),
+            file_id: Uuid::nil(),
+            version: i as i32 + 1,
+            user_id: None,
+            is_anonymous: false,
+            size: 1,
+            chunks: 1,
+            sha256: None,
+            created_at: *created_at,
+            pinned_at: None,
+        })
+        .collect()
+}
+
+fn policy(keep_all_days: u32) -> RetentionPolicy {
+    RetentionPolicy {
+        keep_all_days,
+        keep_hourly: 0,
+        keep_daily: 0,
+        keep_weekly: 0,
+        keep_monthly: 0,
+        max_versions: None,
+    }
+}
+
+fn sorted(mut versions: Vec<i32>) -> Vec<i32> {
+    versions.sort();
+    versions
+}
+
+#[test]
+fn everything_within_keep_all_days_is_kept() {
+    let now = at(2026, 10, 19, 12);
+    let history = history(&[now - 3 * DAY, now - DAY - HOUR, now - DAY + HOUR, now - 60]);
+
+    assert_eq!(sorted(policy(1).prunable(&history, now)), vec![1, 2]);
+    assert!(policy(7).prunable(&history, now).is_empty());
+    assert_eq!(sorted(policy(0).prunable(&history, now)), vec![1, 2, 3, 4]);
+}
+
+#[test]
+fn newest_version_of_each_hour_and_day_is_kept() {
+    let now = at(2026, 10, 19, 12);
+    let history = history(&[
+        at(2026, 10, 16, 9),
+        at(2026, 10, 17, 9),
+        at(2026, 10, 17, 10),
+        at(2026, 10, 18, 9),
+        at(2026, 10, 18, 10),
+        at(2026, 10, 18, 10) + 60,
+    ]);
+
+    let hourly = RetentionPolicy {
+        keep_hourly: 2,
+        ..policy(0)
+    };
+    assert_eq!(sorted(hourly.prunable(&history, now)), vec![1, 2, 3, 5]);
+
+    let daily = RetentionPolicy {
+        keep_daily: 2,
+        ..policy(0)
+    };
+    assert_eq!(sorted(daily.prunable(&history, now)), vec![1, 2, 4, 5]);
+
+    // Buckets only count past the keep-everything window.
+    let both = RetentionPolicy {
+        keep_daily: 1,
+        ..policy(2)
+    };
+    assert_eq!(sorted(both.prunable(&history, now)), vec![1, 2]);
+}
+
+#[test]
+fn weeks_start_on_monday() {
+    let now = at(2026, 10, 21, 12);
+    let history = history(&[
+        at(2026, 10, 18, 12),
+        at(2026, 10, 19, 12),
+        at(2026, 10, 20, 12),
+    ]);
+
+    let weekly = RetentionPolicy {
+        keep_weekly: 2,
+        ..policy(0)
+    };
+    assert_eq!(weekly.prunable(&history, now), vec![2]);
+}
+
+#[test]
+fn newest_version_of_each_month_is_kept() {
+    let now = at(2026, 10, 19, 12);
+    let history = history(&[
+        at(2026, 1, 31, 12),
+        at(2026, 2, 1, 12),
+        at(2026, 2, 28, 12),
+        at(2026, 3, 15, 12),
+    ]);
+
+    let monthly = RetentionPolicy {
+        keep_monthly: 2,
+        ..policy(0)
+    };
+    assert_eq!(sorted(monthly.prunable(&history, now)), vec![1, 2]);
+}
+
+#[test]
+fn cap_drops_the_oldest_and_skips_pinned_versions() {
+    let now = at(2026, 10, 19, 12);
+    let mut history = history(&[now - 50, now - 40, now - 30, now - 20, now - 10]);
+    history[0].pinned_at = Some(now);
+
+    let capped = RetentionPolicy {
+        max_versions: Some(2),
+        ..policy(1)
+    };
+    assert_eq!(sorted(capped.prunable(&history, now)), vec![2, 3]);
+
+    // Pinned versions outlive even a policy that keeps nothing.
+    assert_eq!(sorted(policy(0).prunable(&history, now)), vec![2, 3, 4, 5]);
+}
@@ -0,0 +1,142 @@
+use crate::{mock::create_file, repository::Repository};
+use context::Context;
+use entity::{files, EntityTrait};
+
+#[actix_web::test]
+async fn trashed_folder_disappears_and_comes_back_whole() {
+    let context = Context::mock_sqlite().await;
+    let repository = Repository::new(&context.db);
+    let user = entity::mock::create_user(&context.db, "first@test.com", None).await;
+
+    let parent = create_file(&context, &user, "parent", None, Some("dir"))
+        .await
+        .unwrap();
+    let dir = create_file(&context, &user, "dir", Some(parent.id), Some("dir"))
+        .await
+        .unwrap();
+    let file = create_file(
+        &context,
+        &user,
+        "file.json",
+        Some(dir.id),
+        Some("application/json"),
+    )
+    .await
+    .unwrap();
+
+    let trashed = repository
+        .trash(user.id)
+        .trash_many(vec![dir.id])
+        .await
+        .unwrap();
+    assert_eq!(trashed.len(), 2);
+
+    assert!(repository.by_id(dir.id, user.id).await.is_err());
+    assert!(repository.by_id(file.id, user.id).await.is_err());
+
+    let list = repository.trash(user.id).list().await.unwrap();
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0].id, dir.id);
+
+    // Trashed bytes still take up the quota until they are purged.
+    let query = repository.query(user.id);
+    assert_eq!(query.used_space().await.unwrap(), 100);
+    assert_eq!(query.trashed_space().await.unwrap(), 100);
+
+    let restored = repository.trash(user.id).restore(dir.id).await.unwrap();
+    assert_eq!(restored.file_id, Some(parent.id));
+    assert!(repository.by_id(file.id, user.id).await.is_ok());
+    assert!(repository.trash(user.id).list().await.unwrap().is_empty());
+    assert_eq!(query.trashed_space().await.unwrap(), 0);
+}
+
+#[actix_web::test]
+async fn restore_falls_back_to_the_top_level_and_refuses_name_clashes() {
+    let context = Context::mock_sqlite().await;
+    let repository = Repository::new(&context.db);
+    let user = entity::mock::create_user(&context.db, "first@test.com", None).await;
+
+    let parent = create_file(&context, &user, "parent", None, Some("dir"))
+        .await
+        .unwrap();
+    let file = create_file(
+        &context,
+        &user,
+        "file.json",
+        Some(parent.id),
+        Some("application/json"),
+    )
+    .await
+    .unwrap();
+
+    let trash = repository.trash(user.id);
+    trash.trash_many(vec![file.id]).await.unwrap();
+    trash.trash_many(vec![parent.id]).await.unwrap();
+
+    let clash = create_file(&context, &user, "file.json", None, Some("application/json"))
+        .await
+        .unwrap();
+    assert!(trash.restore(file.id).await.is_err());
+
+    repository
+        .manage(user.id)
+        .delete_many(vec![clash.id])
+        .await
+        .unwrap();
+
+    let restored = trash.restore(file.id).await.unwrap();
+    assert_eq!(restored.file_id, None);
+}
+
+#[actix_web::test]
+async fn purge_deletes_only_what_outlived_the_retention() {
+    let context = Context::mock_sqlite().await;
+    let repository = Repository::new(&context.db);
+    let user = entity::mock::create_user(&context.db, "first@test.com", None).await;
+
+    let dir = create_file(&context, &user, "dir", None, Some("dir"))
+        .await
+        .unwrap();
+    create_file(
+        &context,
+        &user,
+        "file.json",
+        Some(dir.id),
+        Some("application/json"),
+    )
+    .await
+    .unwrap();
+    let kept = create_file(&context, &user, "kept.json", None, Some("application/json"))
+        .await
+        .unwrap();
+
+    repository
+        .trash(user.id)
+        .trash_many(vec![dir.id, kept.id])
+        .await
+        .unwrap();
+
+    let now = chrono::Utc::now().timestamp();
+    assert!(repository
+        .purge_expired_trash(now - 60)
+        .await
+        .unwrap()
+        .is_empty());
+
+    let purged = repository
+        .trash(user.id)
+        .purge(Some(vec![dir.id]))
+        .await
+        .unwrap();
+    assert_eq!(purged.len(), 2);
+
+    let purged = repository.purge_expired_trash(now + 60).await.unwrap();
+    assert_eq!(purged.len(), 1);
+    assert_eq!(purged[0].id, kept.id);
+
+    assert!(files::Entity::find()
+        .all(&context.db)
+        .await
+        .unwrap()
+        .is_empty());
+}
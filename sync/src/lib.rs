@@ -0,0 +1,590 @@
+//! `hoodik-sync`, a local directory mirrored with a Hoodik folder both ways.
+//!
+//! Every pass compares the directory and the folder against the state
+//! database, the picture of both sides from the last pass:
+//!
+//! - renames on either side are replayed on the other, remote ones by id
+//!   and local ones by content hash, so files keep their id and history;
+//! - files whose hash changed are uploaded, editable files through
+//!   `replace_content` so the server keeps the previous content as a
+//!   version;
+//! - a file changed on both sides keeps the remote content under its name,
+//!   the local content is renamed to a conflict copy and uploaded too;
+//! - deletes are only replayed for files the other side did not change.
+//!
+//! Empty files are left alone, Hoodik does not store them.
+pub mod local;
+pub mod state;
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    path::{Path, PathBuf},
+};
+
+use client::{tree::segments, Entry, Error, Result, Session};
+
+use crate::{
+    local::{Local, RESERVED_PREFIX},
+    state::{Record, State},
+};
+
+/// What a pass did.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub uploaded: usize,
+    pub downloaded: usize,
+    pub renamed: usize,
+    pub deleted: usize,
+    pub conflicts: usize,
+    /// Paths that failed, they are tried again on the next pass.
+    pub failed: usize,
+}
+
+pub struct Syncer {
+    session: Session,
+    root: PathBuf,
+    remote_root: Option<String>,
+    state: State,
+}
+
+impl Syncer {
+    /// Sync `root` with the folder at `remote`, creating it when missing.
+    /// The state database defaults to a file inside `root`.
+    pub async fn new(
+        session: Session,
+        root: &Path,
+        remote: &str,
+        state: Option<&Path>,
+    ) -> Result<Self> {
+        if !root.is_dir() {
+            return Err(Error::Invalid(format!(
+                "{} is not a folder",
+                root.display()
+            )));
+        }
+
+        let remote_root = session.create_dirs(remote).await?;
+        let state = match state {
+            Some(state) => State::open(state).await?,
+            None => State::open(&root.join(format!("{RESERVED_PREFIX}.db"))).await?,
+        };
+        state.bind_remote(remote_root.as_deref()).await?;
+
+        Ok(Self {
+            session,
+            root: root.to_path_buf(),
+            remote_root,
+            state,
+        })
+    }
+
+    /// Bring both sides in line once.
+    pub async fn run_once(&self) -> Result<Report> {
+        let state = self.state.load().await?;
+        let local = local::scan(&self.root, &state)?;
+
+        let mut remote = BTreeMap::new();
+        let mut dirs = HashMap::from([(String::new(), self.remote_root.clone())]);
+        for (path, entry) in self.session.walk(self.remote_root.as_deref()).await? {
+            if entry.name.contains('/') || entry.name.starts_with(RESERVED_PREFIX) {
+                continue;
+            }
+            if entry.is_dir() {
+                dirs.insert(path.clone(), Some(entry.id.clone()));
+            } else if entry.finished_upload_at.is_none() {
+                continue;
+            }
+            remote.insert(path, entry);
+        }
+
+        let mut pass = Pass {
+            syncer: self,
+            state,
+            local,
+            remote,
+            dirs,
+            report: Report::default(),
+        };
+        pass.remote_renames().await?;
+        pass.local_renames().await;
+        pass.reconcile().await;
+
+        Ok(pass.report)
+    }
+}
+
+struct Pass<'a> {
+    syncer: &'a Syncer,
+    state: BTreeMap<String, Record>,
+    local: BTreeMap<String, Local>,
+    remote: BTreeMap<String, Entry>,
+    /// Remote folder ids by path, `""` being the synced folder itself.
+    dirs: HashMap<String, Option<String>>,
+    report: Report,
+}
+
+impl Pass<'_> {
+    fn session(&self) -> &Session {
+        &self.syncer.session
+    }
+
+    fn absolute(&self, path: &str) -> PathBuf {
+        self.syncer.root.join(path)
+    }
+
+    /// Entries that kept their id but now live under another path were
+    /// moved or renamed remotely, the local copy follows them.
+    async fn remote_renames(&mut self) -> Result<()> {
+        let by_id: HashMap<&str, &str> = self
+            .remote
+            .iter()
+            .map(|(path, entry)| (entry.id.as_str(), path.as_str()))
+            .collect();
+
+        let mut moves: Vec<(String, String)> = self
+            .state
+            .iter()
+            .filter_map(|(path, record)| {
+                let moved_to = *by_id.get(record.remote_id.as_str())?;
+
+                (moved_to != path).then(|| (path.clone(), moved_to.to_string()))
+            })
+            .collect();
+        moves.sort_by_key(|(from, _)| from.matches('/').count());
+
+        for (from, to) in moves {
+            // A folder moved earlier in this loop took its contents along.
+            let still_there = self.state.get(&from).is_some_and(|record| {
+                self.remote
+                    .get(&to)
+                    .is_some_and(|entry| entry.id == record.remote_id)
+            });
+            if !still_there || !self.local.contains_key(&from) || self.local.contains_key(&to) {
+                continue;
+            }
+
+            let target = self.absolute(&to);
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::rename(self.absolute(&from), &target)?;
+            log::info!("Moved {from} to {to}");
+
+            for (old, new) in rebase(&self.state, &from, &to) {
+                if let Some(record) = self.state.remove(&old) {
+                    self.syncer.state.remove(&old).await?;
+                    self.syncer.state.save(&new, &record).await?;
+                    self.state.insert(new, record);
+                }
+            }
+            for (old, new) in rebase(&self.local, &from, &to) {
+                if let Some(local) = self.local.remove(&old) {
+                    self.local.insert(new, local);
+                }
+            }
+            self.report.renamed += 1;
+        }
+
+        Ok(())
+    }
+
+    /// A file missing locally whose content turned up under a new path was
+    /// moved or renamed locally, the remote file follows it.
+    async fn local_renames(&mut self) {
+        let missing: Vec<(String, Record)> = self
+            .state
+            .iter()
+            .filter(|(path, record)| {
+                !record.is_dir
+                    && !self.local.contains_key(*path)
+                    && self
+                        .remote
+                        .get(*path)
+                        .is_some_and(|entry| unchanged(entry, record))
+            })
+            .map(|(path, record)| (path.clone(), record.clone()))
+            .collect();
+
+        let mut taken = BTreeSet::new();
+        for (from, record) in missing {
+            let candidates: Vec<&String> = self
+                .local
+                .iter()
+                .filter(|(path, local)| {
+                    !local.is_dir
+                        && local.sha256 == record.sha256
+                        && !self.state.contains_key(*path)
+                        && !self.remote.contains_key(*path)
+                        && !taken.contains(*path)
+                })
+                .map(|(path, _)| path)
+                .collect();
+
+            // Copies of the same content leave it open which one was moved.
+            let [to] = candidates.as_slice() else {
+                continue;
+            };
+            let to = to.to_string();
+
+            if let Err(e) = self.move_remote(&from, &to, record).await {
+                log::error!("Failed to move {from} to {to}: {e}");
+                self.report.failed += 1;
+                continue;
+            }
+            taken.insert(to);
+        }
+    }
+
+    async fn move_remote(&mut self, from: &str, to: &str, mut record: Record) -> Result<()> {
+        let Some(mut entry) = self.remote.remove(from) else {
+            return Ok(());
+        };
+        let (from_parent, _) = split(from);
+        let (to_parent, name) = split(to);
+
+        if from_parent != to_parent {
+            let parent = self.remote_dir(to_parent).await?;
+            self.session()
+                .move_many(std::slice::from_ref(&entry.id), parent.as_deref())
+                .await?;
+            entry.parent = parent;
+        }
+        if entry.name != name {
+            self.session().rename(&entry, name).await?;
+            entry.name = name.to_string();
+        }
+        log::info!("Moved {from} to {to} remotely");
+
+        if let Some(local) = self.local.get(to) {
+            record.modified = local.modified;
+        }
+        self.syncer.state.remove(from).await?;
+        self.syncer.state.save(to, &record).await?;
+        self.state.remove(from);
+        self.state.insert(to.to_string(), record);
+        self.remote.insert(to.to_string(), entry);
+        self.report.renamed += 1;
+
+        Ok(())
+    }
+
+    /// Walk every path either side or the state knows about, parents
+    /// before their children. Folders deleted on one side are only
+    /// removed from the other once nothing in them is left to keep.
+    async fn reconcile(&mut self) {
+        let paths: BTreeSet<String> = self
+            .state
+            .keys()
+            .chain(self.local.keys())
+            .chain(self.remote.keys())
+            .cloned()
+            .collect();
+
+        let mut deleted_dirs = vec![];
+        for path in paths {
+            let local = self.local.get(&path).cloned();
+            let remote = self.remote.get(&path).cloned();
+            let record = self.state.get(&path).cloned();
+
+            if local
+                .as_ref()
+                .is_some_and(|local| !local.is_dir && local.size == 0)
+            {
+                log::debug!("Skipping {path}, empty files are not stored");
+                continue;
+            }
+
+            let is_dir = match (&local, &remote) {
+                (Some(local), Some(remote)) if local.is_dir != remote.is_dir() => {
+                    log::warn!(
+                        "Skipping {path}, it is a file on one side and a folder on the other"
+                    );
+                    continue;
+                }
+                (Some(local), _) => local.is_dir,
+                (None, Some(remote)) => remote.is_dir(),
+                (None, None) => record.as_ref().is_some_and(|record| record.is_dir),
+            };
+
+            let result = match is_dir {
+                true => match (local, remote, record) {
+                    (Some(_), Some(remote), _) => self.remember(&path, &remote).await,
+                    (Some(_), None, None) => self.upload_dir(&path).await,
+                    (None, Some(remote), None) => self.download_dir(&path, &remote).await,
+                    (None, None, _) => self.forget(&path).await,
+                    (Some(_), None, Some(_)) | (None, Some(_), Some(_)) => {
+                        deleted_dirs.push(path.clone());
+                        Ok(())
+                    }
+                },
+                false => self.file(&path, local, remote, record).await,
+            };
+
+            if let Err(e) = result {
+                log::error!("Failed to sync {path}: {e}");
+                self.report.failed += 1;
+            }
+        }
+
+        // Children first, so a folder is empty of kept paths by the time
+        // it is looked at.
+        for path in deleted_dirs.into_iter().rev() {
+            if let Err(e) = self.delete_dir(&path).await {
+                log::error!("Failed to sync {path}: {e}");
+                self.report.failed += 1;
+            }
+        }
+    }
+
+    async fn file(
+        &mut self,
+        path: &str,
+        local: Option<Local>,
+        remote: Option<Entry>,
+        record: Option<Record>,
+    ) -> Result<()> {
+        let local_changed = match (&local, &record) {
+            (Some(local), Some(record)) => local.sha256 != record.sha256,
+            (None, None) => false,
+            _ => true,
+        };
+        let remote_changed = match (&remote, &record) {
+            (Some(remote), Some(record)) => !unchanged(remote, record),
+            (None, None) => false,
+            _ => true,
+        };
+
+        match (local, remote) {
+            (None, None) => self.forget(path).await,
+            (Some(_), None) if !local_changed => {
+                std::fs::remove_file(self.absolute(path))?;
+                log::info!("Deleted {path}, it was deleted remotely");
+                self.report.deleted += 1;
+
+                self.forget(path).await
+            }
+            (Some(local), None) => self.upload(path, &local).await,
+            (None, Some(remote)) if !remote_changed => {
+                self.session().delete(&remote.id).await?;
+                log::info!("Deleted {path} remotely, it was deleted here");
+                self.report.deleted += 1;
+
+                self.forget(path).await
+            }
+            (None, Some(remote)) => self.download(path, &remote).await,
+            (Some(local), Some(remote)) => match (local_changed, remote_changed) {
+                (false, false) => Ok(()),
+                (true, false) => self.replace(path, &remote).await,
+                (false, true) => self.download(path, &remote).await,
+                (true, true) if remote.sha256.is_some() && remote.sha256 == local.sha256 => {
+                    self.remember(path, &remote).await
+                }
+                (true, true) => self.conflict(path, &remote).await,
+            },
+        }
+    }
+
+    async fn upload(&mut self, path: &str, local: &Local) -> Result<()> {
+        let (parent, name) = split(path);
+        let parent = self.remote_dir(parent).await?;
+        let entry = self
+            .session()
+            .upload(parent.as_deref(), name, &self.absolute(path))
+            .await?;
+        log::info!("Uploaded {path}");
+        self.report.uploaded += 1;
+
+        self.local.insert(path.to_string(), local.clone());
+        self.remember(path, &entry).await
+    }
+
+    async fn replace(&mut self, path: &str, remote: &Entry) -> Result<()> {
+        let entry = self.session().replace(remote, &self.absolute(path)).await?;
+        log::info!("Uploaded the new content of {path}");
+        self.report.uploaded += 1;
+
+        self.remember(path, &entry).await
+    }
+
+    /// Download next to the target first, a half written file never takes
+    /// the place of a good one.
+    async fn download(&mut self, path: &str, remote: &Entry) -> Result<()> {
+        let target = self.absolute(path);
+        let parent = target.parent().unwrap_or(&self.syncer.root).to_path_buf();
+        std::fs::create_dir_all(&parent)?;
+
+        let partial = parent.join(format!("{RESERVED_PREFIX}-{}.part", uuid::Uuid::new_v4()));
+        if let Err(e) = self.session().download(remote, &partial).await {
+            let _ = std::fs::remove_file(&partial);
+
+            return Err(e);
+        }
+        std::fs::rename(&partial, &target)?;
+        log::info!("Downloaded {path}");
+        self.report.downloaded += 1;
+
+        self.local.insert(path.to_string(), local::read(&target)?);
+        self.remember(path, remote).await
+    }
+
+    /// Both sides changed the file. The remote content keeps the name and
+    /// the local content moves to a conflict copy, uploaded like any new
+    /// file.
+    async fn conflict(&mut self, path: &str, remote: &Entry) -> Result<()> {
+        let copy = conflict_copy(path);
+        std::fs::rename(self.absolute(path), self.absolute(&copy))?;
+        log::warn!("{path} changed on both sides, the local content is kept as {copy}");
+        self.report.conflicts += 1;
+
+        let local = local::read(&self.absolute(&copy))?;
+        self.local.remove(path);
+        self.upload(&copy, &local).await?;
+
+        self.download(path, remote).await
+    }
+
+    async fn upload_dir(&mut self, path: &str) -> Result<()> {
+        self.remote_dir(path).await?;
+        log::info!("Created the folder {path} remotely");
+
+        Ok(())
+    }
+
+    async fn download_dir(&mut self, path: &str, remote: &Entry) -> Result<()> {
+        std::fs::create_dir_all(self.absolute(path))?;
+        log::info!("Created the folder {path}");
+        self.local
+            .insert(path.to_string(), local::read(&self.absolute(path))?);
+
+        self.remember(path, remote).await
+    }
+
+    /// A folder gone from one side goes from the other too, unless
+    /// something was synced into it during this pass.
+    async fn delete_dir(&mut self, path: &str) -> Result<()> {
+        let prefix = format!("{path}/");
+        let kept = self.state.keys().any(|kept| kept.starts_with(&prefix));
+
+        match (
+            self.local.contains_key(path),
+            self.remote.get(path).cloned(),
+        ) {
+            (true, None) if !kept => {
+                std::fs::remove_dir_all(self.absolute(path))?;
+                log::info!("Deleted the folder {path}, it was deleted remotely");
+                self.report.deleted += 1;
+                self.forget(path).await
+            }
+            (false, Some(remote)) if !kept => {
+                self.session().delete(&remote.id).await?;
+                log::info!("Deleted the folder {path} remotely, it was deleted here");
+                self.report.deleted += 1;
+                self.forget(path).await
+            }
+            (true, None) => {
+                self.remote_dir(path).await?;
+                Ok(())
+            }
+            (false, Some(remote)) => {
+                std::fs::create_dir_all(self.absolute(path))?;
+                self.local
+                    .insert(path.to_string(), local::read(&self.absolute(path))?);
+                self.remember(path, &remote).await
+            }
+            _ => self.forget(path).await,
+        }
+    }
+
+    /// The remote folder at `path`, created along with its parents when
+    /// missing.
+    async fn remote_dir(&mut self, path: &str) -> Result<Option<String>> {
+        if let Some(id) = self.dirs.get(path) {
+            return Ok(id.clone());
+        }
+
+        let (parent, name) = split(path);
+        let parent = Box::pin(self.remote_dir(parent)).await?;
+        let entry = self.session().create_dir(parent.as_deref(), name).await?;
+        self.dirs.insert(path.to_string(), Some(entry.id.clone()));
+
+        if let Ok(local) = local::read(&self.absolute(path)) {
+            self.local.insert(path.to_string(), local);
+        }
+        self.remember(path, &entry).await?;
+        self.remote.insert(path.to_string(), entry.clone());
+
+        Ok(Some(entry.id))
+    }
+
+    /// Record `path` as synced with `remote`, from its local state.
+    async fn remember(&mut self, path: &str, remote: &Entry) -> Result<()> {
+        let local = match self.local.get(path) {
+            Some(local) => local.clone(),
+            None => local::read(&self.absolute(path))?,
+        };
+        let record = Record {
+            remote_id: remote.id.clone(),
+            is_dir: remote.is_dir(),
+            sha256: local.sha256,
+            size: local.size,
+            modified: local.modified,
+            version: remote.version,
+        };
+
+        if self.state.get(path) != Some(&record) {
+            self.syncer.state.save(path, &record).await?;
+            self.state.insert(path.to_string(), record);
+        }
+
+        Ok(())
+    }
+
+    async fn forget(&mut self, path: &str) -> Result<()> {
+        if self.state.remove(path).is_some() {
+            self.syncer.state.remove(path).await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The remote file is still what was synced last time.
+fn unchanged(remote: &Entry, record: &Record) -> bool {
+    remote.id == record.remote_id && remote.version == record.version
+}
+
+/// Parent path and name.
+fn split(path: &str) -> (&str, &str) {
+    path.rsplit_once('/').unwrap_or(("", path))
+}
+
+/// `from` and everything under it, each with its path under `to`.
+fn rebase<T>(map: &BTreeMap<String, T>, from: &str, to: &str) -> Vec<(String, String)> {
+    let prefix = format!("{from}/");
+
+    map.keys()
+        .filter_map(|path| match path.as_str() {
+            path if path == from => Some((path.to_string(), to.to_string())),
+            path => path
+                .strip_prefix(&prefix)
+                .map(|rest| (path.to_string(), format!("{to}/{rest}"))),
+        })
+        .collect()
+}
+
+/// `notes (conflict 2024-05-01 101500).txt` for `notes.txt`.
+fn conflict_copy(path: &str) -> String {
+    let (parent, name) = split(path);
+    let stamp = chrono::Local::now().format("%Y-%m-%d %H%M%S");
+    let name = match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => {
+            format!("{stem} (conflict {stamp}).{extension}")
+        }
+        _ => format!("{name} (conflict {stamp})"),
+    };
+
+    match segments(parent).is_empty() {
+        true => name,
+        false => format!("{parent}/{name}"),
+    }
+}
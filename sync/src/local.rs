@@ -0,0 +1,117 @@
+//! The local side: the synced directory, hashed only where it changed.
+use std::{collections::BTreeMap, fs::Metadata, io::Read, path::Path, time::UNIX_EPOCH};
+
+use client::Result;
+use sha2::Digest;
+
+use crate::state::Record;
+
+/// Files the daemon keeps for itself in the synced directory start with
+/// this, they are never synced.
+pub const RESERVED_PREFIX: &str = ".hoodik-sync";
+
+/// A file or folder on disk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Local {
+    pub is_dir: bool,
+    pub size: u64,
+    /// Modification time in milliseconds.
+    pub modified: i64,
+    /// Content hash of files, `None` for folders.
+    pub sha256: Option<String>,
+}
+
+/// Everything under `root` by its `/` separated relative path. Files
+/// whose size and modification time match their record keep its hash.
+pub fn scan(root: &Path, known: &BTreeMap<String, Record>) -> Result<BTreeMap<String, Local>> {
+    let mut found = BTreeMap::new();
+    let mut pending = vec![(root.to_path_buf(), String::new())];
+
+    while let Some((dir, prefix)) = pending.pop() {
+        for child in std::fs::read_dir(&dir)? {
+            let child = child?;
+            let Some(name) = child.file_name().to_str().map(|name| name.to_string()) else {
+                log::warn!("Skipping {}, its name is not UTF-8", child.path().display());
+                continue;
+            };
+            if name.starts_with(RESERVED_PREFIX) {
+                continue;
+            }
+
+            let meta = std::fs::symlink_metadata(child.path())?;
+            if meta.file_type().is_symlink() {
+                log::debug!("Skipping the symlink {}", child.path().display());
+                continue;
+            }
+
+            let path = match prefix.is_empty() {
+                true => name,
+                false => format!("{prefix}/{name}"),
+            };
+
+            if meta.is_dir() {
+                pending.push((child.path(), path.clone()));
+                found.insert(path, stat(&meta, None));
+                continue;
+            }
+
+            let mut local = stat(&meta, None);
+            local.sha256 = match known.get(&path) {
+                Some(record) if record.size == local.size && record.modified == local.modified => {
+                    record.sha256.clone()
+                }
+                _ => Some(sha256(&child.path())?),
+            };
+            found.insert(path, local);
+        }
+    }
+
+    Ok(found)
+}
+
+/// The file or folder at `path` as it is now, hashed.
+pub fn read(path: &Path) -> Result<Local> {
+    let meta = std::fs::metadata(path)?;
+    let sha256 = match meta.is_dir() {
+        true => None,
+        false => Some(sha256(path)?),
+    };
+
+    Ok(stat(&meta, sha256))
+}
+
+fn stat(meta: &Metadata, sha256: Option<String>) -> Local {
+    let modified = meta
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|time| time.as_millis() as i64)
+        .unwrap_or_default();
+
+    Local {
+        is_dir: meta.is_dir(),
+        size: match meta.is_dir() {
+            true => 0,
+            false => meta.len(),
+        },
+        modified,
+        sha256,
+    }
+}
+
+/// Hex sha256 of a file, the same hash the server keeps for its content.
+pub fn sha256(path: &Path) -> Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = sha2::Sha256::new();
+    let mut buffer = vec![0; 1024 * 1024];
+
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+
+    Ok(hex::encode(hasher.finalize()))
+}
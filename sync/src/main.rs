@@ -0,0 +1,139 @@
+use std::{path::PathBuf, time::Duration};
+
+use clap::{value_parser, Arg, ArgAction, Command};
+use client::{KeyPair, Session};
+use sync::Syncer;
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    if std::env::var("RUST_LOG").is_err() {
+        std::env::set_var("RUST_LOG", "info");
+    }
+    env_logger::init();
+
+    let matches = Command::new("hoodik-sync")
+        .version(env!("CARGO_PKG_VERSION"))
+        .about("Keep a local directory and a Hoodik folder in sync, encrypting and decrypting on this machine")
+        .arg(
+            Arg::new("url")
+                .long("url")
+                .env("HOODIK_URL")
+                .help("Address of the Hoodik instance, for example https://hoodik.example.com")
+                .required(true),
+        )
+        .arg(
+            Arg::new("email")
+                .long("email")
+                .env("HOODIK_EMAIL")
+                .help("Email of the account, to sign in with the password"),
+        )
+        .arg(
+            Arg::new("password")
+                .long("password")
+                .env("HOODIK_PASSWORD")
+                .hide_env_values(true)
+                .help("Password of the account, it never leaves this machine"),
+        )
+        .arg(
+            Arg::new("otp")
+                .long("otp")
+                .env("HOODIK_OTP")
+                .help("Current two factor code, for accounts that have it enabled"),
+        )
+        .arg(
+            Arg::new("key")
+                .long("key")
+                .env("HOODIK_KEY_FILE")
+                .help("File with the account's private key, instead of the email and password")
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("local")
+                .help("Local directory to sync")
+                .value_parser(value_parser!(PathBuf))
+                .required(true),
+        )
+        .arg(
+            Arg::new("remote")
+                .help("Folder in the account to sync it with, created when missing")
+                .required(true),
+        )
+        .arg(
+            Arg::new("state")
+                .long("state")
+                .env("HOODIK_SYNC_STATE")
+                .help("State database, .hoodik-sync.db in the local directory by default")
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("interval")
+                .long("interval")
+                .env("HOODIK_SYNC_INTERVAL")
+                .help("Seconds between passes")
+                .value_parser(value_parser!(u64))
+                .default_value("30"),
+        )
+        .arg(
+            Arg::new("once")
+                .long("once")
+                .help("Run a single pass and exit")
+                .action(ArgAction::SetTrue),
+        )
+        .get_matches();
+
+    let url = matches.get_one::<String>("url").unwrap();
+    let local = matches.get_one::<PathBuf>("local").unwrap();
+    let remote = matches.get_one::<String>("remote").unwrap();
+    let interval = Duration::from_secs(*matches.get_one::<u64>("interval").unwrap());
+
+    let session = match (
+        matches.get_one::<PathBuf>("key"),
+        matches.get_one::<String>("email"),
+        matches.get_one::<String>("password"),
+    ) {
+        (Some(key), _, _) => {
+            async { Session::login_with_key(url, KeyPair::from_file(key)?).await }.await
+        }
+        (None, Some(email), Some(password)) => {
+            let otp = matches.get_one::<String>("otp").map(|otp| otp.as_str());
+
+            Session::login_with_password(url, email, password, otp).await
+        }
+        _ => {
+            log::error!("Pass --email and --password, or --key to sign in");
+            std::process::exit(1);
+        }
+    }
+    .unwrap_or_else(|e| {
+        log::error!("Failed to sign in to {url}: {e}");
+        std::process::exit(1);
+    });
+
+    let syncer = Syncer::new(
+        session,
+        local,
+        remote,
+        matches
+            .get_one::<PathBuf>("state")
+            .map(|state| state.as_path()),
+    )
+    .await
+    .unwrap_or_else(|e| {
+        log::error!("Failed to start syncing {}: {e}", local.display());
+        std::process::exit(1);
+    });
+
+    log::info!("Syncing {} with {remote} on {url}", local.display());
+
+    loop {
+        match syncer.run_once().await {
+            Ok(report) => log::debug!("Pass finished: {report:?}"),
+            Err(e) => log::error!("Pass failed: {e}"),
+        }
+
+        if matches.get_flag("once") {
+            break;
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
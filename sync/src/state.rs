@@ -0,0 +1,161 @@
+//! The state database: how every synced path looked on both sides the last
+//! time they agreed. A change is whatever differs from it, which is how a
+//! local delete is told apart from a new remote file.
+use std::{collections::BTreeMap, path::Path};
+
+use client::{Error, Result};
+use sea_orm::{ConnectionTrait, Database, DatabaseConnection, DbBackend, DbErr, Statement};
+
+/// One synced path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub remote_id: String,
+    pub is_dir: bool,
+    /// Content hash of files, `None` for folders.
+    pub sha256: Option<String>,
+    pub size: u64,
+    /// Local modification time in milliseconds, files whose size and time
+    /// did not move are not hashed again.
+    pub modified: i64,
+    /// Remote content version, it moves with every save of an editable file.
+    pub version: i32,
+}
+
+pub struct State {
+    db: DatabaseConnection,
+}
+
+impl State {
+    pub async fn open(path: &Path) -> Result<Self> {
+        let db = Database::connect(format!("sqlite://{}?mode=rwc", path.display()))
+            .await
+            .map_err(db_error)?;
+
+        db.execute_unprepared(
+            "CREATE TABLE IF NOT EXISTS entries (
+                path TEXT PRIMARY KEY NOT NULL,
+                remote_id TEXT NOT NULL,
+                is_dir INTEGER NOT NULL,
+                sha256 TEXT,
+                size BIGINT NOT NULL,
+                modified BIGINT NOT NULL,
+                version INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS settings (
+                key TEXT PRIMARY KEY NOT NULL,
+                value TEXT NOT NULL
+            );",
+        )
+        .await
+        .map_err(db_error)?;
+
+        Ok(Self { db })
+    }
+
+    /// Remember which remote folder this state belongs to, and refuse to
+    /// mix it up with another one.
+    pub async fn bind_remote(&self, remote_root: Option<&str>) -> Result<()> {
+        let remote_root = remote_root.unwrap_or_default();
+        let bound = self
+            .db
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "SELECT value FROM settings WHERE key = 'remote_root'",
+                [],
+            ))
+            .await
+            .map_err(db_error)?
+            .map(|row| row.try_get::<String>("", "value"))
+            .transpose()
+            .map_err(db_error)?;
+
+        match bound {
+            Some(bound) if bound != remote_root => Err(Error::Invalid(
+                "The state database belongs to another remote folder".to_string(),
+            )),
+            Some(_) => Ok(()),
+            None => self
+                .db
+                .execute(Statement::from_sql_and_values(
+                    DbBackend::Sqlite,
+                    "INSERT INTO settings (key, value) VALUES ('remote_root', $1)",
+                    [remote_root.into()],
+                ))
+                .await
+                .map(|_| ())
+                .map_err(db_error),
+        }
+    }
+
+    pub async fn load(&self) -> Result<BTreeMap<String, Record>> {
+        let rows = self
+            .db
+            .query_all(Statement::from_string(
+                DbBackend::Sqlite,
+                "SELECT path, remote_id, is_dir, sha256, size, modified, version FROM entries",
+            ))
+            .await
+            .map_err(db_error)?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok((
+                    row.try_get::<String>("", "path")?,
+                    Record {
+                        remote_id: row.try_get("", "remote_id")?,
+                        is_dir: row.try_get("", "is_dir")?,
+                        sha256: row.try_get("", "sha256")?,
+                        size: row.try_get::<i64>("", "size")? as u64,
+                        modified: row.try_get("", "modified")?,
+                        version: row.try_get("", "version")?,
+                    },
+                ))
+            })
+            .collect::<std::result::Result<_, DbErr>>()
+            .map_err(db_error)
+    }
+
+    pub async fn save(&self, path: &str, record: &Record) -> Result<()> {
+        self.db
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "INSERT INTO entries (path, remote_id, is_dir, sha256, size, modified, version)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                ON CONFLICT (path) DO UPDATE SET
+                    remote_id = excluded.remote_id,
+                    is_dir = excluded.is_dir,
+                    sha256 = excluded.sha256,
+                    size = excluded.size,
+                    modified = excluded.modified,
+                    version = excluded.version",
+                [
+                    path.into(),
+                    record.remote_id.clone().into(),
+                    record.is_dir.into(),
+                    record.sha256.clone().into(),
+                    (record.size as i64).into(),
+                    record.modified.into(),
+                    record.version.into(),
+                ],
+            ))
+            .await
+            .map(|_| ())
+            .map_err(db_error)
+    }
+
+    pub async fn remove(&self, path: &str) -> Result<()> {
+        self.db
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "DELETE FROM entries WHERE path = $1",
+                [path.into()],
+            ))
+            .await
+            .map(|_| ())
+            .map_err(db_error)
+    }
+}
+
+fn db_error(e: DbErr) -> Error {
+    Error::Io(format!("State database: {e}"))
+}
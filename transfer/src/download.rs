@@ -3,10 +3,10 @@ use crate::error::{Error, Result};
 use crate::platform::{HttpClient, ProgressReporter};
 use crate::types::{Auth, DownloadSource};
 use futures::future::LocalBoxFuture;
-use std::cell::RefCell;
-use std::str::FromStr;
 use futures::stream::{FuturesUnordered, StreamExt};
+use std::cell::RefCell;
 use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
 
 /// Minimum growth in received bytes between two progress emissions.
 ///
@@ -1,6 +1,5 @@
 pub mod checksum;
 pub mod config;
-pub(crate) mod upload_trace;
 pub mod download;
 pub mod error;
 pub mod platform;
@@ -8,6 +7,7 @@ pub mod tar;
 pub mod types;
 pub mod upload;
 pub mod upload_tar;
+pub(crate) mod upload_trace;
 
 // Re-export the primary public API for library consumers.
 pub use download::Downloader;
@@ -1,7 +1,7 @@
 use crate::error::{Error, HttpError, Result};
 use crate::platform::HttpClient;
-use futures::StreamExt;
 use crate::types::{Auth, ChunkResponse, DownloadSource, FileHashes};
+use futures::StreamExt;
 use std::collections::HashMap;
 
 /// Native HTTP client backed by `reqwest`.
@@ -19,7 +19,8 @@ impl NativeHttpClient {
         let mut stream = resp.bytes_stream();
 
         while let Some(piece) = stream.next().await {
-            let piece = piece.map_err(|e| Error::Io(format!("Failed to read response bytes: {e}")))?;
+            let piece =
+                piece.map_err(|e| Error::Io(format!("Failed to read response bytes: {e}")))?;
             data.extend_from_slice(&piece);
             on_bytes(data.len() as u64);
         }
@@ -35,7 +36,8 @@ impl NativeHttpClient {
         Ok(Self { client })
     }
 
-    fn auth_headers(auth: &Auth) -> reqwest::header::HeaderMap {
+    /// Headers carrying the session from `auth`.
+    pub fn auth_headers(auth: &Auth) -> reqwest::header::HeaderMap {
         let mut headers = reqwest::header::HeaderMap::new();
 
         if let Some(ref token) = auth.jwt_token {
@@ -102,10 +102,7 @@ pub fn extract_tar(archive: &[u8]) -> Result<Vec<TarEntry>> {
         }
 
         // Parse filename from bytes 0..100 (null-terminated).
-        let name_end = header[..100]
-            .iter()
-            .position(|&b| b == 0)
-            .unwrap_or(100);
+        let name_end = header[..100].iter().position(|&b| b == 0).unwrap_or(100);
         let name = std::str::from_utf8(&header[..name_end])
             .map_err(|e| Error::Io(format!("invalid tar entry name: {e}")))?
             .to_string();
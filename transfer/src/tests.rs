@@ -21,7 +21,7 @@ fn test_auth() -> Auth {
 
 fn test_key() -> Vec<u8> {
     // 32 bytes: first 16 = Ascon128a key, last 16 = nonce
-    b"test-encryption-key!test-nonce!!" .to_vec()
+    b"test-encryption-key!test-nonce!!".to_vec()
 }
 
 struct MockDataSource {
@@ -109,8 +109,7 @@ impl HttpClient for MockHttpClient {
         _checksum: &str,
         data: &[u8],
     ) -> Pin<Box<dyn std::future::Future<Output = Result<ChunkResponse>> + '_>> {
-        self.upload_call_count
-            .set(self.upload_call_count.get() + 1);
+        self.upload_call_count.set(self.upload_call_count.get() + 1);
 
         let scripted = self.scripted_upload_responses.borrow_mut().pop_front();
 
@@ -184,7 +183,11 @@ impl HttpClient for MockHttpClient {
 
         let entries: Vec<(String, Vec<u8>)> = indices
             .into_iter()
-            .filter_map(|idx| stored.get(&idx).map(|d| (format!("{:06}.enc", idx), d.clone())))
+            .filter_map(|idx| {
+                stored
+                    .get(&idx)
+                    .map(|d| (format!("{:06}.enc", idx), d.clone()))
+            })
             .collect();
         let archive = crate::tar::build_tar(&entries);
         Box::pin(async move {
@@ -357,9 +360,20 @@ async fn upload_small_file() {
     let http = MockHttpClient::new();
     let progress = MockProgressReporter::new();
 
-    let hashes = crate::upload::upload_file(&http, &source, &progress, &test_auth(), "f1", &test_key(), &[], UploadHashOptions::default(), None, cryptfns::cipher::DEFAULT)
-        .await
-        .unwrap();
+    let hashes = crate::upload::upload_file(
+        &http,
+        &source,
+        &progress,
+        &test_auth(),
+        "f1",
+        &test_key(),
+        &[],
+        UploadHashOptions::default(),
+        None,
+        cryptfns::cipher::DEFAULT,
+    )
+    .await
+    .unwrap();
 
     assert_eq!(http.upload_count(), 1);
     assert_eq!(http.stored_chunks.borrow().len(), 1);
@@ -382,9 +396,20 @@ async fn upload_multi_chunk() {
     let http = MockHttpClient::new();
     let progress = MockProgressReporter::new();
 
-    crate::upload::upload_file(&http, &source, &progress, &test_auth(), "f2", &test_key(), &[], UploadHashOptions::default(), None, cryptfns::cipher::DEFAULT)
-        .await
-        .unwrap();
+    crate::upload::upload_file(
+        &http,
+        &source,
+        &progress,
+        &test_auth(),
+        "f2",
+        &test_key(),
+        &[],
+        UploadHashOptions::default(),
+        None,
+        cryptfns::cipher::DEFAULT,
+    )
+    .await
+    .unwrap();
 
     assert_eq!(http.upload_count(), 2);
     assert_eq!(http.stored_chunks.borrow().len(), 2);
@@ -450,9 +475,20 @@ async fn upload_checksum_retry_succeeds() {
         validation: Some(validation),
     }));
 
-    crate::upload::upload_file(&http, &source, &progress, &test_auth(), "f4", &test_key(), &[], UploadHashOptions::default(), None, cryptfns::cipher::DEFAULT)
-        .await
-        .unwrap();
+    crate::upload::upload_file(
+        &http,
+        &source,
+        &progress,
+        &test_auth(),
+        "f4",
+        &test_key(),
+        &[],
+        UploadHashOptions::default(),
+        None,
+        cryptfns::cipher::DEFAULT,
+    )
+    .await
+    .unwrap();
 
     // First call failed, second succeeded
     assert_eq!(http.upload_count(), 2);
@@ -480,9 +516,19 @@ async fn upload_checksum_exhausts_retries() {
         }));
     }
 
-    let result =
-        crate::upload::upload_file(&http, &source, &progress, &test_auth(), "f5", &test_key(), &[], UploadHashOptions::default(), None, cryptfns::cipher::DEFAULT)
-            .await;
+    let result = crate::upload::upload_file(
+        &http,
+        &source,
+        &progress,
+        &test_auth(),
+        "f5",
+        &test_key(),
+        &[],
+        UploadHashOptions::default(),
+        None,
+        cryptfns::cipher::DEFAULT,
+    )
+    .await;
 
     assert!(result.is_err());
     assert!(matches!(result.unwrap_err(), Error::Http(_)));
@@ -508,9 +554,20 @@ async fn upload_chunk_already_exists_is_not_an_error() {
         validation: Some(validation),
     }));
 
-    crate::upload::upload_file(&http, &source, &progress, &test_auth(), "f6", &test_key(), &[], UploadHashOptions::default(), None, cryptfns::cipher::DEFAULT)
-        .await
-        .unwrap();
+    crate::upload::upload_file(
+        &http,
+        &source,
+        &progress,
+        &test_auth(),
+        "f6",
+        &test_key(),
+        &[],
+        UploadHashOptions::default(),
+        None,
+        cryptfns::cipher::DEFAULT,
+    )
+    .await
+    .unwrap();
 
     assert_eq!(http.upload_count(), 1);
     assert!(matches!(
@@ -526,9 +583,19 @@ async fn upload_cancelled_before_any_work() {
     let http = MockHttpClient::new();
     let progress = MockProgressReporter::cancelling_immediately();
 
-    let result =
-        crate::upload::upload_file(&http, &source, &progress, &test_auth(), "f7", &test_key(), &[], UploadHashOptions::default(), None, cryptfns::cipher::DEFAULT)
-            .await;
+    let result = crate::upload::upload_file(
+        &http,
+        &source,
+        &progress,
+        &test_auth(),
+        "f7",
+        &test_key(),
+        &[],
+        UploadHashOptions::default(),
+        None,
+        cryptfns::cipher::DEFAULT,
+    )
+    .await;
 
     assert!(matches!(result, Err(Error::Cancelled)));
     assert_eq!(http.upload_count(), 0);
@@ -542,9 +609,20 @@ async fn upload_empty_file() {
 
     // Zero-byte file still produces 1 chunk. Ascon128a adds a 16-byte auth tag,
     // so the encrypted output is non-empty and the upload succeeds.
-    crate::upload::upload_file(&http, &source, &progress, &test_auth(), "f8", &test_key(), &[], UploadHashOptions::default(), None, cryptfns::cipher::DEFAULT)
-        .await
-        .unwrap();
+    crate::upload::upload_file(
+        &http,
+        &source,
+        &progress,
+        &test_auth(),
+        "f8",
+        &test_key(),
+        &[],
+        UploadHashOptions::default(),
+        None,
+        cryptfns::cipher::DEFAULT,
+    )
+    .await
+    .unwrap();
 
     assert_eq!(http.upload_count(), 1);
 }
@@ -737,9 +815,20 @@ async fn download_multi_chunk_ordering() {
     let http = MockHttpClient::new();
     let up = MockProgressReporter::new();
 
-    crate::upload::upload_file(&http, &source, &up, &test_auth(), "ord", &test_key(), &[], UploadHashOptions::default(), None, cryptfns::cipher::DEFAULT)
-        .await
-        .unwrap();
+    crate::upload::upload_file(
+        &http,
+        &source,
+        &up,
+        &test_auth(),
+        "ord",
+        &test_key(),
+        &[],
+        UploadHashOptions::default(),
+        None,
+        cryptfns::cipher::DEFAULT,
+    )
+    .await
+    .unwrap();
 
     let dl = MockProgressReporter::new();
     let chunk_count = compute_chunk_count(data.len() as u64);
@@ -772,9 +861,20 @@ async fn upload_multi_chunk_uses_distinct_nonces() {
     let http = MockHttpClient::new();
     let progress = MockProgressReporter::new();
 
-    crate::upload::upload_file(&http, &source, &progress, &test_auth(), "nonce", &test_key(), &[], UploadHashOptions::default(), None, cryptfns::cipher::DEFAULT)
-        .await
-        .unwrap();
+    crate::upload::upload_file(
+        &http,
+        &source,
+        &progress,
+        &test_auth(),
+        "nonce",
+        &test_key(),
+        &[],
+        UploadHashOptions::default(),
+        None,
+        cryptfns::cipher::DEFAULT,
+    )
+    .await
+    .unwrap();
 
     let stored = http.stored_chunks.borrow();
     assert_ne!(stored[&0], stored[&1]);
@@ -826,9 +926,20 @@ async fn upload_single_chunk_matches_legacy_ciphertext() {
     let http = MockHttpClient::new();
     let progress = MockProgressReporter::new();
 
-    crate::upload::upload_file(&http, &source, &progress, &test_auth(), "ident", &test_key(), &[], UploadHashOptions::default(), None, cryptfns::cipher::DEFAULT)
-        .await
-        .unwrap();
+    crate::upload::upload_file(
+        &http,
+        &source,
+        &progress,
+        &test_auth(),
+        "ident",
+        &test_key(),
+        &[],
+        UploadHashOptions::default(),
+        None,
+        cryptfns::cipher::DEFAULT,
+    )
+    .await
+    .unwrap();
 
     let cipher = cryptfns::cipher::Cipher::from_str(cryptfns::cipher::DEFAULT).unwrap();
     let legacy = cipher.encrypt(test_key(), data).unwrap();
@@ -856,10 +967,7 @@ fn byte_tally_reports_from_partial_chunks_and_survives_restart() {
     assert_eq!(tally.record(1, 10 * 1024, total), None);
 
     // The next report reflects the corrected sum (crossing the delta again).
-    assert_eq!(
-        tally.record(0, 700 * 1024, total),
-        Some(710 * 1024)
-    );
+    assert_eq!(tally.record(0, 700 * 1024, total), Some(710 * 1024));
 }
 
 #[test]
@@ -883,9 +991,20 @@ async fn download_reports_progress_before_first_chunk_completes() {
     let http = MockHttpClient::new();
     let up = MockProgressReporter::new();
 
-    crate::upload::upload_file(&http, &source, &up, &test_auth(), "stream", &test_key(), &[], UploadHashOptions::default(), None, cryptfns::cipher::DEFAULT)
-        .await
-        .unwrap();
+    crate::upload::upload_file(
+        &http,
+        &source,
+        &up,
+        &test_auth(),
+        "stream",
+        &test_key(),
+        &[],
+        UploadHashOptions::default(),
+        None,
+        cryptfns::cipher::DEFAULT,
+    )
+    .await
+    .unwrap();
 
     let dl = MockProgressReporter::new();
     let chunk_count = compute_chunk_count(data.len() as u64);
@@ -915,7 +1034,9 @@ async fn download_reports_progress_before_first_chunk_completes() {
     // The mock streams each chunk in two reads, so at least one report
     // lands mid-chunk — strictly between zero and the full size.
     assert!(
-        byte_events.iter().any(|(b, _)| *b > 0 && *b < data.len() as u64),
+        byte_events
+            .iter()
+            .any(|(b, _)| *b > 0 && *b < data.len() as u64),
         "expected a mid-transfer progress report, got {byte_events:?}"
     );
 
@@ -939,9 +1060,20 @@ async fn download_streaming_emits_ordered_chunks() {
     let http = MockHttpClient::new();
     let up = MockProgressReporter::new();
 
-    crate::upload::upload_file(&http, &source, &up, &test_auth(), "sink", &test_key(), &[], UploadHashOptions::default(), None, cryptfns::cipher::DEFAULT)
-        .await
-        .unwrap();
+    crate::upload::upload_file(
+        &http,
+        &source,
+        &up,
+        &test_auth(),
+        "sink",
+        &test_key(),
+        &[],
+        UploadHashOptions::default(),
+        None,
+        cryptfns::cipher::DEFAULT,
+    )
+    .await
+    .unwrap();
 
     let dl = MockProgressReporter::new();
     let chunk_count = compute_chunk_count(data.len() as u64);
@@ -1127,7 +1259,9 @@ async fn upload_tar_roundtrip_via_mock() {
     .await
     .unwrap();
 
-    let captured = http.take_last_tar_body().expect("mock must record the tar body");
+    let captured = http
+        .take_last_tar_body()
+        .expect("mock must record the tar body");
     let parsed = crate::tar::extract_tar(&captured).expect("captured body must parse as tar");
     assert_eq!(parsed.len(), entries.len());
     for (expected, actual) in entries.iter().zip(parsed.iter()) {
@@ -1166,12 +1300,18 @@ async fn upload_tar_progress_reported() {
             _ => {}
         }
     }
-    assert!(!downloaded_bytes.is_empty(), "expected at least one progress tick");
+    assert!(
+        !downloaded_bytes.is_empty(),
+        "expected at least one progress tick"
+    );
     assert!(
         downloaded_bytes.iter().all(|&b| b > 0),
         "progress must report non-zero bytes"
     );
-    assert!(saw_complete, "on_complete must fire once all chunks are stored");
+    assert!(
+        saw_complete,
+        "on_complete must fire once all chunks are stored"
+    );
 }
 
 #[tokio::test(flavor = "current_thread")]
@@ -1209,13 +1349,38 @@ fn download_source_maps_route_and_method() {
         "https://h.example/api/storage/f-1?chunk=3"
     );
 
-    let link = DownloadSource::PublicLink("l-1");
+    let link = DownloadSource::PublicLink {
+        link_id: "l-1",
+        file_id: None,
+        access_token: None,
+    };
     assert_eq!(link.id(), "l-1");
     assert_eq!(link.method(), "POST");
     assert_eq!(
         link.chunk_url("https://h.example", 0),
         "https://h.example/api/links/l-1?chunk=0"
     );
+
+    let unlocked = DownloadSource::PublicLink {
+        link_id: "l-1",
+        file_id: None,
+        access_token: Some("99.abc"),
+    };
+    assert_eq!(
+        unlocked.chunk_url("https://h.example", 2),
+        "https://h.example/api/links/l-1?chunk=2&access_token=99.abc"
+    );
+
+    let folder = DownloadSource::PublicLink {
+        link_id: "l-1",
+        file_id: Some("f-2"),
+        access_token: Some("99.abc"),
+    };
+    assert_eq!(folder.id(), "f-2");
+    assert_eq!(
+        folder.chunk_url("https://h.example", 1),
+        "https://h.example/api/links/l-1?chunk=1&file_id=f-2&access_token=99.abc"
+    );
 }
 
 #[tokio::test(flavor = "current_thread")]
@@ -1246,7 +1411,11 @@ async fn public_link_download_hits_link_route_and_roundtrips() {
         &http,
         &dl_progress,
         &test_auth(),
-        DownloadSource::PublicLink("link-uuid"),
+        DownloadSource::PublicLink {
+            link_id: "link-uuid",
+            file_id: None,
+            access_token: None,
+        },
         original.len() as u64,
         chunk_count,
         &test_key(),
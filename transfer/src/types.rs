@@ -27,14 +27,24 @@ pub enum DownloadSource<'a> {
     /// A file owned by or shared with the authenticated user.
     Storage(&'a str),
     /// A file behind a public share link.
-    PublicLink(&'a str),
+    PublicLink {
+        link_id: &'a str,
+        /// The file to fetch when the link is for a folder.
+        file_id: Option<&'a str>,
+        /// From unlocking the link when it has a password.
+        access_token: Option<&'a str>,
+    },
 }
 
 impl<'a> DownloadSource<'a> {
-    /// The id progress events are keyed by: file id or link id.
+    /// The id progress events are keyed by: file id, or link id for a link
+    /// to a single file.
     pub fn id(&self) -> &'a str {
         match self {
-            Self::Storage(id) | Self::PublicLink(id) => id,
+            Self::Storage(id) => id,
+            Self::PublicLink {
+                link_id, file_id, ..
+            } => file_id.unwrap_or(link_id),
         }
     }
 
@@ -42,7 +52,20 @@ impl<'a> DownloadSource<'a> {
     pub fn chunk_url(&self, base_url: &str, chunk: u64) -> String {
         match self {
             Self::Storage(id) => format!("{base_url}/api/storage/{id}?chunk={chunk}"),
-            Self::PublicLink(id) => format!("{base_url}/api/links/{id}?chunk={chunk}"),
+            Self::PublicLink {
+                link_id,
+                file_id,
+                access_token,
+            } => {
+                let mut url = format!("{base_url}/api/links/{link_id}?chunk={chunk}");
+                if let Some(file_id) = file_id {
+                    url.push_str(&format!("&file_id={file_id}"));
+                }
+                if let Some(token) = access_token {
+                    url.push_str(&format!("&access_token={token}"));
+                }
+                url
+            }
         }
     }
 
@@ -50,7 +73,7 @@ impl<'a> DownloadSource<'a> {
     pub fn method(&self) -> &'static str {
         match self {
             Self::Storage(_) => "GET",
-            Self::PublicLink(_) => "POST",
+            Self::PublicLink { .. } => "POST",
         }
     }
 }
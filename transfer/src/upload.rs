@@ -1,7 +1,7 @@
 use crate::checksum;
 use crate::config::{
-    CHUNK_SIZE_BYTES, CONCURRENT_UPLOADS_IN_FLIGHT, ENCRYPTED_CHUNKS_BUFFER_LIMIT,
-    MAX_UPLOAD_RETRIES, UploadHashOptions,
+    UploadHashOptions, CHUNK_SIZE_BYTES, CONCURRENT_UPLOADS_IN_FLIGHT,
+    ENCRYPTED_CHUNKS_BUFFER_LIMIT, MAX_UPLOAD_RETRIES,
 };
 use crate::error::{Error, HttpError, Result};
 use crate::platform::{DataSource, HttpClient, ProgressReporter};
@@ -9,9 +9,9 @@ use crate::types::{Auth, FileHashes};
 use crate::upload_trace;
 use digest::Digest;
 use futures::future::LocalBoxFuture;
-use std::str::FromStr;
 use futures::stream::{FuturesUnordered, StreamExt};
 use std::collections::{HashSet, VecDeque};
+use std::str::FromStr;
 
 /// Called for every plaintext chunk in order when SHA-256 (and optional other hashes) are
 /// offloaded to a separate execution context (e.g. a dedicated Web Worker).
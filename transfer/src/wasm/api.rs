@@ -158,9 +158,9 @@ impl TransferUploader {
         // so the hash worker has had the full upload duration to finish reading the file).
         // Return the resolved sha256 so the caller can persist it to the server.
         if let Some(promise) = external_hash {
-            let sha256_js = JsFuture::from(promise)
-                .await
-                .map_err(|e| JsValue::from_str(&format!("external hash promise rejected: {e:?}")))?;
+            let sha256_js = JsFuture::from(promise).await.map_err(|e| {
+                JsValue::from_str(&format!("external hash promise rejected: {e:?}"))
+            })?;
             let sha256 = sha256_js.as_string().unwrap_or_default();
             let final_hashes = FileHashes { sha256, ..hashes };
             return to_value(&final_hashes).map_err(|e| JsValue::from_str(&format!("{e}")));
@@ -196,11 +196,24 @@ pub struct TransferDownloader {
     cipher: String,
     /// Chunks come from the anonymous public-link route instead of storage.
     public_link: bool,
+    /// Access token from unlocking a password-protected public link.
+    link_access_token: Option<String>,
+    /// File to fetch through a public link to a folder.
+    link_file_id: Option<String>,
 }
 
-fn source_of(id: &str, public_link: bool) -> DownloadSource<'_> {
+fn source_of<'a>(
+    id: &'a str,
+    public_link: bool,
+    link_file_id: Option<&'a str>,
+    link_access_token: Option<&'a str>,
+) -> DownloadSource<'a> {
     if public_link {
-        DownloadSource::PublicLink(id)
+        DownloadSource::PublicLink {
+            link_id: id,
+            file_id: link_file_id,
+            access_token: link_access_token,
+        }
     } else {
         DownloadSource::Storage(id)
     }
@@ -240,6 +253,8 @@ impl TransferDownloader {
             decryption_key,
             cipher: cryptfns::cipher::DEFAULT.to_string(),
             public_link: false,
+            link_access_token: None,
+            link_file_id: None,
         }
     }
 
@@ -270,9 +285,25 @@ impl TransferDownloader {
             decryption_key,
             cipher: cryptfns::cipher::DEFAULT.to_string(),
             public_link: true,
+            link_access_token: None,
+            link_file_id: None,
         }
     }
 
+    /// Set the access token from `POST /api/links/{link_id}/unlock`, needed
+    /// when the public link has a password. Must be called before [`download`].
+    #[wasm_bindgen(js_name = "set_link_access_token")]
+    pub fn set_link_access_token(&mut self, access_token: String) {
+        self.link_access_token = Some(access_token);
+    }
+
+    /// Set the file to fetch when the public link is for a folder. Must be
+    /// called before [`download`].
+    #[wasm_bindgen(js_name = "set_link_file_id")]
+    pub fn set_link_file_id(&mut self, file_id: String) {
+        self.link_file_id = Some(file_id);
+    }
+
     /// Set the cipher used to decrypt each chunk.
     /// Accepts `"ascon128a"` (default) or `"chacha20poly1305"`.
     /// Must be called before [`download`].
@@ -302,6 +333,8 @@ impl TransferDownloader {
         let decryption_key = self.decryption_key.clone();
         let cipher = self.cipher.clone();
         let public_link = self.public_link;
+        let link_access_token = self.link_access_token.clone();
+        let link_file_id = self.link_file_id.clone();
 
         let http = WasmHttpClient::new();
         let reporter = JsProgressReporter::new(on_progress, is_cancelled);
@@ -310,7 +343,12 @@ impl TransferDownloader {
             &http,
             &reporter,
             &auth,
-            source_of(&file_id, public_link),
+            source_of(
+                &file_id,
+                public_link,
+                link_file_id.as_deref(),
+                link_access_token.as_deref(),
+            ),
             file_size,
             chunk_count,
             &decryption_key,
@@ -341,6 +379,8 @@ impl TransferDownloader {
         let decryption_key = self.decryption_key.clone();
         let cipher = self.cipher.clone();
         let public_link = self.public_link;
+        let link_access_token = self.link_access_token.clone();
+        let link_file_id = self.link_file_id.clone();
 
         let http = WasmHttpClient::new();
         let reporter = JsProgressReporter::new(on_progress, is_cancelled);
@@ -349,7 +389,12 @@ impl TransferDownloader {
             &http,
             &reporter,
             &auth,
-            source_of(&file_id, public_link),
+            source_of(
+                &file_id,
+                public_link,
+                link_file_id.as_deref(),
+                link_access_token.as_deref(),
+            ),
             file_size,
             chunk_count,
             &decryption_key,
@@ -381,13 +426,20 @@ impl TransferDownloader {
         let decryption_key = self.decryption_key.clone();
         let cipher = self.cipher.clone();
         let public_link = self.public_link;
+        let link_access_token = self.link_access_token.clone();
+        let link_file_id = self.link_file_id.clone();
 
         let http = WasmHttpClient::new();
 
         let (_, result) = crate::download::fetch_and_decrypt(
             &http,
             &auth,
-            source_of(&file_id, public_link),
+            source_of(
+                &file_id,
+                public_link,
+                link_file_id.as_deref(),
+                link_access_token.as_deref(),
+            ),
             chunk_index as u64,
             &decryption_key,
             &cipher,
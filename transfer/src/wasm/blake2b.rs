@@ -25,4 +25,3 @@ impl TransferBlake2b512 {
         hex::encode(self.inner.finalize().as_slice())
     }
 }
-
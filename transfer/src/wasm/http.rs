@@ -5,7 +5,7 @@ use std::collections::HashMap;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
 use wasm_bindgen_futures::JsFuture;
-use web_sys::{Headers, RequestInit, RequestMode, RequestCredentials, Response};
+use web_sys::{Headers, RequestCredentials, RequestInit, RequestMode, Response};
 
 pub struct WasmHttpClient;
 
@@ -29,7 +29,6 @@ impl WasmHttpClient {
         Ok(headers)
     }
 
-
     /// Collect a response body by pulling its `ReadableStream`, reporting the
     /// running byte count after every read so download progress moves with
     /// the wire instead of jumping when the body completes. Bodies without a
@@ -37,7 +36,9 @@ impl WasmHttpClient {
     /// read with a single final report.
     async fn read_streaming(resp: &Response, on_bytes: &dyn Fn(u64)) -> Result<Vec<u8>> {
         let Some(body) = resp.body() else {
-            let ab_promise = resp.array_buffer().map_err(|e| Error::Io(format!("{e:?}")))?;
+            let ab_promise = resp
+                .array_buffer()
+                .map_err(|e| Error::Io(format!("{e:?}")))?;
             let array_buffer = JsFuture::from(ab_promise)
                 .await
                 .map_err(|e| Error::Io(format!("{e:?}")))?;
@@ -216,10 +217,7 @@ impl HttpClient for WasmHttpClient {
         Box::pin(async move {
             let headers = Self::build_headers(&auth)?;
 
-            let url = format!(
-                "{}/api/storage/{}?format=tar",
-                auth.base_url, file_id
-            );
+            let url = format!("{}/api/storage/{}?format=tar", auth.base_url, file_id);
 
             let opts = RequestInit::new();
             opts.set_method("GET");
@@ -263,10 +261,7 @@ impl HttpClient for WasmHttpClient {
                 .set("Content-Type", "application/x-tar")
                 .map_err(|e| Error::Io(format!("{e:?}")))?;
 
-            let url = format!(
-                "{}/api/storage/{}?format=tar",
-                auth.base_url, file_id
-            );
+            let url = format!("{}/api/storage/{}?format=tar", auth.base_url, file_id);
 
             let body = js_sys::Uint8Array::from(tar_body.as_slice());
 
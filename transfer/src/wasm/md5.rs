@@ -25,4 +25,3 @@ impl TransferMd5 {
         hex::encode(self.inner.finalize().as_slice())
     }
 }
-
@@ -1,14 +1,13 @@
 pub mod api;
+mod blake2b;
 mod http;
-mod progress;
 mod md5;
+mod progress;
 mod sha1;
 mod sha256;
-mod blake2b;
 mod source;
 
 pub use blake2b::TransferBlake2b512;
 pub use md5::TransferMd5;
 pub use sha1::TransferSha1;
 pub use sha256::TransferSha256;
-
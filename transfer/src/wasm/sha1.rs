@@ -25,4 +25,3 @@ impl TransferSha1 {
         hex::encode(self.inner.finalize().as_slice())
     }
 }
-
@@ -30,11 +30,9 @@ impl DataSource for FileSource {
                 .slice_with_f64_and_f64(start, end)
                 .map_err(|e| Error::Io(format!("File.slice failed: {e:?}")))?;
 
-            let array_buffer = JsFuture::from(
-                blob.array_buffer(),
-            )
-            .await
-            .map_err(|e| Error::Io(format!("arrayBuffer failed: {e:?}")))?;
+            let array_buffer = JsFuture::from(blob.array_buffer())
+                .await
+                .map_err(|e| Error::Io(format!("arrayBuffer failed: {e:?}")))?;
 
             let uint8 = js_sys::Uint8Array::new(&array_buffer);
             Ok(uint8.to_vec())
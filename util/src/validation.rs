@@ -13,19 +13,18 @@ macro_rules! rule_required_if {
             }
         })
     };
-    () => {
-        
-    };
+    () => {};
 }
 
 /// Validate password to be certain strength according to zxcvbn
 pub fn validate_password(password: &str) -> bool {
-    let entropy = match zxcvbn::zxcvbn(password, &[]) {
-        Ok(e) => e,
-        Err(_) => return false,
-    };
+    password_score(password).is_some_and(|score| score > 3)
+}
 
-    entropy.score() > 3
+/// Score of the password on the zxcvbn 0-4 scale, `None` when it can't be
+/// scored at all (an empty password)
+pub fn password_score(password: &str) -> Option<u8> {
+    zxcvbn::zxcvbn(password, &[]).ok().map(|e| e.score())
 }
 
 /// Validate the provided token with the provided secret that it matches
@@ -22,19 +22,19 @@ async function openFolderSharingModal(
 }
 
 test.describe('Sharing modal: folder tabs', () => {
-  test('SharingModal on a folder collapses the tab strip', async ({ page }) => {
-    // Folders never expose a Public link tab; with only one surface to
-    // show, the tab strip collapses and the People body renders directly.
+  test('SharingModal on a folder shows the Public link tab', async ({ page }) => {
+    // A folder link shares everything under the folder, so owned folders
+    // get both tabs like files do.
     await createUser(page, randomEmail(), randomPassword())
     await page.locator('[name="create-dir"]').click()
-    await page.locator('#name').fill('no-link-folder')
+    await page.locator('#name').fill('link-folder')
     await page.getByRole('button', { name: 'Create', exact: true }).click()
-    await expect(page.getByTestId('file-row-no-link-folder')).toBeVisible({ timeout: 15_000 })
+    await expect(page.getByTestId('file-row-link-folder')).toBeVisible({ timeout: 15_000 })
 
-    await openFolderSharingModal(page, 'no-link-folder')
+    await openFolderSharingModal(page, 'link-folder')
     await expect(page.getByTestId('share-dialog-submit')).toBeVisible()
-    await expect(page.getByTestId('sharing-modal-tab-link')).toHaveCount(0)
-    await expect(page.getByTestId('sharing-modal-tab-people')).toHaveCount(0)
+    await expect(page.getByTestId('sharing-modal-tab-people')).toBeVisible()
+    await expect(page.getByTestId('sharing-modal-tab-link')).toBeVisible()
   })
 
   test('SharingModal on a file still shows the Public link tab', async ({ page }) => {
@@ -11,6 +11,7 @@ import * as cryptfns from '!/cryptfns'
 import * as opaque from '!/cryptfns/opaque'
 import * as envelope from '!/cryptfns/envelope'
 import { encodeBundle } from '!/auth/bundle'
+import { passwordScore } from '@/utils/password'
 
 interface OpaqueRegisterStartResponse {
   registration_response: string
@@ -79,6 +80,7 @@ export async function changePasswordV2(
       signature: string
       issued_at: number
       token: string | null
+      password_score: number
     },
     void
   >('/api/auth/pake/register/finish', undefined, {
@@ -86,7 +88,8 @@ export async function changePasswordV2(
     encrypted_private_key,
     signature,
     issued_at,
-    token: token || null
+    token: token || null,
+    password_score: passwordScore(newPassword)
   })
 }
 
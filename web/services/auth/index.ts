@@ -69,7 +69,7 @@ export async function ensureAuthenticated(
         await store.refresh(crypto)
 
         if (crypto.keypair.input) {
-          return
+          return ensureEnrolled(router, route)
         }
       } catch (e) {
         return bounce(router, route, crypto)
@@ -82,6 +82,25 @@ export async function ensureAuthenticated(
 
     return bounce(router, route, crypto)
   }
+
+  return ensureEnrolled(router, route)
+}
+
+/**
+ * Keep a session the security policy confines to enrollment on the pages
+ * that fulfill it; the server refuses everything else with
+ * `403 enrollment_required` until the session is refreshed.
+ */
+export async function ensureEnrolled(
+  router: Router,
+  route: RouteLocationNormalizedLoaded
+): Promise<void | NavigationFailure> {
+  const store = login.store()
+  const required = store.authenticated?.enrollment?.required || []
+
+  if (required.length && !['account-enroll', 'account-change-password'].includes(String(route.name))) {
+    return router.push({ name: 'account-enroll', replace: true })
+  }
 }
 
 /**
@@ -15,6 +15,7 @@ import type { Authenticated, Credentials, CryptoStore, KeyPair, PrivateKeyLogin,
 import { useRouter } from 'vue-router'
 import { notify } from '@kyvg/vue3-notification'
 import * as logger from '!/logger'
+import { passwordScore } from '@/utils/password'
 
 export interface LoginStartResponse {
   method: 'password' | 'opaque'
@@ -804,7 +805,8 @@ export const store = defineStore('login', () => {
       transition_issued_at: issuedAt,
       opaque_registration_upload: regFinish.message,
       encrypted_private_key: env,
-      audit_event_signature: auditSignature
+      audit_event_signature: auditSignature,
+      password_score: passwordScore(password)
     }
 
     const completeResp = await Api.post('/api/auth/migration/complete', undefined, completeBody)
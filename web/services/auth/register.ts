@@ -8,6 +8,7 @@ import { encodeBundle } from './bundle'
 import { default as Api, type InnerValidationErrors } from '../api'
 import type { Authenticated, CreateUser, CryptoStore, KeyPair, LoginStore, User } from 'types'
 import type { RouteLocation } from 'vue-router'
+import { passwordScore } from '@/utils/password'
 
 interface OpaqueSignupStartRequest {
   email: string
@@ -174,6 +175,7 @@ export const store = defineStore('register', () => {
       key_type: 'curve25519',
       encrypted_private_key: env,
       opaque_registration_upload: regFinish.message,
+      password_score: passwordScore(password),
       secret: data.secret,
       token: data.token,
       invitation_id: data.invitation_id
@@ -216,6 +218,16 @@ export const store = defineStore('register', () => {
     return response.body as User
   }
 
+  /**
+   * End every session of the user, from the "this wasn't me" link
+   * of a new device alert
+   * @throws
+   */
+  async function killSessions(token: string): Promise<void> {
+    const action = 'kill-sessions'
+    await Api.post(`/api/auth/action/${action}/${token}`)
+  }
+
   /**
    * Attempt to resend email verification
    */
@@ -229,6 +241,7 @@ export const store = defineStore('register', () => {
     clear,
     register,
     verifyEmail,
+    killSessions,
     resendActivation,
     preload,
 
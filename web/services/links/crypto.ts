@@ -1,5 +1,11 @@
 import * as cryptfns from '!/cryptfns'
-import type { AppLink, EncryptedAppLink, KeyPair } from 'types'
+import type {
+  AppLink,
+  AppLinkFile,
+  EncryptedAppLink,
+  EncryptedAppLinkFile,
+  KeyPair
+} from 'types'
 
 /**
  * Link metadata is symmetric-encrypted under the link key with Ascon-128a —
@@ -82,12 +88,39 @@ export async function decryptLink(link: EncryptedAppLink, link_key_hex: string):
     key = cryptfns.uint8.fromHex(fileKeyHex)
   }
 
+  const files = link.files
+    ? await Promise.all(link.files.map((file) => decryptLinkFile(file, link_key)))
+    : undefined
+
   return {
     ...link,
     name,
     thumbnail,
     link_key,
     link_key_hex,
-    key
+    key,
+    files
   }
 }
+
+/**
+ * Decrypt the name and key of a file under a linked folder
+ */
+export async function decryptLinkFile(
+  file: EncryptedAppLinkFile,
+  link_key: Uint8Array
+): Promise<AppLinkFile> {
+  const name = await cryptfns.cipher.decryptString(LINK_CIPHER, file.encrypted_name, link_key)
+
+  let key: Uint8Array | undefined
+  if (file.encrypted_file_key) {
+    const fileKeyHex = await cryptfns.cipher.decryptString(
+      LINK_CIPHER,
+      file.encrypted_file_key,
+      link_key
+    )
+    key = cryptfns.uint8.fromHex(fileKeyHex)
+  }
+
+  return { ...file, name, key }
+}
@@ -100,10 +100,15 @@ export const store = defineStore('links', () => {
   }
 
   /**
-   * Share a link with a publicly accessible link.
+   * Share a link with a publicly accessible link, optionally limited to a
+   * number of downloads and protected with an extra password.
    */
-  async function create(link: AppFile, kp: KeyPair): Promise<AppLink> {
-    const createLink = await meta.createLinkFromFile(link, kp)
+  async function create(
+    link: AppFile,
+    kp: KeyPair,
+    restrictions?: Pick<CreateLink, 'max_downloads' | 'password'>
+  ): Promise<AppLink> {
+    const createLink = { ...(await meta.createLinkFromFile(link, kp)), ...restrictions }
 
     const response = await Api.post<CreateLink, EncryptedAppLink>(
       '/api/links',
@@ -255,16 +260,18 @@ export const store = defineStore('links', () => {
   /**
    * Get link from the store (as its owner)
    */
-  async function get(id: string, key: string): Promise<AppLink> {
+  async function get(id: string, key: string, accessToken?: string): Promise<AppLink> {
     const link = getItem(id)
 
-    if (link) {
+    // Listings leave out the files under a linked folder, only the metadata
+    // route lists them.
+    if (link && (link.file_mime !== 'dir' || link.files)) {
       return link
     }
 
-    const metadata = await meta.metadata(id, key)
+    const metadata = await meta.metadata(id, key, accessToken)
 
-    addItem(metadata)
+    upsertItem(metadata)
 
     return metadata
   }
@@ -1,11 +1,20 @@
 import * as cryptfns from '!/cryptfns'
 import * as crypto from './crypto'
 import * as storageMeta from '!/storage/meta'
+import { collectSubtree } from '!/shares/subtree'
 import Api from '!/api'
 import { CHUNK_SIZE_BYTES } from '!/constants'
 import { TransferDownloader } from 'transfer'
 
-import type { AppLink, CreateLink, EncryptedAppLink, KeyPair, AppFile } from 'types'
+import type {
+  AppLink,
+  AppLinkFile,
+  CreateLink,
+  CreateLinkFile,
+  EncryptedAppLink,
+  KeyPair,
+  AppFile
+} from 'types'
 
 /**
  * Load all the shared links for the user.
@@ -32,24 +41,34 @@ export function linkChunks(link: AppLink): number {
 }
 
 /**
- * Build a wasm downloader for a public link. Chunks come from the anonymous
- * link route; the content key was unwrapped from the link metadata with the
- * fragment key and never leaves the browser — the server only ever streams
- * ciphertext. Callers must `free()` the downloader.
+ * Build a wasm downloader for a public link, or for `file` under a linked
+ * folder. Chunks come from the anonymous link route; the content key was
+ * unwrapped from the link metadata with the fragment key and never leaves
+ * the browser — the server only ever streams ciphertext. Callers must
+ * `free()` the downloader.
  */
-function linkDownloader(link: AppLink): TransferDownloader {
-  if (!link.key) {
+function linkDownloader(link: AppLink, file?: AppLinkFile): TransferDownloader {
+  const key = file ? file.key : link.key
+  if (!key) {
     throw new Error('Cannot decrypt link content without the file key')
   }
 
+  const size = file ? file.size || 0 : link.file_size || 0
+
   const downloader = TransferDownloader.forPublicLink(
     link.id,
-    link.file_size || 0,
-    linkChunks(link),
+    size,
+    file ? Math.max(1, Math.ceil(size / CHUNK_SIZE_BYTES)) : linkChunks(link),
     new Api().toJson().apiUrl || '',
-    link.key
+    key
   )
-  downloader.set_cipher(link.file_cipher)
+  downloader.set_cipher(file ? file.cipher : link.file_cipher)
+  if (file) {
+    downloader.set_link_file_id(file.id)
+  }
+  if (link.access_token) {
+    downloader.set_link_access_token(link.access_token)
+  }
 
   return downloader
 }
@@ -61,9 +80,10 @@ function linkDownloader(link: AppLink): TransferDownloader {
  */
 export async function downloadAndDecrypt(
   link: AppLink,
-  onBytes?: (bytes: number) => void
+  onBytes?: (bytes: number) => void,
+  file?: AppLinkFile
 ): Promise<Uint8Array> {
-  const downloader = linkDownloader(link)
+  const downloader = linkDownloader(link, file)
 
   try {
     return await downloader.download((progressJson: string) => {
@@ -102,34 +122,54 @@ export async function downloadLinkChunk(
 }
 
 /**
- * Decrypt the link content client-side and trigger a browser save under the
- * name from the (client-decrypted) link metadata.
+ * Decrypt the link content, or `file` under a linked folder, client-side and
+ * trigger a browser save under the name from the (client-decrypted) link
+ * metadata.
  */
-export async function saveDecrypted(link: AppLink): Promise<void> {
-  const data = await downloadAndDecrypt(link)
+export async function saveDecrypted(link: AppLink, file?: AppLinkFile): Promise<void> {
+  const data = await downloadAndDecrypt(link, undefined, file)
 
-  const url = window.URL.createObjectURL(new Blob([data], { type: link.file_mime }))
+  const url = window.URL.createObjectURL(
+    new Blob([data], { type: file ? file.mime : link.file_mime })
+  )
   const anchor = document.createElement('a')
   anchor.href = url
-  anchor.download = link.name || 'download'
+  anchor.download = (file ? file.name : link.name) || 'download'
   anchor.click()
   window.URL.revokeObjectURL(url)
 }
 
+/**
+ * Thrown when a link has a password and no valid access token was given: the
+ * server withholds everything needed to decrypt it until it is unlocked.
+ */
+export class LinkLockedError extends Error {
+  constructor() {
+    super('This link is protected with a password')
+  }
+}
+
 /**
  * Load the link by its id and its metadata from the server.
  */
-export async function metadata(id: string, linkKey: string): Promise<AppLink> {
-  const link = await encryptedMetadata(id)
+export async function metadata(id: string, linkKey: string, accessToken?: string): Promise<AppLink> {
+  const link = await encryptedMetadata(id, accessToken)
+
+  if (link.has_password && !link.encrypted_name) {
+    throw new LinkLockedError()
+  }
 
-  return crypto.decryptLink(link, linkKey)
+  return { ...(await crypto.decryptLink(link, linkKey)), access_token: accessToken }
 }
 
 /**
  * Get the encrypted metadata in case we don't have a key
  */
-export async function encryptedMetadata(id: string): Promise<EncryptedAppLink> {
-  const response = await Api.get<EncryptedAppLink>(`/api/links/${id}/metadata`)
+export async function encryptedMetadata(id: string, accessToken?: string): Promise<EncryptedAppLink> {
+  const response = await Api.get<EncryptedAppLink>(
+    `/api/links/${id}/metadata`,
+    accessToken ? { access_token: accessToken } : undefined
+  )
 
   if (!response.body) {
     throw new Error('Failed to get link')
@@ -139,10 +179,72 @@ export async function encryptedMetadata(id: string): Promise<EncryptedAppLink> {
 }
 
 /**
- * Convert unencrypted app file into a encrypted create link construct
+ * Exchange the password of a protected link for an access token. Sent
+ * without the session refresh retry: a wrong password answers 401, and
+ * repeating it would charge the rate limit twice.
+ */
+export async function unlock(id: string, password: string): Promise<string> {
+  const response = await new Api().make<{ password: string }, { access_token: string }>(
+    'post',
+    `/api/links/${id}/unlock`,
+    undefined,
+    { password },
+    undefined,
+    true
+  )
+
+  if (!response.body) {
+    throw new Error('Failed to unlock link')
+  }
+
+  return response.body.access_token
+}
+
+/**
+ * Wrap the name and key of everything under `folder` with the link key. The
+ * tree is walked the same way a folder share walks it, and every file key is
+ * unwrapped with the owner's own key first.
+ */
+async function createLinkFiles(
+  folder: AppFile,
+  kp: KeyPair,
+  key: Uint8Array
+): Promise<CreateLinkFile[]> {
+  const privateKey = kp.wrappingPrivate || (kp.input as string)
+  const subtree = await collectSubtree(folder)
+
+  return Promise.all(
+    subtree
+      .filter((node) => node.id !== folder.id)
+      .map(async (node) => {
+        const decrypted = await storageMeta.decrypt(node, privateKey)
+
+        return {
+          file_id: node.id,
+          encrypted_name: await cryptfns.cipher.encryptString(
+            crypto.LINK_CIPHER,
+            decrypted.name,
+            key
+          ),
+          encrypted_file_key:
+            node.mime === 'dir'
+              ? undefined
+              : await cryptfns.cipher.encryptString(
+                  crypto.LINK_CIPHER,
+                  cryptfns.uint8.toHex(decrypted.key),
+                  key
+                )
+        }
+      })
+  )
+}
+
+/**
+ * Convert unencrypted app file into a encrypted create link construct. A
+ * folder takes everything under it along.
  */
 export async function createLinkFromFile(file: AppFile, kp: KeyPair): Promise<CreateLink> {
-  if (!file.key) {
+  if (!file.key && file.mime !== 'dir') {
     throw new Error('File key is missing')
   }
 
@@ -160,9 +262,20 @@ export async function createLinkFromFile(file: AppFile, kp: KeyPair): Promise<Cr
     : await cryptfns.rsa.encryptMessage(cryptfns.uint8.toHex(key), wrapPub)
 
   const encrypted_name = await cryptfns.cipher.encryptString(crypto.LINK_CIPHER, file.name || 'no-name', key)
+
+  if (file.mime === 'dir') {
+    return {
+      file_id: file.id,
+      signature,
+      encrypted_link_key,
+      encrypted_name,
+      files: await createLinkFiles(file, kp, key)
+    }
+  }
+
   const encrypted_file_key = await cryptfns.cipher.encryptString(
     crypto.LINK_CIPHER,
-    cryptfns.uint8.toHex(file.key),
+    cryptfns.uint8.toHex(file.key as Uint8Array),
     key
   )
 
@@ -541,15 +541,18 @@ export async function renameGroup(groupId: string, name: string): Promise<AppSha
 }
 
 /**
- * `PATCH /api/users/me` — partial user update. The body currently carries
- * only the share-notifications opt-out flag; future fields land alongside.
+ * `PATCH /api/users/me` — partial user update. The body carries the
+ * share-notification and login-alert opt-out flags; future fields land
+ * alongside.
  */
 export async function patchMe(payload: {
   share_notifications_enabled?: boolean
-}): Promise<{ id: string; share_notifications_enabled: boolean }> {
+  login_alerts_enabled?: boolean
+}): Promise<{ id: string; share_notifications_enabled: boolean; login_alerts_enabled: boolean }> {
   const response = await new Api().withRefresh().make<typeof payload, {
     id: string
     share_notifications_enabled: boolean
+    login_alerts_enabled: boolean
   }>('patch', `/api/users/me`, undefined, payload)
   if (!response.body) {
     throw new Error('Empty response from /api/users/me')
@@ -283,7 +283,7 @@ export async function thumbnail(fileId: string): Promise<string | undefined> {
 export async function stats(): Promise<StorageStatsResponse> {
   const response = await Api.post<undefined, StorageStatsResponse>(`/api/storage/stats`)
 
-  return response.body || { stats: [], used_space: 0, quota: undefined }
+  return response.body || { stats: [], used_space: 0, trashed_space: 0, quota: undefined }
 }
 
 /** Digest lengths in hex characters: MD5, SHA1, SHA256, BLAKE2b. */
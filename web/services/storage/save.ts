@@ -25,6 +25,11 @@ export interface ReplaceContentRequest {
    * pending exists returns 409.
    */
   force?: boolean
+  /**
+   * Chunks identical to the same chunk of an older version. The server
+   * links them to that version instead of waiting for an upload.
+   */
+  reuse_chunks?: { chunk: number; version: number }[]
 }
 
 /**
@@ -8,7 +8,7 @@
  */
 
 import Api from '../api'
-import type { AppFile, FileVersion } from 'types'
+import type { AppFile, FileVersion, VersionPolicy, VersionPolicyResponse } from 'types'
 
 /**
  * Body the server's `fork` route expects. Mirrors `CreateFile` on the
@@ -136,3 +136,63 @@ export async function remove(fileId: string, version: number): Promise<void> {
 export async function purgeAll(fileId: string): Promise<void> {
   await Api.delete(`/api/storage/${fileId}/versions`)
 }
+
+/**
+ * `POST /api/storage/{fileId}/versions/{version}/pin` — keep a historical
+ * snapshot forever. Retention pruning skips pinned versions.
+ */
+export async function pin(fileId: string, version: number): Promise<FileVersion> {
+  const response = await Api.post<undefined, FileVersion>(
+    `/api/storage/${fileId}/versions/${version}/pin`
+  )
+
+  if (!response?.body?.id) {
+    throw new Error(`Failed to pin v${version}`)
+  }
+
+  return response.body
+}
+
+/**
+ * `DELETE /api/storage/{fileId}/versions/{version}/pin` — let retention
+ * prune the snapshot again.
+ */
+export async function unpin(fileId: string, version: number): Promise<void> {
+  await Api.delete(`/api/storage/${fileId}/versions/${version}/pin`)
+}
+
+/**
+ * `GET /api/storage/version-policy`, or `/api/storage/{fileId}/version-policy`
+ * with a file — the retention policy in effect.
+ */
+export async function getPolicy(fileId?: string): Promise<VersionPolicyResponse | undefined> {
+  const response = await Api.get<VersionPolicyResponse>(policyUrl(fileId))
+  return response?.body
+}
+
+/**
+ * `PUT` the retention policy for all of the user's files, or for one file.
+ */
+export async function setPolicy(
+  policy: VersionPolicy,
+  fileId?: string
+): Promise<VersionPolicyResponse | undefined> {
+  const response = await Api.put<VersionPolicy, VersionPolicyResponse>(
+    policyUrl(fileId),
+    undefined,
+    policy
+  )
+  return response?.body
+}
+
+/**
+ * `DELETE` the user's or a file's own retention policy, falling back to the
+ * next one in line.
+ */
+export async function removePolicy(fileId?: string): Promise<void> {
+  await Api.delete(policyUrl(fileId))
+}
+
+function policyUrl(fileId?: string): string {
+  return fileId ? `/api/storage/${fileId}/version-policy` : '/api/storage/version-policy'
+}
@@ -86,6 +86,9 @@ export function useSharingPeopleAdd(props: SharingPeopleAddProps, emit: SharingP
    */
   const folderEditable = ref(false)
 
+  /** Optional moment the recipient loses access on their own. */
+  const expiresAt = ref<Date | undefined>()
+
   const email = ref('')
   const recipient = ref<DiscoveredUser | null>(null)
   const recipientFingerprintHex = ref('')
@@ -150,6 +153,7 @@ export function useSharingPeopleAdd(props: SharingPeopleAddProps, emit: SharingP
     role.value = props.prefillRole ?? 'reader'
     folderEditable.value =
       role.value === 'reader' ? false : props.prefillAddFiles ?? true
+    expiresAt.value = undefined
     discoverError.value = null
     submitting.value = false
     selectedGroup.value = null
@@ -565,6 +569,9 @@ export function useSharingPeopleAdd(props: SharingPeopleAddProps, emit: SharingP
       action,
       shareRoleBefore
     })
+    if (expiresAt.value) {
+      envelope.expires_at = Math.floor(expiresAt.value.valueOf() / 1000)
+    }
 
     progress.value = { ...progress.value, phase: 'submitting' }
     return shares.createShare(envelope)
@@ -681,6 +688,7 @@ export function useSharingPeopleAdd(props: SharingPeopleAddProps, emit: SharingP
   return {
     isDir,
     folderEditable,
+    expiresAt,
     email,
     recipient,
     formattedFingerprint,
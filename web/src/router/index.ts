@@ -136,6 +136,12 @@ const router = createRouter({
       meta: { title: 'Change my password' },
       component: () => import('../views/account/ChangePasswordView.vue')
     },
+    {
+      path: '/account/enroll',
+      name: 'account-enroll',
+      meta: { title: 'Secure your account' },
+      component: () => import('../views/account/EnrollView.vue')
+    },
 
     /**
      * Admin routes
@@ -227,6 +233,12 @@ const router = createRouter({
       name: 'activate-email',
       meta: { title: 'Create Account - Verify Email' },
       component: () => import('../views/auth/VerifyEmailView.vue')
+    },
+    {
+      path: '/auth/kill-sessions/:token',
+      name: 'kill-sessions',
+      meta: { title: 'Sign Out Everywhere' },
+      component: () => import('../views/auth/KillSessionsView.vue')
     }
   ]
 })
@@ -3,3 +3,11 @@ import { zxcvbn } from '@zxcvbn-ts/core'
 export function isStrongPassword(password: string | undefined): boolean {
   return !!password && zxcvbn(password).score > 3
 }
+
+/**
+ * zxcvbn score (0-4) of the password, attested to the server whenever the
+ * password is set so it can hold it to the admin's minimum.
+ */
+export function passwordScore(password: string): number {
+  return zxcvbn(password).score
+}
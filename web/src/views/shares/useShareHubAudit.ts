@@ -24,7 +24,8 @@ export const ACTION_LABELS: Record<AuditEventAction, string> = {
   shared_folder_restore: 'Restored shared version',
   shared_folder_evict: 'Cascade revoked',
   shared_folder_move_out: 'Moved out of shared folder',
-  key_rotation: 'Key rotation'
+  key_rotation: 'Key rotation',
+  expire: 'Share expired'
 }
 
 /**
@@ -287,6 +288,8 @@ export function useShareHubAudit(keypair: Ref<KeyPair | undefined>) {
         return `${sender} moved ${fileLabel} out of a shared folder`
       case 'key_rotation':
         return `${sender} rotated their account encryption keys`
+      case 'expire':
+        return `${recipient || 'A recipient'}'s access to ${fileLabel} expired`
       default:
         return `${ACTION_LABELS[row.action] ?? row.action} on ${fileLabel}`
     }
@@ -259,10 +259,9 @@ describe('SharingModal', () => {
     expect(wrapper.find('[data-testid="share-dialog-submit"]').exists()).toBe(true)
   })
 
-  it('hides the entire tab strip on folders', async () => {
-    // Folder public-link sharing is out of scope for v1; with only one
-    // surface to show, the tab strip collapses and the body renders
-    // directly. An `initialTab` of `link` falls back to People.
+  it('offers a public link on owned folders', async () => {
+    // A folder link carries everything under the folder, so owned folders
+    // keep both tabs and an `initialTab` of `link` opens the Link tab.
     const OWNED_FOLDER: AppFile = {
       ...OWNED_FILE,
       mime: 'dir'
@@ -280,9 +279,9 @@ describe('SharingModal', () => {
     const wrapper = mountModal({ file: OWNED_FOLDER, initialTab: 'link' })
     await flushPromises()
 
-    expect(wrapper.find('[data-testid="sharing-modal-tab-people"]').exists()).toBe(false)
-    expect(wrapper.find('[data-testid="sharing-modal-tab-link"]').exists()).toBe(false)
-    expect(wrapper.find('[data-testid="share-dialog-submit"]').exists()).toBe(true)
+    expect(wrapper.find('[data-testid="sharing-modal-tab-people"]').exists()).toBe(true)
+    const linkTabButton = wrapper.get('[data-testid="sharing-modal-tab-link"]')
+    expect(linkTabButton.classes().some((c) => c.includes('redish-500'))).toBe(true)
   })
 
   it('keeps both tabs on owned files', async () => {
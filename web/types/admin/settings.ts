@@ -1,6 +1,8 @@
 export interface Data {
   users: Users
   sharing: Sharing
+  trash?: Trash
+  security?: Security
   /**
    * Read-only deployment flag surfaced on the settings GET (not persisted).
    * When true the admin "send test email" card is hidden and its endpoint 403s.
@@ -29,6 +31,30 @@ export interface Sharing {
   default_cipher: string
 }
 
+/**
+ * Recycle bin policy. Deleted files stay restorable for `retention_days`
+ * before the `purge-trash` job removes them; `0` deletes immediately.
+ */
+export interface Trash {
+  retention_days: number
+}
+
+/**
+ * Account security policy. Users who fall short of it after a change are
+ * confined to enrolling rather than signed out.
+ */
+export interface Security {
+  two_factor: 'off' | 'admins' | 'all'
+  /** Days newly covered users have to set up two-factor */
+  two_factor_grace_days: number
+  /** Set by the server when the requirement last grew */
+  two_factor_enforced_at?: number
+  /** Lowest zxcvbn score (0-4) a new password must reach, `0` asks nothing */
+  min_password_score: number
+  /** Days a session lives from its login, `0` is unlimited */
+  max_session_lifetime_days: number
+}
+
 export interface WhitelistOrBlacklist {
   rules: string[]
 }
@@ -94,6 +94,9 @@ export interface EncryptedAppFile extends AppFileEncryptedPart {
 
   pending_size?: number
 
+  /** Set when a storage check found chunks of this file missing. */
+  damaged_at?: number
+
   /** Indices 0..chunks-1 of already-stored chunks; used for resume. */
   uploaded_chunks?: number[]
 
@@ -164,6 +167,31 @@ export interface FileVersion {
   sha256: string | null
   /** Unix seconds — time the version was archived. */
   created_at: number
+  /** Unix seconds — set while the version is pinned and kept forever. */
+  pinned_at: number | null
+}
+
+/**
+ * Retention of a file's version history. Everything younger than
+ * `keep_all_days` is kept, past that the newest version of each of the
+ * last N hours/days/weeks/months, capped by `max_versions`.
+ */
+export interface VersionPolicy {
+  keep_all_days: number
+  keep_hourly: number
+  keep_daily: number
+  keep_weekly: number
+  keep_monthly: number
+  /** Hard cap on the unpinned versions kept, null for none. */
+  max_versions: number | null
+}
+
+/**
+ * Policy in effect, and whether it is the file's own, the owner's policy
+ * for all of their files or the instance default.
+ */
+export interface VersionPolicyResponse extends VersionPolicy {
+  source: 'file' | 'user' | 'default'
 }
 
 export interface AppFileUnencryptedPart {
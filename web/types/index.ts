@@ -51,6 +51,13 @@ export interface Parameters extends Query {
 
   /** Withhold thumbnail blobs; absent means full rows. */
   compact?: boolean
+
+  /** Page size; with it (or `cursor`) the listing comes back a page at a time. */
+  limit?: number
+  /** The `next_cursor` of the previous page. */
+  cursor?: string
+  /** Count the whole listing and report it as `total`. */
+  with_total?: boolean
 }
 
 export interface SearchQuery {
@@ -69,6 +76,10 @@ export interface SearchQuery {
 export interface FileResponse {
   parents?: AppFile[]
   children: AppFile[]
+  /** Set while a paginated listing has more pages. */
+  next_cursor?: string
+  /** Rows in the whole listing, when `with_total` was asked for. */
+  total?: number
 }
 
 export interface Stats {
@@ -80,6 +91,7 @@ export interface Stats {
 export interface StorageStatsResponse {
   stats: Stats[]
   used_space: number
+  trashed_space: number
   quota?: number
 }
 
@@ -27,14 +27,63 @@ export interface CreateLink {
   encrypted_thumbnail?: string
 
   /**
-   * AES file key converted to hex, encrypted with link key and then again converted to hex
+   * AES file key converted to hex, encrypted with link key and then again converted to hex.
+   * Left out for a folder, which has no content of its own.
    */
-  encrypted_file_key: string
+  encrypted_file_key?: string
+
+  /**
+   * Everything under a linked folder, names and keys encrypted with the link key
+   */
+  files?: CreateLinkFile[]
 
   /**
    * Expiration date of the link
    */
   expires_at?: string
+
+  /**
+   * Completed downloads after which the link stops serving the file
+   */
+  max_downloads?: number
+
+  /**
+   * Extra password recipients have to give before downloading
+   */
+  password?: string
+}
+
+export interface CreateLinkFile {
+  file_id: string
+  encrypted_name: string
+
+  /** Absent for directories. */
+  encrypted_file_key?: string
+}
+
+/**
+ * A file or directory under a linked folder. The listing is flat, `parent_id`
+ * rebuilds the tree.
+ */
+export interface EncryptedAppLinkFile {
+  id: string
+  parent_id?: string
+  mime: string
+  size?: number
+  chunks?: number
+  encrypted_name: string
+  encrypted_file_key?: string
+  file_modified_at: number
+  cipher: string
+  active_version: number
+  editable: boolean
+}
+
+export interface AppLinkFile extends EncryptedAppLinkFile {
+  name: string
+
+  /** File content key, unwrapped from `encrypted_file_key` with the link key. */
+  key?: Uint8Array
 }
 
 export interface AppLink extends EncryptedAppLink {
@@ -48,6 +97,11 @@ export interface AppLink extends EncryptedAppLink {
    * Used with `file_cipher` to decrypt the ciphertext chunks client-side.
    */
   key?: Uint8Array
+
+  /** Token from unlocking a password-protected link, sent with every download. */
+  access_token?: string
+
+  files?: AppLinkFile[]
 }
 
 export interface EncryptedAppLink {
@@ -81,6 +135,18 @@ export interface EncryptedAppLink {
   /** Cipher the file chunks were encrypted with (e.g. "aegis128l"). */
   file_cipher: string
   expires_at?: number
+
+  /** Completed downloads after which the link stops serving the file. */
+  max_downloads?: number
+
+  /**
+   * Whether the link asks for a password. Until it is unlocked, the metadata
+   * comes without the encrypted name, thumbnail and file key.
+   */
+  has_password?: boolean
+
+  /** Everything under a linked folder, only on the single-link metadata response. */
+  files?: EncryptedAppLinkFile[]
 }
 
 export interface EncryptedLink {
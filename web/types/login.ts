@@ -1,6 +1,18 @@
 export interface Authenticated {
   user: User
   session: Session
+  /** What the admin's security policy still asks of the user, absent when nothing. */
+  enrollment?: Enrollment
+}
+
+/**
+ * While `required` is not empty the session is confined to the routes that
+ * fulfill it: setting up two-factor, changing the password and signing out.
+ */
+export interface Enrollment {
+  required: ('two_factor' | 'password')[]
+  /** Unix seconds when two-factor becomes required, during the grace period */
+  two_factor_due_at?: number
 }
 
 export interface User {
@@ -15,6 +27,7 @@ export interface User {
   email_verified_at?: number
   secret: boolean
   share_notifications_enabled?: boolean
+  login_alerts_enabled?: boolean
   security_version?: number
   key_type?: string
   wrapping_pubkey?: string | null
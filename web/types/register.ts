@@ -9,6 +9,8 @@ export interface CreateUser {
   wrapping_pubkey?: string
   encrypted_private_key?: string
   opaque_registration_upload?: string
+  /** zxcvbn score of the password, held to the admin's minimum */
+  password_score?: number
   invitation_id?: string
 
   /**
@@ -16,6 +16,9 @@ export type AuditEventAction =
   // key-rotation scheme (not `AuditEventSigInputV1`). Chained into the
   // owner's per-sender audit chain on RSA→curve25519 migration.
   | 'key_rotation'
+  // System event with no sender, written when a time-limited share runs
+  // out and the `purge-expired-shares` job drops the recipient's rows.
+  | 'expire'
 
 export interface DiscoveredUser {
   user_id: string
@@ -63,6 +66,9 @@ export interface CreateShareEnvelope {
   /** Unix-seconds timestamp embedded in `MemberSigPayloadV1`. Server
    * re-encodes and verifies against this exact value. */
   member_signed_at?: number
+  /** Unix-seconds moment the recipient loses access on their own. Absent
+   * keeps the share until it is revoked. */
+  expires_at?: number
 }
 
 export interface RevokeShareBody {
@@ -81,6 +87,8 @@ export interface AppShare {
   share_role: ShareRole
   created_at: number
   shared_at: number | null
+  /** When the share ends on its own, null until revoked. */
+  expires_at?: number | null
   shared_by_user_id: string | null
   shared_by_email: string | null
 }
@@ -127,6 +135,8 @@ export interface IncomingShare {
   encrypted_key: string
   created_at: number
   shared_at: number | null
+  /** When the share ends on its own, null until revoked. */
+  expires_at?: number | null
   owner_id: string
   owner_email: string
   owner_pubkey: string